@@ -4,24 +4,40 @@ pub const CANONICAL_HEADER_LEN: usize = 512;
 pub const CANONICAL_HEADER_STATIC_LEN: usize = 64;
 pub const CANONICAL_KEYSLOT_LEN: usize = 112;
 pub const MAX_KEYSLOTS: usize = 4;
+/// Most keyslots an extended-capacity V1 header can hold.
+pub const MAX_EXTENDED_KEYSLOTS: usize = 32;
 pub const RETIRED_CURRENT_V1_HEADER_LEN: usize = 416;
 
 pub const HEADER_LEN: usize = CANONICAL_HEADER_LEN;
 pub const HEADER_STATIC_LEN: usize = CANONICAL_HEADER_STATIC_LEN;
 pub const KEYSLOT_LEN: usize = CANONICAL_KEYSLOT_LEN;
+/// Length of an extended-capacity header holding `MAX_EXTENDED_KEYSLOTS` keyslots.
+pub const MAX_HEADER_LEN: usize = HEADER_STATIC_LEN + MAX_EXTENDED_KEYSLOTS * KEYSLOT_LEN;
+/// Longest keyslot label, in bytes. Labels share the keyslot record's last 20
+/// bytes with an 8-byte creation timestamp.
+pub const KEYSLOT_LABEL_LEN: usize = 12;
 
 const _: () = {
     assert!(
         HEADER_STATIC_LEN + MAX_KEYSLOTS * KEYSLOT_LEN == HEADER_LEN,
         "header geometry: static + slots must equal HEADER_LEN"
     );
-    // V1Keyslot::deserialize slices slot_bytes[..92]; padding is KEYSLOT_LEN - 92.
+    // V1Keyslot::deserialize slices slot_bytes[..92]; the rest holds keyslot metadata.
     assert!(
         KEYSLOT_LEN >= 92,
         "keyslot record must hold at least 92 bytes"
     );
+    assert!(
+        92 + 8 + KEYSLOT_LABEL_LEN == KEYSLOT_LEN,
+        "keyslot metadata must fill the keyslot padding"
+    );
 };
 
+/// Ends a V1 header trailer, after the header copy's length as a little-endian
+/// `u64`, so the copy can be located from the end of the file.
+pub const HEADER_TRAILER_MAGIC: [u8; 8] = *b"DXIOHBAK";
+pub const HEADER_TRAILER_FOOTER_LEN: usize = 8 + HEADER_TRAILER_MAGIC.len();
+
 pub const MAGIC: [u8; 4] = *b"DXIO";
 pub const VERSION_V1: [u8; 2] = [0x00, 0x01];
 pub const CANONICAL_V1_DISCRIMINATOR: [u8; 4] = *b"CV1\0";
@@ -154,6 +170,8 @@ pub enum HeaderReadError {
     NonZeroReservedBytes,
     NonZeroActiveKeyslotPadding(usize),
     NonZeroInactiveKeyslotPadding(usize),
+    MissingHeaderTrailer,
+    InvalidHeaderTrailer,
 }
 
 impl Display for HeaderReadError {
@@ -221,6 +239,12 @@ impl Display for HeaderReadError {
                     "non-zero inactive keyslot bytes in V1 header slot {index}"
                 )
             }
+            Self::MissingHeaderTrailer => {
+                f.write_str("no V1 header trailer at the end of the file")
+            }
+            Self::InvalidHeaderTrailer => {
+                f.write_str("V1 header trailer does not hold a whole header copy")
+            }
         }
     }
 }
@@ -242,6 +266,8 @@ pub enum HeaderWriteError {
     NoKeyslots,
     TooManyKeyslots(usize),
     InvalidKeyslotIndex(usize),
+    InvalidKeyslotCapacity(usize),
+    InvalidKeyslotLabel,
     Io(std::io::Error),
 }
 
@@ -255,6 +281,14 @@ impl Display for HeaderWriteError {
             Self::InvalidKeyslotIndex(index) => {
                 write!(f, "invalid V1 keyslot index: {index}")
             }
+            Self::InvalidKeyslotCapacity(capacity) => write!(
+                f,
+                "v1 header keyslot capacity must be {MAX_KEYSLOTS} to {MAX_EXTENDED_KEYSLOTS}, got {capacity}"
+            ),
+            Self::InvalidKeyslotLabel => write!(
+                f,
+                "keyslot labels must be 1 to {KEYSLOT_LABEL_LEN} bytes with no control characters"
+            ),
             Self::Io(error) => write!(f, "unable to write v1 header: {error}"),
         }
     }
@@ -3,7 +3,10 @@ pub mod v1;
 
 pub use common::{HeaderReadError, HeaderWriteError, PayloadNonce, V1HeaderAad};
 
-use common::{CANONICAL_V1_DISCRIMINATOR, HEADER_LEN, MAGIC, VERSION_V1};
+use common::{
+    CANONICAL_V1_DISCRIMINATOR, HEADER_LEN, HEADER_TRAILER_FOOTER_LEN, HEADER_TRAILER_MAGIC, MAGIC,
+    MAX_HEADER_LEN, VERSION_V1,
+};
 
 #[derive(Debug)]
 pub enum ParsedHeader {
@@ -45,9 +48,53 @@ pub fn read_header(reader: &mut impl std::io::Read) -> Result<ParsedHeader, Head
     Ok(ParsedHeader::V1(ParsedV1Payload { header, aad }))
 }
 
+/// Reads the header copy from the trailer at the end of `artifact`, locating
+/// it by the footer that follows it.
+///
+/// The copy goes through the same validation as a leading header, and must
+/// itself declare the trailer it was found in.
+pub fn read_header_trailer(artifact: &[u8]) -> Result<ParsedHeader, HeaderReadError> {
+    let footer_start = artifact
+        .len()
+        .checked_sub(HEADER_TRAILER_FOOTER_LEN)
+        .ok_or(HeaderReadError::MissingHeaderTrailer)?;
+    let (rest, footer) = artifact.split_at(footer_start);
+    let (header_len, magic) = footer.split_at(8);
+    if magic != HEADER_TRAILER_MAGIC {
+        return Err(HeaderReadError::MissingHeaderTrailer);
+    }
+
+    let mut header_len_bytes = [0u8; 8];
+    header_len_bytes.copy_from_slice(header_len);
+    let header_len = usize::try_from(u64::from_le_bytes(header_len_bytes))
+        .ok()
+        .filter(|len| (HEADER_LEN..=MAX_HEADER_LEN).contains(len))
+        .ok_or(HeaderReadError::InvalidHeaderTrailer)?;
+    // the leading header and the payload sit before the copy
+    let copy_start = rest
+        .len()
+        .checked_sub(header_len)
+        .filter(|start| *start > header_len)
+        .ok_or(HeaderReadError::InvalidHeaderTrailer)?;
+    let (_, copy) = rest.split_at(copy_start);
+
+    let bytes = read_canonical_v1_header_bytes(&mut std::io::Cursor::new(copy))?;
+    if bytes.len() != header_len {
+        return Err(HeaderReadError::InvalidHeaderTrailer);
+    }
+    let header = v1::V1Header::deserialize_bytes(&bytes)?;
+    if !header.has_header_trailer() {
+        return Err(HeaderReadError::InvalidHeaderTrailer);
+    }
+    let aad = header.aad();
+    Ok(ParsedHeader::V1(ParsedV1Payload { header, aad }))
+}
+
+/// Reads a V1 header: the canonical 512 bytes, followed by the rest of the
+/// keyslot table when the static header declares an extended capacity.
 pub(crate) fn read_canonical_v1_header_bytes(
     reader: &mut impl std::io::Read,
-) -> Result<[u8; HEADER_LEN], HeaderReadError> {
+) -> Result<Vec<u8>, HeaderReadError> {
     let mut prefix = [0u8; 10];
     reader.read_exact(&mut prefix)?;
 
@@ -79,9 +126,17 @@ pub(crate) fn read_canonical_v1_header_bytes(
         return Err(HeaderReadError::RetiredV1Layout);
     }
 
-    let mut bytes = [0u8; HEADER_LEN];
-    bytes[..10].copy_from_slice(&prefix);
-    reader.read_exact(&mut bytes[10..])?;
+    let mut rest = [0u8; HEADER_LEN - 10];
+    reader.read_exact(&mut rest)?;
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    bytes.extend_from_slice(&prefix);
+    bytes.extend_from_slice(&rest);
+
+    if let Some(header_len) = v1::extended_header_len(&bytes) {
+        let mut extension = vec![0u8; header_len.saturating_sub(HEADER_LEN)];
+        reader.read_exact(&mut extension)?;
+        bytes.extend_from_slice(&extension);
+    }
 
     Ok(bytes)
 }
@@ -1,48 +1,84 @@
 use std::io::{Read, Write};
 
-use crate::kdf::{ARGON2ID_KDF_PARAM_PROFILE_ID, ARGON2ID_KDF_PROFILE_ID, Kdf};
+use crate::kdf::{
+    ARGON2ID_KDF_PARAM_PROFILE_ID, ARGON2ID_KDF_PROFILE_ID, Kdf,
+    X25519_RECIPIENT_KDF_PARAM_PROFILE_ID, X25519_RECIPIENT_KDF_PROFILE_ID,
+};
 use crate::payload::{PayloadFramingProfile, PayloadKind};
+use crate::recipient::{RECIPIENT_KEY_LEN, RecipientPublicKey};
 
 use super::common::{
-    CANONICAL_V1_DISCRIMINATOR, HEADER_LEN, HEADER_STATIC_LEN, HeaderReadError, HeaderWriteError,
-    KEYSLOT_LEN, KeyslotNonce, MAGIC, MAX_KEYSLOTS, PayloadNonce, Salt, V1HeaderAad, VERSION_V1,
+    CANONICAL_V1_DISCRIMINATOR, HEADER_LEN, HEADER_STATIC_LEN, HEADER_TRAILER_FOOTER_LEN,
+    HEADER_TRAILER_MAGIC, HeaderReadError, HeaderWriteError, KEYSLOT_LABEL_LEN, KEYSLOT_LEN,
+    KeyslotNonce, MAGIC, MAX_EXTENDED_KEYSLOTS, MAX_KEYSLOTS, PayloadNonce, Salt, V1HeaderAad,
+    VERSION_V1,
 };
 
 const CANONICAL_SCHEMA_PROFILE: u8 = 0x01;
+const EXTENDED_SCHEMA_PROFILE: u8 = 0x02;
+// Byte 15 of the static header. Unknown flag bits are rejected as reserved.
+const HEADER_FLAG_TRAILER: u8 = 0x01;
 const SLOT_STATE_EMPTY: u8 = 0x00;
 const SLOT_STATE_ACTIVE: u8 = 0x01;
 const KDF_PROFILE_HISTORICAL_ARGON2ID: u8 = 0xDF;
 const KDF_PARAM_PROFILE_HISTORICAL_ARGON2ID: u8 = 0x02;
-const SLOT_WRAPPING_AAD_LEN: usize = HEADER_STATIC_LEN + 1 + 1 + 1 + 16 + 24;
+const KEYSLOT_METADATA_LEN: usize = KEYSLOT_LEN - 92;
+const SLOT_WRAPPING_AAD_LEN: usize = HEADER_STATIC_LEN + 1 + 1 + 1 + 16 + 24 + KEYSLOT_METADATA_LEN;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum KeyslotKdf {
     Argon2id,
+    Argon2idInteractive,
+    Argon2idParanoid,
     UnsupportedArgon2id,
+    X25519Recipient,
 }
 
 impl KeyslotKdf {
+    /// Returns the Argon2id profile that derives this slot's wrapping key, or `None`
+    /// for slots that are not opened with a password or keyfile.
+    #[must_use]
+    pub const fn password_kdf(self) -> Option<Kdf> {
+        match self {
+            Self::Argon2id => Some(Kdf::Argon2id),
+            Self::Argon2idInteractive => Some(Kdf::Argon2idInteractive),
+            Self::Argon2idParanoid => Some(Kdf::Argon2idParanoid),
+            Self::UnsupportedArgon2id | Self::X25519Recipient => None,
+        }
+    }
+
     fn serialize_profile(self) -> u8 {
         match self {
-            Self::Argon2id => ARGON2ID_KDF_PROFILE_ID,
+            Self::Argon2id | Self::Argon2idInteractive | Self::Argon2idParanoid => {
+                ARGON2ID_KDF_PROFILE_ID
+            }
             Self::UnsupportedArgon2id => KDF_PROFILE_HISTORICAL_ARGON2ID,
+            Self::X25519Recipient => X25519_RECIPIENT_KDF_PROFILE_ID,
         }
     }
 
     fn serialize_param_profile(self) -> u8 {
         match self {
-            Self::Argon2id => ARGON2ID_KDF_PARAM_PROFILE_ID,
+            Self::Argon2id => Kdf::Argon2id.param_profile_id(),
+            Self::Argon2idInteractive => Kdf::Argon2idInteractive.param_profile_id(),
+            Self::Argon2idParanoid => Kdf::Argon2idParanoid.param_profile_id(),
             Self::UnsupportedArgon2id => KDF_PARAM_PROFILE_HISTORICAL_ARGON2ID,
+            Self::X25519Recipient => X25519_RECIPIENT_KDF_PARAM_PROFILE_ID,
         }
     }
 
     fn deserialize(profile: u8, param_profile: u8) -> Result<Self, HeaderReadError> {
         match (profile, param_profile) {
-            (ARGON2ID_KDF_PROFILE_ID, ARGON2ID_KDF_PARAM_PROFILE_ID) => Ok(Self::Argon2id),
+            (ARGON2ID_KDF_PROFILE_ID, param_profile) => Kdf::from_param_profile_id(param_profile)
+                .map(Self::from)
+                .ok_or(HeaderReadError::InvalidKdfParamProfile(param_profile)),
             (KDF_PROFILE_HISTORICAL_ARGON2ID, KDF_PARAM_PROFILE_HISTORICAL_ARGON2ID) => {
                 Ok(Self::UnsupportedArgon2id)
             }
-            (ARGON2ID_KDF_PROFILE_ID, param_profile) => {
+            (X25519_RECIPIENT_KDF_PROFILE_ID, X25519_RECIPIENT_KDF_PARAM_PROFILE_ID) => {
+                Ok(Self::X25519Recipient)
+            }
+            (X25519_RECIPIENT_KDF_PROFILE_ID, param_profile) => {
                 Err(HeaderReadError::InvalidKdfParamProfile(param_profile))
             }
             (profile, _) => Err(HeaderReadError::InvalidKdfProfile(profile)),
@@ -54,6 +90,8 @@ impl From<Kdf> for KeyslotKdf {
     fn from(value: Kdf) -> Self {
         match value {
             Kdf::Argon2id => Self::Argon2id,
+            Kdf::Argon2idInteractive => Self::Argon2idInteractive,
+            Kdf::Argon2idParanoid => Self::Argon2idParanoid,
         }
     }
 }
@@ -65,9 +103,9 @@ impl V1KeyslotCount {
     pub const MIN: u8 = 1;
     #[expect(
         clippy::cast_possible_truncation,
-        reason = "MAX_KEYSLOTS is the constant 4, which trivially fits in u8"
+        reason = "MAX_EXTENDED_KEYSLOTS is the constant 32, which trivially fits in u8"
     )]
-    pub const MAX: u8 = MAX_KEYSLOTS as u8;
+    pub const MAX: u8 = MAX_EXTENDED_KEYSLOTS as u8;
 
     pub fn try_from_u8(count: u8) -> Result<Self, HeaderReadError> {
         if !(Self::MIN..=Self::MAX).contains(&count) {
@@ -99,12 +137,12 @@ impl V1KeyslotIndex {
 
     #[expect(
         clippy::cast_possible_truncation,
-        reason = "the preceding bound `index < MAX_KEYSLOTS` (4) guarantees index fits in u8"
+        reason = "the preceding bound `index < MAX_EXTENDED_KEYSLOTS` (32) guarantees index fits in u8"
     )]
     pub fn try_from_physical_index(index: usize) -> Result<Self, HeaderReadError> {
-        if index >= MAX_KEYSLOTS {
+        if index >= MAX_EXTENDED_KEYSLOTS {
             return Err(HeaderReadError::InvalidPhysicalSlotIndex {
-                expected: MAX_KEYSLOTS.saturating_sub(1),
+                expected: MAX_EXTENDED_KEYSLOTS.saturating_sub(1),
                 actual: u8::try_from(index).unwrap_or(u8::MAX),
             });
         }
@@ -117,6 +155,94 @@ impl V1KeyslotIndex {
     }
 }
 
+/// Number of physical keyslots a header reserves.
+///
+/// The canonical 512-byte header holds [`MAX_KEYSLOTS`]. Larger capacities use
+/// the extended schema profile, whose keyslot table grows by `KEYSLOT_LEN` per
+/// slot after the static header. Both the profile and the capacity are part of
+/// [`V1Header::aad`], so a header cannot be re-framed without failing
+/// authentication.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct V1KeyslotCapacity(u8);
+
+impl V1KeyslotCapacity {
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "MAX_KEYSLOTS is the constant 4, which trivially fits in u8"
+    )]
+    pub const CANONICAL: Self = Self(MAX_KEYSLOTS as u8);
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "MAX_EXTENDED_KEYSLOTS is the constant 32, which trivially fits in u8"
+    )]
+    pub const MAX: Self = Self(MAX_EXTENDED_KEYSLOTS as u8);
+
+    pub fn try_from_usize(capacity: usize) -> Result<Self, HeaderWriteError> {
+        if !(MAX_KEYSLOTS..=MAX_EXTENDED_KEYSLOTS).contains(&capacity) {
+            return Err(HeaderWriteError::InvalidKeyslotCapacity(capacity));
+        }
+        u8::try_from(capacity)
+            .map(Self)
+            .map_err(|_| HeaderWriteError::InvalidKeyslotCapacity(capacity))
+    }
+
+    /// The smallest capacity that holds `count` keyslots.
+    pub fn for_keyslot_count(count: usize) -> Result<Self, HeaderWriteError> {
+        Self::try_from_usize(count.max(MAX_KEYSLOTS))
+            .map_err(|_| HeaderWriteError::TooManyKeyslots(count))
+    }
+
+    #[must_use]
+    pub const fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Serialized length of a header with this many keyslots.
+    #[must_use]
+    pub const fn header_len(self) -> usize {
+        HEADER_STATIC_LEN.saturating_add(self.get().saturating_mul(KEYSLOT_LEN))
+    }
+
+    #[must_use]
+    pub const fn is_extended(self) -> bool {
+        self.0 > Self::CANONICAL.0
+    }
+
+    const fn schema_profile(self) -> u8 {
+        if self.is_extended() {
+            EXTENDED_SCHEMA_PROFILE
+        } else {
+            CANONICAL_SCHEMA_PROFILE
+        }
+    }
+
+    /// Reads the capacity from the schema profile (byte 10) and capacity
+    /// (byte 14) of a static header. Each capacity has exactly one encoding:
+    /// the extended profile never describes the canonical four slots.
+    fn from_static_header(schema_profile: u8, capacity: u8) -> Result<Self, HeaderReadError> {
+        match schema_profile {
+            CANONICAL_SCHEMA_PROFILE if capacity == Self::CANONICAL.0 => Ok(Self::CANONICAL),
+            EXTENDED_SCHEMA_PROFILE if capacity > Self::CANONICAL.0 && capacity <= Self::MAX.0 => {
+                Ok(Self(capacity))
+            }
+            CANONICAL_SCHEMA_PROFILE | EXTENDED_SCHEMA_PROFILE => {
+                Err(HeaderReadError::InvalidKeyslotCount(capacity))
+            }
+            profile => Err(HeaderReadError::UnsupportedVersion([0x00, profile])),
+        }
+    }
+}
+
+/// Length of the header whose first `HEADER_LEN` bytes are `prefix`, or `None`
+/// when the prefix does not describe a valid extended-capacity header.
+pub(crate) fn extended_header_len(prefix: &[u8]) -> Option<usize> {
+    let (&schema_profile, &capacity) = (prefix.get(10)?, prefix.get(14)?);
+    V1KeyslotCapacity::from_static_header(schema_profile, capacity)
+        .ok()
+        .filter(|capacity| capacity.is_extended())
+        .map(V1KeyslotCapacity::header_len)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct EncryptedMasterKey([u8; 48]);
 
@@ -144,6 +270,99 @@ impl EncryptedMasterKey {
     }
 }
 
+/// A keyslot's label and creation time.
+///
+/// Metadata fills the last 20 bytes of the keyslot record: an 8-byte
+/// little-endian creation time in seconds since the Unix epoch, then the label
+/// as zero-padded UTF-8. Keyslots without metadata leave those bytes zero, so
+/// headers written before labels existed read back unchanged. When present,
+/// the metadata is part of the slot-wrapping AAD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyslotMetadata {
+    created_at: u64,
+    label: [u8; KEYSLOT_LABEL_LEN],
+}
+
+impl KeyslotMetadata {
+    /// Labels must be 1 to `KEYSLOT_LABEL_LEN` bytes of UTF-8 with no control
+    /// characters.
+    pub fn new(label: &str, created_at: u64) -> Result<Self, HeaderWriteError> {
+        if !is_valid_label(label) {
+            return Err(HeaderWriteError::InvalidKeyslotLabel);
+        }
+
+        let mut bytes = [0u8; KEYSLOT_LABEL_LEN];
+        for (byte, label_byte) in bytes.iter_mut().zip(label.bytes()) {
+            *byte = label_byte;
+        }
+
+        Ok(Self {
+            created_at,
+            label: bytes,
+        })
+    }
+
+    #[must_use]
+    pub fn label(&self) -> &str {
+        let (label, _padding) = split_label(&self.label);
+        std::str::from_utf8(label).unwrap_or_default()
+    }
+
+    /// Seconds since the Unix epoch.
+    #[must_use]
+    pub const fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    fn to_bytes(self) -> [u8; KEYSLOT_METADATA_LEN] {
+        let mut bytes = [0u8; KEYSLOT_METADATA_LEN];
+        let (created_at, label) = bytes.split_at_mut(8);
+        created_at.copy_from_slice(&self.created_at.to_le_bytes());
+        label.copy_from_slice(&self.label);
+        bytes
+    }
+
+    // all-zero bytes are a keyslot without metadata; anything else must be a
+    // valid label followed only by zero padding, and is otherwise reported as
+    // non-zero padding
+    fn from_bytes(bytes: &[u8], physical_index: usize) -> Result<Option<Self>, HeaderReadError> {
+        if bytes.iter().all(|byte| *byte == 0) {
+            return Ok(None);
+        }
+
+        Self::parse(bytes)
+            .map(Some)
+            .ok_or(HeaderReadError::NonZeroActiveKeyslotPadding(physical_index))
+    }
+
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let (created_at, label_bytes) = bytes.split_first_chunk::<8>()?;
+        let label_bytes: &[u8; KEYSLOT_LABEL_LEN] = label_bytes.try_into().ok()?;
+        let (label, padding) = split_label(label_bytes);
+        if padding.iter().any(|byte| *byte != 0) {
+            return None;
+        }
+
+        Self::new(
+            std::str::from_utf8(label).ok()?,
+            u64::from_le_bytes(*created_at),
+        )
+        .ok()
+    }
+}
+
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty() && label.len() <= KEYSLOT_LABEL_LEN && !label.chars().any(char::is_control)
+}
+
+fn split_label(label: &[u8; KEYSLOT_LABEL_LEN]) -> (&[u8], &[u8]) {
+    let len = label
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(KEYSLOT_LABEL_LEN);
+    label.split_at(len)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct V1Keyslot {
     physical_index: u8,
@@ -151,6 +370,7 @@ pub struct V1Keyslot {
     encrypted_master_key: EncryptedMasterKey,
     nonce: KeyslotNonce,
     salt: Salt,
+    metadata: Option<KeyslotMetadata>,
 }
 
 impl V1Keyslot {
@@ -163,6 +383,8 @@ impl V1Keyslot {
     ) -> Self {
         let kdf = match kdf {
             Kdf::Argon2id => KeyslotKdf::Argon2id,
+            Kdf::Argon2idInteractive => KeyslotKdf::Argon2idInteractive,
+            Kdf::Argon2idParanoid => KeyslotKdf::Argon2idParanoid,
         };
 
         Self {
@@ -171,12 +393,61 @@ impl V1Keyslot {
             encrypted_master_key: EncryptedMasterKey::new(encrypted_master_key),
             nonce,
             salt,
+            metadata: None,
         }
     }
 
+    /// Builds an X25519 recipient keyslot.
+    ///
+    /// Recipient slots have no salt or stored nonce: the 32-byte ephemeral public
+    /// key occupies the salt field and the first 16 nonce bytes, and the last 8
+    /// nonce bytes stay zero. The slot-wrapping AAD therefore covers the ephemeral
+    /// key with no change to the record layout.
+    #[must_use]
+    pub fn new_recipient(
+        encrypted_master_key: [u8; 48],
+        ephemeral_public_key: &RecipientPublicKey,
+    ) -> Self {
+        let key = ephemeral_public_key.as_bytes();
+        let mut salt = [0u8; 16];
+        salt.copy_from_slice(&key[..16]);
+        let mut nonce = [0u8; 24];
+        nonce[..16].copy_from_slice(&key[16..]);
+
+        Self {
+            physical_index: 0,
+            kdf: KeyslotKdf::X25519Recipient,
+            encrypted_master_key: EncryptedMasterKey::new(encrypted_master_key),
+            nonce: KeyslotNonce::new(nonce),
+            salt: Salt::new(salt),
+            metadata: None,
+        }
+    }
+
+    /// Attaches a label and creation time. The metadata is authenticated, so
+    /// the slot must be wrapped after it is attached.
+    #[must_use]
+    pub const fn with_metadata(mut self, metadata: Option<KeyslotMetadata>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Returns the ephemeral public key stored in an X25519 recipient keyslot, or
+    /// `None` for password keyslots.
+    #[must_use]
+    pub fn ephemeral_public_key(&self) -> Option<RecipientPublicKey> {
+        if self.kdf != KeyslotKdf::X25519Recipient {
+            return None;
+        }
+        let mut key = [0u8; RECIPIENT_KEY_LEN];
+        key[..16].copy_from_slice(self.salt.as_bytes());
+        key[16..].copy_from_slice(&self.nonce.as_bytes()[..16]);
+        Some(RecipientPublicKey::from_bytes(key))
+    }
+
     #[expect(
         clippy::expect_used,
-        reason = "physical_index is always derived from a slot position < MAX_KEYSLOTS (4), so it fits in u8"
+        reason = "physical_index is always derived from a slot position < MAX_EXTENDED_KEYSLOTS (32), so it fits in u8"
     )]
     fn with_physical_index(mut self, physical_index: usize) -> Self {
         self.physical_index =
@@ -209,6 +480,11 @@ impl V1Keyslot {
         &self.salt
     }
 
+    #[must_use]
+    pub const fn metadata(&self) -> Option<&KeyslotMetadata> {
+        self.metadata.as_ref()
+    }
+
     fn serialize_into(&self, bytes: &mut Vec<u8>) {
         bytes.push(SLOT_STATE_ACTIVE);
         bytes.push(self.physical_index);
@@ -217,13 +493,17 @@ impl V1Keyslot {
         bytes.extend_from_slice(self.salt.as_bytes());
         bytes.extend_from_slice(self.nonce.as_bytes());
         bytes.extend_from_slice(self.encrypted_master_key.as_bytes());
-        bytes.extend_from_slice(&[0u8; KEYSLOT_LEN - 92]);
+        bytes.extend_from_slice(
+            &self
+                .metadata
+                .map_or([0u8; KEYSLOT_METADATA_LEN], KeyslotMetadata::to_bytes),
+        );
     }
 
     #[expect(
         clippy::indexing_slicing,
         clippy::expect_used,
-        reason = "slot_bytes is a KEYSLOT_LEN-sized slice carved by deserialize_bytes; all offsets (0..92) are within the validated keyslot layout, and physical_index is bounded < MAX_KEYSLOTS (4) so it fits in u8"
+        reason = "slot_bytes is a KEYSLOT_LEN-sized slice carved by deserialize_bytes; all offsets (0..KEYSLOT_LEN) are within the validated keyslot layout, and physical_index is bounded < MAX_EXTENDED_KEYSLOTS (32) so it fits in u8"
     )]
     fn deserialize(slot_bytes: &[u8], physical_index: usize) -> Result<Self, HeaderReadError> {
         if slot_bytes[0] != SLOT_STATE_ACTIVE {
@@ -242,6 +522,11 @@ impl V1Keyslot {
         }
 
         let kdf = KeyslotKdf::deserialize(slot_bytes[2], slot_bytes[3])?;
+        // Recipient slots only use the first 16 nonce bytes for the ephemeral key.
+        if kdf == KeyslotKdf::X25519Recipient && slot_bytes[36..44] != [0u8; 8] {
+            return Err(HeaderReadError::NonZeroActiveKeyslotPadding(physical_index));
+        }
+        let metadata = KeyslotMetadata::from_bytes(&slot_bytes[92..], physical_index)?;
 
         Ok(Self {
             physical_index: u8::try_from(physical_index)
@@ -250,6 +535,7 @@ impl V1Keyslot {
             salt: Salt::try_from_slice(&slot_bytes[4..20])?,
             nonce: KeyslotNonce::try_from_slice(&slot_bytes[20..44])?,
             encrypted_master_key: EncryptedMasterKey::try_from_slice(&slot_bytes[44..92])?,
+            metadata,
         })
     }
 }
@@ -257,6 +543,7 @@ impl V1Keyslot {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct V1Keyslots {
     inner: Vec<V1Keyslot>,
+    capacity: V1KeyslotCapacity,
 }
 
 impl V1Keyslots {
@@ -264,38 +551,60 @@ impl V1Keyslots {
     pub fn single(keyslot: V1Keyslot) -> Self {
         Self {
             inner: vec![keyslot.with_physical_index(0)],
+            capacity: V1KeyslotCapacity::CANONICAL,
         }
     }
 
     pub fn try_from_vec(keyslots: Vec<V1Keyslot>) -> Result<Self, HeaderWriteError> {
+        Self::try_from_vec_with_capacity(keyslots, V1KeyslotCapacity::CANONICAL)
+    }
+
+    /// Like [`Self::try_from_vec`], but reserves `capacity` physical slots so
+    /// keys can be added later without re-encrypting.
+    pub fn try_from_vec_with_capacity(
+        keyslots: Vec<V1Keyslot>,
+        capacity: V1KeyslotCapacity,
+    ) -> Result<Self, HeaderWriteError> {
         match keyslots.len() {
             0 => Err(HeaderWriteError::NoKeyslots),
-            len if len > MAX_KEYSLOTS => Err(HeaderWriteError::TooManyKeyslots(len)),
+            len if len > capacity.get() => Err(HeaderWriteError::TooManyKeyslots(len)),
             _ => Ok(Self {
                 inner: keyslots
                     .into_iter()
                     .enumerate()
                     .map(|(index, keyslot)| keyslot.with_physical_index(index))
                     .collect(),
+                capacity,
             }),
         }
     }
 
-    fn try_from_parsed_vec(keyslots: Vec<V1Keyslot>) -> Result<Self, HeaderWriteError> {
+    fn try_from_parsed_vec(
+        keyslots: Vec<V1Keyslot>,
+        capacity: V1KeyslotCapacity,
+    ) -> Result<Self, HeaderWriteError> {
         match keyslots.len() {
             0 => Err(HeaderWriteError::NoKeyslots),
-            len if len > MAX_KEYSLOTS => Err(HeaderWriteError::TooManyKeyslots(len)),
-            _ => Ok(Self { inner: keyslots }),
+            len if len > capacity.get() => Err(HeaderWriteError::TooManyKeyslots(len)),
+            _ => Ok(Self {
+                inner: keyslots,
+                capacity,
+            }),
         }
     }
 
+    #[must_use]
+    pub const fn capacity(&self) -> V1KeyslotCapacity {
+        self.capacity
+    }
+
     #[must_use]
     pub fn as_slice(&self) -> &[V1Keyslot] {
         &self.inner
     }
 
     pub fn iter_physical_slots(&self) -> impl Iterator<Item = (usize, Option<&V1Keyslot>)> + '_ {
-        (0..MAX_KEYSLOTS)
+        (0..self.capacity.get())
             .map(move |physical_index| (physical_index, self.get_physical(physical_index)))
     }
 
@@ -311,19 +620,22 @@ impl V1Keyslots {
 
     #[must_use]
     pub fn is_full(&self) -> bool {
-        self.inner.len() == MAX_KEYSLOTS
+        self.inner.len() >= self.capacity.get()
     }
 
     #[must_use]
     pub fn supported_slot_count(&self) -> usize {
         self.inner
             .iter()
-            .filter(|keyslot| matches!(keyslot.kdf(), KeyslotKdf::Argon2id))
+            .filter(|keyslot| {
+                keyslot.kdf().password_kdf().is_some()
+                    || keyslot.kdf() == KeyslotKdf::X25519Recipient
+            })
             .count()
     }
 
     pub fn first_empty_physical_slot(&self) -> Option<V1KeyslotIndex> {
-        (0..MAX_KEYSLOTS)
+        (0..self.capacity.get())
             .find(|index| self.get_physical(*index).is_none())
             .and_then(|index| V1KeyslotIndex::try_from_physical_index(index).ok())
     }
@@ -332,11 +644,11 @@ impl V1Keyslots {
     #[expect(
         clippy::cast_possible_truncation,
         clippy::expect_used,
-        reason = "V1Keyslots maintains the invariant that inner.len() is in 1..=MAX_KEYSLOTS (4), so the cast never truncates and try_from_u8 always succeeds"
+        reason = "V1Keyslots maintains the invariant that inner.len() is in 1..=capacity (at most 32), so the cast never truncates and try_from_u8 always succeeds"
     )]
     pub fn count(&self) -> V1KeyslotCount {
         V1KeyslotCount::try_from_u8(self.inner.len() as u8)
-            .expect("V1Keyslots invariant keeps count in 1..=4")
+            .expect("V1Keyslots invariant keeps count in 1..=32")
     }
 
     pub fn push(&mut self, keyslot: V1Keyslot) -> Result<(), HeaderWriteError> {
@@ -361,7 +673,7 @@ impl V1Keyslots {
                 self.inner.len().saturating_add(1),
             ));
         }
-        if self.get_physical(index.get()).is_some() {
+        if index.get() >= self.capacity.get() || self.get_physical(index.get()).is_some() {
             return Err(HeaderWriteError::InvalidKeyslotIndex(index.get()));
         }
         self.inner.push(keyslot.with_physical_index(index.get()));
@@ -416,6 +728,7 @@ pub struct V1Header {
     payload_kind: PayloadKind,
     payload_framing: PayloadFramingProfile,
     keyslots: V1Keyslots,
+    header_trailer: bool,
 }
 
 impl V1Header {
@@ -429,6 +742,7 @@ impl V1Header {
             payload_kind: PayloadKind::RawFile,
             payload_framing: PayloadFramingProfile::RawLe31,
             keyslots,
+            header_trailer: false,
         })
     }
 
@@ -442,21 +756,76 @@ impl V1Header {
             payload_kind: PayloadKind::ManifestArchive,
             payload_framing: PayloadFramingProfile::ManifestFirst,
             keyslots,
+            header_trailer: false,
+        })
+    }
+
+    /// Like [`Self::new_manifest_archive`], but body frames carry a codec byte
+    /// and may be compressed.
+    #[must_use = "constructing a V1Header and discarding it is a no-op on a security path"]
+    pub fn new_compressed_manifest_archive(
+        payload_nonce: PayloadNonce,
+        keyslots: V1Keyslots,
+    ) -> Result<Self, HeaderWriteError> {
+        Ok(Self {
+            payload_nonce,
+            payload_kind: PayloadKind::ManifestArchive,
+            payload_framing: PayloadFramingProfile::ManifestFirstCompressed,
+            keyslots,
+            header_trailer: false,
         })
     }
 
+    /// Marks this header as repeated in a trailer after the final payload
+    /// block; see [`Self::serialize_trailer`].
+    ///
+    /// The flag is part of [`Self::aad`], so it must be set before any
+    /// keyslot is wrapped against this header.
+    #[must_use]
+    pub const fn with_header_trailer(mut self) -> Self {
+        self.header_trailer = true;
+        self
+    }
+
     /// Rebuilds this header with a new keyslot table, preserving `payload_nonce`,
-    /// `payload_kind`, and `payload_framing` verbatim from `self`.
+    /// `payload_kind`, `payload_framing`, and the trailer flag verbatim from `self`.
     ///
     /// Use this instead of `V1Header::new` for any key-operation that must not
-    /// corrupt the archive AAD.
+    /// corrupt the archive AAD. The keyslot capacity is part of that AAD, so
+    /// `keyslots` must keep this header's capacity.
     #[must_use = "the rebuilt header must be written; discarding it leaves the original AAD profile in place"]
     pub fn with_keyslots(&self, keyslots: V1Keyslots) -> Result<Self, HeaderWriteError> {
+        if keyslots.capacity() != self.keyslot_capacity() {
+            return Err(HeaderWriteError::InvalidKeyslotCapacity(
+                keyslots.capacity().get(),
+            ));
+        }
         Ok(Self {
             payload_nonce: self.payload_nonce,
             payload_kind: self.payload_kind,
             payload_framing: self.payload_framing,
             keyslots,
+            header_trailer: self.header_trailer,
+        })
+    }
+
+    /// Rebuilds this header for a re-encrypted payload, preserving
+    /// `payload_kind`, `payload_framing`, and the trailer flag verbatim from `self`.
+    ///
+    /// Every slot-wrapping AAD binds the payload nonce, so each keyslot in
+    /// `keyslots` must be wrapped against the returned header.
+    #[must_use = "constructing a V1Header and discarding it is a no-op on a security path"]
+    pub fn with_payload_nonce(
+        &self,
+        payload_nonce: PayloadNonce,
+        keyslots: V1Keyslots,
+    ) -> Result<Self, HeaderWriteError> {
+        Ok(Self {
+            payload_nonce,
+            payload_kind: self.payload_kind,
+            payload_framing: self.payload_framing,
+            keyslots,
+            header_trailer: self.header_trailer,
         })
     }
 
@@ -485,21 +854,53 @@ impl V1Header {
         self.payload_framing
     }
 
+    #[must_use]
+    pub const fn keyslot_capacity(&self) -> V1KeyslotCapacity {
+        self.keyslots.capacity()
+    }
+
+    /// Length of [`Self::serialize`]'s output.
+    #[must_use]
+    pub const fn serialized_len(&self) -> usize {
+        self.keyslot_capacity().header_len()
+    }
+
+    #[must_use]
+    pub const fn has_header_trailer(&self) -> bool {
+        self.header_trailer
+    }
+
+    /// Length of the trailer that follows the final payload block: zero unless
+    /// [`Self::has_header_trailer`].
+    #[must_use]
+    pub const fn trailer_len(&self) -> usize {
+        if self.header_trailer {
+            self.serialized_len()
+                .saturating_add(HEADER_TRAILER_FOOTER_LEN)
+        } else {
+            0
+        }
+    }
+
     #[must_use]
     #[expect(
         clippy::cast_possible_truncation,
-        reason = "MAX_KEYSLOTS (4) trivially fits in u8 for the keyslot-count AAD byte"
+        reason = "keyslot capacity is at most MAX_EXTENDED_KEYSLOTS (32), which fits in u8"
     )]
     pub fn aad(&self) -> V1HeaderAad {
+        let capacity = self.keyslot_capacity();
         let mut aad = [0u8; HEADER_STATIC_LEN];
         aad[..4].copy_from_slice(&MAGIC);
         aad[4..6].copy_from_slice(&VERSION_V1);
         aad[6..10].copy_from_slice(&CANONICAL_V1_DISCRIMINATOR);
-        aad[10] = CANONICAL_SCHEMA_PROFILE;
+        aad[10] = capacity.schema_profile();
         aad[11] = self.payload_kind.to_byte();
         aad[12] = self.payload_framing.to_byte();
         aad[13] = ARGON2ID_KDF_PARAM_PROFILE_ID;
-        aad[14] = MAX_KEYSLOTS as u8;
+        aad[14] = capacity.get() as u8;
+        if self.header_trailer {
+            aad[15] = HEADER_FLAG_TRAILER;
+        }
         aad[16..36].copy_from_slice(self.payload_nonce.as_bytes());
         V1HeaderAad::from_static_header_bytes(aad)
     }
@@ -517,14 +918,14 @@ impl V1Header {
 
     #[expect(
         clippy::expect_used,
-        reason = "the preceding `physical_index >= MAX_KEYSLOTS` guard guarantees the value is < 4, so it fits in u8"
+        reason = "the preceding `physical_index >= capacity` guard guarantees the value is < 32, so it fits in u8"
     )]
     fn slot_wrapping_aad_for_keyslot(
         &self,
         keyslot: &V1Keyslot,
     ) -> Result<Vec<u8>, HeaderWriteError> {
         let physical_index = keyslot.physical_index();
-        if physical_index >= MAX_KEYSLOTS {
+        if physical_index >= self.keyslot_capacity().get() {
             return Err(HeaderWriteError::InvalidKeyslotIndex(physical_index));
         }
 
@@ -535,11 +936,15 @@ impl V1Header {
         aad.push(keyslot.kdf.serialize_param_profile());
         aad.extend_from_slice(keyslot.salt.as_bytes());
         aad.extend_from_slice(keyslot.nonce.as_bytes());
+        // unlabeled slots keep the AAD they had before metadata existed
+        if let Some(metadata) = keyslot.metadata {
+            aad.extend_from_slice(&metadata.to_bytes());
+        }
         Ok(aad)
     }
 
     pub fn serialize(&self) -> Result<Vec<u8>, HeaderWriteError> {
-        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        let mut bytes = Vec::with_capacity(self.serialized_len());
         bytes.extend_from_slice(self.aad().as_bytes());
 
         for (_physical_index, keyslot) in self.keyslots.iter_physical_slots() {
@@ -552,6 +957,17 @@ impl V1Header {
         Ok(bytes)
     }
 
+    /// Serializes the trailer written after the final payload block: a copy of
+    /// [`Self::serialize`], its length as a little-endian `u64`, and
+    /// [`HEADER_TRAILER_MAGIC`].
+    pub fn serialize_trailer(&self) -> Result<Vec<u8>, HeaderWriteError> {
+        let mut bytes = self.serialize()?;
+        let header_len = bytes.len() as u64;
+        bytes.extend_from_slice(&header_len.to_le_bytes());
+        bytes.extend_from_slice(&HEADER_TRAILER_MAGIC);
+        Ok(bytes)
+    }
+
     pub fn write(&self, writer: &mut impl Write) -> Result<(), HeaderWriteError> {
         let serialized = self.serialize()?;
         writer.write_all(&serialized).map_err(HeaderWriteError::Io)
@@ -565,10 +981,13 @@ impl V1Header {
     #[expect(
         clippy::indexing_slicing,
         clippy::arithmetic_side_effects,
-        clippy::cast_possible_truncation,
-        reason = "bytes is a fixed [u8; HEADER_LEN] array; every offset and the per-slot range (HEADER_STATIC_LEN + index*KEYSLOT_LEN for index<MAX_KEYSLOTS) is a compile-time-bounded position inside the canonical header layout, and MAX_KEYSLOTS (4) fits in u8"
+        reason = "bytes is checked to hold the static header before any offset is read, and to be exactly capacity.header_len() before the keyslot table is sliced, so every per-slot range (HEADER_STATIC_LEN + index*KEYSLOT_LEN for index<capacity) is in bounds"
     )]
-    pub(crate) fn deserialize_bytes(bytes: &[u8; HEADER_LEN]) -> Result<Self, HeaderReadError> {
+    pub(crate) fn deserialize_bytes(bytes: &[u8]) -> Result<Self, HeaderReadError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(HeaderReadError::TruncatedHeader);
+        }
+
         let mut magic = [0u8; 4];
         magic.copy_from_slice(&bytes[..4]);
         if magic != MAGIC {
@@ -589,8 +1008,9 @@ impl V1Header {
             ));
         }
 
-        if bytes[10] != CANONICAL_SCHEMA_PROFILE {
-            return Err(HeaderReadError::UnsupportedVersion([0x00, bytes[10]]));
+        let capacity = V1KeyslotCapacity::from_static_header(bytes[10], bytes[14]);
+        if let Err(error @ HeaderReadError::UnsupportedVersion(_)) = capacity {
+            return Err(error);
         }
         let payload_kind = PayloadKind::try_from_byte(bytes[11])
             .map_err(|_| HeaderReadError::InvalidPayloadKind(bytes[11]))?;
@@ -598,23 +1018,31 @@ impl V1Header {
             .map_err(|_| HeaderReadError::InvalidPayloadFraming(bytes[12]))?;
         match (payload_kind, payload_framing) {
             (PayloadKind::RawFile, PayloadFramingProfile::RawLe31)
-            | (PayloadKind::ManifestArchive, PayloadFramingProfile::ManifestFirst) => {}
+            | (
+                PayloadKind::ManifestArchive,
+                PayloadFramingProfile::ManifestFirst
+                | PayloadFramingProfile::ManifestFirstCompressed,
+            ) => {}
             _ => return Err(HeaderReadError::InvalidPayloadFraming(bytes[12])),
         }
         if bytes[13] != ARGON2ID_KDF_PARAM_PROFILE_ID {
             return Err(HeaderReadError::InvalidKdfParamProfile(bytes[13]));
         }
-        if bytes[14] != MAX_KEYSLOTS as u8 {
-            return Err(HeaderReadError::InvalidKeyslotCount(bytes[14]));
-        }
-        if bytes[15] != 0 || bytes[36..HEADER_STATIC_LEN] != [0u8; HEADER_STATIC_LEN - 36] {
+        let capacity = capacity?;
+        if bytes[15] & !HEADER_FLAG_TRAILER != 0
+            || bytes[36..HEADER_STATIC_LEN] != [0u8; HEADER_STATIC_LEN - 36]
+        {
             return Err(HeaderReadError::NonZeroReservedBytes);
         }
 
         let payload_nonce = PayloadNonce::try_from_slice(&bytes[16..36])?;
 
-        let mut keyslots = Vec::with_capacity(MAX_KEYSLOTS);
-        for index in 0..MAX_KEYSLOTS {
+        if bytes.len() != capacity.header_len() {
+            return Err(HeaderReadError::TruncatedHeader);
+        }
+
+        let mut keyslots = Vec::with_capacity(capacity.get());
+        for index in 0..capacity.get() {
             let start = HEADER_STATIC_LEN + (index * KEYSLOT_LEN);
             let end = start + KEYSLOT_LEN;
             let slot_bytes = &bytes[start..end];
@@ -625,23 +1053,20 @@ impl V1Header {
                     }
                 }
                 SLOT_STATE_ACTIVE => {
-                    let keyslot = V1Keyslot::deserialize(slot_bytes, index)?;
-                    if slot_bytes[92..] != [0u8; KEYSLOT_LEN - 92] {
-                        return Err(HeaderReadError::NonZeroActiveKeyslotPadding(index));
-                    }
-                    keyslots.push(keyslot);
+                    keyslots.push(V1Keyslot::deserialize(slot_bytes, index)?);
                 }
                 state => return Err(HeaderReadError::InvalidSlotState { index, state }),
             }
         }
 
-        let keyslots = V1Keyslots::try_from_parsed_vec(keyslots)
+        let keyslots = V1Keyslots::try_from_parsed_vec(keyslots, capacity)
             .map_err(|_| HeaderReadError::InvalidKeyslotCount(0))?;
         Ok(Self {
             payload_nonce,
             payload_kind,
             payload_framing,
             keyslots,
+            header_trailer: bytes[15] & HEADER_FLAG_TRAILER != 0,
         })
     }
 }
@@ -7,20 +7,37 @@ pub const DERIVED_KEY_LEN: usize = 32;
 pub const SALT_LEN: usize = 16;
 
 // Argon2id KDF parameters. Argon2id is the OWASP-recommended, RFC 9106 memory-hard
-// password KDF and the only normal KDF for new V1 keyslots. Parameters are fixed (not
-// user-tunable) and versioned by the canonical KDF param-profile id; changing them
+// password KDF and the only normal KDF for new V1 keyslots. Parameters are never raw
+// user input: each named profile is frozen and versioned by its KDF param-profile id,
+// which every keyslot records next to its KDF profile id. Changing a profile's costs
 // later introduces a new param-profile id.
 //
-// `m_cost` is expressed in KiB: 262_144 KiB == 256 MiB of working memory. `t_cost` = 4
-// passes, `p_cost` = 4 lanes. The RustCrypto `argon2` crate computes lanes sequentially
-// in pure Rust (no threads), but still emits the spec-correct Argon2id p=4 digest; only
-// wall-clock differs. These comfortably exceed the OWASP Argon2id memory floors. See
-// book/src/dexios-core/Password-Hashing.md.
+// `m_cost` is expressed in KiB: 262_144 KiB == 256 MiB of working memory. The default
+// profile uses `t_cost` = 4 passes and `p_cost` = 4 lanes. The RustCrypto `argon2` crate
+// computes lanes sequentially in pure Rust (no threads), but still emits the spec-correct
+// Argon2id p=4 digest; only wall-clock differs. These comfortably exceed the OWASP
+// Argon2id memory floors. See book/src/dexios-core/Password-Hashing.md.
 pub const ARGON2ID_M_COST: u32 = 262_144;
 pub const ARGON2ID_T_COST: u32 = 4;
 pub const ARGON2ID_P_COST: u32 = 4;
+// "interactive": 64 MiB, t=3, p=4, for CI runners and small ARM machines. Still above
+// the OWASP Argon2id floor of 19 MiB / t=2.
+pub const ARGON2ID_INTERACTIVE_M_COST: u32 = 65_536;
+pub const ARGON2ID_INTERACTIVE_T_COST: u32 = 3;
+pub const ARGON2ID_INTERACTIVE_P_COST: u32 = 4;
+// "paranoid": 1 GiB, t=4, p=4, for long-term archives where unlock time matters less.
+pub const ARGON2ID_PARANOID_M_COST: u32 = 1_048_576;
+pub const ARGON2ID_PARANOID_T_COST: u32 = 4;
+pub const ARGON2ID_PARANOID_P_COST: u32 = 4;
 pub const ARGON2ID_KDF_PROFILE_ID: u8 = 0x01;
 pub const ARGON2ID_KDF_PARAM_PROFILE_ID: u8 = 0x01;
+pub const ARGON2ID_INTERACTIVE_KDF_PARAM_PROFILE_ID: u8 = 0x02;
+pub const ARGON2ID_PARANOID_KDF_PARAM_PROFILE_ID: u8 = 0x03;
+// X25519 recipient keyslots carry no password KDF: their wrapping key comes from
+// ephemeral-static ECDH expanded with HKDF-SHA256 (see `crate::recipient`). The
+// profile ids only name that construction in the keyslot record.
+pub const X25519_RECIPIENT_KDF_PROFILE_ID: u8 = 0x10;
+pub const X25519_RECIPIENT_KDF_PARAM_PROFILE_ID: u8 = 0x01;
 pub const ARGON2ID_OUTPUT_LEN: usize = DERIVED_KEY_LEN;
 pub const ARGON2ID_SALT_LEN: usize = SALT_LEN;
 
@@ -39,30 +56,98 @@ impl Salt {
     }
 }
 
+/// A named Argon2id cost profile.
+///
+/// `Argon2id` is the "default" profile and keeps the original canonical
+/// param-profile id, so keyslots written before profiles existed still decode to it.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Kdf {
     Argon2id,
+    Argon2idInteractive,
+    Argon2idParanoid,
 }
 
 impl Kdf {
-    /// Derives a 32-byte wrapping key from `raw_key` and `salt` using the frozen
-    /// production Argon2id parameters ([`ARGON2ID_M_COST`] = 256 MiB, t=4, p=4,
-    /// version 0x13). See `book/src/dexios-core/Password-Hashing.md`.
+    /// Every selectable profile, cheapest first.
+    pub const PROFILES: [Self; 3] = [
+        Self::Argon2idInteractive,
+        Self::Argon2id,
+        Self::Argon2idParanoid,
+    ];
+
+    /// Looks up a profile by its user-facing name.
+    #[must_use]
+    pub fn from_profile_name(name: &str) -> Option<Self> {
+        Self::PROFILES
+            .into_iter()
+            .find(|kdf| kdf.profile_name() == name)
+    }
+
+    #[must_use]
+    pub const fn profile_name(self) -> &'static str {
+        match self {
+            Self::Argon2idInteractive => "interactive",
+            Self::Argon2id => "default",
+            Self::Argon2idParanoid => "paranoid",
+        }
+    }
+
+    /// The keyslot param-profile id that records this profile.
+    #[must_use]
+    pub const fn param_profile_id(self) -> u8 {
+        match self {
+            Self::Argon2idInteractive => ARGON2ID_INTERACTIVE_KDF_PARAM_PROFILE_ID,
+            Self::Argon2id => ARGON2ID_KDF_PARAM_PROFILE_ID,
+            Self::Argon2idParanoid => ARGON2ID_PARANOID_KDF_PARAM_PROFILE_ID,
+        }
+    }
+
+    #[must_use]
+    pub const fn from_param_profile_id(id: u8) -> Option<Self> {
+        match id {
+            ARGON2ID_INTERACTIVE_KDF_PARAM_PROFILE_ID => Some(Self::Argon2idInteractive),
+            ARGON2ID_KDF_PARAM_PROFILE_ID => Some(Self::Argon2id),
+            ARGON2ID_PARANOID_KDF_PARAM_PROFILE_ID => Some(Self::Argon2idParanoid),
+            _ => None,
+        }
+    }
+
+    /// Returns the frozen `(m_cost KiB, t_cost, p_cost)` triple for this profile.
+    #[must_use]
+    pub const fn argon2id_costs(self) -> (u32, u32, u32) {
+        match self {
+            Self::Argon2idInteractive => (
+                ARGON2ID_INTERACTIVE_M_COST,
+                ARGON2ID_INTERACTIVE_T_COST,
+                ARGON2ID_INTERACTIVE_P_COST,
+            ),
+            Self::Argon2id => (ARGON2ID_M_COST, ARGON2ID_T_COST, ARGON2ID_P_COST),
+            Self::Argon2idParanoid => (
+                ARGON2ID_PARANOID_M_COST,
+                ARGON2ID_PARANOID_T_COST,
+                ARGON2ID_PARANOID_P_COST,
+            ),
+        }
+    }
+
+    /// Derives a 32-byte wrapping key from `raw_key` and `salt` using this
+    /// profile's frozen Argon2id parameters (version 0x13). The default profile is
+    /// [`ARGON2ID_M_COST`] = 256 MiB, t=4, p=4. See
+    /// `book/src/dexios-core/Password-Hashing.md`.
     #[must_use = "a derived wrapping key must be used; dropping it wastes an expensive KDF call"]
     pub fn derive(
         self,
         raw_key: &Protected<Vec<u8>>,
         salt: &Salt,
     ) -> Result<Protected<[u8; DERIVED_KEY_LEN]>, KdfError> {
-        match self {
-            Self::Argon2id => derive_argon2id_with_params(
-                raw_key,
-                salt,
-                ARGON2ID_M_COST,
-                ARGON2ID_T_COST,
-                ARGON2ID_P_COST,
-            ),
-        }
+        let (m_cost, t_cost, p_cost) = self.argon2id_costs();
+        derive_argon2id_with_params(raw_key, salt, m_cost, t_cost, p_cost)
+    }
+}
+
+impl Display for Kdf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.profile_name())
     }
 }
 
@@ -83,7 +168,7 @@ impl Display for KdfError {
 impl std::error::Error for KdfError {}
 
 /// Derives a 32-byte key with explicit Argon2id parameters. The public
-/// [`Kdf::derive`] always calls this with the frozen production constants; the
+/// [`Kdf::derive`] always calls this with one profile's frozen constants; the
 /// parameterized form exists so cost parameters live in exactly one place.
 pub(crate) fn derive_argon2id_with_params(
     raw_key: &Protected<Vec<u8>>,
@@ -37,6 +37,7 @@ pub mod key;
 pub mod payload;
 pub mod primitives;
 pub mod protected;
+pub mod recipient;
 pub mod stream;
 pub use aead::Payload;
 pub use zeroize::Zeroize;
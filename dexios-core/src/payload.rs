@@ -1,11 +1,34 @@
 //! Canonical V1 payload kind and Dexios-owned archive framing primitives.
 
+use std::collections::HashSet;
 use std::fmt::{self, Display, Formatter};
 use std::io::{self, Read, Write};
 
+mod compression;
+mod metadata;
+
+pub use compression::{
+    BodyCodec, BodyCompression, BodyDecoder, BodyEncoder, COMPRESSION_CHUNK_LEN,
+    DEFAULT_ZSTD_LEVEL, MAX_ZSTD_LEVEL,
+};
+pub use metadata::{
+    EntryMetadata, EntryTimestamp, ExtendedAttribute, MAX_ENTRY_XATTR_BYTES, MAX_XATTR_NAME_BYTES,
+    MODE_BITS,
+};
+
 pub const MANIFEST_MAGIC: [u8; 4] = *b"DXAR";
 const BODY_FRAME_MAGIC: [u8; 4] = *b"DXBF";
+const DIGEST_KIND_NONE: u8 = 0x00;
+const DIGEST_KIND_BLAKE3: u8 = 0x01;
 pub const MANIFEST_VERSION: u16 = 0x0001;
+/// Manifest version whose entries carry an [`EntryMetadata`] record. Writers
+/// only emit it when at least one entry has metadata.
+pub const MANIFEST_VERSION_METADATA: u16 = 0x0002;
+/// Manifest version 2 plus an optional content digest on every file entry.
+/// Writers only emit it when at least one file entry has a digest.
+pub const MANIFEST_VERSION_DIGEST: u16 = 0x0003;
+/// Length of the BLAKE3 content digest a file entry may carry.
+pub const ENTRY_DIGEST_LEN: usize = 32;
 pub const MAX_MANIFEST_ENTRY_COUNT: u32 = 65_536;
 pub const MAX_NORMALIZED_PATH_BYTES: usize = 4096;
 pub const MAX_BODY_FRAME_LEN: u64 = 1024 * 1024 * 1024;
@@ -39,6 +62,10 @@ impl PayloadKind {
 pub enum PayloadFramingProfile {
     RawLe31 = 0x01,
     ManifestFirst = 0x02,
+    /// [`Self::ManifestFirst`] with a [`BodyCodec`] byte in every body frame
+    /// header. Readers that predate it reject the header instead of misreading
+    /// compressed bodies.
+    ManifestFirstCompressed = 0x03,
 }
 
 impl PayloadFramingProfile {
@@ -51,9 +78,16 @@ impl PayloadFramingProfile {
         match byte {
             0x01 => Ok(Self::RawLe31),
             0x02 => Ok(Self::ManifestFirst),
+            0x03 => Ok(Self::ManifestFirstCompressed),
             _ => Err(PayloadError::UnsupportedPayloadFramingProfile(byte)),
         }
     }
+
+    /// Whether the payload is a `DXAR` manifest followed by `DXBF` body frames.
+    #[must_use]
+    pub const fn is_manifest_first(self) -> bool {
+        matches!(self, Self::ManifestFirst | Self::ManifestFirstCompressed)
+    }
 }
 
 #[derive(Debug)]
@@ -77,11 +111,25 @@ pub enum PayloadError {
     BodyFrameLengthMismatch { expected: u64, actual: u64 },
     TruncatedManifest,
     TrailingBytes(usize),
+    InvalidMetadataFlags(u8),
+    InvalidFileMode(u32),
+    InvalidTimestamp,
+    InvalidExtendedAttributeName,
+    ExtendedAttributeLimitExceeded { limit: usize, actual: usize },
+    InvalidLinkTarget,
+    UnresolvedHardLink(u32),
+    UnsupportedBodyCodec(u8),
+    InvalidCompressionLevel(i32),
+    InvalidCompressedBody,
+    InvalidDigestKind(u8),
+    UnexpectedEntryDigest,
+    EntryDigestMismatch(u32),
 }
 
 impl PartialEq for PayloadError {
     #[expect(
         clippy::match_same_arms,
+        clippy::too_many_lines,
         reason = "each variant pair is matched explicitly for clarity; collapsing arms with identical bodies would obscure which error variants are compared"
     )]
     fn eq(&self, other: &Self) -> bool {
@@ -164,6 +212,30 @@ impl PartialEq for PayloadError {
             ) => left_expected == right_expected && left_actual == right_actual,
             (Self::TruncatedManifest, Self::TruncatedManifest) => true,
             (Self::TrailingBytes(left), Self::TrailingBytes(right)) => left == right,
+            (Self::InvalidMetadataFlags(left), Self::InvalidMetadataFlags(right)) => left == right,
+            (Self::InvalidFileMode(left), Self::InvalidFileMode(right)) => left == right,
+            (Self::InvalidTimestamp, Self::InvalidTimestamp) => true,
+            (Self::InvalidExtendedAttributeName, Self::InvalidExtendedAttributeName) => true,
+            (
+                Self::ExtendedAttributeLimitExceeded {
+                    limit: left_limit,
+                    actual: left_actual,
+                },
+                Self::ExtendedAttributeLimitExceeded {
+                    limit: right_limit,
+                    actual: right_actual,
+                },
+            ) => left_limit == right_limit && left_actual == right_actual,
+            (Self::InvalidLinkTarget, Self::InvalidLinkTarget) => true,
+            (Self::UnresolvedHardLink(left), Self::UnresolvedHardLink(right)) => left == right,
+            (Self::UnsupportedBodyCodec(left), Self::UnsupportedBodyCodec(right)) => left == right,
+            (Self::InvalidCompressionLevel(left), Self::InvalidCompressionLevel(right)) => {
+                left == right
+            }
+            (Self::InvalidCompressedBody, Self::InvalidCompressedBody) => true,
+            (Self::InvalidDigestKind(left), Self::InvalidDigestKind(right)) => left == right,
+            (Self::UnexpectedEntryDigest, Self::UnexpectedEntryDigest) => true,
+            (Self::EntryDigestMismatch(left), Self::EntryDigestMismatch(right)) => left == right,
             _ => false,
         }
     }
@@ -172,6 +244,10 @@ impl PartialEq for PayloadError {
 impl Eq for PayloadError {}
 
 impl Display for PayloadError {
+    #[expect(
+        clippy::too_many_lines,
+        reason = "one message per payload error variant"
+    )]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::Io(error) => write!(f, "manifest-first payload IO failed: {error}"),
@@ -232,6 +308,49 @@ impl Display for PayloadError {
             Self::TrailingBytes(count) => {
                 write!(f, "manifest-first payload has {count} trailing byte(s)")
             }
+            Self::InvalidMetadataFlags(flags) => {
+                write!(f, "invalid manifest entry metadata flags: {flags:#04x}")
+            }
+            Self::InvalidFileMode(mode) => {
+                write!(f, "invalid manifest entry file mode: {mode:#o}")
+            }
+            Self::InvalidTimestamp => {
+                f.write_str("manifest entry timestamp has out-of-range nanoseconds")
+            }
+            Self::InvalidExtendedAttributeName => {
+                f.write_str("manifest entry extended attribute name is empty or contains NUL")
+            }
+            Self::ExtendedAttributeLimitExceeded { limit, actual } => write!(
+                f,
+                "manifest entry extended attribute size {actual} exceeds structural limit {limit}"
+            ),
+            Self::InvalidLinkTarget => {
+                f.write_str("manifest link entry target is empty, too long or contains NUL")
+            }
+            Self::UnresolvedHardLink(index) => write!(
+                f,
+                "hard link manifest entry {index} does not name an earlier file entry"
+            ),
+            Self::UnsupportedBodyCodec(codec) => {
+                write!(f, "unsupported manifest body frame codec: {codec}")
+            }
+            Self::InvalidCompressionLevel(level) => write!(
+                f,
+                "compression level {level} is outside 1..={MAX_ZSTD_LEVEL}"
+            ),
+            Self::InvalidCompressedBody => {
+                f.write_str("manifest body frame does not decompress to its declared length")
+            }
+            Self::InvalidDigestKind(kind) => {
+                write!(f, "invalid manifest entry digest kind: {kind}")
+            }
+            Self::UnexpectedEntryDigest => {
+                f.write_str("only file manifest entries can carry a content digest")
+            }
+            Self::EntryDigestMismatch(index) => write!(
+                f,
+                "manifest body frame for entry {index} does not match its recorded digest"
+            ),
         }
     }
 }
@@ -250,6 +369,10 @@ impl std::error::Error for PayloadError {
 pub enum ManifestEntryKind {
     File = 0x01,
     Directory = 0x02,
+    /// A symbolic link; its target is stored verbatim and never resolved.
+    Symlink = 0x03,
+    /// Another name for an earlier `File` entry, whose path is the target.
+    HardLink = 0x04,
 }
 
 impl ManifestEntryKind {
@@ -257,9 +380,15 @@ impl ManifestEntryKind {
         match byte {
             0x01 => Ok(Self::File),
             0x02 => Ok(Self::Directory),
+            0x03 => Ok(Self::Symlink),
+            0x04 => Ok(Self::HardLink),
             _ => Err(PayloadError::InvalidEntryKind(byte)),
         }
     }
+
+    const fn is_link(self) -> bool {
+        matches!(self, Self::Symlink | Self::HardLink)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -267,6 +396,9 @@ pub struct ManifestEntry {
     kind: ManifestEntryKind,
     normalized_path: Vec<u8>,
     body_len: Option<u64>,
+    link_target: Option<Vec<u8>>,
+    metadata: EntryMetadata,
+    digest: Option<[u8; ENTRY_DIGEST_LEN]>,
 }
 
 impl ManifestEntry {
@@ -275,6 +407,9 @@ impl ManifestEntry {
             kind: ManifestEntryKind::File,
             normalized_path: normalized_path.into(),
             body_len: Some(body_len),
+            link_target: None,
+            metadata: EntryMetadata::default(),
+            digest: None,
         };
         entry.validate()?;
         Ok(entry)
@@ -285,6 +420,42 @@ impl ManifestEntry {
             kind: ManifestEntryKind::Directory,
             normalized_path: normalized_path.into(),
             body_len: None,
+            link_target: None,
+            metadata: EntryMetadata::default(),
+            digest: None,
+        };
+        entry.validate()?;
+        Ok(entry)
+    }
+
+    pub fn symlink(
+        normalized_path: impl Into<Vec<u8>>,
+        target: impl Into<Vec<u8>>,
+    ) -> Result<Self, PayloadError> {
+        Self::link(ManifestEntryKind::Symlink, normalized_path, target)
+    }
+
+    /// A second name for the file entry at `target`, which must come earlier in
+    /// the same manifest.
+    pub fn hard_link(
+        normalized_path: impl Into<Vec<u8>>,
+        target: impl Into<Vec<u8>>,
+    ) -> Result<Self, PayloadError> {
+        Self::link(ManifestEntryKind::HardLink, normalized_path, target)
+    }
+
+    fn link(
+        kind: ManifestEntryKind,
+        normalized_path: impl Into<Vec<u8>>,
+        target: impl Into<Vec<u8>>,
+    ) -> Result<Self, PayloadError> {
+        let entry = Self {
+            kind,
+            normalized_path: normalized_path.into(),
+            body_len: None,
+            link_target: Some(target.into()),
+            metadata: EntryMetadata::default(),
+            digest: None,
         };
         entry.validate()?;
         Ok(entry)
@@ -305,6 +476,41 @@ impl ManifestEntry {
         self.body_len
     }
 
+    /// The recorded target of a `Symlink` or `HardLink` entry.
+    #[must_use]
+    pub fn link_target(&self) -> Option<&[u8]> {
+        self.link_target.as_deref()
+    }
+
+    pub fn with_metadata(mut self, metadata: EntryMetadata) -> Result<Self, PayloadError> {
+        metadata.validate()?;
+        self.metadata = metadata;
+        Ok(self)
+    }
+
+    #[must_use]
+    pub const fn metadata(&self) -> &EntryMetadata {
+        &self.metadata
+    }
+
+    /// Records the BLAKE3 hash of a file entry's decoded body.
+    pub fn with_blake3_digest(
+        mut self,
+        digest: [u8; ENTRY_DIGEST_LEN],
+    ) -> Result<Self, PayloadError> {
+        if !matches!(self.kind, ManifestEntryKind::File) {
+            return Err(PayloadError::UnexpectedEntryDigest);
+        }
+        self.digest = Some(digest);
+        Ok(self)
+    }
+
+    /// The BLAKE3 hash of the decoded body, if the writer recorded one.
+    #[must_use]
+    pub const fn blake3_digest(&self) -> Option<&[u8; ENTRY_DIGEST_LEN]> {
+        self.digest.as_ref()
+    }
+
     fn validate(&self) -> Result<(), PayloadError> {
         if self.normalized_path.is_empty() {
             return Err(PayloadError::EmptyNormalizedPath);
@@ -320,7 +526,18 @@ impl ManifestEntry {
         } else if self.kind == ManifestEntryKind::File {
             return Err(PayloadError::MissingBodyLength);
         }
-        Ok(())
+        match &self.link_target {
+            Some(target)
+                if target.is_empty()
+                    || target.len() > MAX_NORMALIZED_PATH_BYTES
+                    || target.contains(&0) =>
+            {
+                return Err(PayloadError::InvalidLinkTarget);
+            }
+            None if self.kind.is_link() => return Err(PayloadError::InvalidLinkTarget),
+            Some(_) | None => {}
+        }
+        self.metadata.validate()
     }
 }
 
@@ -343,8 +560,22 @@ impl ArchiveManifest {
                 actual: entry_count,
             });
         }
-        for entry in &entries {
+        let mut file_paths = HashSet::new();
+        for (index, entry) in entries.iter().enumerate() {
             entry.validate()?;
+            match (entry.kind, &entry.link_target) {
+                (ManifestEntryKind::File, _) => {
+                    file_paths.insert(entry.normalized_path.as_slice());
+                }
+                (ManifestEntryKind::HardLink, Some(target))
+                    if !file_paths.contains(target.as_slice()) =>
+                {
+                    return Err(PayloadError::UnresolvedHardLink(
+                        u32::try_from(index).unwrap_or(u32::MAX),
+                    ));
+                }
+                _ => {}
+            }
         }
         Ok(Self { entries })
     }
@@ -354,16 +585,33 @@ impl ArchiveManifest {
         &self.entries
     }
 
+    /// Link entries carry a `u16` target length after the path length and the
+    /// target bytes after the path. Older readers reject them as an unknown
+    /// entry kind rather than misreading the record.
+    ///
+    /// In a version 3 manifest every file entry ends with a digest kind byte:
+    /// `0` for none, or `1` followed by the 32-byte BLAKE3 hash of its body.
     #[expect(
         clippy::expect_used,
-        reason = "entry count and path lengths are bounded below MAX_MANIFEST_ENTRY_COUNT / u16::MAX by construction-time validation, so these width conversions cannot overflow"
+        reason = "entry count, path and link target lengths are bounded below MAX_MANIFEST_ENTRY_COUNT / u16::MAX by construction-time validation, so these width conversions cannot overflow"
     )]
     pub fn write_to(&self, writer: &mut impl Write) -> Result<(), PayloadError> {
+        // Archives without metadata or digests stay readable by older readers.
+        let with_digests = self.entries.iter().any(|entry| entry.digest.is_some());
+        let with_metadata =
+            with_digests || self.entries.iter().any(|entry| !entry.metadata.is_empty());
+        let version = if with_digests {
+            MANIFEST_VERSION_DIGEST
+        } else if with_metadata {
+            MANIFEST_VERSION_METADATA
+        } else {
+            MANIFEST_VERSION
+        };
         writer
             .write_all(&MANIFEST_MAGIC)
             .map_err(map_payload_io_error)?;
         writer
-            .write_all(&MANIFEST_VERSION.to_le_bytes())
+            .write_all(&version.to_le_bytes())
             .map_err(map_payload_io_error)?;
         writer
             .write_all(
@@ -389,9 +637,27 @@ impl ArchiveManifest {
                     .write_all(&body_len.to_le_bytes())
                     .map_err(map_payload_io_error)?;
             }
+            if let Some(target) = &entry.link_target {
+                writer
+                    .write_all(
+                        &u16::try_from(target.len())
+                            .expect("link target length is bounded")
+                            .to_le_bytes(),
+                    )
+                    .map_err(map_payload_io_error)?;
+            }
             writer
                 .write_all(&entry.normalized_path)
                 .map_err(map_payload_io_error)?;
+            if let Some(target) = &entry.link_target {
+                writer.write_all(target).map_err(map_payload_io_error)?;
+            }
+            if with_metadata {
+                entry.metadata.write_to(writer)?;
+            }
+            if with_digests && entry.kind == ManifestEntryKind::File {
+                write_digest_to(writer, entry.digest.as_ref())?;
+            }
         }
 
         Ok(())
@@ -403,7 +669,10 @@ impl ArchiveManifest {
             return Err(PayloadError::InvalidManifestMagic(magic));
         }
         let version = read_u16_from(reader)?;
-        if version != MANIFEST_VERSION {
+        if !matches!(
+            version,
+            MANIFEST_VERSION | MANIFEST_VERSION_METADATA | MANIFEST_VERSION_DIGEST
+        ) {
             return Err(PayloadError::UnsupportedManifestVersion(version));
         }
         let entry_count = read_u32_from(reader)?;
@@ -423,11 +692,32 @@ impl ArchiveManifest {
             } else {
                 None
             };
+            let target_len = if kind.is_link() {
+                Some(usize::from(read_u16_from(reader)?))
+            } else {
+                None
+            };
             let path = read_vec_from(reader, path_len)?;
+            let link_target = target_len
+                .map(|target_len| read_vec_from(reader, target_len))
+                .transpose()?;
+            let metadata = if version == MANIFEST_VERSION {
+                EntryMetadata::default()
+            } else {
+                EntryMetadata::read_from(reader)?
+            };
+            let digest = if version == MANIFEST_VERSION_DIGEST && kind == ManifestEntryKind::File {
+                read_digest_from(reader)?
+            } else {
+                None
+            };
             let entry = ManifestEntry {
                 kind,
                 normalized_path: path,
                 body_len,
+                link_target,
+                metadata,
+                digest,
             };
             entry.validate()?;
             entries.push(entry);
@@ -486,6 +776,7 @@ impl ArchiveBodyFrame {
 pub struct ArchiveBodyFrameHeader {
     entry_index: u32,
     body_len: u64,
+    codec: BodyCodec,
 }
 
 impl ArchiveBodyFrameHeader {
@@ -494,9 +785,19 @@ impl ArchiveBodyFrameHeader {
         Ok(Self {
             entry_index,
             body_len,
+            codec: BodyCodec::Stored,
         })
     }
 
+    /// Records how the body following this header is encoded. Only
+    /// [`PayloadFramingProfile::ManifestFirstCompressed`] can carry a codec
+    /// other than [`BodyCodec::Stored`].
+    #[must_use]
+    pub const fn with_codec(mut self, codec: BodyCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
     #[must_use]
     pub const fn entry_index(&self) -> u32 {
         self.entry_index
@@ -507,7 +808,25 @@ impl ArchiveBodyFrameHeader {
         self.body_len
     }
 
+    #[must_use]
+    pub const fn codec(&self) -> BodyCodec {
+        self.codec
+    }
+
+    /// Writes the header in the uncompressed [`PayloadFramingProfile::ManifestFirst`] form.
     pub fn write_to(&self, writer: &mut impl Write) -> Result<(), PayloadError> {
+        self.write_framed_to(writer, PayloadFramingProfile::ManifestFirst)
+    }
+
+    pub fn write_framed_to(
+        &self,
+        writer: &mut impl Write,
+        framing: PayloadFramingProfile,
+    ) -> Result<(), PayloadError> {
+        let with_codec = body_frames_carry_codec(framing)?;
+        if !with_codec && self.codec != BodyCodec::Stored {
+            return Err(PayloadError::UnsupportedBodyCodec(self.codec.to_byte()));
+        }
         writer
             .write_all(&BODY_FRAME_MAGIC)
             .map_err(map_payload_io_error)?;
@@ -516,17 +835,47 @@ impl ArchiveBodyFrameHeader {
             .map_err(map_payload_io_error)?;
         writer
             .write_all(&self.body_len.to_le_bytes())
-            .map_err(map_payload_io_error)
+            .map_err(map_payload_io_error)?;
+        if with_codec {
+            writer
+                .write_all(&[self.codec.to_byte()])
+                .map_err(map_payload_io_error)?;
+        }
+        Ok(())
     }
 
+    /// Reads a header in the uncompressed [`PayloadFramingProfile::ManifestFirst`] form.
     pub fn read_from(reader: &mut impl Read) -> Result<Self, PayloadError> {
+        Self::read_framed_from(reader, PayloadFramingProfile::ManifestFirst)
+    }
+
+    pub fn read_framed_from(
+        reader: &mut impl Read,
+        framing: PayloadFramingProfile,
+    ) -> Result<Self, PayloadError> {
+        let with_codec = body_frames_carry_codec(framing)?;
         let magic = read_array_from::<4>(reader)?;
         if magic != BODY_FRAME_MAGIC {
             return Err(PayloadError::InvalidBodyFrameMagic(magic));
         }
         let entry_index = read_u32_from(reader)?;
         let body_len = read_u64_from(reader)?;
-        Self::new(entry_index, body_len)
+        let codec = if with_codec {
+            BodyCodec::try_from_byte(read_u8_from(reader)?)?
+        } else {
+            BodyCodec::Stored
+        };
+        Ok(Self::new(entry_index, body_len)?.with_codec(codec))
+    }
+}
+
+fn body_frames_carry_codec(framing: PayloadFramingProfile) -> Result<bool, PayloadError> {
+    match framing {
+        PayloadFramingProfile::ManifestFirst => Ok(false),
+        PayloadFramingProfile::ManifestFirstCompressed => Ok(true),
+        PayloadFramingProfile::RawLe31 => Err(PayloadError::UnsupportedPayloadFramingProfile(
+            framing.to_byte(),
+        )),
     }
 }
 
@@ -596,13 +945,22 @@ impl ManifestFirstPayload {
     /// This is intended for trusted, in-memory use (round-trip tests, small archives).
     /// It enforces the per-frame [`MAX_BODY_FRAME_LEN`] and the aggregate
     /// [`MAX_TOTAL_BODY_FRAME_BYTES`] caps, but still allocates each frame fully. Production
-    /// extraction streams bodies via the unpack workflow's `reader.take(..)` path and never
+    /// extraction streams bodies through [`BodyDecoder`] in the unpack workflow and never
     /// calls this. Do not call `parse` on untrusted, large archives.
+    pub fn parse(bytes: &[u8]) -> Result<Self, PayloadError> {
+        Self::parse_framed(bytes, PayloadFramingProfile::ManifestFirst)
+    }
+
+    /// [`Self::parse`] for a payload written under `framing`, decoding any
+    /// compressed body frames. The same trusted-input caveats apply.
     #[expect(
         clippy::expect_used,
         reason = "entry count is bounded below MAX_MANIFEST_ENTRY_COUNT (< u32::MAX) and File entries always carry a validated body length, so these conversions/unwraps cannot fail"
     )]
-    pub fn parse(bytes: &[u8]) -> Result<Self, PayloadError> {
+    pub fn parse_framed(
+        bytes: &[u8],
+        framing: PayloadFramingProfile,
+    ) -> Result<Self, PayloadError> {
         let mut reader = io::Cursor::new(bytes);
         let manifest = ArchiveManifest::read_from(&mut reader)?;
         let mut body_frames = Vec::new();
@@ -612,7 +970,7 @@ impl ManifestFirstPayload {
                 continue;
             }
             let expected_index = u32::try_from(expected_index).expect("entry count is bounded");
-            let header = ArchiveBodyFrameHeader::read_from(&mut reader)?;
+            let header = ArchiveBodyFrameHeader::read_framed_from(&mut reader, framing)?;
             let actual_index = header.entry_index();
             if actual_index != expected_index {
                 return Err(PayloadError::BodyFrameOrderMismatch {
@@ -639,7 +997,13 @@ impl ManifestFirstPayload {
                     limit: MAX_BODY_FRAME_LEN,
                     actual: body_len,
                 })?;
-            let body = read_vec_from(&mut reader, body_len)?;
+            let mut body = Vec::new();
+            BodyDecoder::new(&mut reader, header.codec(), header.body_len())?
+                .read_to_end(&mut body)
+                .map_err(map_body_decode_error)?;
+            if body.len() != body_len {
+                return Err(PayloadError::TruncatedManifest);
+            }
             body_frames.push(ArchiveBodyFrame {
                 entry_index: actual_index,
                 body,
@@ -666,6 +1030,21 @@ fn map_payload_io_error(error: io::Error) -> PayloadError {
     }
 }
 
+fn map_body_decode_error(error: io::Error) -> PayloadError {
+    if !error
+        .get_ref()
+        .is_some_and(<dyn std::error::Error + Send + Sync>::is::<PayloadError>)
+    {
+        return map_payload_io_error(error);
+    }
+    error
+        .into_inner()
+        .and_then(|inner| inner.downcast::<PayloadError>().ok())
+        .map_or(PayloadError::InvalidCompressedBody, |payload_error| {
+            *payload_error
+        })
+}
+
 fn validate_body_len(body_len: u64) -> Result<(), PayloadError> {
     if body_len > MAX_BODY_FRAME_LEN {
         return Err(PayloadError::BodyFrameLimitExceeded {
@@ -734,6 +1113,33 @@ fn body_frame_for(
         .ok_or(PayloadError::MissingBodyFrame(index))
 }
 
+fn write_digest_to(
+    writer: &mut impl Write,
+    digest: Option<&[u8; ENTRY_DIGEST_LEN]>,
+) -> Result<(), PayloadError> {
+    match digest {
+        Some(digest) => {
+            writer
+                .write_all(&[DIGEST_KIND_BLAKE3])
+                .map_err(map_payload_io_error)?;
+            writer.write_all(digest).map_err(map_payload_io_error)
+        }
+        None => writer
+            .write_all(&[DIGEST_KIND_NONE])
+            .map_err(map_payload_io_error),
+    }
+}
+
+fn read_digest_from(
+    reader: &mut impl Read,
+) -> Result<Option<[u8; ENTRY_DIGEST_LEN]>, PayloadError> {
+    match read_u8_from(reader)? {
+        DIGEST_KIND_NONE => Ok(None),
+        DIGEST_KIND_BLAKE3 => read_array_from(reader).map(Some),
+        kind => Err(PayloadError::InvalidDigestKind(kind)),
+    }
+}
+
 fn read_u8_from(reader: &mut impl Read) -> Result<u8, PayloadError> {
     Ok(read_array_from::<1>(reader)?[0])
 }
@@ -0,0 +1,294 @@
+//! Per-frame body compression for the
+//! [`PayloadFramingProfile::ManifestFirstCompressed`](super::PayloadFramingProfile) profile.
+//!
+//! Under that profile every `DXBF` header carries a codec byte after the body
+//! length. A [`BodyCodec::Stored`] body is the raw `body_len` bytes. A
+//! [`BodyCodec::Zstd`] body is a run of chunks:
+//!
+//! ```text
+//! stored_len: u32, then stored_len bytes holding one zstd frame
+//! ```
+//!
+//! Every chunk but the last decompresses to exactly [`COMPRESSION_CHUNK_LEN`]
+//! bytes and the last to whatever remains of `body_len`. Chunk sizes are
+//! implied by the declared length, so a reader never produces more than the
+//! manifest declared and callers can enforce their size limits on `body_len`
+//! before decompressing anything.
+
+use std::io::{self, Read, Write};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{PayloadError, map_payload_io_error};
+
+/// Plaintext bytes compressed independently as one chunk (1 MiB).
+pub const COMPRESSION_CHUNK_LEN: usize = 1024 * 1024;
+/// Highest zstd level accepted; the "ultra" levels above it need far more memory.
+pub const MAX_ZSTD_LEVEL: i32 = 19;
+/// The zstd level used when none is given.
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum BodyCodec {
+    Stored = 0x00,
+    Zstd = 0x01,
+}
+
+impl BodyCodec {
+    #[must_use]
+    pub const fn to_byte(self) -> u8 {
+        self as u8
+    }
+
+    pub const fn try_from_byte(byte: u8) -> Result<Self, PayloadError> {
+        match byte {
+            0x00 => Ok(Self::Stored),
+            0x01 => Ok(Self::Zstd),
+            _ => Err(PayloadError::UnsupportedBodyCodec(byte)),
+        }
+    }
+}
+
+/// How a writer compresses body frames: a codec and its level.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BodyCompression {
+    codec: BodyCodec,
+    level: i32,
+}
+
+impl BodyCompression {
+    /// zstd at `level`, which must be within `1..=MAX_ZSTD_LEVEL`.
+    pub const fn zstd(level: i32) -> Result<Self, PayloadError> {
+        if level < 1 || level > MAX_ZSTD_LEVEL {
+            return Err(PayloadError::InvalidCompressionLevel(level));
+        }
+        Ok(Self {
+            codec: BodyCodec::Zstd,
+            level,
+        })
+    }
+
+    #[must_use]
+    pub const fn codec(&self) -> BodyCodec {
+        self.codec
+    }
+
+    #[must_use]
+    pub const fn level(&self) -> i32 {
+        self.level
+    }
+}
+
+impl Default for BodyCompression {
+    fn default() -> Self {
+        Self {
+            codec: BodyCodec::Zstd,
+            level: DEFAULT_ZSTD_LEVEL,
+        }
+    }
+}
+
+/// Compresses one body frame into chunks as it is written.
+///
+/// [`BodyEncoder::finish`] must be called once the whole body has been
+/// written; dropping the encoder loses the final partial chunk. The chunk
+/// buffers hold plaintext, so they are zeroed after every chunk and on drop.
+pub struct BodyEncoder<W: Write> {
+    writer: W,
+    compressor: zstd::bulk::Compressor<'static>,
+    chunk: Zeroizing<Vec<u8>>,
+    compressed: Zeroizing<Vec<u8>>,
+}
+
+impl<W: Write> BodyEncoder<W> {
+    pub fn new(writer: W, compression: BodyCompression) -> Result<Self, PayloadError> {
+        let compressor =
+            zstd::bulk::Compressor::new(compression.level).map_err(PayloadError::Io)?;
+        Ok(Self {
+            writer,
+            compressor,
+            // Both buffers are sized up front so they never reallocate and
+            // leave a copy behind.
+            chunk: Zeroizing::new(Vec::with_capacity(COMPRESSION_CHUNK_LEN)),
+            compressed: Zeroizing::new(Vec::with_capacity(zstd::zstd_safe::compress_bound(
+                COMPRESSION_CHUNK_LEN,
+            ))),
+        })
+    }
+
+    pub fn finish(mut self) -> Result<W, PayloadError> {
+        if !self.chunk.is_empty() {
+            self.write_chunk().map_err(map_payload_io_error)?;
+        }
+        Ok(self.writer)
+    }
+
+    #[expect(
+        clippy::expect_used,
+        reason = "a chunk holds at most COMPRESSION_CHUNK_LEN bytes, whose compress bound fits in u32"
+    )]
+    fn write_chunk(&mut self) -> io::Result<()> {
+        self.compressed.clear();
+        self.compressor
+            .compress_to_buffer(self.chunk.as_slice(), &mut *self.compressed)?;
+        let stored_len =
+            u32::try_from(self.compressed.len()).expect("compressed chunk length is bounded");
+        self.writer.write_all(&stored_len.to_le_bytes())?;
+        self.writer.write_all(&self.compressed)?;
+        self.chunk.zeroize();
+        self.compressed.zeroize();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for BodyEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let room = COMPRESSION_CHUNK_LEN.saturating_sub(self.chunk.len());
+        let taken = buf.get(..room.min(buf.len())).unwrap_or(buf);
+        self.chunk.extend_from_slice(taken);
+        if self.chunk.len() == COMPRESSION_CHUNK_LEN {
+            self.write_chunk()?;
+        }
+        Ok(taken.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Yields the decoded bytes of one body frame, never more than `body_len`.
+///
+/// Malformed chunks surface as an [`io::Error`] wrapping a [`PayloadError`];
+/// errors from the inner reader pass through unchanged. As with
+/// [`BodyEncoder`], the chunk buffers are zeroed before each chunk and on drop.
+pub struct BodyDecoder<R: Read> {
+    reader: R,
+    codec: BodyCodec,
+    remaining: u64,
+    decompressor: Option<zstd::bulk::Decompressor<'static>>,
+    chunk: Zeroizing<Vec<u8>>,
+    position: usize,
+    compressed: Zeroizing<Vec<u8>>,
+}
+
+impl<R: Read> BodyDecoder<R> {
+    pub fn new(reader: R, codec: BodyCodec, body_len: u64) -> Result<Self, PayloadError> {
+        let (decompressor, chunk_capacity) = match codec {
+            BodyCodec::Stored => (None, 0),
+            BodyCodec::Zstd => (
+                Some(zstd::bulk::Decompressor::new().map_err(PayloadError::Io)?),
+                usize::try_from(body_len)
+                    .unwrap_or(usize::MAX)
+                    .min(COMPRESSION_CHUNK_LEN),
+            ),
+        };
+        // The first chunk is the largest, so neither buffer outgrows these
+        // capacities and reallocates away from a copy it cannot zero.
+        Ok(Self {
+            reader,
+            codec,
+            remaining: body_len,
+            decompressor,
+            chunk: Zeroizing::new(Vec::with_capacity(chunk_capacity)),
+            position: 0,
+            compressed: Zeroizing::new(Vec::with_capacity(zstd::zstd_safe::compress_bound(
+                chunk_capacity,
+            ))),
+        })
+    }
+
+    #[expect(
+        clippy::expect_used,
+        reason = "expected is min(COMPRESSION_CHUNK_LEN, remaining), which fits in usize and in u64"
+    )]
+    fn next_chunk(&mut self) -> io::Result<()> {
+        let expected = usize::try_from(self.remaining.min(COMPRESSION_CHUNK_LEN as u64))
+            .expect("chunk length is bounded");
+        let mut stored_len = [0u8; 4];
+        read_exact_or_truncated(&mut self.reader, &mut stored_len)?;
+        let stored_len = usize::try_from(u32::from_le_bytes(stored_len)).unwrap_or(usize::MAX);
+        if stored_len == 0 || stored_len > zstd::zstd_safe::compress_bound(expected) {
+            return Err(invalid_body());
+        }
+        self.compressed.zeroize();
+        self.compressed.resize(stored_len, 0);
+        read_exact_or_truncated(&mut self.reader, &mut self.compressed)?;
+
+        self.chunk.zeroize();
+        self.chunk.resize(expected, 0);
+        let Some(decompressor) = self.decompressor.as_mut() else {
+            return Err(invalid_body());
+        };
+        // The destination is exactly the chunk's declared size, so a chunk
+        // that would inflate past it fails instead of allocating.
+        let produced = decompressor
+            .decompress_to_buffer(self.compressed.as_slice(), self.chunk.as_mut_slice())
+            .map_err(|_| invalid_body())?;
+        if produced != expected {
+            return Err(invalid_body());
+        }
+        self.position = 0;
+        self.remaining = self
+            .remaining
+            .saturating_sub(u64::try_from(expected).expect("chunk length fits in u64"));
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BodyDecoder<R> {
+    #[expect(
+        clippy::expect_used,
+        reason = "read counts are bounded by buffer lengths, which fit in u64"
+    )]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.codec == BodyCodec::Stored {
+            let limit = usize::try_from(self.remaining)
+                .unwrap_or(usize::MAX)
+                .min(buf.len());
+            let Some(buf) = buf.get_mut(..limit) else {
+                return Ok(0);
+            };
+            let read = self.reader.read(buf)?;
+            self.remaining = self
+                .remaining
+                .saturating_sub(u64::try_from(read).expect("read count fits in u64"));
+            return Ok(read);
+        }
+
+        if self.position == self.chunk.len() {
+            if self.remaining == 0 {
+                return Ok(0);
+            }
+            self.next_chunk()?;
+        }
+        let available = self.chunk.get(self.position..).unwrap_or_default();
+        let count = available.len().min(buf.len());
+        if let (Some(target), Some(source)) = (buf.get_mut(..count), available.get(..count)) {
+            target.copy_from_slice(source);
+        }
+        self.position = self.position.saturating_add(count);
+        Ok(count)
+    }
+}
+
+fn read_exact_or_truncated(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<()> {
+    reader.read_exact(buf).map_err(|error| {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            io::Error::new(io::ErrorKind::InvalidData, PayloadError::TruncatedManifest)
+        } else {
+            error
+        }
+    })
+}
+
+fn invalid_body() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        PayloadError::InvalidCompressedBody,
+    )
+}
@@ -0,0 +1,365 @@
+//! Per-entry file metadata carried by version 2 `DXAR` manifests.
+//!
+//! A version 2 manifest follows every entry's path with one metadata record:
+//!
+//! ```text
+//! flags: u8
+//! mode: u32                       if flags & 0x01
+//! mtime: i64 seconds, u32 nanos   if flags & 0x02
+//! atime: i64 seconds, u32 nanos   if flags & 0x04
+//! uid: u32, gid: u32              if flags & 0x08
+//! xattr count: u16                if flags & 0x10, then per attribute
+//!   name_len: u8, value_len: u32, name, value
+//! ```
+//!
+//! Reserved flag bits must be zero. Integers are little-endian.
+
+use std::io::{Read, Write};
+
+use super::{
+    PayloadError, map_payload_io_error, read_array_from, read_u8_from, read_u16_from,
+    read_u32_from, read_vec_from,
+};
+
+/// Longest extended attribute name, matching the Linux `XATTR_NAME_MAX`.
+pub const MAX_XATTR_NAME_BYTES: usize = 255;
+/// Ceiling on the combined name and value bytes of one entry's extended attributes.
+pub const MAX_ENTRY_XATTR_BYTES: usize = 64 * 1024;
+/// Permission and mode bits an entry may carry (`0o7777`).
+pub const MODE_BITS: u32 = 0o7777;
+
+const FLAG_MODE: u8 = 0x01;
+const FLAG_MTIME: u8 = 0x02;
+const FLAG_ATIME: u8 = 0x04;
+const FLAG_OWNER: u8 = 0x08;
+const FLAG_XATTRS: u8 = 0x10;
+const KNOWN_FLAGS: u8 = FLAG_MODE | FLAG_MTIME | FLAG_ATIME | FLAG_OWNER | FLAG_XATTRS;
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EntryTimestamp {
+    seconds: i64,
+    nanoseconds: u32,
+}
+
+impl EntryTimestamp {
+    pub const fn new(seconds: i64, nanoseconds: u32) -> Result<Self, PayloadError> {
+        if nanoseconds >= NANOS_PER_SECOND {
+            return Err(PayloadError::InvalidTimestamp);
+        }
+        Ok(Self {
+            seconds,
+            nanoseconds,
+        })
+    }
+
+    #[must_use]
+    pub const fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    #[must_use]
+    pub const fn nanoseconds(&self) -> u32 {
+        self.nanoseconds
+    }
+
+    fn write_to(self, writer: &mut impl Write) -> Result<(), PayloadError> {
+        writer
+            .write_all(&self.seconds.to_le_bytes())
+            .map_err(map_payload_io_error)?;
+        writer
+            .write_all(&self.nanoseconds.to_le_bytes())
+            .map_err(map_payload_io_error)
+    }
+
+    fn read_from(reader: &mut impl Read) -> Result<Self, PayloadError> {
+        let seconds = i64::from_le_bytes(read_array_from(reader)?);
+        Self::new(seconds, read_u32_from(reader)?)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct ExtendedAttribute {
+    name: Vec<u8>,
+    value: Vec<u8>,
+}
+
+impl std::fmt::Debug for ExtendedAttribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExtendedAttribute")
+            .field("name", &String::from_utf8_lossy(&self.name))
+            .field("value_len", &self.value.len())
+            .finish()
+    }
+}
+
+impl ExtendedAttribute {
+    pub fn new(name: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) -> Result<Self, PayloadError> {
+        let attribute = Self {
+            name: name.into(),
+            value: value.into(),
+        };
+        attribute.validate()?;
+        Ok(attribute)
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    fn validate(&self) -> Result<(), PayloadError> {
+        if self.name.is_empty() || self.name.contains(&0) {
+            return Err(PayloadError::InvalidExtendedAttributeName);
+        }
+        if self.name.len() > MAX_XATTR_NAME_BYTES {
+            return Err(PayloadError::ExtendedAttributeLimitExceeded {
+                limit: MAX_XATTR_NAME_BYTES,
+                actual: self.name.len(),
+            });
+        }
+        if self.value.len() > MAX_ENTRY_XATTR_BYTES {
+            return Err(PayloadError::ExtendedAttributeLimitExceeded {
+                limit: MAX_ENTRY_XATTR_BYTES,
+                actual: self.value.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.name.len().saturating_add(self.value.len())
+    }
+}
+
+/// File metadata recorded for one manifest entry. Every field is optional; an
+/// empty value records nothing and keeps the manifest at version 1.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EntryMetadata {
+    mode: Option<u32>,
+    mtime: Option<EntryTimestamp>,
+    atime: Option<EntryTimestamp>,
+    owner: Option<(u32, u32)>,
+    xattrs: Vec<ExtendedAttribute>,
+}
+
+impl EntryMetadata {
+    /// Records POSIX mode bits; anything above [`MODE_BITS`] is dropped.
+    #[must_use]
+    pub const fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode & MODE_BITS);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_mtime(mut self, mtime: EntryTimestamp) -> Self {
+        self.mtime = Some(mtime);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_atime(mut self, atime: EntryTimestamp) -> Self {
+        self.atime = Some(atime);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_owner(mut self, uid: u32, gid: u32) -> Self {
+        self.owner = Some((uid, gid));
+        self
+    }
+
+    pub fn with_xattrs(mut self, xattrs: Vec<ExtendedAttribute>) -> Result<Self, PayloadError> {
+        self.xattrs = xattrs;
+        self.validate()?;
+        Ok(self)
+    }
+
+    #[must_use]
+    pub const fn mode(&self) -> Option<u32> {
+        self.mode
+    }
+
+    #[must_use]
+    pub const fn mtime(&self) -> Option<EntryTimestamp> {
+        self.mtime
+    }
+
+    #[must_use]
+    pub const fn atime(&self) -> Option<EntryTimestamp> {
+        self.atime
+    }
+
+    /// `(uid, gid)` of the packed file, when it was recorded.
+    #[must_use]
+    pub const fn owner(&self) -> Option<(u32, u32)> {
+        self.owner
+    }
+
+    #[must_use]
+    pub fn xattrs(&self) -> &[ExtendedAttribute] {
+        &self.xattrs
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.flags() == 0
+    }
+
+    pub(super) fn validate(&self) -> Result<(), PayloadError> {
+        if self.xattrs.len() > usize::from(u16::MAX) {
+            return Err(PayloadError::ExtendedAttributeLimitExceeded {
+                limit: usize::from(u16::MAX),
+                actual: self.xattrs.len(),
+            });
+        }
+        let mut total = 0usize;
+        for attribute in &self.xattrs {
+            attribute.validate()?;
+            total = total.saturating_add(attribute.encoded_len());
+        }
+        if total > MAX_ENTRY_XATTR_BYTES {
+            return Err(PayloadError::ExtendedAttributeLimitExceeded {
+                limit: MAX_ENTRY_XATTR_BYTES,
+                actual: total,
+            });
+        }
+        Ok(())
+    }
+
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.mode.is_some() {
+            flags |= FLAG_MODE;
+        }
+        if self.mtime.is_some() {
+            flags |= FLAG_MTIME;
+        }
+        if self.atime.is_some() {
+            flags |= FLAG_ATIME;
+        }
+        if self.owner.is_some() {
+            flags |= FLAG_OWNER;
+        }
+        if !self.xattrs.is_empty() {
+            flags |= FLAG_XATTRS;
+        }
+        flags
+    }
+
+    #[expect(
+        clippy::expect_used,
+        reason = "validate bounds the attribute count by u16::MAX, names by MAX_XATTR_NAME_BYTES (< u8::MAX + 1) and values by MAX_ENTRY_XATTR_BYTES (< u32::MAX)"
+    )]
+    pub(super) fn write_to(&self, writer: &mut impl Write) -> Result<(), PayloadError> {
+        writer
+            .write_all(&[self.flags()])
+            .map_err(map_payload_io_error)?;
+        if let Some(mode) = self.mode {
+            writer
+                .write_all(&mode.to_le_bytes())
+                .map_err(map_payload_io_error)?;
+        }
+        if let Some(mtime) = self.mtime {
+            mtime.write_to(writer)?;
+        }
+        if let Some(atime) = self.atime {
+            atime.write_to(writer)?;
+        }
+        if let Some((uid, gid)) = self.owner {
+            writer
+                .write_all(&uid.to_le_bytes())
+                .map_err(map_payload_io_error)?;
+            writer
+                .write_all(&gid.to_le_bytes())
+                .map_err(map_payload_io_error)?;
+        }
+        if self.xattrs.is_empty() {
+            return Ok(());
+        }
+
+        writer
+            .write_all(
+                &u16::try_from(self.xattrs.len())
+                    .expect("attribute count is bounded")
+                    .to_le_bytes(),
+            )
+            .map_err(map_payload_io_error)?;
+        for attribute in &self.xattrs {
+            writer
+                .write_all(&[u8::try_from(attribute.name.len()).expect("name length is bounded")])
+                .map_err(map_payload_io_error)?;
+            writer
+                .write_all(
+                    &u32::try_from(attribute.value.len())
+                        .expect("value length is bounded")
+                        .to_le_bytes(),
+                )
+                .map_err(map_payload_io_error)?;
+            writer
+                .write_all(&attribute.name)
+                .map_err(map_payload_io_error)?;
+            writer
+                .write_all(&attribute.value)
+                .map_err(map_payload_io_error)?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn read_from(reader: &mut impl Read) -> Result<Self, PayloadError> {
+        let flags = read_u8_from(reader)?;
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(PayloadError::InvalidMetadataFlags(flags));
+        }
+
+        let mut metadata = Self::default();
+        if flags & FLAG_MODE != 0 {
+            let mode = read_u32_from(reader)?;
+            if mode & !MODE_BITS != 0 {
+                return Err(PayloadError::InvalidFileMode(mode));
+            }
+            metadata.mode = Some(mode);
+        }
+        if flags & FLAG_MTIME != 0 {
+            metadata.mtime = Some(EntryTimestamp::read_from(reader)?);
+        }
+        if flags & FLAG_ATIME != 0 {
+            metadata.atime = Some(EntryTimestamp::read_from(reader)?);
+        }
+        if flags & FLAG_OWNER != 0 {
+            metadata.owner = Some((read_u32_from(reader)?, read_u32_from(reader)?));
+        }
+        if flags & FLAG_XATTRS != 0 {
+            let count = read_u16_from(reader)?;
+            // Lengths are checked against the per-entry budget before each
+            // allocation, so a hostile manifest cannot force large reads.
+            let mut budget = MAX_ENTRY_XATTR_BYTES;
+            for _ in 0..count {
+                let name_len = usize::from(read_u8_from(reader)?);
+                let value_len = usize::try_from(read_u32_from(reader)?).unwrap_or(usize::MAX);
+                let len = name_len.saturating_add(value_len);
+                budget = budget.checked_sub(len).ok_or(
+                    PayloadError::ExtendedAttributeLimitExceeded {
+                        limit: MAX_ENTRY_XATTR_BYTES,
+                        actual: len,
+                    },
+                )?;
+                let attribute = ExtendedAttribute {
+                    name: read_vec_from(reader, name_len)?,
+                    value: read_vec_from(reader, value_len)?,
+                };
+                attribute.validate()?;
+                metadata.xattrs.push(attribute);
+            }
+            if metadata.xattrs.is_empty() {
+                return Err(PayloadError::InvalidMetadataFlags(flags));
+            }
+        }
+        Ok(metadata)
+    }
+}
@@ -0,0 +1,415 @@
+//! X25519 public-key recipients for V1 keyslots.
+//!
+//! A recipient keyslot wraps the master key to a long-term X25519 public key
+//! instead of a password. Each wrap generates a fresh ephemeral X25519 secret,
+//! performs ECDH against the recipient key, and expands the shared secret with
+//! HKDF-SHA256 into a 32-byte wrapping key plus a 24-byte keyslot nonce. The
+//! result is fed into [`crate::cipher::wrap_v1_master_key`] with the ordinary
+//! slot-wrapping AAD, so recipient slots are bound to the header exactly like
+//! Argon2id slots are.
+//!
+//! Only the ephemeral public key is stored in the keyslot. Because the
+//! ephemeral secret is fresh for every wrap, the derived `(key, nonce)` pair is
+//! never reused.
+
+use std::fmt::{Display, Formatter};
+
+use hkdf::Hkdf;
+use rand::Rng;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, SharedSecret, StaticSecret};
+use zeroize::Zeroize;
+
+use crate::cipher::{CipherError, unwrap_v1_master_key, wrap_v1_master_key};
+use crate::header::common::KeyslotNonce;
+use crate::header::v1::EncryptedMasterKey;
+use crate::kdf::DERIVED_KEY_LEN;
+use crate::primitives::{KEYSLOT_NONCE_LEN, MasterKey, WrappingKey};
+use crate::protected::Protected;
+
+pub const RECIPIENT_KEY_LEN: usize = 32;
+
+/// Text prefix of an encoded recipient public key, e.g. `dexios-pk-<64 hex chars>`.
+pub const RECIPIENT_PUBLIC_KEY_PREFIX: &str = "dexios-pk-";
+/// Text prefix of an encoded recipient identity (secret key).
+pub const RECIPIENT_IDENTITY_PREFIX: &str = "DEXIOS-SK-";
+
+// Domain-separates recipient wrapping keys from every other HKDF use.
+const HKDF_INFO: &[u8] = b"dexios v1 x25519 recipient keyslot";
+const HKDF_OUTPUT_LEN: usize = DERIVED_KEY_LEN + KEYSLOT_NONCE_LEN;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientError {
+    InvalidPublicKey,
+    InvalidIdentity,
+    NonContributory,
+    KeyDerivation,
+    Cipher,
+    Authentication,
+}
+
+impl Display for RecipientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPublicKey => f.write_str("invalid recipient public key"),
+            Self::InvalidIdentity => f.write_str("invalid recipient identity"),
+            Self::NonContributory => {
+                f.write_str("recipient key agreement produced a non-contributory shared secret")
+            }
+            Self::KeyDerivation => f.write_str("unable to derive recipient wrapping key"),
+            Self::Cipher => f.write_str("unable to wrap master key for recipient"),
+            Self::Authentication => f.write_str("recipient keyslot authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for RecipientError {}
+
+/// A recipient's long-term X25519 public key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecipientPublicKey([u8; RECIPIENT_KEY_LEN]);
+
+impl RecipientPublicKey {
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; RECIPIENT_KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; RECIPIENT_KEY_LEN] {
+        &self.0
+    }
+
+    /// Parses the `dexios-pk-<hex>` text form printed by `dexios keygen`.
+    ///
+    /// Low-order points (including the all-zero encoding) are rejected here, so
+    /// a key that could never produce a contributory shared secret fails as an
+    /// invalid public key instead of later during the wrap.
+    pub fn parse(encoded: &str) -> Result<Self, RecipientError> {
+        let hex = encoded
+            .trim()
+            .strip_prefix(RECIPIENT_PUBLIC_KEY_PREFIX)
+            .ok_or(RecipientError::InvalidPublicKey)?;
+        let mut bytes = [0u8; RECIPIENT_KEY_LEN];
+        decode_hex_into(hex.as_bytes(), &mut bytes).ok_or(RecipientError::InvalidPublicKey)?;
+        if !is_contributory_point(&bytes) {
+            return Err(RecipientError::InvalidPublicKey);
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl Display for RecipientPublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(RECIPIENT_PUBLIC_KEY_PREFIX)?;
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A recipient's X25519 secret key.
+///
+/// The secret is held in [`Protected`] and zeroized on drop.
+pub struct RecipientIdentity(Protected<[u8; RECIPIENT_KEY_LEN]>);
+
+impl RecipientIdentity {
+    /// Generates a fresh identity from the OS CSPRNG.
+    #[must_use]
+    pub fn generate() -> Self {
+        let mut secret = [0u8; RECIPIENT_KEY_LEN];
+        rand::rng().fill_bytes(&mut secret);
+        let identity = Self(Protected::new(secret));
+        secret.zeroize();
+        identity
+    }
+
+    /// Parses an identity file: the first non-empty line that is not a `#`
+    /// comment must hold the `DEXIOS-SK-<hex>` text form.
+    pub fn parse(contents: &[u8]) -> Result<Self, RecipientError> {
+        let contents =
+            std::str::from_utf8(contents).map_err(|_| RecipientError::InvalidIdentity)?;
+        let line = contents
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .ok_or(RecipientError::InvalidIdentity)?;
+        let hex = line
+            .strip_prefix(RECIPIENT_IDENTITY_PREFIX)
+            .ok_or(RecipientError::InvalidIdentity)?;
+        let mut secret = [0u8; RECIPIENT_KEY_LEN];
+        if decode_hex_into(hex.as_bytes(), &mut secret).is_none() {
+            secret.zeroize();
+            return Err(RecipientError::InvalidIdentity);
+        }
+        let identity = Self(Protected::new(secret));
+        secret.zeroize();
+        Ok(identity)
+    }
+
+    #[must_use]
+    pub fn public_key(&self) -> RecipientPublicKey {
+        self.0.with_exposed(|secret| {
+            let secret = StaticSecret::from(*secret);
+            RecipientPublicKey(PublicKey::from(&secret).to_bytes())
+        })
+    }
+
+    /// Encodes the identity file contents, including a comment line with the
+    /// matching public key.
+    #[must_use]
+    pub fn to_identity_file(&self) -> Protected<Vec<u8>> {
+        let mut contents = format!("# public key: {}\n", self.public_key()).into_bytes();
+        contents.extend_from_slice(RECIPIENT_IDENTITY_PREFIX.as_bytes());
+        self.0.with_exposed(|secret| {
+            for byte in secret {
+                contents.extend_from_slice(&hex_pair(*byte));
+            }
+        });
+        contents.push(b'\n');
+        Protected::new(contents)
+    }
+
+    /// Unwraps a master key from a recipient keyslot.
+    ///
+    /// `aad` must be the slot-wrapping AAD of the keyslot that stored
+    /// `ephemeral_public_key` and `encrypted_master_key`.
+    pub fn unwrap_master_key(
+        &self,
+        ephemeral_public_key: &RecipientPublicKey,
+        encrypted_master_key: &EncryptedMasterKey,
+        aad: &[u8],
+    ) -> Result<MasterKey, RecipientError> {
+        let shared = self.0.with_exposed(|secret| {
+            StaticSecret::from(*secret).diffie_hellman(&PublicKey::from(ephemeral_public_key.0))
+        });
+        let (wrapping_key, nonce) =
+            derive_wrap_material(&shared, ephemeral_public_key, &self.public_key())?;
+        unwrap_v1_master_key(wrapping_key, encrypted_master_key, &nonce, aad).map_err(|error| {
+            match error {
+                CipherError::Authentication => RecipientError::Authentication,
+                _ => RecipientError::Cipher,
+            }
+        })
+    }
+}
+
+impl std::fmt::Debug for RecipientIdentity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RecipientIdentity")
+            .field(&self.public_key())
+            .finish()
+    }
+}
+
+/// Key material for wrapping one master key to one recipient.
+///
+/// Build the recipient keyslot from [`Self::ephemeral_public_key`] first, then
+/// compute its slot-wrapping AAD and call [`Self::wrap_master_key`]. The value
+/// is consumed by the wrap so its derived nonce cannot be reused.
+pub struct RecipientWrap {
+    ephemeral_public_key: RecipientPublicKey,
+    wrapping_key: WrappingKey,
+    nonce: KeyslotNonce,
+}
+
+impl RecipientWrap {
+    pub fn new(recipient: &RecipientPublicKey) -> Result<Self, RecipientError> {
+        let mut ephemeral = [0u8; RECIPIENT_KEY_LEN];
+        rand::rng().fill_bytes(&mut ephemeral);
+        let ephemeral_secret = StaticSecret::from(ephemeral);
+        ephemeral.zeroize();
+        let ephemeral_public_key =
+            RecipientPublicKey(PublicKey::from(&ephemeral_secret).to_bytes());
+        let shared = ephemeral_secret.diffie_hellman(&PublicKey::from(recipient.0));
+        let (wrapping_key, nonce) =
+            derive_wrap_material(&shared, &ephemeral_public_key, recipient)?;
+
+        Ok(Self {
+            ephemeral_public_key,
+            wrapping_key,
+            nonce,
+        })
+    }
+
+    #[must_use]
+    pub const fn ephemeral_public_key(&self) -> &RecipientPublicKey {
+        &self.ephemeral_public_key
+    }
+
+    pub fn wrap_master_key(
+        self,
+        master_key: &MasterKey,
+        aad: &[u8],
+    ) -> Result<EncryptedMasterKey, RecipientError> {
+        wrap_v1_master_key(self.wrapping_key, master_key, &self.nonce, aad)
+            .map_err(|_| RecipientError::Cipher)
+    }
+}
+
+// Sender and recipient both salt HKDF with `ephemeral_pk || recipient_pk`, so the
+// derived material is tied to this exact keyslot and this exact recipient.
+fn derive_wrap_material(
+    shared: &SharedSecret,
+    ephemeral_public_key: &RecipientPublicKey,
+    recipient_public_key: &RecipientPublicKey,
+) -> Result<(WrappingKey, KeyslotNonce), RecipientError> {
+    if !shared.was_contributory() {
+        return Err(RecipientError::NonContributory);
+    }
+
+    let mut salt = [0u8; RECIPIENT_KEY_LEN * 2];
+    let (ephemeral_half, recipient_half) = salt.split_at_mut(RECIPIENT_KEY_LEN);
+    ephemeral_half.copy_from_slice(ephemeral_public_key.as_bytes());
+    recipient_half.copy_from_slice(recipient_public_key.as_bytes());
+
+    let hkdf = Hkdf::<Sha256>::new(Some(&salt), shared.as_bytes());
+    let mut okm = [0u8; HKDF_OUTPUT_LEN];
+    if hkdf.expand(HKDF_INFO, &mut okm).is_err() {
+        okm.zeroize();
+        return Err(RecipientError::KeyDerivation);
+    }
+
+    let (key_bytes, nonce_bytes) = okm.split_at(DERIVED_KEY_LEN);
+    let mut key = [0u8; DERIVED_KEY_LEN];
+    key.copy_from_slice(key_bytes);
+    let mut nonce = [0u8; KEYSLOT_NONCE_LEN];
+    nonce.copy_from_slice(nonce_bytes);
+    okm.zeroize();
+
+    let wrapping_key = WrappingKey::new(key);
+    key.zeroize();
+    Ok((wrapping_key, KeyslotNonce::new(nonce)))
+}
+
+fn hex_pair(byte: u8) -> [u8; 2] {
+    [hex_digit(byte >> 4), hex_digit(byte & 0x0f)]
+}
+
+fn hex_digit(nibble: u8) -> u8 {
+    char::from_digit(u32::from(nibble), 16)
+        .and_then(|digit| u8::try_from(digit).ok())
+        .unwrap_or(b'0')
+}
+
+fn decode_hex_into(hex: &[u8], out: &mut [u8]) -> Option<()> {
+    if hex.len() != out.len().checked_mul(2)? {
+        return None;
+    }
+    for (byte, pair) in out.iter_mut().zip(hex.chunks_exact(2)) {
+        let [high, low] = pair else {
+            return None;
+        };
+        *byte = (hex_value(*high)? << 4) | hex_value(*low)?;
+    }
+    Some(())
+}
+
+// Clamped X25519 scalars are multiples of the cofactor, so a check DH with any
+// fixed scalar maps every low-order point, and only those, to the all-zero output.
+fn is_contributory_point(bytes: &[u8; RECIPIENT_KEY_LEN]) -> bool {
+    StaticSecret::from([0x55; RECIPIENT_KEY_LEN])
+        .diffie_hellman(&PublicKey::from(*bytes))
+        .was_contributory()
+}
+
+fn hex_value(digit: u8) -> Option<u8> {
+    char::from(digit)
+        .to_digit(16)
+        .and_then(|value| u8::try_from(value).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_key_text_form_round_trips() {
+        let public_key = RecipientIdentity::generate().public_key();
+        let encoded = public_key.to_string();
+
+        assert!(encoded.starts_with(RECIPIENT_PUBLIC_KEY_PREFIX));
+        assert_eq!(RecipientPublicKey::parse(&encoded).unwrap(), public_key);
+    }
+
+    #[test]
+    fn identity_file_round_trips_and_keeps_public_key() {
+        let identity = RecipientIdentity::generate();
+        let file = identity.to_identity_file();
+
+        let parsed = file
+            .with_exposed(|file| RecipientIdentity::parse(file))
+            .unwrap();
+
+        assert_eq!(parsed.public_key(), identity.public_key());
+    }
+
+    #[test]
+    fn malformed_text_forms_are_rejected() {
+        assert_eq!(
+            RecipientPublicKey::parse("dexios-pk-zz"),
+            Err(RecipientError::InvalidPublicKey)
+        );
+        assert!(RecipientIdentity::parse(b"# only a comment\n").is_err());
+        assert!(RecipientIdentity::parse(b"DEXIOS-SK-0011").is_err());
+    }
+
+    #[test]
+    fn low_order_public_keys_are_rejected_at_parse_time() {
+        let mut one = [0u8; RECIPIENT_KEY_LEN];
+        one[0] = 1;
+        // p - 1, the order-2 point, in little-endian form.
+        let mut p_minus_one = [0xffu8; RECIPIENT_KEY_LEN];
+        p_minus_one[0] = 0xec;
+        p_minus_one[31] = 0x7f;
+
+        for bytes in [[0u8; RECIPIENT_KEY_LEN], one, p_minus_one] {
+            let encoded = RecipientPublicKey::from_bytes(bytes).to_string();
+            assert_eq!(
+                RecipientPublicKey::parse(&encoded),
+                Err(RecipientError::InvalidPublicKey),
+                "{encoded} must be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn wrap_is_only_unwrapped_by_the_matching_identity() {
+        let identity = RecipientIdentity::generate();
+        let master_key = MasterKey::new([7u8; 32]);
+        let wrap = RecipientWrap::new(&identity.public_key()).unwrap();
+        let ephemeral = *wrap.ephemeral_public_key();
+        let encrypted = wrap.wrap_master_key(&master_key, b"slot aad").unwrap();
+
+        let unwrapped = identity
+            .unwrap_master_key(&ephemeral, &encrypted, b"slot aad")
+            .unwrap();
+        assert!(unwrapped.same_secret_as(&master_key));
+
+        let other = RecipientIdentity::generate();
+        assert!(matches!(
+            other.unwrap_master_key(&ephemeral, &encrypted, b"slot aad"),
+            Err(RecipientError::Authentication)
+        ));
+        assert!(matches!(
+            identity.unwrap_master_key(&ephemeral, &encrypted, b"other aad"),
+            Err(RecipientError::Authentication)
+        ));
+    }
+
+    #[test]
+    fn low_order_ephemeral_key_is_rejected() {
+        let identity = RecipientIdentity::generate();
+        let encrypted = EncryptedMasterKey::new([0u8; 48]);
+
+        assert!(matches!(
+            identity.unwrap_master_key(
+                &RecipientPublicKey::from_bytes([0u8; 32]),
+                &encrypted,
+                b"slot aad"
+            ),
+            Err(RecipientError::NonContributory)
+        ));
+    }
+}
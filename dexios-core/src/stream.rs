@@ -10,7 +10,7 @@ use std::io::{self, ErrorKind, Read, Write};
 
 use aead::{
     KeyInit, Payload,
-    stream::{DecryptorLE31, EncryptorLE31},
+    stream::{DecryptorLE31, EncryptorLE31, NewStream, StreamLE31},
 };
 use chacha20poly1305::XChaCha20Poly1305;
 use zeroize::Zeroize;
@@ -20,6 +20,15 @@ use crate::header::common::{PayloadNonce, V1HeaderAad};
 use crate::header::v1::V1Header;
 use crate::primitives::{BLOCK_SIZE, MasterKey};
 
+#[cfg(feature = "parallel")]
+mod parallel;
+mod random_access;
+
+pub use random_access::V1PayloadRandomAccessReader;
+
+// Poly1305 tag appended to every sealed block.
+const TAG_LEN: usize = 16;
+
 #[derive(Debug)]
 pub enum StreamError {
     InvalidNonceLength(usize),
@@ -83,6 +92,60 @@ fn read_up_to_full(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, S
     Ok(filled)
 }
 
+// Withholds the last `trailer_len` bytes of `reader`, so a payload followed by
+// a header trailer reads as if it ended at its final block.
+struct HeaderTrailerReader<R: Read> {
+    reader: R,
+    trailer_len: usize,
+    held: Vec<u8>,
+}
+
+impl<R: Read> HeaderTrailerReader<R> {
+    const fn new(reader: R, trailer_len: usize) -> Self {
+        Self {
+            reader,
+            trailer_len,
+            held: Vec::new(),
+        }
+    }
+}
+
+impl<R: Read> Read for HeaderTrailerReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.trailer_len == 0 {
+            return self.reader.read(buf);
+        }
+
+        let wanted = self.trailer_len.saturating_add(buf.len());
+        while self.held.len() < wanted {
+            let filled = self.held.len();
+            self.held.resize(wanted, 0);
+            let result = self
+                .reader
+                .read(self.held.get_mut(filled..).unwrap_or_default());
+            let read_count = result.as_ref().map_or(0, |read_count| *read_count);
+            self.held.truncate(filled.saturating_add(read_count));
+            match result {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        let take = self
+            .held
+            .len()
+            .saturating_sub(self.trailer_len)
+            .min(buf.len());
+        if let (Some(out), Some(released)) = (buf.get_mut(..take), self.held.get(..take)) {
+            out.copy_from_slice(released);
+        }
+        self.held.drain(..take);
+        Ok(take)
+    }
+}
+
 pub struct V1PayloadStream;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -113,11 +176,57 @@ impl V1PayloadStream {
     ) -> Result<V1FinalAuth, StreamError> {
         V1PayloadDecryptor::new(master_key, payload)?.decrypt_file_uncommitted(reader, writer)
     }
+
+    /// Like [`Self::encrypt_file`], but seals up to `threads` blocks at a time on
+    /// scoped worker threads. The ciphertext is identical to the sequential
+    /// output.
+    #[cfg(feature = "parallel")]
+    pub fn encrypt_file_parallel(
+        master_key: MasterKey,
+        header: &V1Header,
+        reader: &mut impl Read,
+        writer: &mut impl Write,
+        threads: std::num::NonZeroUsize,
+    ) -> Result<(), StreamError> {
+        #[cfg(feature = "visual")]
+        let pb = crate::visual::create_spinner();
+
+        parallel::encrypt_file(master_key, header, reader, writer, threads)?;
+
+        #[cfg(feature = "visual")]
+        pb.finish_and_clear();
+
+        Ok(())
+    }
+
+    /// Like [`Self::decrypt_file_uncommitted`], but opens up to `threads` blocks
+    /// at a time on scoped worker threads. Plaintext reaches `writer` in order,
+    /// and only the blocks before the first failure are written.
+    #[cfg(feature = "parallel")]
+    pub fn decrypt_file_uncommitted_parallel(
+        master_key: MasterKey,
+        payload: &ParsedV1Payload,
+        reader: &mut impl Read,
+        writer: &mut impl Write,
+        threads: std::num::NonZeroUsize,
+    ) -> Result<V1FinalAuth, StreamError> {
+        #[cfg(feature = "visual")]
+        let pb = crate::visual::create_spinner();
+
+        let final_auth =
+            parallel::decrypt_file_uncommitted(master_key, payload, reader, writer, threads)?;
+
+        #[cfg(feature = "visual")]
+        pb.finish_and_clear();
+
+        Ok(final_auth)
+    }
 }
 
 pub struct V1PayloadEncryptor {
     stream: EncryptionStreams,
     aad: V1HeaderAad,
+    trailer: Vec<u8>,
 }
 
 impl V1PayloadEncryptor {
@@ -125,6 +234,7 @@ impl V1PayloadEncryptor {
         Ok(Self {
             stream: EncryptionStreams::initialize(master_key, header.payload_nonce())?,
             aad: header.aad(),
+            trailer: header_trailer_bytes(header)?,
         })
     }
 
@@ -162,6 +272,7 @@ impl V1PayloadEncryptor {
         #[cfg(feature = "visual")]
         let pb = crate::visual::create_spinner();
 
+        let trailer = std::mem::take(&mut self.trailer);
         let mut read_buffer = vec![0u8; BLOCK_SIZE].into_boxed_slice();
         loop {
             let read_count = match read_up_to_full(reader, &mut read_buffer) {
@@ -202,6 +313,7 @@ impl V1PayloadEncryptor {
         }
 
         read_buffer.zeroize();
+        writer.write_all(&trailer).map_err(StreamError::Write)?;
         writer.flush().map_err(StreamError::Flush)?;
 
         #[cfg(feature = "visual")]
@@ -310,10 +422,11 @@ impl<W: Write> V1PayloadEncryptingWriter<W> {
             return Ok(());
         }
 
-        let encryptor = self
+        let mut encryptor = self
             .encryptor
             .take()
             .expect("unfinished writer has encryptor");
+        let trailer = std::mem::take(&mut encryptor.trailer);
         let encrypted = match encryptor.encrypt_last(&self.buffer[..self.buffered]) {
             Ok(encrypted) => encrypted,
             Err(error) => {
@@ -326,6 +439,7 @@ impl<W: Write> V1PayloadEncryptingWriter<W> {
         self.buffered = 0;
         let writer = self.writer.as_mut().expect("unfinished writer is present");
         writer.write_all(&encrypted).map_err(StreamError::Write)?;
+        writer.write_all(&trailer).map_err(StreamError::Write)?;
         writer.flush().map_err(StreamError::Flush)?;
         self.finished = true;
         Ok(())
@@ -356,7 +470,7 @@ impl<W: Write> Drop for V1PayloadEncryptingWriter<W> {
 /// Exposes uncommitted plaintext chunks before final authentication completes.
 pub struct V1PayloadDecryptingReader<R: Read> {
     decryptor: Option<V1PayloadDecryptor>,
-    reader: R,
+    reader: HeaderTrailerReader<R>,
     ciphertext_buffer: Box<[u8]>,
     plaintext_buffer: Vec<u8>,
     plaintext_offset: usize,
@@ -371,7 +485,7 @@ impl<R: Read> V1PayloadDecryptingReader<R> {
     ) -> Result<Self, StreamError> {
         Ok(Self {
             decryptor: Some(V1PayloadDecryptor::new(master_key, payload)?),
-            reader,
+            reader: HeaderTrailerReader::new(reader, payload.header().trailer_len()),
             ciphertext_buffer: vec![0u8; BLOCK_SIZE + 16].into_boxed_slice(),
             plaintext_buffer: Vec::new(),
             plaintext_offset: 0,
@@ -464,6 +578,35 @@ impl<R: Read> Drop for V1PayloadDecryptingReader<R> {
     }
 }
 
+// The position-addressable LE31 primitive behind the sequential streams, for
+// callers that seal or open blocks out of order.
+fn le31_stream(
+    key: MasterKey,
+    nonce: &PayloadNonce,
+) -> Result<StreamLE31<XChaCha20Poly1305>, StreamError> {
+    if nonce.as_bytes().len() != crate::primitives::PAYLOAD_NONCE_LEN {
+        return Err(StreamError::InvalidNonceLength(nonce.as_bytes().len()));
+    }
+
+    let cipher = key.with_exposed(|key| {
+        XChaCha20Poly1305::new_from_slice(key).map_err(|_| StreamError::CipherInit)
+    })?;
+    Ok(StreamLE31::from_aead(
+        cipher,
+        nonce.as_bytes().as_ref().into(),
+    ))
+}
+
+// The trailer the encryptors append after the final block, or nothing.
+fn header_trailer_bytes(header: &V1Header) -> Result<Vec<u8>, StreamError> {
+    if !header.has_header_trailer() {
+        return Ok(Vec::new());
+    }
+    header
+        .serialize_trailer()
+        .map_err(|error| StreamError::Write(io::Error::other(error)))
+}
+
 fn stream_error_to_io(error: StreamError) -> io::Error {
     match error {
         StreamError::Write(error) | StreamError::Flush(error) => error,
@@ -474,6 +617,7 @@ fn stream_error_to_io(error: StreamError) -> io::Error {
 pub struct V1PayloadDecryptor {
     stream: DecryptionStreams,
     aad: V1HeaderAad,
+    trailer_len: usize,
 }
 
 impl V1PayloadDecryptor {
@@ -481,6 +625,7 @@ impl V1PayloadDecryptor {
         Ok(Self {
             stream: DecryptionStreams::initialize(master_key, payload.payload_nonce())?,
             aad: *payload.aad(),
+            trailer_len: payload.header().trailer_len(),
         })
     }
 
@@ -524,9 +669,10 @@ impl V1PayloadDecryptor {
         #[cfg(feature = "visual")]
         let pb = crate::visual::create_spinner();
 
+        let mut reader = HeaderTrailerReader::new(reader, self.trailer_len);
         let mut buffer = vec![0u8; BLOCK_SIZE + 16].into_boxed_slice();
         loop {
-            let read_count = match read_up_to_full(reader, &mut buffer) {
+            let read_count = match read_up_to_full(&mut reader, &mut buffer) {
                 Ok(read_count) => read_count,
                 Err(error) => {
                     buffer.zeroize();
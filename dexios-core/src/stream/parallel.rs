@@ -0,0 +1,227 @@
+//! Multi-threaded V1 payload sealing and opening.
+//!
+//! LE31 stream nonces are derived from the block position, so once a block's
+//! position is known it can be sealed or opened independently of its
+//! neighbours. Blocks are read in batches, processed on scoped worker threads,
+//! and written back in position order, so the output is byte-for-byte identical
+//! to the sequential [`super::V1PayloadStream`] helpers.
+
+use std::io::{Read, Write};
+use std::num::NonZeroUsize;
+use std::thread;
+
+use aead::stream::StreamPrimitive;
+use zeroize::Zeroizing;
+
+use super::{
+    HeaderTrailerReader, StreamError, TAG_LEN, V1FinalAuth, header_trailer_bytes, le31_stream,
+    read_up_to_full,
+};
+use crate::header::ParsedV1Payload;
+use crate::header::v1::V1Header;
+use crate::primitives::{BLOCK_SIZE, MasterKey};
+
+// Each worker takes a few blocks per batch so thread start-up is amortised over
+// several MiB of work while the batch (and its memory) stays bounded.
+const BLOCKS_PER_WORKER: usize = 4;
+
+struct Block {
+    position: u32,
+    last: bool,
+    data: Zeroizing<Vec<u8>>,
+}
+
+// `Eof` means the reader ended exactly where another block was expected.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BatchEnd {
+    More,
+    Last,
+    Eof,
+}
+
+pub(super) fn encrypt_file(
+    master_key: MasterKey,
+    header: &V1Header,
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    threads: NonZeroUsize,
+) -> Result<(), StreamError> {
+    let stream = le31_stream(master_key, header.payload_nonce())?;
+    let aad = header.aad();
+    let trailer = header_trailer_bytes(header)?;
+    let mut next_position = 0;
+
+    loop {
+        let (mut batch, end) = read_batch(reader, BLOCK_SIZE, true, threads, &mut next_position)?;
+        if let Some((_, error)) = process_batch(&mut batch, threads, |block| {
+            stream
+                .encrypt_in_place(block.position, block.last, aad.as_bytes(), &mut *block.data)
+                .map_err(|_| StreamError::Authentication)
+        }) {
+            return Err(error);
+        }
+        for block in &batch {
+            writer.write_all(&block.data).map_err(StreamError::Write)?;
+        }
+        if end != BatchEnd::More {
+            break;
+        }
+    }
+
+    writer.write_all(&trailer).map_err(StreamError::Write)?;
+    writer.flush().map_err(StreamError::Flush)
+}
+
+pub(super) fn decrypt_file_uncommitted(
+    master_key: MasterKey,
+    payload: &ParsedV1Payload,
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    threads: NonZeroUsize,
+) -> Result<V1FinalAuth, StreamError> {
+    let stream = le31_stream(master_key, payload.payload_nonce())?;
+    let aad = *payload.aad();
+    let mut reader = HeaderTrailerReader::new(reader, payload.header().trailer_len());
+    let mut next_position = 0;
+
+    loop {
+        let (mut batch, end) = read_batch(
+            &mut reader,
+            BLOCK_SIZE + TAG_LEN,
+            false,
+            threads,
+            &mut next_position,
+        )?;
+        let failure = process_batch(&mut batch, threads, |block| {
+            if block.last {
+                if block.data.is_empty() {
+                    return Err(StreamError::MissingFinalBlock);
+                }
+                if block.data.len() < TAG_LEN {
+                    return Err(StreamError::TruncatedCiphertext);
+                }
+            }
+            stream
+                .decrypt_in_place(block.position, block.last, aad.as_bytes(), &mut *block.data)
+                .map_err(|_| {
+                    if block.last {
+                        StreamError::FinalBlockAuthentication
+                    } else {
+                        StreamError::Authentication
+                    }
+                })
+        });
+
+        // Blocks before the first failure authenticated and are written exactly as
+        // the sequential decryptor would have written them.
+        let authenticated = failure.as_ref().map_or(batch.len(), |(index, _)| *index);
+        for block in batch.iter().take(authenticated) {
+            writer.write_all(&block.data).map_err(StreamError::Write)?;
+        }
+        if let Some((_, error)) = failure {
+            return Err(error);
+        }
+        match end {
+            BatchEnd::More => {}
+            BatchEnd::Last => break,
+            BatchEnd::Eof => return Err(StreamError::MissingFinalBlock),
+        }
+    }
+
+    writer.flush().map_err(StreamError::Flush)?;
+    Ok(V1FinalAuth { _private: () })
+}
+
+// Reads up to `threads * BLOCKS_PER_WORKER` blocks of `block_len` bytes. A short
+// read marks the final block. A zero-byte read where a block was expected is an
+// empty final block when `empty_last` is set (encryption) and `Eof` otherwise.
+fn read_batch(
+    reader: &mut impl Read,
+    block_len: usize,
+    empty_last: bool,
+    threads: NonZeroUsize,
+    next_position: &mut u32,
+) -> Result<(Vec<Block>, BatchEnd), StreamError> {
+    let batch_len = threads.get().saturating_mul(BLOCKS_PER_WORKER);
+    let mut batch = Vec::with_capacity(batch_len);
+
+    while batch.len() < batch_len {
+        let mut data = Zeroizing::new(Vec::with_capacity(block_len.saturating_add(TAG_LEN)));
+        data.resize(block_len, 0);
+        let read_count = read_up_to_full(reader, &mut data)?;
+        data.truncate(read_count);
+
+        let position = *next_position;
+        *next_position = position.checked_add(1).ok_or(StreamError::Authentication)?;
+
+        if read_count == block_len {
+            batch.push(Block {
+                position,
+                last: false,
+                data,
+            });
+            continue;
+        }
+
+        if read_count == 0 && !empty_last {
+            return Ok((batch, BatchEnd::Eof));
+        }
+        batch.push(Block {
+            position,
+            last: true,
+            data,
+        });
+        return Ok((batch, BatchEnd::Last));
+    }
+
+    Ok((batch, BatchEnd::More))
+}
+
+// Runs `process` over every block, spreading contiguous runs of the batch across
+// up to `threads` scoped workers. Returns the lowest-positioned failure, if any;
+// each worker stops at its own first failure.
+fn process_batch<F>(
+    batch: &mut [Block],
+    threads: NonZeroUsize,
+    process: F,
+) -> Option<(usize, StreamError)>
+where
+    F: Fn(&mut Block) -> Result<(), StreamError> + Sync,
+{
+    let per_worker = batch.len().div_ceil(threads.get()).max(1);
+    if batch.len() <= 1 || threads.get() == 1 {
+        return process_run(batch, 0, &process);
+    }
+
+    thread::scope(|scope| {
+        let workers = batch
+            .chunks_mut(per_worker)
+            .enumerate()
+            .map(|(run, blocks)| {
+                let process = &process;
+                let offset = run.saturating_mul(per_worker);
+                scope.spawn(move || process_run(blocks, offset, process))
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .filter_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .min_by_key(|(index, _)| *index)
+    })
+}
+
+fn process_run<F>(blocks: &mut [Block], offset: usize, process: &F) -> Option<(usize, StreamError)>
+where
+    F: Fn(&mut Block) -> Result<(), StreamError>,
+{
+    blocks.iter_mut().enumerate().find_map(|(index, block)| {
+        process(block)
+            .err()
+            .map(|error| (offset.saturating_add(index), error))
+    })
+}
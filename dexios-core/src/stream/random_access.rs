@@ -0,0 +1,196 @@
+//! Random-access reads from a V1 payload.
+//!
+//! LE31 stream nonces are derived from the block position, so any single block
+//! can be opened without touching its neighbours. The reader decrypts only the
+//! blocks a read lands in, which means truncation or tampering elsewhere in the
+//! payload goes unnoticed unless the caller also checks the final block with
+//! [`V1PayloadRandomAccessReader::verify_final_block`].
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use aead::stream::{StreamLE31, StreamPrimitive};
+use chacha20poly1305::XChaCha20Poly1305;
+use zeroize::Zeroizing;
+
+use super::{StreamError, TAG_LEN, le31_stream, read_up_to_full, stream_error_to_io};
+use crate::header::ParsedV1Payload;
+use crate::header::common::V1HeaderAad;
+use crate::primitives::{BLOCK_SIZE, MasterKey};
+
+const PLAINTEXT_BLOCK_LEN: u64 = BLOCK_SIZE as u64;
+const CIPHERTEXT_BLOCK_LEN: u64 = (BLOCK_SIZE + TAG_LEN) as u64;
+
+/// Plaintext view of a V1 payload that authenticates each block it returns.
+///
+/// Bytes returned by a read have passed authentication for their own block
+/// only; they are not a commitment that the rest of the payload is intact.
+pub struct V1PayloadRandomAccessReader<R: Read + Seek> {
+    stream: StreamLE31<XChaCha20Poly1305>,
+    aad: V1HeaderAad,
+    reader: R,
+    payload_offset: u64,
+    final_index: u32,
+    final_len: usize,
+    plaintext_len: u64,
+    position: u64,
+    cached: Option<u32>,
+    block: Zeroizing<Vec<u8>>,
+}
+
+impl<R: Read + Seek> V1PayloadRandomAccessReader<R> {
+    /// Wraps `reader`, whose V1 payload ciphertext starts at `payload_offset`
+    /// and runs to the end of the stream, or to the header trailer when the
+    /// header declares one.
+    ///
+    /// The payload length is checked against the block layout up front, so a
+    /// payload cut at a block boundary or inside a tag is rejected here.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        reason = "tail >= TAG_LEN is checked before `tail - TAG_LEN`; full_blocks * PLAINTEXT_BLOCK_LEN is below the ciphertext length, which fits in u64"
+    )]
+    pub fn new(
+        master_key: MasterKey,
+        payload: &ParsedV1Payload,
+        mut reader: R,
+        payload_offset: u64,
+    ) -> Result<Self, StreamError> {
+        let stream = le31_stream(master_key, payload.payload_nonce())?;
+        let end = reader.seek(SeekFrom::End(0)).map_err(StreamError::Read)?;
+        let trailer_len = payload.header().trailer_len() as u64;
+        let ciphertext_len = end
+            .checked_sub(trailer_len)
+            .and_then(|end| end.checked_sub(payload_offset))
+            .ok_or(StreamError::MissingFinalBlock)?;
+
+        // The final block is always shorter than a full ciphertext block, so a
+        // payload ending on a block boundary has lost its final block.
+        let full_blocks = ciphertext_len / CIPHERTEXT_BLOCK_LEN;
+        let tail = ciphertext_len % CIPHERTEXT_BLOCK_LEN;
+        if tail == 0 {
+            return Err(StreamError::MissingFinalBlock);
+        }
+        if tail < TAG_LEN as u64 {
+            return Err(StreamError::TruncatedCiphertext);
+        }
+        let final_index = u32::try_from(full_blocks).map_err(|_| StreamError::Authentication)?;
+        let final_len = usize::try_from(tail).map_err(|_| StreamError::TruncatedCiphertext)?;
+
+        Ok(Self {
+            stream,
+            aad: *payload.aad(),
+            reader,
+            payload_offset,
+            final_index,
+            final_len,
+            plaintext_len: full_blocks * PLAINTEXT_BLOCK_LEN + (tail - TAG_LEN as u64),
+            position: 0,
+            cached: None,
+            block: Zeroizing::new(Vec::with_capacity(BLOCK_SIZE + TAG_LEN)),
+        })
+    }
+
+    /// Plaintext length implied by the ciphertext length.
+    pub const fn plaintext_len(&self) -> u64 {
+        self.plaintext_len
+    }
+
+    /// Authenticates the final block, proving the payload was not truncated
+    /// or extended past it.
+    pub fn verify_final_block(&mut self) -> Result<(), StreamError> {
+        self.load_block(self.final_index)
+    }
+
+    /// Reads plaintext at the current position, authenticating the block it
+    /// comes from. Returns 0 at or past the end of the plaintext.
+    #[expect(
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::cast_possible_truncation,
+        reason = "position < plaintext_len bounds the block index to final_index; offset < BLOCK_SIZE fits in usize and offset < block.len() because only the final block is short and position is below its end"
+    )]
+    pub fn read_authenticated(&mut self, buf: &mut [u8]) -> Result<usize, StreamError> {
+        if buf.is_empty() || self.position >= self.plaintext_len {
+            return Ok(0);
+        }
+
+        let index = (self.position / PLAINTEXT_BLOCK_LEN) as u32;
+        let offset = (self.position % PLAINTEXT_BLOCK_LEN) as usize;
+        self.load_block(index)?;
+
+        let take = (self.block.len() - offset).min(buf.len());
+        buf[..take].copy_from_slice(&self.block[offset..offset + take]);
+        self.position += take as u64;
+        Ok(take)
+    }
+
+    #[expect(
+        clippy::arithmetic_side_effects,
+        reason = "index <= final_index, so the block's ciphertext offset lies inside the payload measured in new"
+    )]
+    fn load_block(&mut self, index: u32) -> Result<(), StreamError> {
+        if self.cached == Some(index) {
+            return Ok(());
+        }
+        self.cached = None;
+
+        let last = index == self.final_index;
+        let offset = self.payload_offset + u64::from(index) * CIPHERTEXT_BLOCK_LEN;
+        self.reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(StreamError::Read)?;
+
+        // the final block is read to its measured length, short of any trailer
+        self.block.resize(
+            if last {
+                self.final_len
+            } else {
+                BLOCK_SIZE + TAG_LEN
+            },
+            0,
+        );
+        let read_count = read_up_to_full(&mut self.reader, &mut self.block)?;
+        self.block.truncate(read_count);
+        if !last && read_count != BLOCK_SIZE + TAG_LEN {
+            return Err(StreamError::TruncatedCiphertext);
+        }
+        if last && read_count < TAG_LEN {
+            return Err(StreamError::TruncatedCiphertext);
+        }
+
+        self.stream
+            .decrypt_in_place(index, last, self.aad.as_bytes(), &mut *self.block)
+            .map_err(|_| {
+                if last {
+                    StreamError::FinalBlockAuthentication
+                } else {
+                    StreamError::Authentication
+                }
+            })?;
+        self.cached = Some(index);
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Read for V1PayloadRandomAccessReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_authenticated(buf).map_err(stream_error_to_io)
+    }
+}
+
+impl<R: Read + Seek> Seek for V1PayloadRandomAccessReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.plaintext_len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        self.position = position;
+        Ok(position)
+    }
+}
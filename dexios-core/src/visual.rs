@@ -1,6 +1,6 @@
 //! This module offers visual functionality within `dexios-core`.
 //!
-//! It isn't rather populated, nor does `dexios` itself use it, but the option is always there.
+//! It isn't rather populated, and `dexios` only uses it for the progress bar of `dexios hash`, but the option is always there.
 //!
 //! This can be enabled with the `visual` feature, and you will notice a blue spinner on encryption and decryption - useful for knowing that something is still happening.
 
@@ -27,6 +27,21 @@ pub fn create_spinner() -> ProgressBar {
     pb
 }
 
+#[cfg(feature = "visual")]
+#[must_use]
+/// This creates a progress bar for work of a known number of bytes, such as hashing a set of files.
+///
+/// Like the spinner, it draws to stderr and stays hidden when stderr is not a terminal.
+pub fn create_progress_bar(total_bytes: u64) -> ProgressBar {
+    let pb = ProgressBar::new(total_bytes);
+    pb.set_style(
+        ProgressStyle::with_template("{bar:40.cyan/blue} {bytes}/{total_bytes} ({eta})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+
+    pb
+}
+
 #[cfg(all(test, feature = "visual"))]
 mod tests {
     use super::*;
@@ -115,3 +115,48 @@ fn argon2id_distinct_inputs_yield_distinct_keys() {
             .with_exposed(|other| assert_ne!(base, other, "password must change the key"));
     });
 }
+
+#[test]
+fn argon2id_profiles_round_trip_names_and_param_profile_ids() {
+    for kdf in Kdf::PROFILES {
+        assert_eq!(Kdf::from_profile_name(kdf.profile_name()), Some(kdf));
+        assert_eq!(
+            Kdf::from_param_profile_id(kdf.param_profile_id()),
+            Some(kdf)
+        );
+    }
+
+    assert_eq!(Kdf::Argon2id.profile_name(), "default");
+    assert_eq!(Kdf::Argon2id.param_profile_id(), 0x01);
+    assert_eq!(Kdf::from_profile_name("fast"), None);
+    assert_eq!(Kdf::from_param_profile_id(0x7F), None);
+}
+
+#[test]
+fn argon2id_profiles_order_memory_cost_from_interactive_to_paranoid() {
+    let memory = Kdf::PROFILES.map(|kdf| kdf.argon2id_costs().0);
+
+    assert!(memory[0] < memory[1] && memory[1] < memory[2]);
+    assert_eq!(
+        Kdf::Argon2id.argon2id_costs(),
+        (
+            dexios_core::kdf::ARGON2ID_M_COST,
+            dexios_core::kdf::ARGON2ID_T_COST,
+            dexios_core::kdf::ARGON2ID_P_COST
+        )
+    );
+}
+
+#[test]
+fn argon2id_interactive_profile_derives_a_different_key_than_default() {
+    let password = Protected::new(b"test-password".to_vec());
+    let salt = Salt::new([5u8; 16]);
+    let default = Kdf::Argon2id.derive(&password, &salt).unwrap();
+    let interactive = Kdf::Argon2idInteractive.derive(&password, &salt).unwrap();
+
+    default.with_exposed(|default| {
+        interactive.with_exposed(|interactive| {
+            assert_ne!(default, interactive, "cost profile must change the key");
+        });
+    });
+}
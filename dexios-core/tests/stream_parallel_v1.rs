@@ -0,0 +1,226 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::unreachable,
+        clippy::string_slice,
+        clippy::too_many_lines,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        clippy::match_same_arms,
+        clippy::items_after_statements,
+        clippy::redundant_closure_for_method_calls,
+        clippy::needless_collect,
+        clippy::manual_let_else,
+        clippy::format_collect,
+        clippy::case_sensitive_file_extension_comparisons,
+        clippy::struct_excessive_bools,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#![cfg(feature = "parallel")]
+
+use std::io::Cursor;
+use std::num::NonZeroUsize;
+
+use dexios_core::header::common::{KeyslotNonce, PayloadNonce, Salt as HeaderSalt};
+use dexios_core::header::v1::{V1Header, V1Keyslot, V1Keyslots};
+use dexios_core::header::{ParsedHeader, ParsedV1Payload};
+use dexios_core::kdf::Kdf;
+use dexios_core::primitives::{BLOCK_SIZE, MasterKey};
+use dexios_core::stream::{StreamError, V1PayloadStream};
+
+const STREAM_TAG_LEN: usize = 16;
+
+fn sample_v1_header() -> V1Header {
+    let keyslot = V1Keyslot::new(
+        Kdf::Argon2id,
+        [11; 48],
+        KeyslotNonce::try_from_slice(&[13; 24]).expect("valid keyslot nonce"),
+        HeaderSalt::new([17; 16]),
+    );
+    V1Header::new(
+        PayloadNonce::try_from_slice(&[7; 20]).expect("valid payload nonce"),
+        V1Keyslots::try_from_vec(vec![keyslot]).expect("sample keyslot count"),
+    )
+    .expect("sample v1 header")
+}
+
+fn parsed_payload_for(header: &V1Header) -> ParsedV1Payload {
+    let bytes = header.serialize().expect("serialize header");
+    let ParsedHeader::V1(payload) =
+        dexios_core::header::read_header(&mut Cursor::new(bytes)).expect("parse header");
+    payload
+}
+
+fn master_key() -> MasterKey {
+    MasterKey::new([31u8; 32])
+}
+
+fn threads(count: usize) -> NonZeroUsize {
+    NonZeroUsize::new(count).expect("nonzero thread count")
+}
+
+fn plaintext(len: usize) -> Vec<u8> {
+    (0..len).map(|index| (index % 251) as u8).collect()
+}
+
+// Covers the empty and exact-block final blocks plus a payload long enough to
+// span several batches at two threads.
+fn payload_lengths() -> [usize; 6] {
+    [
+        0,
+        1,
+        BLOCK_SIZE,
+        BLOCK_SIZE + 1,
+        BLOCK_SIZE * 3 + 37,
+        BLOCK_SIZE * 9 + 5,
+    ]
+}
+
+fn sequential_ciphertext(header: &V1Header, plaintext: &[u8]) -> Vec<u8> {
+    let mut ciphertext = Vec::new();
+    V1PayloadStream::encrypt_file(
+        master_key(),
+        header,
+        &mut Cursor::new(plaintext),
+        &mut ciphertext,
+    )
+    .expect("sequential encrypt");
+    ciphertext
+}
+
+fn parallel_decrypt(
+    payload: &ParsedV1Payload,
+    ciphertext: Vec<u8>,
+    thread_count: usize,
+) -> (Result<(), StreamError>, Vec<u8>) {
+    let mut scratch = Vec::new();
+    let result = V1PayloadStream::decrypt_file_uncommitted_parallel(
+        master_key(),
+        payload,
+        &mut Cursor::new(ciphertext),
+        &mut scratch,
+        threads(thread_count),
+    )
+    .map(|_| ());
+    (result, scratch)
+}
+
+#[test]
+fn parallel_encryption_matches_sequential_ciphertext_exactly() {
+    let header = sample_v1_header();
+    for len in payload_lengths() {
+        let plaintext = plaintext(len);
+        let expected = sequential_ciphertext(&header, &plaintext);
+        for thread_count in [1, 2, 3, 8] {
+            let mut ciphertext = Vec::new();
+            V1PayloadStream::encrypt_file_parallel(
+                master_key(),
+                &header,
+                &mut Cursor::new(&plaintext),
+                &mut ciphertext,
+                threads(thread_count),
+            )
+            .expect("parallel encrypt");
+            assert!(
+                ciphertext == expected,
+                "len={len} threads={thread_count}: parallel ciphertext differs"
+            );
+        }
+    }
+}
+
+#[test]
+fn parallel_decryption_opens_sequential_ciphertext() {
+    let header = sample_v1_header();
+    let payload = parsed_payload_for(&header);
+    for len in payload_lengths() {
+        let plaintext = plaintext(len);
+        let ciphertext = sequential_ciphertext(&header, &plaintext);
+        for thread_count in [1, 2, 8] {
+            let (result, decrypted) = parallel_decrypt(&payload, ciphertext.clone(), thread_count);
+            result.expect("parallel decrypt");
+            assert!(
+                decrypted == plaintext,
+                "len={len} threads={thread_count}: plaintext differs"
+            );
+        }
+    }
+}
+
+#[test]
+fn parallel_decryption_writes_only_blocks_before_a_tampered_block() {
+    let header = sample_v1_header();
+    let payload = parsed_payload_for(&header);
+    let plaintext = plaintext(BLOCK_SIZE * 9 + 5);
+    let mut ciphertext = sequential_ciphertext(&header, &plaintext);
+    let tampered_block = 6;
+    ciphertext[tampered_block * (BLOCK_SIZE + STREAM_TAG_LEN) + 3] ^= 0x01;
+
+    let (result, decrypted) = parallel_decrypt(&payload, ciphertext, 4);
+
+    assert!(matches!(result, Err(StreamError::Authentication)));
+    assert_eq!(decrypted.len(), tampered_block * BLOCK_SIZE);
+    assert!(decrypted == plaintext[..decrypted.len()]);
+}
+
+#[test]
+fn parallel_decryption_rejects_tampered_and_missing_final_blocks() {
+    let header = sample_v1_header();
+    let payload = parsed_payload_for(&header);
+    let plaintext = plaintext(BLOCK_SIZE * 2 + 37);
+    let ciphertext = sequential_ciphertext(&header, &plaintext);
+
+    let mut tampered = ciphertext.clone();
+    *tampered.last_mut().expect("final tag byte") ^= 0x01;
+    let (result, _) = parallel_decrypt(&payload, tampered, 2);
+    assert!(matches!(result, Err(StreamError::FinalBlockAuthentication)));
+
+    let full_blocks = ciphertext[..2 * (BLOCK_SIZE + STREAM_TAG_LEN)].to_vec();
+    let (result, decrypted) = parallel_decrypt(&payload, full_blocks, 2);
+    assert!(matches!(result, Err(StreamError::MissingFinalBlock)));
+    assert!(decrypted == plaintext[..2 * BLOCK_SIZE]);
+
+    let truncated_tag = ciphertext[..2 * (BLOCK_SIZE + STREAM_TAG_LEN) + 5].to_vec();
+    let (result, _) = parallel_decrypt(&payload, truncated_tag, 2);
+    assert!(matches!(result, Err(StreamError::TruncatedCiphertext)));
+}
+
+#[test]
+fn parallel_paths_write_and_skip_the_header_trailer() {
+    let header = sample_v1_header().with_header_trailer();
+    let payload = parsed_payload_for(&header);
+    for len in payload_lengths() {
+        let plaintext = plaintext(len);
+        let expected = sequential_ciphertext(&header, &plaintext);
+        for thread_count in [1, 3] {
+            let mut ciphertext = Vec::new();
+            V1PayloadStream::encrypt_file_parallel(
+                master_key(),
+                &header,
+                &mut Cursor::new(&plaintext),
+                &mut ciphertext,
+                threads(thread_count),
+            )
+            .expect("parallel encrypt");
+            assert!(
+                ciphertext == expected,
+                "len={len} threads={thread_count}: parallel ciphertext differs"
+            );
+
+            let (result, decrypted) = parallel_decrypt(&payload, ciphertext, thread_count);
+            result.expect("parallel decrypt");
+            assert!(
+                decrypted == plaintext,
+                "len={len} threads={thread_count}: plaintext differs"
+            );
+        }
+    }
+}
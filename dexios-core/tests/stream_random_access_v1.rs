@@ -0,0 +1,247 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::unreachable,
+        clippy::string_slice,
+        clippy::too_many_lines,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        clippy::match_same_arms,
+        clippy::items_after_statements,
+        clippy::redundant_closure_for_method_calls,
+        clippy::needless_collect,
+        clippy::manual_let_else,
+        clippy::format_collect,
+        clippy::case_sensitive_file_extension_comparisons,
+        clippy::struct_excessive_bools,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use dexios_core::header::common::{KeyslotNonce, PayloadNonce, Salt as HeaderSalt};
+use dexios_core::header::v1::{V1Header, V1Keyslot, V1Keyslots};
+use dexios_core::header::{ParsedHeader, ParsedV1Payload};
+use dexios_core::kdf::Kdf;
+use dexios_core::primitives::{BLOCK_SIZE, MasterKey};
+use dexios_core::stream::{StreamError, V1PayloadRandomAccessReader, V1PayloadStream};
+
+const STREAM_TAG_LEN: usize = 16;
+const CIPHERTEXT_BLOCK_LEN: usize = BLOCK_SIZE + STREAM_TAG_LEN;
+
+fn sample_v1_header() -> V1Header {
+    let keyslot = V1Keyslot::new(
+        Kdf::Argon2id,
+        [11; 48],
+        KeyslotNonce::try_from_slice(&[13; 24]).expect("valid keyslot nonce"),
+        HeaderSalt::new([17; 16]),
+    );
+    V1Header::new(
+        PayloadNonce::try_from_slice(&[7; 20]).expect("valid payload nonce"),
+        V1Keyslots::try_from_vec(vec![keyslot]).expect("sample keyslot count"),
+    )
+    .expect("sample v1 header")
+}
+
+fn parsed_payload_for(header: &V1Header) -> ParsedV1Payload {
+    let bytes = header.serialize().expect("serialize header");
+    let ParsedHeader::V1(payload) =
+        dexios_core::header::read_header(&mut Cursor::new(bytes)).expect("parse header");
+    payload
+}
+
+fn master_key() -> MasterKey {
+    MasterKey::new([31u8; 32])
+}
+
+fn plaintext(len: usize) -> Vec<u8> {
+    (0..len).map(|index| (index % 251) as u8).collect()
+}
+
+fn ciphertext_for(header: &V1Header, plaintext: &[u8]) -> Vec<u8> {
+    let mut ciphertext = Vec::new();
+    V1PayloadStream::encrypt_file(
+        master_key(),
+        header,
+        &mut Cursor::new(plaintext),
+        &mut ciphertext,
+    )
+    .expect("encrypt payload");
+    ciphertext
+}
+
+fn open(
+    payload: &ParsedV1Payload,
+    bytes: Vec<u8>,
+    payload_offset: u64,
+) -> Result<V1PayloadRandomAccessReader<Cursor<Vec<u8>>>, StreamError> {
+    V1PayloadRandomAccessReader::new(master_key(), payload, Cursor::new(bytes), payload_offset)
+}
+
+fn read_range(
+    reader: &mut V1PayloadRandomAccessReader<Cursor<Vec<u8>>>,
+    start: u64,
+    len: usize,
+) -> Result<Vec<u8>, StreamError> {
+    reader
+        .seek(SeekFrom::Start(start))
+        .expect("seek plaintext position");
+    let mut out = vec![0u8; len];
+    let mut filled = 0;
+    while filled < len {
+        let read = reader.read_authenticated(&mut out[filled..])?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    out.truncate(filled);
+    Ok(out)
+}
+
+#[test]
+fn random_access_reads_ranges_within_and_across_blocks() {
+    let header = sample_v1_header();
+    let payload = parsed_payload_for(&header);
+    let plaintext = plaintext(BLOCK_SIZE * 3 + 37);
+    let mut reader = open(&payload, ciphertext_for(&header, &plaintext), 0).expect("open");
+
+    assert_eq!(reader.plaintext_len(), plaintext.len() as u64);
+    for (start, len) in [
+        (0, 10),
+        (BLOCK_SIZE - 3, 7),
+        (BLOCK_SIZE * 2 + 5, BLOCK_SIZE + 32),
+        (BLOCK_SIZE * 3, 37),
+        (plaintext.len() - 1, 1),
+    ] {
+        let range = read_range(&mut reader, start as u64, len).expect("read range");
+        assert!(
+            range == plaintext[start..start + len],
+            "start={start} len={len}: plaintext differs"
+        );
+    }
+
+    let past_end = read_range(&mut reader, plaintext.len() as u64, 5).expect("read at end");
+    assert!(past_end.is_empty());
+    reader.verify_final_block().expect("final block");
+}
+
+#[test]
+fn random_access_honors_payload_offset_and_std_io_traits() {
+    let header = sample_v1_header();
+    let payload = parsed_payload_for(&header);
+    let plaintext = plaintext(BLOCK_SIZE + 100);
+    let mut bytes = vec![0xAA; 512];
+    bytes.extend(ciphertext_for(&header, &plaintext));
+    let mut reader = open(&payload, bytes, 512).expect("open");
+
+    reader.seek(SeekFrom::End(-50)).expect("seek from end");
+    let mut tail = Vec::new();
+    reader.read_to_end(&mut tail).expect("read to end");
+    assert!(tail == plaintext[plaintext.len() - 50..]);
+
+    assert!(
+        reader
+            .seek(SeekFrom::Current(-(plaintext.len() as i64) - 1))
+            .is_err()
+    );
+}
+
+#[test]
+fn random_access_authenticates_only_the_blocks_it_touches() {
+    let header = sample_v1_header();
+    let payload = parsed_payload_for(&header);
+    let plaintext = plaintext(BLOCK_SIZE * 3 + 37);
+    let mut ciphertext = ciphertext_for(&header, &plaintext);
+    ciphertext[CIPHERTEXT_BLOCK_LEN + 3] ^= 0x01;
+    let mut reader = open(&payload, ciphertext, 0).expect("open");
+
+    let untouched = read_range(&mut reader, BLOCK_SIZE as u64 * 2, 64).expect("clean block");
+    assert!(untouched == plaintext[BLOCK_SIZE * 2..BLOCK_SIZE * 2 + 64]);
+    reader.verify_final_block().expect("final block");
+
+    let tampered = read_range(&mut reader, BLOCK_SIZE as u64 + 10, 4);
+    assert!(matches!(tampered, Err(StreamError::Authentication)));
+}
+
+#[test]
+fn random_access_detects_truncated_and_tampered_final_blocks() {
+    let header = sample_v1_header();
+    let payload = parsed_payload_for(&header);
+    let plaintext = plaintext(BLOCK_SIZE * 2 + 37);
+    let ciphertext = ciphertext_for(&header, &plaintext);
+
+    let mut tampered = ciphertext.clone();
+    *tampered.last_mut().expect("final tag byte") ^= 0x01;
+    let mut reader = open(&payload, tampered, 0).expect("open");
+    read_range(&mut reader, 0, 16).expect("first block still authenticates");
+    assert!(matches!(
+        reader.verify_final_block(),
+        Err(StreamError::FinalBlockAuthentication)
+    ));
+
+    let full_blocks = ciphertext[..2 * CIPHERTEXT_BLOCK_LEN].to_vec();
+    assert!(matches!(
+        open(&payload, full_blocks, 0),
+        Err(StreamError::MissingFinalBlock)
+    ));
+
+    let truncated_tag = ciphertext[..2 * CIPHERTEXT_BLOCK_LEN + 5].to_vec();
+    assert!(matches!(
+        open(&payload, truncated_tag, 0),
+        Err(StreamError::TruncatedCiphertext)
+    ));
+
+    // Cutting whole trailing blocks leaves a plausible length, but the new
+    // last block was sealed as a non-final block.
+    let cut_after_first = ciphertext[..CIPHERTEXT_BLOCK_LEN + 37 + STREAM_TAG_LEN].to_vec();
+    let mut reader = open(&payload, cut_after_first, 0).expect("open");
+    assert!(matches!(
+        reader.verify_final_block(),
+        Err(StreamError::FinalBlockAuthentication)
+    ));
+}
+
+#[test]
+fn random_access_opens_an_empty_payload() {
+    let header = sample_v1_header();
+    let payload = parsed_payload_for(&header);
+    let mut reader = open(&payload, ciphertext_for(&header, &[]), 0).expect("open");
+
+    assert_eq!(reader.plaintext_len(), 0);
+    reader.verify_final_block().expect("empty final block");
+    assert!(
+        read_range(&mut reader, 0, 8)
+            .expect("read empty")
+            .is_empty()
+    );
+}
+
+#[test]
+fn random_access_excludes_the_header_trailer() {
+    let header = sample_v1_header().with_header_trailer();
+    let payload = parsed_payload_for(&header);
+    let plaintext = plaintext(BLOCK_SIZE * 2 + 37);
+    let ciphertext = ciphertext_for(&header, &plaintext);
+    let mut bytes = vec![0xAA; 512];
+    bytes.extend(&ciphertext);
+    let mut reader = open(&payload, bytes, 512).expect("open");
+
+    assert_eq!(reader.plaintext_len(), plaintext.len() as u64);
+    reader.verify_final_block().expect("final block");
+    let tail = read_range(&mut reader, plaintext.len() as u64 - 40, 64).expect("read tail");
+    assert!(tail == plaintext[plaintext.len() - 40..]);
+
+    let empty = ciphertext_for(&header, &[]);
+    let mut reader = open(&payload, empty, 0).expect("open empty");
+    assert_eq!(reader.plaintext_len(), 0);
+    reader.verify_final_block().expect("empty final block");
+}
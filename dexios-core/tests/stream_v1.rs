@@ -31,8 +31,11 @@ use dexios_core::header::v1::{V1Header, V1Keyslot, V1Keyslots};
 use dexios_core::header::{ParsedHeader, ParsedV1Payload};
 use dexios_core::kdf::Kdf;
 use dexios_core::payload::{
-    ArchiveBodyFrame, ArchiveBodyFrameHeader, ArchiveManifest, MANIFEST_MAGIC, MAX_BODY_FRAME_LEN,
-    MAX_MANIFEST_ENTRY_COUNT, MAX_NORMALIZED_PATH_BYTES, ManifestEntry, ManifestFirstPayload,
+    ArchiveBodyFrame, ArchiveBodyFrameHeader, ArchiveManifest, BodyCodec, BodyCompression,
+    BodyDecoder, BodyEncoder, COMPRESSION_CHUNK_LEN, ENTRY_DIGEST_LEN, EntryMetadata,
+    EntryTimestamp, ExtendedAttribute, MANIFEST_MAGIC, MANIFEST_VERSION, MANIFEST_VERSION_DIGEST,
+    MANIFEST_VERSION_METADATA, MAX_BODY_FRAME_LEN, MAX_ENTRY_XATTR_BYTES, MAX_MANIFEST_ENTRY_COUNT,
+    MAX_NORMALIZED_PATH_BYTES, ManifestEntry, ManifestEntryKind, ManifestFirstPayload,
     PayloadError, PayloadFramingProfile, PayloadKind,
 };
 use dexios_core::primitives::{BLOCK_SIZE, MasterKey};
@@ -468,6 +471,319 @@ fn manifest_first_streaming_helpers_roundtrip_manifest_and_body_headers() {
     assert_eq!(parsed_header.body_len(), 5);
 }
 
+fn compress_body(body: &[u8]) -> Vec<u8> {
+    let mut encoder =
+        BodyEncoder::new(Vec::new(), BodyCompression::default()).expect("create body encoder");
+    encoder.write_all(body).expect("compress body");
+    encoder.finish().expect("finish compressed body")
+}
+
+fn decompress_body(encoded: &[u8], body_len: u64) -> std::io::Result<Vec<u8>> {
+    let mut decoder = BodyDecoder::new(Cursor::new(encoded), BodyCodec::Zstd, body_len)
+        .expect("create body decoder");
+    let mut decoded = Vec::new();
+    decoder.read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+#[test]
+fn compressed_body_roundtrips_across_chunk_boundaries() {
+    for len in [
+        0,
+        1,
+        COMPRESSION_CHUNK_LEN,
+        COMPRESSION_CHUNK_LEN + 1,
+        2 * COMPRESSION_CHUNK_LEN + 17,
+    ] {
+        let body = (0..len).map(|index| (index % 97) as u8).collect::<Vec<_>>();
+        let encoded = compress_body(&body);
+        assert!(
+            len < COMPRESSION_CHUNK_LEN || encoded.len() < body.len(),
+            "compressible body of {len} bytes must shrink"
+        );
+
+        let decoded = decompress_body(&encoded, len as u64).expect("decompress body");
+        assert_eq!(decoded, body, "body of {len} bytes must roundtrip");
+    }
+}
+
+#[test]
+fn compressed_body_rejects_corrupt_oversized_and_truncated_chunks() {
+    let body = vec![7u8; 4096];
+    let encoded = compress_body(&body);
+
+    let mut corrupt = encoded.clone();
+    let last = corrupt.len() - 1;
+    corrupt[last] ^= 0xff;
+
+    for (name, bytes, body_len) in [
+        ("corrupt frame", corrupt, 4096),
+        ("declared length shorter than chunk", encoded.clone(), 1024),
+        ("zero stored length", 0u32.to_le_bytes().to_vec(), 4096),
+        (
+            "oversized stored length",
+            u32::MAX.to_le_bytes().to_vec(),
+            4096,
+        ),
+    ] {
+        let error = decompress_body(&bytes, body_len).expect_err(name);
+        assert!(
+            matches!(
+                error
+                    .get_ref()
+                    .and_then(|inner| inner.downcast_ref::<PayloadError>()),
+                Some(PayloadError::InvalidCompressedBody)
+            ),
+            "{name} must be rejected as an invalid compressed body, got {error:?}"
+        );
+    }
+
+    let error = decompress_body(&encoded[..encoded.len() - 1], 4096)
+        .expect_err("truncated chunk must fail");
+    assert!(matches!(
+        error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<PayloadError>()),
+        Some(PayloadError::TruncatedManifest)
+    ));
+}
+
+#[test]
+fn compressed_framing_carries_codec_byte_and_rejects_unknown_codecs() {
+    let frame_header = ArchiveBodyFrameHeader::new(1, 5)
+        .expect("bounded body frame header")
+        .with_codec(BodyCodec::Zstd);
+    let mut frame_bytes = Vec::new();
+    frame_header
+        .write_framed_to(
+            &mut frame_bytes,
+            PayloadFramingProfile::ManifestFirstCompressed,
+        )
+        .expect("write compressed body frame header");
+    let parsed = ArchiveBodyFrameHeader::read_framed_from(
+        &mut Cursor::new(&frame_bytes),
+        PayloadFramingProfile::ManifestFirstCompressed,
+    )
+    .expect("read compressed body frame header");
+    assert_eq!(parsed.codec(), BodyCodec::Zstd);
+    assert_eq!(parsed.body_len(), 5);
+
+    let error = frame_header
+        .write_framed_to(&mut Vec::new(), PayloadFramingProfile::ManifestFirst)
+        .expect_err("plain manifest-first framing cannot carry a codec");
+    assert!(matches!(error, PayloadError::UnsupportedBodyCodec(0x01)));
+
+    *frame_bytes.last_mut().expect("codec byte") = 0x7f;
+    let error = ArchiveBodyFrameHeader::read_framed_from(
+        &mut Cursor::new(&frame_bytes),
+        PayloadFramingProfile::ManifestFirstCompressed,
+    )
+    .expect_err("unknown codec byte must fail");
+    assert!(matches!(error, PayloadError::UnsupportedBodyCodec(0x7f)));
+
+    for level in [0, 20, -1] {
+        assert!(matches!(
+            BodyCompression::zstd(level),
+            Err(PayloadError::InvalidCompressionLevel(rejected)) if rejected == level
+        ));
+    }
+}
+
+fn manifest_version(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[4], bytes[5]])
+}
+
+#[test]
+fn manifest_metadata_roundtrips_and_bumps_manifest_version() {
+    let metadata = EntryMetadata::default()
+        .with_mode(0o100_640)
+        .with_mtime(EntryTimestamp::new(1_700_000_000, 123_456_789).unwrap())
+        .with_atime(EntryTimestamp::new(-5, 0).unwrap())
+        .with_owner(1000, 100)
+        .with_xattrs(vec![
+            ExtendedAttribute::new(b"user.tag".to_vec(), b"blue".to_vec()).unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(metadata.mode(), Some(0o640), "file type bits are dropped");
+
+    let manifest = ArchiveManifest::new(vec![
+        ManifestEntry::directory(b"dir".to_vec()).unwrap(),
+        ManifestEntry::file(b"dir/a.txt".to_vec(), 5)
+            .unwrap()
+            .with_metadata(metadata.clone())
+            .unwrap(),
+    ])
+    .unwrap();
+    let mut bytes = Vec::new();
+    manifest.write_to(&mut bytes).unwrap();
+    assert_eq!(manifest_version(&bytes), MANIFEST_VERSION_METADATA);
+
+    let parsed = ArchiveManifest::read_from(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(parsed, manifest);
+    assert!(parsed.entries()[0].metadata().is_empty());
+    assert_eq!(parsed.entries()[1].metadata(), &metadata);
+
+    let mut plain = Vec::new();
+    sample_manifest_first_payload()
+        .manifest()
+        .write_to(&mut plain)
+        .unwrap();
+    assert_eq!(
+        manifest_version(&plain),
+        MANIFEST_VERSION,
+        "manifests without metadata stay at version 1"
+    );
+}
+
+#[test]
+fn manifest_metadata_rejects_malformed_records() {
+    let manifest = ArchiveManifest::new(vec![
+        ManifestEntry::directory(b"d".to_vec())
+            .unwrap()
+            .with_metadata(EntryMetadata::default().with_mode(0o755))
+            .unwrap(),
+    ])
+    .unwrap();
+    let mut bytes = Vec::new();
+    manifest.write_to(&mut bytes).unwrap();
+    // magic, version, count, kind, path_len, path, then the flags byte.
+    let flags_offset = 4 + 2 + 4 + 1 + 2 + 1;
+
+    let mut unknown_flags = bytes.clone();
+    unknown_flags[flags_offset] |= 0x80;
+    assert_eq!(
+        ArchiveManifest::read_from(&mut Cursor::new(&unknown_flags)),
+        Err(PayloadError::InvalidMetadataFlags(0x81))
+    );
+
+    let mut bad_mode = bytes.clone();
+    bad_mode[flags_offset + 1..flags_offset + 5].copy_from_slice(&0o17_0000u32.to_le_bytes());
+    assert_eq!(
+        ArchiveManifest::read_from(&mut Cursor::new(&bad_mode)),
+        Err(PayloadError::InvalidFileMode(0o17_0000))
+    );
+
+    assert_eq!(
+        ArchiveManifest::read_from(&mut Cursor::new(&bytes[..bytes.len() - 1])),
+        Err(PayloadError::TruncatedManifest)
+    );
+    assert_eq!(
+        EntryTimestamp::new(0, 1_000_000_000),
+        Err(PayloadError::InvalidTimestamp)
+    );
+    assert_eq!(
+        ExtendedAttribute::new(b"user.\0x".to_vec(), Vec::new()),
+        Err(PayloadError::InvalidExtendedAttributeName)
+    );
+    let error = EntryMetadata::default()
+        .with_xattrs(vec![
+            ExtendedAttribute::new(b"user.a".to_vec(), vec![0; MAX_ENTRY_XATTR_BYTES / 2]).unwrap(),
+            ExtendedAttribute::new(b"user.b".to_vec(), vec![0; MAX_ENTRY_XATTR_BYTES / 2]).unwrap(),
+        ])
+        .expect_err("combined attributes over the per-entry budget must fail");
+    assert!(matches!(
+        error,
+        PayloadError::ExtendedAttributeLimitExceeded { .. }
+    ));
+}
+
+#[test]
+fn manifest_digests_roundtrip_and_bump_manifest_version() {
+    let digest = [0xa5; ENTRY_DIGEST_LEN];
+    let manifest = ArchiveManifest::new(vec![
+        ManifestEntry::directory(b"dir".to_vec()).unwrap(),
+        ManifestEntry::file(b"dir/a.txt".to_vec(), 5)
+            .unwrap()
+            .with_blake3_digest(digest)
+            .unwrap(),
+        ManifestEntry::file(b"dir/b.txt".to_vec(), 0).unwrap(),
+        ManifestEntry::symlink(b"dir/c".to_vec(), b"a.txt".to_vec()).unwrap(),
+    ])
+    .unwrap();
+    let mut bytes = Vec::new();
+    manifest.write_to(&mut bytes).unwrap();
+    assert_eq!(manifest_version(&bytes), MANIFEST_VERSION_DIGEST);
+
+    let parsed = ArchiveManifest::read_from(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(parsed, manifest);
+    assert_eq!(parsed.entries()[1].blake3_digest(), Some(&digest));
+    assert_eq!(parsed.entries()[2].blake3_digest(), None);
+
+    assert_eq!(
+        ManifestEntry::directory(b"dir".to_vec())
+            .unwrap()
+            .with_blake3_digest(digest),
+        Err(PayloadError::UnexpectedEntryDigest)
+    );
+
+    // The second file entry has no digest: its record ends in a zero kind byte.
+    let kind_offset = bytes
+        .windows(b"dir/b.txt".len())
+        .position(|window| window == b"dir/b.txt")
+        .unwrap()
+        + b"dir/b.txt".len()
+        + 1;
+    assert_eq!(bytes[kind_offset], 0x00);
+    let mut unknown_kind = bytes.clone();
+    unknown_kind[kind_offset] = 0x02;
+    assert_eq!(
+        ArchiveManifest::read_from(&mut Cursor::new(&unknown_kind)),
+        Err(PayloadError::InvalidDigestKind(0x02))
+    );
+}
+
+#[test]
+fn manifest_link_entries_roundtrip_without_body_frames() {
+    let payload = ManifestFirstPayload::new(
+        ArchiveManifest::new(vec![
+            ManifestEntry::file(b"lib/libfoo.so.1".to_vec(), 3).unwrap(),
+            ManifestEntry::symlink(b"lib/libfoo.so".to_vec(), b"libfoo.so.1".to_vec()).unwrap(),
+            ManifestEntry::hard_link(b"lib/libfoo-copy.so".to_vec(), b"lib/libfoo.so.1".to_vec())
+                .unwrap(),
+        ])
+        .unwrap(),
+        vec![ArchiveBodyFrame::new(0, b"elf".to_vec()).unwrap()],
+    )
+    .unwrap();
+
+    let bytes = payload.serialize().unwrap();
+    assert_eq!(manifest_version(&bytes), MANIFEST_VERSION);
+    let parsed = ManifestFirstPayload::parse(&bytes).unwrap();
+    assert_eq!(parsed, payload);
+    let entries = parsed.manifest().entries();
+    assert_eq!(entries[1].kind(), ManifestEntryKind::Symlink);
+    assert_eq!(entries[1].link_target(), Some(&b"libfoo.so.1"[..]));
+    assert_eq!(entries[1].body_len(), None);
+    assert_eq!(entries[2].kind(), ManifestEntryKind::HardLink);
+    assert_eq!(entries[0].link_target(), None);
+}
+
+#[test]
+fn manifest_link_entries_reject_missing_targets_and_unresolved_hard_links() {
+    assert_eq!(
+        ManifestEntry::symlink(b"link".to_vec(), Vec::new()),
+        Err(PayloadError::InvalidLinkTarget)
+    );
+    assert_eq!(
+        ManifestEntry::symlink(b"link".to_vec(), b"a\0b".to_vec()),
+        Err(PayloadError::InvalidLinkTarget)
+    );
+    assert_eq!(
+        ArchiveManifest::new(vec![
+            ManifestEntry::hard_link(b"early".to_vec(), b"later".to_vec()).unwrap(),
+            ManifestEntry::file(b"later".to_vec(), 0).unwrap(),
+        ]),
+        Err(PayloadError::UnresolvedHardLink(0))
+    );
+    assert_eq!(
+        ArchiveManifest::new(vec![
+            ManifestEntry::directory(b"dir".to_vec()).unwrap(),
+            ManifestEntry::hard_link(b"link".to_vec(), b"dir".to_vec()).unwrap(),
+        ]),
+        Err(PayloadError::UnresolvedHardLink(1))
+    );
+}
+
 #[test]
 fn manifest_first_streaming_helpers_reject_invalid_body_magic_and_limits() {
     let mut invalid_magic = Vec::new();
@@ -1120,3 +1436,70 @@ fn decrypt_failure_output_is_uncommitted_scratch() {
         "failed decrypt output is only uncommitted scratch until final authentication succeeds"
     );
 }
+
+#[test]
+fn header_trailer_follows_final_block_and_is_skipped_on_decrypt() {
+    let header = support::sample_v1_header().with_header_trailer();
+    let payload = support::parsed_payload_for(&header);
+    let plaintext = plaintext_spanning_normal_chunks();
+
+    let mut encrypted = Vec::new();
+    V1PayloadStream::encrypt_file(
+        support::master_key(),
+        &header,
+        &mut Cursor::new(&plaintext),
+        &mut encrypted,
+    )
+    .expect("encrypt v1 stream with trailer");
+    let trailer = header.serialize_trailer().expect("serialize trailer");
+    assert!(encrypted.ends_with(&trailer));
+    assert_eq!(
+        &encrypted[..encrypted.len() - trailer.len()],
+        flatten_chunks(&encrypt_chunks(&header, &plaintext)).as_slice()
+    );
+
+    let mut writer = V1PayloadEncryptingWriter::new(support::master_key(), &header, Vec::new())
+        .expect("create encrypting writer");
+    writer
+        .write_all(&plaintext)
+        .expect("write plaintext into encrypting writer");
+    assert_eq!(
+        writer.finish().expect("finish encrypting writer"),
+        encrypted
+    );
+
+    let (result, decrypted) = decrypt_file_with(support::master_key(), &payload, encrypted.clone());
+    result.expect("decrypt v1 stream with trailer");
+    assert_eq!(decrypted, plaintext);
+
+    let mut reader = V1PayloadDecryptingReader::new(
+        support::master_key(),
+        &payload,
+        ShortRead::new(Cursor::new(encrypted), 97),
+    )
+    .expect("create decrypting reader");
+    let mut decrypted = Vec::new();
+    read_uncommitted_to_end(&mut reader, &mut decrypted).expect("read decrypting reader to EOF");
+    reader.finish().expect("finish after authenticated EOF");
+    assert_eq!(decrypted, plaintext);
+}
+
+#[test]
+fn truncated_header_trailer_fails_authentication() {
+    let header = support::sample_v1_header().with_header_trailer();
+    let payload = support::parsed_payload_for(&header);
+    let plaintext = plaintext_spanning_normal_chunks();
+
+    let mut encrypted = Vec::new();
+    V1PayloadStream::encrypt_file(
+        support::master_key(),
+        &header,
+        &mut Cursor::new(&plaintext),
+        &mut encrypted,
+    )
+    .expect("encrypt v1 stream with trailer");
+    encrypted.truncate(encrypted.len() - 1);
+
+    let (result, _) = decrypt_file_with(support::master_key(), &payload, encrypted);
+    assert!(result.is_err(), "a truncated trailer must not authenticate");
+}
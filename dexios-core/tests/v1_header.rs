@@ -26,16 +26,21 @@
 )]
 use dexios_core::header::common::{
     CANONICAL_HEADER_LEN, CANONICAL_HEADER_STATIC_LEN, CANONICAL_V1_DISCRIMINATOR, HEADER_LEN,
-    HEADER_STATIC_LEN, KEYSLOT_LEN, KeyslotNonce, MAGIC, PayloadNonce, Salt as HeaderSalt,
-    VERSION_V1,
+    HEADER_STATIC_LEN, HEADER_TRAILER_FOOTER_LEN, HEADER_TRAILER_MAGIC, KEYSLOT_LEN, KeyslotNonce,
+    MAGIC, PayloadNonce, Salt as HeaderSalt, VERSION_V1,
 };
 use dexios_core::header::v1::{
-    EncryptedMasterKey, KeyslotKdf, V1Header, V1Keyslot, V1KeyslotIndex, V1Keyslots,
+    EncryptedMasterKey, KeyslotKdf, KeyslotMetadata, V1Header, V1Keyslot, V1KeyslotCapacity,
+    V1KeyslotIndex, V1Keyslots,
 };
 use dexios_core::header::{HeaderReadError, ParsedHeader, ParsedV1Payload};
-use dexios_core::kdf::{ARGON2ID_KDF_PARAM_PROFILE_ID, ARGON2ID_KDF_PROFILE_ID, Kdf};
+use dexios_core::kdf::{
+    ARGON2ID_KDF_PARAM_PROFILE_ID, ARGON2ID_KDF_PROFILE_ID, Kdf,
+    X25519_RECIPIENT_KDF_PARAM_PROFILE_ID, X25519_RECIPIENT_KDF_PROFILE_ID,
+};
 use dexios_core::payload::{PayloadFramingProfile, PayloadKind};
 use dexios_core::primitives::{MasterKey, WrappingKey};
+use dexios_core::recipient::{RecipientIdentity, RecipientPublicKey, RecipientWrap};
 use dexios_core::stream::{StreamError, V1PayloadDecryptor, V1PayloadEncryptor, V1PayloadStream};
 use std::io::Read;
 use std::path::Path;
@@ -457,6 +462,38 @@ fn new_manifest_archive_constructor_sets_canonical_payload_metadata() {
     );
 }
 
+#[test]
+fn compressed_manifest_archive_header_roundtrips_and_requires_manifest_kind() {
+    let raw_header = support::sample_v1_header();
+    let header = V1Header::new_compressed_manifest_archive(
+        payload_nonce([9u8; 20]),
+        raw_header.keyslots_collection().clone(),
+    )
+    .expect("compressed manifest archive header");
+    let mut bytes = header.serialize().expect("serialize compressed header");
+
+    assert_eq!(header.payload_kind(), PayloadKind::ManifestArchive);
+    assert_eq!(
+        bytes[12],
+        PayloadFramingProfile::ManifestFirstCompressed.to_byte()
+    );
+
+    let ParsedHeader::V1(parsed) =
+        dexios_core::header::read_header(&mut std::io::Cursor::new(bytes.clone()))
+            .expect("compressed manifest archive header parses");
+    assert_eq!(
+        parsed.header().payload_framing(),
+        PayloadFramingProfile::ManifestFirstCompressed
+    );
+    assert!(parsed.header().payload_framing().is_manifest_first());
+
+    bytes[11] = PayloadKind::RawFile.to_byte();
+    assert!(
+        dexios_core::header::read_header(&mut std::io::Cursor::new(bytes)).is_err(),
+        "raw payloads must not declare compressed manifest framing"
+    );
+}
+
 #[test]
 fn read_header_returns_v1_payload_with_header_and_matching_aad() {
     let header = support::sample_v1_header();
@@ -720,7 +757,7 @@ fn parser_table_rejects_canonical_v1_malformed_metadata_by_variant() {
     let mut malformed_discriminator = canonical.clone();
     malformed_discriminator[7] = b'X';
     let mut malformed_schema = canonical.clone();
-    malformed_schema[10] = 0x02;
+    malformed_schema[10] = 0x03;
     let mut malformed_reserved = canonical.clone();
     malformed_reserved[36] = 0x01;
     let mut unsupported_payload_kind = canonical.clone();
@@ -763,7 +800,7 @@ fn parser_table_rejects_canonical_v1_malformed_metadata_by_variant() {
             },
         ),
         ("malformed_schema", malformed_schema, |error| {
-            matches!(error, HeaderReadError::UnsupportedVersion([0x00, 0x02]))
+            matches!(error, HeaderReadError::UnsupportedVersion([0x00, 0x03]))
         }),
         ("malformed_reserved", malformed_reserved, |error| {
             matches!(error, HeaderReadError::NonZeroReservedBytes)
@@ -889,6 +926,109 @@ fn v1_header_parses_historical_argon2id_tag_as_unsupported_keyslot_metadata() {
     );
 }
 
+fn recipient_v1_header(ephemeral_public_key: [u8; 32]) -> V1Header {
+    let keyslot = V1Keyslot::new_recipient(
+        [11u8; 48],
+        &RecipientPublicKey::from_bytes(ephemeral_public_key),
+    );
+    V1Header::new(payload_nonce([7u8; 20]), V1Keyslots::single(keyslot)).unwrap()
+}
+
+#[test]
+fn recipient_keyslot_round_trips_ephemeral_key_through_salt_and_nonce_fields() {
+    let ephemeral: [u8; 32] = std::array::from_fn(|index| index as u8 + 1);
+    let bytes = recipient_v1_header(ephemeral).serialize().unwrap();
+    let slot = &bytes[HEADER_STATIC_LEN..HEADER_STATIC_LEN + KEYSLOT_LEN];
+
+    assert_eq!(
+        &slot[..4],
+        &[
+            0x01,
+            0x00,
+            X25519_RECIPIENT_KDF_PROFILE_ID,
+            X25519_RECIPIENT_KDF_PARAM_PROFILE_ID
+        ]
+    );
+    assert_eq!(&slot[4..36], &ephemeral);
+    assert_eq!(&slot[36..44], &[0u8; 8]);
+
+    let ParsedHeader::V1(payload) =
+        dexios_core::header::read_header(&mut std::io::Cursor::new(bytes)).unwrap();
+    let keyslot = &payload.header().keyslots()[0];
+    assert_eq!(keyslot.kdf(), KeyslotKdf::X25519Recipient);
+    assert_eq!(
+        keyslot.ephemeral_public_key(),
+        Some(RecipientPublicKey::from_bytes(ephemeral))
+    );
+    assert_eq!(
+        payload
+            .header()
+            .keyslots_collection()
+            .supported_slot_count(),
+        1
+    );
+}
+
+#[test]
+fn recipient_keyslot_rejects_nonzero_unused_nonce_bytes() {
+    let mut bytes = recipient_v1_header([5u8; 32]).serialize().unwrap();
+    bytes[HEADER_STATIC_LEN + 43] = 1;
+
+    let error = dexios_core::header::read_header(&mut std::io::Cursor::new(bytes))
+        .expect_err("recipient keyslot nonce tail must be zero");
+
+    assert!(matches!(
+        error,
+        HeaderReadError::NonZeroActiveKeyslotPadding(0)
+    ));
+}
+
+#[test]
+fn recipient_keyslot_rejects_unknown_param_profile() {
+    let mut bytes = recipient_v1_header([5u8; 32]).serialize().unwrap();
+    bytes[HEADER_STATIC_LEN + 3] = 0x7F;
+
+    let error = dexios_core::header::read_header(&mut std::io::Cursor::new(bytes))
+        .expect_err("unknown recipient param profile should fail");
+
+    assert!(matches!(
+        error,
+        HeaderReadError::InvalidKdfParamProfile(0x7F)
+    ));
+}
+
+#[test]
+fn recipient_wrap_is_bound_to_the_slot_wrapping_aad() {
+    let identity = RecipientIdentity::generate();
+    let master_key = MasterKey::new([3u8; 32]);
+    let wrap = RecipientWrap::new(&identity.public_key()).unwrap();
+    let ephemeral = *wrap.ephemeral_public_key();
+    let placeholder = recipient_v1_header(*ephemeral.as_bytes());
+    let index = V1KeyslotIndex::try_from_physical_index(0).unwrap();
+    let aad = placeholder
+        .slot_wrapping_aad_for_physical_slot(index)
+        .unwrap();
+    let encrypted = wrap.wrap_master_key(&master_key, &aad).unwrap();
+
+    let unwrapped = identity
+        .unwrap_master_key(&ephemeral, &encrypted, &aad)
+        .unwrap();
+    assert!(unwrapped.same_secret_as(&master_key));
+
+    let moved = V1Header::new(
+        payload_nonce([8u8; 20]),
+        placeholder.keyslots_collection().clone(),
+    )
+    .unwrap();
+    let moved_aad = moved.slot_wrapping_aad_for_physical_slot(index).unwrap();
+    assert!(
+        identity
+            .unwrap_master_key(&ephemeral, &encrypted, &moved_aad)
+            .is_err(),
+        "a recipient keyslot copied into another header must not unwrap"
+    );
+}
+
 #[test]
 fn new_keyslot_constructor_uses_supported_kdf_selector() {
     let keyslot = V1Keyslot::new(
@@ -907,6 +1047,83 @@ fn new_keyslot_constructor_uses_supported_kdf_selector() {
     );
 }
 
+#[test]
+fn argon2id_cost_profiles_are_recorded_per_keyslot() {
+    let keyslot = |kdf, byte| {
+        V1Keyslot::new(
+            kdf,
+            [byte; 48],
+            keyslot_nonce([byte; 24]),
+            HeaderSalt::new([byte; 16]),
+        )
+    };
+    let keyslots = V1Keyslots::try_from_vec(vec![
+        keyslot(Kdf::Argon2idInteractive, 1),
+        keyslot(Kdf::Argon2id, 2),
+        keyslot(Kdf::Argon2idParanoid, 3),
+    ])
+    .unwrap();
+    let header = V1Header::new(payload_nonce([7u8; 20]), keyslots).unwrap();
+    let bytes = header.serialize().unwrap();
+
+    for (index, kdf) in [
+        Kdf::Argon2idInteractive,
+        Kdf::Argon2id,
+        Kdf::Argon2idParanoid,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let slot = HEADER_STATIC_LEN + index * KEYSLOT_LEN;
+        assert_eq!(bytes[slot + 2], ARGON2ID_KDF_PROFILE_ID);
+        assert_eq!(bytes[slot + 3], kdf.param_profile_id());
+    }
+    assert_eq!(bytes[13], ARGON2ID_KDF_PARAM_PROFILE_ID);
+
+    let ParsedHeader::V1(payload) =
+        dexios_core::header::read_header(&mut std::io::Cursor::new(bytes)).unwrap();
+    let kdfs: Vec<_> = payload
+        .header()
+        .keyslots()
+        .iter()
+        .map(|keyslot| keyslot.kdf().password_kdf())
+        .collect();
+    assert_eq!(
+        kdfs,
+        [
+            Some(Kdf::Argon2idInteractive),
+            Some(Kdf::Argon2id),
+            Some(Kdf::Argon2idParanoid)
+        ]
+    );
+}
+
+#[test]
+fn slot_wrapping_aad_binds_the_argon2id_cost_profile() {
+    let header_with = |kdf| {
+        let keyslot = V1Keyslot::new(
+            kdf,
+            [11u8; 48],
+            keyslot_nonce([13u8; 24]),
+            HeaderSalt::new([17u8; 16]),
+        );
+        V1Header::new(payload_nonce([7u8; 20]), V1Keyslots::single(keyslot)).unwrap()
+    };
+    let index = V1KeyslotIndex::try_from_physical_index(0).unwrap();
+
+    let default_aad = header_with(Kdf::Argon2id)
+        .slot_wrapping_aad_for_physical_slot(index)
+        .unwrap();
+    let interactive_aad = header_with(Kdf::Argon2idInteractive)
+        .slot_wrapping_aad_for_physical_slot(index)
+        .unwrap();
+
+    assert_ne!(
+        default_aad, interactive_aad,
+        "downgrading a keyslot's cost profile must break its unwrap authentication"
+    );
+}
+
 #[test]
 fn canonical_header_serializes_kdf_profile_ids_not_parameter_values() {
     let header = support::sample_v1_header();
@@ -951,6 +1168,229 @@ fn v1_header_rejects_active_keyslot_padding() {
     ));
 }
 
+fn labeled_v1_header(label: &str) -> V1Header {
+    let metadata = KeyslotMetadata::new(label, 1_700_000_000).unwrap();
+    let keyslot = support::sample_v1_header().keyslots()[0].with_metadata(Some(metadata));
+    V1Header::new(payload_nonce([7u8; 20]), V1Keyslots::single(keyslot)).unwrap()
+}
+
+#[test]
+fn keyslot_metadata_roundtrips_through_the_keyslot_padding() {
+    let header = labeled_v1_header("ops-recovery");
+    let bytes = header.serialize().unwrap();
+    let slot = &bytes[HEADER_STATIC_LEN..HEADER_STATIC_LEN + KEYSLOT_LEN];
+    assert_eq!(slot[92..100], 1_700_000_000u64.to_le_bytes());
+    assert_eq!(&slot[100..], b"ops-recovery");
+
+    let parsed = support::parsed_payload_for(&header);
+    let metadata = parsed.header().keyslots()[0].metadata().unwrap();
+    assert_eq!(metadata.label(), "ops-recovery");
+    assert_eq!(metadata.created_at(), 1_700_000_000);
+    assert_eq!(parsed.header(), &header);
+
+    let unlabeled = support::parsed_payload_for(&support::sample_v1_header());
+    assert_eq!(unlabeled.header().keyslots()[0].metadata(), None);
+}
+
+#[test]
+fn keyslot_metadata_rejects_labels_that_do_not_fit() {
+    for label in ["", "thirteen-byte", "tab\tlabel", "nul\0label"] {
+        assert!(
+            matches!(
+                KeyslotMetadata::new(label, 0),
+                Err(dexios_core::header::HeaderWriteError::InvalidKeyslotLabel)
+            ),
+            "{label:?}"
+        );
+    }
+    assert_eq!(KeyslotMetadata::new("élan", 0).unwrap().label(), "élan");
+}
+
+#[test]
+fn v1_header_rejects_malformed_keyslot_metadata() {
+    let label_start = HEADER_STATIC_LEN + 100;
+    let mut cases = Vec::new();
+
+    let mut gap = labeled_v1_header("alice").serialize().unwrap();
+    gap[label_start + 7] = b'x';
+    cases.push(gap);
+    let mut invalid_utf8 = labeled_v1_header("alice").serialize().unwrap();
+    invalid_utf8[label_start] = 0xff;
+    cases.push(invalid_utf8);
+    let mut control = labeled_v1_header("alice").serialize().unwrap();
+    control[label_start] = b'\n';
+    cases.push(control);
+
+    for bytes in cases {
+        let error = dexios_core::header::read_header(&mut std::io::Cursor::new(bytes))
+            .expect_err("malformed keyslot metadata should fail");
+        assert!(matches!(
+            error,
+            HeaderReadError::NonZeroActiveKeyslotPadding(0)
+        ));
+    }
+}
+
+#[test]
+fn slot_wrapping_aad_binds_keyslot_metadata() {
+    let index = V1KeyslotIndex::try_from_physical_index(0).unwrap();
+    let unlabeled_aad = support::sample_v1_header()
+        .slot_wrapping_aad_for_physical_slot(index)
+        .unwrap();
+    let alice_aad = labeled_v1_header("alice")
+        .slot_wrapping_aad_for_physical_slot(index)
+        .unwrap();
+    let mallory_aad = labeled_v1_header("mallory")
+        .slot_wrapping_aad_for_physical_slot(index)
+        .unwrap();
+
+    assert_eq!(alice_aad[..unlabeled_aad.len()], unlabeled_aad);
+    assert_eq!(alice_aad.len(), unlabeled_aad.len() + 20);
+    assert_ne!(
+        alice_aad, mallory_aad,
+        "renaming a keyslot must break its unwrap authentication"
+    );
+}
+
+fn extended_v1_header(capacity: usize, active: usize) -> V1Header {
+    let keyslot = support::sample_v1_header().keyslots()[0];
+    let capacity = V1KeyslotCapacity::try_from_usize(capacity).unwrap();
+    let keyslots = V1Keyslots::try_from_vec_with_capacity(vec![keyslot; active], capacity).unwrap();
+    V1Header::new(payload_nonce([7u8; 20]), keyslots).unwrap()
+}
+
+#[test]
+fn extended_v1_header_roundtrips_with_its_keyslot_capacity() {
+    let mut header = extended_v1_header(10, 6);
+    let mut keyslots = header.keyslots_collection().clone();
+    keyslots
+        .insert_physical_slot(
+            V1KeyslotIndex::try_from_physical_index(9).unwrap(),
+            header.keyslots()[0],
+        )
+        .unwrap();
+    assert!(matches!(
+        keyslots.insert_physical_slot(
+            V1KeyslotIndex::try_from_physical_index(10).unwrap(),
+            header.keyslots()[0],
+        ),
+        Err(dexios_core::header::HeaderWriteError::InvalidKeyslotIndex(
+            10
+        ))
+    ));
+    header = header.with_keyslots(keyslots).unwrap();
+
+    let mut bytes = header.serialize().unwrap();
+    assert_eq!(bytes.len(), HEADER_STATIC_LEN + 10 * KEYSLOT_LEN);
+    assert_eq!(bytes.len(), header.serialized_len());
+    assert_eq!((bytes[10], bytes[14]), (0x02, 10));
+
+    bytes.extend_from_slice(b"payload");
+    let mut reader = std::io::Cursor::new(bytes);
+    let ParsedHeader::V1(parsed) = dexios_core::header::read_header(&mut reader).unwrap();
+    assert_eq!(parsed.header(), &header);
+    assert_eq!(parsed.header().keyslot_capacity().get(), 10);
+    assert_eq!(
+        usize::try_from(reader.position()).unwrap(),
+        header.serialized_len()
+    );
+    let physical_indexes = parsed
+        .header()
+        .keyslots()
+        .iter()
+        .map(V1Keyslot::physical_index)
+        .collect::<Vec<_>>();
+    assert_eq!(physical_indexes, [0, 1, 2, 3, 4, 5, 9]);
+
+    let canonical = support::sample_v1_header().serialize().unwrap();
+    assert_eq!(canonical.len(), HEADER_LEN);
+    assert_eq!((canonical[10], canonical[14]), (0x01, 4));
+}
+
+#[test]
+fn v1_header_aad_binds_the_keyslot_capacity() {
+    let index = V1KeyslotIndex::try_from_physical_index(0).unwrap();
+    let canonical = extended_v1_header(4, 2);
+    let five = extended_v1_header(5, 2);
+    let six = extended_v1_header(6, 2);
+
+    assert_eq!(canonical, {
+        let keyslots = V1Keyslots::try_from_vec(canonical.keyslots().to_vec()).unwrap();
+        V1Header::new(payload_nonce([7u8; 20]), keyslots).unwrap()
+    });
+    assert_ne!(canonical.aad(), five.aad());
+    assert_ne!(five.aad(), six.aad());
+    assert_ne!(
+        five.slot_wrapping_aad_for_physical_slot(index).unwrap(),
+        six.slot_wrapping_aad_for_physical_slot(index).unwrap(),
+        "re-framing a header at another capacity must break keyslot unwrap"
+    );
+
+    assert!(matches!(
+        canonical.with_keyslots(five.keyslots_collection().clone()),
+        Err(dexios_core::header::HeaderWriteError::InvalidKeyslotCapacity(5))
+    ));
+    for capacity in [0, 3, 33] {
+        assert!(matches!(
+            V1KeyslotCapacity::try_from_usize(capacity),
+            Err(dexios_core::header::HeaderWriteError::InvalidKeyslotCapacity(actual))
+                if actual == capacity
+        ));
+    }
+    assert!(matches!(
+        V1Keyslots::try_from_vec_with_capacity(
+            vec![canonical.keyslots()[0]; 6],
+            V1KeyslotCapacity::try_from_usize(5).unwrap(),
+        ),
+        Err(dexios_core::header::HeaderWriteError::TooManyKeyslots(6))
+    ));
+}
+
+#[test]
+fn v1_header_rejects_unknown_schema_profiles_and_capacities() {
+    let canonical = support::sample_v1_header().serialize().unwrap();
+    let extended = extended_v1_header(6, 1).serialize().unwrap();
+    let read = |bytes: Vec<u8>| {
+        dexios_core::header::read_header(&mut std::io::Cursor::new(bytes))
+            .expect_err("header should be rejected")
+    };
+
+    let mut unknown_profile = canonical.clone();
+    unknown_profile[10] = 0x03;
+    assert!(matches!(
+        read(unknown_profile),
+        HeaderReadError::UnsupportedVersion([0x00, 0x03])
+    ));
+
+    let mut canonical_as_extended = canonical;
+    canonical_as_extended[10] = 0x02;
+    assert!(matches!(
+        read(canonical_as_extended),
+        HeaderReadError::InvalidKeyslotCount(4)
+    ));
+
+    for capacity in [0, 4, 33, 0xff] {
+        let mut bytes = extended.clone();
+        bytes[14] = capacity;
+        let error = read(bytes);
+        assert!(
+            matches!(error, HeaderReadError::InvalidKeyslotCount(actual) if actual == capacity),
+            "{capacity}: {error:?}"
+        );
+    }
+
+    let mut larger_capacity = extended.clone();
+    larger_capacity[14] = 7;
+    assert!(matches!(
+        read(larger_capacity),
+        HeaderReadError::TruncatedHeader
+    ));
+    assert!(matches!(
+        read(extended[..extended.len() - 1].to_vec()),
+        HeaderReadError::TruncatedHeader
+    ));
+}
+
 #[test]
 fn v1_header_rejects_inactive_keyslot_bytes() {
     let mut bytes = support::sample_v1_header().serialize().unwrap();
@@ -968,7 +1408,7 @@ fn v1_header_rejects_inactive_keyslot_bytes() {
 #[test]
 fn v1_header_rejects_nonzero_reserved_bytes() {
     let mut bytes = support::sample_v1_header().serialize().unwrap();
-    bytes[15] = 1;
+    bytes[15] = 0x80;
 
     let error = dexios_core::header::read_header(&mut std::io::Cursor::new(bytes))
         .expect_err("non-zero reserved byte should fail");
@@ -976,6 +1416,98 @@ fn v1_header_rejects_nonzero_reserved_bytes() {
     assert!(matches!(error, HeaderReadError::NonZeroReservedBytes));
 }
 
+#[test]
+fn header_trailer_flag_roundtrips_and_is_bound_into_aad() {
+    let plain = support::sample_v1_header();
+    let flagged = plain.clone().with_header_trailer();
+    let bytes = flagged.serialize().unwrap();
+
+    assert_eq!(bytes[15], 0x01);
+    assert_eq!(plain.serialize().unwrap()[15], 0x00);
+    assert_ne!(flagged.aad().as_bytes(), plain.aad().as_bytes());
+    assert_eq!(
+        flagged.trailer_len(),
+        HEADER_LEN + HEADER_TRAILER_FOOTER_LEN
+    );
+    assert_eq!(plain.trailer_len(), 0);
+
+    let ParsedHeader::V1(payload) =
+        dexios_core::header::read_header(&mut std::io::Cursor::new(bytes)).unwrap();
+    assert!(payload.header().has_header_trailer());
+    assert_eq!(payload.aad().as_bytes(), flagged.aad().as_bytes());
+    assert!(
+        payload
+            .header()
+            .with_keyslots(flagged.keyslots_collection().clone())
+            .unwrap()
+            .has_header_trailer()
+    );
+}
+
+fn artifact_with_trailer(header: &V1Header, payload_len: usize) -> Vec<u8> {
+    let mut artifact = header.serialize().unwrap();
+    artifact.extend(std::iter::repeat_n(0xA5, payload_len));
+    artifact.extend(header.serialize_trailer().unwrap());
+    artifact
+}
+
+#[test]
+fn read_header_trailer_parses_copy_after_payload() {
+    let header = support::sample_v1_header().with_header_trailer();
+    let mut artifact = artifact_with_trailer(&header, 77);
+    artifact[..HEADER_LEN].fill(0);
+
+    let trailer = header.serialize_trailer().unwrap();
+    assert_eq!(trailer.len(), header.trailer_len());
+    assert_eq!(&trailer[trailer.len() - 8..], &HEADER_TRAILER_MAGIC);
+
+    let ParsedHeader::V1(payload) = dexios_core::header::read_header_trailer(&artifact).unwrap();
+    assert_eq!(payload.header(), &header);
+    assert_eq!(payload.aad().as_bytes(), header.aad().as_bytes());
+}
+
+#[test]
+fn read_header_trailer_reports_missing_and_invalid_trailers() {
+    let plain = support::sample_v1_header();
+    let mut artifact = plain.serialize().unwrap();
+    artifact.extend([0xA5; 64]);
+    assert!(matches!(
+        dexios_core::header::read_header_trailer(&artifact),
+        Err(HeaderReadError::MissingHeaderTrailer)
+    ));
+    assert!(matches!(
+        dexios_core::header::read_header_trailer(&[]),
+        Err(HeaderReadError::MissingHeaderTrailer)
+    ));
+
+    let header = plain.with_header_trailer();
+    let artifact = artifact_with_trailer(&header, 77);
+    let footer_len_at = artifact.len() - HEADER_TRAILER_FOOTER_LEN;
+
+    let mut oversized = artifact.clone();
+    oversized[footer_len_at..footer_len_at + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(matches!(
+        dexios_core::header::read_header_trailer(&oversized),
+        Err(HeaderReadError::InvalidHeaderTrailer)
+    ));
+
+    // a trailer with no room for the leading header before it
+    let trailer_only = header.serialize_trailer().unwrap();
+    assert!(matches!(
+        dexios_core::header::read_header_trailer(&trailer_only),
+        Err(HeaderReadError::InvalidHeaderTrailer)
+    ));
+
+    // a copy that does not declare the trailer it sits in
+    let mut unflagged = artifact;
+    let copy_start = footer_len_at - HEADER_LEN;
+    unflagged[copy_start + 15] = 0;
+    assert!(matches!(
+        dexios_core::header::read_header_trailer(&unflagged),
+        Err(HeaderReadError::InvalidHeaderTrailer)
+    ));
+}
+
 #[test]
 fn v1_primitives_reject_invalid_lengths() {
     assert!(matches!(
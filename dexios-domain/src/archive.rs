@@ -1,7 +1,11 @@
 //! Archive policy types owned by Dexios, not by the ZIP implementation.
 
+mod select;
+
 use std::path::{Component, Path};
 
+pub use select::{ArchivePattern, ArchivePatternError, ArchiveSelection};
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct ArchivePolicy {
     _private: (),
@@ -123,6 +127,52 @@ impl Default for ArchiveLimits {
     }
 }
 
+/// Which file metadata pack records and unpack restores.
+///
+/// `Default` is [`MetadataOptions::none`], keeping archives at manifest
+/// version 1 and unpacked files at the umask and current time. Ownership is
+/// opt-in on both sides: restoring it usually needs privileges and a uid from
+/// another machine rarely means the same user.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each flag is an independent opt-out surfaced directly as a CLI switch"
+)]
+pub struct MetadataOptions {
+    pub mode: bool,
+    pub times: bool,
+    pub owner: bool,
+    pub xattrs: bool,
+}
+
+impl MetadataOptions {
+    #[must_use]
+    pub const fn none() -> Self {
+        Self {
+            mode: false,
+            times: false,
+            owner: false,
+            xattrs: false,
+        }
+    }
+
+    /// Mode bits, timestamps and extended attributes; not ownership.
+    #[must_use]
+    pub const fn defaults() -> Self {
+        Self {
+            mode: true,
+            times: true,
+            owner: false,
+            xattrs: true,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_none(self) -> bool {
+        !(self.mode || self.times || self.owner || self.xattrs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
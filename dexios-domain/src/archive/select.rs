@@ -0,0 +1,232 @@
+//! Include/exclude selection of archive entries by normalized path.
+//!
+//! Patterns are matched against the `/`-separated manifest path. `*` matches
+//! any run of characters within one component, `?` matches one character, and
+//! a `**` component matches zero or more whole components. A pattern with no
+//! `/` matches an entry's final component at any depth, so `*.sql` selects
+//! every SQL file. A pattern that matches a directory also matches everything
+//! beneath it.
+
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArchivePatternError {
+    Empty,
+    NotRelative(String),
+}
+
+impl fmt::Display for ArchivePatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("archive path pattern is empty"),
+            Self::NotRelative(pattern) => write!(
+                f,
+                "archive path pattern must be relative without empty, '.' or '..' components: {pattern}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArchivePatternError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    AnyComponents,
+    Glob(Vec<char>),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchivePattern {
+    segments: Vec<Segment>,
+}
+
+impl ArchivePattern {
+    pub fn new(pattern: &str) -> Result<Self, ArchivePatternError> {
+        if pattern.is_empty() {
+            return Err(ArchivePatternError::Empty);
+        }
+
+        let mut segments = Vec::new();
+        for component in pattern.split('/') {
+            if component.is_empty() || component == "." || component == ".." {
+                return Err(ArchivePatternError::NotRelative(pattern.to_owned()));
+            }
+            segments.push(if component == "**" {
+                Segment::AnyComponents
+            } else {
+                Segment::Glob(component.chars().collect())
+            });
+        }
+        if !pattern.contains('/') && segments != [Segment::AnyComponents] {
+            segments.insert(0, Segment::AnyComponents);
+        }
+
+        Ok(Self { segments })
+    }
+
+    // `path` is a normalized manifest path. The pattern matches it when it
+    // matches the path itself or any of its ancestor directories.
+    fn matches(&self, path: &[u8]) -> bool {
+        let Ok(path) = std::str::from_utf8(path) else {
+            return false;
+        };
+        let components = path.split('/').collect::<Vec<_>>();
+        (1..=components.len()).any(|len| {
+            components
+                .get(..len)
+                .is_some_and(|prefix| match_segments(&self.segments, prefix))
+        })
+    }
+}
+
+/// Which archive entries an unpack extracts.
+///
+/// With no include patterns every entry is included; an entry matching any
+/// exclude pattern is skipped even when an include pattern also matches it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ArchiveSelection {
+    include: Vec<ArchivePattern>,
+    exclude: Vec<ArchivePattern>,
+}
+
+impl ArchiveSelection {
+    #[must_use]
+    pub fn new(
+        include: impl IntoIterator<Item = ArchivePattern>,
+        exclude: impl IntoIterator<Item = ArchivePattern>,
+    ) -> Self {
+        Self {
+            include: include.into_iter().collect(),
+            exclude: exclude.into_iter().collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_all(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    #[must_use]
+    pub fn selects(&self, path: &[u8]) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|pattern| pattern.matches(path)))
+            && !self.exclude.iter().any(|pattern| pattern.matches(path))
+    }
+}
+
+fn match_segments(segments: &[Segment], components: &[&str]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((Segment::AnyComponents, rest)) => (0..=components.len()).any(|skip| {
+            components
+                .get(skip..)
+                .is_some_and(|tail| match_segments(rest, tail))
+        }),
+        Some((Segment::Glob(glob), rest)) => match components.split_first() {
+            Some((component, tail)) => {
+                match_glob(glob, &component.chars().collect::<Vec<_>>())
+                    && match_segments(rest, tail)
+            }
+            None => false,
+        },
+    }
+}
+
+// Iterative wildcard match that backtracks to the most recent `*`.
+fn match_glob(glob: &[char], text: &[char]) -> bool {
+    let (mut g, mut t) = (0_usize, 0_usize);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match (glob.get(g), text.get(t)) {
+            (Some('*'), _) => {
+                star = Some((g, t));
+                g = g.saturating_add(1);
+            }
+            (Some('?'), _) => {
+                g = g.saturating_add(1);
+                t = t.saturating_add(1);
+            }
+            (Some(expected), Some(actual)) if expected == actual => {
+                g = g.saturating_add(1);
+                t = t.saturating_add(1);
+            }
+            _ => match star {
+                Some((star_g, star_t)) => {
+                    let resume = star_t.saturating_add(1);
+                    star = Some((star_g, resume));
+                    g = star_g.saturating_add(1);
+                    t = resume;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    glob.get(g..)
+        .is_some_and(|rest| rest.iter().all(|character| *character == '*'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(include: &[&str], exclude: &[&str]) -> ArchiveSelection {
+        ArchiveSelection::new(
+            include.iter().map(|p| ArchivePattern::new(p).unwrap()),
+            exclude.iter().map(|p| ArchivePattern::new(p).unwrap()),
+        )
+    }
+
+    #[test]
+    fn basename_patterns_match_at_any_depth() {
+        let selection = selection(&["*.sql"], &[]);
+
+        assert!(selection.selects(b"dump.sql"));
+        assert!(selection.selects(b"db/2024/dump.sql"));
+        assert!(!selection.selects(b"db/dump.sql.gz"));
+        assert!(!selection.selects(b"db"));
+    }
+
+    #[test]
+    fn exclude_wins_and_covers_directory_contents() {
+        let selection = selection(&["*.sql"], &["tmp/**"]);
+
+        assert!(selection.selects(b"db/dump.sql"));
+        assert!(!selection.selects(b"tmp"));
+        assert!(!selection.selects(b"tmp/dump.sql"));
+        assert!(!selection.selects(b"tmp/nested/dump.sql"));
+    }
+
+    #[test]
+    fn anchored_patterns_match_from_the_archive_root() {
+        let selection = selection(&["docs/?.md", "src"], &[]);
+
+        assert!(selection.selects(b"docs/a.md"));
+        assert!(!selection.selects(b"docs/ab.md"));
+        assert!(!selection.selects(b"other/docs/a.md"));
+        assert!(selection.selects(b"src/lib/mod.rs"));
+    }
+
+    #[test]
+    fn star_does_not_cross_components() {
+        let selection = selection(&["a/*/c"], &[]);
+
+        assert!(selection.selects(b"a/b/c"));
+        assert!(!selection.selects(b"a/b/x/c"));
+        assert!(selection.selects(b"a/b/c/d"));
+    }
+
+    #[test]
+    fn rejects_empty_and_non_relative_patterns() {
+        assert_eq!(ArchivePattern::new(""), Err(ArchivePatternError::Empty));
+        for pattern in ["/abs", "a//b", "a/../b", "./a", "dir/"] {
+            assert!(
+                matches!(
+                    ArchivePattern::new(pattern),
+                    Err(ArchivePatternError::NotRelative(_))
+                ),
+                "{pattern:?} must be rejected"
+            );
+        }
+    }
+}
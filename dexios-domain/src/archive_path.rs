@@ -94,6 +94,42 @@ impl NormalizedArchivePath {
     }
 }
 
+/// Accepts a symlink target only when it resolves inside the archive root,
+/// read relative to the directory holding `link`.
+///
+/// The target must be relative, with any `..` components leading and no more
+/// of them than `link` has parent directories. `.` components name the
+/// directory they are in and are skipped. Disallowing `..` after a named
+/// component keeps the check lexical: a name that is itself a symlink can only
+/// lead to somewhere its own target check already allowed.
+pub(crate) fn check_symlink_target(
+    link: &NormalizedArchivePath,
+    target: &[u8],
+) -> Result<(), ArchivePathError> {
+    let unsafe_target = || ArchivePathError::Unsafe(link.as_path().to_path_buf());
+    let target = std::str::from_utf8(target).map_err(|_| unsafe_target())?;
+    let mut parents = link.as_path().components().count().saturating_sub(1);
+    let mut named = false;
+    for component in target.split('/') {
+        if component == "." {
+            continue;
+        }
+        if component == ".." && !named {
+            parents = parents.checked_sub(1).ok_or_else(unsafe_target)?;
+            continue;
+        }
+        if component.is_empty()
+            || component == ".."
+            || component.contains('\\')
+            || component.contains('\0')
+        {
+            return Err(unsafe_target());
+        }
+        named = true;
+    }
+    Ok(())
+}
+
 fn reject_empty_or_dot_raw_components(text: &str, original: &Path) -> Result<(), ArchivePathError> {
     for component in text.split(is_raw_path_separator) {
         if component.is_empty() || component == "." || component == ".." {
@@ -197,6 +233,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn symlink_targets_must_stay_inside_the_archive_root() {
+        let link = NormalizedArchivePath::from_path(Path::new("app/lib/libfoo.so")).unwrap();
+        for target in [
+            "libfoo.so.1",
+            "../../assets/logo.png",
+            "../bin/tool",
+            "./libfoo.so.1",
+            "./../bin/./tool",
+        ] {
+            assert!(
+                check_symlink_target(&link, target.as_bytes()).is_ok(),
+                "{target:?} must be accepted"
+            );
+        }
+        for target in [
+            "/etc/passwd",
+            "../../../outside",
+            "sub/../../..",
+            "./../../../outside",
+            "sub/./../tool",
+            "lib//foo",
+            "dir/",
+            "",
+            "..\\..\\outside",
+        ] {
+            assert!(
+                check_symlink_target(&link, target.as_bytes()).is_err(),
+                "{target:?} must be rejected"
+            );
+        }
+    }
+
     #[test]
     fn rejects_non_utf8_manifest_bytes() {
         assert!(NormalizedArchivePath::from_manifest_bytes(b"dir/\xFF.txt").is_err());
@@ -2,16 +2,20 @@
 
 use std::cell::RefCell;
 use std::io::{self, Read, Seek, Write};
+use std::num::NonZeroUsize;
 use std::path::Path;
 
-use core::header::common::HEADER_LEN;
-use core::header::v1::V1Header;
+use core::header::v1::{V1Header, V1KeyslotIndex};
 use core::header::{HeaderReadError, ParsedHeader, ParsedV1Payload, read_header};
+use core::payload::PayloadKind;
 use core::primitives::MasterKey;
-use core::protected::Protected;
-use core::stream::{StreamError, V1FinalAuth, V1PayloadStream};
+use core::stream::{
+    StreamError, V1FinalAuth, V1PayloadDecryptingReader, V1PayloadRandomAccessReader,
+    V1PayloadStream,
+};
+use zeroize::Zeroizing;
 
-use crate::key::decrypt_v1_master_key_with_index;
+use crate::key::{Credential, decrypt_v1_master_key_with_index};
 use crate::storage::cleanup::{CleanupReceipt, ProcessedSourceCleanupResult};
 use crate::storage::identity::{
     IdentityError, OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget,
@@ -42,6 +46,11 @@ pub enum Error {
     WriteDataWithSource(io::Error),
     RewindDataReader,
     RewindDataReaderWithSource(io::Error),
+    RangeRequiresRawFile,
+    RangeOutOfBounds {
+        range: PlaintextRange,
+        plaintext_len: u64,
+    },
     PathIdentity(IdentityError),
     Transaction(TransactionError),
 }
@@ -66,6 +75,9 @@ impl Error {
             Self::DecryptMasterKey => WorkflowErrorClass::IncorrectKey,
             Self::UnsupportedKdf(_) => WorkflowErrorClass::KdfFailure,
             Self::DecryptData => WorkflowErrorClass::AuthenticationFailure,
+            Self::RangeRequiresRawFile | Self::RangeOutOfBounds { .. } => {
+                WorkflowErrorClass::UnsupportedWorkflow
+            }
             Self::PathIdentity(error) => classify_identity_error(error),
             Self::Transaction(error) => classify_transaction_error(error),
             Self::InitializeCiphers | Self::InitializeStreams => WorkflowErrorClass::Other,
@@ -99,6 +111,17 @@ impl std::fmt::Display for Error {
             Self::RewindDataReader | Self::RewindDataReaderWithSource(_) => {
                 f.write_str("Unable to rewind the reader")
             }
+            Self::RangeRequiresRawFile => {
+                f.write_str("Byte ranges can only be decrypted from single-file payloads")
+            }
+            Self::RangeOutOfBounds {
+                range,
+                plaintext_len,
+            } => write!(
+                f,
+                "Range {}:{} is outside the {plaintext_len}-byte plaintext",
+                range.start, range.len
+            ),
             Self::PathIdentity(error) => write!(f, "{error}"),
             Self::Transaction(error) => write!(f, "{error}"),
         }
@@ -126,8 +149,9 @@ pub struct DecryptIntent {
     detached_header_target: Option<ResolvedTarget>,
     output_target: ResolvedTarget,
     cleanup_receipt: CleanupReceipt,
-    raw_key: Protected<Vec<u8>>,
+    credential: Credential,
     on_decrypted_header: Option<OnDecryptedHeaderFn>,
+    threads: NonZeroUsize,
 }
 
 impl std::fmt::Debug for DecryptIntent {
@@ -137,11 +161,12 @@ impl std::fmt::Debug for DecryptIntent {
             .field("detached_header_target", &self.detached_header_target)
             .field("output_target", &self.output_target)
             .field("cleanup_receipt", &self.cleanup_receipt)
-            .field("raw_key", &self.raw_key)
+            .field("credential", &self.credential)
             .field(
                 "on_decrypted_header",
                 &self.on_decrypted_header.as_ref().map(|_| "<callback>"),
             )
+            .field("threads", &self.threads)
             .finish()
     }
 }
@@ -152,7 +177,7 @@ impl DecryptIntent {
         output_path: O,
         output_overwrite: OverwritePolicy,
         detached_header_path: Option<H>,
-        raw_key: Protected<Vec<u8>>,
+        credential: impl Into<Credential>,
         on_decrypted_header: Option<OnDecryptedHeaderFn>,
     ) -> Result<Self, Error>
     where
@@ -181,12 +206,330 @@ impl DecryptIntent {
             detached_header_target,
             output_target,
             cleanup_receipt,
-            raw_key,
+            credential: credential.into(),
             on_decrypted_header,
+            threads: NonZeroUsize::MIN,
+        })
+    }
+
+    /// Opens up to `threads` payload blocks concurrently. Output is still staged
+    /// and committed only after final authentication.
+    #[cfg(feature = "parallel")]
+    #[must_use]
+    pub fn with_threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = threads;
+        self
+    }
+}
+
+/// Decrypts a byte stream whose ends need not be filesystem paths.
+///
+/// A missing input path means the encrypted file (embedded header first) is read
+/// from the reader handed to [`execute_pipe`]; a missing output path means
+/// plaintext is written to the writer handed to it.
+///
+/// A path output is staged and only committed after final stream authentication,
+/// exactly like [`DecryptIntent`]. Plaintext written to a caller writer cannot be
+/// staged: every authenticated chunk is written as soon as it is decrypted, so a
+/// reader on the other end of a pipe may already have consumed bytes before a
+/// later chunk or the final block fails authentication. Those bytes cannot be
+/// recalled. Callers must treat streamed output as uncommitted until
+/// [`execute_pipe`] returns `Ok`, and must report failure (for example with a
+/// non-zero exit status) when it returns `Err`.
+#[derive(Debug)]
+pub struct PipeIntent {
+    input_target: Option<ResolvedTarget>,
+    output_target: Option<ResolvedTarget>,
+    credential: Credential,
+}
+
+impl PipeIntent {
+    pub fn new<P, O>(
+        input_path: Option<P>,
+        output_path: Option<O>,
+        output_overwrite: OverwritePolicy,
+        credential: impl Into<Credential>,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        let mut graph = PathIdentityGraph::new();
+        let input_target = input_path
+            .map(|path| graph.add_existing(path, PathRole::ProcessedSource))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        let output_target = output_path
+            .map(|path| graph.add_output(path, PathRole::Output, output_overwrite))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+
+        Ok(Self {
+            input_target,
+            output_target,
+            credential: credential.into(),
         })
     }
 }
 
+/// Runs a [`PipeIntent`], reading ciphertext from `reader` and writing plaintext
+/// to `writer` for whichever ends have no path.
+///
+/// `Ok` is only returned after the final block authenticated. It carries the
+/// commit receipt when the output is a path, or `None` for a caller writer.
+pub fn execute_pipe<R, W>(
+    intent: PipeIntent,
+    mut reader: R,
+    mut writer: W,
+) -> Result<Option<CommitReceipt>, Error>
+where
+    R: Read,
+    W: Write,
+{
+    let PipeIntent {
+        input_target,
+        output_target,
+        credential,
+    } = intent;
+
+    let stor = crate::storage::FileStorage;
+    let input = input_target
+        .as_ref()
+        .map(|target| stor.read_resolved_existing_no_follow(target))
+        .transpose()
+        .map_err(map_read_storage_error)?;
+    let mut input_reader = input
+        .as_ref()
+        .map(|entry| entry.try_reader().map(RefCell::borrow_mut))
+        .transpose()
+        .map_err(map_read_storage_error)?;
+    let mut reader: &mut dyn Read = match input_reader.as_mut() {
+        Some(file) => &mut **file,
+        None => &mut reader,
+    };
+
+    let parsed = read_header(&mut reader).map_err(map_header_read_error)?;
+    let ParsedHeader::V1(payload) = parsed;
+    let master_key = decrypt_master_key(&payload, credential)?;
+
+    let Some(output_target) = output_target else {
+        decrypt_stream(&payload, reader, &mut writer, master_key)?;
+        return Ok(None);
+    };
+
+    let mut transaction =
+        StagedOutputTransaction::new(output_target).map_err(Error::Transaction)?;
+    let final_auth = transaction
+        .with_writer_result(|file| decrypt_stream(&payload, reader, file, master_key))
+        .map_err(|error| match error {
+            StagedWriteError::Operation(error) => error,
+            StagedWriteError::Transaction(error) => map_decrypt_transaction_error(error),
+        })?;
+    commit_after_final_auth(transaction, final_auth).map(Some)
+}
+
+// Copies uncommitted plaintext chunks to `writer` and returns the final
+// authentication receipt once the last block has been verified.
+fn decrypt_stream<W>(
+    payload: &ParsedV1Payload,
+    reader: &mut dyn Read,
+    writer: &mut W,
+    master_key: MasterKey,
+) -> Result<V1FinalAuth, Error>
+where
+    W: Write,
+{
+    let mut decrypting_reader =
+        V1PayloadDecryptingReader::new(master_key, payload, reader).map_err(map_stream_error)?;
+    // holds unauthenticated plaintext until it is handed to the writer
+    let mut buffer = Zeroizing::new(vec![0u8; core::primitives::BLOCK_SIZE]);
+    loop {
+        let read_count = match decrypting_reader.read_uncommitted(&mut buffer) {
+            Ok(0) => break,
+            Ok(read_count) => read_count,
+            Err(error) => return Err(map_stream_error(error)),
+        };
+        let chunk = buffer.get(..read_count).unwrap_or_default();
+        writer
+            .write_all(chunk)
+            .map_err(Error::WriteDataWithSource)?;
+    }
+
+    writer.flush().map_err(Error::WriteDataWithSource)?;
+    decrypting_reader.finish().map_err(map_stream_error)
+}
+
+/// A span of plaintext bytes, `len` bytes starting at offset `start`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PlaintextRange {
+    pub start: u64,
+    pub len: u64,
+}
+
+/// Decrypts a single byte range of a raw-file payload without opening the rest.
+///
+/// Only the blocks covering the range and the final block are authenticated.
+/// The final block check rejects truncated payloads, but tampering in blocks
+/// outside the range is not detected. A missing output path means the range is
+/// written to the writer handed to [`execute_range`]; a path output is staged
+/// and committed only after every block in the range authenticated.
+#[derive(Debug)]
+pub struct RangeIntent {
+    input_target: ResolvedTarget,
+    detached_header_target: Option<ResolvedTarget>,
+    output_target: Option<ResolvedTarget>,
+    credential: Credential,
+    range: PlaintextRange,
+}
+
+impl RangeIntent {
+    pub fn new<P, O, H>(
+        input_path: P,
+        output_path: Option<O>,
+        output_overwrite: OverwritePolicy,
+        detached_header_path: Option<H>,
+        credential: impl Into<Credential>,
+        range: PlaintextRange,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+        H: AsRef<Path>,
+    {
+        let mut graph = PathIdentityGraph::new();
+        let input_target = graph
+            .add_existing(input_path, PathRole::ProcessedSource)
+            .map_err(Error::PathIdentity)?;
+        let detached_header_target = detached_header_path
+            .map(|path| graph.add_existing(path, PathRole::DetachedHeader))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        let output_target = output_path
+            .map(|path| graph.add_output(path, PathRole::Output, output_overwrite))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+
+        Ok(Self {
+            input_target,
+            detached_header_target,
+            output_target,
+            credential: credential.into(),
+            range,
+        })
+    }
+}
+
+/// Runs a [`RangeIntent`], writing the range to `writer` when it has no output
+/// path.
+///
+/// Bytes written to a caller writer have authenticated block by block; if a
+/// later block in the range fails, earlier bytes have already been written and
+/// the caller must report the failure.
+pub fn execute_range<W>(intent: RangeIntent, mut writer: W) -> Result<Option<CommitReceipt>, Error>
+where
+    W: Write,
+{
+    let RangeIntent {
+        input_target,
+        detached_header_target,
+        output_target,
+        credential,
+        range,
+    } = intent;
+
+    let stor = crate::storage::FileStorage;
+    let input = stor
+        .read_resolved_existing_no_follow(&input_target)
+        .map_err(map_read_storage_error)?;
+    let reader = input.try_reader().map_err(map_read_storage_error)?;
+    let detached_header = detached_header_target
+        .as_ref()
+        .map(|target| stor.read_resolved_existing_no_follow(target))
+        .transpose()
+        .map_err(map_read_storage_error)?;
+    let header_reader = detached_header
+        .as_ref()
+        .map(|entry| entry.try_reader())
+        .transpose()
+        .map_err(map_read_storage_error)?;
+
+    let payload = read_v1_payload(header_reader, reader)?;
+    if payload.header().payload_kind() != PayloadKind::RawFile {
+        return Err(Error::RangeRequiresRawFile);
+    }
+    let master_key = decrypt_master_key(&payload, credential)?;
+
+    // read_v1_payload leaves the reader at the first payload byte, past an
+    // embedded header or a detached-header placeholder.
+    let mut encrypted = reader.borrow_mut();
+    let payload_offset = encrypted
+        .stream_position()
+        .map_err(Error::ReadEncryptedDataWithSource)?;
+    let mut plaintext =
+        V1PayloadRandomAccessReader::new(master_key, &payload, &mut *encrypted, payload_offset)
+            .map_err(map_stream_error)?;
+    plaintext.verify_final_block().map_err(map_stream_error)?;
+    if range
+        .start
+        .checked_add(range.len)
+        .is_none_or(|end| end > plaintext.plaintext_len())
+    {
+        return Err(Error::RangeOutOfBounds {
+            range,
+            plaintext_len: plaintext.plaintext_len(),
+        });
+    }
+
+    let Some(output_target) = output_target else {
+        copy_range(&mut plaintext, range, &mut writer)?;
+        return Ok(None);
+    };
+
+    let mut transaction =
+        StagedOutputTransaction::new(output_target).map_err(Error::Transaction)?;
+    transaction
+        .with_writer_result(|file| copy_range(&mut plaintext, range, file))
+        .map_err(|error| match error {
+            StagedWriteError::Operation(error) => error,
+            StagedWriteError::Transaction(error) => map_decrypt_transaction_error(error),
+        })?;
+    transaction.commit().map(Some).map_err(Error::Transaction)
+}
+
+fn copy_range<R, W>(
+    plaintext: &mut V1PayloadRandomAccessReader<R>,
+    range: PlaintextRange,
+    writer: &mut W,
+) -> Result<(), Error>
+where
+    R: Read + Seek,
+    W: Write,
+{
+    plaintext
+        .seek(io::SeekFrom::Start(range.start))
+        .map_err(Error::ReadEncryptedDataWithSource)?;
+    let mut buffer = Zeroizing::new(vec![0u8; core::primitives::BLOCK_SIZE]);
+    let mut remaining = range.len;
+    while remaining > 0 {
+        let want = usize::try_from(remaining).map_or(buffer.len(), |left| left.min(buffer.len()));
+        let chunk = buffer.get_mut(..want).unwrap_or_default();
+        let read_count = plaintext
+            .read_authenticated(chunk)
+            .map_err(map_stream_error)?;
+        if read_count == 0 {
+            return Err(Error::DecryptData);
+        }
+        writer
+            .write_all(chunk.get(..read_count).unwrap_or_default())
+            .map_err(Error::WriteDataWithSource)?;
+        remaining = remaining.saturating_sub(read_count as u64);
+    }
+
+    writer.flush().map_err(Error::WriteDataWithSource)
+}
+
 #[cfg(test)]
 pub(crate) struct HandleRequest<'a, R, W>
 where
@@ -196,7 +539,7 @@ where
     pub(crate) header_reader: Option<&'a RefCell<R>>,
     pub(crate) reader: &'a RefCell<R>,
     pub(crate) writer: &'a RefCell<W>,
-    pub(crate) raw_key: Protected<Vec<u8>>,
+    pub(crate) raw_key: core::protected::Protected<Vec<u8>>,
     pub(crate) on_decrypted_header: Option<OnDecryptedHeaderFn>,
 }
 
@@ -212,12 +555,13 @@ where
         cb(payload.header());
     }
 
-    let master_key = decrypt_master_key(&payload, req.raw_key)?;
+    let master_key = decrypt_master_key(&payload, req.raw_key.into())?;
     let _final_auth = decrypt_payload_with_master_key(
         &payload,
         req.reader,
         &mut *req.writer.borrow_mut(),
         master_key,
+        NonZeroUsize::MIN,
     )?;
     Ok(())
 }
@@ -228,8 +572,9 @@ pub fn execute(intent: DecryptIntent) -> Result<CommitReceipt, Error> {
         detached_header_target,
         output_target,
         cleanup_receipt: _,
-        raw_key,
+        credential,
         on_decrypted_header,
+        threads,
     } = intent;
 
     let stor = crate::storage::FileStorage;
@@ -252,8 +597,9 @@ pub fn execute(intent: DecryptIntent) -> Result<CommitReceipt, Error> {
         header_reader,
         reader,
         output_target,
-        raw_key,
+        credential,
         on_decrypted_header,
+        threads,
     )
 }
 
@@ -273,8 +619,9 @@ fn execute_transactional_target<R>(
     header_reader: Option<&RefCell<R>>,
     reader: &RefCell<R>,
     output_target: ResolvedTarget,
-    raw_key: Protected<Vec<u8>>,
+    credential: Credential,
     on_decrypted_header: Option<OnDecryptedHeaderFn>,
+    threads: NonZeroUsize,
 ) -> Result<CommitReceipt, Error>
 where
     R: Read + Seek,
@@ -285,12 +632,12 @@ where
         cb(payload.header());
     }
 
-    let master_key = decrypt_master_key(&payload, raw_key)?;
+    let master_key = decrypt_master_key(&payload, credential)?;
     let mut transaction =
         StagedOutputTransaction::new(output_target).map_err(Error::Transaction)?;
     let final_auth = transaction
         .with_writer_result(|writer| {
-            decrypt_payload_with_master_key(&payload, reader, writer, master_key)
+            decrypt_payload_with_master_key(&payload, reader, writer, master_key, threads)
         })
         .map_err(|error| match error {
             StagedWriteError::Operation(error) => error,
@@ -327,7 +674,7 @@ where
             read_header(&mut *header_reader.borrow_mut()).map_err(map_header_read_error)?;
         let ParsedHeader::V1(payload) = parsed;
         // Try reading an empty header from the content.
-        let mut header_bytes = vec![0u8; HEADER_LEN];
+        let mut header_bytes = vec![0u8; payload.header().serialized_len()];
 
         let needs_rewind = match reader.borrow_mut().read_exact(&mut header_bytes) {
             Ok(()) => !header_bytes.into_iter().all(|b| b == 0),
@@ -353,27 +700,51 @@ where
 
 pub(crate) fn decrypt_master_key(
     payload: &ParsedV1Payload,
-    raw_key: Protected<Vec<u8>>,
+    credential: Credential,
 ) -> Result<MasterKey, Error> {
-    let (master_key, _) =
-        decrypt_v1_master_key_with_index(payload.header(), raw_key).map_err(|err| match err {
-            crate::key::Error::UnsupportedKdf(tag) => Error::UnsupportedKdf(tag),
-            _ => Error::DecryptMasterKey,
-        })?;
+    decrypt_master_key_with_index(payload, credential).map(|(master_key, _)| master_key)
+}
 
-    Ok(master_key)
+pub(crate) fn decrypt_master_key_with_index(
+    payload: &ParsedV1Payload,
+    credential: Credential,
+) -> Result<(MasterKey, V1KeyslotIndex), Error> {
+    decrypt_v1_master_key_with_index(payload.header(), credential).map_err(|err| match err {
+        crate::key::Error::UnsupportedKdf(tag) => Error::UnsupportedKdf(tag),
+        _ => Error::DecryptMasterKey,
+    })
 }
 
+#[cfg_attr(
+    not(feature = "parallel"),
+    expect(
+        unused_variables,
+        reason = "the thread count only selects the parallel pipeline"
+    )
+)]
 fn decrypt_payload_with_master_key<R, W>(
     payload: &ParsedV1Payload,
     reader: &RefCell<R>,
     writer: &mut W,
     master_key: MasterKey,
+    threads: NonZeroUsize,
 ) -> Result<V1FinalAuth, Error>
 where
     R: Read + Seek,
     W: Write + Seek,
 {
+    #[cfg(feature = "parallel")]
+    if threads.get() > 1 {
+        return V1PayloadStream::decrypt_file_uncommitted_parallel(
+            master_key,
+            payload,
+            &mut *reader.borrow_mut(),
+            &mut *writer,
+            threads,
+        )
+        .map_err(map_stream_error);
+    }
+
     V1PayloadStream::decrypt_file_uncommitted(
         master_key,
         payload,
@@ -413,9 +784,9 @@ fn map_header_read_error(error: HeaderReadError) -> Error {
         | HeaderReadError::InvalidEncryptedMasterKeyLength(_)
         | HeaderReadError::NonZeroReservedBytes
         | HeaderReadError::NonZeroActiveKeyslotPadding(_)
-        | HeaderReadError::NonZeroInactiveKeyslotPadding(_)) => {
-            Error::DeserializeHeaderWithSource(error)
-        }
+        | HeaderReadError::NonZeroInactiveKeyslotPadding(_)
+        | HeaderReadError::MissingHeaderTrailer
+        | HeaderReadError::InvalidHeaderTrailer) => Error::DeserializeHeaderWithSource(error),
     }
 }
 
@@ -440,11 +811,13 @@ pub(crate) fn map_stream_error(error: StreamError) -> Error {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use core::header::common::HEADER_LEN;
     use std::io::{self, Cursor};
 
     use crate::encrypt;
     use crate::encrypt::tests::PASSWORD;
     use core::kdf::Kdf;
+    use core::protected::Protected;
     use core::stream::StreamError;
 
     struct FailingPayloadReader {
@@ -551,7 +924,8 @@ mod tests {
         .expect("build decrypt intent");
         let DecryptIntent {
             output_target,
-            raw_key,
+            credential,
+            threads,
             ..
         } = intent;
         let reader = RefCell::new(FailingPayloadReader::new(
@@ -559,8 +933,9 @@ mod tests {
             u64::try_from(HEADER_LEN).expect("header length"),
         ));
 
-        let error = execute_transactional_target(None, &reader, output_target, raw_key, None)
-            .expect_err("payload read failure must be reported");
+        let error =
+            execute_transactional_target(None, &reader, output_target, credential, None, threads)
+                .expect_err("payload read failure must be reported");
 
         assert!(matches!(error, Error::ReadEncryptedDataWithSource(_)));
         assert_eq!(error.workflow_class(), WorkflowErrorClass::IoFailure);
@@ -0,0 +1,428 @@
+//! This contains the logic for comparing a packed archive with a directory on
+//! disk without extracting it.
+//!
+//! This is known as "diffing" within Dexios.
+//!
+//! Archive paths are matched under the directory the way unpack would place
+//! them. Every body is decrypted and hashed with BLAKE3, and nothing is
+//! reported until the archive's final block authenticates. Metadata is not
+//! compared.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use core::payload::{ArchiveManifest, ManifestEntry, ManifestEntryKind, PayloadKind};
+use core::stream::V1PayloadDecryptingReader;
+
+use crate::archive_path::NormalizedArchivePath;
+use crate::decrypt;
+use crate::hash;
+use crate::hasher::Blake3Hasher;
+use crate::key::Credential;
+use crate::storage::identity::{IdentityError, PathIdentityGraph, PathRole, ResolvedTarget};
+use crate::storage::{self, FileMode};
+use crate::unpack::{self, UncommittedPlaintextReader};
+use crate::utils;
+use crate::workflow_error::{WorkflowErrorClass, classify_identity_error, classify_storage_error};
+
+#[derive(Debug)]
+pub enum Error {
+    NotADirectory(PathBuf),
+    Storage(storage::Error),
+    PathIdentity(IdentityError),
+    Decrypt(decrypt::Error),
+    Archive(unpack::Error),
+    /// Reading the directory being compared failed.
+    Local {
+        path: PathBuf,
+        source: storage::Error,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotADirectory(path) => write!(f, "Not a directory: {}", path.display()),
+            Self::Storage(inner) => write!(f, "Storage error: {inner}"),
+            Self::PathIdentity(inner) => write!(f, "Path identity error: {inner}"),
+            Self::Decrypt(inner) => write!(f, "Decrypt error: {inner}"),
+            Self::Archive(inner) => write!(f, "{inner}"),
+            Self::Local { path, source } => {
+                write!(f, "Unable to read {}: {source}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Storage(error) | Self::Local { source: error, .. } => Some(error),
+            Self::PathIdentity(error) => Some(error),
+            Self::Decrypt(error) => Some(error),
+            Self::Archive(error) => Some(error),
+            Self::NotADirectory(_) => None,
+        }
+    }
+}
+
+impl Error {
+    #[must_use]
+    pub fn workflow_class(&self) -> WorkflowErrorClass {
+        match self {
+            Self::NotADirectory(_) => WorkflowErrorClass::UnsupportedWorkflow,
+            Self::Storage(error) | Self::Local { source: error, .. } => {
+                classify_storage_error(error)
+            }
+            Self::PathIdentity(error) => classify_identity_error(error),
+            Self::Decrypt(error) => error.workflow_class(),
+            Self::Archive(error) => error.workflow_class(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_resource_pressure(&self) -> bool {
+        storage::error_chain_contains_resource_pressure(self)
+    }
+}
+
+impl From<unpack::Error> for Error {
+    fn from(value: unpack::Error) -> Self {
+        Self::Archive(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct DiffIntent {
+    input_target: ResolvedTarget,
+    detached_header_target: Option<ResolvedTarget>,
+    directory_target: ResolvedTarget,
+    credential: Credential,
+}
+
+impl DiffIntent {
+    pub fn new<P, D>(
+        input_path: P,
+        detached_header_path: Option<&Path>,
+        directory_path: D,
+        credential: impl Into<Credential>,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        D: AsRef<Path>,
+    {
+        let mut graph = PathIdentityGraph::new();
+        let input_target = graph
+            .add_existing(input_path, PathRole::Input)
+            .map_err(Error::PathIdentity)?;
+        let detached_header_target = detached_header_path
+            .map(|path| graph.add_existing(path, PathRole::DetachedHeader))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        let directory_target = graph
+            .add_existing(directory_path.as_ref(), PathRole::Input)
+            .map_err(Error::PathIdentity)?;
+        if !directory_target.is_dir() {
+            return Err(Error::NotADirectory(directory_path.as_ref().to_path_buf()));
+        }
+        graph.validate().map_err(Error::PathIdentity)?;
+
+        Ok(Self {
+            input_target,
+            detached_header_target,
+            directory_target,
+            credential: credential.into(),
+        })
+    }
+}
+
+/// Paths that differ between the archive and the directory, each sorted.
+///
+/// The archive is the old side: `added` paths exist only in the directory and
+/// `removed` paths only in the archive.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ArchiveDiff {
+    added: Vec<PathBuf>,
+    removed: Vec<PathBuf>,
+    modified: Vec<PathBuf>,
+}
+
+impl ArchiveDiff {
+    #[must_use]
+    pub fn added(&self) -> &[PathBuf] {
+        &self.added
+    }
+
+    #[must_use]
+    pub fn removed(&self) -> &[PathBuf] {
+        &self.removed
+    }
+
+    /// Paths present on both sides whose content, link target or kind differs.
+    #[must_use]
+    pub fn modified(&self) -> &[PathBuf] {
+        &self.modified
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+enum ArchiveNode {
+    /// `None` for a hard link whose target is not a file in the archive.
+    File(Option<String>),
+    Directory,
+    Symlink(Vec<u8>),
+}
+
+enum LocalNode {
+    File(PathBuf),
+    Directory,
+    Symlink(Vec<u8>),
+    Other,
+}
+
+pub fn execute(intent: DiffIntent) -> Result<ArchiveDiff, Error> {
+    let DiffIntent {
+        input_target,
+        detached_header_target,
+        directory_target,
+        credential,
+    } = intent;
+
+    let archive = read_archive_nodes(&input_target, detached_header_target.as_ref(), credential)?;
+    let local = read_local_nodes(directory_target.target_path())?;
+
+    let mut diff = ArchiveDiff::default();
+    for (path, node) in &archive {
+        match local.get(path) {
+            None => diff.removed.push(path.clone()),
+            Some(local_node) if !same_content(node, local_node)? => {
+                diff.modified.push(path.clone());
+            }
+            Some(_) => {}
+        }
+    }
+    diff.added = local
+        .into_keys()
+        .filter(|path| !archive.contains_key(path))
+        .collect();
+
+    Ok(diff)
+}
+
+fn read_archive_nodes(
+    input_target: &ResolvedTarget,
+    detached_header_target: Option<&ResolvedTarget>,
+    credential: Credential,
+) -> Result<BTreeMap<PathBuf, ArchiveNode>, Error> {
+    let stor = storage::FileStorage;
+    let input = stor
+        .read_resolved_existing_no_follow(input_target)
+        .map_err(Error::Storage)?;
+    let reader = input.try_reader().map_err(Error::Storage)?;
+    let detached_header = detached_header_target
+        .map(|target| stor.read_resolved_existing_no_follow(target))
+        .transpose()
+        .map_err(Error::Storage)?;
+    let header_reader = detached_header
+        .as_ref()
+        .map(|header| header.try_reader())
+        .transpose()
+        .map_err(Error::Storage)?;
+
+    let payload = decrypt::read_v1_payload(header_reader, reader).map_err(Error::Decrypt)?;
+    let framing = payload.header().payload_framing();
+    if payload.header().payload_kind() != PayloadKind::ManifestArchive
+        || !framing.is_manifest_first()
+    {
+        return Err(unpack::Error::OpenArchive.into());
+    }
+
+    let master_key = decrypt::decrypt_master_key(&payload, credential).map_err(Error::Decrypt)?;
+    let mut encrypted_reader = reader.borrow_mut();
+    let mut plaintext_reader =
+        V1PayloadDecryptingReader::new(master_key, &payload, &mut *encrypted_reader)
+            .map_err(decrypt::map_stream_error)
+            .map_err(Error::Decrypt)?;
+    let (manifest, mut hashes) = {
+        let mut uncommitted_reader = UncommittedPlaintextReader(&mut plaintext_reader);
+        let manifest = ArchiveManifest::read_from(&mut uncommitted_reader)
+            .map_err(unpack::map_payload_error)?;
+        unpack::validate_manifest(&manifest)?;
+        let mut hashes = BTreeMap::new();
+        unpack::for_each_manifest_body(
+            &mut uncommitted_reader,
+            framing,
+            &manifest,
+            |index, body, body_len| {
+                // A recorded digest is checked against the body as it drains,
+                // so the body need not be hashed a second time.
+                if let Some(digest) = manifest
+                    .entries()
+                    .get(index)
+                    .and_then(ManifestEntry::blake3_digest)
+                {
+                    unpack::drain_manifest_body(body, body_len)?;
+                    hashes.insert(index, utils::hex_encode(digest));
+                    return Ok(());
+                }
+                let (hash, hashed) = hash::hash_reader(Blake3Hasher::default(), body)
+                    .map_err(unpack::map_body_io_error)?;
+                if hashed != body_len {
+                    return Err(unpack::Error::ArchivePayload(
+                        core::payload::PayloadError::TruncatedManifest,
+                    ));
+                }
+                hashes.insert(index, hash);
+                Ok(())
+            },
+        )?;
+        unpack::drain_trailing_plaintext_to_final_auth(&mut uncommitted_reader)?;
+        (manifest, hashes)
+    };
+    let _final_auth = plaintext_reader
+        .finish()
+        .map_err(decrypt::map_stream_error)
+        .map_err(Error::Decrypt)?;
+
+    // validate_manifest already accepted every path and link target, so these
+    // conversions cannot fail here.
+    let mut file_hashes = BTreeMap::new();
+    let mut nodes = BTreeMap::new();
+    let mut hard_links = Vec::new();
+    for (index, entry) in manifest.entries().iter().enumerate() {
+        let path = archive_path(entry.normalized_path())?;
+        let node = match entry.kind() {
+            ManifestEntryKind::File => {
+                let hash = hashes.remove(&index);
+                file_hashes.insert(path.clone(), hash.clone());
+                ArchiveNode::File(hash)
+            }
+            ManifestEntryKind::Directory => ArchiveNode::Directory,
+            ManifestEntryKind::Symlink => {
+                ArchiveNode::Symlink(entry.link_target().unwrap_or_default().to_vec())
+            }
+            ManifestEntryKind::HardLink => {
+                hard_links.push((path, archive_path(entry.link_target().unwrap_or_default())?));
+                continue;
+            }
+        };
+        nodes.insert(path, node);
+    }
+    // A hard link restores as a copy of its target's content.
+    for (path, target) in hard_links {
+        let hash = file_hashes.get(&target).cloned().flatten();
+        nodes.insert(path, ArchiveNode::File(hash));
+    }
+
+    Ok(nodes)
+}
+
+fn archive_path(bytes: &[u8]) -> Result<PathBuf, Error> {
+    NormalizedArchivePath::from_manifest_bytes(bytes)
+        .map(|path| path.as_path().to_path_buf())
+        .map_err(|_| {
+            unpack::Error::ArchivePath(PathBuf::from(String::from_utf8_lossy(bytes).into_owned()))
+                .into()
+        })
+}
+
+fn read_local_nodes(root: &Path) -> Result<BTreeMap<PathBuf, LocalNode>, Error> {
+    let mut nodes = BTreeMap::new();
+    for walked in walkdir::WalkDir::new(root).min_depth(1) {
+        let walked = walked.map_err(|error| {
+            let path = error.path().unwrap_or(root).to_path_buf();
+            Error::Local {
+                path,
+                source: match error.into_io_error() {
+                    Some(source) => storage::Error::DirEntriesWithSource(source),
+                    None => storage::Error::DirEntries,
+                },
+            }
+        })?;
+        let Ok(relative) = walked.path().strip_prefix(root) else {
+            continue;
+        };
+        let file_type = walked.file_type();
+        let node = if file_type.is_symlink() {
+            LocalNode::Symlink(local_link_target(walked.path())?)
+        } else if file_type.is_dir() {
+            LocalNode::Directory
+        } else if file_type.is_file() {
+            LocalNode::File(walked.path().to_path_buf())
+        } else {
+            LocalNode::Other
+        };
+        nodes.insert(relative.to_path_buf(), node);
+    }
+    Ok(nodes)
+}
+
+#[cfg(unix)]
+fn local_link_target(path: &Path) -> Result<Vec<u8>, Error> {
+    use std::os::unix::ffi::OsStringExt;
+
+    fs::read_link(path)
+        .map(|target| target.into_os_string().into_vec())
+        .map_err(|source| Error::Local {
+            path: path.to_path_buf(),
+            source: storage::Error::FileAccessWithSource(source),
+        })
+}
+
+#[cfg(not(unix))]
+fn local_link_target(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read_link(path)
+        .map(|target| target.to_string_lossy().replace('\\', "/").into_bytes())
+        .map_err(|source| Error::Local {
+            path: path.to_path_buf(),
+            source: storage::Error::FileAccessWithSource(source),
+        })
+}
+
+fn same_content(archive: &ArchiveNode, local: &LocalNode) -> Result<bool, Error> {
+    match (archive, local) {
+        (ArchiveNode::File(Some(hash)), LocalNode::File(path)) => {
+            Ok(hash_local_file(path)? == *hash)
+        }
+        (ArchiveNode::Directory, LocalNode::Directory) => Ok(true),
+        (ArchiveNode::Symlink(target), LocalNode::Symlink(local_target)) => {
+            Ok(target == local_target)
+        }
+        _ => Ok(false),
+    }
+}
+
+fn hash_local_file(path: &Path) -> Result<String, Error> {
+    let local_error = |source| Error::Local {
+        path: path.to_path_buf(),
+        source,
+    };
+    let file = fs::File::open(path).map_err(|source| {
+        local_error(storage::Error::OpenFileWithSource {
+            mode: FileMode::Read,
+            source,
+        })
+    })?;
+    hash::execute(
+        Blake3Hasher::default(),
+        hash::Request {
+            reader: RefCell::new(file),
+        },
+    )
+    .map_err(|error| {
+        local_error(match error {
+            hash::Error::ReadDataWithSource(source) | hash::Error::OpenFile(source) => {
+                storage::Error::FileAccessWithSource(source)
+            }
+            hash::Error::ResetCursorPosition
+            | hash::Error::ReadData
+            | hash::Error::WalkDirectory(_) => storage::Error::FileAccess,
+        })
+    })
+}
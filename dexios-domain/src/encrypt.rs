@@ -2,16 +2,22 @@
 
 use std::cell::RefCell;
 use std::io::{self, Read, Seek, Write};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use core::cipher::wrap_v1_master_key;
 use core::header::common::Salt;
-use core::header::v1::{V1Header, V1Keyslot, V1KeyslotIndex, V1Keyslots};
+use core::header::common::{MAX_EXTENDED_KEYSLOTS, MAX_KEYSLOTS};
+use core::header::v1::{V1Header, V1Keyslot, V1KeyslotCapacity, V1KeyslotIndex, V1Keyslots};
 use core::kdf::Kdf;
 use core::primitives::{MasterKey, WrappingKey, gen_keyslot_nonce, gen_payload_nonce};
 use core::protected::Protected;
+use core::recipient::{RecipientPublicKey, RecipientWrap};
 use core::stream::{StreamError, V1PayloadEncryptingWriter, V1PayloadStream};
 
+use crate::key::BatchPassphrase;
+use crate::parity::{ParitySidecarTarget, ResolvedParitySidecar};
 use crate::storage::cleanup::{CleanupReceipt, ProcessedSourceCleanupResult};
 use crate::storage::identity::{
     IdentityError, OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget,
@@ -29,6 +35,80 @@ use crate::workflow_error::{
 enum V1PayloadProfile {
     RawFile,
     ManifestArchive,
+    CompressedManifestArchive,
+}
+
+// How the fresh master key is protected in the new header's keyslots, how
+// many keyslots that header reserves, and whether it is repeated in a trailer.
+#[derive(Debug)]
+pub(crate) enum V1KeyProtection {
+    Passphrase {
+        raw_key: Protected<Vec<u8>>,
+        kdf: Kdf,
+        capacity: V1KeyslotCapacity,
+        header_trailer: bool,
+    },
+    Recipients {
+        recipients: Vec<RecipientPublicKey>,
+        capacity: V1KeyslotCapacity,
+        header_trailer: bool,
+    },
+    BatchPassphrase {
+        passphrase: Arc<BatchPassphrase>,
+        kdf: Kdf,
+        capacity: V1KeyslotCapacity,
+        header_trailer: bool,
+    },
+}
+
+impl V1KeyProtection {
+    pub(crate) const fn passphrase(raw_key: Protected<Vec<u8>>, kdf: Kdf) -> Self {
+        Self::Passphrase {
+            raw_key,
+            kdf,
+            capacity: V1KeyslotCapacity::CANONICAL,
+            header_trailer: false,
+        }
+    }
+
+    // Each recipient needs its own keyslot, so more than `MAX_KEYSLOTS`
+    // recipients select an extended-capacity header.
+    pub(crate) fn for_recipients(recipients: Vec<RecipientPublicKey>) -> Result<Self, Error> {
+        if recipients.is_empty() {
+            return Err(Error::NoRecipients);
+        }
+        let capacity = V1KeyslotCapacity::for_keyslot_count(recipients.len())
+            .map_err(|_| Error::TooManyRecipients(recipients.len()))?;
+        Ok(Self::Recipients {
+            recipients,
+            capacity,
+            header_trailer: false,
+        })
+    }
+
+    // Reserves at least `requested` keyslots; recipients always keep one each.
+    pub(crate) fn with_keyslot_capacity(mut self, requested: usize) -> Result<Self, Error> {
+        let requested = V1KeyslotCapacity::try_from_usize(requested)
+            .map_err(|_| Error::InvalidKeyslotCapacity(requested))?;
+        match &mut self {
+            Self::Passphrase { capacity, .. } | Self::BatchPassphrase { capacity, .. } => {
+                *capacity = requested;
+            }
+            Self::Recipients { capacity, .. } => *capacity = (*capacity).max(requested),
+        }
+        Ok(self)
+    }
+
+    pub(crate) const fn with_header_trailer(mut self) -> Self {
+        match &mut self {
+            Self::Passphrase { header_trailer, .. }
+            | Self::Recipients { header_trailer, .. }
+            | Self::BatchPassphrase { header_trailer, .. } => {
+                *header_trailer = true;
+            }
+        }
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -48,6 +128,11 @@ pub enum Error {
     PathIdentity(IdentityError),
     Transaction(TransactionError),
     DetachedPublication(TransactionError),
+    NoRecipients,
+    TooManyRecipients(usize),
+    InvalidKeyslotCapacity(usize),
+    DetachedHeaderTrailer,
+    Parity(crate::parity::Error),
 }
 
 impl Error {
@@ -67,6 +152,11 @@ impl Error {
             Self::Transaction(error) | Self::DetachedPublication(error) => {
                 classify_transaction_error(error)
             }
+            Self::NoRecipients
+            | Self::TooManyRecipients(_)
+            | Self::InvalidKeyslotCapacity(_)
+            | Self::DetachedHeaderTrailer => WorkflowErrorClass::UnsupportedWorkflow,
+            Self::Parity(error) => error.workflow_class(),
             Self::EncryptMasterKey | Self::InitializeStreams | Self::InitializeCiphers => {
                 WorkflowErrorClass::Other
             }
@@ -96,6 +186,19 @@ impl std::fmt::Display for Error {
             Self::DetachedPublication(error) => {
                 write!(f, "Detached publication incomplete: {error}")
             }
+            Self::NoRecipients => f.write_str("At least one recipient is required"),
+            Self::TooManyRecipients(count) => write!(
+                f,
+                "Cannot encrypt to {count} recipients; a V1 header holds at most {MAX_EXTENDED_KEYSLOTS} keyslots"
+            ),
+            Self::InvalidKeyslotCapacity(capacity) => write!(
+                f,
+                "Cannot reserve {capacity} keyslots; a V1 header holds {MAX_KEYSLOTS} to {MAX_EXTENDED_KEYSLOTS}"
+            ),
+            Self::DetachedHeaderTrailer => {
+                f.write_str("Cannot write a header trailer when the header is detached")
+            }
+            Self::Parity(error) => write!(f, "Unable to refresh the parity sidecar: {error}"),
         }
     }
 }
@@ -109,6 +212,7 @@ impl std::error::Error for Error {
             | Self::WriteHeaderWithSource(error) => Some(error),
             Self::PathIdentity(error) => Some(error),
             Self::Transaction(error) | Self::DetachedPublication(error) => Some(error),
+            Self::Parity(error) => Some(error),
             _ => None,
         }
     }
@@ -144,9 +248,10 @@ pub struct EncryptIntent {
     input_target: ResolvedTarget,
     output_target: ResolvedTarget,
     header_target: Option<ResolvedTarget>,
+    parity_sidecar: Option<ResolvedParitySidecar>,
     cleanup_receipt: CleanupReceipt,
-    raw_key: Protected<Vec<u8>>,
-    kdf: Kdf,
+    protection: V1KeyProtection,
+    threads: NonZeroUsize,
 }
 
 impl EncryptIntent {
@@ -158,6 +263,77 @@ impl EncryptIntent {
         raw_key: Protected<Vec<u8>>,
         kdf: Kdf,
     ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Self::with_protection(
+            input_path,
+            output_path,
+            output_overwrite,
+            header,
+            V1KeyProtection::passphrase(raw_key, kdf),
+        )
+    }
+
+    /// Encrypts to X25519 recipients instead of a password: every recipient gets
+    /// its own keyslot, so any one of their identities can decrypt the output.
+    pub fn new_for_recipients<P, O>(
+        input_path: P,
+        output_path: O,
+        output_overwrite: OverwritePolicy,
+        header: Option<DetachedHeaderTarget>,
+        recipients: Vec<RecipientPublicKey>,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Self::with_protection(
+            input_path,
+            output_path,
+            output_overwrite,
+            header,
+            V1KeyProtection::for_recipients(recipients)?,
+        )
+    }
+
+    /// Encrypts one file of a batch under a shared passphrase. Every file of
+    /// the batch that uses the same KDF profile shares that profile's salt, so
+    /// the batch runs one Argon2id derivation per profile.
+    pub fn new_for_batch<P, O>(
+        input_path: P,
+        output_path: O,
+        output_overwrite: OverwritePolicy,
+        header: Option<DetachedHeaderTarget>,
+        passphrase: Arc<BatchPassphrase>,
+        kdf: Kdf,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Self::with_protection(
+            input_path,
+            output_path,
+            output_overwrite,
+            header,
+            V1KeyProtection::BatchPassphrase {
+                passphrase,
+                kdf,
+                capacity: V1KeyslotCapacity::CANONICAL,
+                header_trailer: false,
+            },
+        )
+    }
+
+    fn with_protection<P, O>(
+        input_path: P,
+        output_path: O,
+        output_overwrite: OverwritePolicy,
+        header: Option<DetachedHeaderTarget>,
+        protection: V1KeyProtection,
+    ) -> Result<Self, Error>
     where
         P: AsRef<Path>,
         O: AsRef<Path>,
@@ -177,16 +353,228 @@ impl EncryptIntent {
             .transpose()
             .map_err(Error::PathIdentity)?;
         graph.validate().map_err(Error::PathIdentity)?;
+        let parity_sidecar =
+            ResolvedParitySidecar::existing_for(&output_target).map_err(Error::Parity)?;
 
         Ok(Self {
             input_target,
             output_target,
             header_target,
+            parity_sidecar,
             cleanup_receipt,
-            raw_key,
-            kdf,
+            protection,
+            threads: NonZeroUsize::MIN,
         })
     }
+
+    /// Seals up to `threads` payload blocks concurrently. The ciphertext is
+    /// identical to the single-threaded output.
+    #[cfg(feature = "parallel")]
+    #[must_use]
+    pub fn with_threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Reserves `capacity` keyslots in the header, so up to that many keys
+    /// can be added later with `key add`. More than four keyslots selects the
+    /// extended header profile.
+    pub fn with_keyslot_capacity(mut self, capacity: usize) -> Result<Self, Error> {
+        self.protection = self.protection.with_keyslot_capacity(capacity)?;
+        Ok(self)
+    }
+
+    /// Appends a copy of the header after the final payload block, from which
+    /// `header::repair` can rebuild a damaged leading header. A detached
+    /// header has no leading copy to repair, so it cannot have a trailer.
+    pub fn with_header_trailer(mut self) -> Result<Self, Error> {
+        if self.header_target.is_some() {
+            return Err(Error::DetachedHeaderTrailer);
+        }
+        self.protection = self.protection.with_header_trailer();
+        Ok(self)
+    }
+
+    /// Writes a parity sidecar over the output file, published together with
+    /// it, from which `parity::repair` can rebuild damaged blocks. A detached
+    /// header is not covered.
+    ///
+    /// Without this, a sidecar already next to the output is still written
+    /// again, with the parity block count it had.
+    pub fn with_parity_sidecar(mut self, sidecar: ParitySidecarTarget) -> Result<Self, Error> {
+        let resolved = [&self.input_target, &self.output_target]
+            .into_iter()
+            .chain(self.header_target.as_ref());
+        self.parity_sidecar = Some(
+            sidecar
+                .resolve(resolved, PathRole::ParitySidecar)
+                .map_err(Error::PathIdentity)?,
+        );
+        Ok(self)
+    }
+}
+
+/// Encrypts a byte stream whose ends need not be filesystem paths.
+///
+/// A missing input path means the plaintext is read from the reader handed to
+/// [`execute_pipe`]; a missing output path means the ciphertext is written to the
+/// writer handed to it. Path ends still go through `PathIdentityGraph`, and a
+/// path output is published through a staged transaction as usual. Ciphertext
+/// written to a caller writer is not staged: if encryption fails part-way, the
+/// bytes already written form an incomplete payload that will not decrypt.
+#[derive(Debug)]
+pub struct PipeIntent {
+    input_target: Option<ResolvedTarget>,
+    output_target: Option<ResolvedTarget>,
+    protection: V1KeyProtection,
+}
+
+impl PipeIntent {
+    pub fn new<P, O>(
+        input_path: Option<P>,
+        output_path: Option<O>,
+        output_overwrite: OverwritePolicy,
+        raw_key: Protected<Vec<u8>>,
+        kdf: Kdf,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Self::with_protection(
+            input_path,
+            output_path,
+            output_overwrite,
+            V1KeyProtection::passphrase(raw_key, kdf),
+        )
+    }
+
+    pub fn new_for_recipients<P, O>(
+        input_path: Option<P>,
+        output_path: Option<O>,
+        output_overwrite: OverwritePolicy,
+        recipients: Vec<RecipientPublicKey>,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Self::with_protection(
+            input_path,
+            output_path,
+            output_overwrite,
+            V1KeyProtection::for_recipients(recipients)?,
+        )
+    }
+
+    fn with_protection<P, O>(
+        input_path: Option<P>,
+        output_path: Option<O>,
+        output_overwrite: OverwritePolicy,
+        protection: V1KeyProtection,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        let mut graph = PathIdentityGraph::new();
+        let input_target = input_path
+            .map(|path| graph.add_existing(path, PathRole::ProcessedSource))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        let output_target = output_path
+            .map(|path| graph.add_output(path, PathRole::Output, output_overwrite))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+
+        Ok(Self {
+            input_target,
+            output_target,
+            protection,
+        })
+    }
+
+    /// See [`EncryptIntent::with_keyslot_capacity`].
+    pub fn with_keyslot_capacity(mut self, capacity: usize) -> Result<Self, Error> {
+        self.protection = self.protection.with_keyslot_capacity(capacity)?;
+        Ok(self)
+    }
+
+    /// See [`EncryptIntent::with_header_trailer`].
+    #[must_use]
+    pub fn with_header_trailer(mut self) -> Self {
+        self.protection = self.protection.with_header_trailer();
+        self
+    }
+}
+
+/// Runs a [`PipeIntent`], reading plaintext from `reader` and writing ciphertext
+/// to `writer` for whichever ends have no path.
+///
+/// Returns the commit receipt when the output is a path, or `None` once the final
+/// authenticated block has been written and flushed to `writer`.
+pub fn execute_pipe<R, W>(
+    intent: PipeIntent,
+    mut reader: R,
+    mut writer: W,
+) -> Result<Option<CommitReceipt>, Error>
+where
+    R: Read,
+    W: Write,
+{
+    let PipeIntent {
+        input_target,
+        output_target,
+        protection,
+    } = intent;
+
+    let stor = crate::storage::FileStorage;
+    let input = input_target
+        .as_ref()
+        .map(|target| stor.read_resolved_existing_no_follow(target))
+        .transpose()
+        .map_err(map_input_storage_error)?;
+    let mut input_reader = input
+        .as_ref()
+        .map(|entry| entry.try_reader().map(RefCell::borrow_mut))
+        .transpose()
+        .map_err(map_input_storage_error)?;
+    let reader: &mut dyn Read = match input_reader.as_mut() {
+        Some(file) => &mut **file,
+        None => &mut reader,
+    };
+
+    let (header, master_key) =
+        build_v1_encryption_state_for(protection, V1PayloadProfile::RawFile)?;
+
+    let Some(output_target) = output_target else {
+        encrypt_stream(reader, &mut writer, master_key, &header)?;
+        return Ok(None);
+    };
+
+    let mut transaction =
+        StagedOutputTransaction::new(output_target).map_err(Error::Transaction)?;
+    transaction
+        .with_writer_result(|file| encrypt_stream(reader, file, master_key, &header))
+        .map_err(map_encrypt_staged_write_error)?;
+    transaction.commit().map(Some).map_err(Error::Transaction)
+}
+
+// Writes the header, then streams `reader` through the V1 payload writer.
+fn encrypt_stream<W>(
+    reader: &mut dyn Read,
+    writer: &mut W,
+    master_key: MasterKey,
+    header: &V1Header,
+) -> Result<(), Error>
+where
+    W: Write,
+{
+    let mut payload_writer = begin_v1_payload_writer_with_header(writer, None, header, master_key)?;
+    io::copy(reader, &mut payload_writer).map_err(Error::EncryptFileWithSource)?;
+    finish_v1_payload_writer(payload_writer)?;
+    Ok(())
 }
 
 // Private crate adapter for legacy in-memory callers. Public encrypt workflows
@@ -246,24 +634,36 @@ where
         }
     }
 
-    encrypt_payload(reader, &mut *writer.borrow_mut(), master_key, &header)
+    encrypt_payload(
+        reader,
+        &mut *writer.borrow_mut(),
+        master_key,
+        &header,
+        NonZeroUsize::MIN,
+    )
 }
 
+/// Starts a manifest archive payload; `compressed` selects the framing whose
+/// body frame headers carry a codec byte.
 pub(crate) fn begin_v1_manifest_archive_writer<'a, W>(
     writer: &'a mut W,
     header_writer: Option<&mut dyn Write>,
-    raw_key: Protected<Vec<u8>>,
-    kdf: Kdf,
+    protection: V1KeyProtection,
+    compressed: bool,
 ) -> Result<V1PayloadEncryptingWriter<&'a mut W>, Error>
 where
     W: Write,
 {
-    let (header, master_key) =
-        build_v1_encryption_state_for(raw_key, kdf, V1PayloadProfile::ManifestArchive)?;
+    let payload_profile = if compressed {
+        V1PayloadProfile::CompressedManifestArchive
+    } else {
+        V1PayloadProfile::ManifestArchive
+    };
+    let (header, master_key) = build_v1_encryption_state_for(protection, payload_profile)?;
     begin_v1_payload_writer_with_header(writer, header_writer, &header, master_key)
 }
 
-fn begin_v1_payload_writer_with_header<'a, W>(
+pub(crate) fn begin_v1_payload_writer_with_header<'a, W>(
     writer: &'a mut W,
     header_writer: Option<&mut dyn Write>,
     header: &V1Header,
@@ -298,9 +698,10 @@ pub fn execute(intent: EncryptIntent) -> Result<CommitReceipt, Error> {
         input_target,
         output_target,
         header_target,
+        parity_sidecar,
         cleanup_receipt: _,
-        raw_key,
-        kdf,
+        protection,
+        threads,
     } = intent;
     let stor = crate::storage::FileStorage;
     let input = stor
@@ -308,7 +709,16 @@ pub fn execute(intent: EncryptIntent) -> Result<CommitReceipt, Error> {
         .map_err(map_input_storage_error)?;
     let reader = input.try_reader().map_err(map_input_storage_error)?;
 
-    execute_transactional_targets(reader, output_target, header_target, raw_key, kdf)
+    execute_transactional_targets(
+        reader,
+        OutputTargets {
+            output_target,
+            header_target,
+            parity_sidecar,
+        },
+        protection,
+        threads,
+    )
 }
 
 pub fn execute_transactional(intent: EncryptIntent) -> Result<CommitReceipt, Error> {
@@ -323,54 +733,81 @@ pub fn execute_transactional_with_cleanup(
         .map(|commit_receipt| ProcessedSourceCleanupResult::new(commit_receipt, cleanup_receipt))
 }
 
-fn execute_transactional_targets<R>(
-    reader: &RefCell<R>,
+struct OutputTargets {
     output_target: ResolvedTarget,
     header_target: Option<ResolvedTarget>,
-    raw_key: Protected<Vec<u8>>,
-    kdf: Kdf,
+    parity_sidecar: Option<ResolvedParitySidecar>,
+}
+
+fn execute_transactional_targets<R>(
+    reader: &RefCell<R>,
+    targets: OutputTargets,
+    protection: V1KeyProtection,
+    threads: NonZeroUsize,
 ) -> Result<CommitReceipt, Error>
 where
     R: Read + Seek,
 {
-    let (header, master_key) = build_v1_encryption_state(raw_key, kdf)?;
+    let OutputTargets {
+        output_target,
+        header_target,
+        parity_sidecar,
+    } = targets;
+    let (header, master_key) =
+        build_v1_encryption_state_for(protection, V1PayloadProfile::RawFile)?;
     let header_bytes = header.serialize().map_err(|_| Error::WriteHeader)?;
 
-    if let Some(header_target) = header_target {
-        let mut transaction = LinkedOutputTransaction::new();
-        let output_index = transaction
-            .stage(output_target)
-            .map_err(Error::Transaction)?;
-        let header_index = transaction
-            .stage(header_target)
-            .map_err(Error::Transaction)?;
-
-        transaction
-            .staged_output_mut(header_index)
-            .ok_or(Error::WriteHeader)?
-            .write_all(&header_bytes)
-            .map_err(map_header_transaction_error)?;
-
-        transaction
-            .staged_output_mut(output_index)
-            .ok_or(Error::EncryptFile)?
-            .with_writer_result(|writer| encrypt_payload(reader, writer, master_key, &header))
-            .map_err(map_encrypt_staged_write_error_detached)?;
-
-        transaction
-            .commit_all()
-            .map_err(map_detached_publication_transaction_error)
-    } else {
+    if header_target.is_none() && parity_sidecar.is_none() {
         let mut transaction =
             StagedOutputTransaction::new(output_target).map_err(Error::Transaction)?;
         transaction
             .write_all(&header_bytes)
             .map_err(map_header_transaction_error)?;
         transaction
-            .with_writer_result(|writer| encrypt_payload(reader, writer, master_key, &header))
+            .with_writer_result(|writer| {
+                encrypt_payload(reader, writer, master_key, &header, threads)
+            })
             .map_err(map_encrypt_staged_write_error)?;
-        transaction.commit().map_err(Error::Transaction)
+        return transaction.commit().map_err(Error::Transaction);
+    }
+
+    let mut transaction = LinkedOutputTransaction::new();
+    let output_index = transaction
+        .stage(output_target)
+        .map_err(Error::Transaction)?;
+    let header_index = header_target
+        .map(|target| transaction.stage(target))
+        .transpose()
+        .map_err(Error::Transaction)?;
+    let parity_index = parity_sidecar
+        .map(|sidecar| {
+            transaction
+                .stage(sidecar.target)
+                .map(|index| (index, sidecar.parity_blocks))
+        })
+        .transpose()
+        .map_err(Error::Transaction)?;
+
+    transaction
+        .staged_output_mut(header_index.unwrap_or(output_index))
+        .ok_or(Error::WriteHeader)?
+        .write_all(&header_bytes)
+        .map_err(map_header_transaction_error)?;
+
+    transaction
+        .staged_output_mut(output_index)
+        .ok_or(Error::EncryptFile)?
+        .with_writer_result(|writer| encrypt_payload(reader, writer, master_key, &header, threads))
+        .map_err(map_encrypt_staged_write_error_detached)?;
+
+    if let Some((parity_index, parity_blocks)) = parity_index {
+        crate::parity::stage_sidecar(&mut transaction, output_index, parity_index, parity_blocks)
+            .map_err(map_detached_publication_transaction_error)?;
     }
+
+    transaction
+        .commit_all()
+        .map_err(map_detached_publication_transaction_error)
 }
 
 fn map_input_storage_error(error: crate::storage::Error) -> Error {
@@ -387,16 +824,70 @@ fn map_input_storage_error(error: crate::storage::Error) -> Error {
     }
 }
 
+#[cfg(test)]
 fn build_v1_encryption_state(
     raw_key: Protected<Vec<u8>>,
     kdf: Kdf,
 ) -> Result<(V1Header, MasterKey), Error> {
-    build_v1_encryption_state_for(raw_key, kdf, V1PayloadProfile::RawFile)
+    build_v1_encryption_state_for(
+        V1KeyProtection::passphrase(raw_key, kdf),
+        V1PayloadProfile::RawFile,
+    )
 }
 
 fn build_v1_encryption_state_for(
+    protection: V1KeyProtection,
+    payload_profile: V1PayloadProfile,
+) -> Result<(V1Header, MasterKey), Error> {
+    match protection {
+        V1KeyProtection::Passphrase {
+            raw_key,
+            kdf,
+            capacity,
+            header_trailer,
+        } => build_v1_passphrase_encryption_state(
+            raw_key,
+            kdf,
+            capacity,
+            header_trailer,
+            payload_profile,
+        ),
+        V1KeyProtection::BatchPassphrase {
+            passphrase,
+            kdf,
+            capacity,
+            header_trailer,
+        } => {
+            let (header_salt, key) = passphrase.encryption_key(kdf).map_err(|_| Error::HashKey)?;
+            build_v1_wrapped_passphrase_state(
+                PassphraseKeyslot {
+                    kdf,
+                    salt: header_salt,
+                    key,
+                },
+                capacity,
+                header_trailer,
+                payload_profile,
+            )
+        }
+        V1KeyProtection::Recipients {
+            recipients,
+            capacity,
+            header_trailer,
+        } => build_v1_recipient_encryption_state(
+            &recipients,
+            capacity,
+            header_trailer,
+            payload_profile,
+        ),
+    }
+}
+
+fn build_v1_passphrase_encryption_state(
     raw_key: Protected<Vec<u8>>,
     kdf: Kdf,
+    capacity: V1KeyslotCapacity,
+    header_trailer: bool,
     payload_profile: V1PayloadProfile,
 ) -> Result<(V1Header, MasterKey), Error> {
     let salt_bytes = gen_salt();
@@ -408,27 +899,56 @@ fn build_v1_encryption_state_for(
         .map_err(|_| Error::HashKey)?;
     drop(raw_key);
 
+    build_v1_wrapped_passphrase_state(
+        PassphraseKeyslot {
+            kdf,
+            salt: header_salt,
+            key: WrappingKey::from(key),
+        },
+        capacity,
+        header_trailer,
+        payload_profile,
+    )
+}
+
+// the wrapping key derived from a passphrase, with the profile and salt that
+// the new keyslot records so it can be derived again
+struct PassphraseKeyslot {
+    kdf: Kdf,
+    salt: Salt,
+    key: WrappingKey,
+}
+
+fn build_v1_wrapped_passphrase_state(
+    keyslot: PassphraseKeyslot,
+    capacity: V1KeyslotCapacity,
+    header_trailer: bool,
+    payload_profile: V1PayloadProfile,
+) -> Result<(V1Header, MasterKey), Error> {
+    let PassphraseKeyslot {
+        kdf,
+        salt: header_salt,
+        key,
+    } = keyslot;
     let master_key: MasterKey = gen_master_key();
     let master_key_nonce = gen_keyslot_nonce();
     let payload_nonce = gen_payload_nonce();
     let placeholder_keyslot = V1Keyslot::new(kdf, [0u8; 48], master_key_nonce, header_salt);
     let placeholder_header = build_v1_header_for(
         payload_profile,
+        header_trailer,
         payload_nonce,
-        V1Keyslots::single(placeholder_keyslot),
+        V1Keyslots::try_from_vec_with_capacity(vec![placeholder_keyslot], capacity)
+            .map_err(|_| Error::WriteHeader)?,
     )?;
     let slot_wrapping_aad = placeholder_header
         .slot_wrapping_aad_for_physical_slot(
             V1KeyslotIndex::try_from_physical_index(0).map_err(|_| Error::WriteHeader)?,
         )
         .map_err(|_| Error::WriteHeader)?;
-    let master_key_encrypted = wrap_v1_master_key(
-        WrappingKey::from(key),
-        &master_key,
-        &master_key_nonce,
-        &slot_wrapping_aad,
-    )
-    .map_err(|_| Error::EncryptMasterKey)?;
+    let master_key_encrypted =
+        wrap_v1_master_key(key, &master_key, &master_key_nonce, &slot_wrapping_aad)
+            .map_err(|_| Error::EncryptMasterKey)?;
 
     let keyslot = V1Keyslot::new(
         kdf,
@@ -436,30 +956,109 @@ fn build_v1_encryption_state_for(
         master_key_nonce,
         header_salt,
     );
-    let header = build_v1_header_for(payload_profile, payload_nonce, V1Keyslots::single(keyslot))?;
+    let header = build_v1_header_for(
+        payload_profile,
+        header_trailer,
+        payload_nonce,
+        V1Keyslots::try_from_vec_with_capacity(vec![keyslot], capacity)
+            .map_err(|_| Error::WriteHeader)?,
+    )?;
+
+    Ok((header, master_key))
+}
+
+fn build_v1_recipient_encryption_state(
+    recipients: &[RecipientPublicKey],
+    capacity: V1KeyslotCapacity,
+    header_trailer: bool,
+    payload_profile: V1PayloadProfile,
+) -> Result<(V1Header, MasterKey), Error> {
+    let master_key: MasterKey = gen_master_key();
+    let payload_nonce = gen_payload_nonce();
+
+    let mut wraps = Vec::with_capacity(recipients.len());
+    let mut placeholder_keyslots = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let wrap = RecipientWrap::new(recipient).map_err(|_| Error::EncryptMasterKey)?;
+        placeholder_keyslots.push(V1Keyslot::new_recipient(
+            [0u8; 48],
+            wrap.ephemeral_public_key(),
+        ));
+        wraps.push(wrap);
+    }
+    let placeholder_header = build_v1_header_for(
+        payload_profile,
+        header_trailer,
+        payload_nonce,
+        V1Keyslots::try_from_vec_with_capacity(placeholder_keyslots, capacity)
+            .map_err(|_| Error::WriteHeader)?,
+    )?;
+
+    let mut keyslots = Vec::with_capacity(wraps.len());
+    for (physical_index, wrap) in wraps.into_iter().enumerate() {
+        let slot_wrapping_aad = placeholder_header
+            .slot_wrapping_aad_for_physical_slot(
+                V1KeyslotIndex::try_from_physical_index(physical_index)
+                    .map_err(|_| Error::WriteHeader)?,
+            )
+            .map_err(|_| Error::WriteHeader)?;
+        let ephemeral_public_key = *wrap.ephemeral_public_key();
+        let master_key_encrypted = wrap
+            .wrap_master_key(&master_key, &slot_wrapping_aad)
+            .map_err(|_| Error::EncryptMasterKey)?;
+        keyslots.push(V1Keyslot::new_recipient(
+            *master_key_encrypted.as_bytes(),
+            &ephemeral_public_key,
+        ));
+    }
+    let header = build_v1_header_for(
+        payload_profile,
+        header_trailer,
+        payload_nonce,
+        V1Keyslots::try_from_vec_with_capacity(keyslots, capacity)
+            .map_err(|_| Error::WriteHeader)?,
+    )?;
 
     Ok((header, master_key))
 }
 
 fn build_v1_header_for(
     payload_profile: V1PayloadProfile,
+    header_trailer: bool,
     payload_nonce: core::header::common::PayloadNonce,
     keyslots: V1Keyslots,
 ) -> Result<V1Header, Error> {
-    match payload_profile {
+    let header = match payload_profile {
         V1PayloadProfile::RawFile => V1Header::new(payload_nonce, keyslots),
         V1PayloadProfile::ManifestArchive => {
             V1Header::new_manifest_archive(payload_nonce, keyslots)
         }
+        V1PayloadProfile::CompressedManifestArchive => {
+            V1Header::new_compressed_manifest_archive(payload_nonce, keyslots)
+        }
     }
-    .map_err(|_| Error::WriteHeader)
+    .map_err(|_| Error::WriteHeader)?;
+    // the trailer flag is in the AAD every keyslot is wrapped against
+    Ok(if header_trailer {
+        header.with_header_trailer()
+    } else {
+        header
+    })
 }
 
+#[cfg_attr(
+    not(feature = "parallel"),
+    expect(
+        unused_variables,
+        reason = "the thread count only selects the parallel pipeline"
+    )
+)]
 fn encrypt_payload<R, W>(
     reader: &RefCell<R>,
     writer: &mut W,
     master_key: MasterKey,
     header: &V1Header,
+    threads: NonZeroUsize,
 ) -> Result<(), Error>
 where
     R: Read + Seek,
@@ -470,6 +1069,18 @@ where
         .rewind()
         .map_err(Error::ResetCursorPositionWithSource)?;
 
+    #[cfg(feature = "parallel")]
+    if threads.get() > 1 {
+        return V1PayloadStream::encrypt_file_parallel(
+            master_key,
+            header,
+            &mut *reader,
+            &mut *writer,
+            threads,
+        )
+        .map_err(map_stream_error);
+    }
+
     V1PayloadStream::encrypt_file(master_key, header, &mut *reader, &mut *writer)
         .map_err(map_stream_error)?;
 
@@ -514,8 +1125,9 @@ fn map_encrypt_staged_write_error(error: StagedWriteError<Error>) -> Error {
     }
 }
 
-// On the detached path a mid-payload staged-write transaction failure must classify as a
-// detached-publication failure, matching commit_all's mapping (encrypt-1).
+// On the linked path (detached header or parity sidecar) a mid-payload staged-write
+// transaction failure must classify as a detached-publication failure, matching
+// commit_all's mapping (encrypt-1).
 fn map_encrypt_staged_write_error_detached(error: StagedWriteError<Error>) -> Error {
     match error {
         StagedWriteError::Operation(error) => error,
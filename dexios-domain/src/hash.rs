@@ -1,60 +1,241 @@
-//! This provides functionality for hashing a file with `BLAKE3`, using a stream reader to keep memory usage low.
+//! This provides functionality for hashing a file with any [`Hasher`], such as `BLAKE3` or SHA-256, using a stream reader to keep memory usage low.
+//!
+//! [`hash_files`] hashes many files at once across threads, and [`collect_files`] expands directories into the files beneath them.
 
 use core::primitives::BLOCK_SIZE;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::fs::File;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 use std::{
     cell::RefCell,
-    io::{Read, Seek},
+    io::{self, Read, Seek},
 };
 
-use crate::hasher::Hasher;
+use crate::hasher::{HashAlgorithm, Hasher};
+
+/// Files at least this large are read in [`LARGE_CHUNK_LEN`] chunks and fed to
+/// [`Hasher::write_large`], which lets BLAKE3 spread a single file across threads.
+pub const LARGE_FILE_THRESHOLD: u64 = 64 * 1024 * 1024;
+/// The read size used for files of at least [`LARGE_FILE_THRESHOLD`] bytes.
+pub const LARGE_CHUNK_LEN: usize = 16 * 1024 * 1024;
 
 #[derive(Debug)]
 pub enum Error {
     ResetCursorPosition,
     ReadData,
+    ReadDataWithSource(io::Error),
+    OpenFile(io::Error),
+    WalkDirectory(walkdir::Error),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::ResetCursorPosition => f.write_str("Unable to reset cursor position"),
-            Self::ReadData => f.write_str("Unable to read data"),
+            Self::ReadData | Self::ReadDataWithSource(_) => f.write_str("Unable to read data"),
+            Self::OpenFile(_) => f.write_str("Unable to open file"),
+            Self::WalkDirectory(error) => match error.path() {
+                Some(path) => write!(f, "Unable to read directory: {}", path.display()),
+                None => f.write_str("Unable to read directory"),
+            },
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ReadDataWithSource(error) | Self::OpenFile(error) => Some(error),
+            Self::WalkDirectory(error) => Some(error),
+            _ => None,
+        }
+    }
+}
 
 pub struct Request<R: Read + Seek> {
     pub reader: RefCell<R>,
 }
 
+pub fn execute<R: Read + Seek>(hasher: impl Hasher, req: Request<R>) -> Result<String, Error> {
+    let mut reader = req.reader.borrow_mut();
+    reader.rewind().map_err(|_| Error::ResetCursorPosition)?;
+
+    hash_reader(hasher, &mut *reader)
+        .map(|(hash, _)| hash)
+        .map_err(Error::ReadDataWithSource)
+}
+
+/// Hashes everything `reader` yields until end of file, returning the hash and
+/// the number of bytes hashed.
+///
+/// Unlike [`execute`], this takes readers that cannot seek, such as a body
+/// being decrypted out of an archive.
+pub fn hash_reader<R: Read + ?Sized>(
+    hasher: impl Hasher,
+    reader: &mut R,
+) -> io::Result<(String, u64)> {
+    hash_chunks(hasher, reader, BLOCK_SIZE, Hasher::write)
+}
+
 #[expect(
     clippy::indexing_slicing,
     reason = "read_count is the byte count returned by read() into `buffer`, so buffer[..read_count] is always in bounds"
 )]
-pub fn execute<R: Read + Seek>(mut hasher: impl Hasher, req: Request<R>) -> Result<String, Error> {
-    req.reader
-        .borrow_mut()
-        .rewind()
-        .map_err(|_| Error::ResetCursorPosition)?;
-
-    let mut buffer = vec![0u8; BLOCK_SIZE].into_boxed_slice();
+fn hash_chunks<H: Hasher, R: Read + ?Sized>(
+    mut hasher: H,
+    reader: &mut R,
+    chunk_len: usize,
+    write: fn(&mut H, &[u8]),
+) -> io::Result<(String, u64)> {
+    let mut buffer = vec![0u8; chunk_len].into_boxed_slice();
+    let mut hashed: u64 = 0;
 
     loop {
-        let read_count = req
-            .reader
-            .borrow_mut()
-            .read(&mut buffer)
-            .map_err(|_| Error::ReadData)?;
-        hasher.write(&buffer[..read_count]);
-        if read_count != BLOCK_SIZE {
-            break;
+        let read_count = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read_count) => read_count,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        write(&mut hasher, &buffer[..read_count]);
+        hashed = hashed.saturating_add(read_count as u64);
+    }
+
+    Ok((hasher.finish(), hashed))
+}
+
+/// Expands `inputs` into the files to hash, keeping their order.
+///
+/// Directories are walked recursively in file-name order and contribute the
+/// regular files beneath them; symlinks inside them are not followed. Any
+/// other input is kept as given, so a missing file fails when it is hashed.
+pub fn collect_files<P: AsRef<Path>>(inputs: &[P]) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+
+    for input in inputs {
+        let input = input.as_ref();
+        if !input.is_dir() {
+            files.push(input.to_path_buf());
+            continue;
+        }
+
+        for walked in walkdir::WalkDir::new(input).sort_by_file_name() {
+            let walked = walked.map_err(Error::WalkDirectory)?;
+            if walked.file_type().is_file() {
+                files.push(walked.into_path());
+            }
         }
     }
 
-    Ok(hasher.finish())
+    Ok(files)
+}
+
+struct ProgressReader<'a, R> {
+    inner: R,
+    progress: &'a (dyn Fn(u64) + Sync),
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_count = self.inner.read(buf)?;
+        (self.progress)(read_count as u64);
+        Ok(read_count)
+    }
+}
+
+fn hash_file(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    progress: &(dyn Fn(u64) + Sync),
+) -> Result<String, Error> {
+    let file = File::open(path).map_err(Error::OpenFile)?;
+    let len = file.metadata().map_err(Error::ReadDataWithSource)?.len();
+    let mut reader = ProgressReader {
+        inner: file,
+        progress,
+    };
+
+    let hashed = if len >= LARGE_FILE_THRESHOLD {
+        hash_chunks(
+            algorithm.hasher(),
+            &mut reader,
+            LARGE_CHUNK_LEN,
+            Hasher::write_large,
+        )
+    } else {
+        hash_reader(algorithm.hasher(), &mut reader)
+    };
+    hashed
+        .map(|(hash, _)| hash)
+        .map_err(Error::ReadDataWithSource)
+}
+
+/// Hashes `files` with `algorithm` on up to `threads` worker threads.
+///
+/// `emit` is called on the calling thread once per file, in the order of
+/// `files`, as soon as that file and every file before it have been hashed.
+/// `progress` is called from the workers with the number of bytes just read.
+/// A file that cannot be hashed is reported to `emit` and does not stop the
+/// others.
+pub fn hash_files<F>(
+    files: &[PathBuf],
+    algorithm: HashAlgorithm,
+    threads: NonZeroUsize,
+    progress: &(dyn Fn(u64) + Sync),
+    mut emit: F,
+) where
+    F: FnMut(&Path, Result<String, Error>),
+{
+    let workers = threads.get().min(files.len());
+    if workers <= 1 {
+        for path in files {
+            emit(path, hash_file(path, algorithm, progress));
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = files.get(index) else {
+                        break;
+                    };
+                    if sender
+                        .send((index, hash_file(path, algorithm, progress)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // results arrive in completion order; hold them back until every
+        // earlier file has been emitted
+        let mut pending = BTreeMap::new();
+        let mut emitted = 0usize;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&emitted) {
+                if let Some(path) = files.get(emitted) {
+                    emit(path, result);
+                }
+                emitted = emitted.saturating_add(1);
+            }
+        }
+    });
 }
 
 #[cfg(test)]
@@ -115,7 +296,7 @@ mod tests {
         let mut bytes = text.as_bytes();
         let mut reader = Cursor::new(&mut bytes);
 
-        reader.seek(std::io::SeekFrom::End(0)).unwrap();
+        reader.seek(io::SeekFrom::End(0)).unwrap();
 
         let req = Request {
             reader: RefCell::new(reader),
@@ -128,4 +309,116 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn hash_reader_counts_bytes_across_short_reads() {
+        let text = b"Hello world";
+        let mut reader = Read::chain(&text[..5], &text[5..]);
+
+        let (hash, hashed) = hash_reader(Blake3Hasher::default(), &mut reader).unwrap();
+
+        assert_eq!(hash, blake3::hash(text).to_hex().to_string());
+        assert_eq!(hashed, text.len() as u64);
+    }
+
+    #[test]
+    fn every_algorithm_matches_its_reference_vector() {
+        use crate::hasher::HashAlgorithm;
+
+        let expected = [
+            (
+                HashAlgorithm::Blake3,
+                "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+            ),
+            (
+                HashAlgorithm::Sha256,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                HashAlgorithm::Sha512,
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            ),
+            (
+                HashAlgorithm::Sha3_256,
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+        ];
+
+        for (algorithm, hash) in expected {
+            let (actual, _) = hash_reader(algorithm.hasher(), &mut &b"abc"[..]).unwrap();
+            assert_eq!(actual, hash, "{}", algorithm.name());
+            assert_eq!(actual.len(), algorithm.digest_len() * 2);
+            assert_eq!(HashAlgorithm::from_name(algorithm.name()), Some(algorithm));
+        }
+    }
+
+    #[test]
+    fn large_chunks_hash_like_the_block_loop() {
+        let data = (0..300 * 1024)
+            .map(|index| u8::try_from(index % 251).unwrap())
+            .collect::<Vec<_>>();
+
+        let (hash, hashed) = hash_chunks(
+            Blake3Hasher::default(),
+            &mut &data[..],
+            64 * 1024,
+            Hasher::write_large,
+        )
+        .unwrap();
+
+        assert_eq!(hash, blake3::hash(&data).to_hex().to_string());
+        assert_eq!(hashed, data.len() as u64);
+    }
+
+    #[test]
+    fn hash_files_emits_walked_files_in_order_across_threads() {
+        let test_dir = tempfile::tempdir().expect("temp dir");
+        let root = test_dir.path();
+        std::fs::create_dir_all(root.join("tree/b")).unwrap();
+        std::fs::write(root.join("tree/b/two.txt"), vec![2u8; BLOCK_SIZE * 3]).unwrap();
+        std::fs::write(root.join("tree/a.txt"), b"a").unwrap();
+        std::fs::write(root.join("tree/c.txt"), b"").unwrap();
+        std::fs::write(root.join("single.txt"), b"single").unwrap();
+
+        let files = collect_files(&[
+            root.join("tree"),
+            root.join("single.txt"),
+            root.join("missing"),
+        ])
+        .unwrap();
+        assert_eq!(
+            files,
+            [
+                root.join("tree/a.txt"),
+                root.join("tree/b/two.txt"),
+                root.join("tree/c.txt"),
+                root.join("single.txt"),
+                root.join("missing"),
+            ]
+        );
+
+        let read = AtomicUsize::new(0);
+        let mut emitted = Vec::new();
+        hash_files(
+            &files,
+            HashAlgorithm::Blake3,
+            NonZeroUsize::new(4).unwrap(),
+            &|count| {
+                read.fetch_add(usize::try_from(count).unwrap(), Ordering::Relaxed);
+            },
+            |path, result| emitted.push((path.to_path_buf(), result.ok())),
+        );
+
+        let expected = files
+            .iter()
+            .map(|path| {
+                let hash = std::fs::read(path)
+                    .ok()
+                    .map(|bytes| blake3::hash(&bytes).to_hex().to_string());
+                (path.clone(), hash)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, expected);
+        assert_eq!(read.load(Ordering::Relaxed), BLOCK_SIZE * 3 + 1 + 6);
+    }
 }
@@ -1,5 +1,16 @@
+//! Content hashers for `dexios hash` and the `--hash` flags.
+
+use sha2::digest::{Digest, FixedOutputReset};
+
 pub trait Hasher {
     fn write(&mut self, input: &[u8]);
+
+    /// Like [`Hasher::write`], for inputs of several MiB. Hashers that can
+    /// spread one input across threads override this.
+    fn write_large(&mut self, input: &[u8]) {
+        self.write(input);
+    }
+
     fn finish(&mut self) -> String;
 }
 
@@ -15,12 +26,117 @@ impl Default for Blake3Hasher {
     }
 }
 
+impl Blake3Hasher {
+    /// The raw 32-byte hash of everything written so far.
+    #[must_use]
+    pub fn digest(&self) -> [u8; 32] {
+        *self.inner.finalize().as_bytes()
+    }
+}
+
 impl Hasher for Blake3Hasher {
     fn write(&mut self, input: &[u8]) {
         self.inner.update(input);
     }
 
+    #[cfg(feature = "parallel")]
+    fn write_large(&mut self, input: &[u8]) {
+        self.inner.update_rayon(input);
+    }
+
     fn finish(&mut self) -> String {
         crate::utils::hex_encode(self.inner.finalize().as_bytes())
     }
 }
+
+/// A [`Hasher`] over a `RustCrypto` digest such as SHA-256 or SHA3-256.
+pub struct DigestHasher<D> {
+    inner: D,
+}
+
+impl<D: Default> Default for DigestHasher<D> {
+    fn default() -> Self {
+        Self {
+            inner: D::default(),
+        }
+    }
+}
+
+impl<D: Digest + FixedOutputReset> Hasher for DigestHasher<D> {
+    fn write(&mut self, input: &[u8]) {
+        Digest::update(&mut self.inner, input);
+    }
+
+    fn finish(&mut self) -> String {
+        crate::utils::hex_encode(&Digest::finalize_reset(&mut self.inner))
+    }
+}
+
+pub type Sha256Hasher = DigestHasher<sha2::Sha256>;
+pub type Sha512Hasher = DigestHasher<sha2::Sha512>;
+pub type Sha3_256Hasher = DigestHasher<sha3::Sha3_256>;
+
+impl<H: Hasher + ?Sized> Hasher for Box<H> {
+    fn write(&mut self, input: &[u8]) {
+        (**self).write(input);
+    }
+
+    fn write_large(&mut self, input: &[u8]) {
+        (**self).write_large(input);
+    }
+
+    fn finish(&mut self) -> String {
+        (**self).finish()
+    }
+}
+
+/// The content hash algorithms Dexios can compute.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum HashAlgorithm {
+    #[default]
+    Blake3,
+    Sha256,
+    Sha512,
+    Sha3_256,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [Self; 4] = [Self::Blake3, Self::Sha256, Self::Sha512, Self::Sha3_256];
+
+    /// The lowercase name used on the command line, such as `sha3-256`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Blake3 => "blake3",
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+            Self::Sha3_256 => "sha3-256",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Length of the hash in bytes; its hex form is twice as long.
+    #[must_use]
+    pub const fn digest_len(self) -> usize {
+        match self {
+            Self::Blake3 | Self::Sha256 | Self::Sha3_256 => 32,
+            Self::Sha512 => 64,
+        }
+    }
+
+    #[must_use]
+    pub fn hasher(self) -> Box<dyn Hasher + Send> {
+        match self {
+            Self::Blake3 => Box::new(Blake3Hasher::default()),
+            Self::Sha256 => Box::new(Sha256Hasher::default()),
+            Self::Sha512 => Box::new(Sha512Hasher::default()),
+            Self::Sha3_256 => Box::new(Sha3_256Hasher::default()),
+        }
+    }
+}
@@ -1,12 +1,14 @@
-//! This module contains all Dexios header-related functions, such as dumping the header, restoring a dumped header, or stripping it entirely.
+//! This module contains all Dexios header-related functions, such as dumping the header, restoring a dumped header, repairing it from its trailer copy, or stripping it entirely.
 
 pub mod details;
 pub mod dump;
+pub mod repair;
 pub mod restore;
 pub mod strip;
 
 use core::header::common::HeaderReadError;
 
+use crate::parity;
 use crate::storage::identity::{IdentityError, PathRole};
 use crate::storage::mutation::MutationFreshnessError;
 use crate::storage::transaction::TransactionError;
@@ -37,8 +39,11 @@ pub enum Error {
     TargetChanged,
     DetachedHeaderChanged,
     DetachedHeaderMismatch,
+    MissingHeaderTrailer,
+    HeaderNotDamaged,
     PathIdentity(IdentityError),
     Transaction(TransactionError),
+    Parity(parity::Error),
 }
 
 impl Error {
@@ -58,7 +63,9 @@ impl Error {
             | Self::UnsupportedFormat(_)
             | Self::UnsupportedVersion(_)
             | Self::RetiredV1Layout => WorkflowErrorClass::UnsupportedFormat,
-            Self::UnsupportedRestore => WorkflowErrorClass::UnsupportedWorkflow,
+            Self::UnsupportedRestore | Self::MissingHeaderTrailer | Self::HeaderNotDamaged => {
+                WorkflowErrorClass::UnsupportedWorkflow
+            }
             Self::Write
             | Self::Read
             | Self::WriteIo
@@ -69,6 +76,7 @@ impl Error {
             | Self::DetachedHeaderChanged => WorkflowErrorClass::IoFailure,
             Self::PathIdentity(error) => classify_identity_error(error),
             Self::Transaction(error) => classify_transaction_error(error),
+            Self::Parity(error) => error.workflow_class(),
         }
     }
 }
@@ -76,11 +84,11 @@ impl Error {
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use Error::{
-            DetachedHeaderMismatch, HeaderSizeParse, InvalidFile, InvalidMagic, MalformedV1Header,
-            MissingPayload, PathIdentity, Read, ReadIo, ReadIoWithSource, RetiredV1Layout, Rewind,
-            ShortDetachedHeader, TargetChanged, TargetNotStripped, TargetTooShort,
-            TrailingDetachedHeader, Transaction, UnsupportedFormat, UnsupportedRestore,
-            UnsupportedVersion, Write, WriteIo,
+            DetachedHeaderMismatch, HeaderNotDamaged, HeaderSizeParse, InvalidFile, InvalidMagic,
+            MalformedV1Header, MissingHeaderTrailer, MissingPayload, Parity, PathIdentity, Read,
+            ReadIo, ReadIoWithSource, RetiredV1Layout, Rewind, ShortDetachedHeader, TargetChanged,
+            TargetNotStripped, TargetTooShort, TrailingDetachedHeader, Transaction,
+            UnsupportedFormat, UnsupportedRestore, UnsupportedVersion, Write, WriteIo,
         };
         match self {
             UnsupportedRestore => f.write_str("The provided request is unsupported with this file. It maybe isn't an encrypted file, or it was encrypted in detached mode."),
@@ -120,8 +128,15 @@ impl std::fmt::Display for Error {
             DetachedHeaderMismatch => f.write_str(
                 "Detached header does not match the embedded header; refusing to strip",
             ),
+            MissingHeaderTrailer => f.write_str(
+                "The file has no header trailer to repair from. It was encrypted without one, or the trailer was stripped or damaged.",
+            ),
+            HeaderNotDamaged => {
+                f.write_str("The leading header already matches its trailer copy; nothing to repair")
+            }
             PathIdentity(error) => write!(f, "{error}"),
             Transaction(error) => write!(f, "{error}"),
+            Parity(error) => write!(f, "Unable to refresh the parity sidecar: {error}"),
         }
     }
 }
@@ -133,6 +148,7 @@ impl std::error::Error for Error {
             Self::ReadIoWithSource(error) => Some(error),
             Self::PathIdentity(error) => Some(error),
             Self::Transaction(error) => Some(error),
+            Self::Parity(error) => Some(error),
             Self::UnsupportedRestore
             | Self::InvalidFile
             | Self::InvalidMagic(_)
@@ -152,7 +168,9 @@ impl std::error::Error for Error {
             | Self::TargetNotStripped
             | Self::TargetChanged
             | Self::DetachedHeaderChanged
-            | Self::DetachedHeaderMismatch => None,
+            | Self::DetachedHeaderMismatch
+            | Self::MissingHeaderTrailer
+            | Self::HeaderNotDamaged => None,
         }
     }
 }
@@ -168,7 +186,9 @@ pub(crate) fn map_mutation_freshness_error(error: MutationFreshnessError) -> Err
         | PathRole::UnpackRoot
         | PathRole::MutationTarget
         | PathRole::ProcessedSource
-        | PathRole::CleanupTarget => Error::TargetChanged,
+        | PathRole::CleanupTarget
+        | PathRole::ParitySidecar
+        | PathRole::GeneratedParitySidecar => Error::TargetChanged,
     }
 }
 
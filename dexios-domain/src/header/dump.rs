@@ -71,7 +71,7 @@ pub fn execute_transactional(intent: DumpIntent) -> Result<CommitReceipt, Error>
 }
 
 // Reads only the fixed-size header region instead of slurping the whole (possibly
-// multi-GB) file: `read_header` consumes exactly `HEADER_LEN` bytes from the reader, and
+// multi-GB) file: `read_header` consumes exactly the header's bytes from the reader, and
 // the length is confirmed via `metadata()` (parse-2). `dump` is read-only, so there is no
 // mutation-freshness contract to preserve here.
 fn read_header_only(input: &Entry<File>) -> Result<Vec<u8>, Error> {
@@ -89,7 +89,11 @@ fn read_header_only(input: &Entry<File>) -> Result<Vec<u8>, Error> {
     let parsed = read_header(&mut *file).map_err(Error::from)?;
     let ParsedHeader::V1(payload) = parsed;
     let serialized = payload.header().serialize().map_err(|_| Error::WriteIo)?;
-    debug_assert_eq!(serialized.len(), HEADER_LEN);
+    if len <= serialized.len() as u64 {
+        return Err(Error::MissingPayload {
+            actual_len: usize::try_from(len).unwrap_or(usize::MAX),
+        });
+    }
 
     Ok(serialized)
 }
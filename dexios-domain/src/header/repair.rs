@@ -0,0 +1,101 @@
+//! This provides functionality for rebuilding a damaged leading header from the header trailer that an opt-in encrypt or pack appended after the payload.
+
+use super::Error;
+use std::path::Path;
+
+use core::header::{HeaderReadError, ParsedHeader, read_header_trailer};
+
+use crate::parity::{ResolvedParitySidecar, SidecarFollowingTransaction};
+use crate::storage::identity::{OverwritePolicy, PathIdentityGraph, PathRole};
+use crate::storage::mutation::{MutationFreshnessError, MutationSnapshot};
+use crate::storage::transaction::{CommitReceipt, TransactionError};
+
+#[derive(Debug)]
+pub struct RepairIntent {
+    target: MutationSnapshot,
+    sidecar: Option<ResolvedParitySidecar>,
+}
+
+impl RepairIntent {
+    pub fn new<T>(target_path: T) -> Result<Self, Error>
+    where
+        T: AsRef<Path>,
+    {
+        let target_path = target_path.as_ref().to_path_buf();
+        let mut graph = PathIdentityGraph::new();
+        let target = graph
+            .add_output(
+                &target_path,
+                PathRole::MutationTarget,
+                OverwritePolicy::ReplaceAtCommit,
+            )
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+        let target = MutationSnapshot::read(target).map_err(map_snapshot_read_error)?;
+        let sidecar =
+            ResolvedParitySidecar::existing_for(target.target()).map_err(Error::Parity)?;
+
+        Ok(Self { target, sidecar })
+    }
+}
+
+/// Overwrites the leading header of the target with the copy in its header
+/// trailer.
+///
+/// # Not payload-bound
+///
+/// Like [`super::restore::execute`], this validates the trailer copy for structure
+/// only. The copy is authenticated the same way as the leading header: its static
+/// fields are in the payload AAD and each keyslot is sealed, so a forged or stale
+/// copy leaves the payload undecryptable rather than decrypting wrongly. A leading
+/// header that already matches the copy is reported as [`Error::HeaderNotDamaged`].
+pub fn execute(intent: RepairIntent) -> Result<CommitReceipt, Error> {
+    let RepairIntent { target, sidecar } = intent;
+    let replacement = repaired_header_bytes(target.original_bytes().to_vec())?;
+    target
+        .ensure_fresh()
+        .map_err(super::map_mutation_freshness_error)?;
+    let (target, _) = target.into_parts();
+
+    let mut transaction =
+        SidecarFollowingTransaction::new(target, sidecar).map_err(Error::Transaction)?;
+    transaction
+        .output()
+        .write_all(&replacement)
+        .map_err(map_write_transaction_error)?;
+    transaction.commit().map_err(Error::Transaction)
+}
+
+fn repaired_header_bytes(mut target: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let parsed = read_header_trailer(&target).map_err(|error| match error {
+        HeaderReadError::MissingHeaderTrailer => Error::MissingHeaderTrailer,
+        error => Error::from(error),
+    })?;
+    let ParsedHeader::V1(payload) = parsed;
+    let serialized = payload.header().serialize().map_err(|_| Error::WriteIo)?;
+
+    let target_header = target
+        .get_mut(..serialized.len())
+        .ok_or(Error::MissingHeaderTrailer)?;
+    if *target_header == *serialized {
+        return Err(Error::HeaderNotDamaged);
+    }
+
+    target_header.copy_from_slice(&serialized);
+
+    Ok(target)
+}
+
+fn map_write_transaction_error(error: TransactionError) -> Error {
+    match error {
+        TransactionError::Write { .. } => Error::WriteIo,
+        error => Error::Transaction(error),
+    }
+}
+
+fn map_snapshot_read_error(error: MutationFreshnessError) -> Error {
+    match error {
+        MutationFreshnessError::Read { .. } => Error::ReadIo,
+        error => super::map_mutation_freshness_error(error),
+    }
+}
@@ -5,16 +5,18 @@ use std::io::Cursor;
 use std::path::Path;
 
 use core::header::common::HEADER_LEN;
-use core::header::{ParsedHeader, read_header};
+use core::header::{HeaderReadError, ParsedHeader, read_header};
 
+use crate::parity::{ResolvedParitySidecar, SidecarFollowingTransaction};
 use crate::storage::identity::{OverwritePolicy, PathIdentityGraph, PathRole};
 use crate::storage::mutation::{MutationFreshnessError, MutationSnapshot};
-use crate::storage::transaction::{CommitReceipt, StagedOutputTransaction, TransactionError};
+use crate::storage::transaction::{CommitReceipt, TransactionError};
 
 #[derive(Debug)]
 pub struct RestoreIntent {
     header_target: MutationSnapshot,
     target: MutationSnapshot,
+    sidecar: Option<ResolvedParitySidecar>,
 }
 
 impl RestoreIntent {
@@ -39,10 +41,13 @@ impl RestoreIntent {
         graph.validate().map_err(Error::PathIdentity)?;
         let header_target = read_snapshot(header_target)?;
         let target = read_snapshot(target)?;
+        let sidecar =
+            ResolvedParitySidecar::existing_for(target.target()).map_err(Error::Parity)?;
 
         Ok(Self {
             header_target,
             target,
+            sidecar,
         })
     }
 }
@@ -61,6 +66,7 @@ pub fn execute(intent: RestoreIntent) -> Result<CommitReceipt, Error> {
     let RestoreIntent {
         header_target,
         target,
+        sidecar,
     } = intent;
     let replacement = restored_header_bytes(
         header_target.original_bytes(),
@@ -74,8 +80,10 @@ pub fn execute(intent: RestoreIntent) -> Result<CommitReceipt, Error> {
         .map_err(super::map_mutation_freshness_error)?;
     let (target, _) = target.into_parts();
 
-    let mut transaction = StagedOutputTransaction::new(target).map_err(Error::Transaction)?;
+    let mut transaction =
+        SidecarFollowingTransaction::new(target, sidecar).map_err(Error::Transaction)?;
     transaction
+        .output()
         .write_all(&replacement)
         .map_err(map_write_transaction_error)?;
     transaction.commit().map_err(Error::Transaction)
@@ -86,33 +94,61 @@ pub fn execute_transactional(intent: RestoreIntent) -> Result<CommitReceipt, Err
 }
 
 fn restored_header_bytes(header: &[u8], mut target: Vec<u8>) -> Result<Vec<u8>, Error> {
-    match header.len() {
-        len if len < HEADER_LEN => return Err(Error::ShortDetachedHeader { actual_len: len }),
-        len if len > HEADER_LEN => return Err(Error::TrailingDetachedHeader { actual_len: len }),
-        _ => {}
+    if header.len() < HEADER_LEN {
+        return Err(Error::ShortDetachedHeader {
+            actual_len: header.len(),
+        });
     }
 
     let mut reader = Cursor::new(header);
-    let parsed = read_header(&mut reader).map_err(Error::from)?;
+    let parsed = read_header(&mut reader).map_err(|error| match error {
+        // an extended-capacity header continues past the canonical 512 bytes
+        HeaderReadError::TruncatedHeader => Error::ShortDetachedHeader {
+            actual_len: header.len(),
+        },
+        error => Error::from(error),
+    })?;
     let ParsedHeader::V1(payload) = parsed;
     let serialized = payload.header().serialize().map_err(|_| Error::WriteIo)?;
-    debug_assert_eq!(serialized.len(), HEADER_LEN);
+    if header.len() > serialized.len() {
+        return Err(Error::TrailingDetachedHeader {
+            actual_len: header.len(),
+        });
+    }
+
+    let trailer = if payload.header().has_header_trailer() {
+        payload
+            .header()
+            .serialize_trailer()
+            .map_err(|_| Error::WriteIo)?
+    } else {
+        Vec::new()
+    };
 
     let target_len = target.len();
-    if target_len <= HEADER_LEN {
+    let trailer_start = target_len.saturating_sub(trailer.len());
+    if trailer_start <= serialized.len() {
         return Err(Error::TargetTooShort {
             actual_len: target_len,
         });
     }
 
-    let target_header = target.get_mut(..HEADER_LEN).ok_or(Error::TargetTooShort {
-        actual_len: target_len,
-    })?;
-    if !target_header.iter().all(|b| *b == 0) {
+    let (target_header, rest) = target.split_at_mut(serialized.len());
+    let target_trailer = rest
+        .get_mut(trailer_start.saturating_sub(serialized.len())..)
+        .ok_or(Error::TargetTooShort {
+            actual_len: target_len,
+        })?;
+    if !target_header
+        .iter()
+        .chain(target_trailer.iter())
+        .all(|b| *b == 0)
+    {
         return Err(Error::TargetNotStripped);
     }
 
     target_header.copy_from_slice(&serialized);
+    target_trailer.copy_from_slice(&trailer);
 
     Ok(target)
 }
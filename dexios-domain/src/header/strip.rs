@@ -7,14 +7,16 @@ use std::path::Path;
 use core::header::common::HEADER_LEN;
 use core::header::{ParsedHeader, read_header};
 
+use crate::parity::{ResolvedParitySidecar, SidecarFollowingTransaction};
 use crate::storage::identity::{OverwritePolicy, PathIdentityGraph, PathRole};
 use crate::storage::mutation::{MutationFreshnessError, MutationSnapshot};
-use crate::storage::transaction::{CommitReceipt, StagedOutputTransaction, TransactionError};
+use crate::storage::transaction::{CommitReceipt, TransactionError};
 
 #[derive(Debug)]
 pub struct StripIntent {
     detached_header: MutationSnapshot,
     target: MutationSnapshot,
+    sidecar: Option<ResolvedParitySidecar>,
 }
 
 impl StripIntent {
@@ -39,15 +41,19 @@ impl StripIntent {
         graph.validate().map_err(Error::PathIdentity)?;
         let detached_header = read_snapshot(detached_header)?;
         let target = read_snapshot(target)?;
+        let sidecar =
+            ResolvedParitySidecar::existing_for(target.target()).map_err(Error::Parity)?;
 
         Ok(Self {
             detached_header,
             target,
+            sidecar,
         })
     }
 }
 
-/// Strips (zeroes) the embedded header from the target artifact.
+/// Strips (zeroes) the embedded header, and any header trailer, from the target
+/// artifact.
 ///
 /// # Detached-header backup guard
 ///
@@ -61,6 +67,7 @@ pub fn execute(intent: StripIntent) -> Result<CommitReceipt, Error> {
     let StripIntent {
         detached_header,
         target,
+        sidecar,
     } = intent;
     verify_detached_header_matches_embedded(
         detached_header.original_bytes(),
@@ -75,29 +82,31 @@ pub fn execute(intent: StripIntent) -> Result<CommitReceipt, Error> {
         .map_err(super::map_mutation_freshness_error)?;
     let (target, _) = target.into_parts();
 
-    let mut transaction = StagedOutputTransaction::new(target).map_err(Error::Transaction)?;
+    let mut transaction =
+        SidecarFollowingTransaction::new(target, sidecar).map_err(Error::Transaction)?;
     transaction
+        .output()
         .write_all(&replacement)
         .map_err(map_write_transaction_error)?;
     transaction.commit().map_err(Error::Transaction)
 }
 
-/// Verifies that `detached_header` is exactly a valid 512-byte V1 header that is
+/// Verifies that `detached_header` is exactly a valid V1 header that is
 /// byte-equal to the header region currently embedded in `target`.
 fn verify_detached_header_matches_embedded(
     detached_header: &[u8],
     target: &[u8],
 ) -> Result<(), Error> {
-    if detached_header.len() != HEADER_LEN {
-        return Err(Error::DetachedHeaderMismatch);
-    }
     let mut reader = Cursor::new(detached_header);
-    match read_header(&mut reader) {
-        Ok(ParsedHeader::V1(_)) => {}
+    let header_len = match read_header(&mut reader) {
+        Ok(ParsedHeader::V1(payload)) => payload.header().serialized_len(),
         Err(_) => return Err(Error::DetachedHeaderMismatch),
+    };
+    if detached_header.len() != header_len {
+        return Err(Error::DetachedHeaderMismatch);
     }
     let embedded_header = target
-        .get(..HEADER_LEN)
+        .get(..header_len)
         .ok_or(Error::DetachedHeaderMismatch)?;
     if detached_header != embedded_header {
         return Err(Error::DetachedHeaderMismatch);
@@ -118,12 +127,26 @@ fn stripped_header_bytes(mut original: Vec<u8>) -> Result<Vec<u8>, Error> {
 
     let mut reader = Cursor::new(original.as_slice());
     let parsed = read_header(&mut reader).map_err(Error::from)?;
-    match parsed {
-        ParsedHeader::V1(_) => {}
+    let (header_len, trailer_len) = match parsed {
+        ParsedHeader::V1(payload) => (
+            payload.header().serialized_len(),
+            payload.header().trailer_len(),
+        ),
+    };
+    let trailer_start = original.len().saturating_sub(trailer_len);
+    if trailer_start <= header_len {
+        return Err(Error::MissingPayload {
+            actual_len: original.len(),
+        });
     }
 
-    let header = original.get_mut(..HEADER_LEN).ok_or(Error::InvalidFile)?;
+    let header = original.get_mut(..header_len).ok_or(Error::InvalidFile)?;
     header.fill(0);
+    // a trailer copy left in place would undo the strip
+    let trailer = original
+        .get_mut(trailer_start..)
+        .ok_or(Error::InvalidFile)?;
+    trailer.fill(0);
 
     Ok(original)
 }
@@ -1,27 +1,36 @@
 use core::cipher::{unwrap_v1_master_key, wrap_v1_master_key};
-use core::header::common::{HEADER_LEN, KeyslotNonce, Salt};
+use core::header::common::{KEYSLOT_LABEL_LEN, Salt};
 use core::header::v1::{
-    EncryptedMasterKey, KeyslotKdf, V1Header, V1Keyslot, V1KeyslotIndex, V1Keyslots,
+    EncryptedMasterKey, KeyslotKdf, KeyslotMetadata, V1Header, V1Keyslot, V1KeyslotIndex,
+    V1Keyslots,
 };
 use core::header::{HeaderReadError, ParsedHeader, read_header};
-use core::kdf::Kdf;
+use core::kdf::{DERIVED_KEY_LEN, Kdf, KdfError};
 use core::primitives::{
     ENCRYPTED_MASTER_KEY_LEN, MasterKey, WrappingKey, gen_keyslot_nonce, gen_salt,
 };
 use core::protected::Protected;
-use std::io::Cursor;
+use core::recipient::RecipientIdentity;
+use core::stream::{StreamError, V1PayloadEncryptingWriter, V1PayloadStream};
+use std::io::{BufReader, Cursor, Write};
 use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::decrypt;
+use crate::parity::{self, ResolvedParitySidecar, SidecarFollowingTransaction};
 
 use crate::storage::identity::{
     IdentityError, OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget,
 };
-use crate::storage::mutation::{MutationFreshnessError, MutationSnapshot};
-use crate::storage::transaction::{CommitReceipt, StagedOutputTransaction, TransactionError};
+use crate::storage::mutation::{MutationDigest, MutationFreshnessError, MutationSnapshot};
+use crate::storage::transaction::{CommitReceipt, StagedWriteError, TransactionError};
 use crate::workflow_error::WorkflowErrorClass;
 
 pub mod add;
 pub mod change;
 pub mod delete;
+pub mod rotate;
 pub mod verify;
 
 #[derive(Debug)]
@@ -49,6 +58,14 @@ pub enum Error {
     TargetChanged,
     CannotRemoveFinalV1Keyslot,
     CannotAddV1KeyslotWithoutReencrypt,
+    CannotRotateDetachedHeader,
+    NoRotationCredentials,
+    Reencrypt(decrypt::Error),
+    Parity(parity::Error),
+    InvalidKeyslotLabel,
+    KeyslotLabelNotFound(String),
+    DuplicateKeyslotLabel(String),
+    EmptyKeyslot(usize),
 }
 
 impl Error {
@@ -74,7 +91,15 @@ impl Error {
             Self::Transaction(error) => crate::workflow_error::classify_transaction_error(error),
             Self::TooManyKeyslots
             | Self::CannotRemoveFinalV1Keyslot
-            | Self::CannotAddV1KeyslotWithoutReencrypt => WorkflowErrorClass::UnsupportedWorkflow,
+            | Self::CannotAddV1KeyslotWithoutReencrypt
+            | Self::CannotRotateDetachedHeader
+            | Self::NoRotationCredentials
+            | Self::InvalidKeyslotLabel
+            | Self::KeyslotLabelNotFound(_)
+            | Self::DuplicateKeyslotLabel(_)
+            | Self::EmptyKeyslot(_) => WorkflowErrorClass::UnsupportedWorkflow,
+            Self::Reencrypt(error) => error.workflow_class(),
+            Self::Parity(error) => error.workflow_class(),
             Self::MasterKeyEncrypt | Self::CipherInit => WorkflowErrorClass::Other,
         }
     }
@@ -106,6 +131,23 @@ impl std::fmt::Display for Error {
             Self::CannotAddV1KeyslotWithoutReencrypt => {
                 f.write_str("Cannot add a V1 keyslot without re-encrypting the payload")
             }
+            Self::CannotRotateDetachedHeader => {
+                f.write_str("Cannot rotate the master key of a detached header")
+            }
+            Self::NoRotationCredentials => {
+                f.write_str("Rotating the master key requires at least one credential")
+            }
+            Self::Reencrypt(error) => write!(f, "Unable to re-encrypt the payload: {error}"),
+            Self::Parity(error) => write!(f, "Unable to refresh the parity sidecar: {error}"),
+            Self::InvalidKeyslotLabel => write!(
+                f,
+                "Keyslot labels must be 1 to {KEYSLOT_LABEL_LEN} bytes with no control characters"
+            ),
+            Self::KeyslotLabelNotFound(label) => write!(f, "No keyslot is labeled {label:?}"),
+            Self::DuplicateKeyslotLabel(label) => {
+                write!(f, "A keyslot is already labeled {label:?}")
+            }
+            Self::EmptyKeyslot(index) => write!(f, "Keyslot {index} is not in use"),
             Self::CipherInit => f.write_str("Unable to initialize a cipher"),
             Self::KeyHash => f.write_str("Unable to hash your key"),
             Self::TooManyKeyslots => {
@@ -123,9 +165,162 @@ impl std::fmt::Display for Error {
     }
 }
 
+/// What a caller presents to open a keyslot.
+///
+/// Passphrases and keyfiles only open Argon2id slots; identities from
+/// `dexios keygen` only open X25519 recipient slots. Callers say which one they
+/// hold, so key material is never classified by what its bytes look like.
+#[derive(Debug)]
+pub enum Credential {
+    Passphrase(Protected<Vec<u8>>),
+    Identity(RecipientIdentity),
+    BatchPassphrase(Arc<BatchPassphrase>),
+}
+
+impl From<Protected<Vec<u8>>> for Credential {
+    fn from(raw_key: Protected<Vec<u8>>) -> Self {
+        Self::Passphrase(raw_key)
+    }
+}
+
+impl From<RecipientIdentity> for Credential {
+    fn from(identity: RecipientIdentity) -> Self {
+        Self::Identity(identity)
+    }
+}
+
+impl From<Arc<BatchPassphrase>> for Credential {
+    fn from(passphrase: Arc<BatchPassphrase>) -> Self {
+        Self::BatchPassphrase(passphrase)
+    }
+}
+
+/// A passphrase shared by every file of a batch.
+///
+/// It keeps each wrapping key it derives, by KDF profile and salt, so a batch
+/// pays for one Argon2id derivation per salt instead of one per file. Files
+/// encrypted with it share one salt per profile; each still gets a fresh master
+/// key and fresh nonces, so only the cost of guessing the passphrase is shared.
+pub struct BatchPassphrase {
+    raw_key: Protected<Vec<u8>>,
+    derived: Mutex<Vec<DerivedWrappingKey>>,
+}
+
+struct DerivedWrappingKey {
+    kdf: Kdf,
+    salt: Salt,
+    key: Protected<[u8; DERIVED_KEY_LEN]>,
+}
+
+impl std::fmt::Debug for BatchPassphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchPassphrase").finish_non_exhaustive()
+    }
+}
+
+impl BatchPassphrase {
+    #[must_use]
+    pub const fn new(raw_key: Protected<Vec<u8>>) -> Self {
+        Self {
+            raw_key,
+            derived: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// How many Argon2id derivations this passphrase has run so far.
+    #[must_use]
+    pub fn derivations(&self) -> usize {
+        self.derived_keys().len()
+    }
+
+    pub(crate) fn raw_key(&self) -> Protected<Vec<u8>> {
+        Protected::new(self.raw_key.with_exposed(Clone::clone))
+    }
+
+    /// The salt new keyslots of `kdf` use, with its wrapping key. The first
+    /// call for a profile generates the salt; later calls reuse it.
+    pub(crate) fn encryption_key(&self, kdf: Kdf) -> Result<(Salt, WrappingKey), KdfError> {
+        let mut derived = self.derived_keys();
+        if let Some(entry) = derived.iter().find(|entry| entry.kdf == kdf) {
+            return Ok((entry.salt, entry.wrapping_key()));
+        }
+
+        let salt = Salt::new(gen_salt());
+        let entry = DerivedWrappingKey::derive(&self.raw_key, kdf, salt)?;
+        let wrapping_key = entry.wrapping_key();
+        derived.push(entry);
+        drop(derived);
+        Ok((salt, wrapping_key))
+    }
+
+    /// The wrapping key for a keyslot of `kdf` under `salt`, derived at most once.
+    pub(crate) fn wrapping_key(&self, kdf: Kdf, salt: Salt) -> Result<WrappingKey, KdfError> {
+        let mut derived = self.derived_keys();
+        if let Some(entry) = derived
+            .iter()
+            .find(|entry| entry.kdf == kdf && entry.salt == salt)
+        {
+            return Ok(entry.wrapping_key());
+        }
+
+        let entry = DerivedWrappingKey::derive(&self.raw_key, kdf, salt)?;
+        let wrapping_key = entry.wrapping_key();
+        derived.push(entry);
+        drop(derived);
+        Ok(wrapping_key)
+    }
+
+    fn derived_keys(&self) -> MutexGuard<'_, Vec<DerivedWrappingKey>> {
+        self.derived.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl DerivedWrappingKey {
+    fn derive(raw_key: &Protected<Vec<u8>>, kdf: Kdf, salt: Salt) -> Result<Self, KdfError> {
+        let key = kdf.derive(raw_key, &salt.to_kdf_salt())?;
+        Ok(Self { kdf, salt, key })
+    }
+
+    fn wrapping_key(&self) -> WrappingKey {
+        WrappingKey::from(Protected::new(self.key.with_exposed(|key| *key)))
+    }
+}
+
+/// Finds the keyslot that `credential` opens and returns the master key with
+/// that slot's physical index.
+///
+/// A passphrase is tried against Argon2id slots, each derived with the cost
+/// profile that slot records; an identity is tried against recipient slots.
 pub fn decrypt_v1_master_key_with_index(
     header: &V1Header,
-    raw_key_old: Protected<Vec<u8>>,
+    credential: impl Into<Credential>,
+) -> Result<(MasterKey, V1KeyslotIndex), Error> {
+    decrypt_v1_master_key_with_credential(header, &credential.into())
+}
+
+pub(in crate::key) fn decrypt_v1_master_key_with_credential(
+    header: &V1Header,
+    credential: &Credential,
+) -> Result<(MasterKey, V1KeyslotIndex), Error> {
+    match credential {
+        Credential::Passphrase(raw_key) => {
+            decrypt_v1_master_key_with_passphrase(header, |kdf, salt| {
+                kdf.derive(raw_key, &salt.to_kdf_salt())
+                    .map(WrappingKey::from)
+            })
+        }
+        Credential::BatchPassphrase(passphrase) => {
+            decrypt_v1_master_key_with_passphrase(header, |kdf, salt| {
+                passphrase.wrapping_key(kdf, salt)
+            })
+        }
+        Credential::Identity(identity) => decrypt_v1_master_key_with_identity(header, identity),
+    }
+}
+
+fn decrypt_v1_master_key_with_passphrase(
+    header: &V1Header,
+    derive: impl Fn(Kdf, Salt) -> Result<WrappingKey, KdfError>,
 ) -> Result<(MasterKey, V1KeyslotIndex), Error> {
     let keyslots = header.keyslots_collection();
     let mut index = None;
@@ -135,17 +330,15 @@ pub fn decrypt_v1_master_key_with_index(
     // we need the index, so we can't use `decrypt_master_key()`
     for keyslot in keyslots.as_slice() {
         let physical_index = keyslot.physical_index();
-        let kdf = match keyslot.kdf() {
-            KeyslotKdf::Argon2id => Kdf::Argon2id,
-            KeyslotKdf::UnsupportedArgon2id => {
-                saw_unsupported_kdf = Some([0xDF, 0x02]);
-                continue;
-            }
+        if keyslot.kdf() == KeyslotKdf::UnsupportedArgon2id {
+            saw_unsupported_kdf = Some([0xDF, 0x02]);
+            continue;
+        }
+        // each slot records its own Argon2id cost profile
+        let Some(kdf) = keyslot.kdf().password_kdf() else {
+            continue;
         };
-        let salt = keyslot.salt().to_kdf_salt();
-        let key_old = kdf
-            .derive(&raw_key_old, &salt)
-            .map_err(|_| Error::KeyHash)?;
+        let key_old = derive(kdf, *keyslot.salt()).map_err(|_| Error::KeyHash)?;
 
         let slot_index = V1KeyslotIndex::try_from_physical_index(physical_index)
             .map_err(|_| Error::HeaderDeserialize)?;
@@ -154,7 +347,7 @@ pub fn decrypt_v1_master_key_with_index(
             .slot_wrapping_aad_for_physical_slot(slot_index)
             .map_err(|_| Error::HeaderDeserialize)?;
         let master_key_result = unwrap_v1_master_key(
-            WrappingKey::from(key_old),
+            key_old,
             &encrypted_master_key,
             keyslot.nonce(),
             &slot_wrapping_aad,
@@ -170,8 +363,6 @@ pub fn decrypt_v1_master_key_with_index(
         break;
     }
 
-    drop(raw_key_old);
-
     let Some(index) = index else {
         if let Some(tag) = saw_unsupported_kdf {
             return Err(Error::UnsupportedKdf(tag));
@@ -186,6 +377,48 @@ pub fn decrypt_v1_master_key_with_index(
     Ok((master_key, index))
 }
 
+fn decrypt_v1_master_key_with_identity(
+    header: &V1Header,
+    identity: &RecipientIdentity,
+) -> Result<(MasterKey, V1KeyslotIndex), Error> {
+    for keyslot in header.keyslots_collection().as_slice() {
+        if keyslot.kdf() != KeyslotKdf::X25519Recipient {
+            continue;
+        }
+        let index = V1KeyslotIndex::try_from_physical_index(keyslot.physical_index())
+            .map_err(|_| Error::HeaderDeserialize)?;
+        if let Ok(master_key) = unwrap_recipient_keyslot(header, index, identity) {
+            return Ok((master_key, index));
+        }
+    }
+
+    Err(Error::IncorrectKey)
+}
+
+fn unwrap_recipient_keyslot(
+    header: &V1Header,
+    index: V1KeyslotIndex,
+    identity: &RecipientIdentity,
+) -> Result<MasterKey, Error> {
+    let keyslot = header
+        .keyslots_collection()
+        .get_physical(index.get())
+        .ok_or(Error::IncorrectKey)?;
+    let ephemeral_public_key = keyslot.ephemeral_public_key().ok_or(Error::IncorrectKey)?;
+    let encrypted_master_key = EncryptedMasterKey::new(*keyslot.encrypted_master_key());
+    let slot_wrapping_aad = header
+        .slot_wrapping_aad_for_physical_slot(index)
+        .map_err(|_| Error::HeaderDeserialize)?;
+
+    identity
+        .unwrap_master_key(
+            &ephemeral_public_key,
+            &encrypted_master_key,
+            &slot_wrapping_aad,
+        )
+        .map_err(|_| Error::IncorrectKey)
+}
+
 pub(crate) fn decrypt_v1_master_key_at_index(
     header: &V1Header,
     index: V1KeyslotIndex,
@@ -196,8 +429,12 @@ pub(crate) fn decrypt_v1_master_key_at_index(
         .get_physical(index.get())
         .ok_or(Error::IncorrectKey)?;
     let kdf = match keyslot.kdf() {
-        KeyslotKdf::Argon2id => Kdf::Argon2id,
         KeyslotKdf::UnsupportedArgon2id => return Err(Error::UnsupportedKdf([0xDF, 0x02])),
+        // a passphrase never opens a recipient slot
+        KeyslotKdf::X25519Recipient => return Err(Error::IncorrectKey),
+        KeyslotKdf::Argon2id => Kdf::Argon2id,
+        KeyslotKdf::Argon2idInteractive => Kdf::Argon2idInteractive,
+        KeyslotKdf::Argon2idParanoid => Kdf::Argon2idParanoid,
     };
     let salt = keyslot.salt().to_kdf_salt();
     let key = kdf.derive(&raw_key, &salt).map_err(|_| Error::KeyHash)?;
@@ -222,6 +459,8 @@ impl std::error::Error for Error {
             Self::ReadIoWithSource(error) => Some(error),
             Self::PathIdentity(error) => Some(error),
             Self::Transaction(error) => Some(error),
+            Self::Reencrypt(error) => Some(error),
+            Self::Parity(error) => Some(error),
             Self::HeaderSizeParse
             | Self::Unsupported
             | Self::UnsupportedKdf(_)
@@ -240,7 +479,13 @@ impl std::error::Error for Error {
             | Self::Seek
             | Self::TargetChanged
             | Self::CannotRemoveFinalV1Keyslot
-            | Self::CannotAddV1KeyslotWithoutReencrypt => None,
+            | Self::CannotAddV1KeyslotWithoutReencrypt
+            | Self::CannotRotateDetachedHeader
+            | Self::NoRotationCredentials
+            | Self::InvalidKeyslotLabel
+            | Self::KeyslotLabelNotFound(_)
+            | Self::DuplicateKeyslotLabel(_)
+            | Self::EmptyKeyslot(_) => None,
         }
     }
 }
@@ -262,6 +507,7 @@ pub(in crate::key) struct V1MutationIntent {
     target: ResolvedTarget,
     original: Vec<u8>,
     header: V1Header,
+    sidecar: Option<ResolvedParitySidecar>,
 }
 
 impl V1MutationIntent {
@@ -269,23 +515,16 @@ impl V1MutationIntent {
     where
         P: AsRef<Path>,
     {
-        let mut graph = PathIdentityGraph::new();
-        let target = graph
-            .add_output(
-                target_path,
-                PathRole::MutationTarget,
-                OverwritePolicy::ReplaceAtCommit,
-            )
-            .map_err(Error::PathIdentity)?;
-        graph.validate().map_err(Error::PathIdentity)?;
-
+        let target = resolve_mutation_target(target_path)?;
         let (target, original) = read_mutation_target(target)?;
         let header = parse_v1_header(&original)?;
+        let sidecar = ResolvedParitySidecar::existing_for(&target).map_err(Error::Parity)?;
 
         Ok(Self {
             target,
             original,
             header,
+            sidecar,
         })
     }
 
@@ -300,24 +539,148 @@ impl V1MutationIntent {
         let Self {
             target,
             mut original,
-            header: _,
+            header,
+            sidecar,
         } = self;
 
         let header_bytes = validated_v1_header_bytes(replacement_header)?;
         ensure_target_unchanged(&target, &original)?;
+        if header_bytes.len() != header.serialized_len() {
+            return Err(Error::HeaderWrite);
+        }
         let target_header = original
-            .get_mut(..HEADER_LEN)
+            .get_mut(..header_bytes.len())
             .ok_or(Error::HeaderDeserialize)?;
         target_header.copy_from_slice(&header_bytes);
+        // the trailer copy follows every header change, so `header repair`
+        // never restores a keyslot that has since been removed
+        if replacement_header.has_header_trailer() && original.len() > header_bytes.len() {
+            let trailer = replacement_header
+                .serialize_trailer()
+                .map_err(|_| Error::HeaderWrite)?;
+            let trailer_start = original
+                .len()
+                .checked_sub(trailer.len())
+                .filter(|start| *start > header_bytes.len())
+                .ok_or(Error::HeaderDeserialize)?;
+            original
+                .get_mut(trailer_start..)
+                .ok_or(Error::HeaderDeserialize)?
+                .copy_from_slice(&trailer);
+        }
 
-        let mut transaction = StagedOutputTransaction::new(target).map_err(Error::Transaction)?;
+        let mut transaction =
+            SidecarFollowingTransaction::new(target, sidecar).map_err(Error::Transaction)?;
         transaction
+            .output()
             .write_all(&original)
             .map_err(Error::Transaction)?;
         transaction.commit().map_err(Error::Transaction)
     }
 }
 
+/// A mutation that re-encrypts the whole target rather than patching its
+/// header. The payload can be any size, so the target is held as a digest
+/// instead of in memory and streamed again at commit.
+pub(in crate::key) struct V1ReencryptIntent {
+    target: MutationDigest,
+    header: V1Header,
+    has_payload: bool,
+    sidecar: Option<ResolvedParitySidecar>,
+}
+
+impl V1ReencryptIntent {
+    pub(in crate::key) fn new<P>(target_path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let target = resolve_mutation_target(target_path)?;
+        let target = MutationDigest::read(target).map_err(map_mutation_freshness_error)?;
+        let mut file = target.open().map_err(map_mutation_freshness_error)?;
+        let len = file.metadata().map_err(Error::ReadIoWithSource)?.len();
+        let header = read_v1_header_from_reader(&mut file)?;
+        let has_payload = len > header.serialized_len() as u64;
+        let sidecar =
+            ResolvedParitySidecar::existing_for(target.target()).map_err(Error::Parity)?;
+
+        Ok(Self {
+            target,
+            header,
+            has_payload,
+            sidecar,
+        })
+    }
+
+    pub(in crate::key) fn header(&self) -> &V1Header {
+        &self.header
+    }
+
+    /// Whether the target holds a payload, rather than being a detached header.
+    pub(in crate::key) fn has_payload(&self) -> bool {
+        self.has_payload
+    }
+
+    /// Replaces the whole target: the payload is decrypted under `master_key`
+    /// and re-encrypted under `replacement_master_key` into a staged file, which
+    /// only commits once the original payload's final block has authenticated
+    /// and the target is unchanged since the intent was created.
+    pub(in crate::key) fn commit(
+        self,
+        master_key: MasterKey,
+        replacement_header: &V1Header,
+        replacement_master_key: MasterKey,
+    ) -> Result<CommitReceipt, Error> {
+        let Self {
+            target,
+            header: _,
+            has_payload: _,
+            sidecar,
+        } = self;
+
+        let header_bytes = validated_v1_header_bytes(replacement_header)?;
+        let mut reader = BufReader::new(target.open().map_err(map_mutation_freshness_error)?);
+        let ParsedHeader::V1(payload) = read_header(&mut reader)?;
+
+        let mut transaction = SidecarFollowingTransaction::new(target.target().clone(), sidecar)
+            .map_err(Error::Transaction)?;
+        let _final_auth = transaction
+            .output()
+            .with_writer_result(|file| {
+                file.write_all(&header_bytes).map_err(|error| {
+                    Error::Reencrypt(decrypt::Error::WriteDataWithSource(error))
+                })?;
+                let mut writer = V1PayloadEncryptingWriter::new(
+                    replacement_master_key,
+                    replacement_header,
+                    file,
+                )
+                .map_err(map_reencrypt_stream_error)?;
+                let final_auth = V1PayloadStream::decrypt_file_uncommitted(
+                    master_key,
+                    &payload,
+                    &mut reader,
+                    &mut writer,
+                )
+                .map_err(map_reencrypt_stream_error)?;
+                writer.finish().map_err(map_reencrypt_stream_error)?;
+                Ok(final_auth)
+            })
+            .map_err(|error| match error {
+                StagedWriteError::Operation(error) => error,
+                StagedWriteError::Transaction(error) => Error::Transaction(error),
+            })?;
+
+        target
+            .ensure_fresh()
+            .map_err(map_mutation_freshness_error)?;
+        transaction.commit().map_err(Error::Transaction)
+    }
+}
+
+fn map_reencrypt_stream_error(error: StreamError) -> Error {
+    Error::Reencrypt(decrypt::map_stream_error(error))
+}
+
 pub(in crate::key) fn read_v1_header_from_reader(
     reader: &mut impl std::io::Read,
 ) -> Result<V1Header, Error> {
@@ -333,7 +696,7 @@ pub(in crate::key) fn parse_v1_header(bytes: &[u8]) -> Result<V1Header, Error> {
 
 pub(in crate::key) fn validated_v1_header_bytes(header: &V1Header) -> Result<Vec<u8>, Error> {
     let header_bytes = header.serialize().map_err(|_| Error::HeaderWrite)?;
-    if header_bytes.len() != HEADER_LEN {
+    if header_bytes.len() != header.serialized_len() {
         return Err(Error::HeaderWrite);
     }
 
@@ -348,6 +711,22 @@ pub(crate) fn ensure_target_unchanged(
     crate::storage::mutation::ensure_fresh(target, original).map_err(map_mutation_freshness_error)
 }
 
+fn resolve_mutation_target<P>(target_path: P) -> Result<ResolvedTarget, Error>
+where
+    P: AsRef<Path>,
+{
+    let mut graph = PathIdentityGraph::new();
+    let target = graph
+        .add_output(
+            target_path,
+            PathRole::MutationTarget,
+            OverwritePolicy::ReplaceAtCommit,
+        )
+        .map_err(Error::PathIdentity)?;
+    graph.validate().map_err(Error::PathIdentity)?;
+    Ok(target)
+}
+
 pub(crate) fn read_mutation_target(
     target: ResolvedTarget,
 ) -> Result<(ResolvedTarget, Vec<u8>), Error> {
@@ -386,12 +765,38 @@ pub(in crate::key) enum V1KeyslotWrite {
     Replace,
 }
 
+/// Labels a new keyslot, stamped with the current time.
+pub(in crate::key) fn new_keyslot_metadata(label: &str) -> Result<KeyslotMetadata, Error> {
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    KeyslotMetadata::new(label, created_at).map_err(|_| Error::InvalidKeyslotLabel)
+}
+
+pub(in crate::key) fn labeled_keyslot_index(
+    header: &V1Header,
+    label: &str,
+) -> Result<V1KeyslotIndex, Error> {
+    let keyslot = header
+        .keyslots()
+        .iter()
+        .find(|keyslot| {
+            keyslot
+                .metadata()
+                .is_some_and(|metadata| metadata.label() == label)
+        })
+        .ok_or_else(|| Error::KeyslotLabelNotFound(label.to_string()))?;
+    V1KeyslotIndex::try_from_physical_index(keyslot.physical_index())
+        .map_err(|_| Error::HeaderDeserialize)
+}
+
 pub(in crate::key) fn build_v1_rewrapped_keyslot_header(
     header: &V1Header,
     master_key: &MasterKey,
     index: V1KeyslotIndex,
     raw_key_new: &Protected<Vec<u8>>,
     kdf: Kdf,
+    metadata: Option<KeyslotMetadata>,
     write: V1KeyslotWrite,
 ) -> Result<V1Header, Error> {
     let mut keyslots = header.keyslots_collection().clone();
@@ -405,7 +810,8 @@ pub(in crate::key) fn build_v1_rewrapped_keyslot_header(
         [0u8; ENCRYPTED_MASTER_KEY_LEN],
         fresh_wrapping_nonce,
         salt,
-    );
+    )
+    .with_metadata(metadata);
 
     match write {
         V1KeyslotWrite::Insert => {
@@ -428,15 +834,14 @@ pub(in crate::key) fn build_v1_rewrapped_keyslot_header(
         index,
         master_key,
         key_new,
-        &fresh_wrapping_nonce,
-        &salt,
-        kdf,
+        &placeholder_keyslot,
     )?;
 
     keyslots
         .replace(
             index,
-            V1Keyslot::new(kdf, encrypted_master_key, fresh_wrapping_nonce, salt),
+            V1Keyslot::new(kdf, encrypted_master_key, fresh_wrapping_nonce, salt)
+                .with_metadata(metadata),
         )
         .map_err(|_| Error::HeaderWrite)?;
 
@@ -447,16 +852,16 @@ pub(in crate::key) fn build_v1_rewrapped_keyslot_header(
 
 // Crate-private: external callers must not be able to supply a reused keyslot nonce.
 // The public, contract-bearing wrap API is `dexios_core::cipher::wrap_v1_master_key`.
+// `placeholder_keyslot` carries everything the slot-wrapping AAD binds: the KDF,
+// salt, wrapping nonce and metadata of the keyslot being written.
 pub(crate) fn encrypt_master_key(
     header: &V1Header,
     index: V1KeyslotIndex,
     master_key: &MasterKey,
     key_new: Protected<[u8; 32]>,
-    nonce: &KeyslotNonce,
-    salt: &Salt,
-    kdf: Kdf,
+    placeholder_keyslot: &V1Keyslot,
 ) -> Result<[u8; ENCRYPTED_MASTER_KEY_LEN], Error> {
-    let placeholder_keyslot = V1Keyslot::new(kdf, [0u8; ENCRYPTED_MASTER_KEY_LEN], *nonce, *salt);
+    let placeholder_keyslot = *placeholder_keyslot;
     let mut placeholder_keyslots = header.keyslots_collection().clone();
     if placeholder_keyslots.get_physical(index.get()).is_some() {
         placeholder_keyslots
@@ -476,7 +881,7 @@ pub(crate) fn encrypt_master_key(
     let encrypted_master_key = wrap_v1_master_key(
         WrappingKey::from(key_new),
         master_key,
-        nonce,
+        placeholder_keyslot.nonce(),
         &slot_wrapping_aad,
     )
     .map_err(|_| Error::MasterKeyEncrypt)?;
@@ -1,8 +1,8 @@
 //! This provides functionality for adding a key to a header that adheres to the
 //! Dexios V1 format.
 
-use super::Error;
-use core::header::v1::{V1Header, V1KeyslotIndex};
+use super::{Credential, Error};
+use core::header::v1::{KeyslotMetadata, V1Header, V1KeyslotIndex};
 use core::kdf::Kdf;
 use core::primitives::MasterKey;
 use core::protected::Protected;
@@ -13,6 +13,7 @@ use crate::storage::transaction::CommitReceipt;
 pub struct AddIntent {
     mutation: super::V1MutationIntent,
     empty_index: V1KeyslotIndex,
+    metadata: Option<KeyslotMetadata>,
 }
 
 impl AddIntent {
@@ -36,10 +37,24 @@ impl AddIntent {
         Ok(Self {
             mutation,
             empty_index,
+            metadata: None,
         })
     }
 
-    pub fn verify_old_key(self, raw_key_old: Protected<Vec<u8>>) -> Result<ProvenAddIntent, Error> {
+    /// Labels the new keyslot. Labels must be unique within a header.
+    pub fn with_label(mut self, label: &str) -> Result<Self, Error> {
+        if super::labeled_keyslot_index(self.mutation.header(), label).is_ok() {
+            return Err(Error::DuplicateKeyslotLabel(label.to_string()));
+        }
+
+        self.metadata = Some(super::new_keyslot_metadata(label)?);
+        Ok(self)
+    }
+
+    pub fn verify_old_key(
+        self,
+        raw_key_old: impl Into<Credential>,
+    ) -> Result<ProvenAddIntent, Error> {
         let (master_key, _index) =
             super::decrypt_v1_master_key_with_index(self.mutation.header(), raw_key_old)?;
 
@@ -47,6 +62,7 @@ impl AddIntent {
             mutation: self.mutation,
             master_key,
             empty_index: self.empty_index,
+            metadata: self.metadata,
         })
     }
 }
@@ -55,6 +71,7 @@ pub struct ProvenAddIntent {
     mutation: super::V1MutationIntent,
     master_key: MasterKey,
     empty_index: V1KeyslotIndex,
+    metadata: Option<KeyslotMetadata>,
 }
 
 pub fn execute(
@@ -66,6 +83,7 @@ pub fn execute(
         mutation,
         master_key,
         empty_index,
+        metadata,
     } = intent;
 
     let replacement_header = added_header(
@@ -74,6 +92,7 @@ pub fn execute(
         empty_index,
         new_key_secret,
         kdf,
+        metadata,
     )?;
     mutation.commit_replacement_header(&replacement_header)
 }
@@ -84,6 +103,7 @@ fn added_header(
     index: V1KeyslotIndex,
     new_key_secret: Protected<Vec<u8>>,
     kdf: Kdf,
+    metadata: Option<KeyslotMetadata>,
 ) -> Result<V1Header, Error> {
     // The shared helper keeps the former KDF borrow shape: .derive(&new_key_secret, ...).
     let replacement_header = super::build_v1_rewrapped_keyslot_header(
@@ -92,6 +112,7 @@ fn added_header(
         index,
         &new_key_secret,
         kdf,
+        metadata,
         super::V1KeyslotWrite::Insert,
     )?;
     let replacement_master_key =
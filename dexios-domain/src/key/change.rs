@@ -1,8 +1,8 @@
 //! This provides functionality for changing an existing key in a header that
 //! adheres to the Dexios V1 format.
 
-use super::Error;
-use core::header::v1::{V1Header, V1KeyslotIndex};
+use super::{Credential, Error};
+use core::header::v1::{V1Header, V1Keyslot, V1KeyslotIndex};
 use core::kdf::Kdf;
 use core::primitives::MasterKey;
 use core::protected::Protected;
@@ -12,6 +12,7 @@ use crate::storage::transaction::CommitReceipt;
 
 pub struct ChangeIntent {
     mutation: super::V1MutationIntent,
+    target: Option<V1KeyslotIndex>,
 }
 
 impl ChangeIntent {
@@ -27,20 +28,30 @@ impl ChangeIntent {
             }
         }
 
-        Ok(Self { mutation })
+        Ok(Self {
+            mutation,
+            target: None,
+        })
+    }
+
+    /// Changes the keyslot labeled `label` rather than the one the old key
+    /// opens. Any key that opens the file proves authority over it.
+    pub fn target_label(mut self, label: &str) -> Result<Self, Error> {
+        self.target = Some(super::labeled_keyslot_index(self.mutation.header(), label)?);
+        Ok(self)
     }
 
     pub fn verify_old_key(
         self,
-        raw_key_old: Protected<Vec<u8>>,
+        raw_key_old: impl Into<Credential>,
     ) -> Result<ProvenChangeIntent, Error> {
-        let (master_key, index) =
+        let (master_key, opened_index) =
             super::decrypt_v1_master_key_with_index(self.mutation.header(), raw_key_old)?;
 
         Ok(ProvenChangeIntent {
             mutation: self.mutation,
             master_key,
-            index,
+            index: self.target.unwrap_or(opened_index),
         })
     }
 }
@@ -74,6 +85,13 @@ fn changed_header(
     raw_key_new: Protected<Vec<u8>>,
     kdf: Kdf,
 ) -> Result<V1Header, Error> {
+    // the new key keeps the slot's label, stamped with the time it was changed
+    let metadata = header
+        .keyslots_collection()
+        .get_physical(index.get())
+        .and_then(V1Keyslot::metadata)
+        .map(|metadata| super::new_keyslot_metadata(metadata.label()))
+        .transpose()?;
     // The shared helper keeps the former KDF borrow shape: .derive(&raw_key_new, ...).
     let replacement_header = super::build_v1_rewrapped_keyslot_header(
         header,
@@ -81,6 +99,7 @@ fn changed_header(
         index,
         &raw_key_new,
         kdf,
+        metadata,
         super::V1KeyslotWrite::Replace,
     )?;
     let (replacement_master_key, replacement_index) =
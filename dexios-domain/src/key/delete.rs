@@ -1,14 +1,14 @@
 //! This provides functionality for deleting a key from a Dexios V1 header.
 
-use super::Error;
-use core::header::v1::V1Header;
-use core::protected::Protected;
+use super::{Credential, Error};
+use core::header::v1::{V1Header, V1KeyslotIndex};
 use std::path::Path;
 
 use crate::storage::transaction::CommitReceipt;
 
 pub struct DeleteIntent {
     mutation: super::V1MutationIntent,
+    target: Option<V1KeyslotIndex>,
 }
 
 impl DeleteIntent {
@@ -24,26 +24,58 @@ impl DeleteIntent {
             }
         }
 
-        Ok(Self { mutation })
+        Ok(Self {
+            mutation,
+            target: None,
+        })
+    }
+
+    /// Deletes the keyslot labeled `label` rather than the one the key opens.
+    /// Any key that opens the file proves authority over it.
+    pub fn target_label(mut self, label: &str) -> Result<Self, Error> {
+        self.target = Some(super::labeled_keyslot_index(self.mutation.header(), label)?);
+        Ok(self)
+    }
+
+    /// Deletes the keyslot at `physical_index` rather than the one the key
+    /// opens, so a lost key can be revoked with any other key to the file.
+    pub fn target_slot(mut self, physical_index: usize) -> Result<Self, Error> {
+        let index = V1KeyslotIndex::try_from_physical_index(physical_index)
+            .ok()
+            .filter(|index| {
+                self.mutation
+                    .header()
+                    .keyslots_collection()
+                    .get_physical(index.get())
+                    .is_some()
+            })
+            .ok_or(Error::EmptyKeyslot(physical_index))?;
+        self.target = Some(index);
+        Ok(self)
     }
 }
 
 pub fn execute(
     intent: DeleteIntent,
-    raw_key_old: Protected<Vec<u8>>,
+    raw_key_old: impl Into<Credential>,
 ) -> Result<CommitReceipt, Error> {
-    let DeleteIntent { mutation } = intent;
+    let DeleteIntent { mutation, target } = intent;
 
-    let replacement_header = deleted_header(mutation.header(), raw_key_old)?;
+    let replacement_header = deleted_header(mutation.header(), raw_key_old.into(), target)?;
     mutation.commit_replacement_header(&replacement_header)
 }
 
-fn deleted_header(header: &V1Header, raw_key_old: Protected<Vec<u8>>) -> Result<V1Header, Error> {
+fn deleted_header(
+    header: &V1Header,
+    raw_key_old: Credential,
+    target: Option<V1KeyslotIndex>,
+) -> Result<V1Header, Error> {
     let mut keyslots = header.keyslots_collection().clone();
 
     // all of these functions need either the master key, or the index
-    let (master_key, index) = super::decrypt_v1_master_key_with_index(header, raw_key_old)?;
+    let (master_key, opened_index) = super::decrypt_v1_master_key_with_index(header, raw_key_old)?;
     drop(master_key);
+    let index = target.unwrap_or(opened_index);
 
     if keyslots.supported_slot_count() <= 1 {
         return Err(Error::CannotRemoveFinalV1Keyslot);
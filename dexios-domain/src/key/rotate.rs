@@ -0,0 +1,293 @@
+//! This provides functionality for rotating the master key of a file that
+//! adheres to the Dexios V1 format.
+//!
+//! Every keyslot wraps the same master key, so removing a keyslot does not stop
+//! a copy of the old header (or a leaked master key) from opening the payload.
+//! Rotating generates a fresh master key and payload nonce, re-encrypts the
+//! payload under them, and rebuilds each keyslot whose credential was
+//! presented, keeping its label. Every other keyslot is dropped.
+
+use super::{Credential, Error};
+use core::header::common::Salt;
+use core::header::v1::{V1Header, V1Keyslot, V1KeyslotIndex};
+use core::kdf::Kdf;
+use core::primitives::{ENCRYPTED_MASTER_KEY_LEN, MasterKey, gen_keyslot_nonce, gen_payload_nonce};
+use core::protected::Protected;
+use core::recipient::{RecipientPublicKey, RecipientWrap};
+use std::path::Path;
+
+use crate::storage::transaction::CommitReceipt;
+use crate::utils::{gen_master_key, gen_salt};
+
+pub struct RotateIntent {
+    mutation: super::V1ReencryptIntent,
+}
+
+impl RotateIntent {
+    pub fn new<P>(target_path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mutation = super::V1ReencryptIntent::new(target_path)?;
+        if !mutation.has_payload() {
+            return Err(Error::CannotRotateDetachedHeader);
+        }
+
+        Ok(Self { mutation })
+    }
+
+    /// Opens a keyslot with each credential. Every credential must open one;
+    /// credentials that open an already-opened keyslot are ignored.
+    pub fn verify_keys<I>(self, credentials: I) -> Result<ProvenRotateIntent, Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<Credential>,
+    {
+        let (master_key, kept) = open_keyslots(self.mutation.header(), credentials)?;
+
+        Ok(ProvenRotateIntent {
+            mutation: self.mutation,
+            master_key,
+            kept,
+        })
+    }
+}
+
+/// Opens a keyslot of `header` with each credential, returning the master key
+/// and the opened keyslots in physical order. Credentials that open an
+/// already-opened keyslot are ignored.
+pub(crate) fn open_keyslots<I>(
+    header: &V1Header,
+    credentials: I,
+) -> Result<(MasterKey, Vec<KeptKeyslot>), Error>
+where
+    I: IntoIterator,
+    I::Item: Into<Credential>,
+{
+    let mut master_key = None;
+    let mut kept = Vec::<KeptKeyslot>::new();
+
+    for credential in credentials {
+        let credential = credential.into();
+        let (slot_master_key, index) =
+            super::decrypt_v1_master_key_with_credential(header, &credential)?;
+        if kept.iter().any(|slot| slot.index == index) {
+            continue;
+        }
+
+        let passphrase_kdf = || {
+            header
+                .keyslots_collection()
+                .get_physical(index.get())
+                .and_then(|keyslot| keyslot.kdf().password_kdf())
+                .ok_or(Error::IncorrectKey)
+        };
+        let rewrap = match credential {
+            Credential::Passphrase(raw_key) => Rewrap::Passphrase {
+                raw_key,
+                kdf: passphrase_kdf()?,
+            },
+            Credential::BatchPassphrase(passphrase) => Rewrap::Passphrase {
+                raw_key: passphrase.raw_key(),
+                kdf: passphrase_kdf()?,
+            },
+            Credential::Identity(identity) => Rewrap::Identity(identity),
+        };
+        kept.push(KeptKeyslot { index, rewrap });
+        master_key.get_or_insert(slot_master_key);
+    }
+
+    let master_key = master_key.ok_or(Error::NoRotationCredentials)?;
+    kept.sort_by_key(|slot| slot.index.get());
+    Ok((master_key, kept))
+}
+
+enum Rewrap {
+    Passphrase {
+        raw_key: Protected<Vec<u8>>,
+        kdf: Kdf,
+    },
+    Identity(core::recipient::RecipientIdentity),
+    /// A recipient keyslot that was not opened, wrapped again for the public
+    /// key it is given. Nothing here can open it to check the result.
+    Recipient(RecipientPublicKey),
+}
+
+pub(crate) struct KeptKeyslot {
+    index: V1KeyslotIndex,
+    rewrap: Rewrap,
+}
+
+impl KeptKeyslot {
+    /// Rebuilds the recipient keyslot at `physical_index` for `public_key`
+    /// without opening it.
+    pub(crate) fn recipient(
+        physical_index: usize,
+        public_key: RecipientPublicKey,
+    ) -> Result<Self, Error> {
+        let index = V1KeyslotIndex::try_from_physical_index(physical_index)
+            .map_err(|_| Error::HeaderDeserialize)?;
+        Ok(Self {
+            index,
+            rewrap: Rewrap::Recipient(public_key),
+        })
+    }
+
+    pub(crate) const fn physical_index(&self) -> usize {
+        self.index.get()
+    }
+
+    /// The public key a recipient keyslot is wrapped for again.
+    pub(crate) fn recipient_public_key(&self) -> Option<RecipientPublicKey> {
+        match &self.rewrap {
+            Rewrap::Passphrase { .. } => None,
+            Rewrap::Identity(identity) => Some(identity.public_key()),
+            Rewrap::Recipient(public_key) => Some(*public_key),
+        }
+    }
+}
+
+pub struct ProvenRotateIntent {
+    mutation: super::V1ReencryptIntent,
+    master_key: MasterKey,
+    kept: Vec<KeptKeyslot>,
+}
+
+impl ProvenRotateIntent {
+    /// Physical indices of the keyslots that will be rebuilt.
+    #[must_use]
+    pub fn kept_slots(&self) -> Vec<usize> {
+        self.kept.iter().map(KeptKeyslot::physical_index).collect()
+    }
+
+    /// Physical indices of the keyslots that will be dropped.
+    #[must_use]
+    pub fn dropped_slots(&self) -> Vec<usize> {
+        self.mutation
+            .header()
+            .keyslots()
+            .iter()
+            .map(V1Keyslot::physical_index)
+            .filter(|index| !self.kept.iter().any(|slot| slot.physical_index() == *index))
+            .collect()
+    }
+}
+
+pub fn execute(intent: ProvenRotateIntent) -> Result<CommitReceipt, Error> {
+    let ProvenRotateIntent {
+        mutation,
+        master_key,
+        kept,
+    } = intent;
+
+    let replacement_master_key = gen_master_key();
+    let replacement_header = rotated_header(mutation.header(), &replacement_master_key, &kept)?;
+    mutation.commit(master_key, &replacement_header, replacement_master_key)
+}
+
+// Kept keyslots stay at their physical index, so slot numbers are stable
+// across a rotation.
+pub(crate) fn rotated_header(
+    header: &V1Header,
+    master_key: &MasterKey,
+    kept: &[KeptKeyslot],
+) -> Result<V1Header, Error> {
+    let mut keyslots = header.keyslots_collection().clone();
+    for keyslot in header.keyslots() {
+        let index = V1KeyslotIndex::try_from_physical_index(keyslot.physical_index())
+            .map_err(|_| Error::HeaderDeserialize)?;
+        if !kept.iter().any(|slot| slot.index == index) {
+            keyslots
+                .clear_physical_slot(index)
+                .map_err(|_| Error::HeaderWrite)?;
+        }
+    }
+
+    let mut rotated = header
+        .with_payload_nonce(gen_payload_nonce(), keyslots)
+        .map_err(|_| Error::HeaderWrite)?;
+    for slot in kept {
+        let metadata = header
+            .keyslots_collection()
+            .get_physical(slot.index.get())
+            .and_then(V1Keyslot::metadata)
+            .copied();
+        let keyslot = match &slot.rewrap {
+            Rewrap::Passphrase { raw_key, kdf } => {
+                let salt = Salt::new(gen_salt());
+                let nonce = gen_keyslot_nonce();
+                let key = kdf
+                    .derive(raw_key, &salt.to_kdf_salt())
+                    .map_err(|_| Error::KeyHash)?;
+                let placeholder_keyslot =
+                    V1Keyslot::new(*kdf, [0u8; ENCRYPTED_MASTER_KEY_LEN], nonce, salt)
+                        .with_metadata(metadata);
+                let encrypted_master_key = super::encrypt_master_key(
+                    &rotated,
+                    slot.index,
+                    master_key,
+                    key,
+                    &placeholder_keyslot,
+                )?;
+                V1Keyslot::new(*kdf, encrypted_master_key, nonce, salt).with_metadata(metadata)
+            }
+            Rewrap::Identity(_) | Rewrap::Recipient(_) => {
+                let public_key = slot.recipient_public_key().ok_or(Error::MasterKeyEncrypt)?;
+                let wrap = RecipientWrap::new(&public_key).map_err(|_| Error::MasterKeyEncrypt)?;
+                let ephemeral_public_key = *wrap.ephemeral_public_key();
+                let placeholder_header = with_keyslot(
+                    &rotated,
+                    slot.index,
+                    V1Keyslot::new_recipient(
+                        [0u8; ENCRYPTED_MASTER_KEY_LEN],
+                        &ephemeral_public_key,
+                    )
+                    .with_metadata(metadata),
+                )?;
+                let slot_wrapping_aad = placeholder_header
+                    .slot_wrapping_aad_for_physical_slot(slot.index)
+                    .map_err(|_| Error::HeaderDeserialize)?;
+                let encrypted_master_key = wrap
+                    .wrap_master_key(master_key, &slot_wrapping_aad)
+                    .map_err(|_| Error::MasterKeyEncrypt)?;
+                V1Keyslot::new_recipient(*encrypted_master_key.as_bytes(), &ephemeral_public_key)
+                    .with_metadata(metadata)
+            }
+        };
+        rotated = with_keyslot(&rotated, slot.index, keyslot)?;
+    }
+
+    // nothing is staged until every kept credential opens its rebuilt keyslot
+    for slot in kept {
+        let replacement_master_key = match &slot.rewrap {
+            Rewrap::Recipient(_) => continue,
+            Rewrap::Passphrase { raw_key, .. } => super::decrypt_v1_master_key_at_index(
+                &rotated,
+                slot.index,
+                Protected::new(raw_key.with_exposed(Clone::clone)),
+            )?,
+            Rewrap::Identity(identity) => {
+                super::unwrap_recipient_keyslot(&rotated, slot.index, identity)?
+            }
+        };
+        if !master_key.same_secret_as(&replacement_master_key) {
+            return Err(Error::MasterKeyEncrypt);
+        }
+    }
+
+    Ok(rotated)
+}
+
+fn with_keyslot(
+    header: &V1Header,
+    index: V1KeyslotIndex,
+    keyslot: V1Keyslot,
+) -> Result<V1Header, Error> {
+    let mut keyslots = header.keyslots_collection().clone();
+    keyslots
+        .replace(index, keyslot)
+        .map_err(|_| Error::HeaderWrite)?;
+    header
+        .with_keyslots(keyslots)
+        .map_err(|_| Error::HeaderWrite)
+}
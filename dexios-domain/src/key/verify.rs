@@ -4,10 +4,9 @@
 use std::io;
 use std::path::Path;
 
-use super::Error;
+use super::{Credential, Error};
 use core::header::v1::V1Header;
 use core::header::{ParsedHeader, read_header};
-use core::protected::Protected;
 
 use crate::storage;
 use crate::storage::identity::{IdentityError, PathIdentityGraph, PathRole};
@@ -54,7 +53,7 @@ impl VerifyIntent {
     }
 }
 
-pub fn execute(intent: VerifyIntent, raw_key: Protected<Vec<u8>>) -> Result<(), Error> {
+pub fn execute(intent: VerifyIntent, raw_key: impl Into<Credential>) -> Result<(), Error> {
     let (master_key, _) = super::decrypt_v1_master_key_with_index(&intent.header, raw_key)?;
 
     // Ensure the master key is gone from memory in the event that the key is correct.
@@ -0,0 +1,115 @@
+//! This provides functionality for generating an X25519 recipient identity.
+//!
+//! The identity file holds the secret key; its public key is what other people
+//! pass to `encrypt --recipient` so the file can later be opened with
+//! `--identity`.
+
+use std::path::Path;
+
+use core::recipient::{RecipientIdentity, RecipientPublicKey};
+
+use crate::storage::identity::{
+    IdentityError, OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget,
+};
+use crate::storage::transaction::{CommitReceipt, StagedOutputTransaction, TransactionError};
+use crate::workflow_error::{
+    WorkflowErrorClass, classify_identity_error, classify_transaction_error,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    WriteIdentity,
+    PathIdentity(IdentityError),
+    Transaction(TransactionError),
+}
+
+impl Error {
+    #[must_use]
+    pub fn workflow_class(&self) -> WorkflowErrorClass {
+        match self {
+            Self::WriteIdentity => WorkflowErrorClass::IoFailure,
+            Self::PathIdentity(error) => classify_identity_error(error),
+            Self::Transaction(error) => classify_transaction_error(error),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WriteIdentity => f.write_str("Unable to write the identity file"),
+            Self::PathIdentity(error) => write!(f, "{error}"),
+            Self::Transaction(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::WriteIdentity => None,
+            Self::PathIdentity(error) => Some(error),
+            Self::Transaction(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct KeygenIntent {
+    output_target: ResolvedTarget,
+}
+
+impl KeygenIntent {
+    pub fn new<O>(output_path: O, overwrite: OverwritePolicy) -> Result<Self, Error>
+    where
+        O: AsRef<Path>,
+    {
+        let mut graph = PathIdentityGraph::new();
+        let output_target = graph
+            .add_output(output_path, PathRole::Output, overwrite)
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+
+        Ok(Self { output_target })
+    }
+}
+
+#[derive(Debug)]
+pub struct KeygenResult {
+    public_key: RecipientPublicKey,
+    commit_receipt: CommitReceipt,
+}
+
+impl KeygenResult {
+    #[must_use]
+    pub const fn public_key(&self) -> &RecipientPublicKey {
+        &self.public_key
+    }
+
+    #[must_use]
+    pub const fn commit_receipt(&self) -> &CommitReceipt {
+        &self.commit_receipt
+    }
+}
+
+pub fn execute(intent: KeygenIntent) -> Result<KeygenResult, Error> {
+    let identity = RecipientIdentity::generate();
+    let public_key = identity.public_key();
+    let contents = identity.to_identity_file();
+    drop(identity);
+
+    let mut transaction =
+        StagedOutputTransaction::new(intent.output_target).map_err(Error::Transaction)?;
+    contents
+        .with_exposed(|contents| transaction.write_all(contents))
+        .map_err(|error| match error {
+            TransactionError::Write { .. } => Error::WriteIdentity,
+            error => Error::Transaction(error),
+        })?;
+    let commit_receipt = transaction.commit().map_err(Error::Transaction)?;
+
+    Ok(KeygenResult {
+        public_key,
+        commit_receipt,
+    })
+}
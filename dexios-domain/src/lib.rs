@@ -5,8 +5,9 @@
 //! primitives alone, including:
 //!
 //! - V1 encrypt/decrypt request execution,
-//! - pack and unpack workflows,
+//! - pack, unpack, verify and diff workflows,
 //! - header dump/restore/strip operations,
+//! - parity sidecars that repair damaged blocks,
 //! - V1 keyslot manipulation over a shared wrapped master key,
 //! - and storage abstractions for the real filesystem and tests.
 //!
@@ -41,14 +42,19 @@ mod archive_path;
 
 pub mod archive;
 pub mod decrypt;
+pub mod diff;
 pub mod encrypt;
 pub mod hash;
 pub mod hasher;
 pub mod header;
 pub mod key;
+pub mod keygen;
 pub mod pack;
+pub mod parity;
 pub mod storage;
 pub mod unpack;
+pub mod update;
+pub mod verify;
 pub mod workflow_error;
 
 pub mod utils;
@@ -17,12 +17,19 @@ use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 
 use core::kdf::Kdf;
-use core::payload::{ArchiveBodyFrameHeader, ArchiveManifest, ManifestEntry, PayloadError};
+use core::payload::{
+    ArchiveBodyFrameHeader, ArchiveManifest, BodyCompression, BodyEncoder, EntryMetadata,
+    ManifestEntry, PayloadError, PayloadFramingProfile,
+};
 use core::primitives::BLOCK_SIZE;
 use core::protected::Protected;
+use core::recipient::RecipientPublicKey;
 
-use crate::archive::{ArchiveLimitError, ArchiveLimits, ArchivePolicy};
+use crate::archive::{ArchiveLimitError, ArchiveLimits, ArchivePolicy, MetadataOptions};
 use crate::archive_path::{ArchivePathError, NormalizedArchivePath};
+use crate::encrypt::V1KeyProtection;
+use crate::hasher::{Blake3Hasher, Hasher};
+use crate::parity::{ParitySidecarTarget, ResolvedParitySidecar};
 use crate::storage::cleanup::{CleanupReceipt, ProcessedSourceCleanupResult};
 use crate::storage::identity::{
     IdentityError, OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget,
@@ -60,6 +67,10 @@ pub enum Error {
     SymlinkSource(PathBuf),
     ReadSource,
     ReadSourceWithSource(crate::storage::Error),
+    /// A file no longer matched the digest recorded for it while its body was
+    /// being written.
+    SourceChanged(PathBuf),
+    Parity(crate::parity::Error),
 }
 
 impl std::fmt::Display for Error {
@@ -100,6 +111,10 @@ impl std::fmt::Display for Error {
             Self::ReadSource | Self::ReadSourceWithSource(_) => {
                 f.write_str("Unable to read pack source")
             }
+            Self::SourceChanged(path) => {
+                write!(f, "Pack source changed while packing: {}", path.display())
+            }
+            Self::Parity(inner) => write!(f, "Unable to refresh the parity sidecar: {inner}"),
         }
     }
 }
@@ -119,6 +134,7 @@ impl std::error::Error for Error {
             Self::Transaction(error) | Self::DetachedPublication(error) => Some(error),
             Self::ArchivePayload(error) => Some(error),
             Self::ArchiveLimit(error) => Some(error),
+            Self::Parity(error) => Some(error),
             _ => None,
         }
     }
@@ -142,6 +158,7 @@ impl Error {
             | Self::ArchiveRootName
             | Self::SymlinkSource(_) => WorkflowErrorClass::UnsafePath,
             Self::ArchivePayload(error) => classify_payload_error(error),
+            Self::Parity(error) => error.workflow_class(),
             Self::CreateArchive
             | Self::CreateArchiveIoWithSource(_)
             | Self::AddDirToArchive
@@ -153,7 +170,8 @@ impl Error {
             | Self::WriteData
             | Self::WriteDataWithSource(_)
             | Self::TransactionWriter
-            | Self::ReadSource => WorkflowErrorClass::IoFailure,
+            | Self::ReadSource
+            | Self::SourceChanged(_) => WorkflowErrorClass::IoFailure,
         }
     }
 
@@ -215,7 +233,7 @@ impl DetachedHeaderTarget {
     }
 }
 
-struct PackSource {
+pub(crate) struct PackSource {
     target: ResolvedTarget,
     archive_root: PathBuf,
 }
@@ -224,11 +242,14 @@ pub struct PackIntent {
     sources: Vec<PackSource>,
     output_target: ResolvedTarget,
     detached_header_target: Option<ResolvedTarget>,
+    parity_sidecar: Option<ResolvedParitySidecar>,
     cleanup_receipt: CleanupReceipt,
-    raw_key: Protected<Vec<u8>>,
-    kdf: Kdf,
+    protection: V1KeyProtection,
     on_archive_entry: Option<OnArchiveEntryFn>,
     on_walked_entry_after_metadata: Option<OnArchiveEntryFn>,
+    metadata: MetadataOptions,
+    compression: Option<BodyCompression>,
+    entry_digests: bool,
 }
 
 impl PackIntent {
@@ -243,6 +264,68 @@ impl PackIntent {
         detached_header: Option<DetachedHeaderTarget>,
         raw_key: Protected<Vec<u8>>,
         kdf: Kdf,
+        archive_policy: ArchivePolicy,
+        recursive: bool,
+        on_archive_entry: Option<OnArchiveEntryFn>,
+    ) -> Result<Self, Error>
+    where
+        S: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Self::with_protection(
+            source_paths,
+            output_path,
+            output_overwrite,
+            detached_header,
+            V1KeyProtection::passphrase(raw_key, kdf),
+            archive_policy,
+            recursive,
+            on_archive_entry,
+        )
+    }
+
+    /// Packs to X25519 recipients instead of a password; see
+    /// [`crate::encrypt::EncryptIntent::new_for_recipients`].
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "pack intent aggregates the full pack CLI surface in one constructor"
+    )]
+    pub fn new_for_recipients<S, O>(
+        source_paths: Vec<S>,
+        output_path: O,
+        output_overwrite: OverwritePolicy,
+        detached_header: Option<DetachedHeaderTarget>,
+        recipients: Vec<RecipientPublicKey>,
+        archive_policy: ArchivePolicy,
+        recursive: bool,
+        on_archive_entry: Option<OnArchiveEntryFn>,
+    ) -> Result<Self, Error>
+    where
+        S: AsRef<Path>,
+        O: AsRef<Path>,
+    {
+        Self::with_protection(
+            source_paths,
+            output_path,
+            output_overwrite,
+            detached_header,
+            V1KeyProtection::for_recipients(recipients).map_err(Error::Encrypt)?,
+            archive_policy,
+            recursive,
+            on_archive_entry,
+        )
+    }
+
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "pack intent aggregates the full pack CLI surface in one constructor"
+    )]
+    fn with_protection<S, O>(
+        source_paths: Vec<S>,
+        output_path: O,
+        output_overwrite: OverwritePolicy,
+        detached_header: Option<DetachedHeaderTarget>,
+        protection: V1KeyProtection,
         _archive_policy: ArchivePolicy,
         _recursive: bool,
         on_archive_entry: Option<OnArchiveEntryFn>,
@@ -255,29 +338,8 @@ impl PackIntent {
             .into_iter()
             .map(|path| path.as_ref().to_path_buf())
             .collect::<Vec<_>>();
-        if source_paths.is_empty() {
-            return Err(Error::ArchiveRootName);
-        }
-
-        for source_path in &source_paths {
-            reject_symlink_source(source_path)?;
-        }
-
-        let archive_roots = archive_root_names(&source_paths)?;
         let mut graph = PathIdentityGraph::new();
-        let sources = source_paths
-            .iter()
-            .zip(archive_roots)
-            .map(|(source_path, archive_root)| {
-                graph
-                    .add_existing(source_path, PathRole::ProcessedSource)
-                    .map(|target| PackSource {
-                        target,
-                        archive_root,
-                    })
-            })
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(Error::PathIdentity)?;
+        let sources = resolve_pack_sources(&mut graph, &source_paths)?;
         let cleanup_receipt = CleanupReceipt::from_processed_source_trees(
             sources.iter().map(|source| &source.target),
         )
@@ -299,19 +361,82 @@ impl PackIntent {
             .transpose()
             .map_err(Error::PathIdentity)?;
         graph.validate().map_err(Error::PathIdentity)?;
+        let parity_sidecar =
+            ResolvedParitySidecar::existing_for(&output_target).map_err(Error::Parity)?;
 
         Ok(Self {
             sources,
             output_target,
             detached_header_target,
+            parity_sidecar,
             cleanup_receipt,
-            raw_key,
-            kdf,
+            protection,
             on_archive_entry,
             on_walked_entry_after_metadata: None,
+            metadata: MetadataOptions::none(),
+            compression: None,
+            entry_digests: false,
         })
     }
 
+    /// Records the file metadata `options` selects in the manifest. The
+    /// default records none, which keeps the manifest at version 1.
+    #[must_use]
+    pub const fn with_metadata(mut self, options: MetadataOptions) -> Self {
+        self.metadata = options;
+        self
+    }
+
+    /// Compresses every file body with `compression`. The archive is then
+    /// written with the compressed manifest-first framing, which releases
+    /// without it cannot read. The default stores bodies uncompressed.
+    #[must_use]
+    pub const fn with_compression(mut self, compression: BodyCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Records the BLAKE3 hash of every file body in the manifest, which
+    /// writes a version 3 manifest. Each file is read once to hash it and
+    /// again to archive it, and packing fails if the two reads differ.
+    #[must_use]
+    pub const fn with_entry_digests(mut self) -> Self {
+        self.entry_digests = true;
+        self
+    }
+
+    /// Reserves `capacity` keyslots in the header, so up to that many keys
+    /// can be added later with `key add`. More than four keyslots selects the
+    /// extended header profile.
+    pub fn with_keyslot_capacity(mut self, capacity: usize) -> Result<Self, Error> {
+        self.protection = self
+            .protection
+            .with_keyslot_capacity(capacity)
+            .map_err(Error::Encrypt)?;
+        Ok(self)
+    }
+
+    /// See [`crate::encrypt::EncryptIntent::with_header_trailer`].
+    pub fn with_header_trailer(mut self) -> Result<Self, Error> {
+        if self.detached_header_target.is_some() {
+            return Err(Error::Encrypt(crate::encrypt::Error::DetachedHeaderTrailer));
+        }
+        self.protection = self.protection.with_header_trailer();
+        Ok(self)
+    }
+
+    /// See [`crate::encrypt::EncryptIntent::with_parity_sidecar`].
+    pub fn with_parity_sidecar(mut self, sidecar: ParitySidecarTarget) -> Result<Self, Error> {
+        let resolved = self
+            .sources
+            .iter()
+            .map(|source| &source.target)
+            .chain([&self.output_target])
+            .chain(self.detached_header_target.as_ref());
+        self.parity_sidecar = Some(sidecar.resolve(resolved, PathRole::GeneratedParitySidecar)?);
+        Ok(self)
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     #[must_use]
     pub fn with_walked_entry_after_metadata_observer(mut self, observer: OnArchiveEntryFn) -> Self {
@@ -320,6 +445,35 @@ impl PackIntent {
     }
 }
 
+/// Names each source by its archive root and records it in `graph`.
+pub(crate) fn resolve_pack_sources(
+    graph: &mut PathIdentityGraph,
+    source_paths: &[PathBuf],
+) -> Result<Vec<PackSource>, Error> {
+    if source_paths.is_empty() {
+        return Err(Error::ArchiveRootName);
+    }
+
+    for source_path in source_paths {
+        reject_symlink_source(source_path)?;
+    }
+
+    let archive_roots = archive_root_names(source_paths)?;
+    source_paths
+        .iter()
+        .zip(archive_roots)
+        .map(|(source_path, archive_root)| {
+            graph
+                .add_existing(source_path, PathRole::ProcessedSource)
+                .map(|target| PackSource {
+                    target,
+                    archive_root,
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::PathIdentity)
+}
+
 struct HandleRequest<'a, SRW, W>
 where
     SRW: Read + Write + Seek,
@@ -328,16 +482,42 @@ where
     writer: &'a RefCell<W>,
     entries: Vec<ArchiveSourceEntry<SRW>>,
     header_writer: Option<&'a RefCell<W>>,
-    raw_key: Protected<Vec<u8>>,
-    kdf: Kdf,
+    protection: V1KeyProtection,
+    compression: Option<BodyCompression>,
+    entry_digests: bool,
 }
 
-struct ArchiveSourceEntry<RW>
+pub(crate) struct ArchiveSourceEntry<RW>
 where
     RW: Read + Write + Seek,
 {
-    source: crate::storage::Entry<RW>,
+    source: ArchiveSource<RW>,
     archive_path: NormalizedArchivePath,
+    metadata: EntryMetadata,
+}
+
+enum ArchiveSource<RW>
+where
+    RW: Read + Write + Seek,
+{
+    Entry(crate::storage::Entry<RW>),
+    /// A symlink recorded by its target, which is never followed.
+    Symlink(Vec<u8>),
+    /// Another name for the file packed earlier at this archive path.
+    HardLink(Vec<u8>),
+}
+
+impl<RW> ArchiveSourceEntry<RW>
+where
+    RW: Read + Write + Seek,
+{
+    /// The opened source of a regular file entry, whose body is archived.
+    pub(crate) fn file(&self) -> Option<&crate::storage::Entry<RW>> {
+        match &self.source {
+            ArchiveSource::Entry(entry) if !entry.is_dir() => Some(entry),
+            _ => None,
+        }
+    }
 }
 
 pub fn execute(intent: PackIntent) -> Result<CommitReceipt, Error> {
@@ -356,30 +536,41 @@ pub fn execute_transactional_with_cleanup(
         sources,
         output_target,
         detached_header_target,
+        parity_sidecar,
         cleanup_receipt,
-        raw_key,
-        kdf,
+        protection,
         on_archive_entry,
         on_walked_entry_after_metadata,
+        metadata,
+        compression,
+        entry_digests,
     } = intent;
 
     let entries = materialize_archive_entries(
         &sources,
         on_archive_entry.as_deref(),
         on_walked_entry_after_metadata.as_deref(),
+        metadata,
     )?;
-    validate_generated_targets_against_entries(
-        &entries,
-        &output_target,
-        detached_header_target.as_ref(),
-    )?;
+    let companion_targets = detached_header_target
+        .iter()
+        .chain(parity_sidecar.as_ref().map(|sidecar| &sidecar.target))
+        .collect::<Vec<_>>();
+    validate_generated_targets_against_entries(&entries, &output_target, &companion_targets)?;
 
     let mut transaction = LinkedOutputTransaction::new();
     let output_index = transaction.stage(output_target)?;
     let detached_header_index = detached_header_target
         .map(|target| transaction.stage(target))
         .transpose()?;
-    let has_detached_header = detached_header_index.is_some();
+    let parity_index = parity_sidecar
+        .map(|sidecar| {
+            transaction
+                .stage(sidecar.target)
+                .map(|index| (index, sidecar.parity_blocks))
+        })
+        .transpose()?;
+    let has_linked_outputs = detached_header_index.is_some() || parity_index.is_some();
 
     let transaction = Rc::new(RefCell::new(transaction));
     let output_writer = RefCell::new(LinkedStagedWriter::new(
@@ -393,8 +584,9 @@ pub fn execute_transactional_with_cleanup(
         entries,
         writer: &output_writer,
         header_writer: detached_header_writer.as_ref(),
-        raw_key,
-        kdf,
+        protection,
+        compression,
+        entry_digests,
     });
     if let Err(error) = pack_result {
         let resource_pressure = output_writer.borrow().resource_pressure_kind().or_else(|| {
@@ -410,20 +602,24 @@ pub fn execute_transactional_with_cleanup(
 
     drop(output_writer);
     drop(detached_header_writer);
-    let transaction = Rc::try_unwrap(transaction)
+    let mut transaction = Rc::try_unwrap(transaction)
         .map_err(|_| Error::TransactionWriter)?
         .into_inner();
+    if let Some((parity_index, parity_blocks)) = parity_index {
+        crate::parity::stage_sidecar(&mut transaction, output_index, parity_index, parity_blocks)
+            .map_err(|error| map_detached_publication_transaction_error(error, true))?;
+    }
     transaction
         .commit_all()
-        .map_err(|error| map_detached_publication_transaction_error(error, has_detached_header))
+        .map_err(|error| map_detached_publication_transaction_error(error, has_linked_outputs))
         .map(|commit_receipt| ProcessedSourceCleanupResult::new(commit_receipt, cleanup_receipt))
 }
 
 fn map_detached_publication_transaction_error(
     error: TransactionError,
-    has_detached_header: bool,
+    has_linked_outputs: bool,
 ) -> Error {
-    if has_detached_header && error.detached_publication_failure().is_some() {
+    if has_linked_outputs && error.detached_publication_failure().is_some() {
         Error::DetachedPublication(error)
     } else {
         Error::Transaction(error)
@@ -444,8 +640,8 @@ where
         None => crate::encrypt::begin_v1_manifest_archive_writer(
             &mut *output_writer,
             None,
-            req.raw_key,
-            req.kdf,
+            req.protection,
+            req.compression.is_some(),
         )
         .map_err(Error::Encrypt)?,
         Some(header_writer) => {
@@ -453,8 +649,8 @@ where
             crate::encrypt::begin_v1_manifest_archive_writer(
                 &mut *output_writer,
                 Some(&mut *header_writer),
-                req.raw_key,
-                req.kdf,
+                req.protection,
+                req.compression.is_some(),
             )
             .map_err(Error::Encrypt)?
         }
@@ -462,7 +658,7 @@ where
 
     let mut manifest_entries = Vec::with_capacity(req.entries.len());
     for entry in &req.entries {
-        manifest_entries.push(manifest_entry_for(entry)?);
+        manifest_entries.push(manifest_entry_for(entry, req.entry_digests)?);
     }
     let manifest = ArchiveManifest::new(manifest_entries).map_err(Error::ArchivePayload)?;
     let mut encrypting_writer = encrypting_writer;
@@ -470,21 +666,23 @@ where
         .write_to(&mut encrypting_writer)
         .map_err(Error::ArchivePayload)?;
 
-    for (index, entry) in req.entries.iter().enumerate() {
-        if entry.source.is_dir() {
+    for (index, (entry, manifest_entry)) in req.entries.iter().zip(manifest.entries()).enumerate() {
+        let Some(file) = entry.file() else {
             continue;
-        }
+        };
 
-        let body_len = entry_body_len(entry)?;
+        let body_len = entry_body_len(file)?;
         let frame_header = ArchiveBodyFrameHeader::new(
             u32::try_from(index).expect("manifest entry count is bounded"),
             body_len,
         )
         .map_err(Error::ArchivePayload)?;
-        frame_header
-            .write_to(&mut encrypting_writer)
-            .map_err(Error::ArchivePayload)?;
-        write_archive_body(entry, body_len, &mut encrypting_writer)?;
+        write_body_frame(
+            &mut encrypting_writer,
+            frame_header,
+            req.compression,
+            |writer| write_entry_body(file, manifest_entry, body_len, writer),
+        )?;
     }
 
     crate::encrypt::finish_v1_payload_writer(encrypting_writer)
@@ -492,25 +690,74 @@ where
         .map_err(Error::Encrypt)
 }
 
-fn manifest_entry_for<RW>(entry: &ArchiveSourceEntry<RW>) -> Result<ManifestEntry, Error>
+/// Writes one body frame whose body `write_body` produces, compressing it
+/// when `compression` is set.
+pub(crate) fn write_body_frame<W, E>(
+    writer: &mut W,
+    frame_header: ArchiveBodyFrameHeader,
+    compression: Option<BodyCompression>,
+    write_body: impl FnOnce(&mut dyn Write) -> Result<(), E>,
+) -> Result<(), E>
+where
+    W: Write,
+    E: From<Error>,
+{
+    let Some(compression) = compression else {
+        frame_header
+            .write_to(writer)
+            .map_err(Error::ArchivePayload)?;
+        return write_body(writer);
+    };
+    frame_header
+        .with_codec(compression.codec())
+        .write_framed_to(writer, PayloadFramingProfile::ManifestFirstCompressed)
+        .map_err(Error::ArchivePayload)?;
+    let mut encoder = BodyEncoder::new(writer, compression).map_err(Error::ArchivePayload)?;
+    write_body(&mut encoder)?;
+    encoder.finish().map_err(Error::ArchivePayload)?;
+    Ok(())
+}
+
+pub(crate) fn manifest_entry_for<RW>(
+    entry: &ArchiveSourceEntry<RW>,
+    with_digest: bool,
+) -> Result<ManifestEntry, Error>
 where
     RW: Read + Write + Seek,
 {
     let normalized_path = entry.archive_path.as_manifest_bytes().to_vec();
-    if entry.source.is_dir() {
-        ManifestEntry::directory(normalized_path).map_err(Error::ArchivePayload)
-    } else {
-        let body_len = entry_body_len(entry)?;
-        ManifestEntry::file(normalized_path, body_len).map_err(Error::ArchivePayload)
-    }
+    let manifest_entry = match &entry.source {
+        ArchiveSource::Entry(source) if source.is_dir() => {
+            ManifestEntry::directory(normalized_path)
+        }
+        ArchiveSource::Entry(source) => {
+            let body_len = entry_body_len(source)?;
+            let manifest_entry = ManifestEntry::file(normalized_path, body_len);
+            if with_digest {
+                let mut digesting = DigestingWriter::new(io::sink());
+                write_archive_body(source, body_len, &mut digesting)?;
+                manifest_entry.and_then(|manifest_entry| {
+                    manifest_entry.with_blake3_digest(digesting.digest())
+                })
+            } else {
+                manifest_entry
+            }
+        }
+        ArchiveSource::Symlink(target) => ManifestEntry::symlink(normalized_path, target.clone()),
+        ArchiveSource::HardLink(target) => {
+            ManifestEntry::hard_link(normalized_path, target.clone())
+        }
+    };
+    manifest_entry
+        .and_then(|manifest_entry| manifest_entry.with_metadata(entry.metadata.clone()))
+        .map_err(Error::ArchivePayload)
 }
 
-fn entry_body_len<RW>(entry: &ArchiveSourceEntry<RW>) -> Result<u64, Error>
+pub(crate) fn entry_body_len<RW>(source: &crate::storage::Entry<RW>) -> Result<u64, Error>
 where
     RW: Read + Write + Seek,
 {
-    let mut reader = entry
-        .source
+    let mut reader = source
         .try_reader()
         .map_err(Error::ReadDataStorageWithSource)?
         .borrow_mut();
@@ -532,17 +779,16 @@ where
     clippy::arithmetic_side_effects,
     reason = "limit = min(remaining, BLOCK_SIZE) <= buffer.len() and read_count <= limit keep the buffer ranges in bounds; both bounded sizes fit usize/u64, and remaining only decreases by the actual read_count (<= remaining)"
 )]
-fn write_archive_body<RW, W>(
-    entry: &ArchiveSourceEntry<RW>,
+pub(crate) fn write_archive_body<RW, W>(
+    source: &crate::storage::Entry<RW>,
     body_len: u64,
     writer: &mut W,
 ) -> Result<(), Error>
 where
     RW: Read + Write + Seek,
-    W: Write,
+    W: Write + ?Sized,
 {
-    let mut reader = entry
-        .source
+    let mut reader = source
         .try_reader()
         .map_err(Error::ReadDataStorageWithSource)?
         .borrow_mut();
@@ -568,6 +814,60 @@ where
     Ok(())
 }
 
+/// Writes the body of `file` like [`write_archive_body`], checking it against
+/// the digest `manifest_entry` recorded for it, if any.
+pub(crate) fn write_entry_body<RW, W>(
+    file: &crate::storage::Entry<RW>,
+    manifest_entry: &ManifestEntry,
+    body_len: u64,
+    writer: &mut W,
+) -> Result<(), Error>
+where
+    RW: Read + Write + Seek,
+    W: Write + ?Sized,
+{
+    let Some(expected) = manifest_entry.blake3_digest() else {
+        return write_archive_body(file, body_len, writer);
+    };
+    let mut digesting = DigestingWriter::new(writer);
+    write_archive_body(file, body_len, &mut digesting)?;
+    if digesting.digest() != *expected {
+        return Err(Error::SourceChanged(file.path().to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Hashes everything written through it with BLAKE3.
+struct DigestingWriter<W> {
+    inner: W,
+    hasher: Blake3Hasher,
+}
+
+impl<W: Write> DigestingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Blake3Hasher::default(),
+        }
+    }
+
+    fn digest(&self) -> [u8; 32] {
+        self.hasher.digest()
+    }
+}
+
+impl<W: Write> Write for DigestingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.write(buf.get(..written).unwrap_or(buf));
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 fn reject_symlink_source(path: &Path) -> Result<(), Error> {
     match fs::symlink_metadata(path) {
         Ok(metadata) if metadata.file_type().is_symlink() => {
@@ -577,15 +877,17 @@ fn reject_symlink_source(path: &Path) -> Result<(), Error> {
     }
 }
 
-fn materialize_archive_entries(
+pub(crate) fn materialize_archive_entries(
     sources: &[PackSource],
     on_archive_entry: Option<&dyn Fn(&Path)>,
     on_walked_entry_after_metadata: Option<&dyn Fn(&Path)>,
+    metadata: MetadataOptions,
 ) -> Result<Vec<ArchiveSourceEntry<fs::File>>, Error> {
     materialize_archive_entries_with_limits(
         sources,
         on_archive_entry,
         on_walked_entry_after_metadata,
+        metadata,
         ArchiveLimits::default(),
     )
 }
@@ -594,10 +896,12 @@ fn materialize_archive_entries_with_limits(
     sources: &[PackSource],
     on_archive_entry: Option<&dyn Fn(&Path)>,
     on_walked_entry_after_metadata: Option<&dyn Fn(&Path)>,
+    metadata: MetadataOptions,
     limits: ArchiveLimits,
 ) -> Result<Vec<ArchiveSourceEntry<fs::File>>, Error> {
     let stor = crate::storage::FileStorage;
     let mut entries = Vec::new();
+    let mut hard_links = HardLinkIndex::default();
 
     for source_root in sources {
         let file = stor
@@ -615,8 +919,18 @@ fn materialize_archive_entries_with_limits(
                         None => crate::storage::Error::DirEntries,
                     })
                 })?;
+                let archive_path = walked_archive_path(source_root, &root_path, source.path())?;
                 if source.path_is_symlink() {
-                    return Err(Error::SymlinkSource(source.path().to_path_buf()));
+                    let target = symlink_target(source.path(), &archive_path)?;
+                    push_archive_entry(
+                        &mut entries,
+                        ArchiveSource::Symlink(target),
+                        archive_path,
+                        EntryMetadata::default(),
+                        limits,
+                        on_archive_entry,
+                    )?;
+                    continue;
                 }
                 let walked_metadata = source.metadata().map_err(|error| {
                     Error::ReadSourceWithSource(match error.into_io_error() {
@@ -631,23 +945,52 @@ fn materialize_archive_entries_with_limits(
                     .read_file_no_follow(source.path())
                     .map_err(Error::ReadSourceWithSource)?;
                 verify_walked_entry_matches_opened(&source, &walked_metadata)?;
-                let relative = source
-                    .path()
-                    .strip_prefix(&root_path)
-                    .map_err(|_| Error::ReadSource)?;
-                let archive_path = if relative.as_os_str().is_empty() {
-                    source_root.archive_root.clone()
+                let (source, entry_metadata) = if let Some(first_name) =
+                    hard_links.earlier_name(&walked_metadata, &archive_path)?
+                {
+                    // The file's metadata is already recorded under its first name.
+                    (
+                        ArchiveSource::HardLink(first_name),
+                        EntryMetadata::default(),
+                    )
                 } else {
-                    source_root.archive_root.join(relative)
+                    let entry_metadata = stor
+                        .capture_entry_metadata(&source, &walked_metadata, metadata)
+                        .map_err(Error::ReadSourceWithSource)?;
+                    (ArchiveSource::Entry(source), entry_metadata)
                 };
 
-                push_archive_entry(&mut entries, source, archive_path, limits, on_archive_entry)?;
+                push_archive_entry(
+                    &mut entries,
+                    source,
+                    archive_path,
+                    entry_metadata,
+                    limits,
+                    on_archive_entry,
+                )?;
             }
         } else {
+            let entry_metadata = if metadata.is_none() {
+                EntryMetadata::default()
+            } else {
+                let opened_metadata = file
+                    .try_reader()
+                    .map_err(Error::ReadSourceWithSource)?
+                    .borrow()
+                    .metadata()
+                    .map_err(|source| {
+                        Error::ReadSourceWithSource(crate::storage::Error::FileAccessWithSource(
+                            source,
+                        ))
+                    })?;
+                stor.capture_entry_metadata(&file, &opened_metadata, metadata)
+                    .map_err(Error::ReadSourceWithSource)?
+            };
             push_archive_entry(
                 &mut entries,
-                file,
+                ArchiveSource::Entry(file),
                 source_root.archive_root.clone(),
+                entry_metadata,
                 limits,
                 on_archive_entry,
             )?;
@@ -657,6 +1000,90 @@ fn materialize_archive_entries_with_limits(
     Ok(entries)
 }
 
+fn walked_archive_path(
+    source_root: &PackSource,
+    root_path: &Path,
+    walked_path: &Path,
+) -> Result<PathBuf, Error> {
+    let relative = walked_path
+        .strip_prefix(root_path)
+        .map_err(|_| Error::ReadSource)?;
+    Ok(if relative.as_os_str().is_empty() {
+        source_root.archive_root.clone()
+    } else {
+        source_root.archive_root.join(relative)
+    })
+}
+
+/// Remembers the first archive path of every file with more than one name, so
+/// later names are packed as hard links instead of second copies.
+#[derive(Default)]
+struct HardLinkIndex {
+    #[cfg(unix)]
+    first_names: std::collections::HashMap<(u64, u64), Vec<u8>>,
+}
+
+impl HardLinkIndex {
+    #[cfg(unix)]
+    fn earlier_name(
+        &mut self,
+        walked_metadata: &fs::Metadata,
+        archive_path: &Path,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        if !walked_metadata.is_file() || walked_metadata.nlink() < 2 {
+            return Ok(None);
+        }
+        let identity = (walked_metadata.dev(), walked_metadata.ino());
+        if let Some(first_name) = self.first_names.get(&identity) {
+            return Ok(Some(first_name.clone()));
+        }
+        let archive_path =
+            NormalizedArchivePath::from_path(archive_path).map_err(map_archive_path_error)?;
+        self.first_names
+            .insert(identity, archive_path.as_manifest_bytes().to_vec());
+        Ok(None)
+    }
+
+    #[cfg(not(unix))]
+    #[expect(
+        clippy::unnecessary_wraps,
+        clippy::unused_self,
+        reason = "keeps the Unix signature so the walk stays platform-neutral"
+    )]
+    fn earlier_name(
+        &mut self,
+        _walked_metadata: &fs::Metadata,
+        _archive_path: &Path,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        // non-Unix fallback packs every name as its own file; std exposes no
+        // stable file identity there.
+        Ok(None)
+    }
+}
+
+/// Reads a walked symlink's target, accepting it only when it stays inside the
+/// archive the same way unpack will require.
+#[cfg(unix)]
+fn symlink_target(path: &Path, archive_path: &Path) -> Result<Vec<u8>, Error> {
+    use std::os::unix::ffi::OsStringExt;
+
+    let target = fs::read_link(path).map_err(|source| {
+        Error::ReadSourceWithSource(crate::storage::Error::FileAccessWithSource(source))
+    })?;
+    let link = NormalizedArchivePath::from_path(archive_path).map_err(map_archive_path_error)?;
+    let target = target.into_os_string().into_vec();
+    crate::archive_path::check_symlink_target(&link, &target)
+        .map_err(|_| Error::SymlinkSource(path.to_path_buf()))?;
+    Ok(target)
+}
+
+#[cfg(not(unix))]
+fn symlink_target(path: &Path, _archive_path: &Path) -> Result<Vec<u8>, Error> {
+    // non-Unix fallback keeps rejecting symlinks: their targets are not POSIX
+    // paths and unpack there cannot recreate them.
+    Err(Error::SymlinkSource(path.to_path_buf()))
+}
+
 #[cfg(unix)]
 fn verify_walked_entry_matches_opened(
     entry: &crate::storage::Entry<fs::File>,
@@ -710,8 +1137,9 @@ fn verify_walked_entry_matches_opened(
 
 fn push_archive_entry<RW>(
     entries: &mut Vec<ArchiveSourceEntry<RW>>,
-    source: crate::storage::Entry<RW>,
+    source: ArchiveSource<RW>,
     archive_path: PathBuf,
+    metadata: EntryMetadata,
     limits: ArchiveLimits,
     on_archive_entry: Option<&dyn Fn(&Path)>,
 ) -> Result<(), Error>
@@ -734,36 +1162,35 @@ where
     entries.push(ArchiveSourceEntry {
         source,
         archive_path,
+        metadata,
     });
     Ok(())
 }
 
 #[expect(
     clippy::expect_used,
-    reason = "generated pack output/detached-header targets are always constructed with an overwrite policy, so these accessors are never None here"
+    reason = "generated pack output, detached-header and parity targets are always constructed with an overwrite policy, so these accessors are never None here"
 )]
-fn validate_generated_targets_against_entries<RW>(
+pub(crate) fn validate_generated_targets_against_entries<RW>(
     entries: &[ArchiveSourceEntry<RW>],
     output_target: &ResolvedTarget,
-    detached_header_target: Option<&ResolvedTarget>,
+    companion_targets: &[&ResolvedTarget],
 ) -> Result<(), Error>
 where
     RW: Read + Write + Seek,
 {
     let generated_target_exists =
-        output_target.exists() || detached_header_target.is_some_and(ResolvedTarget::exists);
+        output_target.exists() || companion_targets.iter().any(|target| target.exists());
 
     if !generated_target_exists {
         return Ok(());
     }
 
-    for entry in entries {
-        if entry.source.is_dir() {
-            continue;
-        }
-
+    // Links are skipped: a symlink is never read through and a hard link's
+    // file is checked under its first name.
+    for source in entries.iter().filter_map(ArchiveSourceEntry::file) {
         let mut graph = PathIdentityGraph::new();
-        graph.add_existing(entry.source.path(), PathRole::Input)?;
+        graph.add_existing(source.path(), PathRole::Input)?;
         graph.add_output(
             output_target.original_path(),
             PathRole::GeneratedOutput,
@@ -771,13 +1198,13 @@ where
                 .overwrite_policy()
                 .expect("generated output target has overwrite policy"),
         )?;
-        if let Some(target) = detached_header_target {
+        for target in companion_targets {
             graph.add_output(
                 target.original_path(),
-                PathRole::GeneratedDetachedHeader,
+                target.role(),
                 target
                     .overwrite_policy()
-                    .expect("generated detached header target has overwrite policy"),
+                    .expect("generated companion target has overwrite policy"),
             )?;
         }
         graph.validate()?;
@@ -927,14 +1354,14 @@ fn archive_root_names(inputs: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
     }
 }
 
-struct LinkedStagedWriter {
+pub(crate) struct LinkedStagedWriter {
     transaction: Rc<RefCell<LinkedOutputTransaction>>,
     index: usize,
     resource_pressure: Cell<Option<io::ErrorKind>>,
 }
 
 impl LinkedStagedWriter {
-    fn new(transaction: Rc<RefCell<LinkedOutputTransaction>>, index: usize) -> Self {
+    pub(crate) fn new(transaction: Rc<RefCell<LinkedOutputTransaction>>, index: usize) -> Self {
         Self {
             transaction,
             index,
@@ -942,7 +1369,7 @@ impl LinkedStagedWriter {
         }
     }
 
-    fn resource_pressure_kind(&self) -> Option<io::ErrorKind> {
+    pub(crate) fn resource_pressure_kind(&self) -> Option<io::ErrorKind> {
         self.resource_pressure.get()
     }
 
@@ -984,7 +1411,7 @@ fn transaction_to_io_error(error: TransactionError) -> io::Error {
     io::Error::other(error)
 }
 
-fn map_encrypt_output_resource_pressure(
+pub(crate) fn map_encrypt_output_resource_pressure(
     error: Error,
     resource_pressure: Option<io::ErrorKind>,
 ) -> Error {
@@ -1092,7 +1519,8 @@ pub(crate) mod tests {
                     PathBuf::from(source.path().to_string_lossy().trim_end_matches('/'));
                 ArchiveSourceEntry {
                     archive_path: NormalizedArchivePath::from_path(&archive_path).unwrap(),
-                    source,
+                    source: ArchiveSource::Entry(source),
+                    metadata: EntryMetadata::default(),
                 }
             })
             .collect::<Vec<_>>();
@@ -1103,8 +1531,12 @@ pub(crate) mod tests {
             entries,
             writer: output_file.try_writer().unwrap(),
             header_writer: None,
-            raw_key: Protected::new(PASSWORD.to_vec()),
-            kdf: Kdf::Argon2id,
+            protection: V1KeyProtection::passphrase(
+                Protected::new(PASSWORD.to_vec()),
+                Kdf::Argon2id,
+            ),
+            compression: None,
+            entry_digests: false,
         };
 
         match execute_streaming_archive(req) {
@@ -1148,16 +1580,18 @@ pub(crate) mod tests {
 
         push_archive_entry(
             &mut entries,
-            stor.read_file("hello.txt").unwrap(),
+            ArchiveSource::Entry(stor.read_file("hello.txt").unwrap()),
             PathBuf::from("one.txt"),
+            EntryMetadata::default(),
             limits,
             None,
         )
         .unwrap();
         let result = push_archive_entry(
             &mut entries,
-            stor.read_file("hello.txt").unwrap(),
+            ArchiveSource::Entry(stor.read_file("hello.txt").unwrap()),
             PathBuf::from("two.txt"),
+            EntryMetadata::default(),
             limits,
             None,
         );
@@ -1181,8 +1615,9 @@ pub(crate) mod tests {
 
         let result = push_archive_entry(
             &mut entries,
-            stor.read_file("hello.txt").unwrap(),
+            ArchiveSource::Entry(stor.read_file("hello.txt").unwrap()),
             PathBuf::from("long-name.txt"),
+            EntryMetadata::default(),
             limits,
             None,
         );
@@ -1206,8 +1641,9 @@ pub(crate) mod tests {
 
         let result = push_archive_entry(
             &mut entries,
-            stor.read_file("hello.txt").unwrap(),
+            ArchiveSource::Entry(stor.read_file("hello.txt").unwrap()),
             PathBuf::from("nested/file.txt"),
+            EntryMetadata::default(),
             limits,
             None,
         );
@@ -0,0 +1,765 @@
+//! This module contains parity sidecars for long-term archives.
+//!
+//! Encrypt and pack can write Reed-Solomon parity over the 1 MiB blocks of
+//! their output to a file next to it, from which [`repair`] rebuilds damaged
+//! blocks without the key.
+//!
+//! A sidecar is laid out as:
+//!
+//! - a 16-byte prefix: magic `DXPR`, version, blocks per group, parity blocks per
+//!   group and block length, then two zero bytes
+//! - the parity blocks of every group, in order
+//! - a BLAKE3 digest of every artifact block, then of every parity block
+//! - the artifact length as a little-endian `u64`, then a BLAKE3 digest of the
+//!   prefix, the digest table and the length
+//!
+//! The digests locate damaged blocks, so repair only has to fill known gaps:
+//! each group of [`PARITY_GROUP_BLOCKS`] artifact blocks survives as many
+//! damaged blocks as it has parity blocks.
+
+pub mod repair;
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use core::primitives::BLOCK_SIZE;
+use reed_solomon_erasure::galois_8::ReedSolomon;
+
+use crate::storage::identity::{
+    IdentityError, OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget,
+};
+use crate::storage::mutation::MutationFreshnessError;
+use crate::storage::transaction::{CommitReceipt, LinkedOutputTransaction, TransactionError};
+use crate::storage::{FileStorage, NamedStagedOutput};
+use crate::workflow_error::WorkflowErrorClass;
+use crate::workflow_error::{classify_identity_error, classify_transaction_error};
+
+pub const PARITY_MAGIC: [u8; 4] = *b"DXPR";
+pub const PARITY_VERSION: [u8; 2] = [0x00, 0x01];
+pub const PARITY_PREFIX_LEN: usize = 16;
+pub const PARITY_FOOTER_LEN: usize = 8 + PARITY_DIGEST_LEN;
+pub const PARITY_DIGEST_LEN: usize = blake3::OUT_LEN;
+/// Artifact blocks covered by one set of parity blocks.
+pub const PARITY_GROUP_BLOCKS: usize = 64;
+pub const MAX_PARITY_BLOCKS: usize = 32;
+pub const PARITY_BLOCK_LEN: usize = BLOCK_SIZE;
+/// Extension [`sidecar_path_for`] appends to the artifact path.
+pub const SIDECAR_EXTENSION: &str = "dxpar";
+
+// GF(2^8) Reed-Solomon codes hold at most 256 shards.
+const MAX_CODEC_SHARDS: usize = 256;
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidParityBlocks(usize),
+    MalformedSidecar,
+    DamagedSidecar,
+    NotDamaged,
+    TooManyDamagedBlocks { damaged: usize, correctable: usize },
+    TargetChanged,
+    SidecarChanged,
+    ReadIo,
+    WriteIo,
+    PathIdentity(IdentityError),
+    Transaction(TransactionError),
+}
+
+impl Error {
+    #[must_use]
+    pub fn workflow_class(&self) -> WorkflowErrorClass {
+        match self {
+            Self::InvalidParityBlocks(_) | Self::NotDamaged => {
+                WorkflowErrorClass::UnsupportedWorkflow
+            }
+            Self::MalformedSidecar | Self::DamagedSidecar | Self::TooManyDamagedBlocks { .. } => {
+                WorkflowErrorClass::MalformedFormat
+            }
+            Self::TargetChanged | Self::SidecarChanged | Self::ReadIo | Self::WriteIo => {
+                WorkflowErrorClass::IoFailure
+            }
+            Self::PathIdentity(error) => classify_identity_error(error),
+            Self::Transaction(error) => classify_transaction_error(error),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidParityBlocks(count) => write!(
+                f,
+                "Cannot write {count} parity blocks per group; use 1 to {MAX_PARITY_BLOCKS}"
+            ),
+            Self::MalformedSidecar => f.write_str("Not a Dexios parity sidecar"),
+            Self::DamagedSidecar => f.write_str("The parity sidecar is damaged"),
+            Self::NotDamaged => f.write_str("No damaged blocks to repair"),
+            Self::TooManyDamagedBlocks {
+                damaged,
+                correctable,
+            } => write!(
+                f,
+                "{damaged} damaged blocks in one group; the sidecar can repair {correctable}"
+            ),
+            Self::TargetChanged => f.write_str("Repair target changed before commit"),
+            Self::SidecarChanged => f.write_str("Parity sidecar changed before commit"),
+            Self::ReadIo => f.write_str("Unable to read data"),
+            Self::WriteIo => f.write_str("Unable to write data"),
+            Self::PathIdentity(error) => write!(f, "{error}"),
+            Self::Transaction(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::PathIdentity(error) => Some(error),
+            Self::Transaction(error) => Some(error),
+            Self::InvalidParityBlocks(_)
+            | Self::MalformedSidecar
+            | Self::DamagedSidecar
+            | Self::NotDamaged
+            | Self::TooManyDamagedBlocks { .. }
+            | Self::TargetChanged
+            | Self::SidecarChanged
+            | Self::ReadIo
+            | Self::WriteIo => None,
+        }
+    }
+}
+
+pub(crate) fn map_mutation_freshness_error(error: MutationFreshnessError) -> Error {
+    match error.role() {
+        PathRole::ParitySidecar | PathRole::GeneratedParitySidecar => Error::SidecarChanged,
+        PathRole::Input
+        | PathRole::Output
+        | PathRole::DetachedHeader
+        | PathRole::GeneratedOutput
+        | PathRole::GeneratedDetachedHeader
+        | PathRole::UnpackRoot
+        | PathRole::MutationTarget
+        | PathRole::ProcessedSource
+        | PathRole::CleanupTarget => Error::TargetChanged,
+    }
+}
+
+/// The sidecar path used when none is given: the artifact path with
+/// [`SIDECAR_EXTENSION`] appended.
+#[must_use]
+pub fn sidecar_path_for(artifact_path: &Path) -> PathBuf {
+    let mut path = artifact_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(SIDECAR_EXTENSION);
+    PathBuf::from(path)
+}
+
+/// Where encrypt or pack writes a parity sidecar, and how many parity blocks
+/// each group of [`PARITY_GROUP_BLOCKS`] artifact blocks gets.
+#[derive(Debug)]
+pub struct ParitySidecarTarget {
+    pub(crate) path: PathBuf,
+    pub(crate) overwrite: OverwritePolicy,
+    pub(crate) parity_blocks: usize,
+}
+
+impl ParitySidecarTarget {
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        overwrite: OverwritePolicy,
+        parity_blocks: usize,
+    ) -> Result<Self, Error> {
+        if !(1..=MAX_PARITY_BLOCKS).contains(&parity_blocks) {
+            return Err(Error::InvalidParityBlocks(parity_blocks));
+        }
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            overwrite,
+            parity_blocks,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Geometry {
+    group_blocks: usize,
+    parity_blocks: usize,
+    block_len: usize,
+    artifact_len: usize,
+}
+
+impl Geometry {
+    fn for_artifact(parity_blocks: usize, artifact_len: usize) -> Self {
+        Self {
+            group_blocks: PARITY_GROUP_BLOCKS,
+            parity_blocks,
+            block_len: artifact_len.clamp(1, PARITY_BLOCK_LEN),
+            artifact_len,
+        }
+    }
+
+    fn codec(&self) -> Option<ReedSolomon> {
+        if self.group_blocks.checked_add(self.parity_blocks)? > MAX_CODEC_SHARDS {
+            return None;
+        }
+        ReedSolomon::new(self.group_blocks, self.parity_blocks).ok()
+    }
+
+    fn data_blocks(&self) -> usize {
+        self.artifact_len.div_ceil(self.block_len)
+    }
+
+    fn groups(&self) -> usize {
+        self.data_blocks().div_ceil(self.group_blocks)
+    }
+
+    fn total_parity_blocks(&self) -> Option<usize> {
+        self.groups().checked_mul(self.parity_blocks)
+    }
+
+    // Length of artifact block `index`; only the last block may be short.
+    fn data_block_len(&self, index: usize) -> usize {
+        let start = index.saturating_mul(self.block_len);
+        self.artifact_len.saturating_sub(start).min(self.block_len)
+    }
+
+    fn parity_len(&self) -> Option<usize> {
+        self.total_parity_blocks()?.checked_mul(self.block_len)
+    }
+
+    fn digest_table_len(&self) -> Option<usize> {
+        self.data_blocks()
+            .checked_add(self.total_parity_blocks()?)?
+            .checked_mul(PARITY_DIGEST_LEN)
+    }
+
+    fn sidecar_len(&self) -> Option<usize> {
+        PARITY_PREFIX_LEN
+            .checked_add(self.parity_len()?)?
+            .checked_add(self.digest_table_len()?)?
+            .checked_add(PARITY_FOOTER_LEN)
+    }
+
+    fn prefix(&self) -> Option<[u8; PARITY_PREFIX_LEN]> {
+        let group_blocks = u16::try_from(self.group_blocks).ok()?.to_le_bytes();
+        let parity_blocks = u16::try_from(self.parity_blocks).ok()?.to_le_bytes();
+        let block_len = u32::try_from(self.block_len).ok()?.to_le_bytes();
+
+        let mut prefix = [0u8; PARITY_PREFIX_LEN];
+        let fields = PARITY_MAGIC
+            .iter()
+            .chain(&PARITY_VERSION)
+            .chain(&group_blocks)
+            .chain(&parity_blocks)
+            .chain(&block_len);
+        for (byte, field) in prefix.iter_mut().zip(fields) {
+            *byte = *field;
+        }
+        Some(prefix)
+    }
+
+    /// Reads the fields of a sidecar prefix; the artifact length is left at
+    /// zero, since it is only recorded in the footer.
+    fn from_prefix(prefix: &[u8]) -> Result<Self, Error> {
+        if prefix.get(..4) != Some(&PARITY_MAGIC[..])
+            || prefix.get(4..6) != Some(&PARITY_VERSION[..])
+        {
+            return Err(Error::MalformedSidecar);
+        }
+
+        let field =
+            |range: std::ops::Range<usize>| prefix.get(range).ok_or(Error::MalformedSidecar);
+        let geometry = Self {
+            group_blocks: usize::from(u16::from_le_bytes(
+                field(6..8)?
+                    .try_into()
+                    .map_err(|_| Error::MalformedSidecar)?,
+            )),
+            parity_blocks: usize::from(u16::from_le_bytes(
+                field(8..10)?
+                    .try_into()
+                    .map_err(|_| Error::MalformedSidecar)?,
+            )),
+            block_len: usize::try_from(u32::from_le_bytes(
+                field(10..14)?
+                    .try_into()
+                    .map_err(|_| Error::MalformedSidecar)?,
+            ))
+            .map_err(|_| Error::MalformedSidecar)?,
+            artifact_len: 0,
+        };
+        if field(14..16)? != [0, 0] || geometry.group_blocks == 0 || geometry.parity_blocks == 0 {
+            return Err(Error::MalformedSidecar);
+        }
+        Ok(geometry)
+    }
+}
+
+/// Writes the parity sidecar for the `artifact_len` bytes read from `artifact`.
+///
+/// Only one block and the parity of the current group are held in memory.
+pub(crate) fn write_sidecar<R, W>(
+    artifact: &mut R,
+    artifact_len: usize,
+    parity_blocks: usize,
+    sidecar: &mut W,
+) -> io::Result<()>
+where
+    R: Read,
+    W: Write,
+{
+    let geometry = Geometry::for_artifact(parity_blocks, artifact_len);
+    let invalid = || io::Error::other("invalid parity sidecar geometry");
+    let codec = geometry.codec().ok_or_else(invalid)?;
+    let prefix = geometry.prefix().ok_or_else(invalid)?;
+    let data_blocks = geometry.data_blocks();
+
+    let mut checksum = blake3::Hasher::new();
+    checksum.update(&prefix);
+    sidecar.write_all(&prefix)?;
+
+    let mut data_digests = Vec::with_capacity(data_blocks.saturating_mul(PARITY_DIGEST_LEN));
+    let mut parity_digests = Vec::new();
+    let mut block = vec![0u8; geometry.block_len];
+    let mut parity = vec![vec![0u8; geometry.block_len]; parity_blocks];
+    for index in 0..data_blocks {
+        let len = geometry.data_block_len(index);
+        let (filled, padding) = block.split_at_mut(len);
+        artifact.read_exact(filled)?;
+        padding.fill(0);
+        data_digests.extend_from_slice(blake3::hash(filled).as_bytes());
+
+        let group_index = index
+            .checked_rem(geometry.group_blocks)
+            .ok_or_else(invalid)?;
+        codec
+            .encode_single_sep(group_index, &block, &mut parity)
+            .map_err(|_| invalid())?;
+
+        let group_end = group_index.saturating_add(1) == geometry.group_blocks;
+        if group_end || index.saturating_add(1) == data_blocks {
+            for shard in &parity {
+                sidecar.write_all(shard)?;
+                parity_digests.extend_from_slice(blake3::hash(shard).as_bytes());
+            }
+        }
+    }
+
+    let artifact_len = u64::try_from(artifact_len)
+        .map_err(|_| invalid())?
+        .to_le_bytes();
+    for bytes in [&data_digests[..], &parity_digests[..], &artifact_len[..]] {
+        checksum.update(bytes);
+        sidecar.write_all(bytes)?;
+    }
+    sidecar.write_all(checksum.finalize().as_bytes())
+}
+
+/// A [`ParitySidecarTarget`] resolved alongside the artifact it protects.
+#[derive(Debug)]
+pub(crate) struct ResolvedParitySidecar {
+    pub(crate) target: ResolvedTarget,
+    pub(crate) parity_blocks: usize,
+}
+
+impl ParitySidecarTarget {
+    /// Resolves the sidecar path against the targets of the intent it joins.
+    pub(crate) fn resolve<'a>(
+        self,
+        resolved: impl IntoIterator<Item = &'a ResolvedTarget>,
+        role: PathRole,
+    ) -> Result<ResolvedParitySidecar, IdentityError> {
+        let mut graph = PathIdentityGraph::from_resolved(resolved);
+        let target = graph.add_output(self.path, role, self.overwrite)?;
+        graph.validate()?;
+        Ok(ResolvedParitySidecar {
+            target,
+            parity_blocks: self.parity_blocks,
+        })
+    }
+}
+
+impl ResolvedParitySidecar {
+    /// Finds the sidecar [`sidecar_path_for`] names next to `artifact`, with
+    /// the parity block count it was written with.
+    ///
+    /// A workflow that rewrites `artifact` writes this sidecar again in the
+    /// same commit: left as it was, it would let `repair` put the old bytes
+    /// back, undoing a key removal or a header strip.
+    pub(crate) fn existing_for(artifact: &ResolvedTarget) -> Result<Option<Self>, Error> {
+        let mut graph = PathIdentityGraph::from_resolved([artifact]);
+        let target = graph
+            .add_output(
+                sidecar_path_for(artifact.target_path()),
+                PathRole::ParitySidecar,
+                OverwritePolicy::ReplaceAtCommit,
+            )
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+        if !target.exists() {
+            return Ok(None);
+        }
+
+        let entry = FileStorage
+            .read_resolved_existing_no_follow(&target)
+            .map_err(|_| Error::ReadIo)?;
+        let mut prefix = [0u8; PARITY_PREFIX_LEN];
+        entry
+            .try_reader()
+            .map_err(|_| Error::ReadIo)?
+            .borrow_mut()
+            .read_exact(&mut prefix)
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::UnexpectedEof => Error::MalformedSidecar,
+                _ => Error::ReadIo,
+            })?;
+        let parity_blocks = Geometry::from_prefix(&prefix)?.parity_blocks;
+        if parity_blocks > MAX_PARITY_BLOCKS {
+            return Err(Error::MalformedSidecar);
+        }
+
+        Ok(Some(Self {
+            target,
+            parity_blocks,
+        }))
+    }
+}
+
+/// An in-place rewrite of a file, staged together with a fresh copy of its
+/// parity sidecar when it has one, so both publish in one commit.
+pub(crate) struct SidecarFollowingTransaction {
+    transaction: LinkedOutputTransaction,
+    sidecar: Option<(usize, usize)>,
+}
+
+// The rewritten file is always staged first.
+const FOLLOWED_INDEX: usize = 0;
+
+impl SidecarFollowingTransaction {
+    pub(crate) fn new(
+        target: ResolvedTarget,
+        sidecar: Option<ResolvedParitySidecar>,
+    ) -> Result<Self, TransactionError> {
+        let mut transaction = LinkedOutputTransaction::new();
+        transaction.stage(target)?;
+        let sidecar = sidecar
+            .map(|sidecar| {
+                transaction
+                    .stage(sidecar.target)
+                    .map(|index| (index, sidecar.parity_blocks))
+            })
+            .transpose()?;
+        Ok(Self {
+            transaction,
+            sidecar,
+        })
+    }
+
+    /// The staged replacement of the rewritten file.
+    #[expect(
+        clippy::expect_used,
+        reason = "new() stages the rewritten file before anything else"
+    )]
+    pub(crate) fn output(&mut self) -> &mut NamedStagedOutput {
+        self.transaction
+            .staged_output_mut(FOLLOWED_INDEX)
+            .expect("rewritten file is staged")
+    }
+
+    pub(crate) fn commit(mut self) -> Result<CommitReceipt, TransactionError> {
+        if let Some((sidecar_index, parity_blocks)) = self.sidecar {
+            stage_sidecar(
+                &mut self.transaction,
+                FOLLOWED_INDEX,
+                sidecar_index,
+                parity_blocks,
+            )?;
+        }
+        self.transaction.commit_all()
+    }
+}
+
+/// Writes the parity sidecar staged at `sidecar_index` over the artifact bytes
+/// already staged at `artifact_index`, so both publish in one commit.
+#[expect(
+    clippy::expect_used,
+    reason = "both indexes come from stage() on this transaction"
+)]
+pub(crate) fn stage_sidecar(
+    transaction: &mut LinkedOutputTransaction,
+    artifact_index: usize,
+    sidecar_index: usize,
+    parity_blocks: usize,
+) -> Result<(), TransactionError> {
+    let (mut artifact, artifact_len) = transaction
+        .staged_output_mut(artifact_index)
+        .expect("artifact is staged")
+        .with_writer(|file| {
+            let artifact = file.try_clone()?;
+            let len = usize::try_from(file.metadata()?.len()).map_err(io::Error::other)?;
+            Ok((artifact, len))
+        })?;
+
+    transaction
+        .staged_output_mut(sidecar_index)
+        .expect("parity sidecar is staged")
+        .with_writer(|sidecar| {
+            artifact.rewind()?;
+            let mut sidecar = io::BufWriter::new(sidecar);
+            write_sidecar(
+                &mut io::BufReader::new(&mut artifact),
+                artifact_len,
+                parity_blocks,
+                &mut sidecar,
+            )?;
+            sidecar.flush()
+        })
+}
+
+/// The geometry and digest table of a sidecar; its parity blocks stay on disk
+/// until a damaged group needs them.
+struct SidecarIndex {
+    geometry: Geometry,
+    codec: ReedSolomon,
+    data_digests: Vec<u8>,
+    parity_digests: Vec<u8>,
+}
+
+impl SidecarIndex {
+    fn read<R: Read + Seek>(sidecar: &mut R) -> Result<Self, Error> {
+        let sidecar_len = sidecar.seek(SeekFrom::End(0)).map_err(|_| Error::ReadIo)?;
+        let sidecar_len = usize::try_from(sidecar_len).map_err(|_| Error::MalformedSidecar)?;
+        if sidecar_len < PARITY_PREFIX_LEN.saturating_add(PARITY_FOOTER_LEN) {
+            return Err(Error::MalformedSidecar);
+        }
+
+        let mut prefix = [0u8; PARITY_PREFIX_LEN];
+        read_at(sidecar, 0, &mut prefix)?;
+        let mut geometry = Geometry::from_prefix(&prefix)?;
+        let mut footer = [0u8; PARITY_FOOTER_LEN];
+        read_at(
+            sidecar,
+            sidecar_len.saturating_sub(PARITY_FOOTER_LEN),
+            &mut footer,
+        )?;
+        let (artifact_len, checksum) = footer.split_at(8);
+        geometry.artifact_len = usize::try_from(u64::from_le_bytes(
+            artifact_len
+                .try_into()
+                .map_err(|_| Error::MalformedSidecar)?,
+        ))
+        .map_err(|_| Error::MalformedSidecar)?;
+        if geometry.block_len != geometry.artifact_len.clamp(1, PARITY_BLOCK_LEN)
+            || geometry.sidecar_len() != Some(sidecar_len)
+        {
+            return Err(Error::MalformedSidecar);
+        }
+        let codec = geometry.codec().ok_or(Error::MalformedSidecar)?;
+
+        let parity_len = geometry.parity_len().ok_or(Error::MalformedSidecar)?;
+        let mut digests = vec![0u8; geometry.digest_table_len().ok_or(Error::MalformedSidecar)?];
+        read_at(
+            sidecar,
+            PARITY_PREFIX_LEN.saturating_add(parity_len),
+            &mut digests,
+        )?;
+        let mut expected = blake3::Hasher::new();
+        expected.update(&prefix);
+        expected.update(&digests);
+        expected.update(artifact_len);
+        if expected.finalize()
+            != blake3::Hash::from_slice(checksum).map_err(|_| Error::MalformedSidecar)?
+        {
+            return Err(Error::DamagedSidecar);
+        }
+
+        let parity_digests = digests.split_off(
+            geometry
+                .data_blocks()
+                .checked_mul(PARITY_DIGEST_LEN)
+                .ok_or(Error::MalformedSidecar)?,
+        );
+        Ok(Self {
+            geometry,
+            codec,
+            data_digests: digests,
+            parity_digests,
+        })
+    }
+
+    /// Reads parity block `index`, or `None` if it no longer matches its digest.
+    fn read_parity_block<R: Read + Seek>(
+        &self,
+        sidecar: &mut R,
+        index: usize,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let block_len = self.geometry.block_len;
+        let mut shard = vec![0u8; block_len];
+        read_at(
+            sidecar,
+            PARITY_PREFIX_LEN.saturating_add(index.saturating_mul(block_len)),
+            &mut shard,
+        )?;
+        Ok(digest_matches(&self.parity_digests, index, &shard).then_some(shard))
+    }
+}
+
+fn read_at<R: Read + Seek>(reader: &mut R, offset: usize, buf: &mut [u8]) -> Result<(), Error> {
+    let offset = u64::try_from(offset).map_err(|_| Error::MalformedSidecar)?;
+    reader
+        .seek(SeekFrom::Start(offset))
+        .and_then(|_| reader.read_exact(buf))
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::UnexpectedEof => Error::MalformedSidecar,
+            _ => Error::ReadIo,
+        })
+}
+
+fn digest_matches(digests: &[u8], index: usize, block: &[u8]) -> bool {
+    let start = index.saturating_mul(PARITY_DIGEST_LEN);
+    digests
+        .get(start..start.saturating_add(PARITY_DIGEST_LEN))
+        .is_some_and(|digest| digest == blake3::hash(block).as_bytes())
+}
+
+/// Copies the recorded length of `artifact` into `staged` one block at a time
+/// and returns the indexes of the blocks that fail their digest, in order. A
+/// missing tail is zero-filled and counts as damaged; bytes past the recorded
+/// length are dropped. Only one block is held in memory.
+fn copy_checking_blocks<R: Read>(
+    sidecar: &SidecarIndex,
+    artifact: &mut R,
+    staged: &mut File,
+) -> Result<CopiedArtifact, Error> {
+    let geometry = sidecar.geometry;
+    let mut staged_writer = io::BufWriter::new(staged);
+    let mut block = vec![0u8; geometry.block_len];
+    let mut damaged = Vec::new();
+    let mut resized = false;
+    for index in 0..geometry.data_blocks() {
+        let len = geometry.data_block_len(index);
+        let block = block.get_mut(..len).ok_or(Error::ReadIo)?;
+        let filled = read_up_to(artifact, block)?;
+        if let Some(missing) = block.get_mut(filled..) {
+            missing.fill(0);
+        }
+        if filled < len {
+            resized = true;
+        }
+        if filled < len || !digest_matches(&sidecar.data_digests, index, block) {
+            damaged.push(index);
+        }
+        staged_writer.write_all(block).map_err(|_| Error::WriteIo)?;
+    }
+    staged_writer.flush().map_err(|_| Error::WriteIo)?;
+    if !resized {
+        resized = read_up_to(artifact, &mut [0u8; 1])? != 0;
+    }
+
+    Ok(CopiedArtifact { damaged, resized })
+}
+
+struct CopiedArtifact {
+    damaged: Vec<usize>,
+    resized: bool,
+}
+
+// `read_exact`, but a short read at the end of the artifact is not an error.
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut filled = 0usize;
+    while let Some(rest) = buf.get_mut(filled..).filter(|rest| !rest.is_empty()) {
+        match reader.read(rest) {
+            Ok(0) => break,
+            Ok(read) => filled = filled.saturating_add(read),
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => return Err(Error::ReadIo),
+        }
+    }
+    Ok(filled)
+}
+
+/// Rebuilds the `damaged` blocks of the artifact copied into `staged`, one
+/// group at a time, and writes back only those blocks.
+fn repair_blocks<R: Read + Seek>(
+    sidecar: &SidecarIndex,
+    sidecar_file: &mut R,
+    damaged: &[usize],
+    staged: &mut File,
+) -> Result<(), Error> {
+    let geometry = sidecar.geometry;
+    for group_damage in damaged.chunk_by(|left, right| {
+        left.checked_div(geometry.group_blocks) == right.checked_div(geometry.group_blocks)
+    }) {
+        let Some(group) = group_damage
+            .first()
+            .and_then(|index| index.checked_div(geometry.group_blocks))
+        else {
+            continue;
+        };
+        let first = group.saturating_mul(geometry.group_blocks);
+        let mut shards =
+            Vec::with_capacity(geometry.group_blocks.saturating_add(geometry.parity_blocks));
+        for index in first..first.saturating_add(geometry.group_blocks) {
+            if group_damage.contains(&index) {
+                shards.push(None);
+                continue;
+            }
+            let mut shard = vec![0u8; geometry.block_len];
+            if index < geometry.data_blocks() {
+                let block = shard
+                    .get_mut(..geometry.data_block_len(index))
+                    .ok_or(Error::WriteIo)?;
+                read_staged_at(staged, index.saturating_mul(geometry.block_len), block)?;
+            }
+            shards.push(Some(shard));
+        }
+
+        let mut intact_parity = 0usize;
+        for parity_index in 0..geometry.parity_blocks {
+            let index = group
+                .saturating_mul(geometry.parity_blocks)
+                .saturating_add(parity_index);
+            let shard = sidecar.read_parity_block(sidecar_file, index)?;
+            if shard.is_some() {
+                intact_parity = intact_parity.saturating_add(1);
+            }
+            shards.push(shard);
+        }
+        if group_damage.len() > intact_parity {
+            return Err(Error::TooManyDamagedBlocks {
+                damaged: group_damage.len(),
+                correctable: intact_parity,
+            });
+        }
+        sidecar
+            .codec
+            .reconstruct_data(&mut shards)
+            .map_err(|_| Error::DamagedSidecar)?;
+
+        for &index in group_damage {
+            let rebuilt = shards
+                .get(index.saturating_sub(first))
+                .and_then(Option::as_ref)
+                .and_then(|shard| shard.get(..geometry.data_block_len(index)))
+                .ok_or(Error::DamagedSidecar)?;
+            if !digest_matches(&sidecar.data_digests, index, rebuilt) {
+                return Err(Error::DamagedSidecar);
+            }
+            let offset = u64::try_from(index.saturating_mul(geometry.block_len))
+                .map_err(|_| Error::WriteIo)?;
+            staged
+                .seek(SeekFrom::Start(offset))
+                .and_then(|_| staged.write_all(rebuilt))
+                .map_err(|_| Error::WriteIo)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_staged_at(staged: &mut File, offset: usize, buf: &mut [u8]) -> Result<(), Error> {
+    let offset = u64::try_from(offset).map_err(|_| Error::WriteIo)?;
+    staged
+        .seek(SeekFrom::Start(offset))
+        .and_then(|_| staged.read_exact(buf))
+        .map_err(|_| Error::WriteIo)
+}
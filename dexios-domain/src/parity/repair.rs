@@ -0,0 +1,127 @@
+//! This provides functionality for rebuilding the damaged blocks of an encrypted artifact from its parity sidecar. No key is needed: blocks are checked against the digests the sidecar records and only the mismatched ones are rewritten.
+
+use super::{CopiedArtifact, Error, SidecarIndex, copy_checking_blocks, repair_blocks};
+use std::path::Path;
+
+use crate::storage::identity::{OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget};
+use crate::storage::mutation::{MutationDigest, MutationFreshnessError};
+use crate::storage::transaction::{
+    CommitReceipt, StagedOutputTransaction, StagedWriteError, TransactionError,
+};
+
+#[derive(Debug)]
+pub struct RepairIntent {
+    target: MutationDigest,
+    sidecar: MutationDigest,
+}
+
+impl RepairIntent {
+    pub fn new<T, S>(target_path: T, sidecar_path: S) -> Result<Self, Error>
+    where
+        T: AsRef<Path>,
+        S: AsRef<Path>,
+    {
+        let target_path = target_path.as_ref().to_path_buf();
+        let sidecar_path = sidecar_path.as_ref().to_path_buf();
+        let mut graph = PathIdentityGraph::new();
+        let sidecar = graph
+            .add_existing(&sidecar_path, PathRole::ParitySidecar)
+            .map_err(Error::PathIdentity)?;
+        let target = graph
+            .add_output(
+                &target_path,
+                PathRole::MutationTarget,
+                OverwritePolicy::ReplaceAtCommit,
+            )
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+        let sidecar = read_snapshot(sidecar)?;
+        let target = read_snapshot(target)?;
+
+        Ok(Self { target, sidecar })
+    }
+}
+
+#[derive(Debug)]
+pub struct RepairReceipt {
+    commit_receipt: CommitReceipt,
+    repaired_blocks: usize,
+}
+
+impl RepairReceipt {
+    #[must_use]
+    pub const fn commit_receipt(&self) -> &CommitReceipt {
+        &self.commit_receipt
+    }
+
+    /// How many blocks were rebuilt. A target that was only cut short or
+    /// extended is restored to its recorded length without rebuilding any.
+    #[must_use]
+    pub const fn repaired_blocks(&self) -> usize {
+        self.repaired_blocks
+    }
+}
+
+/// Rebuilds the damaged blocks of the target in place from its parity sidecar.
+///
+/// The target is checked and copied into the staged output one block at a
+/// time; only the groups with damaged blocks are then loaded to rebuild them.
+/// An undamaged target is left untouched and reported as
+/// [`Error::NotDamaged`].
+pub fn execute(intent: RepairIntent) -> Result<RepairReceipt, Error> {
+    let RepairIntent { target, sidecar } = intent;
+    let mut sidecar_file = sidecar.open().map_err(map_snapshot_read_error)?;
+    let index = SidecarIndex::read(&mut sidecar_file)?;
+    let mut artifact = std::io::BufReader::new(target.open().map_err(map_snapshot_read_error)?);
+
+    let mut transaction =
+        StagedOutputTransaction::new(target.target().clone()).map_err(Error::Transaction)?;
+    let repaired_blocks = transaction
+        .with_writer_result(|staged| {
+            let CopiedArtifact { damaged, resized } =
+                copy_checking_blocks(&index, &mut artifact, staged)?;
+            if damaged.is_empty() && !resized {
+                return Err(Error::NotDamaged);
+            }
+            repair_blocks(&index, &mut sidecar_file, &damaged, staged)?;
+            Ok(damaged.len())
+        })
+        .map_err(map_staged_write_error)?;
+    sidecar
+        .ensure_fresh()
+        .map_err(super::map_mutation_freshness_error)?;
+    target
+        .ensure_fresh()
+        .map_err(super::map_mutation_freshness_error)?;
+    let commit_receipt = transaction.commit().map_err(Error::Transaction)?;
+
+    Ok(RepairReceipt {
+        commit_receipt,
+        repaired_blocks,
+    })
+}
+
+fn map_staged_write_error(error: StagedWriteError<Error>) -> Error {
+    match error {
+        StagedWriteError::Operation(error) => error,
+        StagedWriteError::Transaction(error) => map_write_transaction_error(error),
+    }
+}
+
+fn map_write_transaction_error(error: TransactionError) -> Error {
+    match error {
+        TransactionError::Write { .. } => Error::WriteIo,
+        error => Error::Transaction(error),
+    }
+}
+
+fn read_snapshot(target: ResolvedTarget) -> Result<MutationDigest, Error> {
+    MutationDigest::read(target).map_err(map_snapshot_read_error)
+}
+
+fn map_snapshot_read_error(error: MutationFreshnessError) -> Error {
+    match error {
+        MutationFreshnessError::Read { .. } => Error::ReadIo,
+        error => super::map_mutation_freshness_error(error),
+    }
+}
@@ -31,11 +31,48 @@ pub enum CleanupTargetIdentity {
         handle: Arc<same_file::Handle>,
         is_symlink: bool,
     },
+    /// A symlink, identified by the link itself rather than what it points
+    /// to, so a dangling link is captured too.
+    Link {
+        source: &'static str,
+        link: CleanupLinkIdentity,
+    },
     Unchecked {
         source: &'static str,
     },
 }
 
+/// A symlink read without following it: its inode where the platform has
+/// one, and its target.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CleanupLinkIdentity {
+    #[cfg(unix)]
+    dev: u64,
+    #[cfg(unix)]
+    ino: u64,
+    target: PathBuf,
+}
+
+impl CleanupLinkIdentity {
+    #[cfg(unix)]
+    fn capture(path: &Path, metadata: &fs::Metadata) -> io::Result<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        Ok(Self {
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+            target: fs::read_link(path)?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn capture(path: &Path, _metadata: &fs::Metadata) -> io::Result<Self> {
+        Ok(Self {
+            target: fs::read_link(path)?,
+        })
+    }
+}
+
 impl CleanupTargetIdentity {
     fn verified(path: &Path, is_symlink: bool) -> io::Result<Self> {
         let handle = same_file::Handle::from_path(path)?;
@@ -53,7 +90,9 @@ impl CleanupTargetIdentity {
     #[must_use]
     pub fn source(&self) -> &'static str {
         match self {
-            Self::Verified { source, .. } | Self::Unchecked { source } => source,
+            Self::Verified { source, .. }
+            | Self::Link { source, .. }
+            | Self::Unchecked { source } => source,
         }
     }
 }
@@ -162,7 +201,15 @@ struct CapturedTarget {
 
 impl CapturedTarget {
     fn capture(path: &Path, metadata: &fs::Metadata, is_symlink: bool) -> io::Result<Self> {
-        if metadata.file_type().is_file() {
+        if is_symlink {
+            Ok(Self {
+                identity: CleanupTargetIdentity::Link {
+                    source: "cleanup target identity snapshot from the unfollowed symlink",
+                    link: CleanupLinkIdentity::capture(path, metadata)?,
+                },
+                stamp: CleanupTargetStamp::Verified { file_content: None },
+            })
+        } else if metadata.file_type().is_file() {
             let (file_content, handle) = CleanupFileStamp::capture(path)?;
             Ok(Self {
                 identity: CleanupTargetIdentity::from_handle(handle, is_symlink),
@@ -710,6 +757,16 @@ fn revalidate_target(target: &CleanupTarget) -> io::Result<()> {
                 return Err(changed_target_error("changed cleanup identity"));
             }
         }
+        CleanupTargetIdentity::Link { link, .. } => {
+            if !current_is_symlink {
+                return Err(changed_target_error(
+                    "changed cleanup target symlink status",
+                ));
+            }
+            if CleanupLinkIdentity::capture(&target.path, &metadata)? != *link {
+                return Err(changed_target_error("changed cleanup identity"));
+            }
+        }
         CleanupTargetIdentity::Unchecked { .. } => {}
     }
 
@@ -5,8 +5,10 @@ use std::io::{self, Write};
 use std::os::unix::fs::MetadataExt;
 use std::path::{Component, Path, PathBuf};
 
-use super::identity::ResolvedTarget;
+use super::identity::{OverwritePolicy, ResolvedTarget};
 use super::{Entry, Error, FileData, FileMode, Storage, TempArtifact};
+use crate::archive::MetadataOptions;
+use core::payload::EntryMetadata;
 #[cfg(unix)]
 use rustix::fs::{CWD, Mode, OFlags, openat};
 
@@ -133,6 +135,58 @@ impl FileStorage {
         }
     }
 
+    /// Captures the metadata `options` selects for a no-follow opened entry,
+    /// from the `metadata` its walk already collected.
+    pub fn capture_entry_metadata(
+        &self,
+        entry: &Entry<std_fs::File>,
+        metadata: &std_fs::Metadata,
+        options: MetadataOptions,
+    ) -> Result<EntryMetadata, Error> {
+        super::metadata::capture(entry, metadata, options)
+    }
+
+    /// Restores `metadata` onto the already committed entry at `root/relative`,
+    /// opening it fd-relative to `root` without following symlinks.
+    pub fn apply_unpack_metadata<P: AsRef<Path>>(
+        &self,
+        root: P,
+        relative: &Path,
+        metadata: &EntryMetadata,
+        options: MetadataOptions,
+    ) -> Result<(), Error> {
+        let root = root.as_ref();
+        reject_mutated_root(root)?;
+        super::metadata::apply(root, relative, metadata, options)
+    }
+
+    /// Creates a symlink at `root/relative` holding `target` verbatim. The
+    /// caller has already checked that `target` stays inside `root`.
+    pub fn create_unpack_symlink<P: AsRef<Path>>(
+        &self,
+        root: P,
+        relative: &Path,
+        target: &[u8],
+        overwrite: OverwritePolicy,
+    ) -> Result<(), Error> {
+        let root = root.as_ref();
+        reject_mutated_root(root)?;
+        super::link::symlink(root, relative, target, overwrite)
+    }
+
+    /// Links `root/relative` to the committed file at `root/existing`.
+    pub fn create_unpack_hard_link<P: AsRef<Path>>(
+        &self,
+        root: P,
+        relative: &Path,
+        existing: &Path,
+        overwrite: OverwritePolicy,
+    ) -> Result<(), Error> {
+        let root = root.as_ref();
+        reject_mutated_root(root)?;
+        super::link::hard_link(root, relative, existing, overwrite)
+    }
+
     pub fn create_unpack_dir_all<P: AsRef<Path>>(
         &self,
         root: P,
@@ -16,6 +16,8 @@ pub enum PathRole {
     MutationTarget,
     ProcessedSource,
     CleanupTarget,
+    ParitySidecar,
+    GeneratedParitySidecar,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -164,6 +166,14 @@ impl PathIdentityGraph {
         Self::default()
     }
 
+    /// Starts from targets another graph already resolved, so a builder can
+    /// check one more target against them.
+    pub(crate) fn from_resolved<'a>(targets: impl IntoIterator<Item = &'a ResolvedTarget>) -> Self {
+        Self {
+            nodes: targets.into_iter().cloned().collect(),
+        }
+    }
+
     pub fn add_existing<P: AsRef<Path>>(
         &mut self,
         path: P,
@@ -332,7 +342,9 @@ fn generated_target_inside_input(input: &ResolvedTarget, generated: &ResolvedTar
         && input.is_dir
         && matches!(
             generated.role,
-            PathRole::GeneratedOutput | PathRole::GeneratedDetachedHeader
+            PathRole::GeneratedOutput
+                | PathRole::GeneratedDetachedHeader
+                | PathRole::GeneratedParitySidecar
         )
         && generated.target_path.starts_with(&input.target_path)
 }
@@ -0,0 +1,149 @@
+//! Creation of the symlink and hard link entries an unpack extracts.
+
+use std::path::Path;
+
+use super::Error;
+use crate::storage::identity::OverwritePolicy;
+
+/// Creates a symlink at `root/relative` pointing at `target`, which is stored
+/// verbatim and never resolved here.
+#[cfg(unix)]
+pub(super) fn symlink(
+    root: &Path,
+    relative: &Path,
+    target: &[u8],
+    overwrite: OverwritePolicy,
+) -> Result<(), Error> {
+    unix::create(root, relative, overwrite, |parent, name| {
+        rustix::fs::symlinkat(target, parent, name)
+    })
+}
+
+/// Links `root/relative` to the already committed file at `root/existing`.
+#[cfg(unix)]
+pub(super) fn hard_link(
+    root: &Path,
+    relative: &Path,
+    existing: &Path,
+    overwrite: OverwritePolicy,
+) -> Result<(), Error> {
+    let full_path = root.join(existing);
+    let root_fd =
+        super::temp::open_absolute_dir(root).map_err(|_| Error::UnsafePath(full_path.clone()))?;
+    let (existing_parent, existing_name) = super::temp::open_parent_fd_relative(&root_fd, existing)
+        .map_err(|_| Error::UnsafePath(full_path))?;
+    unix::create(root, relative, overwrite, |parent, name| {
+        rustix::fs::linkat(
+            &existing_parent,
+            existing_name.as_c_str(),
+            parent,
+            name,
+            rustix::fs::AtFlags::empty(),
+        )
+    })
+}
+
+#[cfg(not(unix))]
+pub(super) fn symlink(
+    root: &Path,
+    relative: &Path,
+    _target: &[u8],
+    _overwrite: OverwritePolicy,
+) -> Result<(), Error> {
+    // non-Unix fallback refuses links: archive link targets are POSIX paths and
+    // creating them portably needs privileges Dexios does not assume.
+    Err(Error::UnsafePath(root.join(relative)))
+}
+
+#[cfg(not(unix))]
+pub(super) fn hard_link(
+    root: &Path,
+    relative: &Path,
+    _existing: &Path,
+    _overwrite: OverwritePolicy,
+) -> Result<(), Error> {
+    // non-Unix fallback refuses links; see `symlink`.
+    Err(Error::UnsafePath(root.join(relative)))
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::ffi::{CStr, CString};
+    use std::io;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use rustix::fd::{AsFd, BorrowedFd};
+    use rustix::io::Errno;
+
+    use super::Error;
+    use crate::storage::identity::OverwritePolicy;
+
+    const TEMP_NAME_ATTEMPTS: u32 = 16;
+
+    static TEMP_NAME_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    /// Runs `make` in the parent of `root/relative` without following any
+    /// symlink on the way. A replacement is made under a temporary name and
+    /// renamed over the existing entry, so the path never goes missing.
+    pub(super) fn create(
+        root: &Path,
+        relative: &Path,
+        overwrite: OverwritePolicy,
+        make: impl Fn(BorrowedFd<'_>, &CStr) -> Result<(), Errno>,
+    ) -> Result<(), Error> {
+        let full_path = root.join(relative);
+        let root_fd = crate::storage::temp::open_absolute_dir(root)
+            .map_err(|_| Error::UnsafePath(full_path.clone()))?;
+        let (parent, name) = crate::storage::temp::open_parent_fd_relative(&root_fd, relative)
+            .map_err(|_| Error::UnsafePath(full_path))?;
+
+        match overwrite {
+            OverwritePolicy::CreateNew => make(parent.as_fd(), &name).map_err(link_error)?,
+            OverwritePolicy::ReplaceAtCommit => {
+                let temp_name = create_under_temp_name(parent.as_fd(), &name, &make)?;
+                if let Err(error) =
+                    rustix::fs::renameat(&parent, temp_name.as_c_str(), &parent, name.as_c_str())
+                {
+                    let _cleanup = rustix::fs::unlinkat(
+                        &parent,
+                        temp_name.as_c_str(),
+                        rustix::fs::AtFlags::empty(),
+                    );
+                    return Err(link_error(error));
+                }
+            }
+        }
+        rustix::fs::fsync(&parent).map_err(link_error)
+    }
+
+    fn create_under_temp_name(
+        parent: BorrowedFd<'_>,
+        name: &CStr,
+        make: &impl Fn(BorrowedFd<'_>, &CStr) -> Result<(), Errno>,
+    ) -> Result<CString, Error> {
+        for _ in 0..TEMP_NAME_ATTEMPTS {
+            let mut temp_name = b".".to_vec();
+            temp_name.extend_from_slice(name.to_bytes());
+            temp_name.extend_from_slice(
+                format!(
+                    ".dexios-link-{}-{}",
+                    std::process::id(),
+                    TEMP_NAME_COUNTER.fetch_add(1, Ordering::Relaxed)
+                )
+                .as_bytes(),
+            );
+            let temp_name = CString::new(temp_name).map_err(|_| Error::CreateLink)?;
+            match make(parent, &temp_name) {
+                Ok(()) => return Ok(temp_name),
+                Err(Errno::EXIST) => {}
+                Err(error) => return Err(link_error(error)),
+            }
+        }
+        Err(Error::CreateLink)
+    }
+
+    fn link_error(error: Errno) -> Error {
+        Error::CreateLinkWithSource(io::Error::from(error))
+    }
+}
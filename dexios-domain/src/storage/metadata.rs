@@ -0,0 +1,252 @@
+//! Capture and restore of the per-entry metadata carried in archive manifests.
+
+use std::fs as std_fs;
+use std::io;
+use std::path::Path;
+
+use core::payload::EntryMetadata;
+
+use super::{Entry, Error};
+use crate::archive::MetadataOptions;
+
+#[cfg(unix)]
+pub(super) fn capture(
+    entry: &Entry<std_fs::File>,
+    metadata: &std_fs::Metadata,
+    options: MetadataOptions,
+) -> Result<EntryMetadata, Error> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut captured = EntryMetadata::default();
+    if options.mode {
+        captured = captured.with_mode(metadata.mode());
+    }
+    if options.times {
+        captured = captured
+            .with_mtime(unix::timestamp(metadata.mtime(), metadata.mtime_nsec())?)
+            .with_atime(unix::timestamp(metadata.atime(), metadata.atime_nsec())?);
+    }
+    if options.owner {
+        captured = captured.with_owner(metadata.uid(), metadata.gid());
+    }
+    if options.xattrs {
+        let xattrs = match entry {
+            Entry::File(file) => xattr::read(Some(&*file.stream.borrow()), file.path.as_path())?,
+            Entry::Dir(path) => xattr::read(None, path)?,
+        };
+        if !xattrs.is_empty() {
+            captured = captured.with_xattrs(xattrs).map_err(invalid_data)?;
+        }
+    }
+    Ok(captured)
+}
+
+#[cfg(not(unix))]
+pub(super) fn capture(
+    _entry: &Entry<std_fs::File>,
+    _metadata: &std_fs::Metadata,
+    _options: MetadataOptions,
+) -> Result<EntryMetadata, Error> {
+    // non-Unix fallback records no metadata; the manifest fields are POSIX
+    // shaped and have no faithful mapping there.
+    Ok(EntryMetadata::default())
+}
+
+/// Applies `metadata` to the committed entry at `root/relative`.
+///
+/// Extended attributes go first because a read-only mode would refuse them,
+/// ownership before mode because `chown` clears the setuid/setgid bits, and
+/// times last. Setuid/setgid bits are only restored together with ownership.
+#[cfg(unix)]
+pub(super) fn apply(
+    root: &Path,
+    relative: &Path,
+    metadata: &EntryMetadata,
+    options: MetadataOptions,
+) -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    const SET_ID_BITS: u32 = 0o6000;
+
+    let full_path = root.join(relative);
+    let root_fd =
+        super::temp::open_absolute_dir(root).map_err(|_| Error::UnsafePath(full_path.clone()))?;
+    let file = std_fs::File::from(
+        super::temp::open_entry_fd_relative(&root_fd, relative)
+            .map_err(|_| Error::UnsafePath(full_path.clone()))?,
+    );
+
+    if options.xattrs {
+        xattr::write(&file, metadata)?;
+    }
+    let owner = metadata.owner().filter(|_| options.owner);
+    if let Some((uid, gid)) = owner {
+        std::os::unix::fs::fchown(&file, Some(uid), Some(gid))
+            .map_err(Error::MetadataWithSource)?;
+    }
+    if let Some(mode) = metadata.mode().filter(|_| options.mode) {
+        let mode = if owner.is_some() {
+            mode
+        } else {
+            mode & !SET_ID_BITS
+        };
+        file.set_permissions(std_fs::Permissions::from_mode(mode))
+            .map_err(Error::MetadataWithSource)?;
+    }
+    if options.times {
+        let mut times = std_fs::FileTimes::new();
+        if let Some(mtime) = metadata.mtime() {
+            times = times.set_modified(unix::system_time(mtime)?);
+        }
+        if let Some(atime) = metadata.atime() {
+            times = times.set_accessed(unix::system_time(atime)?);
+        }
+        file.set_times(times).map_err(Error::MetadataWithSource)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(super) fn apply(
+    _root: &Path,
+    _relative: &Path,
+    _metadata: &EntryMetadata,
+    _options: MetadataOptions,
+) -> Result<(), Error> {
+    // non-Unix fallback restores nothing; see `capture`.
+    Ok(())
+}
+
+fn invalid_data(error: core::payload::PayloadError) -> Error {
+    Error::MetadataWithSource(io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::time::{Duration, SystemTime};
+
+    use core::payload::EntryTimestamp;
+
+    use super::{Error, invalid_data};
+
+    pub(super) fn timestamp(seconds: i64, nanoseconds: i64) -> Result<EntryTimestamp, Error> {
+        let nanoseconds = u32::try_from(nanoseconds).map_err(|_| Error::Metadata)?;
+        EntryTimestamp::new(seconds, nanoseconds).map_err(invalid_data)
+    }
+
+    pub(super) fn system_time(timestamp: EntryTimestamp) -> Result<SystemTime, Error> {
+        let whole = Duration::from_secs(timestamp.seconds().unsigned_abs());
+        let base = if timestamp.seconds() >= 0 {
+            SystemTime::UNIX_EPOCH.checked_add(whole)
+        } else {
+            SystemTime::UNIX_EPOCH.checked_sub(whole)
+        };
+        base.and_then(|base| {
+            base.checked_add(Duration::from_nanos(u64::from(timestamp.nanoseconds())))
+        })
+        .ok_or(Error::Metadata)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+mod xattr {
+    use std::fs as std_fs;
+    use std::io;
+    use std::path::Path;
+
+    use core::payload::{EntryMetadata, ExtendedAttribute, MAX_ENTRY_XATTR_BYTES};
+    use rustix::fs::{XattrFlags, fgetxattr, flistxattr, fsetxattr, lgetxattr, llistxattr};
+    use rustix::io::Errno;
+
+    use super::{Error, invalid_data};
+
+    /// Reads the entry's extended attributes, from the open file when there is
+    /// one and otherwise by path without following a final symlink.
+    pub(super) fn read(
+        file: Option<&std_fs::File>,
+        path: &Path,
+    ) -> Result<Vec<ExtendedAttribute>, Error> {
+        let mut names = vec![0u8; MAX_ENTRY_XATTR_BYTES];
+        let names_len = match file {
+            Some(file) => flistxattr(file, &mut names[..]),
+            None => llistxattr(path, &mut names[..]),
+        };
+        // A filesystem without xattr support has nothing to capture.
+        let names_len = match names_len {
+            Err(Errno::NOTSUP) => return Ok(Vec::new()),
+            result => result.map_err(map_xattr_error)?,
+        };
+        names.truncate(names_len);
+
+        let mut xattrs = Vec::new();
+        let mut value = vec![0u8; MAX_ENTRY_XATTR_BYTES];
+        for name in names.split(|byte| *byte == 0) {
+            if name.is_empty() || !portable_name(name) {
+                continue;
+            }
+            let value_len = match file {
+                Some(file) => fgetxattr(file, name, &mut value[..]),
+                None => lgetxattr(path, name, &mut value[..]),
+            }
+            .map_err(map_xattr_error)?;
+            let captured = value.get(..value_len).ok_or(Error::Metadata)?;
+            xattrs.push(ExtendedAttribute::new(name, captured).map_err(invalid_data)?);
+        }
+        Ok(xattrs)
+    }
+
+    pub(super) fn write(file: &std_fs::File, metadata: &EntryMetadata) -> Result<(), Error> {
+        for attribute in metadata.xattrs() {
+            fsetxattr(
+                file,
+                attribute.name(),
+                attribute.value(),
+                XattrFlags::empty(),
+            )
+            .map_err(map_xattr_error)?;
+        }
+        Ok(())
+    }
+
+    // Linux keeps security labels, ACLs and trusted attributes in the same
+    // namespace list; only `user.*` is owner-settable and means the same thing
+    // on another machine.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn portable_name(name: &[u8]) -> bool {
+        name.starts_with(b"user.")
+    }
+
+    #[cfg(target_vendor = "apple")]
+    fn portable_name(_name: &[u8]) -> bool {
+        true
+    }
+
+    fn map_xattr_error(error: Errno) -> Error {
+        Error::MetadataWithSource(io::Error::from(error))
+    }
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "linux", target_os = "android", target_vendor = "apple"))
+))]
+mod xattr {
+    use std::fs as std_fs;
+    use std::path::Path;
+
+    use core::payload::{EntryMetadata, ExtendedAttribute};
+
+    use super::Error;
+
+    // Extended attributes are only wired up where rustix exposes them.
+    pub(super) fn read(
+        _file: Option<&std_fs::File>,
+        _path: &Path,
+    ) -> Result<Vec<ExtendedAttribute>, Error> {
+        Ok(Vec::new())
+    }
+
+    pub(super) fn write(_file: &std_fs::File, _metadata: &EntryMetadata) -> Result<(), Error> {
+        Ok(())
+    }
+}
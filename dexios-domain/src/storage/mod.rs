@@ -5,8 +5,10 @@ pub mod cleanup;
 mod entry;
 mod fs;
 pub mod identity;
+mod link;
 #[cfg(test)]
 mod memory;
+mod metadata;
 pub mod mutation;
 mod temp;
 /// Deterministic failure hooks for storage safety tests; runtime workflows do not use them.
@@ -34,6 +36,8 @@ pub enum Error {
     CreateDirWithSource(io::Error),
     CreateFile,
     CreateFileWithSource(io::Error),
+    CreateLink,
+    CreateLinkWithSource(io::Error),
     OpenFile(FileMode),
     OpenFileWithSource { mode: FileMode, source: io::Error },
     RemoveFile,
@@ -50,6 +54,8 @@ pub enum Error {
     FileAccessWithSource(io::Error),
     FileLen,
     FileLenWithSource(io::Error),
+    Metadata,
+    MetadataWithSource(io::Error),
     UnsafePath(PathBuf),
 }
 
@@ -62,6 +68,9 @@ impl std::fmt::Display for Error {
             Self::CreateFile | Self::CreateFileWithSource(_) => {
                 f.write_str("Unable to create a new file")
             }
+            Self::CreateLink | Self::CreateLinkWithSource(_) => {
+                f.write_str("Unable to create a link")
+            }
             Self::OpenFile(mode) | Self::OpenFileWithSource { mode, .. } => {
                 write!(f, "Unable to read the file in {mode:?} mode")
             }
@@ -78,6 +87,9 @@ impl std::fmt::Display for Error {
             }
             Self::FileAccess | Self::FileAccessWithSource(_) => f.write_str("Permission denied"),
             Self::FileLen | Self::FileLenWithSource(_) => f.write_str("Unable to get file length"),
+            Self::Metadata | Self::MetadataWithSource(_) => {
+                f.write_str("Unable to read or apply file metadata")
+            }
             Self::UnsafePath(path) => {
                 write!(f, "Unsafe extraction path: {}", path.display())
             }
@@ -90,6 +102,7 @@ impl std::error::Error for Error {
         match self {
             Self::CreateDirWithSource(source)
             | Self::CreateFileWithSource(source)
+            | Self::CreateLinkWithSource(source)
             | Self::RemoveFileWithSource(source)
             | Self::RemoveDirWithSource(source)
             | Self::DirEntriesWithSource(source)
@@ -97,9 +110,11 @@ impl std::error::Error for Error {
             | Self::SyncFileWithSource(source)
             | Self::FileAccessWithSource(source)
             | Self::FileLenWithSource(source)
+            | Self::MetadataWithSource(source)
             | Self::OpenFileWithSource { source, .. } => Some(source),
             Self::CreateDir
             | Self::CreateFile
+            | Self::CreateLink
             | Self::OpenFile(_)
             | Self::RemoveFile
             | Self::RemoveDir
@@ -108,6 +123,7 @@ impl std::error::Error for Error {
             | Self::SyncFile
             | Self::FileAccess
             | Self::FileLen
+            | Self::Metadata
             | Self::UnsafePath(_) => None,
         }
     }
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::fs::File;
 #[cfg(unix)]
 use std::fs;
 use std::io::{self, Read};
@@ -60,6 +61,47 @@ impl MutationSnapshot {
     }
 }
 
+/// Digest snapshot of a file that is rewritten from a stream rather than from
+/// memory.
+///
+/// [`ensure_fresh`](Self::ensure_fresh) keeps the byte-exact contract of
+/// [`MutationSnapshot`] by comparing a BLAKE3 digest of the whole file, so a
+/// large target is read again instead of being held. Parity repair uses it,
+/// rewriting only damaged blocks, and so does key rotation, which streams the
+/// re-encrypted payload from the target into the staged output.
+#[derive(Debug)]
+pub struct MutationDigest {
+    target: ResolvedTarget,
+    digest: blake3::Hash,
+}
+
+impl MutationDigest {
+    pub fn read(target: ResolvedTarget) -> Result<Self, MutationFreshnessError> {
+        let digest = hash_target_no_follow(&target)?;
+        Ok(Self { target, digest })
+    }
+
+    #[must_use]
+    pub fn target(&self) -> &ResolvedTarget {
+        &self.target
+    }
+
+    /// Opens the target again, checking it is still the file that was hashed.
+    pub fn open(&self) -> Result<File, MutationFreshnessError> {
+        open_target_no_follow(&self.target)
+    }
+
+    pub fn ensure_fresh(&self) -> Result<(), MutationFreshnessError> {
+        if hash_target_no_follow(&self.target)? != self.digest {
+            return Err(MutationFreshnessError::ContentChanged {
+                role: self.target.role(),
+                path: self.target.target_path().to_path_buf(),
+            });
+        }
+        Ok(())
+    }
+}
+
 pub fn ensure_fresh(
     target: &ResolvedTarget,
     original: &[u8],
@@ -75,24 +117,29 @@ pub fn ensure_fresh(
 }
 
 fn read_target_bytes_no_follow(target: &ResolvedTarget) -> Result<Vec<u8>, MutationFreshnessError> {
+    let mut bytes = Vec::new();
+    open_target_no_follow(target)?
+        .read_to_end(&mut bytes)
+        .map_err(|source| {
+            MutationFreshnessError::read(target.role(), target.target_path().to_path_buf(), source)
+        })?;
+    Ok(bytes)
+}
+
+fn hash_target_no_follow(target: &ResolvedTarget) -> Result<blake3::Hash, MutationFreshnessError> {
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut open_target_no_follow(target)?, &mut hasher).map_err(|source| {
+        MutationFreshnessError::read(target.role(), target.target_path().to_path_buf(), source)
+    })?;
+    Ok(hasher.finalize())
+}
+
+fn open_target_no_follow(target: &ResolvedTarget) -> Result<File, MutationFreshnessError> {
     ensure_identity_fresh(target)?;
 
     let storage = FileStorage;
     match storage.read_resolved_existing_no_follow(target) {
-        Ok(Entry::File(FileData { stream, .. })) => {
-            let mut bytes = Vec::new();
-            stream
-                .borrow_mut()
-                .read_to_end(&mut bytes)
-                .map_err(|source| {
-                    MutationFreshnessError::read(
-                        target.role(),
-                        target.target_path().to_path_buf(),
-                        source,
-                    )
-                })?;
-            Ok(bytes)
-        }
+        Ok(Entry::File(FileData { stream, .. })) => Ok(stream.into_inner()),
         Ok(Entry::Dir(_)) => Err(MutationFreshnessError::read(
             target.role(),
             target.target_path().to_path_buf(),
@@ -230,5 +277,6 @@ fn role_label(role: PathRole) -> &'static str {
         PathRole::MutationTarget => "mutation target",
         PathRole::ProcessedSource => "processed source",
         PathRole::CleanupTarget => "cleanup target",
+        PathRole::ParitySidecar | PathRole::GeneratedParitySidecar => "parity sidecar",
     }
 }
@@ -425,7 +425,9 @@ fn persist_unsafe_path_error(path: &Path, source: Option<io::Error>) -> Transact
 }
 
 #[cfg(unix)]
-pub(super) use unix_fd_persist::{create_dirs_fd_relative, open_absolute_dir};
+pub(super) use unix_fd_persist::{
+    create_dirs_fd_relative, open_absolute_dir, open_entry_fd_relative, open_parent_fd_relative,
+};
 
 #[cfg(unix)]
 mod unix_fd_persist {
@@ -572,6 +574,55 @@ mod unix_fd_persist {
         Ok(created)
     }
 
+    /// Opens the file or directory at `relative` beneath `root_dir` read-only,
+    /// walking every hop `O_NOFOLLOW` so neither a parent nor the entry itself
+    /// can be a symlink.
+    pub(crate) fn open_entry_fd_relative(
+        root_dir: &OwnedFd,
+        relative: &Path,
+    ) -> io::Result<OwnedFd> {
+        let mut components = relative.components().peekable();
+        let mut dir = reopen_dir(root_dir)?;
+        while let Some(component) = components.next() {
+            let Component::Normal(name) = component else {
+                return Err(invalid_path("unsafe entry component"));
+            };
+            if components.peek().is_some() {
+                dir = open_child_dir(&dir, name)?;
+                continue;
+            }
+            let name = component_to_cstring(name)?;
+            return openat(
+                dir.as_fd(),
+                &name,
+                OFlags::RDONLY | OFlags::CLOEXEC | OFlags::NOFOLLOW | OFlags::NONBLOCK,
+                Mode::empty(),
+            )
+            .map_err(io::Error::from);
+        }
+        Ok(dir)
+    }
+
+    /// Opens the directory holding `relative` beneath `root_dir`, walking every
+    /// hop `O_NOFOLLOW`, and returns it with the final component's name.
+    pub(crate) fn open_parent_fd_relative(
+        root_dir: &OwnedFd,
+        relative: &Path,
+    ) -> io::Result<(OwnedFd, CString)> {
+        let mut components = relative.components().peekable();
+        let mut dir = reopen_dir(root_dir)?;
+        while let Some(component) = components.next() {
+            let Component::Normal(name) = component else {
+                return Err(invalid_path("unsafe entry component"));
+            };
+            if components.peek().is_none() {
+                return Ok((dir, component_to_cstring(name)?));
+            }
+            dir = open_child_dir(&dir, name)?;
+        }
+        Err(invalid_path("entry path has no final file name"))
+    }
+
     fn reopen_dir(dir: &OwnedFd) -> io::Result<OwnedFd> {
         openat(
             dir.as_fd(),
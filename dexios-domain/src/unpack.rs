@@ -8,9 +8,10 @@
 //! outputs commit only after stream final authentication.
 
 mod callback;
+pub mod list;
 
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::OsString;
 use std::fs;
 use std::io;
@@ -18,8 +19,10 @@ use std::io::{Read, Seek, Write};
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
-use crate::archive::{ArchiveLimitError, ArchiveLimits};
+use crate::archive::{ArchiveLimitError, ArchiveLimits, ArchiveSelection, MetadataOptions};
 use crate::decrypt;
+use crate::hasher::{Blake3Hasher, Hasher};
+use crate::key::Credential;
 use crate::storage::cleanup::{CleanupReceipt, ProcessedSourceCleanupResult};
 use crate::storage::identity::{
     IdentityError, OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget,
@@ -34,13 +37,12 @@ use crate::workflow_error::{
     WorkflowErrorClass, classify_identity_error, classify_storage_error, classify_transaction_error,
 };
 use core::payload::{
-    ArchiveBodyFrameHeader, ArchiveManifest, ManifestEntryKind, PayloadError,
-    PayloadFramingProfile, PayloadKind,
+    ArchiveBodyFrameHeader, ArchiveManifest, BodyDecoder, ENTRY_DIGEST_LEN, EntryMetadata,
+    ManifestEntry, ManifestEntryKind, PayloadError, PayloadFramingProfile, PayloadKind,
 };
-use core::protected::Protected;
 use core::stream::{StreamError, V1PayloadDecryptingReader};
 
-use crate::archive_path::{ArchivePathError, NormalizedArchivePath};
+use crate::archive_path::{ArchivePathError, NormalizedArchivePath, check_symlink_target};
 
 pub use callback::ArchiveFileCallbackError;
 
@@ -56,11 +58,22 @@ pub enum Error {
     DuplicateOutputPath(PathBuf),
     ArchiveLimit(ArchiveLimitError),
     ArchivePath(PathBuf),
+    UnsafeLinkTarget(PathBuf),
     Storage(storage::Error),
     PathIdentity(IdentityError),
     Transaction(TransactionError),
     Decrypt(decrypt::Error),
     ArchiveFileCallback(ArchiveFileCallbackError),
+    /// Restoring metadata failed after every output was committed.
+    ApplyMetadata {
+        path: PathBuf,
+        source: storage::Error,
+    },
+    /// Creating a link failed after every file output was committed.
+    CreateLink {
+        path: PathBuf,
+        source: storage::Error,
+    },
 }
 
 impl std::fmt::Display for Error {
@@ -90,11 +103,26 @@ impl std::fmt::Display for Error {
                     path.display()
                 )
             }
+            Self::UnsafeLinkTarget(path) => {
+                write!(
+                    f,
+                    "Archive path error: Unsafe link target: {}",
+                    path.display()
+                )
+            }
             Self::Storage(inner) => write!(f, "Storage error: {inner}"),
             Self::PathIdentity(inner) => write!(f, "Path identity error: {inner}"),
             Self::Transaction(inner) => write!(f, "Transaction error: {inner}"),
             Self::Decrypt(inner) => write!(f, "Decrypt error: {inner}"),
             Self::ArchiveFileCallback(inner) => write!(f, "Archive file callback error: {inner}"),
+            Self::ApplyMetadata { path, source } => write!(
+                f,
+                "Unable to restore file metadata on {}: {source}",
+                path.display()
+            ),
+            Self::CreateLink { path, source } => {
+                write!(f, "Unable to create link {}: {source}", path.display())
+            }
         }
     }
 }
@@ -112,6 +140,7 @@ impl std::error::Error for Error {
             Self::Transaction(error) => Some(error),
             Self::Decrypt(error) => Some(error),
             Self::ArchiveFileCallback(error) => Some(error),
+            Self::ApplyMetadata { source, .. } | Self::CreateLink { source, .. } => Some(source),
             _ => None,
         }
     }
@@ -126,11 +155,14 @@ impl Error {
             Self::UnsafeOutputPath(_)
             | Self::DuplicateOutputPath(_)
             | Self::ArchiveLimit(_)
-            | Self::ArchivePath(_) => WorkflowErrorClass::UnsafePath,
+            | Self::ArchivePath(_)
+            | Self::UnsafeLinkTarget(_) => WorkflowErrorClass::UnsafePath,
             Self::ArchivePayload(error) => classify_payload_error(error),
             Self::OpenArchive => WorkflowErrorClass::MalformedFormat,
             Self::Decrypt(error) => error.workflow_class(),
-            Self::Storage(error) => classify_storage_error(error),
+            Self::Storage(error)
+            | Self::ApplyMetadata { source: error, .. }
+            | Self::CreateLink { source: error, .. } => classify_storage_error(error),
             Self::PathIdentity(error) => classify_identity_error(error),
             Self::WriteData
             | Self::WriteDataWithSource(_)
@@ -170,8 +202,10 @@ pub struct UnpackIntent {
     input: storage::Entry<fs::File>,
     detached_header: Option<storage::Entry<fs::File>>,
     cleanup_receipt: CleanupReceipt,
-    raw_key: Protected<Vec<u8>>,
+    credential: Credential,
     output_dir_path: PathBuf,
+    selection: ArchiveSelection,
+    metadata: MetadataOptions,
     on_decrypted_header: Option<decrypt::OnDecryptedHeaderFn>,
     on_archive_info: Option<OnArchiveInfo>,
     on_archive_file: Option<OnArchiveFileFn>,
@@ -183,7 +217,7 @@ impl UnpackIntent {
         input_path: P,
         detached_header_path: Option<&Path>,
         output_dir_path: O,
-        raw_key: Protected<Vec<u8>>,
+        credential: impl Into<Credential>,
         on_decrypted_header: Option<decrypt::OnDecryptedHeaderFn>,
         on_archive_info: Option<OnArchiveInfo>,
         on_archive_file: Option<OnArchiveFileFn>,
@@ -219,8 +253,10 @@ impl UnpackIntent {
             input,
             detached_header,
             cleanup_receipt,
-            raw_key,
+            credential: credential.into(),
             output_dir_path: output_dir_path.as_ref().to_path_buf(),
+            selection: ArchiveSelection::default(),
+            metadata: MetadataOptions::none(),
             on_decrypted_header,
             on_archive_info,
             on_archive_file,
@@ -228,6 +264,22 @@ impl UnpackIntent {
         })
     }
 
+    /// Extracts only the entries `selection` selects, plus the directories that
+    /// contain them. Skipped file bodies are still decrypted, then discarded.
+    #[must_use]
+    pub fn with_selection(mut self, selection: ArchiveSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Restores the manifest metadata `options` selects once every output has
+    /// committed. The default restores none.
+    #[must_use]
+    pub const fn with_metadata(mut self, options: MetadataOptions) -> Self {
+        self.metadata = options;
+        self
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     #[must_use]
     pub fn with_after_final_auth_observer(mut self, observer: OnAfterFinalAuthFn) -> Self {
@@ -244,8 +296,10 @@ where
     header_reader: Option<&'a RefCell<R>>,
     input_path: PathBuf,
     detached_header_path: Option<PathBuf>,
-    raw_key: Protected<Vec<u8>>,
+    credential: Credential,
     output_dir_path: PathBuf,
+    selection: ArchiveSelection,
+    metadata: MetadataOptions,
     on_decrypted_header: Option<decrypt::OnDecryptedHeaderFn>,
     on_archive_info: Option<OnArchiveInfo>,
     on_archive_file: Option<OnArchiveFileFn>,
@@ -257,11 +311,16 @@ struct ExtractionEntity {
     relative_path: NormalizedArchivePath,
     archive_index: usize,
     kind: ExtractionKind,
+    metadata: EntryMetadata,
 }
 
 enum ExtractionKind {
     Directory(ResolvedTarget),
     File(ResolvedTarget),
+    /// A symlink holding its already checked target bytes.
+    Symlink(ResolvedTarget, Vec<u8>),
+    /// A second name for the file entry at the given archive path.
+    HardLink(ResolvedTarget, NormalizedArchivePath),
 }
 
 struct PreparedExtraction {
@@ -274,9 +333,12 @@ struct ScannedEntry {
     relative_path: NormalizedArchivePath,
     archive_index: usize,
     kind: ExtractionKind,
+    metadata: EntryMetadata,
 }
 
-struct UncommittedPlaintextReader<'a, R: Read>(&'a mut V1PayloadDecryptingReader<R>);
+pub(crate) struct UncommittedPlaintextReader<'a, R: Read>(
+    pub(crate) &'a mut V1PayloadDecryptingReader<R>,
+);
 
 impl<R: Read> Read for UncommittedPlaintextReader<'_, R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -356,8 +418,10 @@ fn execute_with_transaction(
         input,
         detached_header,
         cleanup_receipt,
-        raw_key,
+        credential,
         output_dir_path,
+        selection,
+        metadata,
         on_decrypted_header,
         on_archive_info,
         on_archive_file,
@@ -379,8 +443,10 @@ fn execute_with_transaction(
         header_reader,
         input_path,
         detached_header_path,
-        raw_key,
+        credential,
         output_dir_path,
+        selection,
+        metadata,
         on_decrypted_header,
         on_archive_info,
         on_archive_file,
@@ -406,12 +472,14 @@ where
         on_decrypted_header(payload.header());
     }
     if payload.header().payload_kind() != PayloadKind::ManifestArchive
-        || payload.header().payload_framing() != PayloadFramingProfile::ManifestFirst
+        || !payload.header().payload_framing().is_manifest_first()
     {
         return Err(Error::OpenArchive);
     }
+    let framing = payload.header().payload_framing();
 
-    let master_key = decrypt::decrypt_master_key(&payload, req.raw_key).map_err(Error::Decrypt)?;
+    let master_key =
+        decrypt::decrypt_master_key(&payload, req.credential).map_err(Error::Decrypt)?;
     let mut encrypted_reader = req.reader.borrow_mut();
     let mut plaintext_reader =
         V1PayloadDecryptingReader::new(master_key, &payload, &mut *encrypted_reader)
@@ -423,9 +491,11 @@ where
         let (prepared, transaction) = stage_manifest_extraction(
             &stor,
             &mut uncommitted_reader,
+            framing,
             &req.output_dir_path,
             &req.input_path,
             req.detached_header_path.as_deref(),
+            &req.selection,
             req.on_archive_file.as_ref(),
             transaction,
         )?;
@@ -452,6 +522,8 @@ where
     match transaction.commit_all() {
         Ok(mut receipt) => {
             receipt.extend_artifacts(directory_creation.artifacts);
+            receipt.extend_artifacts(create_extracted_links(&stor, &prepared)?);
+            apply_extracted_metadata(&stor, &prepared, req.metadata)?;
             Ok(receipt)
         }
         Err(error) => {
@@ -467,14 +539,17 @@ where
 
 #[expect(
     clippy::expect_used,
-    reason = "manifest entry count is bounded below MAX_MANIFEST_ENTRY_COUNT (< u32::MAX), File entries always carry a validated body length, and prepared entity indexes are captured from the same entity vector"
+    clippy::too_many_arguments,
+    reason = "prepared entity indexes are captured from the same entity vector; staging threads the whole unpack request through as borrowed parts"
 )]
 fn stage_manifest_extraction<R: Read>(
     stor: &storage::FileStorage,
     plaintext_reader: &mut R,
+    framing: PayloadFramingProfile,
     output_dir_path: &Path,
     input_path: &Path,
     detached_header_path: Option<&Path>,
+    selection: &ArchiveSelection,
     on_archive_file: Option<&OnArchiveFileFn>,
     mut transaction: LinkedOutputTransaction,
 ) -> Result<(PreparedExtraction, LinkedOutputTransaction), Error> {
@@ -485,6 +560,7 @@ fn stage_manifest_extraction<R: Read>(
         output_dir_path,
         input_path,
         detached_header_path,
+        selection,
         on_archive_file,
     )?;
     let mut file_entities_by_index = BTreeMap::new();
@@ -494,13 +570,58 @@ fn stage_manifest_extraction<R: Read>(
         }
     }
 
+    for_each_manifest_body(
+        plaintext_reader,
+        framing,
+        &manifest,
+        |index, body, body_len| match file_entities_by_index.get(&index) {
+            Some(entity_index) => {
+                let entity = prepared.entities.get(*entity_index).expect(
+                    "prepared entity index came from enumerate over the same entity vector",
+                );
+                stage_manifest_file_body(
+                    stor,
+                    body,
+                    &prepared.output_root,
+                    &mut transaction,
+                    entity,
+                    body_len,
+                )
+            }
+            None => drain_manifest_body(body, body_len),
+        },
+    )?;
+
+    Ok((prepared, transaction))
+}
+
+/// Reads the body frame of every `File` entry in manifest order and hands its
+/// decoded body to `visit`, which must consume exactly `body_len` bytes.
+///
+/// Frame order, declared lengths and the aggregate body ceiling are checked
+/// before `visit` sees a frame, and a recorded digest once it returns.
+#[expect(
+    clippy::expect_used,
+    reason = "manifest entry count is bounded below MAX_MANIFEST_ENTRY_COUNT (< u32::MAX) and File entries always carry a validated body length"
+)]
+pub(crate) fn for_each_manifest_body<R, F>(
+    plaintext_reader: &mut R,
+    framing: PayloadFramingProfile,
+    manifest: &ArchiveManifest,
+    mut visit: F,
+) -> Result<u64, Error>
+where
+    R: Read,
+    F: FnMut(usize, &mut ManifestBody<BodyDecoder<&mut R>>, u64) -> Result<(), Error>,
+{
     let mut total_body: u64 = 0;
     for (index, entry) in manifest.entries().iter().enumerate() {
         if entry.kind() != ManifestEntryKind::File {
             continue;
         }
         let expected_index = u32::try_from(index).expect("manifest entry count is bounded");
-        let frame_header = read_manifest_body_frame_header(plaintext_reader, expected_index)?;
+        let frame_header =
+            read_manifest_body_frame_header(plaintext_reader, framing, expected_index)?;
         if frame_header.entry_index() != expected_index {
             return Err(Error::ArchivePayload(
                 PayloadError::BodyFrameOrderMismatch {
@@ -521,38 +642,71 @@ fn stage_manifest_extraction<R: Read>(
             ));
         }
 
-        // Enforce the aggregate body-byte ceiling before staging this frame (parse-1).
+        // Enforce the aggregate body-byte ceiling before visiting this frame (parse-1).
         total_body = total_body.saturating_add(frame_header.body_len());
         ArchiveLimits::defaults()
             .check_total_body_bytes(total_body)
             .map_err(Error::ArchiveLimit)?;
 
-        if let Some(entity_index) = file_entities_by_index.get(&index) {
-            let entity = prepared
-                .entities
-                .get(*entity_index)
-                .expect("prepared entity index came from enumerate over the same entity vector");
-            stage_manifest_file_body(
-                stor,
-                plaintext_reader,
-                &prepared.output_root,
-                &mut transaction,
-                entity,
-                frame_header.body_len(),
-            )?;
-        } else {
-            drain_manifest_body(plaintext_reader, frame_header.body_len())?;
+        // The decoder never yields more than the declared length checked above,
+        // so a compressed frame cannot inflate past the aggregate ceiling.
+        let decoder = BodyDecoder::new(
+            &mut *plaintext_reader,
+            frame_header.codec(),
+            frame_header.body_len(),
+        )
+        .map_err(Error::ArchivePayload)?;
+        let mut body = ManifestBody::new(decoder, entry.blake3_digest());
+        visit(index, &mut body, frame_header.body_len())?;
+        body.check_digest(expected_index)
+            .map_err(Error::ArchivePayload)?;
+    }
+
+    Ok(total_body)
+}
+
+/// The decoded body of one file entry. When the entry recorded a digest the
+/// body is hashed as it is read, so it can be checked once fully consumed.
+pub(crate) struct ManifestBody<R> {
+    inner: R,
+    digest: Option<([u8; ENTRY_DIGEST_LEN], Blake3Hasher)>,
+}
+
+impl<R: Read> ManifestBody<R> {
+    pub(crate) fn new(inner: R, expected: Option<&[u8; ENTRY_DIGEST_LEN]>) -> Self {
+        Self {
+            inner,
+            digest: expected.map(|expected| (*expected, Blake3Hasher::default())),
         }
     }
 
-    Ok((prepared, transaction))
+    /// Compares what was read against the recorded digest of entry `index`.
+    pub(crate) fn check_digest(&self, index: u32) -> Result<(), PayloadError> {
+        match &self.digest {
+            Some((expected, hasher)) if hasher.digest() != *expected => {
+                Err(PayloadError::EntryDigestMismatch(index))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<R: Read> Read for ManifestBody<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if let Some((_, hasher)) = &mut self.digest {
+            hasher.write(buf.get(..read).unwrap_or_default());
+        }
+        Ok(read)
+    }
 }
 
 fn read_manifest_body_frame_header<R: Read>(
     plaintext_reader: &mut R,
+    framing: PayloadFramingProfile,
     expected_index: u32,
 ) -> Result<ArchiveBodyFrameHeader, Error> {
-    match ArchiveBodyFrameHeader::read_from(plaintext_reader) {
+    match ArchiveBodyFrameHeader::read_framed_from(plaintext_reader, framing) {
         Ok(header) => Ok(header),
         Err(PayloadError::TruncatedManifest) => Err(Error::ArchivePayload(
             PayloadError::MissingBodyFrame(expected_index),
@@ -567,6 +721,7 @@ fn prepare_manifest_extraction_entities(
     output_dir_path: &Path,
     input_path: &Path,
     detached_header_path: Option<&Path>,
+    selection: &ArchiveSelection,
     on_archive_file: Option<&OnArchiveFileFn>,
 ) -> Result<PreparedExtraction, Error> {
     let output_dir = stor
@@ -591,15 +746,15 @@ fn prepare_manifest_extraction_entities(
 
     let mut scanned_entries = Vec::new();
     let mut archive_paths = ArchivePathTree::default();
-    for (index, entry) in manifest.entries().iter().enumerate() {
-        let path = NormalizedArchivePath::from_manifest_bytes(entry.normalized_path())
-            .map_err(map_archive_path_error)?;
-        path.check_limits(&limits).map_err(Error::ArchiveLimit)?;
-        let archive_entry_kind = match entry.kind() {
-            ManifestEntryKind::Directory => ArchiveEntryKind::Directory,
-            ManifestEntryKind::File => ArchiveEntryKind::File,
-        };
-        archive_paths.insert(path.as_path(), archive_entry_kind)?;
+    let selected = select_manifest_entries(manifest.entries(), selection);
+    for ((index, entry), selected) in manifest.entries().iter().enumerate().zip(selected) {
+        let (path, archive_entry_kind, link) =
+            check_manifest_entry(&mut archive_paths, &limits, entry)?;
+        // Skipped entries are still collision-checked above but never resolved
+        // against the output directory, so they cannot block the unpack.
+        if !selected {
+            continue;
+        }
 
         let full_path = stor
             .resolve_unpack_path(&output_dir, path.as_path())
@@ -617,7 +772,15 @@ fn prepare_manifest_extraction_entities(
             let target = identity_graph
                 .add_output(&full_path, PathRole::Output, overwrite_policy)
                 .map_err(map_identity_error)?;
-            ExtractionKind::File(target)
+            match link {
+                None => ExtractionKind::File(target),
+                Some(ExtractionLink::Symlink(link_target)) => {
+                    ExtractionKind::Symlink(target, link_target)
+                }
+                Some(ExtractionLink::HardLink(link_target)) => {
+                    ExtractionKind::HardLink(target, link_target)
+                }
+            }
         };
 
         scanned_entries.push(ScannedEntry {
@@ -625,10 +788,67 @@ fn prepare_manifest_extraction_entities(
             relative_path: path,
             archive_index: index,
             kind,
+            metadata: entry.metadata().clone(),
         });
     }
 
+    let entities = consented_entities(scanned_entries, on_archive_file)?;
+
+    Ok(PreparedExtraction {
+        output_root,
+        entities,
+    })
+}
+
+/// Checks the manifest's structure without resolving any output path.
+pub(crate) fn validate_manifest(manifest: &ArchiveManifest) -> Result<(), Error> {
+    let limits = ArchiveLimits::default();
+    limits
+        .check_entry_count(manifest.entries().len())
+        .map_err(Error::ArchiveLimit)?;
+    let mut archive_paths = ArchivePathTree::default();
+    for entry in manifest.entries() {
+        check_manifest_entry(&mut archive_paths, &limits, entry)?;
+    }
+    Ok(())
+}
+
+fn check_manifest_entry(
+    archive_paths: &mut ArchivePathTree,
+    limits: &ArchiveLimits,
+    entry: &ManifestEntry,
+) -> Result<
+    (
+        NormalizedArchivePath,
+        ArchiveEntryKind,
+        Option<ExtractionLink>,
+    ),
+    Error,
+> {
+    let path = NormalizedArchivePath::from_manifest_bytes(entry.normalized_path())
+        .map_err(map_archive_path_error)?;
+    path.check_limits(limits).map_err(Error::ArchiveLimit)?;
+    // Links are leaves like files, so nothing can be extracted through one.
+    let archive_entry_kind = match entry.kind() {
+        ManifestEntryKind::Directory => ArchiveEntryKind::Directory,
+        ManifestEntryKind::File | ManifestEntryKind::Symlink | ManifestEntryKind::HardLink => {
+            ArchiveEntryKind::File
+        }
+    };
+    archive_paths.insert(path.as_path(), archive_entry_kind)?;
+    let link = link_for_entry(entry, &path)?;
+    Ok((path, archive_entry_kind, link))
+}
+
+// Files go first, then directories deepest-first, so a restored read-only or
+// untraversable directory mode never blocks reaching the entries inside it
+// and restoring a child does not bump its parent's mtime afterwards.
+fn consented_entities(
+    scanned_entries: Vec<ScannedEntry>,
+    on_archive_file: Option<&OnArchiveFileFn>,
+) -> Result<Vec<ExtractionEntity>, Error> {
     let mut entities = Vec::new();
+    let mut extracted_files = BTreeSet::new();
     for entry in scanned_entries {
         if let Some(on_archive_file) = on_archive_file {
             let unpack_allowed =
@@ -637,19 +857,197 @@ fn prepare_manifest_extraction_entities(
                 continue;
             }
         }
+        // A hard link whose file was declined would otherwise link to whatever
+        // already sits at that path.
+        match &entry.kind {
+            ExtractionKind::File(_) => {
+                extracted_files.insert(entry.relative_path.as_path().to_path_buf());
+            }
+            ExtractionKind::HardLink(_, link_target)
+                if !extracted_files.contains(link_target.as_path()) =>
+            {
+                continue;
+            }
+            _ => {}
+        }
 
         entities.push(ExtractionEntity {
             full_path: entry.full_path,
             relative_path: entry.relative_path,
             archive_index: entry.archive_index,
             kind: entry.kind,
+            metadata: entry.metadata,
         });
     }
 
-    Ok(PreparedExtraction {
-        output_root,
-        entities,
-    })
+    Ok(entities)
+}
+
+enum ExtractionLink {
+    Symlink(Vec<u8>),
+    HardLink(NormalizedArchivePath),
+}
+
+// Link targets are checked for every entry, selected or not, like paths.
+fn link_for_entry(
+    entry: &ManifestEntry,
+    path: &NormalizedArchivePath,
+) -> Result<Option<ExtractionLink>, Error> {
+    let unsafe_target = || Error::UnsafeLinkTarget(path.as_path().to_path_buf());
+    match (entry.kind(), entry.link_target()) {
+        (ManifestEntryKind::Symlink, Some(target)) => {
+            check_symlink_target(path, target).map_err(|_| unsafe_target())?;
+            Ok(Some(ExtractionLink::Symlink(target.to_vec())))
+        }
+        (ManifestEntryKind::HardLink, Some(target)) => {
+            let target =
+                NormalizedArchivePath::from_manifest_bytes(target).map_err(|_| unsafe_target())?;
+            Ok(Some(ExtractionLink::HardLink(target)))
+        }
+        (ManifestEntryKind::Symlink | ManifestEntryKind::HardLink, None) => Err(unsafe_target()),
+        (ManifestEntryKind::File | ManifestEntryKind::Directory, _) => Ok(None),
+    }
+}
+
+// Links are made in manifest order once every file has committed, so a hard
+// link's file is already in place.
+fn create_extracted_links(
+    stor: &storage::FileStorage,
+    prepared: &PreparedExtraction,
+) -> Result<Vec<CommittedArtifact>, Error> {
+    let root = prepared.output_root.target_path();
+    let mut artifacts = Vec::new();
+    for entity in &prepared.entities {
+        let relative = entity.relative_path.as_path();
+        let (target, created) = match &entity.kind {
+            ExtractionKind::Symlink(target, link_target) => (
+                target,
+                stor.create_unpack_symlink(
+                    root,
+                    relative,
+                    link_target,
+                    link_overwrite_policy(target),
+                ),
+            ),
+            ExtractionKind::HardLink(target, link_target) => (
+                target,
+                stor.create_unpack_hard_link(
+                    root,
+                    relative,
+                    link_target.as_path(),
+                    link_overwrite_policy(target),
+                ),
+            ),
+            ExtractionKind::Directory(_) | ExtractionKind::File(_) => continue,
+        };
+        created.map_err(|source| Error::CreateLink {
+            path: entity.full_path.clone(),
+            source,
+        })?;
+        artifacts.push(CommittedArtifact::new(
+            target.role(),
+            entity.full_path.clone(),
+        ));
+    }
+    Ok(artifacts)
+}
+
+fn link_overwrite_policy(target: &ResolvedTarget) -> OverwritePolicy {
+    target
+        .overwrite_policy()
+        .unwrap_or(OverwritePolicy::CreateNew)
+}
+
+fn apply_extracted_metadata(
+    stor: &storage::FileStorage,
+    prepared: &PreparedExtraction,
+    options: MetadataOptions,
+) -> Result<(), Error> {
+    if options.is_none() {
+        return Ok(());
+    }
+
+    // Links carry no metadata of their own: a symlink's is never followed and
+    // a hard link shares its file's.
+    let mut ordered = prepared
+        .entities
+        .iter()
+        .filter(|entity| !entity.metadata.is_empty())
+        .filter(|entity| {
+            matches!(
+                entity.kind,
+                ExtractionKind::File(_) | ExtractionKind::Directory(_)
+            )
+        })
+        .collect::<Vec<_>>();
+    ordered.sort_by_key(|entity| match entity.kind {
+        ExtractionKind::Directory(_) => (
+            1,
+            std::cmp::Reverse(entity.relative_path.as_path().components().count()),
+        ),
+        _ => (0, std::cmp::Reverse(0)),
+    });
+    for entity in ordered {
+        stor.apply_unpack_metadata(
+            prepared.output_root.target_path(),
+            entity.relative_path.as_path(),
+            &entity.metadata,
+            options,
+        )
+        .map_err(|source| Error::ApplyMetadata {
+            path: entity.full_path.clone(),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+// Directories that contain a selected entry are kept so its parent exists at
+// commit time, even when the directory itself was not selected.
+fn select_manifest_entries(entries: &[ManifestEntry], selection: &ArchiveSelection) -> Vec<bool> {
+    let mut selected = entries
+        .iter()
+        .map(|entry| selection.selects(entry.normalized_path()))
+        .collect::<Vec<_>>();
+    if selection.is_all() {
+        return selected;
+    }
+
+    // A hard link has no body of its own, so it brings its file along.
+    let link_targets = entries
+        .iter()
+        .zip(&selected)
+        .filter(|(entry, selected)| **selected && entry.kind() == ManifestEntryKind::HardLink)
+        .filter_map(|(entry, _)| entry.link_target())
+        .map(<[u8]>::to_vec)
+        .collect::<BTreeSet<_>>();
+    for (entry, selected) in entries.iter().zip(&mut selected) {
+        if entry.kind() == ManifestEntryKind::File && link_targets.contains(entry.normalized_path())
+        {
+            *selected = true;
+        }
+    }
+
+    let ancestors = entries
+        .iter()
+        .zip(&selected)
+        .filter(|(_, selected)| **selected)
+        .flat_map(|(entry, _)| {
+            let path = entry.normalized_path();
+            path.iter()
+                .enumerate()
+                .filter(|(_, byte)| **byte == b'/')
+                .filter_map(|(end, _)| path.get(..end))
+        })
+        .collect::<BTreeSet<_>>();
+    for (entry, selected) in entries.iter().zip(&mut selected) {
+        if entry.kind() == ManifestEntryKind::Directory
+            && ancestors.contains(entry.normalized_path())
+        {
+            *selected = true;
+        }
+    }
+    selected
 }
 
 fn stage_manifest_file_body<R: Read>(
@@ -703,7 +1101,7 @@ fn copy_manifest_body<R: Read, W: Write>(
     Ok(())
 }
 
-fn drain_manifest_body<R: Read>(reader: &mut R, body_len: u64) -> Result<(), Error> {
+pub(crate) fn drain_manifest_body<R: Read>(reader: &mut R, body_len: u64) -> Result<(), Error> {
     let copied =
         io::copy(&mut reader.take(body_len), &mut io::sink()).map_err(map_body_io_error)?;
     if copied != body_len {
@@ -712,7 +1110,7 @@ fn drain_manifest_body<R: Read>(reader: &mut R, body_len: u64) -> Result<(), Err
     Ok(())
 }
 
-fn drain_trailing_plaintext_to_final_auth<R: Read>(reader: &mut R) -> Result<(), Error> {
+pub(crate) fn drain_trailing_plaintext_to_final_auth<R: Read>(reader: &mut R) -> Result<(), Error> {
     let trailing = io::copy(reader, &mut io::sink()).map_err(map_body_io_error)?;
     if trailing == 0 {
         return Ok(());
@@ -789,7 +1187,9 @@ fn revalidate_extraction_targets(
                     target,
                 )
                 .map_err(map_storage_path_error)?,
-            ExtractionKind::File(target) => stor
+            ExtractionKind::File(target)
+            | ExtractionKind::Symlink(target, _)
+            | ExtractionKind::HardLink(target, _) => stor
                 .revalidate_unpack_target(&output_dir, entity.relative_path.as_path(), target)
                 .map_err(map_storage_path_error)?,
         }
@@ -797,7 +1197,7 @@ fn revalidate_extraction_targets(
     Ok(())
 }
 
-fn map_payload_error(error: PayloadError) -> Error {
+pub(crate) fn map_payload_error(error: PayloadError) -> Error {
     match error {
         PayloadError::Io(error) => map_body_io_error(error),
         error => Error::ArchivePayload(error),
@@ -844,7 +1244,7 @@ fn map_staged_inner_error(path: PathBuf, inner: Box<dyn std::error::Error + Send
     }
 }
 
-fn map_body_io_error(error: io::Error) -> Error {
+pub(crate) fn map_body_io_error(error: io::Error) -> Error {
     let kind = error.kind();
     let message = error.to_string();
     match error.into_inner() {
@@ -0,0 +1,112 @@
+//! Lists the entries of a packed archive from its `DXAR` manifest.
+//!
+//! The manifest precedes every body frame, so only the payload blocks that hold
+//! it are decrypted. Decryption stops there and the final block is never
+//! reached: a listing is not final-authenticated, and a truncated or tampered
+//! archive can still list cleanly. Only a full unpack or `verify` proves the
+//! archive intact.
+
+use std::path::Path;
+
+use core::payload::{ArchiveManifest, ManifestEntry, PayloadKind};
+use core::stream::V1PayloadDecryptingReader;
+
+use super::{Error, UncommittedPlaintextReader, map_payload_error};
+use crate::decrypt;
+use crate::key::Credential;
+use crate::storage;
+use crate::storage::identity::{PathIdentityGraph, PathRole, ResolvedTarget};
+
+#[derive(Debug)]
+pub struct ListIntent {
+    input_target: ResolvedTarget,
+    detached_header_target: Option<ResolvedTarget>,
+    credential: Credential,
+}
+
+impl ListIntent {
+    pub fn new<P>(
+        input_path: P,
+        detached_header_path: Option<&Path>,
+        credential: impl Into<Credential>,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut graph = PathIdentityGraph::new();
+        let input_target = graph
+            .add_existing(input_path, PathRole::Input)
+            .map_err(Error::PathIdentity)?;
+        let detached_header_target = detached_header_path
+            .map(|path| graph.add_existing(path, PathRole::DetachedHeader))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+
+        Ok(Self {
+            input_target,
+            detached_header_target,
+            credential: credential.into(),
+        })
+    }
+}
+
+/// Manifest entries read before final authentication.
+#[derive(Debug)]
+pub struct UncommittedListing {
+    manifest: ArchiveManifest,
+}
+
+impl UncommittedListing {
+    #[must_use]
+    pub fn entries(&self) -> &[ManifestEntry] {
+        self.manifest.entries()
+    }
+}
+
+/// Decrypts the archive manifest and stops.
+///
+/// The returned entries authenticated block by block but the archive as a whole
+/// did not; callers must present them as unverified.
+pub fn execute_uncommitted(intent: ListIntent) -> Result<UncommittedListing, Error> {
+    let ListIntent {
+        input_target,
+        detached_header_target,
+        credential,
+    } = intent;
+
+    let stor = storage::FileStorage;
+    let input = stor
+        .read_resolved_existing_no_follow(&input_target)
+        .map_err(Error::Storage)?;
+    let reader = input.try_reader().map_err(Error::Storage)?;
+    let detached_header = detached_header_target
+        .as_ref()
+        .map(|target| stor.read_resolved_existing_no_follow(target))
+        .transpose()
+        .map_err(Error::Storage)?;
+    let header_reader = detached_header
+        .as_ref()
+        .map(|header| header.try_reader())
+        .transpose()
+        .map_err(Error::Storage)?;
+
+    let payload = decrypt::read_v1_payload(header_reader, reader).map_err(Error::Decrypt)?;
+    if payload.header().payload_kind() != PayloadKind::ManifestArchive
+        || !payload.header().payload_framing().is_manifest_first()
+    {
+        return Err(Error::OpenArchive);
+    }
+
+    let master_key = decrypt::decrypt_master_key(&payload, credential).map_err(Error::Decrypt)?;
+    let mut encrypted_reader = reader.borrow_mut();
+    let mut plaintext_reader =
+        V1PayloadDecryptingReader::new(master_key, &payload, &mut *encrypted_reader)
+            .map_err(decrypt::map_stream_error)
+            .map_err(Error::Decrypt)?;
+    let manifest =
+        ArchiveManifest::read_from(&mut UncommittedPlaintextReader(&mut plaintext_reader))
+            .map_err(map_payload_error)?;
+
+    Ok(UncommittedListing { manifest })
+}
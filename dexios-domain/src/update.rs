@@ -0,0 +1,853 @@
+//! This contains the logic for adding, replacing and removing entries of a
+//! packed archive without unpacking it to disk.
+//!
+//! This is known as "updating" within Dexios.
+//!
+//! The existing payload is decrypted and streamed into a new staged archive,
+//! with the requested changes applied to the manifest and body frames on the
+//! way through.
+//!
+//! The input's keyslots cannot be copied into the output as they are. Every
+//! keyslot's wrapping AAD binds the payload nonce, and keeping that nonce under
+//! the same master key for a different payload would reuse the stream
+//! keystream. The updated archive is therefore encrypted under a fresh master
+//! key and payload nonce, and every keyslot is rebuilt for it the way
+//! `key rotate` does. A passphrase or keyfile keyslot takes its credential; a
+//! recipient keyslot only takes the recipient's public key.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use core::header::ParsedV1Payload;
+use core::header::v1::{KeyslotKdf, V1Header};
+use core::payload::{
+    ArchiveBodyFrameHeader, ArchiveManifest, BodyCompression, BodyDecoder, ManifestEntry,
+    ManifestEntryKind, PayloadError, PayloadFramingProfile, PayloadKind,
+};
+use core::primitives::MasterKey;
+use core::recipient::RecipientPublicKey;
+use core::stream::{StreamError, V1PayloadDecryptingReader};
+
+use crate::archive::{ArchiveLimitError, ArchiveLimits, MetadataOptions};
+use crate::archive_path::{ArchivePathError, NormalizedArchivePath};
+use crate::decrypt;
+use crate::key::{self, Credential};
+use crate::pack::{self, ArchiveSourceEntry, LinkedStagedWriter, OnArchiveEntryFn, PackSource};
+use crate::parity::{self, ResolvedParitySidecar};
+use crate::storage;
+use crate::storage::identity::{
+    IdentityError, OverwritePolicy, PathIdentityGraph, PathRole, ResolvedTarget,
+};
+use crate::storage::transaction::{CommitReceipt, LinkedOutputTransaction, TransactionError};
+use crate::unpack::{ManifestBody, UncommittedPlaintextReader};
+use crate::utils::gen_master_key;
+use crate::workflow_error::{
+    WorkflowErrorClass, classify_identity_error, classify_storage_error, classify_transaction_error,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    NothingToUpdate,
+    OpenArchive,
+    /// No credential opened the keyslots at these physical indexes, and no
+    /// recipient public key was left to rebuild them.
+    LockedKeyslots(Vec<usize>),
+    /// This many recipient public keys had no unopened recipient keyslot to
+    /// rebuild.
+    UnusedRecipients(usize),
+    EntryNotFound(PathBuf),
+    PathConflict(PathBuf),
+    /// A kept hard link names a file that was removed or replaced.
+    HardLinkTarget(PathBuf),
+    ArchivePath(PathBuf),
+    ArchiveLimit(ArchiveLimitError),
+    ArchivePayload(PayloadError),
+    Storage(storage::Error),
+    PathIdentity(IdentityError),
+    Transaction(TransactionError),
+    TransactionWriter,
+    Decrypt(decrypt::Error),
+    Key(key::Error),
+    Pack(pack::Error),
+    Parity(parity::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NothingToUpdate => f.write_str("No entries to add or remove"),
+            Self::OpenArchive => f.write_str("Unable to open archive"),
+            Self::LockedKeyslots(slots) => write!(
+                f,
+                "Keyslot(s) {} were not opened; every keyslot needs its key, or its recipient's public key, to carry it into the updated archive",
+                slots
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::UnusedRecipients(count) => write!(
+                f,
+                "{count} recipient public key(s) had no unopened recipient keyslot to rebuild"
+            ),
+            Self::EntryNotFound(path) => {
+                write!(f, "No archive entry to remove at {}", path.display())
+            }
+            Self::PathConflict(path) => {
+                write!(
+                    f,
+                    "Updated archive path conflicts with an existing entry: {}",
+                    path.display()
+                )
+            }
+            Self::HardLinkTarget(path) => write!(
+                f,
+                "Hard link {} names a file that is removed or replaced",
+                path.display()
+            ),
+            Self::ArchivePath(path) => {
+                write!(
+                    f,
+                    "Archive path error: Unsafe archive path: {}",
+                    path.display()
+                )
+            }
+            Self::ArchiveLimit(inner) => write!(f, "Archive limit error: {inner}"),
+            Self::ArchivePayload(inner) => write!(f, "Archive payload error: {inner}"),
+            Self::Storage(inner) => write!(f, "Storage error: {inner}"),
+            Self::PathIdentity(inner) => write!(f, "Path identity error: {inner}"),
+            Self::Transaction(inner) => write!(f, "Update transaction failed: {inner}"),
+            Self::TransactionWriter => f.write_str("Unable to release staged update writers"),
+            Self::Decrypt(inner) => write!(f, "Decrypt error: {inner}"),
+            Self::Key(inner) => write!(f, "{inner}"),
+            Self::Pack(inner) => write!(f, "{inner}"),
+            Self::Parity(inner) => write!(f, "Unable to refresh the parity sidecar: {inner}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ArchiveLimit(error) => Some(error),
+            Self::ArchivePayload(error) => Some(error),
+            Self::Storage(error) => Some(error),
+            Self::PathIdentity(error) => Some(error),
+            Self::Transaction(error) => Some(error),
+            Self::Decrypt(error) => Some(error),
+            Self::Key(error) => Some(error),
+            Self::Pack(error) => Some(error),
+            Self::Parity(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    #[must_use]
+    pub fn workflow_class(&self) -> WorkflowErrorClass {
+        match self {
+            Self::Transaction(error) => classify_transaction_error(error),
+            _ if self.is_resource_pressure() => WorkflowErrorClass::ResourcePressure,
+            Self::NothingToUpdate | Self::LockedKeyslots(_) | Self::UnusedRecipients(_) => {
+                WorkflowErrorClass::UnsupportedWorkflow
+            }
+            Self::EntryNotFound(_)
+            | Self::PathConflict(_)
+            | Self::HardLinkTarget(_)
+            | Self::ArchivePath(_)
+            | Self::ArchiveLimit(_) => WorkflowErrorClass::UnsafePath,
+            Self::ArchivePayload(PayloadError::Io(_)) | Self::TransactionWriter => {
+                WorkflowErrorClass::IoFailure
+            }
+            Self::OpenArchive | Self::ArchivePayload(_) => WorkflowErrorClass::MalformedFormat,
+            Self::Storage(error) => classify_storage_error(error),
+            Self::PathIdentity(error) => classify_identity_error(error),
+            Self::Decrypt(error) => error.workflow_class(),
+            Self::Key(error) => error.workflow_class(),
+            Self::Pack(error) => error.workflow_class(),
+            Self::Parity(error) => error.workflow_class(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_resource_pressure(&self) -> bool {
+        storage::error_chain_contains_resource_pressure(self)
+    }
+}
+
+impl From<pack::Error> for Error {
+    fn from(value: pack::Error) -> Self {
+        Self::Pack(value)
+    }
+}
+
+fn map_archive_path_error(error: ArchivePathError) -> Error {
+    match error {
+        ArchivePathError::Unsafe(path) => Error::ArchivePath(path),
+    }
+}
+
+pub struct UpdateIntent {
+    input: storage::Entry<fs::File>,
+    output_target: ResolvedTarget,
+    parity_sidecar: Option<ResolvedParitySidecar>,
+    credentials: Vec<Credential>,
+    recipients: Vec<RecipientPublicKey>,
+    drop_locked_keyslots: bool,
+    additions: Vec<PackSource>,
+    removals: Vec<NormalizedArchivePath>,
+    metadata: MetadataOptions,
+    on_archive_entry: Option<OnArchiveEntryFn>,
+}
+
+impl UpdateIntent {
+    /// Writes `input_path` with `additions` packed in and `removals` taken out
+    /// to `output_path`.
+    ///
+    /// Added sources are named by their archive roots the way `pack` names
+    /// them, and replace any existing entry at the same archive path. A
+    /// removal takes out the entry at that archive path and everything below
+    /// it.
+    ///
+    /// `credential` must open one of the input's keyslots; an input with more
+    /// keyslots needs the others' credentials through
+    /// [`Self::with_credentials`], or their recipients' public keys through
+    /// [`Self::with_recipients`].
+    pub fn new<I, O, S, P>(
+        input_path: I,
+        output_path: O,
+        output_overwrite: OverwritePolicy,
+        credential: impl Into<Credential>,
+        additions: Vec<S>,
+        removals: Vec<P>,
+    ) -> Result<Self, Error>
+    where
+        I: AsRef<Path>,
+        O: AsRef<Path>,
+        S: AsRef<Path>,
+        P: AsRef<Path>,
+    {
+        if additions.is_empty() && removals.is_empty() {
+            return Err(Error::NothingToUpdate);
+        }
+        let removals = removals
+            .iter()
+            .map(|path| NormalizedArchivePath::from_path(path.as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(map_archive_path_error)?;
+
+        let mut graph = PathIdentityGraph::new();
+        let input_target = graph
+            .add_existing(input_path, PathRole::Input)
+            .map_err(Error::PathIdentity)?;
+        let additions = if additions.is_empty() {
+            Vec::new()
+        } else {
+            let source_paths = additions
+                .iter()
+                .map(|path| path.as_ref().to_path_buf())
+                .collect::<Vec<_>>();
+            pack::resolve_pack_sources(&mut graph, &source_paths)?
+        };
+        let output_target = graph
+            .add_output(output_path, PathRole::GeneratedOutput, output_overwrite)
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+        // a sidecar left next to a replaced output would repair it back
+        let parity_sidecar =
+            ResolvedParitySidecar::existing_for(&output_target).map_err(Error::Parity)?;
+
+        let input = storage::FileStorage
+            .read_resolved_existing_no_follow(&input_target)
+            .map_err(Error::Storage)?;
+
+        Ok(Self {
+            input,
+            output_target,
+            parity_sidecar,
+            credentials: vec![credential.into()],
+            recipients: Vec::new(),
+            drop_locked_keyslots: false,
+            additions,
+            removals,
+            metadata: MetadataOptions::none(),
+            on_archive_entry: None,
+        })
+    }
+
+    /// Records the file metadata `options` selects for added entries. Kept
+    /// entries keep whatever the input recorded.
+    #[must_use]
+    pub const fn with_metadata(mut self, options: MetadataOptions) -> Self {
+        self.metadata = options;
+        self
+    }
+
+    /// Adds credentials for the input's other keyslots. Every keyslot is
+    /// rebuilt in the updated archive at its index and with its label, so
+    /// each must be opened by one of the credentials; otherwise the update is
+    /// refused with [`Error::LockedKeyslots`].
+    #[must_use]
+    pub fn with_credentials<I>(mut self, credentials: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Credential>,
+    {
+        self.credentials
+            .extend(credentials.into_iter().map(Into::into));
+        self
+    }
+
+    /// Rebuilds recipient keyslots that no credential opens for these public
+    /// keys. A recipient keyslot does not say whose it is, so the keys are
+    /// taken in order for the unopened recipient keyslots in physical order;
+    /// a key whose identity was given as a credential is skipped.
+    #[must_use]
+    pub fn with_recipients(mut self, recipients: Vec<RecipientPublicKey>) -> Self {
+        self.recipients = recipients;
+        self
+    }
+
+    /// Writes the updated archive without the keyslots that stay unopened,
+    /// instead of refusing with [`Error::LockedKeyslots`].
+    #[must_use]
+    pub const fn with_locked_keyslots_dropped(mut self) -> Self {
+        self.drop_locked_keyslots = true;
+        self
+    }
+
+    #[must_use]
+    pub fn with_archive_entry_observer(mut self, observer: OnArchiveEntryFn) -> Self {
+        self.on_archive_entry = Some(observer);
+        self
+    }
+}
+
+/// Where an entry of the updated manifest comes from.
+#[derive(Clone, Copy)]
+enum EntrySource {
+    /// The input entry at this index.
+    Kept(usize),
+    /// The added entry at this index.
+    Added(usize),
+}
+
+/// The updated manifest, in order, and where each of its entries comes from.
+struct UpdatePlan {
+    manifest: ArchiveManifest,
+    sources: Vec<EntrySource>,
+}
+
+pub fn execute(intent: UpdateIntent) -> Result<CommitReceipt, Error> {
+    let UpdateIntent {
+        input,
+        output_target,
+        parity_sidecar,
+        credentials,
+        recipients,
+        drop_locked_keyslots,
+        additions,
+        removals,
+        metadata,
+        on_archive_entry,
+    } = intent;
+
+    let added =
+        pack::materialize_archive_entries(&additions, on_archive_entry.as_deref(), None, metadata)?;
+    pack::validate_generated_targets_against_entries(&added, &output_target, &[])?;
+
+    let reader = input.try_reader().map_err(Error::Storage)?;
+    let payload = decrypt::read_v1_payload(None, reader).map_err(Error::Decrypt)?;
+    let header = payload.header();
+    if header.payload_kind() != PayloadKind::ManifestArchive
+        || !header.payload_framing().is_manifest_first()
+    {
+        return Err(Error::OpenArchive);
+    }
+    let (master_key, replacement_header, replacement_master_key) =
+        unlock_input(&payload, credentials, recipients, drop_locked_keyslots)?;
+
+    let mut transaction = LinkedOutputTransaction::new();
+    let output_index = transaction
+        .stage(output_target)
+        .map_err(Error::Transaction)?;
+    let parity_index = parity_sidecar
+        .map(|sidecar| {
+            transaction
+                .stage(sidecar.target)
+                .map(|index| (index, sidecar.parity_blocks))
+        })
+        .transpose()
+        .map_err(Error::Transaction)?;
+    let transaction = Rc::new(RefCell::new(transaction));
+    let output_writer = RefCell::new(LinkedStagedWriter::new(
+        Rc::clone(&transaction),
+        output_index,
+    ));
+
+    let mut encrypted_reader = reader.borrow_mut();
+    let mut plaintext_reader =
+        V1PayloadDecryptingReader::new(master_key, &payload, &mut *encrypted_reader)
+            .map_err(decrypt::map_stream_error)
+            .map_err(Error::Decrypt)?;
+    let stream_result = stream_updated_archive(
+        &mut UncommittedPlaintextReader(&mut plaintext_reader),
+        payload.header().payload_framing(),
+        &mut *output_writer.borrow_mut(),
+        &replacement_header,
+        replacement_master_key,
+        &added,
+        &removals,
+    );
+    if let Err(error) = stream_result {
+        let resource_pressure = output_writer.borrow().resource_pressure_kind();
+        return Err(match error {
+            Error::Pack(error) => Error::Pack(pack::map_encrypt_output_resource_pressure(
+                error,
+                resource_pressure,
+            )),
+            error => error,
+        });
+    }
+    // Nothing commits until the input's final block has authenticated.
+    plaintext_reader
+        .finish()
+        .map_err(decrypt::map_stream_error)
+        .map_err(Error::Decrypt)?;
+
+    drop(output_writer);
+    let mut transaction = Rc::try_unwrap(transaction)
+        .map_err(|_| Error::TransactionWriter)?
+        .into_inner();
+    if let Some((parity_index, parity_blocks)) = parity_index {
+        parity::stage_sidecar(&mut transaction, output_index, parity_index, parity_blocks)
+            .map_err(Error::Transaction)?;
+    }
+    transaction.commit_all().map_err(Error::Transaction)
+}
+
+// The input's keyslots cannot be carried over with `V1Header::with_keyslots`:
+// each one wraps the input's master key under AAD that binds the input's
+// payload nonce, and the updated payload needs a fresh master key and nonce
+// so the input's keystream is never reused. Every keyslot is therefore
+// rebuilt: a passphrase keeps its slot's KDF profile under a fresh salt, and
+// a recipient keyslot is wrapped again for its public key, which comes from
+// the identity that opened it or from `recipients`. The header keeps the
+// input's keyslot capacity, labels and header trailer.
+fn unlock_input(
+    payload: &ParsedV1Payload,
+    credentials: Vec<Credential>,
+    recipients: Vec<RecipientPublicKey>,
+    drop_locked_keyslots: bool,
+) -> Result<(MasterKey, V1Header, MasterKey), Error> {
+    let header = payload.header();
+    let (master_key, mut kept) =
+        key::rotate::open_keyslots(header, credentials).map_err(Error::Key)?;
+
+    let opened_recipients = kept
+        .iter()
+        .filter_map(key::rotate::KeptKeyslot::recipient_public_key)
+        .collect::<Vec<_>>();
+    let mut recipients = recipients
+        .into_iter()
+        .filter(|recipient| !opened_recipients.contains(recipient));
+    let mut locked = Vec::new();
+    for keyslot in header.keyslots() {
+        let index = keyslot.physical_index();
+        if kept.iter().any(|slot| slot.physical_index() == index) {
+            continue;
+        }
+        let recipient = (keyslot.kdf() == KeyslotKdf::X25519Recipient)
+            .then(|| recipients.next())
+            .flatten();
+        match recipient {
+            Some(recipient) => kept
+                .push(key::rotate::KeptKeyslot::recipient(index, recipient).map_err(Error::Key)?),
+            None => locked.push(index),
+        }
+    }
+    let unused_recipients = recipients.count();
+    if unused_recipients > 0 {
+        return Err(Error::UnusedRecipients(unused_recipients));
+    }
+    if !locked.is_empty() && !drop_locked_keyslots {
+        return Err(Error::LockedKeyslots(locked));
+    }
+    kept.sort_by_key(key::rotate::KeptKeyslot::physical_index);
+
+    let replacement_master_key = gen_master_key();
+    let replacement_header =
+        key::rotate::rotated_header(header, &replacement_master_key, &kept).map_err(Error::Key)?;
+    Ok((master_key, replacement_header, replacement_master_key))
+}
+
+#[expect(
+    clippy::expect_used,
+    reason = "manifest entry counts are bounded below MAX_MANIFEST_ENTRY_COUNT (< u32::MAX)"
+)]
+fn stream_updated_archive<R, W>(
+    plaintext_reader: &mut R,
+    framing: PayloadFramingProfile,
+    writer: &mut W,
+    header: &V1Header,
+    master_key: MasterKey,
+    added: &[ArchiveSourceEntry<fs::File>],
+    removals: &[NormalizedArchivePath],
+) -> Result<(), Error>
+where
+    R: Read,
+    W: Write,
+{
+    let input_manifest = ArchiveManifest::read_from(plaintext_reader).map_err(map_payload_error)?;
+    let plan = plan_update(&input_manifest, added, removals)?;
+
+    let compressed = framing == PayloadFramingProfile::ManifestFirstCompressed;
+    let compression = compressed.then(BodyCompression::default);
+    let mut encrypting_writer =
+        crate::encrypt::begin_v1_payload_writer_with_header(writer, None, header, master_key)
+            .map_err(pack::Error::Encrypt)?;
+    plan.manifest
+        .write_to(&mut encrypting_writer)
+        .map_err(Error::ArchivePayload)?;
+
+    // Kept entries keep their input order, so the input's body frames are
+    // read front to back and the dropped ones drained on the way.
+    let mut input_bodies = InputBodies {
+        reader: plaintext_reader,
+        framing,
+        entries: input_manifest.entries(),
+        next: 0,
+    };
+    for ((new_index, source), manifest_entry) in
+        plan.sources.iter().enumerate().zip(plan.manifest.entries())
+    {
+        let new_index = u32::try_from(new_index).expect("manifest entry count is bounded");
+        match *source {
+            EntrySource::Kept(index) => {
+                input_bodies.drain_before(index)?;
+                let Some((frame_header, mut body)) = input_bodies.open(index)? else {
+                    continue;
+                };
+                let body_len = frame_header.body_len();
+                let frame_header = ArchiveBodyFrameHeader::new(new_index, body_len)
+                    .map_err(Error::ArchivePayload)?;
+                pack::write_body_frame(
+                    &mut encrypting_writer,
+                    frame_header,
+                    compression,
+                    |writer| copy_body(&mut body, writer, body_len),
+                )?;
+                body.check_digest(u32::try_from(index).expect("manifest entry count is bounded"))
+                    .map_err(Error::ArchivePayload)?;
+            }
+            EntrySource::Added(index) => {
+                let Some(file) = added.get(index).and_then(ArchiveSourceEntry::file) else {
+                    continue;
+                };
+                let body_len = pack::entry_body_len(file)?;
+                let frame_header = ArchiveBodyFrameHeader::new(new_index, body_len)
+                    .map_err(Error::ArchivePayload)?;
+                pack::write_body_frame(
+                    &mut encrypting_writer,
+                    frame_header,
+                    compression,
+                    |writer| pack::write_entry_body(file, manifest_entry, body_len, writer),
+                )?;
+            }
+        }
+    }
+    input_bodies.drain_before(input_manifest.entries().len())?;
+
+    drain_trailing_plaintext(plaintext_reader)?;
+    crate::encrypt::finish_v1_payload_writer(encrypting_writer)
+        .map(|_| ())
+        .map_err(|error| Error::Pack(pack::Error::Encrypt(error)))
+}
+
+type InputBody<'a, R> = ManifestBody<BodyDecoder<&'a mut R>>;
+
+/// The input's body frames, read in manifest order.
+struct InputBodies<'a, R: Read> {
+    reader: &'a mut R,
+    framing: PayloadFramingProfile,
+    entries: &'a [ManifestEntry],
+    next: usize,
+}
+
+impl<R: Read> InputBodies<'_, R> {
+    /// Drains the bodies of input entries before `index` that were not copied.
+    fn drain_before(&mut self, index: usize) -> Result<(), Error> {
+        while self.next < index {
+            if let Some((frame_header, mut body)) = self.open(self.next)? {
+                copy_body(&mut body, &mut io::sink(), frame_header.body_len())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the frame header of the entry at `index`, which must be the next
+    /// one, and returns a reader over its body if it is a file.
+    #[expect(
+        clippy::expect_used,
+        reason = "manifest entry counts are bounded below MAX_MANIFEST_ENTRY_COUNT (< u32::MAX), and File entries always carry a validated body length"
+    )]
+    fn open(
+        &mut self,
+        index: usize,
+    ) -> Result<Option<(ArchiveBodyFrameHeader, InputBody<'_, R>)>, Error> {
+        self.next = index.saturating_add(1);
+        let Some(entry) = self.entries.get(index) else {
+            return Ok(None);
+        };
+        if entry.kind() != ManifestEntryKind::File {
+            return Ok(None);
+        }
+        let expected_index = u32::try_from(index).expect("manifest entry count is bounded");
+        let expected_len = entry
+            .body_len()
+            .expect("file manifest entry has body length");
+        let frame_header = read_body_frame_header(self.reader, self.framing, expected_index)?;
+        if frame_header.body_len() != expected_len {
+            return Err(Error::ArchivePayload(
+                PayloadError::BodyFrameLengthMismatch {
+                    expected: expected_len,
+                    actual: frame_header.body_len(),
+                },
+            ));
+        }
+        let body = BodyDecoder::new(&mut *self.reader, frame_header.codec(), expected_len)
+            .map_err(Error::ArchivePayload)?;
+        Ok(Some((
+            frame_header,
+            ManifestBody::new(body, entry.blake3_digest()),
+        )))
+    }
+}
+
+// Input entries keep their order with removed and replaced ones dropped. An
+// added directory takes the place of the directory it replaces so that kept
+// entries below it still follow it; every other added entry follows the kept
+// ones in the order it was walked.
+fn plan_update(
+    input_manifest: &ArchiveManifest,
+    added: &[ArchiveSourceEntry<fs::File>],
+    removals: &[NormalizedArchivePath],
+) -> Result<UpdatePlan, Error> {
+    // Added files get digests when the input recorded any, so a digested
+    // archive stays fully digested.
+    let with_digests = input_manifest
+        .entries()
+        .iter()
+        .any(|entry| entry.blake3_digest().is_some());
+    let added_entries = added
+        .iter()
+        .map(|entry| pack::manifest_entry_for(entry, with_digests))
+        .collect::<Result<Vec<_>, _>>()?;
+    let added_by_path = added_entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| (entry.normalized_path(), index))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut unmatched_removals = removals
+        .iter()
+        .map(NormalizedArchivePath::as_manifest_bytes)
+        .collect::<BTreeSet<_>>();
+    let mut sources = Vec::new();
+    let mut placed = BTreeSet::new();
+    for (index, entry) in input_manifest.entries().iter().enumerate() {
+        let path = entry.normalized_path();
+        let mut removed = false;
+        for removal in removals
+            .iter()
+            .map(NormalizedArchivePath::as_manifest_bytes)
+        {
+            if path_is_within(path, removal) {
+                unmatched_removals.remove(removal);
+                removed = true;
+            }
+        }
+        if removed {
+            continue;
+        }
+        match added_by_path.get(path) {
+            None => sources.push(EntrySource::Kept(index)),
+            Some(&added_index)
+                if entry.kind() == ManifestEntryKind::Directory
+                    && added_entries
+                        .get(added_index)
+                        .is_some_and(|added| added.kind() == ManifestEntryKind::Directory) =>
+            {
+                sources.push(EntrySource::Added(added_index));
+                placed.insert(added_index);
+            }
+            Some(_) => {}
+        }
+    }
+    if let Some(removal) = unmatched_removals.first() {
+        return Err(Error::EntryNotFound(display_path(removal)));
+    }
+    sources.extend(
+        (0..added_entries.len())
+            .filter(|index| !placed.contains(index))
+            .map(EntrySource::Added),
+    );
+
+    let limits = ArchiveLimits::defaults();
+    limits
+        .check_entry_count(sources.len())
+        .map_err(Error::ArchiveLimit)?;
+    let entries = sources
+        .iter()
+        .filter_map(|source| match *source {
+            EntrySource::Kept(index) => input_manifest.entries().get(index),
+            EntrySource::Added(index) => added_entries.get(index),
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let total_body = entries
+        .iter()
+        .filter_map(ManifestEntry::body_len)
+        .fold(0u64, u64::saturating_add);
+    limits
+        .check_total_body_bytes(total_body)
+        .map_err(Error::ArchiveLimit)?;
+    check_updated_entries(&entries, &sources)?;
+    let manifest = ArchiveManifest::new(entries).map_err(Error::ArchivePayload)?;
+
+    Ok(UpdatePlan { manifest, sources })
+}
+
+// Kept hard links must still name a kept file, and no entry may sit below
+// something that is not a directory.
+fn check_updated_entries(entries: &[ManifestEntry], sources: &[EntrySource]) -> Result<(), Error> {
+    let kept = || {
+        entries
+            .iter()
+            .zip(sources)
+            .filter(|(_, source)| matches!(source, EntrySource::Kept(_)))
+            .map(|(entry, _)| entry)
+    };
+    let kept_files = kept()
+        .filter(|entry| entry.kind() == ManifestEntryKind::File)
+        .map(ManifestEntry::normalized_path)
+        .collect::<BTreeSet<_>>();
+    for entry in kept() {
+        if entry.kind() == ManifestEntryKind::HardLink
+            && !entry
+                .link_target()
+                .is_some_and(|target| kept_files.contains(target))
+        {
+            return Err(Error::HardLinkTarget(display_path(entry.normalized_path())));
+        }
+    }
+
+    let kinds = entries
+        .iter()
+        .map(|entry| (entry.normalized_path(), entry.kind()))
+        .collect::<BTreeMap<_, _>>();
+    for entry in entries {
+        let path = entry.normalized_path();
+        let blocked = path
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte == b'/')
+            .filter_map(|(end, _)| path.get(..end))
+            .filter_map(|parent| kinds.get(parent))
+            .any(|kind| *kind != ManifestEntryKind::Directory);
+        if blocked {
+            return Err(Error::PathConflict(display_path(path)));
+        }
+    }
+    Ok(())
+}
+
+fn path_is_within(path: &[u8], root: &[u8]) -> bool {
+    path.strip_prefix(root)
+        .is_some_and(|rest| rest.is_empty() || rest.first() == Some(&b'/'))
+}
+
+fn display_path(path: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(path).into_owned())
+}
+
+fn read_body_frame_header<R: Read>(
+    plaintext_reader: &mut R,
+    framing: PayloadFramingProfile,
+    expected_index: u32,
+) -> Result<ArchiveBodyFrameHeader, Error> {
+    let frame_header = match ArchiveBodyFrameHeader::read_framed_from(plaintext_reader, framing) {
+        Ok(header) => header,
+        Err(PayloadError::TruncatedManifest) => {
+            return Err(Error::ArchivePayload(PayloadError::MissingBodyFrame(
+                expected_index,
+            )));
+        }
+        Err(error) => return Err(map_payload_error(error)),
+    };
+    if frame_header.entry_index() != expected_index {
+        return Err(Error::ArchivePayload(
+            PayloadError::BodyFrameOrderMismatch {
+                expected: expected_index,
+                actual: frame_header.entry_index(),
+            },
+        ));
+    }
+    Ok(frame_header)
+}
+
+fn copy_body<R, W>(body: &mut R, writer: &mut W, body_len: u64) -> Result<(), Error>
+where
+    R: Read,
+    W: Write + ?Sized,
+{
+    let copied = io::copy(&mut body.take(body_len), writer).map_err(map_body_io_error)?;
+    if copied != body_len {
+        return Err(Error::ArchivePayload(PayloadError::TruncatedManifest));
+    }
+    Ok(())
+}
+
+fn drain_trailing_plaintext<R: Read>(reader: &mut R) -> Result<(), Error> {
+    let trailing = io::copy(reader, &mut io::sink()).map_err(map_body_io_error)?;
+    if trailing == 0 {
+        return Ok(());
+    }
+
+    Err(Error::ArchivePayload(PayloadError::TrailingBytes(
+        usize::try_from(trailing).unwrap_or(usize::MAX),
+    )))
+}
+
+fn map_payload_error(error: PayloadError) -> Error {
+    match error {
+        PayloadError::Io(error) => map_body_io_error(error),
+        error => Error::ArchivePayload(error),
+    }
+}
+
+// Read errors carry the stream or payload error that caused them; write errors
+// come from the staged output.
+fn map_body_io_error(error: io::Error) -> Error {
+    let kind = error.kind();
+    let message = error.to_string();
+    match error.into_inner() {
+        Some(inner) => match inner.downcast::<StreamError>() {
+            Ok(stream_error) => Error::Decrypt(decrypt::map_stream_error(*stream_error)),
+            Err(inner) => match inner.downcast::<PayloadError>() {
+                Ok(payload_error) => Error::ArchivePayload(*payload_error),
+                Err(inner) => {
+                    Error::Pack(pack::Error::WriteDataWithSource(io::Error::other(inner)))
+                }
+            },
+        },
+        None => Error::Pack(pack::Error::WriteDataWithSource(io::Error::new(
+            kind, message,
+        ))),
+    }
+}
@@ -0,0 +1,275 @@
+//! This contains the logic for authenticating an encrypted file end to end
+//! without writing its plaintext anywhere.
+//!
+//! This is known as "verifying" within Dexios.
+//!
+//! Raw files are decrypted into a counting sink. Archives also have their
+//! manifest and every body frame checked exactly as unpack would, including
+//! any recorded content digests, so a verified archive is one that unpacks
+//! cleanly.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use core::payload::{ArchiveManifest, ManifestEntry, ManifestEntryKind, PayloadKind};
+use core::stream::{V1FinalAuth, V1PayloadDecryptingReader, V1PayloadStream};
+
+use crate::decrypt;
+use crate::key::Credential;
+use crate::storage;
+use crate::storage::identity::{IdentityError, PathIdentityGraph, PathRole, ResolvedTarget};
+use crate::unpack::{self, UncommittedPlaintextReader};
+use crate::workflow_error::{WorkflowErrorClass, classify_identity_error, classify_storage_error};
+
+#[derive(Debug)]
+pub enum Error {
+    Storage(storage::Error),
+    PathIdentity(IdentityError),
+    Decrypt(decrypt::Error),
+    Archive(unpack::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Storage(inner) => write!(f, "Storage error: {inner}"),
+            Self::PathIdentity(inner) => write!(f, "Path identity error: {inner}"),
+            Self::Decrypt(inner) => write!(f, "Decrypt error: {inner}"),
+            Self::Archive(inner) => write!(f, "{inner}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Storage(error) => Some(error),
+            Self::PathIdentity(error) => Some(error),
+            Self::Decrypt(error) => Some(error),
+            Self::Archive(error) => Some(error),
+        }
+    }
+}
+
+impl Error {
+    #[must_use]
+    pub fn workflow_class(&self) -> WorkflowErrorClass {
+        match self {
+            Self::Storage(error) => classify_storage_error(error),
+            Self::PathIdentity(error) => classify_identity_error(error),
+            Self::Decrypt(error) => error.workflow_class(),
+            Self::Archive(error) => error.workflow_class(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_resource_pressure(&self) -> bool {
+        storage::error_chain_contains_resource_pressure(self)
+    }
+}
+
+impl From<unpack::Error> for Error {
+    fn from(value: unpack::Error) -> Self {
+        Self::Archive(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct VerifyIntent {
+    input_target: ResolvedTarget,
+    detached_header_target: Option<ResolvedTarget>,
+    credential: Credential,
+}
+
+impl VerifyIntent {
+    pub fn new<P>(
+        input_path: P,
+        detached_header_path: Option<&Path>,
+        credential: impl Into<Credential>,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut graph = PathIdentityGraph::new();
+        let input_target = graph
+            .add_existing(input_path, PathRole::Input)
+            .map_err(Error::PathIdentity)?;
+        let detached_header_target = detached_header_path
+            .map(|path| graph.add_existing(path, PathRole::DetachedHeader))
+            .transpose()
+            .map_err(Error::PathIdentity)?;
+        graph.validate().map_err(Error::PathIdentity)?;
+
+        Ok(Self {
+            input_target,
+            detached_header_target,
+            credential: credential.into(),
+        })
+    }
+}
+
+/// Number of archive entries of each kind.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ArchiveEntryCounts {
+    pub files: usize,
+    pub directories: usize,
+    pub symlinks: usize,
+    pub hard_links: usize,
+}
+
+impl ArchiveEntryCounts {
+    fn count(entries: &[ManifestEntry]) -> Self {
+        entries.iter().fold(Self::default(), |mut counts, entry| {
+            let count = match entry.kind() {
+                ManifestEntryKind::File => &mut counts.files,
+                ManifestEntryKind::Directory => &mut counts.directories,
+                ManifestEntryKind::Symlink => &mut counts.symlinks,
+                ManifestEntryKind::HardLink => &mut counts.hard_links,
+            };
+            *count = count.saturating_add(1);
+            counts
+        })
+    }
+
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.files
+            .saturating_add(self.directories)
+            .saturating_add(self.symlinks)
+            .saturating_add(self.hard_links)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VerifiedPayload {
+    RawFile {
+        plaintext_len: u64,
+    },
+    Archive {
+        entries: ArchiveEntryCounts,
+        body_bytes: u64,
+        /// File bodies that matched the BLAKE3 digest their entry recorded.
+        digests: usize,
+    },
+}
+
+/// What was authenticated. Only built once the final block has authenticated.
+#[derive(Debug)]
+pub struct VerifyReport {
+    payload: VerifiedPayload,
+    _final_auth: V1FinalAuth,
+}
+
+impl VerifyReport {
+    #[must_use]
+    pub const fn payload(&self) -> VerifiedPayload {
+        self.payload
+    }
+}
+
+struct CountingSink(u64);
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 = self.0.saturating_add(buf.len() as u64);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn execute(intent: VerifyIntent) -> Result<VerifyReport, Error> {
+    let VerifyIntent {
+        input_target,
+        detached_header_target,
+        credential,
+    } = intent;
+
+    let stor = storage::FileStorage;
+    let input = stor
+        .read_resolved_existing_no_follow(&input_target)
+        .map_err(Error::Storage)?;
+    let reader = input.try_reader().map_err(Error::Storage)?;
+    let detached_header = detached_header_target
+        .as_ref()
+        .map(|target| stor.read_resolved_existing_no_follow(target))
+        .transpose()
+        .map_err(Error::Storage)?;
+    let header_reader = detached_header
+        .as_ref()
+        .map(|header| header.try_reader())
+        .transpose()
+        .map_err(Error::Storage)?;
+
+    let payload = decrypt::read_v1_payload(header_reader, reader).map_err(Error::Decrypt)?;
+    let header = payload.header();
+    let framing = header.payload_framing();
+    let is_raw_file = match header.payload_kind() {
+        PayloadKind::RawFile => true,
+        PayloadKind::ManifestArchive if framing.is_manifest_first() => false,
+        PayloadKind::ManifestArchive => return Err(unpack::Error::OpenArchive.into()),
+    };
+
+    let master_key = decrypt::decrypt_master_key(&payload, credential).map_err(Error::Decrypt)?;
+    let mut encrypted_reader = reader.borrow_mut();
+    if is_raw_file {
+        let mut sink = CountingSink(0);
+        let final_auth = V1PayloadStream::decrypt_file_uncommitted(
+            master_key,
+            &payload,
+            &mut *encrypted_reader,
+            &mut sink,
+        )
+        .map_err(decrypt::map_stream_error)
+        .map_err(Error::Decrypt)?;
+        return Ok(VerifyReport {
+            payload: VerifiedPayload::RawFile {
+                plaintext_len: sink.0,
+            },
+            _final_auth: final_auth,
+        });
+    }
+
+    let mut plaintext_reader =
+        V1PayloadDecryptingReader::new(master_key, &payload, &mut *encrypted_reader)
+            .map_err(decrypt::map_stream_error)
+            .map_err(Error::Decrypt)?;
+    let (entries, body_bytes, digests) = {
+        let mut uncommitted_reader = UncommittedPlaintextReader(&mut plaintext_reader);
+        let manifest = ArchiveManifest::read_from(&mut uncommitted_reader)
+            .map_err(unpack::map_payload_error)?;
+        unpack::validate_manifest(&manifest)?;
+        let body_bytes = unpack::for_each_manifest_body(
+            &mut uncommitted_reader,
+            framing,
+            &manifest,
+            |_, body, body_len| unpack::drain_manifest_body(body, body_len),
+        )?;
+        unpack::drain_trailing_plaintext_to_final_auth(&mut uncommitted_reader)?;
+        let digests = manifest
+            .entries()
+            .iter()
+            .filter(|entry| entry.blake3_digest().is_some())
+            .count();
+        (
+            ArchiveEntryCounts::count(manifest.entries()),
+            body_bytes,
+            digests,
+        )
+    };
+    let final_auth = plaintext_reader
+        .finish()
+        .map_err(decrypt::map_stream_error)
+        .map_err(Error::Decrypt)?;
+
+    Ok(VerifyReport {
+        payload: VerifiedPayload::Archive {
+            entries,
+            body_bytes,
+            digests,
+        },
+        _final_auth: final_auth,
+    })
+}
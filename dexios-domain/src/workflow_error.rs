@@ -36,6 +36,26 @@ impl WorkflowErrorClass {
         Self::IncorrectKey,
         Self::Other,
     ];
+
+    /// Stable kebab-case name for reports and machine-readable output.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::MalformedFormat => "malformed-format",
+            Self::UnsupportedFormat => "unsupported-format",
+            Self::KdfFailure => "kdf-failure",
+            Self::AuthenticationFailure => "authentication-failure",
+            Self::UnsafePath => "unsafe-path",
+            Self::IoFailure => "io-failure",
+            Self::OverwriteDenied => "overwrite-denied",
+            Self::TransactionCommitFailure => "transaction-commit-failure",
+            Self::CleanupFailure => "cleanup-failure",
+            Self::ResourcePressure => "resource-pressure",
+            Self::UnsupportedWorkflow => "unsupported-workflow",
+            Self::IncorrectKey => "incorrect-key",
+            Self::Other => "other",
+        }
+    }
 }
 
 pub(crate) fn classify_identity_error(error: &IdentityError) -> WorkflowErrorClass {
@@ -74,6 +94,8 @@ pub(crate) fn classify_storage_error(error: &StorageError) -> WorkflowErrorClass
         | StorageError::CreateDirWithSource(_)
         | StorageError::CreateFile
         | StorageError::CreateFileWithSource(_)
+        | StorageError::CreateLink
+        | StorageError::CreateLinkWithSource(_)
         | StorageError::OpenFile(_)
         | StorageError::OpenFileWithSource { .. }
         | StorageError::RemoveFile
@@ -89,7 +111,9 @@ pub(crate) fn classify_storage_error(error: &StorageError) -> WorkflowErrorClass
         | StorageError::FileAccess
         | StorageError::FileAccessWithSource(_)
         | StorageError::FileLen
-        | StorageError::FileLenWithSource(_) => WorkflowErrorClass::IoFailure,
+        | StorageError::FileLenWithSource(_)
+        | StorageError::Metadata
+        | StorageError::MetadataWithSource(_) => WorkflowErrorClass::IoFailure,
     }
 }
 
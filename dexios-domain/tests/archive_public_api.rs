@@ -169,6 +169,10 @@ fn d03_public_archive_policy_has_no_compression_selector() {
             path: "synthetic/public-renamed-compression-selector.rs",
             text: "pub fn level(self) -> CompressionLevel { CompressionLevel::Default }",
         },
+        Source {
+            path: "synthetic/public-policy-compression-field.rs",
+            text: "pub struct ArchivePolicy { pub compression: BodyCompression }",
+        },
         Source {
             path: "synthetic/public-policy-mode-field.rs",
             text: "pub struct ArchivePolicy { pub mode: ArchiveMode }",
@@ -520,9 +524,19 @@ fn public_line_exposes_zip_type(trimmed: &str) -> bool {
         || trimmed.contains("SimpleFileOptions")
 }
 
+/// The one sanctioned compression knob: an opt-in pack builder typed by the
+/// core body codec, not a ZIP-era archive policy selector.
+const ALLOWED_COMPRESSION_SELECTORS: &[&str] = &[
+    "compression:option<bodycompression>,",
+    "pubconstfnwith_compression(mutself,compression:bodycompression)->self{",
+];
+
 fn public_line_exposes_compression_selector(trimmed: &str) -> bool {
     let compact = trimmed.split_whitespace().collect::<String>();
     let normalized = compact.to_ascii_lowercase();
+    if ALLOWED_COMPRESSION_SELECTORS.contains(&normalized.as_str()) {
+        return false;
+    }
     [
         "archivecompression",
         "compressionlevel",
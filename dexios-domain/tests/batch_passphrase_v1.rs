@@ -0,0 +1,131 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::unreachable,
+        clippy::string_slice,
+        clippy::too_many_lines,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        clippy::match_same_arms,
+        clippy::items_after_statements,
+        clippy::redundant_closure_for_method_calls,
+        clippy::needless_collect,
+        clippy::manual_let_else,
+        clippy::format_collect,
+        clippy::case_sensitive_file_extension_comparisons,
+        clippy::struct_excessive_bools,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/keyslots_v1.rs"]
+mod keyslots_support;
+
+use dexios_domain::key::BatchPassphrase;
+use dexios_domain::storage::identity::OverwritePolicy;
+use keyslots_support::*;
+use std::sync::Arc;
+
+const PASSPHRASE: &[u8] = b"batch-pass";
+
+fn encrypt_batch(dir: &Path, passphrase: &Arc<BatchPassphrase>, count: usize) -> Vec<PathBuf> {
+    (0..count)
+        .map(|index| {
+            let input_path = dir.join(format!("file-{index}.txt"));
+            let output_path = dir.join(format!("file-{index}.txt.dx"));
+            fs::write(&input_path, format!("file {index}")).unwrap();
+            let intent = encrypt::EncryptIntent::new_for_batch(
+                &input_path,
+                &output_path,
+                OverwritePolicy::CreateNew,
+                None,
+                Arc::clone(passphrase),
+                Kdf::Argon2idInteractive,
+            )
+            .unwrap();
+            encrypt::execute(intent).unwrap();
+            output_path
+        })
+        .collect()
+}
+
+fn decrypt_with(path: &Path, credential: impl Into<key::Credential>) -> Result<(), decrypt::Error> {
+    let output_path = path.with_extension("out");
+    let intent = decrypt::DecryptIntent::new(
+        path,
+        &output_path,
+        OverwritePolicy::CreateNew,
+        None::<&Path>,
+        credential,
+        None,
+    )?;
+    decrypt::execute(intent).map(|_| ())
+}
+
+#[test]
+fn a_batch_derives_once_and_shares_one_salt_per_profile() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let passphrase = Arc::new(BatchPassphrase::new(Protected::new(PASSPHRASE.to_vec())));
+
+    let outputs = encrypt_batch(&dir_path, &passphrase, 3);
+    assert_eq!(passphrase.derivations(), 1);
+
+    let keyslots = outputs
+        .iter()
+        .map(|path| {
+            *read_v1_header_from_path(path)
+                .keyslots_collection()
+                .get_physical(0)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        keyslots
+            .iter()
+            .all(|slot| slot.salt() == keyslots[0].salt())
+    );
+    assert_ne!(
+        keyslots[0].encrypted_master_key(),
+        keyslots[1].encrypted_master_key(),
+        "every file must still get its own master key"
+    );
+    assert_ne!(keyslots[0].nonce(), keyslots[1].nonce());
+
+    // a single-file passphrase opens each file of the batch
+    assert_eq!(decrypt_file(&outputs[2], PASSPHRASE).unwrap(), b"file 2");
+}
+
+#[test]
+fn decrypting_a_batch_derives_once_per_salt() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let outputs = encrypt_batch(
+        &dir_path,
+        &Arc::new(BatchPassphrase::new(Protected::new(PASSPHRASE.to_vec()))),
+        3,
+    );
+    let (_other_dir, unrelated) = encrypted_v1_file("unrelated");
+
+    let passphrase = Arc::new(BatchPassphrase::new(Protected::new(PASSPHRASE.to_vec())));
+    for path in &outputs {
+        decrypt_with(path, Arc::clone(&passphrase)).unwrap();
+    }
+    assert_eq!(passphrase.derivations(), 1);
+    assert_eq!(
+        fs::read(outputs[1].with_extension("out")).unwrap(),
+        b"file 1"
+    );
+
+    // a file from outside the batch has its own salt, and is still refused
+    // when the passphrase does not open it
+    assert!(matches!(
+        decrypt_with(&unrelated, Arc::clone(&passphrase)),
+        Err(decrypt::Error::DecryptMasterKey)
+    ));
+    assert_eq!(passphrase.derivations(), 2);
+}
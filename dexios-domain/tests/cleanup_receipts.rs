@@ -26,9 +26,11 @@
 )]
 #[cfg(feature = "test-support")]
 use std::error::Error as _;
+#[cfg(feature = "test-support")]
 use std::fs;
 #[cfg(feature = "test-support")]
 use std::io;
+#[cfg(feature = "test-support")]
 use std::path::PathBuf;
 
 #[cfg(feature = "test-support")]
@@ -45,6 +47,7 @@ use dexios_domain::storage::transaction::{CommitReceipt, StagedOutputTransaction
 #[expect(dead_code, reason = "shared tempdir test helper")]
 #[path = "support/tempdir.rs"]
 mod tempdir;
+#[cfg(feature = "test-support")]
 use tempdir::DomainTestDir as TestDir;
 
 #[cfg(feature = "test-support")]
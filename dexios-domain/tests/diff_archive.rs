@@ -0,0 +1,128 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/tempdir.rs"]
+#[expect(dead_code, reason = "shared tempdir test helper")]
+mod tempdir;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use core::kdf::Kdf;
+use core::protected::Protected;
+use dexios_domain::archive::ArchivePolicy;
+use dexios_domain::diff::{self, ArchiveDiff, DiffIntent};
+use dexios_domain::pack::{self, PackIntent};
+use dexios_domain::storage::identity::OverwritePolicy;
+use dexios_domain::workflow_error::WorkflowErrorClass;
+use tempdir::canonical_tempdir;
+
+const PASSWORD: &[u8; 8] = b"12345678";
+const STREAM_TAG_LEN: usize = 16;
+
+fn write_tree(root: &Path) {
+    fs::create_dir_all(root.join("nested")).unwrap();
+    fs::write(root.join("hello.txt"), b"hello").unwrap();
+    fs::write(root.join("nested/world.txt"), b"world").unwrap();
+}
+
+fn packed_archive(root: &Path) -> PathBuf {
+    let source_dir = root.join("source");
+    write_tree(&source_dir);
+    let archive_path = root.join("archive.enc");
+    let intent = PackIntent::new(
+        vec![source_dir],
+        &archive_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(PASSWORD.to_vec()),
+        Kdf::Argon2id,
+        ArchivePolicy::default(),
+        true,
+        None,
+    )
+    .unwrap();
+    pack::execute_transactional(intent).unwrap();
+    archive_path
+}
+
+fn diff_against(archive: &Path, directory: &Path) -> Result<ArchiveDiff, diff::Error> {
+    let intent = DiffIntent::new(archive, None, directory, Protected::new(PASSWORD.to_vec()))?;
+    diff::execute(intent)
+}
+
+fn paths(paths: &[&str]) -> Vec<PathBuf> {
+    paths.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn diff_of_an_unchanged_restore_is_empty() {
+    let (_root_dir, root) = canonical_tempdir();
+    let archive = packed_archive(&root);
+    let restore = root.join("restore");
+    write_tree(&restore.join("source"));
+
+    let diff = diff_against(&archive, &restore).unwrap();
+
+    assert!(diff.is_empty(), "{diff:?}");
+}
+
+#[test]
+fn diff_reports_added_removed_and_modified_paths() {
+    let (_root_dir, root) = canonical_tempdir();
+    let archive = packed_archive(&root);
+    let restore = root.join("restore");
+    let restored_source = restore.join("source");
+    write_tree(&restored_source);
+    // Same length, different content, so only the hash can tell them apart.
+    fs::write(restored_source.join("hello.txt"), b"HELLO").unwrap();
+    fs::remove_file(restored_source.join("nested/world.txt")).unwrap();
+    fs::write(restored_source.join("new.txt"), b"new").unwrap();
+    fs::remove_dir(restored_source.join("nested")).unwrap();
+    fs::write(restored_source.join("nested"), b"now a file").unwrap();
+
+    let diff = diff_against(&archive, &restore).unwrap();
+
+    assert_eq!(diff.added(), paths(&["source/new.txt"]));
+    assert_eq!(diff.removed(), paths(&["source/nested/world.txt"]));
+    assert_eq!(
+        diff.modified(),
+        paths(&["source/hello.txt", "source/nested"])
+    );
+}
+
+#[test]
+fn diff_reports_nothing_from_a_tampered_archive() {
+    let (_root_dir, root) = canonical_tempdir();
+    let archive = packed_archive(&root);
+    let mut bytes = fs::read(&archive).unwrap();
+    let offset = bytes.len() - STREAM_TAG_LEN;
+    bytes[offset] ^= 0x40;
+    fs::write(&archive, bytes).unwrap();
+
+    let error = diff_against(&archive, &root.join("source")).unwrap_err();
+
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::AuthenticationFailure,
+        "{error}"
+    );
+}
+
+#[test]
+fn diff_requires_a_directory() {
+    let (_root_dir, root) = canonical_tempdir();
+    let archive = packed_archive(&root);
+
+    let error = diff_against(&archive, &root.join("source/hello.txt")).unwrap_err();
+
+    assert!(matches!(error, diff::Error::NotADirectory(_)), "{error:?}");
+}
@@ -0,0 +1,180 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::unreachable,
+        clippy::string_slice,
+        clippy::too_many_lines,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        clippy::match_same_arms,
+        clippy::items_after_statements,
+        clippy::redundant_closure_for_method_calls,
+        clippy::needless_collect,
+        clippy::manual_let_else,
+        clippy::format_collect,
+        clippy::case_sensitive_file_extension_comparisons,
+        clippy::struct_excessive_bools,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/keyslots_v1.rs"]
+mod keyslots_support;
+
+use core::header::common::HEADER_TRAILER_FOOTER_LEN;
+use dexios_domain::header::{self, dump, repair, restore, strip};
+use dexios_domain::storage::identity::OverwritePolicy;
+use keyslots_support::*;
+
+const PLAINTEXT: &[u8] = b"Hello world";
+const TRAILER_LEN: usize = HEADER_LEN + HEADER_TRAILER_FOOTER_LEN;
+
+fn trailer_v1_file(name: &str) -> (tempfile::TempDir, PathBuf) {
+    let (dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join(format!("{name}.txt"));
+    let output_path = dir_path.join(format!("{name}.enc"));
+    fs::write(&input_path, PLAINTEXT).unwrap();
+
+    let intent = encrypt::EncryptIntent::new(
+        &input_path,
+        &output_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .unwrap()
+    .with_header_trailer()
+    .unwrap();
+    encrypt::execute(intent).unwrap();
+
+    (dir, output_path)
+}
+
+fn trailer_copy(bytes: &[u8]) -> &[u8] {
+    &bytes[bytes.len() - TRAILER_LEN..bytes.len() - HEADER_TRAILER_FOOTER_LEN]
+}
+
+fn repair_file(path: &Path) -> Result<(), header::Error> {
+    repair::execute(repair::RepairIntent::new(path)?).map(|_| ())
+}
+
+#[test]
+fn encrypt_appends_a_trailer_that_repairs_a_damaged_header() {
+    let (_dir, path) = trailer_v1_file("trailer-repair");
+    let original = fs::read(&path).unwrap();
+    assert!(read_v1_header_from_path(&path).has_header_trailer());
+    assert_eq!(
+        original.len(),
+        HEADER_LEN + PLAINTEXT.len() + 16 + TRAILER_LEN
+    );
+    assert_eq!(trailer_copy(&original), &original[..HEADER_LEN]);
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), PLAINTEXT);
+
+    let mut damaged = original.clone();
+    damaged[..64].fill(0xEE);
+    fs::write(&path, &damaged).unwrap();
+    assert!(decrypt_file(&path, b"old-pass").is_err());
+
+    repair_file(&path).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), PLAINTEXT);
+
+    let error = repair_file(&path).expect_err("an intact header needs no repair");
+    assert!(matches!(error, header::Error::HeaderNotDamaged));
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::UnsupportedWorkflow
+    );
+    assert_eq!(fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn repair_rejects_files_without_a_header_trailer() {
+    let (_dir, path) = encrypted_v1_file("no-trailer");
+    let original = fs::read(&path).unwrap();
+    assert!(!read_v1_header_from_path(&path).has_header_trailer());
+
+    assert!(matches!(
+        repair_file(&path),
+        Err(header::Error::MissingHeaderTrailer)
+    ));
+    assert_eq!(fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn key_changes_keep_the_trailer_copy_in_sync() {
+    let (_dir, path) = trailer_v1_file("trailer-key-add");
+    add_key_file(&path, b"old-pass", b"second-pass");
+    let bytes = fs::read(&path).unwrap();
+    assert_eq!(trailer_copy(&bytes), &bytes[..HEADER_LEN]);
+    assert_eq!(read_v1_header_from_path(&path).keyslots().len(), 2);
+
+    // a repair from the rewritten trailer keeps the added key usable
+    let mut damaged = bytes;
+    damaged[..HEADER_LEN].fill(0);
+    fs::write(&path, &damaged).unwrap();
+    repair_file(&path).unwrap();
+    assert_eq!(decrypt_file(&path, b"second-pass").unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn strip_zeroes_the_trailer_and_restore_brings_it_back() {
+    let (dir, path) = trailer_v1_file("trailer-detach");
+    let original = fs::read(&path).unwrap();
+    let header_path = dir.path().join("trailer-detach.header");
+
+    dump::execute(dump::DumpIntent::new(&path, &header_path, OverwritePolicy::CreateNew).unwrap())
+        .unwrap();
+    strip::execute(strip::StripIntent::new(&header_path, &path).unwrap()).unwrap();
+    let stripped = fs::read(&path).unwrap();
+    assert_eq!(stripped.len(), original.len());
+    assert!(stripped[..HEADER_LEN].iter().all(|byte| *byte == 0));
+    assert!(
+        stripped[stripped.len() - TRAILER_LEN..]
+            .iter()
+            .all(|byte| *byte == 0)
+    );
+    assert!(matches!(
+        repair_file(&path),
+        Err(header::Error::MissingHeaderTrailer)
+    ));
+
+    restore::execute(restore::RestoreIntent::new(&header_path, &path).unwrap()).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn encrypt_rejects_a_trailer_for_a_detached_header() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    fs::write(&input_path, PLAINTEXT).unwrap();
+
+    let error = encrypt::EncryptIntent::new(
+        &input_path,
+        dir_path.join("plain.enc"),
+        OverwritePolicy::CreateNew,
+        Some(encrypt::DetachedHeaderTarget::new(
+            dir_path.join("plain.header"),
+            OverwritePolicy::CreateNew,
+        )),
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .unwrap()
+    .with_header_trailer()
+    .expect_err("a detached header has no trailer");
+
+    assert!(matches!(error, encrypt::Error::DetachedHeaderTrailer));
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::UnsupportedWorkflow
+    );
+}
@@ -0,0 +1,184 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/keyslots_v1.rs"]
+mod keyslots_support;
+
+use dexios_domain::header::{dump, restore, strip};
+use dexios_domain::storage::identity::OverwritePolicy;
+use keyslots_support::*;
+
+const FIVE_SLOT_HEADER_LEN: usize = HEADER_STATIC_LEN + 5 * KEYSLOT_LEN;
+
+fn five_slot_v1_file(name: &str) -> (tempfile::TempDir, PathBuf) {
+    let (dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join(format!("{name}.txt"));
+    let output_path = dir_path.join(format!("{name}.enc"));
+    fs::write(&input_path, b"Hello world").unwrap();
+
+    let intent = encrypt::EncryptIntent::new(
+        &input_path,
+        &output_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .unwrap()
+    .with_keyslot_capacity(5)
+    .unwrap();
+    encrypt::execute(intent).unwrap();
+
+    (dir, output_path)
+}
+
+fn add_key_file(path: &Path, old_key: &[u8], new_key: &[u8]) -> Result<(), key::Error> {
+    let proven =
+        key::add::AddIntent::new(path)?.verify_old_key(Protected::new(old_key.to_vec()))?;
+    key::add::execute(
+        proven,
+        Protected::new(new_key.to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .map(|_| ())
+}
+
+fn physical_indexes(path: &Path) -> Vec<usize> {
+    read_v1_header_from_path(path)
+        .keyslots()
+        .iter()
+        .map(V1Keyslot::physical_index)
+        .collect()
+}
+
+#[test]
+fn encrypt_reserves_an_extended_keyslot_table_for_key_add() {
+    let (_dir, path) = five_slot_v1_file("capacity-add");
+    let header = read_v1_header_from_path(&path);
+    assert_eq!(header.keyslot_capacity().get(), 5);
+    assert_eq!(
+        fs::read(&path).unwrap().len(),
+        FIVE_SLOT_HEADER_LEN + b"Hello world".len() + 16
+    );
+
+    for (index, key) in [b"key-1", b"key-2", b"key-3", b"key-4"].iter().enumerate() {
+        add_key_file(&path, b"old-pass", *key).unwrap_or_else(|error| panic!("{index}: {error}"));
+    }
+    assert_eq!(physical_indexes(&path), [0, 1, 2, 3, 4]);
+    assert_eq!(decrypt_file(&path, b"key-4").unwrap(), b"Hello world");
+
+    let full = fs::read(&path).unwrap();
+    assert!(matches!(
+        add_key_file(&path, b"old-pass", b"key-5"),
+        Err(key::Error::TooManyKeyslots)
+    ));
+    assert_eq!(fs::read(&path).unwrap(), full);
+
+    let intent = key::delete::DeleteIntent::new(&path)
+        .unwrap()
+        .target_slot(4)
+        .unwrap();
+    key::delete::execute(intent, Protected::new(b"key-1".to_vec())).unwrap();
+    assert_eq!(physical_indexes(&path), [0, 1, 2, 3]);
+    assert_eq!(read_v1_header_from_path(&path).keyslot_capacity().get(), 5);
+    assert!(matches!(
+        decrypt_file(&path, b"key-4"),
+        Err(decrypt::Error::DecryptMasterKey)
+    ));
+}
+
+#[test]
+fn key_rotate_keeps_the_keyslot_capacity() {
+    let (_dir, path) = five_slot_v1_file("capacity-rotate");
+    add_key_file(&path, b"old-pass", b"second-pass").unwrap();
+
+    let proven = key::rotate::RotateIntent::new(&path)
+        .unwrap()
+        .verify_keys(vec![key::Credential::from(Protected::new(
+            b"second-pass".to_vec(),
+        ))])
+        .unwrap();
+    key::rotate::execute(proven).unwrap();
+
+    let header = read_v1_header_from_path(&path);
+    assert_eq!(header.keyslot_capacity().get(), 5);
+    assert_eq!(physical_indexes(&path), [1]);
+    assert_eq!(decrypt_file(&path, b"second-pass").unwrap(), b"Hello world");
+}
+
+#[test]
+fn extended_header_dumps_strips_and_restores_whole() {
+    let (dir, path) = five_slot_v1_file("capacity-detach");
+    let original = fs::read(&path).unwrap();
+    let header_path = dir.path().join("capacity-detach.header");
+
+    dump::execute(dump::DumpIntent::new(&path, &header_path, OverwritePolicy::CreateNew).unwrap())
+        .unwrap();
+    assert_eq!(
+        fs::read(&header_path).unwrap(),
+        original[..FIVE_SLOT_HEADER_LEN]
+    );
+
+    strip::execute(strip::StripIntent::new(&header_path, &path).unwrap()).unwrap();
+    let stripped = fs::read(&path).unwrap();
+    assert_eq!(
+        stripped[..FIVE_SLOT_HEADER_LEN],
+        [0u8; FIVE_SLOT_HEADER_LEN]
+    );
+    assert_eq!(
+        stripped[FIVE_SLOT_HEADER_LEN..],
+        original[FIVE_SLOT_HEADER_LEN..]
+    );
+
+    // a canonical-length prefix of an extended header is not a whole header
+    fs::write(&header_path, &original[..HEADER_LEN]).unwrap();
+    assert!(matches!(
+        restore::execute(restore::RestoreIntent::new(&header_path, &path).unwrap()),
+        Err(dexios_domain::header::Error::ShortDetachedHeader {
+            actual_len: HEADER_LEN
+        })
+    ));
+
+    fs::write(&header_path, &original[..FIVE_SLOT_HEADER_LEN]).unwrap();
+    restore::execute(restore::RestoreIntent::new(&header_path, &path).unwrap()).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), b"Hello world");
+}
+
+#[test]
+fn encrypt_rejects_keyslot_capacities_outside_the_header_profiles() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    fs::write(&input_path, b"Hello world").unwrap();
+
+    for capacity in [0, 3, 33] {
+        let error = encrypt::EncryptIntent::new(
+            &input_path,
+            dir_path.join("plain.enc"),
+            OverwritePolicy::CreateNew,
+            None,
+            Protected::new(b"old-pass".to_vec()),
+            Kdf::Argon2idInteractive,
+        )
+        .unwrap()
+        .with_keyslot_capacity(capacity)
+        .expect_err("capacity outside 4..=32 must be rejected");
+
+        assert_eq!(
+            error.workflow_class(),
+            WorkflowErrorClass::UnsupportedWorkflow
+        );
+        assert!(matches!(
+            error,
+            encrypt::Error::InvalidKeyslotCapacity(actual) if actual == capacity
+        ));
+    }
+}
@@ -162,3 +162,65 @@ fn mutating_payload_nonce_breaks_payload_authentication_with_proven_master_key()
     );
     assert!(plaintext.is_empty());
 }
+
+fn interactive_profile_v1_file() -> (tempfile::TempDir, PathBuf) {
+    let (dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    let output_path = dir_path.join("plain.enc");
+    fs::write(&input_path, b"Hello world").unwrap();
+
+    let intent = encrypt::EncryptIntent::new(
+        &input_path,
+        &output_path,
+        dexios_domain::storage::identity::OverwritePolicy::CreateNew,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .expect("build encrypt intent");
+    encrypt::execute(intent).expect("encrypt with interactive profile");
+
+    (dir, output_path)
+}
+
+#[test]
+fn each_keyslot_is_opened_with_the_cost_profile_it_records() {
+    let (_dir, path) = interactive_profile_v1_file();
+
+    let intent = key::add::AddIntent::new(&path).expect("prepare key add intent");
+    let proven = intent
+        .verify_old_key(Protected::new(b"old-pass".to_vec()))
+        .expect("interactive keyslot proof");
+    key::add::execute(proven, Protected::new(b"new-pass".to_vec()), Kdf::Argon2id)
+        .expect("add default-profile keyslot");
+
+    let kdfs: Vec<_> = read_v1_header_from_path(&path)
+        .keyslots()
+        .iter()
+        .map(V1Keyslot::kdf)
+        .collect();
+    assert_eq!(
+        kdfs,
+        [KeyslotKdf::Argon2idInteractive, KeyslotKdf::Argon2id]
+    );
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), b"Hello world");
+    assert_eq!(decrypt_file(&path, b"new-pass").unwrap(), b"Hello world");
+}
+
+#[test]
+fn rewriting_a_keyslot_cost_profile_fails_unwrap_authentication() {
+    let (_dir, path) = interactive_profile_v1_file();
+    let mut bytes = fs::read(&path).unwrap();
+    let param_profile = keyslot_range(0).start + CANONICAL_KEYSLOT_KDF_TAG_OFFSET + 1;
+    assert_eq!(
+        bytes[param_profile],
+        Kdf::Argon2idInteractive.param_profile_id()
+    );
+    bytes[param_profile] = Kdf::Argon2id.param_profile_id();
+    fs::write(&path, bytes).unwrap();
+
+    assert!(matches!(
+        verify_file(&path, b"old-pass"),
+        Err(key::Error::IncorrectKey)
+    ));
+}
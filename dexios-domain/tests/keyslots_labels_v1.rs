@@ -0,0 +1,168 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/keyslots_v1.rs"]
+mod keyslots_support;
+
+use keyslots_support::*;
+
+fn add_labeled_key_file(
+    path: &Path,
+    old_key: &[u8],
+    new_key: &[u8],
+    label: &str,
+) -> Result<(), key::Error> {
+    let intent = key::add::AddIntent::new(path)?.with_label(label)?;
+    let proven = intent.verify_old_key(Protected::new(old_key.to_vec()))?;
+    key::add::execute(proven, Protected::new(new_key.to_vec()), Kdf::Argon2id).map(|_| ())
+}
+
+fn labeled_v1_file(name: &str) -> (tempfile::TempDir, PathBuf) {
+    let (dir, path) = encrypted_v1_file(name);
+    add_labeled_key_file(&path, b"old-pass", b"ops-pass", "ops-recovery").unwrap();
+    (dir, path)
+}
+
+fn keyslot_labels(path: &Path) -> Vec<(usize, Option<String>)> {
+    read_v1_header_from_path(path)
+        .keyslots()
+        .iter()
+        .map(|keyslot| {
+            (
+                keyslot.physical_index(),
+                keyslot
+                    .metadata()
+                    .map(|metadata| metadata.label().to_string()),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn key_add_labels_the_new_keyslot_with_its_creation_time() {
+    let before = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let (_dir, path) = labeled_v1_file("label-add");
+
+    assert_eq!(
+        keyslot_labels(&path),
+        vec![(0, None), (1, Some("ops-recovery".to_string()))]
+    );
+    let header = read_v1_header_from_path(&path);
+    let created_at = header.keyslots()[1].metadata().unwrap().created_at();
+    assert!(created_at >= before);
+    assert_eq!(decrypt_file(&path, b"ops-pass").unwrap(), b"Hello world");
+
+    let original = fs::read(&path).unwrap();
+    assert!(matches!(
+        add_labeled_key_file(&path, b"old-pass", b"other", "ops-recovery"),
+        Err(key::Error::DuplicateKeyslotLabel(label)) if label == "ops-recovery"
+    ));
+    assert!(matches!(
+        add_labeled_key_file(&path, b"old-pass", b"other", "far-too-long-label"),
+        Err(key::Error::InvalidKeyslotLabel)
+    ));
+    assert_eq!(fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn key_change_by_label_rewraps_the_labeled_keyslot() {
+    let (_dir, path) = labeled_v1_file("label-change");
+
+    let proven = key::change::ChangeIntent::new(&path)
+        .unwrap()
+        .target_label("ops-recovery")
+        .unwrap()
+        .verify_old_key(Protected::new(b"old-pass".to_vec()))
+        .unwrap();
+    key::change::execute(proven, Protected::new(b"ops-new".to_vec()), Kdf::Argon2id).unwrap();
+
+    assert_eq!(
+        keyslot_labels(&path),
+        vec![(0, None), (1, Some("ops-recovery".to_string()))]
+    );
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), b"Hello world");
+    assert_eq!(decrypt_file(&path, b"ops-new").unwrap(), b"Hello world");
+    assert!(matches!(
+        decrypt_file(&path, b"ops-pass"),
+        Err(decrypt::Error::DecryptMasterKey)
+    ));
+}
+
+#[test]
+fn key_del_by_label_removes_the_labeled_keyslot() {
+    let (_dir, path) = labeled_v1_file("label-delete");
+    let original = fs::read(&path).unwrap();
+
+    let missing = key::delete::DeleteIntent::new(&path)
+        .unwrap()
+        .target_label("alice");
+    assert!(matches!(
+        missing,
+        Err(key::Error::KeyslotLabelNotFound(label)) if label == "alice"
+    ));
+    let wrong_key = key::delete::DeleteIntent::new(&path)
+        .unwrap()
+        .target_label("ops-recovery")
+        .unwrap();
+    assert!(matches!(
+        key::delete::execute(wrong_key, Protected::new(b"wrong".to_vec())),
+        Err(key::Error::IncorrectKey)
+    ));
+    assert_eq!(fs::read(&path).unwrap(), original);
+
+    let intent = key::delete::DeleteIntent::new(&path)
+        .unwrap()
+        .target_label("ops-recovery")
+        .unwrap();
+    key::delete::execute(intent, Protected::new(b"old-pass".to_vec())).unwrap();
+
+    assert_eq!(keyslot_labels(&path), vec![(0, None)]);
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), b"Hello world");
+}
+
+#[test]
+fn key_rotate_keeps_the_labels_of_kept_keyslots() {
+    let (_dir, path) = labeled_v1_file("label-rotate");
+
+    let proven = key::rotate::RotateIntent::new(&path)
+        .unwrap()
+        .verify_keys(vec![Protected::new(b"ops-pass".to_vec())])
+        .unwrap();
+    key::rotate::execute(proven).unwrap();
+
+    assert_eq!(
+        keyslot_labels(&path),
+        vec![(1, Some("ops-recovery".to_string()))]
+    );
+    assert_eq!(decrypt_file(&path, b"ops-pass").unwrap(), b"Hello world");
+}
+
+#[test]
+fn renaming_a_keyslot_breaks_its_authentication() {
+    let (_dir, path) = labeled_v1_file("label-tamper");
+    let mut bytes = fs::read(&path).unwrap();
+    // "ops-recovery" -> "ops-recoverx" is still a well-formed label
+    bytes[keyslot_range(1).end - 1] = b'x';
+    fs::write(&path, bytes).unwrap();
+
+    assert_eq!(
+        keyslot_labels(&path),
+        vec![(0, None), (1, Some("ops-recoverx".to_string()))]
+    );
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), b"Hello world");
+    assert!(matches!(
+        decrypt_file(&path, b"ops-pass"),
+        Err(decrypt::Error::DecryptMasterKey)
+    ));
+}
@@ -321,6 +321,62 @@ fn key_delete_removes_only_old_key_proven_slot_and_preserves_payload() {
         "test fixture must prove old key selected the removed slot"
     );
 }
+#[test]
+fn key_delete_by_slot_is_authorized_by_another_keyslot() {
+    let (_dir, encrypted_path) = two_keyslot_v1_file("delete-by-slot", b"leaver-pass");
+    let original = fs::read(&encrypted_path).expect("read original fixture");
+
+    let empty = key::delete::DeleteIntent::new(&encrypted_path)
+        .expect("prepare delete intent")
+        .target_slot(2);
+    assert!(matches!(empty, Err(key::Error::EmptyKeyslot(2))));
+    let out_of_range = key::delete::DeleteIntent::new(&encrypted_path)
+        .expect("prepare delete intent")
+        .target_slot(200);
+    assert!(matches!(out_of_range, Err(key::Error::EmptyKeyslot(200))));
+    let wrong_key = key::delete::DeleteIntent::new(&encrypted_path)
+        .expect("prepare delete intent")
+        .target_slot(1)
+        .expect("slot 1 is active");
+    assert!(matches!(
+        key::delete::execute(wrong_key, Protected::new(b"wrong".to_vec())),
+        Err(key::Error::IncorrectKey)
+    ));
+    assert_eq!(fs::read(&encrypted_path).unwrap(), original);
+
+    let intent = key::delete::DeleteIntent::new(&encrypted_path)
+        .expect("prepare delete intent")
+        .target_slot(1)
+        .expect("slot 1 is active");
+    key::delete::execute(intent, Protected::new(b"old-pass".to_vec()))
+        .expect("slot 0 authorizes deleting slot 1");
+
+    let changed = fs::read(&encrypted_path).expect("read changed fixture");
+    assert_eq!(&changed[HEADER_LEN..], &original[HEADER_LEN..]);
+    assert_eq!(keyslot_bytes(&changed, 1), [0u8; KEYSLOT_LEN]);
+    assert_eq!(keyslot_bytes(&changed, 0), keyslot_bytes(&original, 0));
+    assert!(verify_file(&encrypted_path, b"old-pass").is_ok());
+    assert!(matches!(
+        verify_file(&encrypted_path, b"leaver-pass"),
+        Err(key::Error::IncorrectKey)
+    ));
+}
+
+#[test]
+fn key_delete_by_slot_rejects_target_changed_after_intent_construction() {
+    let (_dir, encrypted_path) = two_keyslot_v1_file("delete-slot-changed", b"leaver-pass");
+    let intent = key::delete::DeleteIntent::new(&encrypted_path)
+        .expect("prepare delete intent")
+        .target_slot(1)
+        .expect("slot 1 is active");
+    fs::write(&encrypted_path, b"changed target").expect("mutate target after intent");
+
+    let result = key::delete::execute(intent, Protected::new(b"old-pass".to_vec()));
+
+    assert!(matches!(result, Err(key::Error::TargetChanged)));
+    assert_eq!(fs::read(&encrypted_path).unwrap(), b"changed target");
+}
+
 #[test]
 fn key_delete_zeroes_only_physical_slot_zero_and_keeps_slot_one_index() {
     let (_dir, encrypted_path) = two_keyslot_v1_file("delete-fixed-physical-slot", b"new-pass");
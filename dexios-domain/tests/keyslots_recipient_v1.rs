@@ -0,0 +1,213 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::unreachable,
+        clippy::string_slice,
+        clippy::too_many_lines,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        clippy::match_same_arms,
+        clippy::items_after_statements,
+        clippy::redundant_closure_for_method_calls,
+        clippy::needless_collect,
+        clippy::manual_let_else,
+        clippy::format_collect,
+        clippy::case_sensitive_file_extension_comparisons,
+        clippy::struct_excessive_bools,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/keyslots_v1.rs"]
+mod keyslots_support;
+
+use core::recipient::RecipientIdentity;
+use keyslots_support::*;
+
+fn recipient_encrypted_file(identities: &[&RecipientIdentity]) -> (tempfile::TempDir, PathBuf) {
+    let (dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    let output_path = dir_path.join("plain.enc");
+    fs::write(&input_path, b"Hello world").unwrap();
+
+    let intent = encrypt::EncryptIntent::new_for_recipients(
+        &input_path,
+        &output_path,
+        dexios_domain::storage::identity::OverwritePolicy::CreateNew,
+        None,
+        identities
+            .iter()
+            .map(|identity| identity.public_key())
+            .collect(),
+    )
+    .expect("build recipient encrypt intent");
+    encrypt::execute(intent).expect("encrypt to recipients");
+
+    (dir, output_path)
+}
+
+fn identity_file(identity: &RecipientIdentity) -> Vec<u8> {
+    identity.to_identity_file().with_exposed(Clone::clone)
+}
+
+fn identity_credential(identity: &RecipientIdentity) -> key::Credential {
+    RecipientIdentity::parse(&identity_file(identity))
+        .expect("reparse identity file")
+        .into()
+}
+
+fn decrypt_with_identity(
+    path: &Path,
+    identity: &RecipientIdentity,
+) -> Result<Vec<u8>, decrypt::Error> {
+    let (_temp_dir, temp_dir_path) = canonical_tempdir();
+    let output_path = temp_dir_path.join("plain.out");
+
+    let intent = decrypt::DecryptIntent::new(
+        path,
+        &output_path,
+        dexios_domain::storage::identity::OverwritePolicy::CreateNew,
+        None::<&Path>,
+        identity_credential(identity),
+        None,
+    )?;
+    decrypt::execute(intent)?;
+
+    Ok(fs::read(output_path).expect("read decrypted fixture"))
+}
+
+#[test]
+fn every_recipient_identity_decrypts_its_own_keyslot() {
+    let alice = RecipientIdentity::generate();
+    let bob = RecipientIdentity::generate();
+    let (_dir, path) = recipient_encrypted_file(&[&alice, &bob]);
+
+    let header = read_v1_header_from_path(&path);
+    let kdfs: Vec<_> = header.keyslots().iter().map(V1Keyslot::kdf).collect();
+    assert_eq!(
+        kdfs,
+        [KeyslotKdf::X25519Recipient, KeyslotKdf::X25519Recipient]
+    );
+
+    assert_eq!(
+        decrypt_with_identity(&path, &alice).unwrap(),
+        b"Hello world"
+    );
+    assert_eq!(decrypt_with_identity(&path, &bob).unwrap(), b"Hello world");
+}
+
+#[test]
+fn unrelated_identity_and_passwords_do_not_open_recipient_keyslots() {
+    let alice = RecipientIdentity::generate();
+    let (_dir, path) = recipient_encrypted_file(&[&alice]);
+
+    let stranger = decrypt_with_identity(&path, &RecipientIdentity::generate());
+    assert!(matches!(stranger, Err(decrypt::Error::DecryptMasterKey)));
+
+    let password = verify_file(&path, b"old-pass");
+    assert!(matches!(password, Err(key::Error::IncorrectKey)));
+
+    // identity file bytes presented as a passphrase are only a passphrase
+    let as_passphrase = decrypt_file(&path, &identity_file(&alice));
+    assert!(matches!(
+        as_passphrase,
+        Err(decrypt::Error::DecryptMasterKey)
+    ));
+}
+
+#[test]
+fn keyfile_that_looks_like_an_identity_still_opens_its_password_slot() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    let output_path = dir_path.join("plain.enc");
+    fs::write(&input_path, b"Hello world").unwrap();
+    let keyfile = identity_file(&RecipientIdentity::generate());
+
+    let intent = encrypt::EncryptIntent::new(
+        &input_path,
+        &output_path,
+        dexios_domain::storage::identity::OverwritePolicy::CreateNew,
+        None,
+        Protected::new(keyfile.clone()),
+        Kdf::Argon2idInteractive,
+    )
+    .expect("build encrypt intent");
+    encrypt::execute(intent).expect("encrypt with identity-shaped keyfile");
+
+    assert_eq!(
+        decrypt_file(&output_path, &keyfile).unwrap(),
+        b"Hello world"
+    );
+}
+
+#[test]
+fn recipient_encrypt_rejects_empty_and_oversized_recipient_lists() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    fs::write(&input_path, b"Hello world").unwrap();
+    let recipient = RecipientIdentity::generate().public_key();
+
+    for (recipients, expected) in [(Vec::new(), 0), (vec![recipient; 33], 33)] {
+        let error = encrypt::EncryptIntent::new_for_recipients(
+            &input_path,
+            dir_path.join("plain.enc"),
+            dexios_domain::storage::identity::OverwritePolicy::CreateNew,
+            None,
+            recipients,
+        )
+        .expect_err("recipient count outside 1..=32 must be rejected");
+
+        assert_eq!(
+            error.workflow_class(),
+            WorkflowErrorClass::UnsupportedWorkflow
+        );
+        match error {
+            encrypt::Error::NoRecipients => assert_eq!(expected, 0),
+            encrypt::Error::TooManyRecipients(count) => assert_eq!(count, expected),
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn more_than_four_recipients_get_an_extended_keyslot_table() {
+    let identities = (0..6)
+        .map(|_| RecipientIdentity::generate())
+        .collect::<Vec<_>>();
+    let (_dir, path) = recipient_encrypted_file(&identities.iter().collect::<Vec<_>>());
+
+    let header = read_v1_header_from_path(&path);
+    assert_eq!(header.keyslot_capacity().get(), 6);
+    assert_eq!(header.keyslots().len(), 6);
+    for identity in [&identities[0], &identities[5]] {
+        assert_eq!(
+            decrypt_with_identity(&path, identity).unwrap(),
+            b"Hello world"
+        );
+    }
+}
+
+#[test]
+fn recipient_identity_authorizes_adding_a_password_keyslot() {
+    let alice = RecipientIdentity::generate();
+    let (_dir, path) = recipient_encrypted_file(&[&alice]);
+
+    let proven = key::add::AddIntent::new(&path)
+        .expect("prepare key add intent")
+        .verify_old_key(identity_credential(&alice))
+        .expect("identity proves the master key");
+    key::add::execute(proven, Protected::new(b"new-pass".to_vec()), Kdf::Argon2id)
+        .expect("add password keyslot");
+
+    assert_eq!(decrypt_file(&path, b"new-pass").unwrap(), b"Hello world");
+    assert_eq!(
+        decrypt_with_identity(&path, &alice).unwrap(),
+        b"Hello world"
+    );
+}
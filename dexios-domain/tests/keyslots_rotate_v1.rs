@@ -0,0 +1,204 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/keyslots_v1.rs"]
+mod keyslots_support;
+
+use core::header::read_header_trailer;
+use core::recipient::RecipientIdentity;
+use dexios_domain::parity::{self, ParitySidecarTarget, repair, sidecar_path_for};
+use dexios_domain::storage::identity::OverwritePolicy;
+use keyslots_support::*;
+
+fn passphrase(key: &[u8]) -> key::Credential {
+    Protected::new(key.to_vec()).into()
+}
+
+fn rotate_file(
+    path: &Path,
+    credentials: Vec<key::Credential>,
+) -> Result<(Vec<usize>, Vec<usize>), key::Error> {
+    let proven = key::rotate::RotateIntent::new(path)?.verify_keys(credentials)?;
+    let slots = (proven.kept_slots(), proven.dropped_slots());
+    key::rotate::execute(proven)?;
+    Ok(slots)
+}
+
+#[test]
+fn key_rotate_reencrypts_the_payload_and_drops_unpresented_keyslots() {
+    let (_dir, path) = two_keyslot_v1_file("rotate-drop", b"second-pass");
+    let original = fs::read(&path).unwrap();
+    let original_header = read_v1_header_from_path(&path);
+
+    let slots = rotate_file(&path, vec![passphrase(b"old-pass")]).unwrap();
+
+    assert_eq!(slots, (vec![0], vec![1]));
+    let rotated = fs::read(&path).unwrap();
+    let rotated_header = read_v1_header_from_path(&path);
+    assert_ne!(
+        rotated_header.payload_nonce(),
+        original_header.payload_nonce()
+    );
+    assert_eq!(rotated_header.keyslots().len(), 1);
+    assert_eq!(
+        rotated_header.payload_framing(),
+        original_header.payload_framing()
+    );
+    assert_ne!(rotated[HEADER_LEN..], original[HEADER_LEN..]);
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), b"Hello world");
+    assert!(matches!(
+        decrypt_file(&path, b"second-pass"),
+        Err(decrypt::Error::DecryptMasterKey)
+    ));
+
+    // a copy of the old header no longer opens the re-encrypted payload
+    let mut spliced = original[..HEADER_LEN].to_vec();
+    spliced.extend_from_slice(&rotated[HEADER_LEN..]);
+    fs::write(&path, spliced).unwrap();
+    assert!(matches!(
+        decrypt_file(&path, b"second-pass"),
+        Err(decrypt::Error::DecryptData)
+    ));
+}
+
+#[test]
+fn key_rotate_rewrites_the_header_trailer_and_parity_sidecar() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.bin");
+    let path = dir_path.join("plain.enc");
+    let sidecar_path = sidecar_path_for(&path);
+    let plaintext = (0..3 * parity::PARITY_BLOCK_LEN + 100)
+        .map(|index| u8::try_from(index % 251).unwrap())
+        .collect::<Vec<_>>();
+    fs::write(&input_path, &plaintext).unwrap();
+    let intent = encrypt::EncryptIntent::new(
+        &input_path,
+        &path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .unwrap()
+    .with_header_trailer()
+    .unwrap()
+    .with_parity_sidecar(
+        ParitySidecarTarget::new(&sidecar_path, OverwritePolicy::CreateNew, 1).unwrap(),
+    )
+    .unwrap();
+    encrypt::execute(intent).unwrap();
+    let original_sidecar = fs::read(&sidecar_path).unwrap();
+
+    rotate_file(&path, vec![passphrase(b"old-pass")]).unwrap();
+
+    let rotated = fs::read(&path).unwrap();
+    let rotated_header = read_v1_header_from_path(&path);
+    assert!(rotated_header.has_header_trailer());
+    let ParsedHeader::V1(trailer) = read_header_trailer(&rotated).unwrap();
+    assert_eq!(
+        trailer.header().payload_nonce(),
+        rotated_header.payload_nonce()
+    );
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), plaintext);
+
+    // the sidecar was rebuilt for the re-encrypted file, so it still repairs it
+    assert_ne!(fs::read(&sidecar_path).unwrap(), original_sidecar);
+    let mut damaged = rotated.clone();
+    for byte in &mut damaged[parity::PARITY_BLOCK_LEN..parity::PARITY_BLOCK_LEN + 64] {
+        *byte ^= 0x5A;
+    }
+    fs::write(&path, damaged).unwrap();
+    let receipt =
+        repair::execute(repair::RepairIntent::new(&path, &sidecar_path).unwrap()).unwrap();
+    assert_eq!(receipt.repaired_blocks(), 1);
+    assert_eq!(fs::read(&path).unwrap(), rotated);
+}
+
+#[test]
+fn key_rotate_rebuilds_every_presented_keyslot_in_place() {
+    let (_dir, path) = two_keyslot_v1_file("rotate-keep", b"second-pass");
+
+    let slots = rotate_file(
+        &path,
+        vec![
+            passphrase(b"second-pass"),
+            passphrase(b"old-pass"),
+            passphrase(b"second-pass"),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(slots, (vec![0, 1], Vec::new()));
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), b"Hello world");
+    assert_eq!(decrypt_file(&path, b"second-pass").unwrap(), b"Hello world");
+}
+
+#[test]
+fn key_rotate_rewraps_recipient_keyslots_at_their_physical_index() {
+    let alice = RecipientIdentity::generate();
+    let bob = RecipientIdentity::generate();
+    let (_dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    let path = dir_path.join("plain.enc");
+    fs::write(&input_path, b"Hello world").unwrap();
+    let intent = encrypt::EncryptIntent::new_for_recipients(
+        &input_path,
+        &path,
+        OverwritePolicy::CreateNew,
+        None,
+        vec![alice.public_key(), bob.public_key()],
+    )
+    .unwrap();
+    encrypt::execute(intent).unwrap();
+    let original_header = read_v1_header_from_path(&path);
+
+    let bob_file = bob.to_identity_file().with_exposed(Clone::clone);
+    let slots = rotate_file(
+        &path,
+        vec![RecipientIdentity::parse(&bob_file).unwrap().into()],
+    )
+    .unwrap();
+
+    assert_eq!(slots, (vec![1], vec![0]));
+    let rotated_header = read_v1_header_from_path(&path);
+    let keyslot = &rotated_header.keyslots()[0];
+    assert_eq!(keyslot.physical_index(), 1);
+    assert_eq!(keyslot.kdf(), KeyslotKdf::X25519Recipient);
+    assert_ne!(
+        keyslot.ephemeral_public_key(),
+        original_header.keyslots()[1].ephemeral_public_key()
+    );
+    assert!(key::decrypt_v1_master_key_with_index(&rotated_header, bob).is_ok());
+    assert!(matches!(
+        key::decrypt_v1_master_key_with_index(&rotated_header, alice),
+        Err(key::Error::IncorrectKey)
+    ));
+}
+
+#[test]
+fn key_rotate_rejects_unusable_credentials_before_writing() {
+    let (dir, path) = encrypted_v1_file("rotate-reject");
+    let original = fs::read(&path).unwrap();
+
+    let wrong = rotate_file(&path, vec![passphrase(b"old-pass"), passphrase(b"wrong")]);
+    assert!(matches!(wrong, Err(key::Error::IncorrectKey)));
+    let none = rotate_file(&path, Vec::new());
+    assert!(matches!(none, Err(key::Error::NoRotationCredentials)));
+    assert_eq!(fs::read(&path).unwrap(), original);
+
+    let header_path = dir.path().join("detached.hdr");
+    fs::write(&header_path, &original[..HEADER_LEN]).unwrap();
+    let detached = key::rotate::RotateIntent::new(&header_path);
+    assert!(matches!(
+        detached,
+        Err(key::Error::CannotRotateDetachedHeader)
+    ));
+}
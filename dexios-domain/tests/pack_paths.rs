@@ -36,7 +36,9 @@ use std::path::{Path, PathBuf};
 
 use core::header::{ParsedHeader, read_header};
 use core::kdf::Kdf;
-use core::payload::{ManifestEntryKind, ManifestFirstPayload, PayloadFramingProfile, PayloadKind};
+use core::payload::{
+    BodyCompression, ManifestEntryKind, ManifestFirstPayload, PayloadFramingProfile, PayloadKind,
+};
 use core::protected::Protected;
 use dexios_domain::archive::{ArchiveLimitKind, ArchivePolicy};
 use dexios_domain::decrypt;
@@ -678,3 +680,257 @@ fn pack_representative_large_tree_materializes_expected_entries() {
     assert!(names.contains(&"source/dir0/file0.txt".to_string()));
     assert!(names.contains(&"source/dir5/file2.txt".to_string()));
 }
+
+#[cfg(unix)]
+#[test]
+fn pack_records_metadata_and_unpack_restores_it_after_commit() {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use std::time::{Duration, SystemTime};
+
+    use dexios_domain::archive::MetadataOptions;
+    use dexios_domain::unpack;
+
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let script = source_dir.join("nested/world.txt");
+    let mtime = SystemTime::UNIX_EPOCH + Duration::new(1_600_000_000, 123_456_789);
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o4751)).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&script)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+    // Only user.* attributes are captured, and tmpfs or overlay roots may not
+    // support them at all.
+    #[cfg(target_os = "linux")]
+    let with_xattr = rustix::fs::setxattr(
+        &script,
+        "user.dexios.origin",
+        b"build-42",
+        rustix::fs::XattrFlags::empty(),
+    )
+    .is_ok();
+    fs::set_permissions(source_dir.join("nested"), fs::Permissions::from_mode(0o750)).unwrap();
+    fs::File::open(source_dir.join("nested"))
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+    let output_path = root.join("archive.enc");
+
+    let intent = pack_intent(vec![source_dir], &output_path, None)
+        .unwrap()
+        .with_metadata(MetadataOptions::defaults());
+    pack::execute_transactional(intent).unwrap();
+
+    let payload = decrypted_manifest_archive(&output_path, None);
+    let recorded = payload
+        .manifest()
+        .entries()
+        .iter()
+        .find(|entry| entry.normalized_path() == b"source/nested/world.txt")
+        .unwrap()
+        .metadata();
+    assert_eq!(recorded.mode(), Some(0o4751));
+    assert_eq!(
+        recorded.mtime().map(|time| time.seconds()),
+        Some(1_600_000_000)
+    );
+    assert_eq!(recorded.owner(), None, "ownership is opt-in");
+
+    let output_dir = root.join("out");
+    let intent = unpack::UnpackIntent::new(
+        &output_path,
+        None,
+        &output_dir,
+        Protected::new(PASSWORD.to_vec()),
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+    .with_metadata(MetadataOptions::defaults());
+    unpack::execute(intent).unwrap();
+
+    let restored = fs::metadata(output_dir.join("source/nested/world.txt")).unwrap();
+    assert_eq!(
+        restored.mode() & 0o7777,
+        0o751,
+        "setuid is only restored together with ownership"
+    );
+    assert_eq!(restored.modified().unwrap(), mtime);
+    let restored_dir = fs::metadata(output_dir.join("source/nested")).unwrap();
+    assert_eq!(restored_dir.mode() & 0o7777, 0o750);
+    assert_eq!(
+        restored_dir.modified().unwrap(),
+        mtime,
+        "directory times are restored after their children"
+    );
+    #[cfg(target_os = "linux")]
+    if with_xattr {
+        let mut value = [0u8; 16];
+        let len = rustix::fs::getxattr(
+            output_dir.join("source/nested/world.txt"),
+            "user.dexios.origin",
+            &mut value[..],
+        )
+        .unwrap();
+        assert_eq!(&value[..len], b"build-42");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn pack_and_unpack_preserve_relative_symlinks_and_hard_links() {
+    use std::os::unix::fs::MetadataExt;
+
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let output_path = root.join("archive.enc");
+    let unpack_dir = root.join("out");
+    std::os::unix::fs::symlink("../hello.txt", source_dir.join("nested/hello-link")).unwrap();
+    fs::hard_link(
+        source_dir.join("hello.txt"),
+        source_dir.join("hello-copy.txt"),
+    )
+    .unwrap();
+
+    let intent = pack_intent(vec![source_dir], &output_path, None).unwrap();
+    pack::execute_transactional(intent).unwrap();
+
+    let payload = decrypted_manifest_archive(&output_path, None);
+    let kinds = payload
+        .manifest()
+        .entries()
+        .iter()
+        .map(|entry| (entry.normalized_path().to_vec(), entry.kind()))
+        .collect::<Vec<_>>();
+    assert!(kinds.contains(&(
+        b"source/nested/hello-link".to_vec(),
+        ManifestEntryKind::Symlink
+    )));
+    assert_eq!(
+        kinds
+            .iter()
+            .filter(|(_, kind)| *kind == ManifestEntryKind::HardLink)
+            .count(),
+        1
+    );
+
+    let intent = dexios_domain::unpack::UnpackIntent::new(
+        &output_path,
+        None,
+        &unpack_dir,
+        Protected::new(PASSWORD.to_vec()),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    dexios_domain::unpack::execute(intent).unwrap();
+
+    let unpacked = unpack_dir.join("source");
+    assert_eq!(
+        fs::read_link(unpacked.join("nested/hello-link")).unwrap(),
+        Path::new("../hello.txt")
+    );
+    assert_eq!(
+        fs::read(unpacked.join("nested/hello-link")).unwrap(),
+        b"hello"
+    );
+    let original = fs::metadata(unpacked.join("hello.txt")).unwrap();
+    let copy = fs::metadata(unpacked.join("hello-copy.txt")).unwrap();
+    assert_eq!(original.ino(), copy.ino());
+    assert_eq!(original.nlink(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn pack_archives_dangling_and_dot_relative_symlinks_as_links() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let output_path = root.join("archive.enc");
+    let unpack_dir = root.join("out");
+    std::os::unix::fs::symlink("../missing", source_dir.join("nested/dangling")).unwrap();
+    std::os::unix::fs::symlink("./world.txt", source_dir.join("nested/dot-link")).unwrap();
+
+    let intent = pack_intent(vec![source_dir], &output_path, None).unwrap();
+    pack::execute_transactional(intent).unwrap();
+
+    let intent = dexios_domain::unpack::UnpackIntent::new(
+        &output_path,
+        None,
+        &unpack_dir,
+        Protected::new(PASSWORD.to_vec()),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    dexios_domain::unpack::execute(intent).unwrap();
+
+    let nested = unpack_dir.join("source/nested");
+    assert_eq!(
+        fs::read_link(nested.join("dangling")).unwrap(),
+        Path::new("../missing")
+    );
+    assert!(!nested.join("dangling").exists());
+    assert_eq!(
+        fs::read_link(nested.join("dot-link")).unwrap(),
+        Path::new("./world.txt")
+    );
+    assert_eq!(
+        fs::read(nested.join("dot-link")).unwrap(),
+        fs::read(nested.join("world.txt")).unwrap()
+    );
+}
+
+#[test]
+fn pack_with_compression_shrinks_archive_and_unpacks_identically() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let repetitive = b"compressible archive body ".repeat(64 * 1024);
+    fs::write(source_dir.join("nested/large.txt"), &repetitive).unwrap();
+    let plain_path = root.join("plain.enc");
+    let compressed_path = root.join("compressed.enc");
+    let unpack_dir = root.join("out");
+
+    pack::execute_transactional(pack_intent(vec![source_dir.clone()], &plain_path, None).unwrap())
+        .unwrap();
+    let intent = pack_intent(vec![source_dir], &compressed_path, None)
+        .unwrap()
+        .with_compression(BodyCompression::default());
+    pack::execute_transactional(intent).unwrap();
+
+    let ParsedHeader::V1(parsed) =
+        read_header(&mut Cursor::new(fs::read(&compressed_path).unwrap())).unwrap();
+    assert_eq!(parsed.header().payload_kind(), PayloadKind::ManifestArchive);
+    assert_eq!(
+        parsed.header().payload_framing(),
+        PayloadFramingProfile::ManifestFirstCompressed
+    );
+    assert!(
+        fs::metadata(&compressed_path).unwrap().len() * 10
+            < fs::metadata(&plain_path).unwrap().len(),
+        "repetitive bodies must compress well"
+    );
+
+    let intent = dexios_domain::unpack::UnpackIntent::new(
+        &compressed_path,
+        None,
+        &unpack_dir,
+        Protected::new(PASSWORD.to_vec()),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    dexios_domain::unpack::execute(intent).unwrap();
+
+    let unpacked = unpack_dir.join("source");
+    assert_eq!(fs::read(unpacked.join("hello.txt")).unwrap(), b"hello");
+    assert_eq!(
+        fs::read(unpacked.join("nested/large.txt")).unwrap(),
+        repetitive
+    );
+}
@@ -0,0 +1,387 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::unreachable,
+        clippy::string_slice,
+        clippy::too_many_lines,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        clippy::match_same_arms,
+        clippy::items_after_statements,
+        clippy::redundant_closure_for_method_calls,
+        clippy::needless_collect,
+        clippy::manual_let_else,
+        clippy::format_collect,
+        clippy::case_sensitive_file_extension_comparisons,
+        clippy::struct_excessive_bools,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/keyslots_v1.rs"]
+mod keyslots_support;
+
+use dexios_domain::archive::ArchivePolicy;
+use dexios_domain::pack;
+use dexios_domain::parity::{self, ParitySidecarTarget, repair, sidecar_path_for};
+use dexios_domain::storage::identity::OverwritePolicy;
+use keyslots_support::*;
+
+const BLOCK: usize = parity::PARITY_BLOCK_LEN;
+
+fn plaintext(len: usize) -> Vec<u8> {
+    (0..len).map(|index| (index % 251) as u8).collect()
+}
+
+fn encrypt_with_sidecar(
+    dir_path: &Path,
+    name: &str,
+    plaintext: &[u8],
+    parity_blocks: usize,
+) -> (PathBuf, PathBuf) {
+    let input_path = dir_path.join(format!("{name}.txt"));
+    let output_path = dir_path.join(format!("{name}.enc"));
+    let sidecar_path = sidecar_path_for(&output_path);
+    fs::write(&input_path, plaintext).unwrap();
+
+    let intent = encrypt::EncryptIntent::new(
+        &input_path,
+        &output_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .unwrap()
+    .with_parity_sidecar(
+        ParitySidecarTarget::new(&sidecar_path, OverwritePolicy::CreateNew, parity_blocks).unwrap(),
+    )
+    .unwrap();
+    encrypt::execute(intent).unwrap();
+
+    (output_path, sidecar_path)
+}
+
+fn repair_file(path: &Path, sidecar_path: &Path) -> Result<usize, parity::Error> {
+    repair::execute(repair::RepairIntent::new(path, sidecar_path)?)
+        .map(|receipt| receipt.repaired_blocks())
+}
+
+fn damage_block(path: &Path, block: usize) {
+    let mut bytes = fs::read(path).unwrap();
+    let start = block * BLOCK;
+    let end = (start + 64).min(bytes.len());
+    for byte in &mut bytes[start..end] {
+        *byte ^= 0x5A;
+    }
+    fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn encrypt_writes_a_sidecar_that_repairs_damaged_blocks() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let plaintext = plaintext(3 * BLOCK + 100);
+    let (path, sidecar_path) = encrypt_with_sidecar(&dir_path, "repair", &plaintext, 2);
+    let original = fs::read(&path).unwrap();
+    assert!(original.len() > 3 * BLOCK);
+    assert_eq!(&fs::read(&sidecar_path).unwrap()[..4], b"DXPR");
+
+    // the header block and one payload block, which parity 2 can both rebuild
+    damage_block(&path, 0);
+    damage_block(&path, 2);
+    assert!(decrypt_file(&path, b"old-pass").is_err());
+
+    assert_eq!(repair_file(&path, &sidecar_path).unwrap(), 2);
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), plaintext);
+
+    let error = repair_file(&path, &sidecar_path).expect_err("nothing left to repair");
+    assert!(matches!(error, parity::Error::NotDamaged));
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::UnsupportedWorkflow
+    );
+}
+
+#[test]
+fn repair_refuses_more_damaged_blocks_than_parity() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let plaintext = plaintext(2 * BLOCK);
+    let (path, sidecar_path) = encrypt_with_sidecar(&dir_path, "too-many", &plaintext, 1);
+    damage_block(&path, 0);
+    damage_block(&path, 1);
+    let damaged = fs::read(&path).unwrap();
+
+    let error = repair_file(&path, &sidecar_path).expect_err("parity 1 repairs one block");
+    assert!(matches!(
+        error,
+        parity::Error::TooManyDamagedBlocks {
+            damaged: 2,
+            correctable: 1
+        }
+    ));
+    assert_eq!(error.workflow_class(), WorkflowErrorClass::MalformedFormat);
+    assert_eq!(fs::read(&path).unwrap(), damaged);
+}
+
+#[test]
+fn repair_restores_a_truncated_small_artifact() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let (path, sidecar_path) = encrypt_with_sidecar(&dir_path, "small", b"Hello world", 1);
+    let original = fs::read(&path).unwrap();
+    assert!(original.len() < BLOCK);
+
+    fs::write(&path, &original[..original.len() - 5]).unwrap();
+    assert_eq!(repair_file(&path, &sidecar_path).unwrap(), 1);
+    assert_eq!(fs::read(&path).unwrap(), original);
+
+    // trailing bytes carry no block, so cutting them is repair enough
+    let mut extended = original.clone();
+    extended.extend_from_slice(b"junk");
+    fs::write(&path, &extended).unwrap();
+    assert_eq!(repair_file(&path, &sidecar_path).unwrap(), 0);
+    assert_eq!(fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn repair_refuses_a_target_changed_after_the_intent() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let plaintext = plaintext(2 * BLOCK);
+    let (path, sidecar_path) = encrypt_with_sidecar(&dir_path, "changed", &plaintext, 2);
+    damage_block(&path, 1);
+    let intent = repair::RepairIntent::new(&path, &sidecar_path).unwrap();
+
+    damage_block(&path, 0);
+    let damaged = fs::read(&path).unwrap();
+    let error = repair::execute(intent).expect_err("target changed since the intent");
+    assert!(matches!(error, parity::Error::TargetChanged));
+    assert_eq!(fs::read(&path).unwrap(), damaged);
+}
+
+#[test]
+fn repair_uses_only_intact_parity_blocks() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let plaintext = plaintext(BLOCK);
+    let (path, sidecar_path) = encrypt_with_sidecar(&dir_path, "parity", &plaintext, 2);
+    let original = fs::read(&path).unwrap();
+
+    let mut sidecar = fs::read(&sidecar_path).unwrap();
+    sidecar[parity::PARITY_PREFIX_LEN] ^= 0xFF;
+    fs::write(&sidecar_path, &sidecar).unwrap();
+    damage_block(&path, 1);
+
+    assert_eq!(repair_file(&path, &sidecar_path).unwrap(), 1);
+    assert_eq!(fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn repair_rejects_malformed_and_damaged_sidecars() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let (path, sidecar_path) = encrypt_with_sidecar(&dir_path, "sidecar", b"Hello world", 1);
+    let sidecar = fs::read(&sidecar_path).unwrap();
+    damage_block(&path, 0);
+    let damaged = fs::read(&path).unwrap();
+
+    let mut digest_damage = sidecar.clone();
+    let digest_offset = sidecar.len() - parity::PARITY_FOOTER_LEN - 1;
+    digest_damage[digest_offset] ^= 0xFF;
+    fs::write(&sidecar_path, &digest_damage).unwrap();
+    let error = repair_file(&path, &sidecar_path).expect_err("checksum mismatch");
+    assert!(matches!(error, parity::Error::DamagedSidecar));
+    assert_eq!(error.workflow_class(), WorkflowErrorClass::MalformedFormat);
+
+    let mut wrong_magic = sidecar;
+    wrong_magic[0] = b'X';
+    fs::write(&sidecar_path, &wrong_magic).unwrap();
+    assert!(matches!(
+        repair_file(&path, &sidecar_path),
+        Err(parity::Error::MalformedSidecar)
+    ));
+    assert_eq!(fs::read(&path).unwrap(), damaged);
+}
+
+#[test]
+fn sidecar_targets_are_validated_before_encrypting() {
+    for parity_blocks in [0, parity::MAX_PARITY_BLOCKS + 1] {
+        let error =
+            ParitySidecarTarget::new("out.dxpar", OverwritePolicy::CreateNew, parity_blocks)
+                .expect_err("parity block count out of range");
+        assert!(
+            matches!(error, parity::Error::InvalidParityBlocks(count) if count == parity_blocks)
+        );
+        assert_eq!(
+            error.workflow_class(),
+            WorkflowErrorClass::UnsupportedWorkflow
+        );
+    }
+
+    let (_dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    let output_path = dir_path.join("plain.enc");
+    fs::write(&input_path, b"Hello world").unwrap();
+    let error = encrypt::EncryptIntent::new(
+        &input_path,
+        &output_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .unwrap()
+    .with_parity_sidecar(
+        ParitySidecarTarget::new(&output_path, OverwritePolicy::CreateNew, 1).unwrap(),
+    )
+    .expect_err("the sidecar cannot alias the output");
+    assert!(matches!(error, encrypt::Error::PathIdentity(_)));
+    assert!(!output_path.exists());
+}
+
+#[test]
+fn detached_header_encrypt_publishes_the_sidecar_with_the_payload() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let input_path = dir_path.join("plain.txt");
+    let output_path = dir_path.join("plain.enc");
+    let header_path = dir_path.join("plain.hdr");
+    let sidecar_path = sidecar_path_for(&output_path);
+    fs::write(&input_path, b"Hello world").unwrap();
+
+    let intent = encrypt::EncryptIntent::new(
+        &input_path,
+        &output_path,
+        OverwritePolicy::CreateNew,
+        Some(encrypt::DetachedHeaderTarget::new(
+            &header_path,
+            OverwritePolicy::CreateNew,
+        )),
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .unwrap()
+    .with_parity_sidecar(
+        ParitySidecarTarget::new(&sidecar_path, OverwritePolicy::CreateNew, 1).unwrap(),
+    )
+    .unwrap();
+    let receipt = encrypt::execute(intent).unwrap();
+    assert_eq!(receipt.committed_artifacts().len(), 3);
+    assert_eq!(fs::read(&header_path).unwrap().len(), HEADER_LEN);
+
+    // the sidecar covers the payload file only
+    let payload = fs::read(&output_path).unwrap();
+    damage_block(&output_path, 0);
+    assert_eq!(repair_file(&output_path, &sidecar_path).unwrap(), 1);
+    assert_eq!(fs::read(&output_path).unwrap(), payload);
+}
+
+#[test]
+fn pack_writes_a_sidecar_with_the_archive() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let source_dir = dir_path.join("source");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("data.bin"), plaintext(BLOCK + 10)).unwrap();
+    let archive_path = dir_path.join("source.dxa");
+    let sidecar_path = sidecar_path_for(&archive_path);
+
+    let intent = pack::PackIntent::new(
+        vec![source_dir.clone()],
+        &archive_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+        ArchivePolicy::default(),
+        true,
+        None,
+    )
+    .unwrap()
+    .with_parity_sidecar(
+        ParitySidecarTarget::new(&sidecar_path, OverwritePolicy::CreateNew, 1).unwrap(),
+    )
+    .unwrap();
+    pack::execute(intent).unwrap();
+    let archive = fs::read(&archive_path).unwrap();
+
+    damage_block(&archive_path, 1);
+    assert_eq!(repair_file(&archive_path, &sidecar_path).unwrap(), 1);
+    assert_eq!(fs::read(&archive_path).unwrap(), archive);
+
+    // a sidecar inside a packed directory would be packed into itself
+    let error = pack::PackIntent::new(
+        vec![source_dir.clone()],
+        dir_path.join("again.dxa"),
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+        ArchivePolicy::default(),
+        true,
+        None,
+    )
+    .unwrap()
+    .with_parity_sidecar(
+        ParitySidecarTarget::new(
+            source_dir.join("again.dxa.dxpar"),
+            OverwritePolicy::CreateNew,
+            1,
+        )
+        .unwrap(),
+    )
+    .err()
+    .expect("sidecar inside a source directory");
+    assert!(matches!(error, pack::Error::PathIdentity(_)));
+}
+
+#[test]
+fn replacing_an_output_writes_its_existing_sidecar_again() {
+    let (_dir, dir_path) = canonical_tempdir();
+    let (path, sidecar_path) = encrypt_with_sidecar(&dir_path, "replace", &plaintext(100), 3);
+    let old_sidecar = fs::read(&sidecar_path).unwrap();
+
+    let replacement = plaintext(BLOCK + 7);
+    let input_path = dir_path.join("replacement.txt");
+    fs::write(&input_path, &replacement).unwrap();
+    let intent = encrypt::EncryptIntent::new(
+        &input_path,
+        &path,
+        OverwritePolicy::ReplaceAtCommit,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .unwrap();
+    encrypt::execute(intent).unwrap();
+
+    let sidecar = fs::read(&sidecar_path).unwrap();
+    assert_ne!(sidecar, old_sidecar);
+    // the sidecar keeps its parity block count and describes the new output
+    assert_eq!(sidecar[8..10], old_sidecar[8..10]);
+    assert!(matches!(
+        repair_file(&path, &sidecar_path),
+        Err(parity::Error::NotDamaged)
+    ));
+    damage_block(&path, 1);
+    assert_eq!(repair_file(&path, &sidecar_path).unwrap(), 1);
+    assert_eq!(decrypt_file(&path, b"old-pass").unwrap(), replacement);
+
+    // a file there that is not a sidecar is not silently replaced
+    fs::write(&sidecar_path, b"not a sidecar").unwrap();
+    let error = encrypt::EncryptIntent::new(
+        &input_path,
+        &path,
+        OverwritePolicy::ReplaceAtCommit,
+        None,
+        Protected::new(b"old-pass".to_vec()),
+        Kdf::Argon2idInteractive,
+    )
+    .expect_err("unreadable sidecar");
+    assert!(matches!(
+        error,
+        encrypt::Error::Parity(parity::Error::MalformedSidecar)
+    ));
+    assert_eq!(error.workflow_class(), WorkflowErrorClass::MalformedFormat);
+}
@@ -0,0 +1,201 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::unreachable,
+        clippy::string_slice,
+        clippy::too_many_lines,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        clippy::match_same_arms,
+        clippy::items_after_statements,
+        clippy::redundant_closure_for_method_calls,
+        clippy::needless_collect,
+        clippy::manual_let_else,
+        clippy::format_collect,
+        clippy::case_sensitive_file_extension_comparisons,
+        clippy::struct_excessive_bools,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::Path;
+
+use core::kdf::Kdf;
+use core::primitives::BLOCK_SIZE;
+use core::protected::Protected;
+use dexios_domain::storage::identity::OverwritePolicy;
+use dexios_domain::workflow_error::WorkflowErrorClass;
+use dexios_domain::{decrypt, encrypt};
+#[expect(dead_code, reason = "shared tempdir test helper")]
+#[path = "support/tempdir.rs"]
+mod tempdir;
+use tempdir::DomainTestDir as TestDir;
+
+const PASSWORD: &[u8] = b"pipe-password";
+
+fn protected_key(secret: &[u8]) -> Protected<Vec<u8>> {
+    Protected::new(secret.to_vec())
+}
+
+fn multichunk_plaintext() -> Vec<u8> {
+    (0..(BLOCK_SIZE * 2 + 37))
+        .map(|index| (index % 251) as u8)
+        .collect()
+}
+
+fn encrypt_in_memory(plaintext: &[u8]) -> Vec<u8> {
+    let intent = encrypt::PipeIntent::new(
+        None::<&Path>,
+        None::<&Path>,
+        OverwritePolicy::CreateNew,
+        protected_key(PASSWORD),
+        Kdf::Argon2idInteractive,
+    )
+    .expect("build encrypt pipe intent");
+    let mut ciphertext = Vec::new();
+    let receipt = encrypt::execute_pipe(intent, Cursor::new(plaintext), &mut ciphertext)
+        .expect("encrypt pipe");
+    assert!(receipt.is_none(), "writer output has nothing to commit");
+    ciphertext
+}
+
+fn decrypt_in_memory(ciphertext: &[u8], output: &mut Vec<u8>) -> Result<(), decrypt::Error> {
+    let intent = decrypt::PipeIntent::new(
+        None::<&Path>,
+        None::<&Path>,
+        OverwritePolicy::CreateNew,
+        protected_key(PASSWORD),
+    )
+    .expect("build decrypt pipe intent");
+    decrypt::execute_pipe(intent, Cursor::new(ciphertext), output).map(|receipt| {
+        assert!(receipt.is_none(), "writer output has nothing to commit");
+    })
+}
+
+#[test]
+fn pipe_encrypt_and_decrypt_round_trip_through_reader_and_writer() {
+    let plaintext = multichunk_plaintext();
+    let ciphertext = encrypt_in_memory(&plaintext);
+
+    let mut decrypted = Vec::new();
+    decrypt_in_memory(&ciphertext, &mut decrypted).expect("decrypt pipe");
+    assert_eq!(decrypted, plaintext);
+}
+
+#[test]
+fn piped_ciphertext_matches_the_file_workflow_format() {
+    let test_dir = TestDir::new("pipe-file-format");
+    let encrypted = test_dir.path().join("plain.enc");
+    let decrypted = test_dir.path().join("plain.out");
+    let plaintext = multichunk_plaintext();
+    fs::write(&encrypted, encrypt_in_memory(&plaintext)).unwrap();
+
+    let intent = decrypt::DecryptIntent::new(
+        &encrypted,
+        &decrypted,
+        OverwritePolicy::CreateNew,
+        None::<&Path>,
+        protected_key(PASSWORD),
+        None,
+    )
+    .expect("build decrypt intent");
+    decrypt::execute(intent).expect("file decrypt of piped ciphertext");
+    assert_eq!(fs::read(&decrypted).unwrap(), plaintext);
+}
+
+#[test]
+fn pipe_path_ends_read_and_commit_through_storage() {
+    let test_dir = TestDir::new("pipe-path-ends");
+    let plain = test_dir.path().join("plain.txt");
+    let encrypted = test_dir.path().join("plain.enc");
+    let decrypted = test_dir.path().join("plain.out");
+    fs::write(&plain, b"Hello pipes").unwrap();
+
+    let intent = encrypt::PipeIntent::new(
+        Some(&plain),
+        Some(&encrypted),
+        OverwritePolicy::CreateNew,
+        protected_key(PASSWORD),
+        Kdf::Argon2idInteractive,
+    )
+    .expect("build encrypt pipe intent");
+    let receipt = encrypt::execute_pipe(intent, io::empty(), io::sink()).expect("encrypt");
+    assert!(receipt.is_some());
+
+    let intent = decrypt::PipeIntent::new(
+        Some(&encrypted),
+        Some(&decrypted),
+        OverwritePolicy::CreateNew,
+        protected_key(PASSWORD),
+    )
+    .expect("build decrypt pipe intent");
+    let receipt = decrypt::execute_pipe(intent, io::empty(), io::sink()).expect("decrypt");
+    assert!(receipt.is_some());
+    assert_eq!(fs::read(&decrypted).unwrap(), b"Hello pipes");
+}
+
+#[test]
+fn tampered_final_block_fails_after_streaming_uncommitted_plaintext() {
+    let plaintext = multichunk_plaintext();
+    let mut ciphertext = encrypt_in_memory(&plaintext);
+    let last = ciphertext.len() - 1;
+    ciphertext[last] ^= 0x01;
+
+    let mut streamed = Vec::new();
+    let error = decrypt_in_memory(&ciphertext, &mut streamed).expect_err("final block tampered");
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::AuthenticationFailure
+    );
+    // Earlier chunks authenticated individually and were already handed to the
+    // writer; they are a prefix of the plaintext but were never committed.
+    assert!(streamed.len() < plaintext.len());
+    assert_eq!(streamed, plaintext[..streamed.len()]);
+}
+
+#[test]
+fn tampered_final_block_never_commits_path_output() {
+    let test_dir = TestDir::new("pipe-tampered-path");
+    let decrypted = test_dir.path().join("plain.out");
+    let mut ciphertext = encrypt_in_memory(&multichunk_plaintext());
+    let last = ciphertext.len() - 1;
+    ciphertext[last] ^= 0x01;
+
+    let intent = decrypt::PipeIntent::new(
+        None::<&Path>,
+        Some(&decrypted),
+        OverwritePolicy::CreateNew,
+        protected_key(PASSWORD),
+    )
+    .expect("build decrypt pipe intent");
+    let error = decrypt::execute_pipe(intent, Cursor::new(ciphertext), io::sink())
+        .expect_err("final block tampered");
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::AuthenticationFailure
+    );
+    assert!(!decrypted.exists());
+}
+
+#[test]
+fn wrong_key_fails_before_any_plaintext_is_written() {
+    let ciphertext = encrypt_in_memory(b"secret");
+    let intent = decrypt::PipeIntent::new(
+        None::<&Path>,
+        None::<&Path>,
+        OverwritePolicy::CreateNew,
+        protected_key(b"wrong-password"),
+    )
+    .expect("build decrypt pipe intent");
+    let mut streamed = Vec::new();
+    decrypt::execute_pipe(intent, Cursor::new(ciphertext), &mut streamed).expect_err("wrong key");
+    assert!(streamed.is_empty());
+}
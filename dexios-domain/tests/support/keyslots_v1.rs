@@ -154,7 +154,7 @@ pub(super) fn write_malformed_v1_fixture(path: &Path) {
     bytes[12] = 0x01;
     bytes[13] = 0x01;
     bytes[14] = 0x04;
-    bytes[15] = 1;
+    bytes[15] = 0x80;
     fs::write(path, bytes).expect("write malformed V1 fixture");
 }
 
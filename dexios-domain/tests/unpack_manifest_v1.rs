@@ -237,3 +237,76 @@ fn unpack_rejects_manifest_payload_with_body_frame_order_mismatch() {
     assert!(!output_dir.join("first.txt").exists());
     assert!(!output_dir.join("second.txt").exists());
 }
+
+#[test]
+fn unpack_rejects_symlink_target_escaping_the_output_root() {
+    let test_dir = TestDir::new("unpack-escaping-symlink");
+    let encrypted_archive = test_dir.path().join("archive.enc");
+    let output_dir = test_dir.path().join("out");
+    let manifest = ArchiveManifest::new(vec![
+        ManifestEntry::directory(b"dir".to_vec()).unwrap(),
+        ManifestEntry::symlink(b"dir/link".to_vec(), b"../../outside".to_vec()).unwrap(),
+    ])
+    .unwrap();
+    let payload = ManifestFirstPayload::new(manifest, Vec::new())
+        .unwrap()
+        .serialize()
+        .unwrap();
+    write_malformed_manifest_archive_payload(&encrypted_archive, payload);
+
+    let result = unpack_archive(&encrypted_archive, &output_dir, None);
+
+    assert!(
+        matches!(result, Err(unpack::Error::UnsafeLinkTarget(_))),
+        "expected escaping symlink rejection, got {result:?}"
+    );
+    assert!(!output_dir.join("dir/link").exists());
+}
+
+#[test]
+fn unpack_checks_recorded_entry_digests() {
+    use dexios_domain::hasher::{Blake3Hasher, Hasher};
+
+    fn digested_payload(digest: [u8; 32]) -> Vec<u8> {
+        let manifest = ArchiveManifest::new(vec![
+            ManifestEntry::file(b"safe.txt".to_vec(), 4)
+                .unwrap()
+                .with_blake3_digest(digest)
+                .unwrap(),
+        ])
+        .unwrap();
+        ManifestFirstPayload::new(
+            manifest,
+            vec![ArchiveBodyFrame::new(0, b"safe".to_vec()).unwrap()],
+        )
+        .unwrap()
+        .serialize()
+        .unwrap()
+    }
+
+    let test_dir = TestDir::new("unpack-entry-digests");
+    let encrypted_archive = test_dir.path().join("archive.enc");
+    let output_dir = test_dir.path().join("out");
+    let mut hasher = Blake3Hasher::default();
+    hasher.write(b"safe");
+
+    write_malformed_manifest_archive_payload(&encrypted_archive, digested_payload(hasher.digest()));
+    unpack_archive(&encrypted_archive, &output_dir, None).unwrap();
+    assert_eq!(fs::read(output_dir.join("safe.txt")).unwrap(), b"safe");
+
+    let mismatched_archive = test_dir.path().join("mismatched.enc");
+    let mismatched_output = test_dir.path().join("mismatched-out");
+    write_malformed_manifest_archive_payload(&mismatched_archive, digested_payload([0; 32]));
+    let result = unpack_archive(&mismatched_archive, &mismatched_output, None);
+
+    assert!(
+        matches!(
+            result,
+            Err(unpack::Error::ArchivePayload(
+                PayloadError::EntryDigestMismatch(0)
+            ))
+        ),
+        "expected entry digest mismatch, got {result:?}"
+    );
+    assert!(!mismatched_output.join("safe.txt").exists());
+}
@@ -0,0 +1,504 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        clippy::unreachable,
+        clippy::string_slice,
+        clippy::too_many_lines,
+        clippy::match_same_arms,
+        clippy::items_after_statements,
+        clippy::redundant_closure_for_method_calls,
+        clippy::needless_collect,
+        clippy::manual_let_else,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/tempdir.rs"]
+#[expect(dead_code, reason = "shared tempdir test helper")]
+mod tempdir;
+
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use core::header::v1::KeyslotKdf;
+use core::header::{ParsedHeader, read_header};
+use core::kdf::Kdf;
+use core::payload::{BodyCompression, ManifestEntryKind, PayloadFramingProfile};
+use core::protected::Protected;
+use core::recipient::RecipientIdentity;
+use dexios_domain::archive::ArchivePolicy;
+use dexios_domain::hasher::{Blake3Hasher, Hasher};
+use dexios_domain::key::{self, Credential};
+use dexios_domain::pack::{self, PackIntent};
+use dexios_domain::storage::identity::OverwritePolicy;
+use dexios_domain::unpack;
+use dexios_domain::update::{self, UpdateIntent};
+use dexios_domain::workflow_error::WorkflowErrorClass;
+use tempdir::canonical_tempdir;
+
+const PASSWORD: &[u8; 8] = b"12345678";
+const OTHER_KEY: &[u8; 8] = b"other-pw";
+
+fn create_source_dir(root: &Path) -> PathBuf {
+    let source_dir = root.join("source");
+    fs::create_dir_all(source_dir.join("nested")).unwrap();
+    fs::write(source_dir.join("hello.txt"), b"hello").unwrap();
+    fs::write(source_dir.join("nested/world.txt"), b"world").unwrap();
+    source_dir
+}
+
+fn pack_archive(source_dir: &Path, archive_path: &Path, compression: Option<BodyCompression>) {
+    let intent = pack_intent(source_dir, archive_path);
+    let intent = match compression {
+        Some(compression) => intent.with_compression(compression),
+        None => intent,
+    };
+    pack::execute_transactional(intent).unwrap();
+}
+
+fn pack_intent(source_dir: &Path, archive_path: &Path) -> PackIntent {
+    PackIntent::new(
+        vec![source_dir.to_path_buf()],
+        archive_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(PASSWORD.to_vec()),
+        Kdf::Argon2id,
+        ArchivePolicy::default(),
+        true,
+        None,
+    )
+    .unwrap()
+}
+
+fn update_intent(
+    input_path: &Path,
+    output_path: &Path,
+    additions: Vec<PathBuf>,
+    removals: Vec<&str>,
+) -> Result<UpdateIntent, update::Error> {
+    UpdateIntent::new(
+        input_path,
+        output_path,
+        OverwritePolicy::CreateNew,
+        Protected::new(PASSWORD.to_vec()),
+        additions,
+        removals,
+    )
+}
+
+fn unpack_archive(archive_path: &Path, output_dir: &Path, key: &[u8]) -> Result<(), unpack::Error> {
+    unpack_archive_with(archive_path, output_dir, Protected::new(key.to_vec()))
+}
+
+fn unpack_archive_with(
+    archive_path: &Path,
+    output_dir: &Path,
+    credential: impl Into<Credential>,
+) -> Result<(), unpack::Error> {
+    let intent =
+        unpack::UnpackIntent::new(archive_path, None, output_dir, credential, None, None, None)?;
+    unpack::execute(intent).map(|_| ())
+}
+
+fn reparsed(identity: &RecipientIdentity) -> RecipientIdentity {
+    RecipientIdentity::parse(&identity.to_identity_file().with_exposed(Clone::clone)).unwrap()
+}
+
+fn parsed_header(path: &Path) -> core::header::ParsedV1Payload {
+    let ParsedHeader::V1(parsed) = read_header(&mut Cursor::new(fs::read(path).unwrap())).unwrap();
+    parsed
+}
+
+#[test]
+fn update_adds_replaces_and_removes_entries_without_touching_the_input() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    pack_archive(&source_dir, &archive_path, None);
+    let input_bytes = fs::read(&archive_path).unwrap();
+
+    let staging = root.join("staging");
+    let replacement = staging.join("source");
+    fs::create_dir_all(&replacement).unwrap();
+    fs::write(replacement.join("hello.txt"), b"hello again").unwrap();
+    fs::write(replacement.join("added.txt"), b"added").unwrap();
+    let extra = staging.join("extra.txt");
+    fs::write(&extra, b"extra").unwrap();
+
+    let updated_path = root.join("updated.enc");
+    let intent = update_intent(
+        &archive_path,
+        &updated_path,
+        vec![replacement, extra],
+        vec!["source/nested/world.txt"],
+    )
+    .unwrap();
+    update::execute(intent).unwrap();
+
+    assert_eq!(fs::read(&archive_path).unwrap(), input_bytes);
+    assert_ne!(
+        parsed_header(&updated_path).header().payload_nonce(),
+        parsed_header(&archive_path).header().payload_nonce(),
+        "the updated archive must not reuse the input's payload nonce"
+    );
+
+    let unpack_dir = root.join("out");
+    unpack_archive(&updated_path, &unpack_dir, PASSWORD).unwrap();
+    assert_eq!(
+        fs::read(unpack_dir.join("source/hello.txt")).unwrap(),
+        b"hello again"
+    );
+    assert_eq!(
+        fs::read(unpack_dir.join("source/added.txt")).unwrap(),
+        b"added"
+    );
+    assert_eq!(fs::read(unpack_dir.join("extra.txt")).unwrap(), b"extra");
+    assert!(unpack_dir.join("source/nested").is_dir());
+    assert!(!unpack_dir.join("source/nested/world.txt").exists());
+}
+
+#[test]
+fn update_keeps_compressed_framing() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    pack_archive(&source_dir, &archive_path, Some(BodyCompression::default()));
+
+    let updated_path = root.join("updated.enc");
+    let intent = update_intent(
+        &archive_path,
+        &updated_path,
+        Vec::new(),
+        vec!["source/hello.txt"],
+    )
+    .unwrap();
+    update::execute(intent).unwrap();
+
+    assert_eq!(
+        parsed_header(&updated_path).header().payload_framing(),
+        PayloadFramingProfile::ManifestFirstCompressed
+    );
+    let unpack_dir = root.join("out");
+    unpack_archive(&updated_path, &unpack_dir, PASSWORD).unwrap();
+    assert!(!unpack_dir.join("source/hello.txt").exists());
+    assert_eq!(
+        fs::read(unpack_dir.join("source/nested/world.txt")).unwrap(),
+        b"world"
+    );
+}
+
+#[test]
+fn pack_and_update_keep_the_header_trailer() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    let intent = pack_intent(&source_dir, &archive_path)
+        .with_header_trailer()
+        .unwrap();
+    pack::execute_transactional(intent).unwrap();
+    assert!(parsed_header(&archive_path).header().has_header_trailer());
+
+    let updated_path = root.join("updated.enc");
+    let intent = update_intent(
+        &archive_path,
+        &updated_path,
+        Vec::new(),
+        vec!["source/hello.txt"],
+    )
+    .unwrap();
+    update::execute(intent).unwrap();
+
+    let updated = fs::read(&updated_path).unwrap();
+    let ParsedHeader::V1(trailer) = core::header::read_header_trailer(&updated).unwrap();
+    assert_eq!(trailer.header(), parsed_header(&updated_path).header());
+    assert!(trailer.header().has_header_trailer());
+
+    let unpack_dir = root.join("out");
+    unpack_archive(&updated_path, &unpack_dir, PASSWORD).unwrap();
+    assert!(!unpack_dir.join("source/hello.txt").exists());
+    assert_eq!(
+        fs::read(unpack_dir.join("source/nested/world.txt")).unwrap(),
+        b"world"
+    );
+}
+
+#[test]
+fn update_keeps_entry_digests_and_records_them_for_added_files() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    pack::execute_transactional(pack_intent(&source_dir, &archive_path).with_entry_digests())
+        .unwrap();
+
+    let extra = root.join("staging/extra.txt");
+    fs::create_dir_all(extra.parent().unwrap()).unwrap();
+    fs::write(&extra, b"extra").unwrap();
+    let updated_path = root.join("updated.enc");
+    let intent = update_intent(
+        &archive_path,
+        &updated_path,
+        vec![extra],
+        vec!["source/hello.txt"],
+    )
+    .unwrap();
+    update::execute(intent).unwrap();
+
+    let listing = unpack::list::execute_uncommitted(
+        unpack::list::ListIntent::new(&updated_path, None, Protected::new(PASSWORD.to_vec()))
+            .unwrap(),
+    )
+    .unwrap();
+    let digests = listing
+        .entries()
+        .iter()
+        .filter(|entry| entry.kind() == ManifestEntryKind::File)
+        .map(|entry| {
+            let mut hasher = Blake3Hasher::default();
+            hasher.write(if entry.normalized_path() == b"extra.txt" {
+                b"extra"
+            } else {
+                b"world"
+            });
+            (entry.blake3_digest().copied(), hasher.digest())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(digests.len(), 2);
+    for (recorded, expected) in digests {
+        assert_eq!(recorded, Some(expected));
+    }
+
+    let unpack_dir = root.join("out");
+    unpack_archive(&updated_path, &unpack_dir, PASSWORD).unwrap();
+    assert_eq!(fs::read(unpack_dir.join("extra.txt")).unwrap(), b"extra");
+}
+
+#[test]
+fn update_rejects_removal_of_missing_entry_before_creating_output() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    pack_archive(&source_dir, &archive_path, None);
+
+    let updated_path = root.join("updated.enc");
+    let intent = update_intent(
+        &archive_path,
+        &updated_path,
+        Vec::new(),
+        vec!["source/missing.txt"],
+    )
+    .unwrap();
+    let error = update::execute(intent).unwrap_err();
+
+    assert!(
+        matches!(&error, update::Error::EntryNotFound(path) if path == Path::new("source/missing.txt")),
+        "{error:?}"
+    );
+    assert_eq!(error.workflow_class(), WorkflowErrorClass::UnsafePath);
+    assert!(!updated_path.exists());
+}
+
+#[test]
+fn update_rejects_entries_below_a_file() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    pack_archive(&source_dir, &archive_path, None);
+
+    // An added top-level file named `source` would leave the kept entries
+    // below it without a parent directory.
+    let staging = root.join("staging");
+    fs::create_dir_all(&staging).unwrap();
+    fs::write(staging.join("source"), b"not a directory").unwrap();
+
+    let updated_path = root.join("updated.enc");
+    let intent = update_intent(
+        &archive_path,
+        &updated_path,
+        vec![staging.join("source")],
+        Vec::new(),
+    )
+    .unwrap();
+    let error = update::execute(intent).unwrap_err();
+
+    assert!(matches!(error, update::Error::PathConflict(_)), "{error:?}");
+    assert!(!updated_path.exists());
+}
+
+#[test]
+fn update_carries_every_keyslot_over_when_each_is_opened() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    pack_archive(&source_dir, &archive_path, None);
+    let proven = key::add::AddIntent::new(&archive_path)
+        .unwrap()
+        .verify_old_key(Protected::new(PASSWORD.to_vec()))
+        .unwrap();
+    key::add::execute(proven, Protected::new(OTHER_KEY.to_vec()), Kdf::Argon2id).unwrap();
+
+    let updated_path = root.join("updated.enc");
+    let error = update::execute(
+        update_intent(
+            &archive_path,
+            &updated_path,
+            Vec::new(),
+            vec!["source/hello.txt"],
+        )
+        .unwrap(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(&error, update::Error::LockedKeyslots(slots) if slots == &[1]),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::UnsupportedWorkflow
+    );
+    assert!(!updated_path.exists());
+
+    let intent = update_intent(
+        &archive_path,
+        &updated_path,
+        Vec::new(),
+        vec!["source/hello.txt"],
+    )
+    .unwrap()
+    .with_credentials([Protected::new(OTHER_KEY.to_vec())]);
+    update::execute(intent).unwrap();
+
+    assert_eq!(parsed_header(&updated_path).header().keyslots().len(), 2);
+    unpack_archive(&updated_path, &root.join("out"), PASSWORD).unwrap();
+    unpack_archive(&updated_path, &root.join("other"), OTHER_KEY).unwrap();
+    assert!(!root.join("other/source/hello.txt").exists());
+    assert_eq!(
+        fs::read(root.join("other/source/nested/world.txt")).unwrap(),
+        b"world"
+    );
+}
+
+#[test]
+fn update_rebuilds_recipient_keyslots_from_their_public_keys() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    let alice = RecipientIdentity::generate();
+    let bob = RecipientIdentity::generate();
+    let intent = PackIntent::new_for_recipients(
+        vec![source_dir],
+        &archive_path,
+        OverwritePolicy::CreateNew,
+        None,
+        vec![alice.public_key(), bob.public_key()],
+        ArchivePolicy::default(),
+        true,
+        None,
+    )
+    .unwrap();
+    pack::execute_transactional(intent).unwrap();
+
+    let recipient_update = |output: &Path| {
+        UpdateIntent::new(
+            &archive_path,
+            output,
+            OverwritePolicy::CreateNew,
+            reparsed(&alice),
+            Vec::<PathBuf>::new(),
+            vec!["source/hello.txt"],
+        )
+        .unwrap()
+    };
+
+    let updated_path = root.join("updated.enc");
+    let error = update::execute(recipient_update(&updated_path)).unwrap_err();
+    assert!(
+        matches!(&error, update::Error::LockedKeyslots(slots) if slots == &[1]),
+        "{error:?}"
+    );
+    let error = update::execute(recipient_update(&updated_path).with_recipients(vec![
+        bob.public_key(),
+        RecipientIdentity::generate().public_key(),
+    ]))
+    .unwrap_err();
+    assert!(
+        matches!(error, update::Error::UnusedRecipients(1)),
+        "{error:?}"
+    );
+    assert!(!updated_path.exists());
+
+    // alice's own public key names the keyslot her identity already opened
+    let intent =
+        recipient_update(&updated_path).with_recipients(vec![alice.public_key(), bob.public_key()]);
+    update::execute(intent).unwrap();
+
+    let header = parsed_header(&updated_path);
+    assert_eq!(header.header().keyslots().len(), 2);
+    assert!(
+        header
+            .header()
+            .keyslots()
+            .iter()
+            .all(|keyslot| keyslot.kdf() == KeyslotKdf::X25519Recipient)
+    );
+    unpack_archive_with(&updated_path, &root.join("alice"), alice).unwrap();
+    unpack_archive_with(&updated_path, &root.join("bob"), bob).unwrap();
+    assert!(!root.join("bob/source/hello.txt").exists());
+    assert_eq!(
+        fs::read(root.join("bob/source/nested/world.txt")).unwrap(),
+        b"world"
+    );
+}
+
+#[test]
+fn update_drops_locked_keyslots_only_when_asked() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    pack_archive(&source_dir, &archive_path, None);
+    let proven = key::add::AddIntent::new(&archive_path)
+        .unwrap()
+        .verify_old_key(Protected::new(PASSWORD.to_vec()))
+        .unwrap();
+    key::add::execute(proven, Protected::new(OTHER_KEY.to_vec()), Kdf::Argon2id).unwrap();
+
+    let updated_path = root.join("updated.enc");
+    let intent = update_intent(
+        &archive_path,
+        &updated_path,
+        Vec::new(),
+        vec!["source/hello.txt"],
+    )
+    .unwrap()
+    .with_locked_keyslots_dropped();
+    update::execute(intent).unwrap();
+
+    let header = parsed_header(&updated_path);
+    assert_eq!(header.header().keyslots().len(), 1);
+    assert_eq!(header.header().keyslots()[0].physical_index(), 0);
+    assert!(unpack_archive(&updated_path, &root.join("other"), OTHER_KEY).is_err());
+    unpack_archive(&updated_path, &root.join("out"), PASSWORD).unwrap();
+    assert!(!root.join("out/source/hello.txt").exists());
+}
+
+#[test]
+fn update_requires_a_change() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = create_source_dir(&root);
+    let archive_path = root.join("archive.enc");
+    pack_archive(&source_dir, &archive_path, None);
+
+    let result = update_intent(
+        &archive_path,
+        &root.join("updated.enc"),
+        Vec::new(),
+        Vec::new(),
+    );
+
+    assert!(matches!(result, Err(update::Error::NothingToUpdate)));
+}
@@ -0,0 +1,201 @@
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects,
+        reason = "integration tests assert exact behavior and may panic on failure"
+    )
+)]
+#[path = "support/tempdir.rs"]
+#[expect(dead_code, reason = "shared tempdir test helper")]
+mod tempdir;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use core::header::common::HEADER_LEN;
+use core::kdf::Kdf;
+use core::payload::BodyCompression;
+use core::primitives::BLOCK_SIZE;
+use core::protected::Protected;
+use dexios_domain::archive::ArchivePolicy;
+use dexios_domain::encrypt;
+use dexios_domain::pack::{self, PackIntent};
+use dexios_domain::storage::identity::OverwritePolicy;
+use dexios_domain::verify::{self, ArchiveEntryCounts, VerifiedPayload, VerifyIntent};
+use dexios_domain::workflow_error::WorkflowErrorClass;
+use tempdir::canonical_tempdir;
+
+const PASSWORD: &[u8; 8] = b"12345678";
+const STREAM_TAG_LEN: usize = 16;
+
+fn verify_file(path: &Path, key: &[u8]) -> Result<VerifiedPayload, verify::Error> {
+    let intent = VerifyIntent::new(path, None, Protected::new(key.to_vec()))?;
+    verify::execute(intent).map(|report| report.payload())
+}
+
+fn encrypted_file(root: &Path, plaintext: &[u8]) -> PathBuf {
+    let plain = root.join("plain.txt");
+    let encrypted = root.join("plain.enc");
+    fs::write(&plain, plaintext).unwrap();
+    let intent = encrypt::EncryptIntent::new(
+        &plain,
+        &encrypted,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(PASSWORD.to_vec()),
+        Kdf::Argon2id,
+    )
+    .unwrap();
+    encrypt::execute(intent).unwrap();
+    encrypted
+}
+
+fn packed_archive(root: &Path, compression: Option<BodyCompression>) -> PathBuf {
+    let source_dir = root.join("source");
+    fs::create_dir_all(source_dir.join("nested")).unwrap();
+    fs::write(source_dir.join("hello.txt"), b"hello").unwrap();
+    fs::write(source_dir.join("nested/world.txt"), b"world").unwrap();
+
+    let archive_path = root.join("archive.enc");
+    let intent = PackIntent::new(
+        vec![source_dir],
+        &archive_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(PASSWORD.to_vec()),
+        Kdf::Argon2id,
+        ArchivePolicy::default(),
+        true,
+        None,
+    )
+    .unwrap();
+    let intent = match compression {
+        Some(compression) => intent.with_compression(compression),
+        None => intent,
+    };
+    pack::execute_transactional(intent).unwrap();
+    archive_path
+}
+
+fn flip_final_tag_byte(path: &Path) {
+    let mut bytes = fs::read(path).unwrap();
+    let offset = bytes.len() - STREAM_TAG_LEN;
+    assert!(offset > HEADER_LEN);
+    bytes[offset] ^= 0x40;
+    fs::write(path, bytes).unwrap();
+}
+
+fn directory_names(path: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(path)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn verify_reports_the_plaintext_length_of_a_raw_file() {
+    let (_root_dir, root) = canonical_tempdir();
+    let plaintext = vec![0x5a; BLOCK_SIZE * 2 + 17];
+    let encrypted = encrypted_file(&root, &plaintext);
+    let before = directory_names(&root);
+
+    let payload = verify_file(&encrypted, PASSWORD).unwrap();
+
+    assert_eq!(
+        payload,
+        VerifiedPayload::RawFile {
+            plaintext_len: plaintext.len() as u64
+        }
+    );
+    assert_eq!(directory_names(&root), before);
+}
+
+#[test]
+fn verify_counts_archive_entries_for_both_framings() {
+    for compression in [None, Some(BodyCompression::default())] {
+        let (_root_dir, root) = canonical_tempdir();
+        let archive = packed_archive(&root, compression);
+
+        let payload = verify_file(&archive, PASSWORD).unwrap();
+
+        assert_eq!(
+            payload,
+            VerifiedPayload::Archive {
+                entries: ArchiveEntryCounts {
+                    files: 2,
+                    directories: 2,
+                    symlinks: 0,
+                    hard_links: 0,
+                },
+                body_bytes: 10,
+                digests: 0,
+            },
+            "{compression:?}"
+        );
+    }
+}
+
+#[test]
+fn verify_counts_checked_entry_digests() {
+    let (_root_dir, root) = canonical_tempdir();
+    let source_dir = root.join("source");
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::write(source_dir.join("a.txt"), b"alpha").unwrap();
+    fs::write(source_dir.join("b.txt"), vec![0x11; BLOCK_SIZE + 3]).unwrap();
+    let archive_path = root.join("archive.enc");
+    let intent = PackIntent::new(
+        vec![source_dir],
+        &archive_path,
+        OverwritePolicy::CreateNew,
+        None,
+        Protected::new(PASSWORD.to_vec()),
+        Kdf::Argon2id,
+        ArchivePolicy::default(),
+        true,
+        None,
+    )
+    .unwrap()
+    .with_entry_digests();
+    pack::execute_transactional(intent).unwrap();
+
+    let payload = verify_file(&archive_path, PASSWORD).unwrap();
+
+    assert!(
+        matches!(payload, VerifiedPayload::Archive { digests: 2, .. }),
+        "{payload:?}"
+    );
+}
+
+#[test]
+fn verify_classifies_tampering_and_wrong_keys() {
+    let (_root_dir, root) = canonical_tempdir();
+    let archive = packed_archive(&root, None);
+
+    let error = verify_file(&archive, b"wrong-pw").unwrap_err();
+    assert_eq!(error.workflow_class(), WorkflowErrorClass::IncorrectKey);
+
+    flip_final_tag_byte(&archive);
+    let error = verify_file(&archive, PASSWORD).unwrap_err();
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::AuthenticationFailure,
+        "{error}"
+    );
+
+    let encrypted = encrypted_file(&root, b"Hello world");
+    let mut bytes = fs::read(&encrypted).unwrap();
+    bytes.pop();
+    fs::write(&encrypted, bytes).unwrap();
+    let error = verify_file(&encrypted, PASSWORD).unwrap_err();
+    assert_eq!(
+        error.workflow_class(),
+        WorkflowErrorClass::AuthenticationFailure,
+        "{error}"
+    );
+}
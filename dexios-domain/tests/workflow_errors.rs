@@ -845,3 +845,13 @@ fn key_delete_errors_classify_mutation_failures_without_display_strings() {
         WorkflowErrorClass::IncorrectKey
     );
 }
+
+#[test]
+fn workflow_error_class_names_are_unique() {
+    let names = WorkflowErrorClass::ALL
+        .iter()
+        .map(|class| class.as_str())
+        .collect::<std::collections::BTreeSet<_>>();
+
+    assert_eq!(names.len(), WorkflowErrorClass::ALL.len());
+}
@@ -1,5 +1,8 @@
 use clap::Command;
+use core::header::common::{MAX_EXTENDED_KEYSLOTS, MAX_KEYSLOTS};
 use core::key::PassphraseWordCount;
+use core::payload::{BodyCompression, MAX_ZSTD_LEVEL};
+use domain::parity::MAX_PARITY_BLOCKS;
 
 mod args;
 mod commands;
@@ -22,6 +25,68 @@ fn validate_autogenerate_words(words: &str) -> Result<String, String> {
     Ok(words.to_owned())
 }
 
+const MAX_THREADS: usize = 256;
+
+fn validate_threads(threads: &str) -> Result<String, String> {
+    let parsed = threads
+        .parse::<usize>()
+        .map_err(|_| "thread count must be a positive integer".to_owned())?;
+    if !(1..=MAX_THREADS).contains(&parsed) {
+        return Err(format!("thread count must be between 1 and {MAX_THREADS}"));
+    }
+    Ok(threads.to_owned())
+}
+
+fn validate_keyslots(keyslots: &str) -> Result<String, String> {
+    let parsed = keyslots
+        .parse::<usize>()
+        .map_err(|_| "keyslot count must be a positive integer".to_owned())?;
+    if !(MAX_KEYSLOTS..=MAX_EXTENDED_KEYSLOTS).contains(&parsed) {
+        return Err(format!(
+            "keyslot count must be between {MAX_KEYSLOTS} and {MAX_EXTENDED_KEYSLOTS}"
+        ));
+    }
+    Ok(keyslots.to_owned())
+}
+
+fn validate_parity(blocks: &str) -> Result<String, String> {
+    let parsed = blocks
+        .parse::<usize>()
+        .map_err(|_| "parity block count must be a positive integer".to_owned())?;
+    if !(1..=MAX_PARITY_BLOCKS).contains(&parsed) {
+        return Err(format!(
+            "parity block count must be between 1 and {MAX_PARITY_BLOCKS}"
+        ));
+    }
+    Ok(blocks.to_owned())
+}
+
+fn validate_compression_level(level: &str) -> Result<String, String> {
+    level
+        .parse::<i32>()
+        .ok()
+        .and_then(|parsed| BodyCompression::zstd(parsed).ok())
+        .ok_or_else(|| format!("compression level must be between 1 and {MAX_ZSTD_LEVEL}"))?;
+    Ok(level.to_owned())
+}
+
+fn validate_range(range: &str) -> Result<String, String> {
+    let (start, len) = range
+        .split_once(':')
+        .ok_or_else(|| "range must be written as start:len".to_owned())?;
+    start
+        .parse::<u64>()
+        .map_err(|_| "range start must be a non-negative byte offset".to_owned())?;
+    len.parse::<u64>()
+        .map_err(|_| "range length must be a non-negative byte count".to_owned())?;
+    Ok(range.to_owned())
+}
+
+fn validate_archive_pattern(pattern: &str) -> Result<String, String> {
+    domain::archive::ArchivePattern::new(pattern).map_err(|error| error.to_string())?;
+    Ok(pattern.to_owned())
+}
+
 // this assembles the clap subcommands and arguments for get_matches()
 pub(crate) fn build_cli() -> Command {
     Command::new("dexios")
@@ -30,12 +95,19 @@ pub(crate) fn build_cli() -> Command {
         .about("Secure, fast and modern command-line encryption of files.")
         .subcommand_required(true)
         .arg_required_else_help(true)
+        .arg(args::output_format_arg())
         .subcommand(commands::stream::encrypt_command())
         .subcommand(commands::stream::decrypt_command())
         .subcommand(commands::hash::hash_command())
         .subcommand(commands::archive::pack_command())
         .subcommand(commands::archive::unpack_command())
+        .subcommand(commands::archive::list_command())
+        .subcommand(commands::archive::diff_command())
+        .subcommand(commands::archive::update_command())
+        .subcommand(commands::stream::verify_command())
+        .subcommand(commands::repair::repair_command())
         .subcommand(commands::key::key_command())
+        .subcommand(commands::keygen::keygen_command())
         .subcommand(commands::header::header_command())
 }
 
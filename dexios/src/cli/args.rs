@@ -1,4 +1,7 @@
-use clap::{Arg, ArgAction};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, value_parser};
+use core::kdf::Kdf;
+use domain::hasher::HashAlgorithm;
 
 pub(super) fn input_arg(help: &'static str) -> Arg {
     Arg::new("input")
@@ -29,6 +32,160 @@ pub(super) fn keyfile_arg() -> Arg {
     keyfile_arg_with_help("Use a keyfile instead of a password")
 }
 
+/// `--keyfile`, `--identity` and `--passphrases` for commands that open
+/// several keyslots, one key each.
+pub(super) fn keyslot_key_args(
+    keyfile_help: &'static str,
+    identity_help: &'static str,
+) -> [Arg; 3] {
+    [
+        keyfile_arg_with_help(keyfile_help).action(ArgAction::Append),
+        Arg::new("identity")
+            .long("identity")
+            .value_name("file")
+            .action(ArgAction::Append)
+            .help(identity_help),
+        Arg::new("passphrases")
+            .long("passphrases")
+            .value_name("count")
+            .value_parser(value_parser!(u8).range(1..))
+            .action(ArgAction::Set)
+            .help("Prompt for this many passphrases [default: 1 without --keyfile or --identity]"),
+    ]
+}
+
+pub(super) fn recipient_arg() -> Arg {
+    Arg::new("recipient")
+        .long("recipient")
+        .value_name("public key")
+        .action(ArgAction::Append)
+        .help("Encrypt to an X25519 public key from `dexios keygen` (repeatable, up to 32)")
+        .conflicts_with_all(["keyfile", "autogenerate"])
+}
+
+pub(super) fn identity_arg(conflict_target: &'static str) -> Arg {
+    Arg::new("identity")
+        .long("identity")
+        .value_name("file")
+        .action(ArgAction::Set)
+        .help("Use an identity file from `dexios keygen` instead of a password")
+        .conflicts_with(conflict_target)
+}
+
+pub(super) fn kdf_profile_arg() -> Arg {
+    Arg::new("kdf-profile")
+        .long("kdf-profile")
+        .value_name("profile")
+        .value_parser(PossibleValuesParser::new(
+            Kdf::PROFILES.map(Kdf::profile_name),
+        ))
+        .action(ArgAction::Set)
+        .help("Argon2id cost profile for the new keyslot [default: default]")
+}
+
+pub(super) fn keyslots_arg() -> Arg {
+    Arg::new("keyslots")
+        .long("keyslots")
+        .value_name("count")
+        .value_parser(super::validate_keyslots)
+        .action(ArgAction::Set)
+        .help("Reserve room for this many keys, 4 to 32 [default: 4, or one per recipient]")
+}
+
+pub(super) fn threads_arg(help: &'static str) -> Arg {
+    Arg::new("threads")
+        .long("threads")
+        .value_name("count")
+        .value_parser(super::validate_threads)
+        .action(ArgAction::Set)
+        .help(help)
+}
+
+/// `--threads` for the encrypt and decrypt block pipeline, which is only
+/// built with the `parallel` feature.
+pub(super) fn block_threads_arg() -> Option<Arg> {
+    cfg!(feature = "parallel").then(|| {
+        threads_arg("Encrypt or decrypt up to this many 1 MiB blocks in parallel [default: 1]")
+    })
+}
+
+pub(super) fn range_arg() -> Arg {
+    let arg = Arg::new("range")
+        .long("range")
+        .value_name("start:len")
+        .value_parser(super::validate_range)
+        .action(ArgAction::Set)
+        .help("Decrypt only `len` plaintext bytes starting at byte `start`")
+        .conflicts_with("delete-input");
+    if cfg!(feature = "parallel") {
+        arg.conflicts_with("threads")
+    } else {
+        arg
+    }
+}
+
+pub(super) fn include_arg() -> Arg {
+    Arg::new("include")
+        .long("include")
+        .value_name("pattern")
+        .value_parser(super::validate_archive_pattern)
+        .action(ArgAction::Append)
+        .help("Only extract entries matching this pattern (may be repeated)")
+}
+
+pub(super) fn exclude_arg() -> Arg {
+    Arg::new("exclude")
+        .long("exclude")
+        .value_name("pattern")
+        .value_parser(super::validate_archive_pattern)
+        .action(ArgAction::Append)
+        .help("Skip entries matching this pattern, even if included (may be repeated)")
+}
+
+pub(super) fn batch_arg(help: &'static str) -> Arg {
+    Arg::new("batch")
+        .long("batch")
+        .value_name("file")
+        .num_args(1..)
+        .action(ArgAction::Set)
+        .requires("out-dir")
+        .conflicts_with_all(["input", "output", "header"])
+        .help(help)
+}
+
+pub(super) fn out_dir_arg() -> Arg {
+    Arg::new("out-dir")
+        .long("out-dir")
+        .value_name("directory")
+        .action(ArgAction::Set)
+        .requires("batch")
+        .conflicts_with_all(["input", "output"])
+        .help("The existing directory that --batch writes its outputs to")
+}
+
+pub(super) fn suffix_arg(help: &'static str) -> Arg {
+    Arg::new("suffix")
+        .long("suffix")
+        .value_name("suffix")
+        .value_parser(clap::builder::NonEmptyStringValueParser::new())
+        .action(ArgAction::Set)
+        .requires("batch")
+        .conflicts_with_all(["input", "output"])
+        .help(help)
+}
+
+// the id differs from the long name because most commands already take a
+// positional `output`
+pub(super) fn output_format_arg() -> Arg {
+    Arg::new("output-format")
+        .long("output")
+        .value_name("format")
+        .value_parser(["text", "json"])
+        .default_value("text")
+        .global(true)
+        .help("Print text for people, or one JSON result document on stdout for scripts")
+}
+
 pub(super) fn force_arg() -> Arg {
     Arg::new("force")
         .short('f')
@@ -42,7 +199,18 @@ pub(super) fn hash_arg() -> Arg {
         .short('H')
         .long("hash")
         .action(ArgAction::SetTrue)
-        .help("Return a BLAKE3 hash of the encrypted file")
+        .help("Return a hash of the encrypted file (BLAKE3 unless --algorithm is given)")
+}
+
+pub(super) fn algorithm_arg() -> Arg {
+    Arg::new("algorithm")
+        .long("algorithm")
+        .value_name("algorithm")
+        .action(ArgAction::Set)
+        .value_parser(PossibleValuesParser::new(
+            HashAlgorithm::ALL.map(HashAlgorithm::name),
+        ))
+        .help("The hash algorithm to use (default: blake3)")
 }
 
 pub(super) fn delete_input_arg(help: &'static str) -> Arg {
@@ -83,6 +251,31 @@ pub(super) fn detached_header_output_arg() -> Arg {
         .help("Store the header separately from the file")
 }
 
+pub(super) fn header_trailer_arg() -> Arg {
+    Arg::new("header-trailer")
+        .long("header-trailer")
+        .action(ArgAction::SetTrue)
+        .conflicts_with("header")
+        .help("Append a backup copy of the header to the end of the file, for `header repair`")
+}
+
+pub(super) fn parity_arg() -> Arg {
+    Arg::new("parity")
+        .long("parity")
+        .value_name("blocks")
+        .value_parser(super::validate_parity)
+        .action(ArgAction::Set)
+        .help("Also write <output>.dxpar, repairing up to this many damaged 1 MiB blocks in every 64, 1 to 32")
+}
+
+pub(super) fn sidecar_arg() -> Arg {
+    Arg::new("sidecar")
+        .long("sidecar")
+        .value_name("file")
+        .action(ArgAction::Set)
+        .help("The parity sidecar [default: <input>.dxpar]")
+}
+
 pub(super) fn detached_header_input_arg() -> Arg {
     Arg::new("header")
         .long("header")
@@ -1,4 +1,4 @@
-use clap::{Arg, ArgAction, Command};
+use clap::{Arg, ArgAction, ArgGroup, Command};
 
 use crate::cli::args;
 
@@ -24,7 +24,147 @@ pub(in crate::cli) fn pack_command() -> Command {
         .arg(args::detached_header_output_arg())
         .arg(args::recursive_arg())
         .arg(args::keyfile_arg())
+        .arg(args::kdf_profile_arg().conflicts_with("recipient"))
+        .arg(args::recipient_arg())
+        .arg(args::keyslots_arg())
+        .arg(args::header_trailer_arg())
+        .arg(args::parity_arg())
+        .arg(
+            Arg::new("no-metadata")
+                .long("no-metadata")
+                .action(ArgAction::SetTrue)
+                .help("Do not record file modes, timestamps or extended attributes"),
+        )
+        .arg(
+            Arg::new("owner")
+                .long("owner")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-metadata")
+                .help("Also record each entry's numeric owner and group"),
+        )
+        .arg(
+            Arg::new("compress")
+                .long("compress")
+                .value_name("level")
+                .num_args(0..=1)
+                .default_missing_value("3")
+                .value_parser(crate::cli::validate_compression_level)
+                .action(ArgAction::Set)
+                .require_equals(true)
+                .help("Compress file bodies with zstd (level 1-19, default 3); older releases cannot unpack the result"),
+        )
+        .arg(
+            Arg::new("entry-digests")
+                .long("entry-digests")
+                .action(ArgAction::SetTrue)
+                .help("Record a BLAKE3 digest of every file in the manifest; older releases cannot unpack the result"),
+        )
         .arg(args::hash_arg())
+        .arg(args::algorithm_arg().requires("hash"))
+        .arg(args::force_arg())
+}
+
+pub(in crate::cli) fn list_command() -> Command {
+    Command::new("list")
+        .about("List the entries of a packed archive without extracting it")
+        .arg(args::input_arg("The packed archive"))
+        .arg(args::keyfile_arg())
+        .arg(args::identity_arg("keyfile"))
+        .arg(args::detached_header_input_arg())
+        .arg(
+            Arg::new("long")
+                .short('l')
+                .long("long")
+                .action(ArgAction::SetTrue)
+                .help("Show the entry kind and body length"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("long")
+                .help("Print the entries as a JSON array"),
+        )
+}
+
+pub(in crate::cli) fn diff_command() -> Command {
+    Command::new("diff")
+        .about("Compare a packed archive with a directory without extracting it")
+        .arg(args::input_arg("The packed archive"))
+        .arg(
+            Arg::new("directory")
+                .value_name("directory")
+                .action(ArgAction::Set)
+                .required(true)
+                .help("The directory to compare against, as it would be unpacked into"),
+        )
+        .arg(args::keyfile_arg())
+        .arg(args::identity_arg("keyfile"))
+        .arg(args::detached_header_input_arg())
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the differences as a JSON object"),
+        )
+}
+
+pub(in crate::cli) fn update_command() -> Command {
+    Command::new("update")
+        .about("Add, replace or remove entries of a packed archive, writing a new archive")
+        .arg(args::input_arg("The packed archive"))
+        .arg(args::output_arg("The updated archive"))
+        .arg(
+            Arg::new("add")
+                .long("add")
+                .value_name("path")
+                .action(ArgAction::Append)
+                .help("A file or directory to pack in, replacing any entry at the same archive path (may be repeated)"),
+        )
+        .arg(
+            Arg::new("remove")
+                .long("remove")
+                .value_name("archive-path")
+                .action(ArgAction::Append)
+                .help("An archive path to remove, along with everything below it (may be repeated)"),
+        )
+        .group(
+            ArgGroup::new("changes")
+                .args(["add", "remove"])
+                .multiple(true)
+                .required(true),
+        )
+        .args(args::keyslot_key_args(
+            "Open a keyslot with this keyfile; every keyslot needs a key (repeatable)",
+            "Open a keyslot with this identity file from `dexios keygen` (repeatable)",
+        ))
+        .arg(
+            Arg::new("recipient")
+                .long("recipient")
+                .value_name("public key")
+                .action(ArgAction::Append)
+                .help("Rebuild an unopened recipient keyslot for this public key, in keyslot order (repeatable)"),
+        )
+        .arg(
+            Arg::new("drop-locked-keys")
+                .long("drop-locked-keys")
+                .action(ArgAction::SetTrue)
+                .help("Write the update without the keyslots that no key or public key opens"),
+        )
+        .arg(
+            Arg::new("no-metadata")
+                .long("no-metadata")
+                .action(ArgAction::SetTrue)
+                .help("Do not record file modes, timestamps or extended attributes of added entries"),
+        )
+        .arg(
+            Arg::new("owner")
+                .long("owner")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-metadata")
+                .help("Also record each added entry's numeric owner and group"),
+        )
+        .arg(args::verbose_arg())
         .arg(args::force_arg())
 }
 
@@ -35,11 +175,39 @@ pub(in crate::cli) fn unpack_command() -> Command {
         .arg(args::input_arg("The file to decrypt"))
         .arg(args::output_arg("The output file"))
         .arg(args::keyfile_arg())
+        .arg(args::identity_arg("keyfile"))
         .arg(args::detached_header_input_arg())
         .arg(args::delete_input_arg(
             "Delete the encrypted input after a successful unpack",
         ))
+        .arg(args::include_arg())
+        .arg(args::exclude_arg())
+        .arg(
+            Arg::new("no-permissions")
+                .long("no-permissions")
+                .action(ArgAction::SetTrue)
+                .help("Do not restore recorded file modes"),
+        )
+        .arg(
+            Arg::new("no-times")
+                .long("no-times")
+                .action(ArgAction::SetTrue)
+                .help("Do not restore recorded access and modification times"),
+        )
+        .arg(
+            Arg::new("no-xattrs")
+                .long("no-xattrs")
+                .action(ArgAction::SetTrue)
+                .help("Do not restore recorded extended attributes"),
+        )
+        .arg(
+            Arg::new("same-owner")
+                .long("same-owner")
+                .action(ArgAction::SetTrue)
+                .help("Restore the recorded owner and group (usually needs root)"),
+        )
         .arg(args::verbose_arg())
         .arg(args::hash_arg())
+        .arg(args::algorithm_arg().requires("hash"))
         .arg(args::force_arg())
 }
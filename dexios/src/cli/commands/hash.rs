@@ -1,12 +1,34 @@
 use clap::{Arg, ArgAction, Command};
 
+use crate::cli::args;
+
 pub(in crate::cli) fn hash_command() -> Command {
-    Command::new("hash").about("Hash files with BLAKE3").arg(
-        Arg::new("input")
-            .value_name("input")
-            .action(ArgAction::Set)
-            .required(true)
-            .help("The file(s) to hash")
-            .num_args(1..),
-    )
+    Command::new("hash")
+        .about("Hash files, or check them against a checksum file")
+        .arg(
+            Arg::new("input")
+                .value_name("input")
+                .action(ArgAction::Set)
+                .required(true)
+                .help("The file(s) or directories to hash, or with --check the checksum file(s) to read")
+                .num_args(1..),
+        )
+        .arg(args::algorithm_arg())
+        .arg(args::threads_arg(
+            "Hash up to this many files at once [default: number of CPUs]",
+        ))
+        .arg(
+            Arg::new("coreutils")
+                .long("coreutils")
+                .action(ArgAction::SetTrue)
+                .help("Print `<hash>  <file>` lines in the format of sha256sum and b3sum"),
+        )
+        .arg(
+            Arg::new("check")
+                .short('c')
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("coreutils")
+                .help("Read `<hash>  <file>` lines and check each file against its hash"),
+        )
 }
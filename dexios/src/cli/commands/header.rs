@@ -9,6 +9,7 @@ pub(in crate::cli) fn header_command() -> Command {
         .subcommand(dump_command())
         .subcommand(restore_command())
         .subcommand(strip_command())
+        .subcommand(repair_command())
         .subcommand(details_command())
 }
 
@@ -39,6 +40,14 @@ fn strip_command() -> Command {
         .arg(args::force_arg())
 }
 
+fn repair_command() -> Command {
+    Command::new("repair")
+        .about("Rebuild a damaged header from the backup copy at the end of the file")
+        .arg_required_else_help(true)
+        .arg(args::input_arg("The encrypted file"))
+        .arg(args::force_arg())
+}
+
 fn details_command() -> Command {
     Command::new("details")
         .about("Show details of a header")
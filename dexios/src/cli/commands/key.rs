@@ -1,4 +1,4 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, ArgGroup, Command, value_parser};
 
 use crate::cli::args;
 
@@ -10,6 +10,7 @@ pub(in crate::cli) fn key_command() -> Command {
         .subcommand(add_command())
         .subcommand(del_command())
         .subcommand(verify_command())
+        .subcommand(rotate_command())
 }
 
 fn change_command() -> Command {
@@ -21,7 +22,12 @@ fn change_command() -> Command {
             "Autogenerate a passphrase (default is 7 words)",
         ))
         .arg(args::keyfile_old_arg())
+        .arg(args::identity_arg("keyfile-old"))
         .arg(args::keyfile_new_arg())
+        .arg(args::kdf_profile_arg())
+        .arg(label_arg(
+            "Change the keyslot with this label; any key that opens the file authorizes it",
+        ))
         .arg(args::force_arg())
 }
 
@@ -34,7 +40,10 @@ fn add_command() -> Command {
             "Autogenerate a passphrase for the new key",
         ))
         .arg(args::keyfile_old_arg())
+        .arg(args::identity_arg("keyfile-old"))
         .arg(args::keyfile_new_arg())
+        .arg(args::kdf_profile_arg())
+        .arg(label_arg("Label the new keyslot (up to 12 bytes)"))
 }
 
 fn del_command() -> Command {
@@ -45,6 +54,30 @@ fn del_command() -> Command {
         .arg(args::keyfile_arg_with_help(
             "Use a keyfile to identify the key you want to delete",
         ))
+        .arg(args::identity_arg("keyfile"))
+        .arg(label_arg(
+            "Delete the keyslot with this label; any key that opens the file authorizes it",
+        ))
+        .arg(
+            Arg::new("slot")
+                .long("slot")
+                .value_name("index")
+                .value_parser(value_parser!(usize))
+                .action(ArgAction::Set)
+                .help(
+                    "Delete the keyslot at this index; any key that opens the file authorizes it",
+                ),
+        )
+        .group(ArgGroup::new("target").args(["label", "slot"]))
+        .arg(
+            Arg::new("authorize-with")
+                .long("authorize-with")
+                .value_name("file")
+                .action(ArgAction::Set)
+                .help("Authorize deleting --slot or --label with this keyfile")
+                .requires("target")
+                .conflicts_with_all(["keyfile", "identity"]),
+        )
         .arg(args::force_arg())
 }
 
@@ -54,6 +87,27 @@ fn verify_command() -> Command {
         .arg_required_else_help(true)
         .arg(args::input_arg("The encrypted file/header file"))
         .arg(args::keyfile_arg_with_help("Verify a keyfile"))
+        .arg(args::identity_arg("keyfile"))
+}
+
+fn rotate_command() -> Command {
+    Command::new("rotate")
+        .about("Re-encrypt a file under a new master key, keeping only the keyslots you unlock")
+        .arg_required_else_help(true)
+        .arg(args::input_arg("The encrypted file"))
+        .args(args::keyslot_key_args(
+            "Keep the keyslot this keyfile opens (repeatable)",
+            "Keep the keyslot this identity file from `dexios keygen` opens (repeatable)",
+        ))
+        .arg(args::force_arg())
+}
+
+fn label_arg(help: &'static str) -> Arg {
+    Arg::new("label")
+        .long("label")
+        .value_name("name")
+        .action(ArgAction::Set)
+        .help(help)
 }
 
 fn autogenerate_new_key_arg(help: &'static str) -> Arg {
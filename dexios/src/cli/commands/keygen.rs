@@ -0,0 +1,11 @@
+use clap::Command;
+
+use crate::cli::args;
+
+pub(in crate::cli) fn keygen_command() -> Command {
+    Command::new("keygen")
+        .about("Generate an X25519 identity for public-key encryption")
+        .arg_required_else_help(true)
+        .arg(args::output_arg("The identity file to create"))
+        .arg(args::force_arg())
+}
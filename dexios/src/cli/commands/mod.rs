@@ -2,4 +2,6 @@ pub(super) mod archive;
 pub(super) mod hash;
 pub(super) mod header;
 pub(super) mod key;
+pub(super) mod keygen;
+pub(super) mod repair;
 pub(super) mod stream;
@@ -0,0 +1,12 @@
+use clap::Command;
+
+use crate::cli::args;
+
+pub(in crate::cli) fn repair_command() -> Command {
+    Command::new("repair")
+        .about("Rebuild damaged blocks of an encrypted file or archive from its parity sidecar")
+        .arg_required_else_help(true)
+        .arg(args::input_arg("The encrypted file or archive"))
+        .arg(args::sidecar_arg())
+        .arg(args::force_arg())
+}
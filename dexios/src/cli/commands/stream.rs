@@ -6,18 +6,41 @@ pub(in crate::cli) fn encrypt_command() -> Command {
     Command::new("encrypt")
         .short_flag('e')
         .about("Encrypt a file")
-        .arg(args::input_arg("The file to encrypt"))
-        .arg(args::output_arg("The output file"))
+        .arg(
+            args::input_arg("The file to encrypt, or - for stdin")
+                .required(false)
+                .required_unless_present("batch"),
+        )
+        .arg(
+            args::output_arg("The output file, or - for stdout")
+                .required(false)
+                .required_unless_present("batch"),
+        )
         .arg(args::keyfile_arg())
         .arg(args::delete_input_arg(
             "Delete the input file after a successful encrypt",
         ))
         .arg(args::hash_arg())
+        .arg(args::algorithm_arg().requires("hash"))
         .arg(args::autogenerate_arg(
             "Autogenerate a passphrase (default is 7 words)",
             "keyfile",
         ))
         .arg(args::detached_header_output_arg())
+        .arg(args::kdf_profile_arg().conflicts_with("recipient"))
+        .arg(args::recipient_arg())
+        .arg(args::keyslots_arg())
+        .arg(args::header_trailer_arg())
+        .arg(args::parity_arg())
+        .args(args::block_threads_arg())
+        .arg(
+            args::batch_arg("Encrypt each of these files into --out-dir, deriving the key once")
+                .conflicts_with("parity"),
+        )
+        .arg(args::out_dir_arg())
+        .arg(args::suffix_arg(
+            "The suffix --batch appends to each output name [default: .dx]",
+        ))
         .arg(args::force_arg())
 }
 
@@ -25,13 +48,42 @@ pub(in crate::cli) fn decrypt_command() -> Command {
     Command::new("decrypt")
         .short_flag('d')
         .about("Decrypt a file")
-        .arg(args::input_arg("The file to decrypt"))
-        .arg(args::output_arg("The output file"))
+        .arg(
+            args::input_arg("The file to decrypt, or - for stdin")
+                .required(false)
+                .required_unless_present("batch"),
+        )
+        .arg(
+            args::output_arg("The output file, or - for stdout")
+                .required(false)
+                .required_unless_present("batch"),
+        )
         .arg(args::keyfile_arg())
+        .arg(args::identity_arg("keyfile"))
         .arg(args::detached_header_input_arg())
         .arg(args::delete_input_arg(
             "Delete the input file after a successful decrypt",
         ))
         .arg(args::hash_arg())
+        .arg(args::algorithm_arg().requires("hash"))
+        .args(args::block_threads_arg())
+        .arg(args::range_arg())
+        .arg(
+            args::batch_arg("Decrypt each of these files into --out-dir, deriving each key once")
+                .conflicts_with("range"),
+        )
+        .arg(args::out_dir_arg())
+        .arg(args::suffix_arg(
+            "The suffix --batch strips from each output name [default: .dx]",
+        ))
         .arg(args::force_arg())
 }
+
+pub(in crate::cli) fn verify_command() -> Command {
+    Command::new("verify")
+        .about("Authenticate an encrypted file or packed archive without writing any plaintext")
+        .arg(args::input_arg("The file to verify"))
+        .arg(args::keyfile_arg())
+        .arg(args::identity_arg("keyfile"))
+        .arg(args::detached_header_input_arg())
+}
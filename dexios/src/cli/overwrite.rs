@@ -4,10 +4,11 @@ use anyhow::Result;
 use domain::storage::identity::OverwritePolicy;
 
 use crate::cli::prompt::overwrite_check;
-use crate::global::states::ForceMode;
+use crate::global::states::{ForceMode, Key};
 use crate::global::structs::CryptoParams;
 
 const STDIN_KEYFILE_PROMPT_CONFLICT: &str = "--keyfile - cannot be combined with interactive overwrite prompts; pass --force to avoid reading confirmation from stdin";
+const STDIN_INPUT_PROMPT_CONFLICT: &str = "reading input from stdin cannot be combined with interactive overwrite prompts; pass --force to avoid reading confirmation from stdin";
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum ExistingPathProbe {
@@ -75,7 +76,15 @@ pub(crate) fn reject_stdin_keyfile_prompt_conflict(
     params: &CryptoParams,
     prompt_needed: bool,
 ) -> Result<()> {
-    if prompt_needed && params.force == ForceMode::Prompt && params.key.reads_stdin() {
+    reject_stdin_key_prompt_conflict(&params.key, params.force, prompt_needed)
+}
+
+pub(crate) fn reject_stdin_key_prompt_conflict(
+    key: &Key,
+    force: ForceMode,
+    prompt_needed: bool,
+) -> Result<()> {
+    if prompt_needed && force == ForceMode::Prompt && key.reads_stdin() {
         return Err(anyhow::anyhow!(STDIN_KEYFILE_PROMPT_CONFLICT));
     }
     Ok(())
@@ -88,10 +97,20 @@ pub(crate) fn reject_stdin_keyfile_dynamic_prompt_conflict(params: &CryptoParams
     Ok(())
 }
 
+pub(crate) fn reject_stdin_input_prompt_conflict(
+    force: ForceMode,
+    prompt_needed: bool,
+) -> Result<()> {
+    if prompt_needed && force == ForceMode::Prompt {
+        return Err(anyhow::anyhow!(STDIN_INPUT_PROMPT_CONFLICT));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::global::states::{DeleteInput, HashMode, HeaderLocation, Key};
+    use crate::global::states::{DeleteInput, HashMode, HeaderLocation, HeaderTrailer, Key};
     use crate::global::structs::CryptoParams;
 
     fn crypto_params_with_stdin_key(force: ForceMode) -> CryptoParams {
@@ -102,6 +121,11 @@ mod tests {
             hash_mode: HashMode::NoHash,
             header_location: HeaderLocation::Embedded,
             delete_input: DeleteInput::Retain,
+            recipients: Vec::new(),
+            keyslots: None,
+            header_trailer: HeaderTrailer::Omit,
+            parity: None,
+            threads: std::num::NonZeroUsize::MIN,
         }
     }
 
@@ -163,4 +187,13 @@ mod tests {
         let error = reject_stdin_keyfile_prompt_conflict(&params, true).expect_err("conflict");
         assert_eq!(error.to_string(), STDIN_KEYFILE_PROMPT_CONFLICT);
     }
+
+    #[test]
+    fn stdin_input_conflict_only_applies_to_interactive_prompts() {
+        let error =
+            reject_stdin_input_prompt_conflict(ForceMode::Prompt, true).expect_err("conflict");
+        assert_eq!(error.to_string(), STDIN_INPUT_PROMPT_CONFLICT);
+        reject_stdin_input_prompt_conflict(ForceMode::Force, true).expect("forced");
+        reject_stdin_input_prompt_conflict(ForceMode::Prompt, false).expect("no prompt");
+    }
 }
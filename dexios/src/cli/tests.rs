@@ -110,7 +110,8 @@ fn top_level_command_registration_order_is_stable() {
     assert_eq!(
         command_names.as_slice(),
         [
-            "encrypt", "decrypt", "hash", "pack", "unpack", "key", "header"
+            "encrypt", "decrypt", "hash", "pack", "unpack", "list", "diff", "update", "verify",
+            "repair", "key", "keygen", "header"
         ]
     );
 }
@@ -124,6 +125,7 @@ fn shared_arg_factories_are_source_gated() {
         "keyfile_arg_with_help",
         "force_arg",
         "hash_arg",
+        "algorithm_arg",
         "delete_input_arg",
         "delete_source_arg",
         "verbose_arg",
@@ -133,6 +135,22 @@ fn shared_arg_factories_are_source_gated() {
         "keyfile_old_arg",
         "keyfile_new_arg",
         "autogenerate_arg",
+        "recipient_arg",
+        "identity_arg",
+        "kdf_profile_arg",
+        "keyslots_arg",
+        "header_trailer_arg",
+        "parity_arg",
+        "sidecar_arg",
+        "output_format_arg",
+        "batch_arg",
+        "out_dir_arg",
+        "suffix_arg",
+        "threads_arg",
+        "block_threads_arg",
+        "range_arg",
+        "include_arg",
+        "exclude_arg",
         "super::validate_autogenerate_words",
         "conflicts_with(conflict_target)",
     ] {
@@ -143,12 +161,49 @@ fn shared_arg_factories_are_source_gated() {
     }
 }
 
+#[test]
+fn output_format_is_global_and_defaults_to_text() {
+    let matches = parse_ok(["dexios", "list", "archive.enc"]);
+    assert_eq!(
+        matches
+            .get_one::<String>("output-format")
+            .map(String::as_str),
+        Some("text")
+    );
+
+    let matches = parse_ok([
+        "dexios", "header", "details", "--output", "json", "file.enc",
+    ]);
+    assert_eq!(
+        matches
+            .get_one::<String>("output-format")
+            .map(String::as_str),
+        Some("json")
+    );
+    let (_, header) = matches.subcommand().expect("subcommand");
+    let (_, details) = header.subcommand().expect("header subcommand");
+    assert_eq!(
+        details.get_one::<String>("input").map(String::as_str),
+        Some("file.enc")
+    );
+
+    assert_parser_error(
+        ["dexios", "--output", "yaml", "list", "archive.enc"],
+        clap::error::ErrorKind::InvalidValue,
+        "yaml",
+    );
+}
+
 #[test]
 fn top_level_command_builders_are_split_by_family() {
     assert!(CLI_STREAM_COMMANDS_RS.contains("fn encrypt_command() -> Command"));
     assert!(CLI_STREAM_COMMANDS_RS.contains("fn decrypt_command() -> Command"));
     assert!(CLI_ARCHIVE_COMMANDS_RS.contains("fn pack_command() -> Command"));
     assert!(CLI_ARCHIVE_COMMANDS_RS.contains("fn unpack_command() -> Command"));
+    assert!(CLI_ARCHIVE_COMMANDS_RS.contains("fn list_command() -> Command"));
+    assert!(CLI_ARCHIVE_COMMANDS_RS.contains("fn diff_command() -> Command"));
+    assert!(CLI_ARCHIVE_COMMANDS_RS.contains("fn update_command() -> Command"));
+    assert!(CLI_STREAM_COMMANDS_RS.contains("fn verify_command() -> Command"));
     assert!(CLI_HASH_COMMANDS_RS.contains("fn hash_command() -> Command"));
 
     for ordered_call in [
@@ -157,6 +212,10 @@ fn top_level_command_builders_are_split_by_family() {
         "commands::hash::hash_command()",
         "commands::archive::pack_command()",
         "commands::archive::unpack_command()",
+        "commands::archive::list_command()",
+        "commands::archive::diff_command()",
+        "commands::archive::update_command()",
+        "commands::stream::verify_command()",
     ] {
         assert!(
             CLI_RS.contains(ordered_call),
@@ -359,6 +418,92 @@ fn hash_command_accepts_multiple_inputs() {
     assert_eq!(values, ["one.bin", "two.bin"]);
 }
 
+#[test]
+fn encrypt_and_decrypt_threads_follow_the_parallel_feature() {
+    for command in ["encrypt", "decrypt"] {
+        let args = ["dexios", command, "--threads", "4", "input", "output"];
+        if cfg!(feature = "parallel") {
+            let matches = parse_ok(args);
+            let (_, sub) = matches.subcommand().expect("subcommand");
+            assert_eq!(
+                sub.get_one::<String>("threads").map(String::as_str),
+                Some("4")
+            );
+        } else {
+            assert_parser_error(args, clap::error::ErrorKind::UnknownArgument, "--threads");
+        }
+    }
+}
+
+#[test]
+fn hash_command_accepts_directories_and_a_thread_count() {
+    let matches = parse_ok(["dexios", "hash", "--threads", "4", "backups/", "one.bin"]);
+
+    let (_, hash) = matches.subcommand().expect("hash subcommand");
+    assert_eq!(
+        hash.get_one::<String>("threads").map(String::as_str),
+        Some("4")
+    );
+    assert_parser_error(
+        ["dexios", "hash", "--threads", "0", "backups/"],
+        clap::error::ErrorKind::ValueValidation,
+        "thread count must be between 1 and 256",
+    );
+}
+
+#[test]
+fn hash_algorithm_is_selectable_and_requires_hash_on_workflows() {
+    let matches = parse_ok([
+        "dexios",
+        "hash",
+        "--algorithm",
+        "sha3-256",
+        "--coreutils",
+        "a",
+    ]);
+    let (_, hash) = matches.subcommand().expect("hash subcommand");
+    assert_eq!(
+        hash.get_one::<String>("algorithm").map(String::as_str),
+        Some("sha3-256")
+    );
+    assert!(hash.get_flag("coreutils"));
+
+    let matches = parse_ok(["dexios", "hash", "-c", "SHA256SUMS"]);
+    let (_, hash) = matches.subcommand().expect("hash subcommand");
+    assert!(hash.get_flag("check"));
+    assert_eq!(hash.get_one::<String>("algorithm"), None);
+
+    for args in [
+        &["dexios", "hash", "--algorithm", "md5", "a"][..],
+        &["dexios", "hash", "--check", "--coreutils", "a"][..],
+        &["dexios", "encrypt", "--algorithm", "sha256", "in", "out"][..],
+        &["dexios", "pack", "--algorithm", "sha256", "dir", "out"][..],
+    ] {
+        assert!(
+            super::build_cli().try_get_matches_from(args).is_err(),
+            "{args:?} should be rejected"
+        );
+    }
+
+    for subcommand in ["encrypt", "decrypt", "pack", "unpack"] {
+        let matches = parse_ok([
+            "dexios",
+            subcommand,
+            "-H",
+            "--algorithm",
+            "sha512",
+            "in",
+            "out",
+        ]);
+        let (_, sub) = matches.subcommand().expect("subcommand");
+        assert_eq!(
+            sub.get_one::<String>("algorithm").map(String::as_str),
+            Some("sha512"),
+            "{subcommand}"
+        );
+    }
+}
+
 #[test]
 fn pack_command_accepts_multiple_paths_without_compression_selector() {
     let matches = parse_ok(["dexios", "pack", "dir-a", "dir-b", "archive.dex"]);
@@ -427,6 +572,118 @@ fn removed_zstd_flag_is_rejected_for_pack() {
     );
 }
 
+#[test]
+fn pack_compress_defaults_to_level_three_and_accepts_explicit_levels() {
+    let matches = parse_ok(["dexios", "pack", "--compress", "dir-a", "archive.dex"]);
+    let (_, sub) = matches.subcommand().expect("subcommand");
+    assert_eq!(
+        sub.get_one::<String>("compress").map(String::as_str),
+        Some("3")
+    );
+
+    let matches = parse_ok(["dexios", "pack", "--compress=19", "dir-a", "archive.dex"]);
+    let (_, sub) = matches.subcommand().expect("subcommand");
+    assert_eq!(
+        sub.get_one::<String>("compress").map(String::as_str),
+        Some("19")
+    );
+
+    let matches = parse_ok(["dexios", "pack", "dir-a", "archive.dex"]);
+    let (_, sub) = matches.subcommand().expect("subcommand");
+    assert_eq!(sub.get_one::<String>("compress"), None);
+}
+
+#[test]
+fn pack_entry_digests_is_an_opt_in_flag() {
+    let matches = parse_ok(["dexios", "pack", "--entry-digests", "dir-a", "archive.dex"]);
+    let (_, sub) = matches.subcommand().expect("subcommand");
+    assert!(sub.get_flag("entry-digests"));
+
+    let matches = parse_ok(["dexios", "pack", "dir-a", "archive.dex"]);
+    let (_, sub) = matches.subcommand().expect("subcommand");
+    assert!(!sub.get_flag("entry-digests"));
+}
+
+#[test]
+fn pack_compress_rejects_out_of_range_levels() {
+    for compress in ["--compress=0", "--compress=20", "--compress=fast"] {
+        let error = super::build_cli()
+            .try_get_matches_from(["dexios", "pack", compress, "dir-a", "archive.dex"])
+            .expect_err("invalid compression level should be rejected");
+        assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+    }
+}
+
+#[test]
+fn update_accepts_repeated_additions_and_removals() {
+    let matches = parse_ok([
+        "dexios",
+        "update",
+        "--add",
+        "notes.txt",
+        "--add",
+        "photos",
+        "--remove",
+        "old/report.pdf",
+        "--keyfile",
+        "first.key",
+        "--keyfile",
+        "second.key",
+        "--recipient",
+        "dexios-pk-a",
+        "--recipient",
+        "dexios-pk-b",
+        "--drop-locked-keys",
+        "archive.dex",
+        "updated.dex",
+    ]);
+
+    let (name, update) = matches.subcommand().expect("update subcommand");
+    assert_eq!(name, "update");
+    assert_eq!(
+        update
+            .get_many::<String>("add")
+            .expect("additions")
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        ["notes.txt", "photos"]
+    );
+    assert_eq!(
+        update.get_one::<String>("remove").map(String::as_str),
+        Some("old/report.pdf")
+    );
+    assert_eq!(
+        update
+            .get_many::<String>("keyfile")
+            .expect("keyfiles")
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        ["first.key", "second.key"]
+    );
+    assert_eq!(
+        update
+            .get_many::<String>("recipient")
+            .expect("recipients")
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        ["dexios-pk-a", "dexios-pk-b"]
+    );
+    assert!(update.get_flag("drop-locked-keys"));
+    assert_eq!(
+        update.get_one::<String>("output").map(String::as_str),
+        Some("updated.dex")
+    );
+}
+
+#[test]
+fn update_requires_an_addition_or_removal() {
+    assert_parser_error(
+        ["dexios", "update", "archive.dex", "updated.dex"],
+        clap::error::ErrorKind::MissingRequiredArgument,
+        "--add <path>",
+    );
+}
+
 #[test]
 fn key_add_command_accepts_old_and_new_keyfiles() {
     let matches = parse_ok([
@@ -506,6 +763,264 @@ fn key_change_command_accepts_old_and_new_keyfiles() {
     );
 }
 
+#[test]
+fn key_rotate_command_accepts_repeated_credentials() {
+    let matches = parse_ok([
+        "dexios",
+        "key",
+        "rotate",
+        "-k",
+        "a.key",
+        "--keyfile",
+        "b.key",
+        "--identity",
+        "alice.key",
+        "--passphrases",
+        "2",
+        "-f",
+        "cipher.enc",
+    ]);
+    let (_, sub) = matches.subcommand().expect("subcommand");
+    let rotate = sub.subcommand_matches("rotate").expect("key rotate");
+
+    assert_eq!(
+        rotate
+            .get_many::<String>("keyfile")
+            .unwrap()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        ["a.key", "b.key"]
+    );
+    assert_eq!(
+        rotate.get_one::<String>("identity").map(String::as_str),
+        Some("alice.key")
+    );
+    assert_eq!(rotate.get_one::<u8>("passphrases"), Some(&2));
+    assert!(rotate.get_flag("force"));
+    assert!(
+        super::build_cli()
+            .try_get_matches_from(["dexios", "key", "rotate", "--passphrases", "0", "x.enc"])
+            .is_err()
+    );
+}
+
+#[test]
+fn encrypt_and_pack_reserve_a_keyslot_count() {
+    for subcommand in ["encrypt", "pack"] {
+        let matches = parse_ok(["dexios", subcommand, "--keyslots", "12", "in", "out.enc"]);
+        let (_, sub) = matches.subcommand().expect("subcommand");
+
+        assert_eq!(
+            sub.get_one::<String>("keyslots").map(String::as_str),
+            Some("12")
+        );
+        for rejected in ["3", "33"] {
+            assert_parser_error(
+                [
+                    "dexios",
+                    subcommand,
+                    "--keyslots",
+                    rejected,
+                    "in",
+                    "out.enc",
+                ],
+                clap::error::ErrorKind::ValueValidation,
+                "keyslot count must be between 4 and 32",
+            );
+        }
+    }
+}
+
+#[test]
+fn encrypt_and_pack_append_a_header_trailer_unless_detached() {
+    for subcommand in ["encrypt", "pack"] {
+        let matches = parse_ok(["dexios", subcommand, "--header-trailer", "in", "out.enc"]);
+        let (_, sub) = matches.subcommand().expect("subcommand");
+        assert!(sub.get_flag("header-trailer"));
+
+        assert_parser_error(
+            [
+                "dexios",
+                subcommand,
+                "--header-trailer",
+                "--header",
+                "out.hdr",
+                "in",
+                "out.enc",
+            ],
+            clap::error::ErrorKind::ArgumentConflict,
+            "--header-trailer",
+        );
+    }
+}
+
+#[test]
+fn encrypt_and_pack_accept_a_parity_block_count() {
+    for subcommand in ["encrypt", "pack"] {
+        let matches = parse_ok(["dexios", subcommand, "--parity", "4", "in", "out.enc"]);
+        let (_, sub) = matches.subcommand().expect("subcommand");
+        assert_eq!(
+            sub.get_one::<String>("parity").map(String::as_str),
+            Some("4")
+        );
+
+        for rejected in ["0", "33", "many"] {
+            assert_parser_error(
+                ["dexios", subcommand, "--parity", rejected, "in", "out.enc"],
+                clap::error::ErrorKind::ValueValidation,
+                "parity block count must be",
+            );
+        }
+    }
+}
+
+#[test]
+fn encrypt_and_decrypt_take_a_batch_instead_of_a_file_pair() {
+    for subcommand in ["encrypt", "decrypt"] {
+        let matches = parse_ok([
+            "dexios",
+            subcommand,
+            "--batch",
+            "a",
+            "b",
+            "c",
+            "--out-dir",
+            "out",
+        ]);
+        let (_, sub) = matches.subcommand().expect("subcommand");
+        assert_eq!(
+            sub.get_many::<String>("batch")
+                .expect("batch inputs")
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+        assert_eq!(sub.get_one::<String>("suffix"), None);
+
+        assert_parser_error(
+            ["dexios", subcommand, "--batch", "a", "b"],
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "--out-dir",
+        );
+        assert_parser_error(
+            ["dexios", subcommand, "--out-dir", "out", "in", "out.enc"],
+            clap::error::ErrorKind::ArgumentConflict,
+            "--out-dir",
+        );
+        assert_parser_error(
+            [
+                "dexios",
+                subcommand,
+                "in",
+                "--batch",
+                "a",
+                "--out-dir",
+                "out",
+            ],
+            clap::error::ErrorKind::ArgumentConflict,
+            "--batch",
+        );
+    }
+
+    assert_parser_error(
+        [
+            "dexios",
+            "encrypt",
+            "--parity",
+            "4",
+            "--batch",
+            "a",
+            "--out-dir",
+            "out",
+        ],
+        clap::error::ErrorKind::ArgumentConflict,
+        "--parity",
+    );
+}
+
+#[test]
+fn repair_command_accepts_input_and_sidecar() {
+    let matches = parse_ok([
+        "dexios",
+        "repair",
+        "--force",
+        "--sidecar",
+        "backup.dxpar",
+        "cipher.enc",
+    ]);
+
+    let (name, repair) = matches.subcommand().expect("subcommand");
+    assert_eq!(name, "repair");
+    assert_eq!(
+        repair.get_one::<String>("input").map(String::as_str),
+        Some("cipher.enc")
+    );
+    assert_eq!(
+        repair.get_one::<String>("sidecar").map(String::as_str),
+        Some("backup.dxpar")
+    );
+    assert!(repair.get_flag("force"));
+}
+
+#[test]
+fn key_add_change_and_del_accept_a_label() {
+    for subcommand in ["add", "change", "del"] {
+        let matches = parse_ok(["dexios", "key", subcommand, "--label", "alice", "x.enc"]);
+        let (_, sub) = matches.subcommand().expect("subcommand");
+        let key = sub.subcommand_matches(subcommand).expect("key subcommand");
+
+        assert_eq!(
+            key.get_one::<String>("label").map(String::as_str),
+            Some("alice")
+        );
+    }
+}
+
+#[test]
+fn key_del_targets_a_slot_authorized_by_another_keyfile() {
+    let matches = parse_ok([
+        "dexios",
+        "key",
+        "del",
+        "--slot",
+        "2",
+        "--authorize-with",
+        "admin.key",
+        "cipher.enc",
+    ]);
+    let (_, sub) = matches.subcommand().expect("subcommand");
+    let del = sub.subcommand_matches("del").expect("key del");
+
+    assert_eq!(del.get_one::<usize>("slot"), Some(&2));
+    assert_eq!(
+        del.get_one::<String>("authorize-with").map(String::as_str),
+        Some("admin.key")
+    );
+    for args in [
+        &["dexios", "key", "del", "--authorize-with", "a.key", "x.enc"][..],
+        &[
+            "dexios", "key", "del", "--slot", "1", "--label", "bob", "x.enc",
+        ],
+        &[
+            "dexios",
+            "key",
+            "del",
+            "--slot",
+            "1",
+            "--authorize-with",
+            "a.key",
+            "-k",
+            "b.key",
+            "x.enc",
+        ],
+    ] {
+        assert!(
+            super::build_cli().try_get_matches_from(args).is_err(),
+            "{args:?}"
+        );
+    }
+}
+
 #[test]
 fn key_change_accepts_force_flag() {
     let matches = parse_ok(["dexios", "key", "change", "--force", "cipher.enc"]);
@@ -678,6 +1193,20 @@ fn header_restore_command_accepts_input_and_output() {
     assert!(restore.get_flag("force"));
 }
 
+#[test]
+fn header_repair_command_accepts_input() {
+    let matches = parse_ok(["dexios", "header", "repair", "--force", "cipher.enc"]);
+
+    let (name, sub) = matches.subcommand().expect("subcommand");
+    assert_eq!(name, "header");
+    let repair = sub.subcommand_matches("repair").expect("header repair");
+    assert_eq!(
+        repair.get_one::<String>("input").map(String::as_str),
+        Some("cipher.enc")
+    );
+    assert!(repair.get_flag("force"));
+}
+
 #[test]
 fn header_strip_command_accepts_input_and_required_header() {
     let matches = parse_ok([
@@ -1021,3 +1550,40 @@ fn assert_missing_command_shape(error: clap::Error) {
         "unexpected error kind: {kind} ({error})"
     );
 }
+
+#[test]
+fn verify_takes_an_input_and_no_output() {
+    let matches = parse_ok(["dexios", "verify", "--header", "archive.hdr", "archive.dex"]);
+
+    let (name, verify) = matches.subcommand().expect("verify subcommand");
+    assert_eq!(name, "verify");
+    assert_eq!(
+        verify.get_one::<String>("input").map(String::as_str),
+        Some("archive.dex")
+    );
+    assert_eq!(
+        verify.get_one::<String>("header").map(String::as_str),
+        Some("archive.hdr")
+    );
+    let error = super::build_cli()
+        .try_get_matches_from(["dexios", "verify", "archive.dex", "plain.out"])
+        .expect_err("verify should not take an output path");
+    assert_eq!(error.kind(), clap::error::ErrorKind::UnknownArgument);
+}
+
+#[test]
+fn diff_takes_an_archive_and_a_directory() {
+    let matches = parse_ok(["dexios", "diff", "--json", "archive.dex", "restore"]);
+
+    let (name, diff) = matches.subcommand().expect("diff subcommand");
+    assert_eq!(name, "diff");
+    assert_eq!(
+        diff.get_one::<String>("input").map(String::as_str),
+        Some("archive.dex")
+    );
+    assert_eq!(
+        diff.get_one::<String>("directory").map(String::as_str),
+        Some("restore")
+    );
+    assert!(diff.get_flag("json"));
+}
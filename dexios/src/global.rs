@@ -1,3 +1,4 @@
+pub(crate) mod output;
 pub(crate) mod parameters;
 pub(crate) mod states;
 pub(crate) mod structs;
@@ -5,21 +6,21 @@ pub(crate) mod structs;
 #[macro_export]
 macro_rules! info {
     ($($arg:tt)*) => {
-        println!("[i] {}", format!($($arg)*))
+        $crate::global::output::human_line(format_args!("[i] {}", format!($($arg)*)))
     }
 }
 
 #[macro_export]
 macro_rules! error {
     ($($arg:tt)*) => {
-        println!("[!] {}", format!($($arg)*))
+        $crate::global::output::human_line(format_args!("[!] {}", format!($($arg)*)))
     }
 }
 
 #[macro_export]
 macro_rules! success {
     ($($arg:tt)*) => {
-        println!("[+] {}", format!($($arg)*))
+        $crate::global::output::human_line(format_args!("[+] {}", format!($($arg)*)))
     }
 }
 
@@ -33,7 +34,7 @@ macro_rules! warn {
 #[macro_export]
 macro_rules! question {
     ($($arg:tt)*) => {
-        print!("[?] {}", format!($($arg)*));
+        $crate::global::output::human_prompt(format_args!("[?] {}", format!($($arg)*)));
 
     }
 }
@@ -0,0 +1,273 @@
+use std::fmt::{self, Write as _};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+use clap::ArgMatches;
+use domain::hasher::HashAlgorithm;
+use domain::storage::cleanup::CleanupResult;
+use domain::storage::identity::PathRole;
+use domain::storage::transaction::CommitReceipt;
+use domain::workflow_error::WorkflowErrorClass;
+
+use super::states::OutputFormat;
+use crate::subcommands::error_class;
+
+// `--output json` prints exactly one document on stdout once the command has
+// finished, so everything a subcommand would otherwise print is collected here
+// and the human-readable lines move to stderr.
+
+/// Bumped when a field is renamed or removed or its meaning changes. New fields
+/// may be added without a bump, so consumers should ignore fields they do not know.
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static REPORT: Mutex<Report> = Mutex::new(Report::new());
+
+struct Report {
+    command: String,
+    artifacts: Vec<String>,
+    hashes: Vec<String>,
+    cleanup: Option<Cleanup>,
+    fields: Vec<(&'static str, String)>,
+}
+
+// a `--batch` run cleans up after every file, so results accumulate
+#[derive(Default)]
+struct Cleanup {
+    deleted: Vec<String>,
+    failures: Vec<String>,
+}
+
+impl Report {
+    const fn new() -> Self {
+        Self {
+            command: String::new(),
+            artifacts: Vec::new(),
+            hashes: Vec::new(),
+            cleanup: None,
+            fields: Vec::new(),
+        }
+    }
+}
+
+fn report() -> MutexGuard<'static, Report> {
+    REPORT.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// this is called once from main.rs, before any subcommand runs
+pub(crate) fn init(matches: &ArgMatches) {
+    let format = match matches
+        .get_one::<String>("output-format")
+        .map(String::as_str)
+    {
+        Some("json") => OutputFormat::Json,
+        _ => OutputFormat::Text,
+    };
+    let _ = FORMAT.set(format);
+
+    let mut command = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub_matches)) = current.subcommand() {
+        command.push(name);
+        current = sub_matches;
+    }
+    report().command = command.join(" ");
+}
+
+pub(crate) fn is_json() -> bool {
+    FORMAT.get() == Some(&OutputFormat::Json)
+}
+
+// used by the `info!`, `error!` and `success!` macros
+pub(crate) fn human_line(line: fmt::Arguments<'_>) {
+    if is_json() {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
+// used by the `question!` macro; the answer is still read from stdin
+pub(crate) fn human_prompt(prompt: fmt::Arguments<'_>) {
+    if is_json() {
+        eprint!("{prompt}");
+    } else {
+        print!("{prompt}");
+    }
+}
+
+pub(crate) fn record_commit(receipt: &CommitReceipt) {
+    if !is_json() {
+        return;
+    }
+
+    let artifacts = receipt.committed_artifacts().iter().map(|artifact| {
+        format!(
+            r#"{{"role":"{}","path":{}}}"#,
+            artifact_role(artifact.role()),
+            json_string(&artifact.path().to_string_lossy())
+        )
+    });
+    report().artifacts.extend(artifacts);
+}
+
+pub(crate) fn record_hash(path: &str, algorithm: HashAlgorithm, digest: &str) {
+    if !is_json() {
+        return;
+    }
+
+    report().hashes.push(format!(
+        r#"{{"path":{},"algorithm":"{}","digest":"{digest}"}}"#,
+        json_string(path),
+        algorithm.name()
+    ));
+}
+
+pub(crate) fn record_cleanup(result: &CleanupResult) {
+    if !is_json() {
+        return;
+    }
+
+    let deleted = result
+        .deleted
+        .iter()
+        .map(|target| json_string(&target.path().to_string_lossy()));
+    let failures = result.failures.iter().map(|failure| {
+        format!(
+            r#"{{"path":{},"error":{}}}"#,
+            json_string(&failure.target.path().to_string_lossy()),
+            json_string(&failure.error.to_string())
+        )
+    });
+    let mut report = report();
+    let cleanup = report.cleanup.get_or_insert_default();
+    cleanup.deleted.extend(deleted);
+    cleanup.failures.extend(failures);
+    drop(report);
+}
+
+/// Adds a command-specific field; `value` must already be valid JSON.
+pub(crate) fn record(field: &'static str, value: String) {
+    if !is_json() {
+        return;
+    }
+
+    report().fields.push((field, value));
+}
+
+// this is called once from main.rs, after the subcommand has returned
+pub(crate) fn finish(result: &anyhow::Result<()>) {
+    if !is_json() {
+        return;
+    }
+
+    let failure = result
+        .as_ref()
+        .err()
+        .map(|error| (error_class(error), error.to_string()));
+    let outcome = match &failure {
+        Some((class, message)) => Err((*class, message.as_str())),
+        None => Ok(()),
+    };
+    println!("{}", document(&report(), outcome));
+}
+
+fn document(report: &Report, outcome: Result<(), (WorkflowErrorClass, &str)>) -> String {
+    let status = if outcome.is_ok() { "ok" } else { "error" };
+    let mut json = format!(
+        r#"{{"schema_version":{SCHEMA_VERSION},"command":{},"status":"{status}","artifacts":[{}],"hashes":[{}]"#,
+        json_string(&report.command),
+        report.artifacts.join(","),
+        report.hashes.join(",")
+    );
+    if let Some(cleanup) = &report.cleanup {
+        let _ = write!(
+            json,
+            r#","cleanup":{{"deleted":[{}],"failures":[{}]}}"#,
+            cleanup.deleted.join(","),
+            cleanup.failures.join(",")
+        );
+    }
+    for (field, value) in &report.fields {
+        let _ = write!(json, r#","{field}":{value}"#);
+    }
+    if let Err((class, message)) = outcome {
+        let _ = write!(
+            json,
+            r#","error":{{"class":"{}","message":{}}}"#,
+            class.as_str(),
+            json_string(message)
+        );
+    }
+    json.push('}');
+    json
+}
+
+// stable kebab-case names, independent of the labels used in error messages
+const fn artifact_role(role: PathRole) -> &'static str {
+    match role {
+        PathRole::Output | PathRole::GeneratedOutput => "payload",
+        PathRole::DetachedHeader | PathRole::GeneratedDetachedHeader => "header",
+        PathRole::ProcessedSource => "source",
+        PathRole::Input => "input",
+        PathRole::UnpackRoot => "unpack-root",
+        PathRole::MutationTarget => "target",
+        PathRole::CleanupTarget => "cleanup-target",
+        PathRole::ParitySidecar | PathRole::GeneratedParitySidecar => "parity-sidecar",
+    }
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len().saturating_add(2));
+    quoted.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            control if control.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", u32::from(control));
+            }
+            other => quoted.push(other),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn success_documents_carry_the_schema_version_and_recorded_fields() {
+        let mut report = Report::new();
+        report.command = String::from("keygen");
+        report
+            .artifacts
+            .push(String::from(r#"{"role":"payload","path":"id.key"}"#));
+        report
+            .fields
+            .push(("public_key", json_string("dexios1pub")));
+
+        assert_eq!(
+            document(&report, Ok(())),
+            r#"{"schema_version":1,"command":"keygen","status":"ok","artifacts":[{"role":"payload","path":"id.key"}],"hashes":[],"public_key":"dexios1pub"}"#
+        );
+    }
+
+    #[test]
+    fn error_documents_name_the_workflow_class() {
+        let mut report = Report::new();
+        report.command = String::from("header details");
+
+        assert_eq!(
+            document(
+                &report,
+                Err((WorkflowErrorClass::MalformedFormat, "Malformed \"header\""))
+            ),
+            r#"{"schema_version":1,"command":"header details","status":"error","artifacts":[],"hashes":[],"error":{"class":"malformed-format","message":"Malformed \"header\""}}"#
+        );
+    }
+}
@@ -1,12 +1,18 @@
 use crate::global::states::{DeleteInput, DeleteSource, ForceMode, HashMode, HeaderLocation};
-use crate::global::structs::CryptoParams;
 use crate::global::structs::PackParams;
+use crate::global::structs::{BatchParams, CryptoParams};
 use anyhow::{Result, anyhow};
 use clap::ArgMatches;
 use clap::parser::MatchesError;
 use core::kdf::Kdf;
-
-use super::states::{DirectoryMode, Key, KeyParams, PrintMode};
+use core::payload::BodyCompression;
+use core::recipient::RecipientPublicKey;
+use domain::archive::{ArchivePattern, ArchiveSelection, MetadataOptions};
+use domain::decrypt::PlaintextRange;
+use domain::hasher::HashAlgorithm;
+use std::num::NonZeroUsize;
+
+use super::states::{DirectoryMode, EntryDigests, HeaderTrailer, Key, KeyParams, PrintMode};
 use super::structs::KeyManipulationParams;
 
 pub(crate) fn get_params(name: &str, sub_matches: &ArgMatches) -> Result<Vec<String>> {
@@ -63,14 +69,25 @@ pub(crate) fn get_optional_param<'a>(
     }
 }
 
+pub(crate) fn get_optional_params(name: &str, sub_matches: &ArgMatches) -> Result<Vec<String>> {
+    match sub_matches.try_get_many::<String>(name) {
+        Ok(values) => Ok(values.into_iter().flatten().cloned().collect()),
+        // Same reasoning as `get_optional_param`: an undefined optional argument is absent.
+        Err(MatchesError::UnknownArgument { .. }) => Ok(Vec::new()),
+        Err(_) => Err(anyhow!(
+            "internal CLI adapter error: optional repeated argument '{name}' unreadable after clap validation"
+        )),
+    }
+}
+
 // `delete_input` is taken as a parameter instead of being read from the matches:
 // not every subcommand routed through here defines the `delete-input` flag
 // (pack governs source removal via `delete-source` on `PackParams` instead).
 fn crypto_params(sub_matches: &ArgMatches, delete_input: DeleteInput) -> Result<CryptoParams> {
-    let key = Key::init(sub_matches, &KeyParams::default(), "keyfile")?;
+    let key = existing_key(sub_matches, &KeyParams::default(), "keyfile")?;
 
     let hash_mode = if sub_matches.get_flag("hash") {
-        HashMode::CalculateHash
+        HashMode::CalculateHash(hash_algorithm(sub_matches)?)
     } else {
         HashMode::NoHash
     };
@@ -82,7 +99,37 @@ fn crypto_params(sub_matches: &ArgMatches, delete_input: DeleteInput) -> Result<
         None => HeaderLocation::Embedded,
     };
 
-    let kdf = kdf(sub_matches);
+    let kdf = kdf(sub_matches)?;
+
+    let recipients = recipients(sub_matches)?;
+
+    let keyslots = get_optional_param("keyslots", sub_matches)?
+        .map(|keyslots| {
+            keyslots
+                .parse()
+                .map_err(|_| anyhow!("Invalid keyslot count: {keyslots}"))
+        })
+        .transpose()?;
+
+    // only encrypt and pack define `header-trailer`; elsewhere it reads as unset
+    let header_trailer = if matches!(
+        sub_matches.try_get_one::<bool>("header-trailer"),
+        Ok(Some(true))
+    ) {
+        HeaderTrailer::Append
+    } else {
+        HeaderTrailer::Omit
+    };
+
+    let parity = get_optional_param("parity", sub_matches)?
+        .map(|parity| {
+            parity
+                .parse()
+                .map_err(|_| anyhow!("Invalid parity block count: {parity}"))
+        })
+        .transpose()?;
+
+    let threads = threads(sub_matches)?;
 
     Ok(CryptoParams {
         hash_mode,
@@ -91,9 +138,24 @@ fn crypto_params(sub_matches: &ArgMatches, delete_input: DeleteInput) -> Result<
         key,
         header_location,
         kdf,
+        recipients,
+        keyslots,
+        header_trailer,
+        parity,
+        threads,
     })
 }
 
+pub(crate) fn recipients(sub_matches: &ArgMatches) -> Result<Vec<RecipientPublicKey>> {
+    get_optional_params("recipient", sub_matches)?
+        .iter()
+        .map(|recipient| {
+            RecipientPublicKey::parse(recipient)
+                .map_err(|_| anyhow!("Invalid recipient public key: {recipient}"))
+        })
+        .collect()
+}
+
 pub(crate) fn parameter_handler(sub_matches: &ArgMatches) -> Result<CryptoParams> {
     let delete_input = if sub_matches.get_flag("delete-input") {
         DeleteInput::Delete
@@ -104,8 +166,97 @@ pub(crate) fn parameter_handler(sub_matches: &ArgMatches) -> Result<CryptoParams
     crypto_params(sub_matches, delete_input)
 }
 
-pub(crate) fn kdf(_sub_matches: &ArgMatches) -> Kdf {
-    Kdf::Argon2id
+pub(crate) fn kdf(sub_matches: &ArgMatches) -> Result<Kdf> {
+    match get_optional_param("kdf-profile", sub_matches)? {
+        Some(profile) => {
+            Kdf::from_profile_name(profile).ok_or_else(|| anyhow!("Unknown KDF profile: {profile}"))
+        }
+        None => Ok(Kdf::Argon2id),
+    }
+}
+
+fn threads(sub_matches: &ArgMatches) -> Result<NonZeroUsize> {
+    match get_optional_param("threads", sub_matches)? {
+        Some(threads) => threads
+            .parse()
+            .map_err(|_| anyhow!("Invalid thread count: {threads}")),
+        None => Ok(NonZeroUsize::MIN),
+    }
+}
+
+// unlike encrypt/decrypt, hashing defaults to one worker per CPU
+pub(crate) fn hash_threads(sub_matches: &ArgMatches) -> Result<NonZeroUsize> {
+    if get_optional_param("threads", sub_matches)?.is_some() {
+        return threads(sub_matches);
+    }
+    Ok(std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN))
+}
+
+const DEFAULT_BATCH_SUFFIX: &str = ".dx";
+
+pub(crate) fn batch_params(sub_matches: &ArgMatches) -> Result<Option<BatchParams>> {
+    let inputs = get_optional_params("batch", sub_matches)?;
+    if inputs.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(BatchParams {
+        inputs,
+        out_dir: get_param("out-dir", sub_matches)?,
+        suffix: get_optional_param("suffix", sub_matches)?
+            .unwrap_or(DEFAULT_BATCH_SUFFIX)
+            .to_owned(),
+    }))
+}
+
+pub(crate) fn decrypt_range(sub_matches: &ArgMatches) -> Result<Option<PlaintextRange>> {
+    let Some(range) = get_optional_param("range", sub_matches)? else {
+        return Ok(None);
+    };
+    let parsed = range
+        .split_once(':')
+        .and_then(|(start, len)| Some((start.parse().ok()?, len.parse().ok()?)));
+    let (start, len) = parsed.ok_or_else(|| anyhow!("Invalid range: {range}"))?;
+    Ok(Some(PlaintextRange { start, len }))
+}
+
+pub(crate) fn archive_selection(sub_matches: &ArgMatches) -> Result<ArchiveSelection> {
+    let patterns = |name| {
+        get_optional_params(name, sub_matches)?
+            .iter()
+            .map(|pattern| {
+                ArchivePattern::new(pattern).map_err(|_| anyhow!("Invalid pattern: {pattern}"))
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    Ok(ArchiveSelection::new(
+        patterns("include")?,
+        patterns("exclude")?,
+    ))
+}
+
+// Unpack restores whatever the archive recorded unless told otherwise;
+// ownership only with `--same-owner`.
+pub(crate) fn unpack_metadata_options(sub_matches: &ArgMatches) -> MetadataOptions {
+    MetadataOptions {
+        mode: !sub_matches.get_flag("no-permissions"),
+        times: !sub_matches.get_flag("no-times"),
+        owner: sub_matches.get_flag("same-owner"),
+        xattrs: !sub_matches.get_flag("no-xattrs"),
+    }
+}
+
+// Packing records everything but ownership unless told otherwise; `update`
+// applies the same flags to the entries it adds.
+pub(crate) fn pack_metadata_options(sub_matches: &ArgMatches) -> MetadataOptions {
+    if sub_matches.get_flag("no-metadata") {
+        MetadataOptions::none()
+    } else {
+        MetadataOptions {
+            owner: sub_matches.get_flag("owner"),
+            ..MetadataOptions::defaults()
+        }
+    }
 }
 
 pub(crate) fn pack_params(sub_matches: &ArgMatches) -> Result<(CryptoParams, PackParams)> {
@@ -129,15 +280,42 @@ pub(crate) fn pack_params(sub_matches: &ArgMatches) -> Result<(CryptoParams, Pac
         DeleteSource::Retain
     };
 
+    let metadata = pack_metadata_options(sub_matches);
+
+    let compression = get_optional_param("compress", sub_matches)?
+        .map(|level| {
+            level
+                .parse()
+                .ok()
+                .and_then(|level| BodyCompression::zstd(level).ok())
+                .ok_or_else(|| anyhow!("Invalid compression level: {level}"))
+        })
+        .transpose()?;
+
+    let entry_digests = if sub_matches.get_flag("entry-digests") {
+        EntryDigests::Record
+    } else {
+        EntryDigests::Omit
+    };
+
     let pack_params = PackParams {
         dir_mode,
         print_mode,
         delete_source,
+        metadata,
+        compression,
+        entry_digests,
     };
 
     Ok((crypto_params, pack_params))
 }
 
+pub(crate) fn hash_algorithm(sub_matches: &ArgMatches) -> Result<HashAlgorithm> {
+    get_optional_param("algorithm", sub_matches)?.map_or(Ok(HashAlgorithm::default()), |name| {
+        HashAlgorithm::from_name(name).ok_or_else(|| anyhow!("Unknown hash algorithm: {name}"))
+    })
+}
+
 pub(crate) fn forcemode(sub_matches: &ArgMatches) -> ForceMode {
     if sub_matches.get_flag("force") {
         ForceMode::Force
@@ -146,8 +324,46 @@ pub(crate) fn forcemode(sub_matches: &ArgMatches) -> ForceMode {
     }
 }
 
+// The key that proves access to existing keyslots: `--identity` when given,
+// otherwise whatever `Key::init` selects. New keys never come from an identity.
+pub(crate) fn existing_key(
+    sub_matches: &ArgMatches,
+    params: &KeyParams,
+    keyfile_name: &str,
+) -> Result<Key> {
+    match get_optional_param("identity", sub_matches)? {
+        Some(identity) => Ok(Key::Identity(identity.to_owned())),
+        None => Key::init(sub_matches, params, keyfile_name),
+    }
+}
+
+// One key per keyslot for `key rotate` and `update`: prompted passphrases
+// first, then keyfiles, then identities. With no keyfile or identity, one passphrase is
+// prompted for.
+pub(crate) fn keyslot_keys(sub_matches: &ArgMatches) -> Result<Vec<Key>> {
+    let keyfiles = get_optional_params("keyfile", sub_matches)?;
+    let identities = get_optional_params("identity", sub_matches)?;
+    let passphrases = match sub_matches.get_one::<u8>("passphrases") {
+        Some(count) => usize::from(*count),
+        None if keyfiles.is_empty() && identities.is_empty() => 1,
+        None => 0,
+    };
+
+    let keys: Vec<Key> = (0..passphrases)
+        .map(|_| Key::User)
+        .chain(keyfiles.into_iter().map(Key::Keyfile))
+        .chain(identities.into_iter().map(Key::Identity))
+        .collect();
+    if keys.iter().filter(|key| key.reads_stdin()).count() > 1 {
+        return Err(anyhow!(
+            "only one --keyfile or --identity can read from stdin; pass the others through files"
+        ));
+    }
+    Ok(keys)
+}
+
 pub(crate) fn key_manipulation_params(sub_matches: &ArgMatches) -> Result<KeyManipulationParams> {
-    let key_old = Key::init(
+    let key_old = existing_key(
         sub_matches,
         &KeyParams {
             user: true,
@@ -167,12 +383,14 @@ pub(crate) fn key_manipulation_params(sub_matches: &ArgMatches) -> Result<KeyMan
         "keyfile-new",
     )?;
 
-    let kdf = kdf(sub_matches);
+    let kdf = kdf(sub_matches)?;
+    let label = get_optional_param("label", sub_matches)?.map(ToString::to_string);
 
     Ok(KeyManipulationParams {
         key_old,
         key_new,
         kdf,
+        label,
         force: ForceMode::Prompt,
     })
 }
@@ -337,6 +555,98 @@ mod tests {
         assert_eq!(params.kdf, Kdf::Argon2id);
     }
 
+    #[test]
+    fn kdf_profile_selects_the_named_argon2id_profile() {
+        for (args, expected) in [
+            (
+                &[
+                    "dexios",
+                    "encrypt",
+                    "-k",
+                    "keyfile",
+                    "--kdf-profile",
+                    "interactive",
+                    "in",
+                    "out",
+                ][..],
+                Kdf::Argon2idInteractive,
+            ),
+            (
+                &[
+                    "dexios",
+                    "pack",
+                    "-k",
+                    "keyfile",
+                    "--kdf-profile",
+                    "paranoid",
+                    "dir",
+                    "out.enc",
+                ][..],
+                Kdf::Argon2idParanoid,
+            ),
+            (
+                &[
+                    "dexios",
+                    "encrypt",
+                    "-k",
+                    "keyfile",
+                    "--kdf-profile",
+                    "default",
+                    "in",
+                    "out",
+                ][..],
+                Kdf::Argon2id,
+            ),
+        ] {
+            let matches = build_cli()
+                .try_get_matches_from(args)
+                .expect("CLI should parse");
+            let (_, sub_matches) = matches.subcommand().expect("subcommand");
+
+            let params = crypto_params(sub_matches, DeleteInput::Retain).expect("params");
+
+            assert_eq!(params.kdf, expected, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn key_add_kdf_profile_applies_to_the_new_keyslot() {
+        let matches = build_cli()
+            .try_get_matches_from([
+                "dexios",
+                "key",
+                "add",
+                "-k",
+                "old",
+                "-n",
+                "new",
+                "--kdf-profile",
+                "interactive",
+                "cipher.enc",
+            ])
+            .expect("CLI should parse");
+        let (_, key_matches) = matches.subcommand().expect("key subcommand");
+        let (_, add_matches) = key_matches.subcommand().expect("add subcommand");
+
+        let params = key_manipulation_params(add_matches).expect("params");
+
+        assert_eq!(params.kdf, Kdf::Argon2idInteractive);
+    }
+
+    #[test]
+    fn unknown_kdf_profile_is_rejected_by_the_parser() {
+        let result = build_cli().try_get_matches_from([
+            "dexios",
+            "encrypt",
+            "--kdf-profile",
+            "fast",
+            "in",
+            "out",
+        ]);
+
+        assert!(result.is_err());
+    }
+
     #[test]
     fn pack_params_always_retain_the_encrypted_input() {
         // pack does not define the `delete-input` flag: removal of the packed
@@ -1,6 +1,9 @@
 use anyhow::{Context, Result};
 use clap::ArgMatches;
 use core::protected::Protected;
+use core::recipient::RecipientIdentity;
+use domain::hasher::HashAlgorithm;
+use domain::key::Credential;
 use std::io::Read;
 use zeroize::Zeroize;
 
@@ -24,12 +27,45 @@ pub(crate) enum DeleteSource {
     Retain,
 }
 
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub(crate) enum EntryDigests {
+    Record,
+    Omit,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub(crate) enum HeaderTrailer {
+    Append,
+    Omit,
+}
+
 #[derive(PartialEq, Eq)]
 pub(crate) enum PrintMode {
     Verbose,
     Quiet,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ListFormat {
+    Names,
+    Long,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Text,
+    /// One machine-readable result document on stdout; human-readable lines
+    /// go to stderr instead.
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DiffFormat {
+    Text,
+    Json,
+}
+
 pub(crate) enum HeaderLocation {
     Embedded,
     Detached(String),
@@ -43,10 +79,17 @@ pub(crate) enum DeleteInput {
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub(crate) enum HashMode {
-    CalculateHash,
+    CalculateHash(HashAlgorithm),
     NoHash,
 }
 
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub(crate) enum HashFormat {
+    Dexios,
+    /// `<hash>  <file>` lines, as written by `sha256sum` and `b3sum`.
+    Coreutils,
+}
+
 #[derive(PartialEq, Eq, Copy, Clone)]
 pub(crate) enum ForceMode {
     Force,
@@ -56,6 +99,8 @@ pub(crate) enum ForceMode {
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum Key {
     Keyfile(String),
+    // An X25519 identity file from `dexios keygen`; it only opens recipient keyslots.
+    Identity(String),
     Generate(PassphraseWordCount),
     User,
 }
@@ -82,6 +127,25 @@ fn get_bytes<R: Read>(reader: &mut R) -> Result<Protected<Vec<u8>>> {
     Ok(Protected::new(data))
 }
 
+fn read_key_file(path: &str, kind: &str) -> Result<Protected<Vec<u8>>> {
+    if path == "-" {
+        let mut reader = std::io::stdin();
+        let secret = get_bytes(&mut reader)?;
+        if secret.with_exposed(Vec::is_empty) {
+            return Err(anyhow::anyhow!("STDIN is empty"));
+        }
+        return Ok(secret);
+    }
+
+    let mut reader =
+        std::fs::File::open(path).with_context(|| format!("Unable to read file: {path}"))?;
+    let secret = get_bytes(&mut reader)?;
+    if secret.with_exposed(Vec::is_empty) {
+        return Err(anyhow::anyhow!(format!("{kind} '{path}' is empty")));
+    }
+    Ok(secret)
+}
+
 fn generated_passphrase_disclosure(passphrase: &str) -> String {
     format!(
         "Your generated passphrase is intentionally shown here and may be captured by terminal scrollback or logs: {passphrase}"
@@ -116,7 +180,7 @@ fn parse_generated_passphrase_word_count(words: &str) -> Result<PassphraseWordCo
 impl Key {
     #[must_use]
     pub(crate) fn reads_stdin(&self) -> bool {
-        matches!(self, Self::Keyfile(path) if path == "-")
+        matches!(self, Self::Keyfile(path) | Self::Identity(path) if path == "-")
     }
 
     pub(crate) fn resolve_key_source(
@@ -141,22 +205,11 @@ impl Key {
 
     pub(crate) fn get_secret(&self, pass_state: &PasswordState) -> Result<Protected<Vec<u8>>> {
         let secret = match self {
-            Self::Keyfile(path) if path == "-" => {
-                let mut reader = std::io::stdin();
-                let secret = get_bytes(&mut reader)?;
-                if secret.with_exposed(Vec::is_empty) {
-                    return Err(anyhow::anyhow!("STDIN is empty"));
-                }
-                secret
-            }
-            Self::Keyfile(path) => {
-                let mut reader = std::fs::File::open(path)
-                    .with_context(|| format!("Unable to read file: {path}"))?;
-                let secret = get_bytes(&mut reader)?;
-                if secret.with_exposed(Vec::is_empty) {
-                    return Err(anyhow::anyhow!(format!("Keyfile '{path}' is empty")));
-                }
-                secret
+            Self::Keyfile(path) => read_key_file(path, "Keyfile")?,
+            Self::Identity(path) => {
+                return Err(anyhow::anyhow!(
+                    "Identity file '{path}' cannot be used as a password or keyfile"
+                ));
             }
             Self::User => get_password(pass_state)?,
             Self::Generate(i) => generated_passphrase_secret(*i, |message| warn!("{message}")),
@@ -169,6 +222,24 @@ impl Key {
         }
     }
 
+    /// Resolves the key source into the credential the domain layer unlocks
+    /// keyslots with. Identity files must parse as identities; they are never
+    /// retried as keyfiles.
+    pub(crate) fn credential(&self, pass_state: &PasswordState) -> Result<Credential> {
+        match self {
+            Self::Identity(path) => {
+                let contents = read_key_file(path, "Identity file")?;
+                let identity = contents
+                    .with_exposed(|contents| RecipientIdentity::parse(contents))
+                    .map_err(|_| anyhow::anyhow!("Invalid identity file: {path}"))?;
+                Ok(identity.into())
+            }
+            Self::Keyfile(_) | Self::Generate(_) | Self::User => {
+                self.get_secret(pass_state).map(Credential::from)
+            }
+        }
+    }
+
     pub(crate) fn init(
         sub_matches: &ArgMatches,
         params: &KeyParams,
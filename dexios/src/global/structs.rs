@@ -1,8 +1,16 @@
+use std::num::NonZeroUsize;
+
 use core::kdf::Kdf;
+use core::payload::BodyCompression;
+use core::recipient::RecipientPublicKey;
+use domain::archive::MetadataOptions;
 
 use crate::global::states::{ForceMode, HashMode};
 
-use super::states::{DeleteInput, DeleteSource, DirectoryMode, HeaderLocation, Key, PrintMode};
+use super::states::{
+    DeleteInput, DeleteSource, DirectoryMode, EntryDigests, HeaderLocation, HeaderTrailer, Key,
+    PrintMode,
+};
 
 pub(crate) struct CryptoParams {
     pub hash_mode: HashMode,
@@ -11,17 +19,34 @@ pub(crate) struct CryptoParams {
     pub key: Key,
     pub header_location: HeaderLocation,
     pub kdf: Kdf,
+    pub recipients: Vec<RecipientPublicKey>,
+    pub keyslots: Option<usize>,
+    pub header_trailer: HeaderTrailer,
+    pub parity: Option<usize>,
+    pub threads: NonZeroUsize,
+}
+
+// `--batch` writes every output into `out_dir`, named after its input: encrypt
+// appends `suffix` and decrypt strips it.
+pub(crate) struct BatchParams {
+    pub inputs: Vec<String>,
+    pub out_dir: String,
+    pub suffix: String,
 }
 
 pub(crate) struct PackParams {
     pub dir_mode: DirectoryMode,
     pub print_mode: PrintMode,
     pub delete_source: DeleteSource,
+    pub metadata: MetadataOptions,
+    pub compression: Option<BodyCompression>,
+    pub entry_digests: EntryDigests,
 }
 
 pub(crate) struct KeyManipulationParams {
     pub key_old: Key,
     pub key_new: Key,
     pub kdf: Kdf,
+    pub label: Option<String>,
     pub force: ForceMode,
 }
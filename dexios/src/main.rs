@@ -26,14 +26,18 @@ mod subcommands;
 // it works so that's good enough, and any changes are rather simple to make to it
 // it handles the calling of other functions, and some (minimal) argument parsing
 fn main() {
-    if let Err(error) = run() {
+    let result = run();
+    global::output::finish(&result);
+    if let Err(error) = result {
         eprintln!("{error}");
-        std::process::exit(1);
+        let class = subcommands::error_class(&error);
+        std::process::exit(i32::from(subcommands::errors::exit_code(class)));
     }
 }
 
 fn run() -> Result<()> {
     let matches = cli::get_matches();
+    global::output::init(&matches);
     CliRoute::from_matches(&matches)?.dispatch()
 }
 
@@ -43,9 +47,15 @@ enum CliRoute<'a> {
     Decrypt(&'a ArgMatches),
     Pack(&'a ArgMatches),
     Unpack(&'a ArgMatches),
+    List(&'a ArgMatches),
+    Diff(&'a ArgMatches),
+    Update(&'a ArgMatches),
+    Verify(&'a ArgMatches),
+    Repair(&'a ArgMatches),
     Hash(&'a ArgMatches),
     Header(HeaderRoute<'a>),
     Key(KeyRoute<'a>),
+    Keygen(&'a ArgMatches),
 }
 
 impl<'a> CliRoute<'a> {
@@ -55,11 +65,17 @@ impl<'a> CliRoute<'a> {
             Some(("decrypt", sub_matches)) => Ok(Self::Decrypt(sub_matches)),
             Some(("pack", sub_matches)) => Ok(Self::Pack(sub_matches)),
             Some(("unpack", sub_matches)) => Ok(Self::Unpack(sub_matches)),
+            Some(("list", sub_matches)) => Ok(Self::List(sub_matches)),
+            Some(("diff", sub_matches)) => Ok(Self::Diff(sub_matches)),
+            Some(("update", sub_matches)) => Ok(Self::Update(sub_matches)),
+            Some(("verify", sub_matches)) => Ok(Self::Verify(sub_matches)),
+            Some(("repair", sub_matches)) => Ok(Self::Repair(sub_matches)),
             Some(("hash", sub_matches)) => Ok(Self::Hash(sub_matches)),
             Some(("header", sub_matches)) => {
                 Ok(Self::Header(HeaderRoute::from_matches(sub_matches)?))
             }
             Some(("key", sub_matches)) => Ok(Self::Key(KeyRoute::from_matches(sub_matches)?)),
+            Some(("keygen", sub_matches)) => Ok(Self::Keygen(sub_matches)),
             Some((name, _)) => anyhow::bail!(
                 "internal CLI adapter error: unsupported top-level command '{name}' after clap validation"
             ),
@@ -75,9 +91,15 @@ impl<'a> CliRoute<'a> {
             Self::Decrypt(sub_matches) => subcommands::decrypt(sub_matches),
             Self::Pack(sub_matches) => subcommands::pack(sub_matches),
             Self::Unpack(sub_matches) => subcommands::unpack(sub_matches),
+            Self::List(sub_matches) => subcommands::list(sub_matches),
+            Self::Diff(sub_matches) => subcommands::diff(sub_matches),
+            Self::Update(sub_matches) => subcommands::update(sub_matches),
+            Self::Verify(sub_matches) => subcommands::verify(sub_matches),
+            Self::Repair(sub_matches) => subcommands::repair(sub_matches),
             Self::Hash(sub_matches) => subcommands::hash_stream(sub_matches),
             Self::Header(route) => route.dispatch(),
             Self::Key(route) => route.dispatch(),
+            Self::Keygen(sub_matches) => subcommands::keygen(sub_matches),
         }
     }
 }
@@ -87,6 +109,7 @@ enum HeaderRoute<'a> {
     Dump(&'a ArgMatches),
     Restore(&'a ArgMatches),
     Strip(&'a ArgMatches),
+    Repair(&'a ArgMatches),
     Details(&'a ArgMatches),
 }
 
@@ -96,6 +119,7 @@ impl<'a> HeaderRoute<'a> {
             Some(("dump", sub_matches)) => Ok(Self::Dump(sub_matches)),
             Some(("restore", sub_matches)) => Ok(Self::Restore(sub_matches)),
             Some(("strip", sub_matches)) => Ok(Self::Strip(sub_matches)),
+            Some(("repair", sub_matches)) => Ok(Self::Repair(sub_matches)),
             Some(("details", sub_matches)) => Ok(Self::Details(sub_matches)),
             Some((name, _)) => anyhow::bail!(
                 "internal CLI adapter error: unsupported header command '{name}' after clap validation"
@@ -111,6 +135,7 @@ impl<'a> HeaderRoute<'a> {
             Self::Dump(sub_matches) => subcommands::header_dump(sub_matches),
             Self::Restore(sub_matches) => subcommands::header_restore(sub_matches),
             Self::Strip(sub_matches) => subcommands::header_strip(sub_matches),
+            Self::Repair(sub_matches) => subcommands::header_repair(sub_matches),
             Self::Details(sub_matches) => subcommands::header_details(sub_matches),
         }
     }
@@ -122,6 +147,7 @@ enum KeyRoute<'a> {
     Add(&'a ArgMatches),
     Del(&'a ArgMatches),
     Verify(&'a ArgMatches),
+    Rotate(&'a ArgMatches),
 }
 
 impl<'a> KeyRoute<'a> {
@@ -131,6 +157,7 @@ impl<'a> KeyRoute<'a> {
             Some(("add", sub_matches)) => Ok(Self::Add(sub_matches)),
             Some(("del", sub_matches)) => Ok(Self::Del(sub_matches)),
             Some(("verify", sub_matches)) => Ok(Self::Verify(sub_matches)),
+            Some(("rotate", sub_matches)) => Ok(Self::Rotate(sub_matches)),
             Some((name, _)) => anyhow::bail!(
                 "internal CLI adapter error: unsupported key command '{name}' after clap validation"
             ),
@@ -146,6 +173,7 @@ impl<'a> KeyRoute<'a> {
             Self::Add(sub_matches) => subcommands::key_add(sub_matches),
             Self::Del(sub_matches) => subcommands::key_del(sub_matches),
             Self::Verify(sub_matches) => subcommands::key_verify(sub_matches),
+            Self::Rotate(sub_matches) => subcommands::key_rotate(sub_matches),
         }
     }
 }
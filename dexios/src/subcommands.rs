@@ -1,40 +1,194 @@
 use anyhow::Result;
 use clap::ArgMatches;
 use std::fmt;
+use std::num::NonZeroUsize;
 
 // this is called from main.rs
 // it gets params and sends them to the appropriate functions
 
+use crate::cli::overwrite::{
+    ExistingPathProbe, PlannedOverwrite, confirm_overwrites, reject_stdin_input_prompt_conflict,
+    reject_stdin_keyfile_prompt_conflict,
+};
 use crate::global::{
+    output,
     parameters::{
-        forcemode, get_param, get_params, key_manipulation_params, pack_params, parameter_handler,
+        archive_selection, batch_params, decrypt_range, existing_key, forcemode,
+        get_optional_param, get_optional_params, get_param, get_params, hash_algorithm,
+        hash_threads, key_manipulation_params, keyslot_keys, pack_metadata_options, pack_params,
+        parameter_handler, recipients, unpack_metadata_options,
+    },
+    states::{
+        DeleteInput, DiffFormat, HashFormat, HashMode, HeaderLocation, Key, KeyParams, ListFormat,
+        PrintMode,
     },
-    states::{HashMode, Key, KeyParams},
+    structs::CryptoParams,
 };
 use domain::storage::cleanup::{
     CleanupFailure, CleanupGateError, CleanupReceipt, CleanupResult, HashVerification,
-    PostCommitSuccess,
+    PostCommitSuccess, ProcessedSourceCleanupResult,
 };
+use domain::storage::identity::OverwritePolicy;
 use domain::storage::transaction::CommitReceipt;
+use domain::workflow_error::WorkflowErrorClass;
+use errors::ClassifiedError;
 
+pub(crate) mod batch;
 pub(crate) mod decrypt;
+pub(crate) mod diff;
 pub(crate) mod encrypt;
 pub(crate) mod errors;
 pub(crate) mod hashing;
 pub(crate) mod header;
 pub(crate) mod key;
+pub(crate) mod keygen;
+pub(crate) mod list;
 pub(crate) mod pack;
+pub(crate) mod repair;
 pub(crate) mod unpack;
+pub(crate) mod update;
+pub(crate) mod verify;
+
+/// Path operand that names stdin (as input) or stdout (as output).
+pub(crate) const STDIO_PATH: &str = "-";
+
+pub(crate) fn is_stdio(path: &str) -> bool {
+    path == STDIO_PATH
+}
+
+/// The end of a pipe-mode invocation that `--hash` reads after success.
+#[derive(Clone, Copy)]
+pub(crate) enum HashedPipeEnd {
+    Input,
+    Output,
+}
+
+/// Input path and planned output of a pipe-mode invocation; `None` is stdio.
+pub(crate) struct PipePlan<'a> {
+    pub input_path: Option<&'a str>,
+    pub output_plan: Option<PlannedOverwrite>,
+}
+
+impl PipePlan<'_> {
+    pub(crate) fn output_path(&self) -> Option<&std::path::Path> {
+        self.output_plan.as_ref().map(PlannedOverwrite::path)
+    }
+
+    pub(crate) fn output_policy(&self) -> OverwritePolicy {
+        self.output_plan
+            .as_ref()
+            .map_or(OverwritePolicy::CreateNew, PlannedOverwrite::policy)
+    }
+}
+
+// Pipe mode has no source file to delete and no second path to place a detached
+// header next to, and stdin can only carry one of the key, the input and an
+// overwrite confirmation. Returns `None` when the user declines an overwrite.
+pub(crate) fn plan_pipe<'a>(
+    input: &'a str,
+    output: &str,
+    params: &CryptoParams,
+    hashed_end: HashedPipeEnd,
+) -> Result<Option<PipePlan<'a>>> {
+    if is_stdio(input) && params.key.reads_stdin() {
+        return Err(anyhow::anyhow!(
+            "--keyfile - cannot be combined with reading input from stdin"
+        ));
+    }
+    if matches!(params.header_location, HeaderLocation::Detached(_)) {
+        return Err(anyhow::anyhow!(
+            "--header cannot be combined with stdin/stdout pipe mode"
+        ));
+    }
+    if params.delete_input == DeleteInput::Delete {
+        return Err(anyhow::anyhow!(
+            "--delete-input cannot be combined with stdin/stdout pipe mode"
+        ));
+    }
+    if params.threads.get() > 1 {
+        return Err(anyhow::anyhow!(
+            "--threads cannot be combined with stdin/stdout pipe mode"
+        ));
+    }
+    if params.parity.is_some() {
+        return Err(anyhow::anyhow!(
+            "--parity cannot be combined with stdin/stdout pipe mode"
+        ));
+    }
+    if is_stdio(output) {
+        reject_json_on_stdout()?;
+    }
+    if matches!(params.hash_mode, HashMode::CalculateHash(_)) {
+        match hashed_end {
+            HashedPipeEnd::Input if is_stdio(input) => {
+                return Err(anyhow::anyhow!("--hash requires a file input"));
+            }
+            HashedPipeEnd::Output if is_stdio(output) => {
+                return Err(anyhow::anyhow!("--hash requires a file output"));
+            }
+            HashedPipeEnd::Input | HashedPipeEnd::Output => {}
+        }
+    }
+
+    let output_plan =
+        (!is_stdio(output)).then(|| PlannedOverwrite::new(output, ExistingPathProbe::Metadata));
+    if let Some(output_plan) = &output_plan {
+        reject_stdin_keyfile_prompt_conflict(params, output_plan.exists())?;
+        if is_stdio(input) {
+            reject_stdin_input_prompt_conflict(params.force, output_plan.exists())?;
+        }
+        if !confirm_overwrites([output_plan], params.force)? {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(PipePlan {
+        input_path: (!is_stdio(input)).then_some(input),
+        output_plan,
+    }))
+}
+
+// The result document and the piped data cannot share stdout.
+pub(crate) fn reject_json_on_stdout() -> Result<()> {
+    if output::is_json() {
+        return Err(anyhow::anyhow!(
+            "--output json cannot be combined with writing to stdout"
+        ));
+    }
+    Ok(())
+}
 
 pub(crate) fn hash_after_commit(files: &[String], hash_mode: HashMode) -> Result<HashVerification> {
-    if hash_mode == HashMode::CalculateHash {
-        hashing::hash_stream(files)?;
+    if let HashMode::CalculateHash(algorithm) = hash_mode {
+        hashing::hash_stream(files, algorithm, HashFormat::Dexios, NonZeroUsize::MIN)?;
         Ok(HashVerification::Succeeded)
     } else {
         Ok(HashVerification::NotRequested)
     }
 }
 
+// Records the commit, hashes `hashed` if asked to, then deletes the input if
+// asked to and the hash succeeded.
+pub(crate) fn finish_processed(
+    result: &ProcessedSourceCleanupResult,
+    hashed: &str,
+    params: &CryptoParams,
+) -> Result<()> {
+    output::record_commit(result.commit_receipt());
+
+    let hash_verification = hash_after_commit(&[hashed.to_string()], params.hash_mode)?;
+
+    if params.delete_input == DeleteInput::Delete {
+        cleanup_after_commit(
+            result.cleanup_receipt(),
+            result.commit_receipt(),
+            hash_verification,
+        )?;
+    }
+
+    Ok(())
+}
+
 pub(crate) fn cleanup_after_commit(
     cleanup_receipt: &CleanupReceipt,
     commit_receipt: &CommitReceipt,
@@ -50,6 +204,7 @@ pub(crate) fn cleanup_after_commit(
     let proof = PostCommitSuccess::from_commit_and_hash(commit_receipt, hash_verification)
         .map_err(CleanupAfterCommitError::Gate)?;
     let result = cleanup_receipt.run(&proof);
+    output::record_cleanup(&result);
     ensure_cleanup_succeeded(result)
 }
 
@@ -102,9 +257,27 @@ impl std::error::Error for CleanupAfterCommitError {
     }
 }
 
+/// The workflow class of an error returned by a subcommand, which picks the exit
+/// code and the `--output json` error class. Errors raised by the CLI itself,
+/// such as conflicting flags, have no class of their own and are
+/// [`WorkflowErrorClass::Other`].
+pub(crate) fn error_class(error: &anyhow::Error) -> WorkflowErrorClass {
+    if let Some(error) = error.downcast_ref::<ClassifiedError>() {
+        return error.class();
+    }
+    if error.downcast_ref::<CleanupAfterCommitError>().is_some() {
+        return WorkflowErrorClass::CleanupFailure;
+    }
+    WorkflowErrorClass::Other
+}
+
 pub(crate) fn encrypt(sub_matches: &ArgMatches) -> Result<()> {
     let params = parameter_handler(sub_matches)?;
 
+    if let Some(batch) = batch_params(sub_matches)? {
+        return encrypt::batch_mode(&batch, &params);
+    }
+
     encrypt::stream_mode(
         &get_param("input", sub_matches)?,
         &get_param("output", sub_matches)?,
@@ -115,6 +288,19 @@ pub(crate) fn encrypt(sub_matches: &ArgMatches) -> Result<()> {
 pub(crate) fn decrypt(sub_matches: &ArgMatches) -> Result<()> {
     let params = parameter_handler(sub_matches)?;
 
+    if let Some(batch) = batch_params(sub_matches)? {
+        return decrypt::batch_mode(&batch, &params);
+    }
+
+    if let Some(range) = decrypt_range(sub_matches)? {
+        return decrypt::range_mode(
+            &get_param("input", sub_matches)?,
+            &get_param("output", sub_matches)?,
+            &params,
+            range,
+        );
+    }
+
     // stream decrypt is the default as it will redirect to memory mode if the header says so (for backwards-compat)
     decrypt::stream_mode(
         &get_param("input", sub_matches)?,
@@ -149,14 +335,86 @@ pub(crate) fn unpack(sub_matches: &ArgMatches) -> Result<()> {
         &get_param("input", sub_matches)?,
         &get_param("output", sub_matches)?,
         print_mode,
+        archive_selection(sub_matches)?,
+        unpack_metadata_options(sub_matches),
         crypto_params,
     )
 }
 
+pub(crate) fn list(sub_matches: &ArgMatches) -> Result<()> {
+    let key = existing_key(sub_matches, &KeyParams::default(), "keyfile")?;
+    let header = get_optional_param("header", sub_matches)?;
+    let format = if sub_matches.get_flag("json") {
+        ListFormat::Json
+    } else if sub_matches.get_flag("long") {
+        ListFormat::Long
+    } else {
+        ListFormat::Names
+    };
+
+    list::list(&get_param("input", sub_matches)?, header, &key, format)
+}
+
+pub(crate) fn diff(sub_matches: &ArgMatches) -> Result<()> {
+    let key = existing_key(sub_matches, &KeyParams::default(), "keyfile")?;
+    let header = get_optional_param("header", sub_matches)?;
+    let format = if sub_matches.get_flag("json") {
+        DiffFormat::Json
+    } else {
+        DiffFormat::Text
+    };
+
+    diff::diff(
+        &get_param("input", sub_matches)?,
+        &get_param("directory", sub_matches)?,
+        header,
+        &key,
+        format,
+    )
+}
+
+pub(crate) fn update(sub_matches: &ArgMatches) -> Result<()> {
+    let print_mode = if sub_matches.get_flag("verbose") {
+        PrintMode::Verbose
+    } else {
+        PrintMode::Quiet
+    };
+
+    update::execute(update::Request {
+        input_file: &get_param("input", sub_matches)?,
+        output_file: &get_param("output", sub_matches)?,
+        additions: get_optional_params("add", sub_matches)?,
+        removals: get_optional_params("remove", sub_matches)?,
+        keys: keyslot_keys(sub_matches)?,
+        recipients: recipients(sub_matches)?,
+        metadata: pack_metadata_options(sub_matches),
+        drop_locked_keys: sub_matches.get_flag("drop-locked-keys"),
+        print_mode,
+        force: forcemode(sub_matches),
+    })
+}
+
+pub(crate) fn verify(sub_matches: &ArgMatches) -> Result<()> {
+    let key = existing_key(sub_matches, &KeyParams::default(), "keyfile")?;
+    let header = get_optional_param("header", sub_matches)?;
+
+    verify::verify(&get_param("input", sub_matches)?, header, &key)
+}
+
 pub(crate) fn hash_stream(sub_matches: &ArgMatches) -> Result<()> {
     let files = get_params("input", sub_matches)?;
+    let algorithm = hash_algorithm(sub_matches)?;
+    let threads = hash_threads(sub_matches)?;
 
-    hashing::hash_stream(&files)
+    if sub_matches.get_flag("check") {
+        return hashing::check_sums(&files, algorithm, threads);
+    }
+    let format = if sub_matches.get_flag("coreutils") {
+        HashFormat::Coreutils
+    } else {
+        HashFormat::Dexios
+    };
+    hashing::hash_stream(&files, algorithm, format, threads)
 }
 
 pub(crate) fn header_dump(sub_matches: &ArgMatches) -> Result<()> {
@@ -179,6 +437,20 @@ pub(crate) fn header_restore(sub_matches: &ArgMatches) -> Result<()> {
     )
 }
 
+pub(crate) fn header_repair(sub_matches: &ArgMatches) -> Result<()> {
+    let force = forcemode(sub_matches);
+
+    header::repair(&get_param("input", sub_matches)?, force)
+}
+
+pub(crate) fn repair(sub_matches: &ArgMatches) -> Result<()> {
+    repair::repair(
+        &get_param("input", sub_matches)?,
+        get_optional_param("sidecar", sub_matches)?,
+        forcemode(sub_matches),
+    )
+}
+
 pub(crate) fn header_strip(sub_matches: &ArgMatches) -> Result<()> {
     let force = forcemode(sub_matches);
 
@@ -196,6 +468,10 @@ pub(crate) fn header_details(sub_matches: &ArgMatches) -> Result<()> {
     )
 }
 
+pub(crate) fn keygen(sub_matches: &ArgMatches) -> Result<()> {
+    keygen::keygen(&get_param("output", sub_matches)?, forcemode(sub_matches))
+}
+
 pub(crate) fn key_change(sub_matches: &ArgMatches) -> Result<()> {
     let mut params = key_manipulation_params(sub_matches)?;
     params.force = forcemode(sub_matches);
@@ -210,14 +486,32 @@ pub(crate) fn key_add(sub_matches: &ArgMatches) -> Result<()> {
 }
 
 pub(crate) fn key_del(sub_matches: &ArgMatches) -> Result<()> {
-    let key = Key::init(sub_matches, &KeyParams::default(), "keyfile")?;
+    let key = match get_optional_param("authorize-with", sub_matches)? {
+        Some(keyfile) => Key::Keyfile(keyfile.to_owned()),
+        None => existing_key(sub_matches, &KeyParams::default(), "keyfile")?,
+    };
+    let target = match (
+        get_optional_param("label", sub_matches)?,
+        sub_matches.get_one::<usize>("slot"),
+    ) {
+        (Some(label), _) => key::DeleteTarget::Label(label),
+        (None, Some(slot)) => key::DeleteTarget::Slot(*slot),
+        (None, None) => key::DeleteTarget::OpenedKeyslot,
+    };
     let force = forcemode(sub_matches);
 
-    key::delete(&get_param("input", sub_matches)?, &key, force)
+    key::delete(&get_param("input", sub_matches)?, &key, target, force)
 }
 
 pub(crate) fn key_verify(sub_matches: &ArgMatches) -> Result<()> {
-    let key = Key::init(sub_matches, &KeyParams::default(), "keyfile")?;
+    let key = existing_key(sub_matches, &KeyParams::default(), "keyfile")?;
 
     key::verify(&get_param("input", sub_matches)?, &key)
 }
+
+pub(crate) fn key_rotate(sub_matches: &ArgMatches) -> Result<()> {
+    let keys = keyslot_keys(sub_matches)?;
+    let force = forcemode(sub_matches);
+
+    key::rotate(&get_param("input", sub_matches)?, &keys, force)
+}
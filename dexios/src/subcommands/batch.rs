@@ -0,0 +1,154 @@
+use crate::cli::overwrite::{
+    ExistingPathProbe, PlannedOverwrite, confirm_overwrites, reject_stdin_keyfile_prompt_conflict,
+};
+use crate::global::output::{self, json_string};
+use crate::global::structs::{BatchParams, CryptoParams};
+use crate::{success, warn};
+use anyhow::{Result, bail};
+use std::ffi::OsString;
+use std::path::Path;
+
+// `--batch` runs encrypt or decrypt over many inputs in one invocation. Every
+// file still commits through its own transaction; a file that fails is
+// reported and the rest of the batch carries on.
+
+/// How an output is named after its input.
+#[derive(Clone, Copy)]
+pub(crate) enum OutputName {
+    AppendSuffix,
+    StripSuffix,
+}
+
+pub(crate) struct BatchFile<'a> {
+    pub input: &'a str,
+    pub output_plan: PlannedOverwrite,
+}
+
+impl BatchFile<'_> {
+    pub(crate) fn output(&self) -> &Path {
+        self.output_plan.path()
+    }
+}
+
+// Every output is planned, and every overwrite confirmed, before the first
+// file is touched. Returns `None` when the user declines an overwrite.
+pub(crate) fn plan<'a>(
+    batch: &'a BatchParams,
+    naming: OutputName,
+    params: &CryptoParams,
+) -> Result<Option<Vec<BatchFile<'a>>>> {
+    let out_dir = Path::new(&batch.out_dir);
+    if !out_dir.is_dir() {
+        bail!("--out-dir {} is not a directory", batch.out_dir);
+    }
+
+    let mut files: Vec<BatchFile<'a>> = Vec::with_capacity(batch.inputs.len());
+    for input in &batch.inputs {
+        if super::is_stdio(input) {
+            bail!("--batch cannot read from stdin");
+        }
+        let Some(file_name) = Path::new(input).file_name() else {
+            bail!("{input}: has no file name to name its output after");
+        };
+        let output = out_dir.join(output_name(file_name, &batch.suffix, naming));
+        if let Some(earlier) = files.iter().find(|file| file.output() == output) {
+            bail!(
+                "{} and {input} would both be written to {}",
+                earlier.input,
+                output.display()
+            );
+        }
+        files.push(BatchFile {
+            input,
+            output_plan: PlannedOverwrite::new(output, ExistingPathProbe::Metadata),
+        });
+    }
+
+    reject_stdin_keyfile_prompt_conflict(
+        params,
+        files.iter().any(|file| file.output_plan.exists()),
+    )?;
+    if !confirm_overwrites(files.iter().map(|file| &file.output_plan), params.force)? {
+        return Ok(None);
+    }
+
+    Ok(Some(files))
+}
+
+fn output_name(file_name: &std::ffi::OsStr, suffix: &str, naming: OutputName) -> OsString {
+    match naming {
+        OutputName::AppendSuffix => {
+            let mut name = file_name.to_os_string();
+            name.push(suffix);
+            name
+        }
+        // a name without the suffix, or that is nothing but the suffix, is kept
+        OutputName::StripSuffix => file_name
+            .to_str()
+            .and_then(|name| name.strip_suffix(suffix))
+            .filter(|stem| !stem.is_empty())
+            .map_or_else(|| file_name.to_os_string(), OsString::from),
+    }
+}
+
+/// Runs `process` over every file, then reports how many failed.
+pub(crate) fn run(
+    files: &[BatchFile<'_>],
+    verb: &str,
+    mut process: impl FnMut(&BatchFile<'_>) -> Result<()>,
+) -> Result<()> {
+    let mut failed = 0usize;
+    let mut results = Vec::with_capacity(files.len());
+    for file in files {
+        let outcome = process(file);
+        match &outcome {
+            Ok(()) => success!("{}: {verb} to {}", file.input, file.output().display()),
+            Err(error) => {
+                failed = failed.saturating_add(1);
+                warn!("{}: {error:#}", file.input);
+            }
+        }
+        if output::is_json() {
+            results.push(file_json(file, &outcome));
+        }
+    }
+    output::record("files", format!("[{}]", results.join(",")));
+
+    if failed > 0 {
+        bail!("{failed} of {} file(s) could not be {verb}", files.len());
+    }
+    success!("{} file(s) {verb}", files.len());
+
+    Ok(())
+}
+
+fn file_json(file: &BatchFile<'_>, outcome: &Result<()>) -> String {
+    let paths = format!(
+        r#""input":{},"output":{}"#,
+        json_string(file.input),
+        json_string(&file.output().to_string_lossy())
+    );
+    match outcome {
+        Ok(()) => format!(r#"{{{paths},"status":"ok"}}"#),
+        Err(error) => format!(
+            r#"{{{paths},"status":"error","error":{{"class":"{}","message":{}}}}}"#,
+            super::error_class(error).as_str(),
+            json_string(&error.to_string())
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outputs_append_or_strip_the_suffix() {
+        let name = |file: &str, naming| output_name(file.as_ref(), ".dx", naming);
+
+        assert_eq!(name("notes.txt", OutputName::AppendSuffix), "notes.txt.dx");
+        assert_eq!(name("notes.txt.dx", OutputName::StripSuffix), "notes.txt");
+        assert_eq!(name("notes.enc", OutputName::StripSuffix), "notes.enc");
+        assert_eq!(name(".dx", OutputName::StripSuffix), ".dx");
+    }
+}
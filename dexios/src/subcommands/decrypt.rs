@@ -1,22 +1,33 @@
 use crate::cli::overwrite::{
     ExistingPathProbe, PlannedOverwrite, confirm_overwrites, reject_stdin_keyfile_prompt_conflict,
 };
-use crate::global::states::{DeleteInput, HeaderLocation, PasswordState};
-use crate::global::structs::CryptoParams;
+use crate::global::output;
+use crate::global::states::{HeaderLocation, Key, PasswordState};
+use crate::global::structs::{BatchParams, CryptoParams};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
+use domain::decrypt::PlaintextRange;
+use domain::key::{BatchPassphrase, Credential};
+use domain::storage::identity::OverwritePolicy;
+use std::io;
+use std::sync::Arc;
 
+use super::batch::{self, OutputName};
 use super::errors::map_decrypt_error;
 
 // Handles user-facing prompts and delegates path validation/opening to the domain layer.
 pub(crate) fn stream_mode(input: &str, output: &str, params: &CryptoParams) -> Result<()> {
+    if super::is_stdio(input) || super::is_stdio(output) {
+        return pipe_mode(input, output, params);
+    }
+
     let output_plan = PlannedOverwrite::new(output, ExistingPathProbe::Metadata);
     reject_stdin_keyfile_prompt_conflict(params, output_plan.exists())?;
     if !confirm_overwrites([&output_plan], params.force)? {
         return Ok(());
     }
 
-    let raw_key = params.key.get_secret(&PasswordState::Direct)?;
+    let credential = params.key.credential(&PasswordState::Direct)?;
 
     let detached_header_path = match &params.header_location {
         HeaderLocation::Embedded => None,
@@ -27,22 +38,140 @@ pub(crate) fn stream_mode(input: &str, output: &str, params: &CryptoParams) -> R
         output,
         output_plan.policy(),
         detached_header_path,
-        raw_key,
+        credential,
         None,
     )
     .map_err(map_decrypt_error)?;
+    #[cfg(feature = "parallel")]
+    let intent = intent.with_threads(params.threads);
     let result =
         domain::decrypt::execute_transactional_with_cleanup(intent).map_err(map_decrypt_error)?;
+    super::finish_processed(&result, input, params)
+}
+
+// `--batch` asks for the passphrase once and derives each keyslot salt it meets
+// once, so files encrypted by one `encrypt --batch` cost one derivation.
+pub(crate) fn batch_mode(batch: &BatchParams, params: &CryptoParams) -> Result<()> {
+    let Some(files) = batch::plan(batch, OutputName::StripSuffix, params)? else {
+        return Ok(());
+    };
+    // an identity involves no derivation, so it is simply read again per file
+    let passphrase = match &params.key {
+        Key::Identity(_) => None,
+        key => Some(Arc::new(BatchPassphrase::new(
+            key.get_secret(&PasswordState::Direct)?,
+        ))),
+    };
+
+    batch::run(&files, "decrypted", |file| {
+        let credential = match &passphrase {
+            Some(passphrase) => Credential::from(Arc::clone(passphrase)),
+            None => params.key.credential(&PasswordState::Direct)?,
+        };
+        let intent = domain::decrypt::DecryptIntent::new(
+            file.input,
+            file.output(),
+            file.output_plan.policy(),
+            None::<&str>,
+            credential,
+            None,
+        )
+        .map_err(map_decrypt_error)?;
+        #[cfg(feature = "parallel")]
+        let intent = intent.with_threads(params.threads);
+        let result = domain::decrypt::execute_transactional_with_cleanup(intent)
+            .map_err(map_decrypt_error)?;
+        super::finish_processed(&result, file.input, params)
+    })
+}
+
+// `-` as input reads the encrypted file from stdin; `-` as output writes plaintext
+// to stdout as each chunk authenticates. Stdout cannot be staged, so a failure
+// after some chunks were written leaves that plaintext with the reader; the error
+// return gives the process a non-zero exit status, which is the only signal.
+fn pipe_mode(input: &str, output: &str, params: &CryptoParams) -> Result<()> {
+    let Some(plan) = super::plan_pipe(input, output, params, super::HashedPipeEnd::Input)? else {
+        return Ok(());
+    };
+
+    let credential = params.key.credential(&PasswordState::Direct)?;
+    let intent = domain::decrypt::PipeIntent::new(
+        plan.input_path,
+        plan.output_path(),
+        plan.output_policy(),
+        credential,
+    )
+    .map_err(map_decrypt_error)?;
+    match domain::decrypt::execute_pipe(intent, io::stdin().lock(), io::stdout().lock()) {
+        Ok(committed) => {
+            if let Some(receipt) = &committed {
+                output::record_commit(receipt);
+            }
+        }
+        Err(error) => {
+            if plan.output_plan.is_none() {
+                crate::warn!(
+                    "Decryption failed after streaming; any plaintext already written to stdout is unauthenticated and must be discarded"
+                );
+            }
+            return Err(map_decrypt_error(error));
+        }
+    }
+
+    super::hash_after_commit(&[input.to_string()], params.hash_mode)?;
 
-    let hash_verification = super::hash_after_commit(&[input.to_string()], params.hash_mode)?;
+    Ok(())
+}
 
-    if params.delete_input == DeleteInput::Delete {
-        super::cleanup_after_commit(
-            result.cleanup_receipt(),
-            result.commit_receipt(),
-            hash_verification,
-        )?;
+// `--range` authenticates the blocks it reads plus the final block, so plaintext
+// elsewhere in the payload is never opened. `-` as output writes the range to
+// stdout; the input must be a file because the reader seeks within it.
+pub(crate) fn range_mode(
+    input: &str,
+    output: &str,
+    params: &CryptoParams,
+    range: PlaintextRange,
+) -> Result<()> {
+    if super::is_stdio(input) {
+        bail!("--range requires a file input");
+    }
+    if super::is_stdio(output) {
+        super::reject_json_on_stdout()?;
     }
 
+    let output_plan = (!super::is_stdio(output))
+        .then(|| PlannedOverwrite::new(output, ExistingPathProbe::Metadata));
+    reject_stdin_keyfile_prompt_conflict(
+        params,
+        output_plan.as_ref().is_some_and(PlannedOverwrite::exists),
+    )?;
+    if !confirm_overwrites(output_plan.iter(), params.force)? {
+        return Ok(());
+    }
+
+    let credential = params.key.credential(&PasswordState::Direct)?;
+    let detached_header_path = match &params.header_location {
+        HeaderLocation::Embedded => None,
+        HeaderLocation::Detached(path) => Some(path.as_str()),
+    };
+    let intent = domain::decrypt::RangeIntent::new(
+        input,
+        output_plan.as_ref().map(|_| output),
+        output_plan
+            .as_ref()
+            .map_or(OverwritePolicy::CreateNew, PlannedOverwrite::policy),
+        detached_header_path,
+        credential,
+        range,
+    )
+    .map_err(map_decrypt_error)?;
+    let committed =
+        domain::decrypt::execute_range(intent, io::stdout().lock()).map_err(map_decrypt_error)?;
+    if let Some(receipt) = &committed {
+        output::record_commit(receipt);
+    }
+
+    super::hash_after_commit(&[input.to_string()], params.hash_mode)?;
+
     Ok(())
 }
@@ -0,0 +1,101 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use domain::diff::{ArchiveDiff, DiffIntent};
+
+use super::errors::map_diff_error;
+use crate::global::output::{self, json_string};
+use crate::global::states::{DiffFormat, Key, PasswordState};
+use crate::success;
+
+// Nothing is printed until the whole archive has authenticated, so a damaged
+// archive never yields a partial diff.
+pub(crate) fn diff(
+    input: &str,
+    directory: &str,
+    header: Option<&str>,
+    key: &Key,
+    format: DiffFormat,
+) -> Result<()> {
+    let intent = DiffIntent::new(
+        input,
+        header.map(Path::new),
+        directory,
+        key.credential(&PasswordState::Direct)?,
+    )
+    .map_err(map_diff_error)?;
+    let diff = domain::diff::execute(intent).map_err(map_diff_error)?;
+
+    if output::is_json() {
+        output::record("changes", diff_json(&changes(&diff)));
+        return Ok(());
+    }
+
+    match format {
+        DiffFormat::Text if diff.is_empty() => success!("No differences"),
+        DiffFormat::Text => print!("{}", diff_text(&changes(&diff))),
+        DiffFormat::Json => println!("{}", diff_json(&changes(&diff))),
+    }
+
+    Ok(())
+}
+
+type Changes<'a> = [(&'static str, &'a [PathBuf]); 3];
+
+fn changes(diff: &ArchiveDiff) -> Changes<'_> {
+    [
+        ("added", diff.added()),
+        ("removed", diff.removed()),
+        ("modified", diff.modified()),
+    ]
+}
+
+fn diff_text(changes: &Changes<'_>) -> String {
+    let mut text = String::new();
+    for (label, paths) in changes {
+        for path in *paths {
+            let _ = writeln!(text, "{label:<8} {}", path.display());
+        }
+    }
+    text
+}
+
+fn diff_json(changes: &Changes<'_>) -> String {
+    let fields = changes
+        .iter()
+        .map(|(label, paths)| {
+            let quoted = paths
+                .iter()
+                .map(|path| json_string(&path.to_string_lossy()))
+                .collect::<Vec<_>>();
+            format!(r#""{label}":[{}]"#, quoted.join(","))
+        })
+        .collect::<Vec<_>>();
+    format!("{{{}}}", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_output_lists_each_change_kind() {
+        let added = [PathBuf::from("a/new \"one\"")];
+        let modified = [PathBuf::from("a/b.txt"), PathBuf::from("a/c")];
+        let changes = [
+            ("added", &added[..]),
+            ("removed", &[][..]),
+            ("modified", &modified[..]),
+        ];
+
+        assert_eq!(
+            diff_json(&changes),
+            r#"{"added":["a/new \"one\""],"removed":[],"modified":["a/b.txt","a/c"]}"#
+        );
+        assert_eq!(
+            diff_text(&changes),
+            "added    a/new \"one\"\nmodified a/b.txt\nmodified a/c\n"
+        );
+    }
+}
@@ -1,14 +1,26 @@
 use crate::cli::overwrite::{
     ExistingPathProbe, PlannedOverwrite, confirm_overwrites, reject_stdin_keyfile_prompt_conflict,
 };
-use crate::global::states::{DeleteInput, HeaderLocation, PasswordState};
-use crate::global::structs::CryptoParams;
+use crate::global::output;
+use crate::global::states::{HeaderLocation, HeaderTrailer, PasswordState};
+use crate::global::structs::{BatchParams, CryptoParams};
 use anyhow::Result;
+use domain::encrypt::EncryptIntent;
+use domain::key::BatchPassphrase;
+use domain::parity::ParitySidecarTarget;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
 
-use super::errors::map_encrypt_error;
+use super::batch::{self, OutputName};
+use super::errors::{map_encrypt_error, map_parity_error};
 
 // Handles user-facing prompts and delegates path validation/opening to the domain layer.
 pub(crate) fn stream_mode(input: &str, output: &str, params: &CryptoParams) -> Result<()> {
+    if super::is_stdio(input) || super::is_stdio(output) {
+        return pipe_mode(input, output, params);
+    }
+
     let output_plan = PlannedOverwrite::new(output, ExistingPathProbe::Metadata);
     let header_plan = match &params.header_location {
         HeaderLocation::Embedded => None,
@@ -16,45 +28,164 @@ pub(crate) fn stream_mode(input: &str, output: &str, params: &CryptoParams) -> R
             Some(PlannedOverwrite::new(path, ExistingPathProbe::Metadata))
         }
     };
+    let parity_plan = params.parity.map(|_| {
+        PlannedOverwrite::new(
+            domain::parity::sidecar_path_for(Path::new(output)),
+            ExistingPathProbe::Metadata,
+        )
+    });
     reject_stdin_keyfile_prompt_conflict(
         params,
-        output_plan.exists() || header_plan.as_ref().is_some_and(PlannedOverwrite::exists),
+        output_plan.exists()
+            || header_plan.as_ref().is_some_and(PlannedOverwrite::exists)
+            || parity_plan.as_ref().is_some_and(PlannedOverwrite::exists),
     )?;
     let mut prompt_targets = vec![&output_plan];
-    if let Some(header_plan) = &header_plan {
-        prompt_targets.push(header_plan);
-    }
+    prompt_targets.extend(&header_plan);
+    prompt_targets.extend(&parity_plan);
     if !confirm_overwrites(prompt_targets, params.force)? {
         return Ok(());
     }
 
-    let raw_key = params.key.get_secret(&PasswordState::Validate)?;
-
     let header = header_plan
         .as_ref()
         .map(|plan| domain::encrypt::DetachedHeaderTarget::new(plan.path(), plan.policy()));
 
     // 2. encrypt file
-    let intent = domain::encrypt::EncryptIntent::new(
-        input,
-        output,
-        output_plan.policy(),
-        header,
-        raw_key,
-        params.kdf,
-    )
+    let intent = if params.recipients.is_empty() {
+        let raw_key = params.key.get_secret(&PasswordState::Validate)?;
+        EncryptIntent::new(
+            input,
+            output,
+            output_plan.policy(),
+            header,
+            raw_key,
+            params.kdf,
+        )
+    } else {
+        EncryptIntent::new_for_recipients(
+            input,
+            output,
+            output_plan.policy(),
+            header,
+            params.recipients.clone(),
+        )
+    }
     .map_err(map_encrypt_error)?;
+    #[cfg(feature = "parallel")]
+    let intent = intent.with_threads(params.threads);
+    let intent = with_keyslot_options(intent, params)?;
+    let intent = match (&parity_plan, params.parity) {
+        (Some(plan), Some(parity_blocks)) => intent
+            .with_parity_sidecar(
+                ParitySidecarTarget::new(plan.path(), plan.policy(), parity_blocks)
+                    .map_err(map_parity_error)?,
+            )
+            .map_err(map_encrypt_error)?,
+        _ => intent,
+    };
     let result =
         domain::encrypt::execute_transactional_with_cleanup(intent).map_err(map_encrypt_error)?;
+    super::finish_processed(&result, output, params)
+}
+
+// `--batch` asks for the passphrase once and derives its wrapping key once:
+// every file shares that key's salt but gets its own master key and nonces.
+pub(crate) fn batch_mode(batch: &BatchParams, params: &CryptoParams) -> Result<()> {
+    let Some(files) = batch::plan(batch, OutputName::AppendSuffix, params)? else {
+        return Ok(());
+    };
+    let passphrase = if params.recipients.is_empty() {
+        let raw_key = params.key.get_secret(&PasswordState::Validate)?;
+        Some(Arc::new(BatchPassphrase::new(raw_key)))
+    } else {
+        None
+    };
+
+    batch::run(&files, "encrypted", |file| {
+        let intent = match &passphrase {
+            Some(passphrase) => EncryptIntent::new_for_batch(
+                file.input,
+                file.output(),
+                file.output_plan.policy(),
+                None,
+                Arc::clone(passphrase),
+                params.kdf,
+            ),
+            None => EncryptIntent::new_for_recipients(
+                file.input,
+                file.output(),
+                file.output_plan.policy(),
+                None,
+                params.recipients.clone(),
+            ),
+        }
+        .map_err(map_encrypt_error)?;
+        #[cfg(feature = "parallel")]
+        let intent = intent.with_threads(params.threads);
+        let intent = with_keyslot_options(intent, params)?;
+        let result = domain::encrypt::execute_transactional_with_cleanup(intent)
+            .map_err(map_encrypt_error)?;
+        super::finish_processed(&result, &file.output().to_string_lossy(), params)
+    })
+}
+
+fn with_keyslot_options(intent: EncryptIntent, params: &CryptoParams) -> Result<EncryptIntent> {
+    let intent = match params.keyslots {
+        Some(capacity) => intent
+            .with_keyslot_capacity(capacity)
+            .map_err(map_encrypt_error)?,
+        None => intent,
+    };
+    Ok(match params.header_trailer {
+        HeaderTrailer::Append => intent.with_header_trailer().map_err(map_encrypt_error)?,
+        HeaderTrailer::Omit => intent,
+    })
+}
+
+// `-` as input reads plaintext from stdin; `-` as output writes the encrypted file
+// to stdout. Nothing else is printed to stdout so the ciphertext stays clean.
+fn pipe_mode(input: &str, output: &str, params: &CryptoParams) -> Result<()> {
+    let Some(plan) = super::plan_pipe(input, output, params, super::HashedPipeEnd::Output)? else {
+        return Ok(());
+    };
+    let output_path = plan.output_path();
+    let output_policy = plan.output_policy();
 
-    let hash_verification = super::hash_after_commit(&[output.to_string()], params.hash_mode)?;
+    let intent = if params.recipients.is_empty() {
+        let raw_key = params.key.get_secret(&PasswordState::Validate)?;
+        domain::encrypt::PipeIntent::new(
+            plan.input_path,
+            output_path,
+            output_policy,
+            raw_key,
+            params.kdf,
+        )
+    } else {
+        domain::encrypt::PipeIntent::new_for_recipients(
+            plan.input_path,
+            output_path,
+            output_policy,
+            params.recipients.clone(),
+        )
+    }
+    .map_err(map_encrypt_error)?;
+    let intent = match params.keyslots {
+        Some(capacity) => intent
+            .with_keyslot_capacity(capacity)
+            .map_err(map_encrypt_error)?,
+        None => intent,
+    };
+    let intent = match params.header_trailer {
+        HeaderTrailer::Append => intent.with_header_trailer(),
+        HeaderTrailer::Omit => intent,
+    };
+    let committed = domain::encrypt::execute_pipe(intent, io::stdin().lock(), io::stdout().lock())
+        .map_err(map_encrypt_error)?;
 
-    if params.delete_input == DeleteInput::Delete {
-        super::cleanup_after_commit(
-            result.cleanup_receipt(),
-            result.commit_receipt(),
-            hash_verification,
-        )?;
+    if let Some(receipt) = &committed {
+        output::record_commit(receipt);
+        super::hash_after_commit(&[output.to_string()], params.hash_mode)?;
     }
 
     Ok(())
@@ -4,8 +4,69 @@ use domain::storage::transaction::{
     CommittedArtifact, DetachedPublicationFailure, PartialDetachedPublication,
 };
 use domain::workflow_error::WorkflowErrorClass;
+use std::fmt;
+
+/// A user-facing error message tagged with the workflow class it was mapped from.
+///
+/// The message is what gets printed; the class is what `--output json` reports
+/// next to it, so scripts never have to match on message text.
+#[derive(Debug)]
+pub(crate) struct ClassifiedError {
+    class: WorkflowErrorClass,
+    message: anyhow::Error,
+}
+
+impl ClassifiedError {
+    pub(crate) const fn class(&self) -> WorkflowErrorClass {
+        self.class
+    }
+}
+
+impl fmt::Display for ClassifiedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.message, f)
+    }
+}
+
+impl std::error::Error for ClassifiedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.message.source()
+    }
+}
+
+/// The process exit code for a failure of this class. The codes are documented
+/// for scripts and must not change once released: `1` is kept for errors
+/// without a more specific class, and `2` is left to clap's usage errors.
+pub(crate) const fn exit_code(class: WorkflowErrorClass) -> u8 {
+    match class {
+        WorkflowErrorClass::Other => 1,
+        WorkflowErrorClass::MalformedFormat => 10,
+        WorkflowErrorClass::UnsupportedFormat => 11,
+        WorkflowErrorClass::UnsupportedWorkflow => 12,
+        WorkflowErrorClass::IncorrectKey => 20,
+        WorkflowErrorClass::AuthenticationFailure => 21,
+        WorkflowErrorClass::KdfFailure => 22,
+        WorkflowErrorClass::UnsafePath => 30,
+        WorkflowErrorClass::OverwriteDenied => 31,
+        WorkflowErrorClass::IoFailure => 32,
+        WorkflowErrorClass::ResourcePressure => 33,
+        WorkflowErrorClass::TransactionCommitFailure => 34,
+        WorkflowErrorClass::CleanupFailure => 35,
+    }
+}
+
+fn classified(class: WorkflowErrorClass, message: anyhow::Error) -> anyhow::Error {
+    anyhow::Error::new(ClassifiedError { class, message })
+}
 
 pub(crate) fn map_encrypt_error(error: domain::encrypt::Error) -> anyhow::Error {
+    classified(error.workflow_class(), encrypt_error_message(error))
+}
+
+fn encrypt_error_message(error: domain::encrypt::Error) -> anyhow::Error {
+    if matches!(error, domain::encrypt::Error::Parity(_)) {
+        return anyhow!("{error}");
+    }
     if let Some(failure) = error.detached_publication_failure() {
         return map_detached_publication_failure(failure);
     }
@@ -22,16 +83,46 @@ pub(crate) fn map_encrypt_error(error: domain::encrypt::Error) -> anyhow::Error
         WorkflowErrorClass::ResourcePressure => {
             anyhow!("Not enough temporary or output storage while encrypting data")
         }
+        WorkflowErrorClass::UnsupportedWorkflow => anyhow!("{error}"),
         WorkflowErrorClass::MalformedFormat
         | WorkflowErrorClass::UnsupportedFormat
         | WorkflowErrorClass::AuthenticationFailure
-        | WorkflowErrorClass::UnsupportedWorkflow
         | WorkflowErrorClass::IncorrectKey
         | WorkflowErrorClass::Other => anyhow!("Encryption failed"),
     }
 }
 
+pub(crate) fn map_keygen_error(error: domain::keygen::Error) -> anyhow::Error {
+    classified(error.workflow_class(), keygen_error_message(error))
+}
+
+fn keygen_error_message(error: domain::keygen::Error) -> anyhow::Error {
+    match error.workflow_class() {
+        WorkflowErrorClass::UnsafePath => anyhow!("Unsafe path: {error}"),
+        WorkflowErrorClass::OverwriteDenied => anyhow!("Output already exists"),
+        WorkflowErrorClass::TransactionCommitFailure => {
+            anyhow!("Unable to commit identity file")
+        }
+        WorkflowErrorClass::ResourcePressure => {
+            anyhow!("Not enough temporary or output storage while writing identity file")
+        }
+        WorkflowErrorClass::IoFailure => anyhow!("I/O failure while writing identity file"),
+        WorkflowErrorClass::MalformedFormat
+        | WorkflowErrorClass::UnsupportedFormat
+        | WorkflowErrorClass::KdfFailure
+        | WorkflowErrorClass::AuthenticationFailure
+        | WorkflowErrorClass::CleanupFailure
+        | WorkflowErrorClass::UnsupportedWorkflow
+        | WorkflowErrorClass::IncorrectKey
+        | WorkflowErrorClass::Other => anyhow!("Key generation failed"),
+    }
+}
+
 pub(crate) fn map_decrypt_error(error: domain::decrypt::Error) -> anyhow::Error {
+    classified(error.workflow_class(), decrypt_error_message(error))
+}
+
+fn decrypt_error_message(error: domain::decrypt::Error) -> anyhow::Error {
     match error.workflow_class() {
         WorkflowErrorClass::MalformedFormat => anyhow!("Malformed Dexios encrypted data"),
         WorkflowErrorClass::UnsupportedFormat => anyhow!("Unsupported Dexios format"),
@@ -54,13 +145,19 @@ pub(crate) fn map_decrypt_error(error: domain::decrypt::Error) -> anyhow::Error
         WorkflowErrorClass::ResourcePressure => {
             anyhow!("Not enough temporary or output storage while decrypting data")
         }
-        WorkflowErrorClass::UnsupportedWorkflow | WorkflowErrorClass::Other => {
-            anyhow!("Decryption failed")
-        }
+        WorkflowErrorClass::UnsupportedWorkflow => anyhow!("{error}"),
+        WorkflowErrorClass::Other => anyhow!("Decryption failed"),
     }
 }
 
 pub(crate) fn map_pack_error(error: domain::pack::Error) -> anyhow::Error {
+    classified(error.workflow_class(), pack_error_message(error))
+}
+
+fn pack_error_message(error: domain::pack::Error) -> anyhow::Error {
+    if matches!(error, domain::pack::Error::Parity(_)) {
+        return anyhow!("{error}");
+    }
     if let Some(failure) = error.detached_publication_failure() {
         return map_detached_publication_failure(failure);
     }
@@ -86,9 +183,35 @@ pub(crate) fn map_pack_error(error: domain::pack::Error) -> anyhow::Error {
         WorkflowErrorClass::KdfFailure => anyhow!("Unable to derive archive encryption key"),
         WorkflowErrorClass::OverwriteDenied => anyhow!("Output already exists"),
         WorkflowErrorClass::CleanupFailure => anyhow!("Cleanup failed after output commit"),
-        WorkflowErrorClass::UnsupportedWorkflow | WorkflowErrorClass::Other => {
-            anyhow!("Archive packing failed")
+        WorkflowErrorClass::UnsupportedWorkflow => anyhow!("{error}"),
+        WorkflowErrorClass::Other => anyhow!("Archive packing failed"),
+    }
+}
+
+pub(crate) fn map_parity_error(error: domain::parity::Error) -> anyhow::Error {
+    classified(error.workflow_class(), parity_error_message(error))
+}
+
+fn parity_error_message(error: domain::parity::Error) -> anyhow::Error {
+    match error.workflow_class() {
+        WorkflowErrorClass::MalformedFormat | WorkflowErrorClass::UnsupportedWorkflow => {
+            anyhow!("{error}")
+        }
+        WorkflowErrorClass::UnsafePath => anyhow!("Unsafe path: {error}"),
+        WorkflowErrorClass::IoFailure => anyhow!("I/O failure while repairing data"),
+        WorkflowErrorClass::OverwriteDenied => anyhow!("Output already exists"),
+        WorkflowErrorClass::TransactionCommitFailure => {
+            anyhow!("Unable to commit repaired file")
+        }
+        WorkflowErrorClass::ResourcePressure => {
+            anyhow!("Not enough temporary or output storage while repairing data")
         }
+        WorkflowErrorClass::UnsupportedFormat
+        | WorkflowErrorClass::KdfFailure
+        | WorkflowErrorClass::AuthenticationFailure
+        | WorkflowErrorClass::CleanupFailure
+        | WorkflowErrorClass::IncorrectKey
+        | WorkflowErrorClass::Other => anyhow!("Repair failed"),
     }
 }
 
@@ -156,10 +279,22 @@ fn role_label(role: PathRole) -> &'static str {
         PathRole::UnpackRoot => "unpack root",
         PathRole::MutationTarget => "target",
         PathRole::CleanupTarget => "cleanup target",
+        PathRole::ParitySidecar | PathRole::GeneratedParitySidecar => "parity sidecar",
     }
 }
 
 pub(crate) fn map_unpack_error(error: domain::unpack::Error) -> anyhow::Error {
+    classified(error.workflow_class(), unpack_error_message(error))
+}
+
+fn unpack_error_message(error: domain::unpack::Error) -> anyhow::Error {
+    // Every output is already in place, so say so instead of a generic I/O failure.
+    if matches!(error, domain::unpack::Error::ApplyMetadata { .. }) {
+        return anyhow!("Unpacked output was committed, but metadata restore failed: {error}");
+    }
+    if matches!(error, domain::unpack::Error::CreateLink { .. }) {
+        return anyhow!("Unpacked output was committed, but link creation failed: {error}");
+    }
     match error.workflow_class() {
         WorkflowErrorClass::ResourcePressure => {
             debug_assert!(error.is_resource_pressure());
@@ -184,6 +319,127 @@ pub(crate) fn map_unpack_error(error: domain::unpack::Error) -> anyhow::Error {
     }
 }
 
+pub(crate) fn map_list_error(error: domain::unpack::Error) -> anyhow::Error {
+    classified(error.workflow_class(), list_error_message(error))
+}
+
+fn list_error_message(error: domain::unpack::Error) -> anyhow::Error {
+    match error.workflow_class() {
+        WorkflowErrorClass::ResourcePressure => {
+            anyhow!("Archive manifest exceeds the archive limits")
+        }
+        WorkflowErrorClass::UnsafePath => anyhow!("Unsafe archive path: {error}"),
+        WorkflowErrorClass::MalformedFormat => anyhow!("Malformed archive data"),
+        WorkflowErrorClass::UnsupportedFormat => anyhow!("Unsupported archive format"),
+        WorkflowErrorClass::AuthenticationFailure | WorkflowErrorClass::IncorrectKey => {
+            anyhow!("Authentication failed")
+        }
+        WorkflowErrorClass::KdfFailure => anyhow!("Unable to derive archive decryption key"),
+        WorkflowErrorClass::IoFailure => anyhow!("I/O failure while listing archive"),
+        WorkflowErrorClass::TransactionCommitFailure
+        | WorkflowErrorClass::OverwriteDenied
+        | WorkflowErrorClass::CleanupFailure
+        | WorkflowErrorClass::UnsupportedWorkflow
+        | WorkflowErrorClass::Other => anyhow!("Archive listing failed"),
+    }
+}
+
+pub(crate) fn map_verify_error(error: domain::verify::Error) -> anyhow::Error {
+    classified(error.workflow_class(), verify_error_message(error))
+}
+
+// The class is part of the message so scripted integrity checks can tell a
+// tampered file from a wrong key or an unreadable one.
+fn verify_error_message(error: domain::verify::Error) -> anyhow::Error {
+    let class = error.workflow_class();
+    let reason = match class {
+        WorkflowErrorClass::ResourcePressure => {
+            String::from("Payload exceeds the archive limits or available resources")
+        }
+        WorkflowErrorClass::UnsafePath => format!("Unsafe archive path: {error}"),
+        WorkflowErrorClass::MalformedFormat => String::from("Malformed data"),
+        WorkflowErrorClass::UnsupportedFormat => String::from("Unsupported format"),
+        WorkflowErrorClass::AuthenticationFailure => String::from("Authentication failed"),
+        WorkflowErrorClass::IncorrectKey => String::from("Incorrect key"),
+        WorkflowErrorClass::KdfFailure => String::from("Unable to derive decryption key"),
+        WorkflowErrorClass::IoFailure => format!("I/O failure: {error}"),
+        WorkflowErrorClass::TransactionCommitFailure
+        | WorkflowErrorClass::OverwriteDenied
+        | WorkflowErrorClass::CleanupFailure
+        | WorkflowErrorClass::UnsupportedWorkflow
+        | WorkflowErrorClass::Other => error.to_string(),
+    };
+    anyhow!("Verification failed ({}): {reason}", class.as_str())
+}
+
+pub(crate) fn map_diff_error(error: domain::diff::Error) -> anyhow::Error {
+    classified(error.workflow_class(), diff_error_message(error))
+}
+
+fn diff_error_message(error: domain::diff::Error) -> anyhow::Error {
+    match error.workflow_class() {
+        WorkflowErrorClass::ResourcePressure => {
+            anyhow!("Archive exceeds the archive limits or available resources")
+        }
+        WorkflowErrorClass::UnsafePath => anyhow!("Unsafe archive path: {error}"),
+        WorkflowErrorClass::MalformedFormat => anyhow!("Malformed archive data"),
+        WorkflowErrorClass::UnsupportedFormat => anyhow!("Unsupported archive format"),
+        WorkflowErrorClass::AuthenticationFailure | WorkflowErrorClass::IncorrectKey => {
+            anyhow!("Authentication failed")
+        }
+        WorkflowErrorClass::KdfFailure => anyhow!("Unable to derive archive decryption key"),
+        WorkflowErrorClass::IoFailure => anyhow!("I/O failure while comparing archive: {error}"),
+        WorkflowErrorClass::UnsupportedWorkflow => anyhow!("{error}"),
+        WorkflowErrorClass::TransactionCommitFailure
+        | WorkflowErrorClass::OverwriteDenied
+        | WorkflowErrorClass::CleanupFailure
+        | WorkflowErrorClass::Other => anyhow!("Archive comparison failed"),
+    }
+}
+
+pub(crate) fn map_update_error(error: domain::update::Error) -> anyhow::Error {
+    classified(error.workflow_class(), update_error_message(error))
+}
+
+fn update_error_message(error: domain::update::Error) -> anyhow::Error {
+    if matches!(error, domain::update::Error::Parity(_)) {
+        return anyhow!("{error}");
+    }
+    match error.workflow_class() {
+        WorkflowErrorClass::ResourcePressure => {
+            debug_assert!(error.is_resource_pressure());
+            anyhow!("Not enough temporary or output storage while updating archive")
+        }
+        WorkflowErrorClass::UnsafePath => match error {
+            domain::update::Error::ArchiveLimit(_) => anyhow!("Archive limit error: {error}"),
+            domain::update::Error::Pack(error) => pack_error_message(error),
+            _ => anyhow!("{error}"),
+        },
+        WorkflowErrorClass::MalformedFormat => anyhow!("Malformed archive data"),
+        WorkflowErrorClass::UnsupportedFormat => anyhow!("Unsupported archive format"),
+        WorkflowErrorClass::AuthenticationFailure | WorkflowErrorClass::IncorrectKey => {
+            anyhow!("Authentication failed")
+        }
+        WorkflowErrorClass::KdfFailure => anyhow!("Unable to derive archive encryption key"),
+        WorkflowErrorClass::IoFailure => anyhow!("I/O failure while updating archive"),
+        WorkflowErrorClass::TransactionCommitFailure => {
+            anyhow!("Unable to commit updated archive")
+        }
+        WorkflowErrorClass::OverwriteDenied => anyhow!("Output already exists"),
+        WorkflowErrorClass::UnsupportedWorkflow => match error {
+            domain::update::Error::LockedKeyslots(_) => {
+                anyhow!(
+                    "{error}; pass --keyfile, --identity, --passphrases or --recipient for each, or --drop-locked-keys to leave them out"
+                )
+            }
+            _ => anyhow!("{error}"),
+        },
+        WorkflowErrorClass::CleanupFailure | WorkflowErrorClass::Other => {
+            anyhow!("Archive update failed")
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 enum HeaderDisclosure {
     Terse,
@@ -191,11 +447,17 @@ enum HeaderDisclosure {
 }
 
 pub(crate) fn map_header_error(error: domain::header::Error) -> anyhow::Error {
-    map_header_error_with_disclosure(error, HeaderDisclosure::Terse)
+    classified(
+        error.workflow_class(),
+        map_header_error_with_disclosure(error, HeaderDisclosure::Terse),
+    )
 }
 
 pub(crate) fn map_header_details_error(error: domain::header::Error) -> anyhow::Error {
-    map_header_error_with_disclosure(error, HeaderDisclosure::Details)
+    classified(
+        error.workflow_class(),
+        map_header_error_with_disclosure(error, HeaderDisclosure::Details),
+    )
 }
 
 #[expect(
@@ -206,6 +468,9 @@ fn map_header_error_with_disclosure(
     error: domain::header::Error,
     disclosure: HeaderDisclosure,
 ) -> anyhow::Error {
+    if matches!(error, domain::header::Error::Parity(_)) {
+        return anyhow!("{error}");
+    }
     match error.workflow_class() {
         WorkflowErrorClass::MalformedFormat => match (error, disclosure) {
             (domain::header::Error::MalformedV1Header(error), HeaderDisclosure::Details) => {
@@ -244,9 +509,15 @@ fn map_header_error_with_disclosure(
             | domain::header::Error::RetiredV1Layout => anyhow!("Unsupported Dexios format"),
             _ => anyhow!("Unsupported Dexios format"),
         },
-        WorkflowErrorClass::UnsupportedWorkflow => {
-            anyhow!("Unsupported header workflow for this file")
-        }
+        WorkflowErrorClass::UnsupportedWorkflow => match error {
+            domain::header::Error::MissingHeaderTrailer => {
+                anyhow!("The file has no header trailer to repair from")
+            }
+            domain::header::Error::HeaderNotDamaged => {
+                anyhow!("The header already matches its trailer copy; nothing to repair")
+            }
+            _ => anyhow!("Unsupported header workflow for this file"),
+        },
         WorkflowErrorClass::UnsafePath => match error {
             domain::header::Error::PathIdentity(error) => anyhow!("Unsafe path: {error}"),
             _ => anyhow!("Unsafe path"),
@@ -290,11 +561,18 @@ fn map_header_error_with_disclosure(
     }
 }
 
+pub(crate) fn map_key_error(error: domain::key::Error) -> anyhow::Error {
+    classified(error.workflow_class(), key_error_message(error))
+}
+
 #[expect(
     clippy::match_same_arms,
     reason = "the explicit `_` fallback arms keep a stable user-facing message per workflow class even when they share text with a named arm; merging them would drop the defensive catch-all"
 )]
-pub(crate) fn map_key_error(error: domain::key::Error) -> anyhow::Error {
+fn key_error_message(error: domain::key::Error) -> anyhow::Error {
+    if matches!(error, domain::key::Error::Parity(_)) {
+        return anyhow!("{error}");
+    }
     match error.workflow_class() {
         WorkflowErrorClass::MalformedFormat => match error {
             domain::key::Error::MalformedV1Header(_)
@@ -330,6 +608,16 @@ pub(crate) fn map_key_error(error: domain::key::Error) -> anyhow::Error {
             domain::key::Error::TooManyKeyslots => {
                 anyhow!("There are already too many populated keyslots within this file")
             }
+            domain::key::Error::CannotRotateDetachedHeader => anyhow!(
+                "Cannot rotate the master key of a detached header; restore it onto its payload first"
+            ),
+            domain::key::Error::NoRotationCredentials => {
+                anyhow!("Rotating the master key requires at least one key")
+            }
+            domain::key::Error::InvalidKeyslotLabel
+            | domain::key::Error::KeyslotLabelNotFound(_)
+            | domain::key::Error::DuplicateKeyslotLabel(_)
+            | domain::key::Error::EmptyKeyslot(_) => anyhow!("{error}"),
             _ => anyhow!("Unsupported key workflow for this header version"),
         },
         WorkflowErrorClass::UnsafePath => match error {
@@ -346,6 +634,9 @@ pub(crate) fn map_key_error(error: domain::key::Error) -> anyhow::Error {
             domain::key::Error::PathIdentity(_) => {
                 anyhow!("I/O failure while checking key workflow target")
             }
+            domain::key::Error::Reencrypt(_) => {
+                anyhow!("I/O failure while re-encrypting the payload")
+            }
             domain::key::Error::Transaction(_)
             | domain::key::Error::HeaderWrite
             | domain::key::Error::Seek => anyhow!("I/O failure while updating keyslots"),
@@ -358,9 +649,12 @@ pub(crate) fn map_key_error(error: domain::key::Error) -> anyhow::Error {
             anyhow!("Not enough temporary or output storage while updating keyslots")
         }
         WorkflowErrorClass::CleanupFailure => anyhow!("Cleanup failed after output commit"),
-        WorkflowErrorClass::AuthenticationFailure
-        | WorkflowErrorClass::OverwriteDenied
-        | WorkflowErrorClass::Other => anyhow!("Key workflow failed"),
+        WorkflowErrorClass::AuthenticationFailure => {
+            anyhow!("Unable to authenticate the payload; the file was not changed")
+        }
+        WorkflowErrorClass::OverwriteDenied | WorkflowErrorClass::Other => {
+            anyhow!("Key workflow failed")
+        }
     }
 }
 
@@ -409,6 +703,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mapped_errors_keep_their_workflow_class() {
+        let mapped = map_unpack_error(domain::unpack::Error::OpenArchive);
+        let classified = mapped
+            .downcast_ref::<ClassifiedError>()
+            .expect("mapped errors should carry their class");
+        assert_eq!(classified.class(), WorkflowErrorClass::MalformedFormat);
+        assert_eq!(format!("{mapped}"), "Malformed archive data");
+    }
+
+    #[test]
+    fn every_workflow_class_has_its_own_exit_code() {
+        let mut codes = WorkflowErrorClass::ALL.map(exit_code);
+        codes.sort_unstable();
+
+        assert!(codes.windows(2).all(|pair| pair[0] != pair[1]), "{codes:?}");
+        assert!(!codes.contains(&0) && !codes.contains(&2), "{codes:?}");
+        assert_eq!(exit_code(WorkflowErrorClass::Other), 1);
+    }
+
     #[test]
     fn unpack_format_and_authentication_errors_stay_distinct() {
         let malformed = map_unpack_error(domain::unpack::Error::OpenArchive);
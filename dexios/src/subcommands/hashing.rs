@@ -1,26 +1,283 @@
 use anyhow::Context;
-use anyhow::Result;
-use std::cell::RefCell;
+use anyhow::{Result, bail};
+use domain::hasher::HashAlgorithm;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 
-use crate::success;
+use crate::global::output::{self, json_string};
+use crate::global::states::HashFormat;
+use crate::{success, warn};
 
-// this hashes the input file
-// it reads it in blocks, updates the hasher, and finalises/displays the hash
-// it's used by hash-standalone mode
-pub(crate) fn hash_stream(files: &[String]) -> Result<()> {
-    for input in files {
-        let mut input_file =
-            std::fs::File::open(input).with_context(|| format!("Unable to open file: {input}"))?;
+type HashResult = Result<String, domain::hash::Error>;
 
-        let hash = domain::hash::execute(
-            domain::hasher::Blake3Hasher::default(),
-            domain::hash::Request {
-                reader: RefCell::new(&mut input_file),
-            },
-        )?;
+// hashes `files` across `threads` workers, calling `emit` in the order of
+// `files`; with the `visual` feature a progress bar is drawn on stderr
+#[cfg(feature = "visual")]
+fn hash_files(
+    files: &[PathBuf],
+    algorithm: HashAlgorithm,
+    threads: NonZeroUsize,
+    mut emit: impl FnMut(&Path, HashResult),
+) {
+    let total = files
+        .iter()
+        .filter_map(|file| std::fs::metadata(file).ok())
+        .fold(0u64, |total, metadata| total.saturating_add(metadata.len()));
+    let pb = core::visual::create_progress_bar(total);
 
-        success!("{}: {}", input, hash);
+    domain::hash::hash_files(
+        files,
+        algorithm,
+        threads,
+        &|read| pb.inc(read),
+        |path, result| pb.suspend(|| emit(path, result)),
+    );
+    pb.finish_and_clear();
+}
+
+#[cfg(not(feature = "visual"))]
+fn hash_files(
+    files: &[PathBuf],
+    algorithm: HashAlgorithm,
+    threads: NonZeroUsize,
+    emit: impl FnMut(&Path, HashResult),
+) {
+    domain::hash::hash_files(files, algorithm, threads, &|_| {}, emit);
+}
+
+// this hashes the input files, walking any directories among them
+// files are hashed concurrently, but printed in the order they were given
+// it's used by hash-standalone mode and the --hash flags
+pub(crate) fn hash_stream(
+    inputs: &[String],
+    algorithm: HashAlgorithm,
+    format: HashFormat,
+    threads: NonZeroUsize,
+) -> Result<()> {
+    let files = domain::hash::collect_files(inputs)?;
+    let mut unreadable = 0usize;
+
+    hash_files(&files, algorithm, threads, |path, result| match result {
+        Ok(hash) => {
+            let input = path.to_string_lossy();
+            if output::is_json() {
+                output::record_hash(&input, algorithm, &hash);
+                return;
+            }
+            match format {
+                HashFormat::Dexios => success!("{}: {}", input, hash),
+                HashFormat::Coreutils => println!("{}", checksum_line(&hash, &input)),
+            }
+        }
+        Err(error) => {
+            warn!("{}: {:#}", path.display(), anyhow::Error::from(error));
+            unreadable = unreadable.saturating_add(1);
+        }
+    });
+
+    if unreadable > 0 {
+        bail!("{unreadable} file(s) could not be hashed");
     }
 
     Ok(())
 }
+
+// coreutils escapes backslashes and newlines in file names, and marks such
+// lines with a leading backslash so `--check` knows to unescape them
+fn checksum_line(hash: &str, input: &str) -> String {
+    if input.contains(['\\', '\n']) {
+        let escaped = input.replace('\\', "\\\\").replace('\n', "\\n");
+        format!("\\{hash}  {escaped}")
+    } else {
+        format!("{hash}  {input}")
+    }
+}
+
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next()? {
+                '\\' => unescaped.push('\\'),
+                'n' => unescaped.push('\n'),
+                _ => return None,
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    Some(unescaped)
+}
+
+// parses a `<hash>  <file>` or `<hash> *<file>` line into the lowercase hash
+// and the file name, or returns None if the line is not in that format
+fn parse_checksum_line(line: &str, algorithm: HashAlgorithm) -> Option<(String, String)> {
+    let (escaped, line) = line
+        .strip_prefix('\\')
+        .map_or((false, line), |line| (true, line));
+    let (hash, name) = line.split_once(' ')?;
+    let name = name.strip_prefix(' ').or_else(|| name.strip_prefix('*'))?;
+
+    if hash.len() != algorithm.digest_len().saturating_mul(2)
+        || !hash.bytes().all(|b| b.is_ascii_hexdigit())
+        || name.is_empty()
+    {
+        return None;
+    }
+
+    let name = if escaped {
+        unescape(name)?
+    } else {
+        name.to_string()
+    };
+    Some((hash.to_ascii_lowercase(), name))
+}
+
+#[derive(Clone, Copy)]
+enum CheckStatus {
+    Ok,
+    Failed,
+    Unreadable,
+}
+
+impl CheckStatus {
+    // the names used in `--output json` documents
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Failed => "failed",
+            Self::Unreadable => "unreadable",
+        }
+    }
+
+    // the coreutils wording, so the output can be compared with `sha256sum -c`
+    const fn line(self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Failed => "FAILED",
+            Self::Unreadable => "FAILED open or read",
+        }
+    }
+}
+
+// this reads checksum files in the format written by `--coreutils` (or by
+// sha256sum/b3sum), re-hashes every listed file and reports whether it matches
+pub(crate) fn check_sums(
+    checksum_files: &[String],
+    algorithm: HashAlgorithm,
+    threads: NonZeroUsize,
+) -> Result<()> {
+    let mut failed = 0usize;
+    let mut unreadable = 0usize;
+    let mut malformed = 0usize;
+    let mut checks = Vec::new();
+
+    for checksum_file in checksum_files {
+        let contents = std::fs::read_to_string(checksum_file)
+            .with_context(|| format!("Unable to read checksum file: {checksum_file}"))?;
+
+        let mut expected = Vec::new();
+        let mut files = Vec::new();
+        for line in contents.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((hash, input)) = parse_checksum_line(line, algorithm) else {
+                malformed = malformed.saturating_add(1);
+                continue;
+            };
+            expected.push(hash);
+            files.push(PathBuf::from(input));
+        }
+
+        if files.is_empty() {
+            bail!(
+                "{checksum_file}: no properly formatted {} checksum lines found",
+                algorithm.name()
+            );
+        }
+
+        let mut expected = expected.into_iter();
+        hash_files(&files, algorithm, threads, |path, result| {
+            let input = path.to_string_lossy();
+            let status = match (result, expected.next()) {
+                (Ok(hash), Some(expected)) if hash == expected => CheckStatus::Ok,
+                (Ok(_), _) => {
+                    failed = failed.saturating_add(1);
+                    CheckStatus::Failed
+                }
+                (Err(error), _) => {
+                    warn!("{:#}", anyhow::Error::from(error));
+                    unreadable = unreadable.saturating_add(1);
+                    CheckStatus::Unreadable
+                }
+            };
+            if output::is_json() {
+                checks.push(format!(
+                    r#"{{"path":{},"status":"{}"}}"#,
+                    json_string(&input),
+                    status.as_str()
+                ));
+            } else {
+                println!("{input}: {}", status.line());
+            }
+        });
+    }
+    output::record("checks", format!("[{}]", checks.join(",")));
+
+    if malformed > 0 {
+        warn!("{malformed} line(s) are improperly formatted");
+    }
+    if unreadable > 0 {
+        warn!("{unreadable} listed file(s) could not be read");
+    }
+    if failed > 0 || unreadable > 0 {
+        bail!(
+            "{} computed checksum(s) did NOT match",
+            failed.saturating_add(unreadable)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_lines_roundtrip_through_the_parser() {
+        let hash = "ab".repeat(32);
+        for name in ["plain.txt", "two  spaces", "back\\slash", "new\nline"] {
+            let line = checksum_line(&hash, name);
+            assert_eq!(
+                parse_checksum_line(&line, HashAlgorithm::Sha256),
+                Some((hash.clone(), name.to_string())),
+                "{line:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn checksum_lines_must_match_the_algorithm() {
+        let sha256 = format!("{}  file", "AB".repeat(32));
+        assert_eq!(
+            parse_checksum_line(&sha256, HashAlgorithm::Sha256),
+            Some(("ab".repeat(32), "file".to_string()))
+        );
+        assert_eq!(
+            parse_checksum_line(&format!("{} *file", "ab".repeat(32)), HashAlgorithm::Blake3),
+            Some(("ab".repeat(32), "file".to_string()))
+        );
+        assert_eq!(parse_checksum_line(&sha256, HashAlgorithm::Sha512), None);
+        assert_eq!(
+            parse_checksum_line(&format!("{} file", "ab".repeat(32)), HashAlgorithm::Sha256),
+            None
+        );
+        assert_eq!(
+            parse_checksum_line("not a checksum", HashAlgorithm::Sha256),
+            None
+        );
+    }
+}
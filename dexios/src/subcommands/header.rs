@@ -1,10 +1,12 @@
 use crate::cli::overwrite::{ExistingPathProbe, PlannedOverwrite, confirm_overwrites};
 use crate::cli::prompt::overwrite_check;
+use crate::global::output::{self, json_string};
 use crate::global::states::ForceMode;
 use anyhow::Result;
-use core::header::ParsedHeader;
 use core::header::v1::KeyslotKdf;
+use core::header::{ParsedHeader, ParsedV1Payload};
 use domain::utils::hex_encode;
+use std::fmt::Write as _;
 
 use super::errors::{map_header_details_error, map_header_error};
 
@@ -17,6 +19,9 @@ pub(crate) fn details(input: &str, raw: bool) -> Result<()> {
         domain::header::details::DetailsIntent::new(input).map_err(map_header_details_error)?;
 
     match domain::header::details::execute(intent).map_err(map_header_details_error)? {
+        ParsedHeader::V1(payload) if output::is_json() => {
+            output::record("header", header_json(&payload, raw));
+        }
         ParsedHeader::V1(payload) => {
             let header = payload.header();
             println!("Header version: V1");
@@ -26,25 +31,55 @@ pub(crate) fn details(input: &str, raw: bool) -> Result<()> {
                 hex_encode(header.payload_nonce().as_bytes())
             );
             println!("AAD: {} (hex)", hex_encode(payload.aad().as_bytes()));
+            println!(
+                "Keyslots: {} of {} in use",
+                header.keyslots().len(),
+                header.keyslot_capacity().get()
+            );
+            println!(
+                "Header trailer: {}",
+                if header.has_header_trailer() {
+                    "yes (repairable with `header repair`)"
+                } else {
+                    "no"
+                }
+            );
 
-            for (i, keyslot) in header.keyslots().iter().enumerate() {
+            for keyslot in header.keyslots() {
                 let kdf = match keyslot.kdf() {
-                    KeyslotKdf::Argon2id => "Argon2id",
+                    KeyslotKdf::Argon2id => "Argon2id (default profile)",
+                    KeyslotKdf::Argon2idInteractive => "Argon2id (interactive profile)",
+                    KeyslotKdf::Argon2idParanoid => "Argon2id (paranoid profile)",
                     KeyslotKdf::UnsupportedArgon2id => "Argon2id (unsupported historical tag)",
+                    KeyslotKdf::X25519Recipient => "X25519 recipient",
                 };
-                println!("Keyslot {i}:");
+                // physical indices, as taken by `key del --slot`
+                println!("Keyslot {}:", keyslot.physical_index());
+                if let Some(metadata) = keyslot.metadata() {
+                    println!("  Label: {}", metadata.label());
+                    println!("  Created: {}", utc_timestamp(metadata.created_at()));
+                }
                 println!("  KDF: {kdf}");
-                println!("  Salt: {} (hex)", hex_encode(keyslot.salt().as_bytes()));
+                if let Some(ephemeral_public_key) = keyslot.ephemeral_public_key() {
+                    println!(
+                        "  Ephemeral public key: {} (hex)",
+                        hex_encode(ephemeral_public_key.as_bytes())
+                    );
+                } else {
+                    println!("  Salt: {} (hex)", hex_encode(keyslot.salt().as_bytes()));
+                }
                 let encrypted_master_key = if raw {
                     hex_encode(keyslot.encrypted_master_key())
                 } else {
                     ENCRYPTED_MASTER_KEY_REDACTION.to_string()
                 };
                 println!("  Encrypted master key: {encrypted_master_key} (hex)");
-                println!(
-                    "  Keyslot nonce: {} (hex)",
-                    hex_encode(keyslot.nonce().as_bytes())
-                );
+                if keyslot.ephemeral_public_key().is_none() {
+                    println!(
+                        "  Keyslot nonce: {} (hex)",
+                        hex_encode(keyslot.nonce().as_bytes())
+                    );
+                }
             }
         }
     }
@@ -52,6 +87,115 @@ pub(crate) fn details(input: &str, raw: bool) -> Result<()> {
     Ok(())
 }
 
+// The same fields as the text output, under stable names. The encrypted master
+// keys stay behind `--raw` here too, and are omitted rather than redacted.
+fn header_json(payload: &ParsedV1Payload, raw: bool) -> String {
+    let header = payload.header();
+    let keyslots = header
+        .keyslots()
+        .iter()
+        .map(|keyslot| {
+            let mut json = format!(
+                r#"{{"slot":{},"kdf":"{}""#,
+                keyslot.physical_index(),
+                kdf_name(keyslot.kdf())
+            );
+            if let Some(metadata) = keyslot.metadata() {
+                let _ = write!(
+                    json,
+                    r#","label":{},"created_at":{}"#,
+                    json_string(metadata.label()),
+                    metadata.created_at()
+                );
+            }
+            if let Some(ephemeral_public_key) = keyslot.ephemeral_public_key() {
+                let _ = write!(
+                    json,
+                    r#","ephemeral_public_key":"{}""#,
+                    hex_encode(ephemeral_public_key.as_bytes())
+                );
+            } else {
+                let _ = write!(
+                    json,
+                    r#","salt":"{}","nonce":"{}""#,
+                    hex_encode(keyslot.salt().as_bytes()),
+                    hex_encode(keyslot.nonce().as_bytes())
+                );
+            }
+            if raw {
+                let _ = write!(
+                    json,
+                    r#","encrypted_master_key":"{}""#,
+                    hex_encode(keyslot.encrypted_master_key())
+                );
+            }
+            json.push('}');
+            json
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        r#"{{"version":"V1","payload_nonce":"{}","aad":"{}","keyslot_capacity":{},"header_trailer":{},"keyslots":[{}]}}"#,
+        hex_encode(header.payload_nonce().as_bytes()),
+        hex_encode(payload.aad().as_bytes()),
+        header.keyslot_capacity().get(),
+        header.has_header_trailer(),
+        keyslots.join(",")
+    )
+}
+
+const fn kdf_name(kdf: KeyslotKdf) -> &'static str {
+    match kdf {
+        KeyslotKdf::Argon2id => "argon2id",
+        KeyslotKdf::Argon2idInteractive => "argon2id-interactive",
+        KeyslotKdf::Argon2idParanoid => "argon2id-paranoid",
+        KeyslotKdf::UnsupportedArgon2id => "argon2id-unsupported",
+        KeyslotKdf::X25519Recipient => "x25519-recipient",
+    }
+}
+
+// formats seconds since the Unix epoch as a UTC date and time, using the
+// days-to-civil conversion from Howard Hinnant's date algorithms
+fn utc_timestamp(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let time = seconds % 86_400;
+
+    let shifted = days.saturating_add(719_468);
+    let era = shifted / 146_097;
+    let day_of_era = shifted % 146_097;
+    let year_of_era = day_of_era
+        .saturating_sub(day_of_era / 1_460)
+        .saturating_add(day_of_era / 36_524)
+        .saturating_sub(day_of_era / 146_096)
+        / 365;
+    let day_of_year = day_of_era.saturating_sub(
+        year_of_era
+            .saturating_mul(365)
+            .saturating_add(year_of_era / 4)
+            .saturating_sub(year_of_era / 100),
+    );
+    let shifted_month = day_of_year.saturating_mul(5).saturating_add(2) / 153;
+    let day = day_of_year
+        .saturating_sub(shifted_month.saturating_mul(153).saturating_add(2) / 5)
+        .saturating_add(1);
+    let (month, year_offset) = if shifted_month < 10 {
+        (shifted_month.saturating_add(3), 0)
+    } else {
+        (shifted_month.saturating_sub(9), 1)
+    };
+    let year = era
+        .saturating_mul(400)
+        .saturating_add(year_of_era)
+        .saturating_add(year_offset);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
 pub(crate) fn dump(input: &str, output: &str, force: ForceMode) -> Result<()> {
     let output_plan = PlannedOverwrite::new(output, ExistingPathProbe::Metadata);
     if !confirm_overwrites([&output_plan], force)? {
@@ -61,7 +205,8 @@ pub(crate) fn dump(input: &str, output: &str, force: ForceMode) -> Result<()> {
     let intent = domain::header::dump::DumpIntent::new(input, output, output_plan.policy())
         .map_err(map_header_error)?;
 
-    let _receipt = domain::header::dump::execute_transactional(intent).map_err(map_header_error)?;
+    let receipt = domain::header::dump::execute_transactional(intent).map_err(map_header_error)?;
+    output::record_commit(&receipt);
 
     Ok(())
 }
@@ -74,8 +219,9 @@ pub(crate) fn restore(input: &str, output: &str, force: ForceMode) -> Result<()>
     let intent =
         domain::header::restore::RestoreIntent::new(input, output).map_err(map_header_error)?;
 
-    let _receipt =
+    let receipt =
         domain::header::restore::execute_transactional(intent).map_err(map_header_error)?;
+    output::record_commit(&receipt);
 
     crate::warn!(
         "Restored header was validated for structure only, not against the payload. \
@@ -85,6 +231,24 @@ pub(crate) fn restore(input: &str, output: &str, force: ForceMode) -> Result<()>
     Ok(())
 }
 
+pub(crate) fn repair(input: &str, force: ForceMode) -> Result<()> {
+    if !overwrite_check(input, force)? {
+        return Ok(());
+    }
+
+    let intent = domain::header::repair::RepairIntent::new(input).map_err(map_header_error)?;
+
+    let receipt = domain::header::repair::execute(intent).map_err(map_header_error)?;
+    output::record_commit(&receipt);
+
+    crate::warn!(
+        "The header was rebuilt from its trailer copy, which was validated for structure only. \
+         If the copy was damaged too, decryption will fail authentication."
+    );
+
+    Ok(())
+}
+
 // The supplied detached header must byte-match the embedded header before the
 // embedded header is destroyed.
 pub(crate) fn strip(input: &str, header: &str, force: ForceMode) -> Result<()> {
@@ -95,8 +259,21 @@ pub(crate) fn strip(input: &str, header: &str, force: ForceMode) -> Result<()> {
     let intent =
         domain::header::strip::StripIntent::new(header, input).map_err(map_header_error)?;
 
-    let _receipt =
-        domain::header::strip::execute_transactional(intent).map_err(map_header_error)?;
+    let receipt = domain::header::strip::execute_transactional(intent).map_err(map_header_error)?;
+    output::record_commit(&receipt);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utc_timestamps_follow_the_civil_calendar() {
+        assert_eq!(utc_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(utc_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(utc_timestamp(1_700_000_000), "2023-11-14 22:13:20 UTC");
+        assert_eq!(utc_timestamp(4_102_444_799), "2099-12-31 23:59:59 UTC");
+    }
+}
@@ -1,4 +1,5 @@
 use crate::cli::prompt::get_answer;
+use crate::global::output;
 use crate::global::states::ForceMode;
 use crate::global::states::Key;
 use crate::global::states::PasswordState;
@@ -7,7 +8,7 @@ use anyhow::Result;
 use std::path::Path;
 
 use super::errors::map_key_error;
-use crate::info;
+use crate::{info, success};
 
 // Confirms a destructive keyslot mutation before it happens. The default answer
 // is No, so an empty line aborts; `--force`/`-f` short-circuits to Yes via
@@ -28,14 +29,19 @@ fn reject_dual_stdin_keyfiles(params: &KeyManipulationParams) -> Result<()> {
 
 pub(crate) fn add(input: &str, params: &KeyManipulationParams) -> Result<()> {
     reject_dual_stdin_keyfiles(params)?;
-    let intent = domain::key::add::AddIntent::new(Path::new(input)).map_err(map_key_error)?;
+    let mut intent = domain::key::add::AddIntent::new(Path::new(input)).map_err(map_key_error)?;
+    if let Some(label) = &params.label {
+        intent = intent.with_label(label).map_err(map_key_error)?;
+    }
 
     if params.key_old == Key::User {
         info!("Please enter your old key below");
     }
 
-    let raw_key_old = params.key_old.get_secret(&PasswordState::Direct)?;
-    let proven = intent.verify_old_key(raw_key_old).map_err(map_key_error)?;
+    let credential_old = params.key_old.credential(&PasswordState::Direct)?;
+    let proven = intent
+        .verify_old_key(credential_old)
+        .map_err(map_key_error)?;
 
     if params.key_new == Key::User {
         info!("Please enter your new key below");
@@ -43,21 +49,29 @@ pub(crate) fn add(input: &str, params: &KeyManipulationParams) -> Result<()> {
 
     let raw_key_new = params.key_new.get_secret(&PasswordState::Validate)?;
 
-    domain::key::add::execute(proven, raw_key_new, params.kdf).map_err(map_key_error)?;
+    let receipt =
+        domain::key::add::execute(proven, raw_key_new, params.kdf).map_err(map_key_error)?;
+    output::record_commit(&receipt);
 
     Ok(())
 }
 
 pub(crate) fn change(input: &str, params: &KeyManipulationParams) -> Result<()> {
     reject_dual_stdin_keyfiles(params)?;
-    let intent = domain::key::change::ChangeIntent::new(Path::new(input)).map_err(map_key_error)?;
+    let mut intent =
+        domain::key::change::ChangeIntent::new(Path::new(input)).map_err(map_key_error)?;
+    if let Some(label) = &params.label {
+        intent = intent.target_label(label).map_err(map_key_error)?;
+    }
 
     if params.key_old == Key::User {
         info!("Please enter your old key below");
     }
 
-    let raw_key_old = params.key_old.get_secret(&PasswordState::Direct)?;
-    let proven = intent.verify_old_key(raw_key_old).map_err(map_key_error)?;
+    let credential_old = params.key_old.credential(&PasswordState::Direct)?;
+    let proven = intent
+        .verify_old_key(credential_old)
+        .map_err(map_key_error)?;
 
     if params.key_new == Key::User {
         info!("Please enter your new key below");
@@ -69,25 +83,46 @@ pub(crate) fn change(input: &str, params: &KeyManipulationParams) -> Result<()>
         return Ok(());
     }
 
-    domain::key::change::execute(proven, raw_key_new, params.kdf).map_err(map_key_error)?;
+    let receipt =
+        domain::key::change::execute(proven, raw_key_new, params.kdf).map_err(map_key_error)?;
+    output::record_commit(&receipt);
 
     Ok(())
 }
 
-pub(crate) fn delete(input: &str, key_old: &Key, force: ForceMode) -> Result<()> {
+// Which keyslot `key del` removes. A labeled or indexed keyslot may be deleted
+// with a key that opens any keyslot.
+pub(crate) enum DeleteTarget<'a> {
+    OpenedKeyslot,
+    Label(&'a str),
+    Slot(usize),
+}
+
+pub(crate) fn delete(
+    input: &str,
+    key_old: &Key,
+    target: DeleteTarget<'_>,
+    force: ForceMode,
+) -> Result<()> {
     let intent = domain::key::delete::DeleteIntent::new(Path::new(input)).map_err(map_key_error)?;
+    let intent = match target {
+        DeleteTarget::OpenedKeyslot => intent,
+        DeleteTarget::Label(label) => intent.target_label(label).map_err(map_key_error)?,
+        DeleteTarget::Slot(index) => intent.target_slot(index).map_err(map_key_error)?,
+    };
 
     if key_old == &Key::User {
         info!("Please enter your key below");
     }
 
-    let raw_key_old = key_old.get_secret(&PasswordState::Direct)?;
+    let credential_old = key_old.credential(&PasswordState::Direct)?;
 
     if !confirm_destructive_keyslot_change(input, force)? {
         return Ok(());
     }
 
-    domain::key::delete::execute(intent, raw_key_old).map_err(map_key_error)?;
+    let receipt = domain::key::delete::execute(intent, credential_old).map_err(map_key_error)?;
+    output::record_commit(&receipt);
 
     Ok(())
 }
@@ -99,9 +134,54 @@ pub(crate) fn verify(input: &str, key: &Key) -> Result<()> {
         info!("Please enter your key below");
     }
 
-    let raw_key = key.get_secret(&PasswordState::Direct)?;
+    let credential = key.credential(&PasswordState::Direct)?;
 
-    domain::key::verify::execute(intent, raw_key).map_err(map_key_error)?;
+    domain::key::verify::execute(intent, credential).map_err(map_key_error)?;
 
     Ok(())
 }
+
+pub(crate) fn rotate(input: &str, keys: &[Key], force: ForceMode) -> Result<()> {
+    let intent = domain::key::rotate::RotateIntent::new(Path::new(input)).map_err(map_key_error)?;
+
+    let mut credentials = Vec::with_capacity(keys.len());
+    for key in keys {
+        if key == &Key::User {
+            info!("Please enter a key to keep below");
+        }
+        credentials.push(key.credential(&PasswordState::Direct)?);
+    }
+    let proven = intent.verify_keys(credentials).map_err(map_key_error)?;
+
+    let dropped = proven.dropped_slots();
+    let prompt = if dropped.is_empty() {
+        format!("This will re-encrypt {input} under a new master key - are you sure?")
+    } else {
+        format!(
+            "This will re-encrypt {input} under a new master key and drop keyslot(s) {} - are you sure?",
+            slot_list(&dropped)
+        )
+    };
+    if !get_answer(&prompt, false, force)? {
+        return Ok(());
+    }
+
+    let kept = proven.kept_slots();
+    let receipt = domain::key::rotate::execute(proven).map_err(map_key_error)?;
+    output::record_commit(&receipt);
+    output::record("kept_slots", format!("[{}]", slot_list(&kept)));
+    success!(
+        "Rotated the master key of {input}, keeping keyslot(s) {}",
+        slot_list(&kept)
+    );
+
+    Ok(())
+}
+
+fn slot_list(slots: &[usize]) -> String {
+    slots
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
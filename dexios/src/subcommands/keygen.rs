@@ -0,0 +1,27 @@
+use crate::cli::overwrite::{ExistingPathProbe, PlannedOverwrite, confirm_overwrites};
+use crate::global::output::{self, json_string};
+use crate::global::states::ForceMode;
+use crate::{info, success};
+use anyhow::Result;
+
+use super::errors::map_keygen_error;
+
+// The identity file is the only copy of the secret key; the printed public key is
+// safe to share and is what senders pass to `encrypt --recipient`.
+pub(crate) fn keygen(output: &str, force: ForceMode) -> Result<()> {
+    let output_plan = PlannedOverwrite::new(output, ExistingPathProbe::Metadata);
+    if !confirm_overwrites([&output_plan], force)? {
+        return Ok(());
+    }
+
+    let intent = domain::keygen::KeygenIntent::new(output, output_plan.policy())
+        .map_err(map_keygen_error)?;
+    let result = domain::keygen::execute(intent).map_err(map_keygen_error)?;
+
+    output::record_commit(result.commit_receipt());
+    output::record("public_key", json_string(&result.public_key().to_string()));
+    success!("Identity written to {output}");
+    info!("Public key: {}", result.public_key());
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+use core::payload::{ManifestEntry, ManifestEntryKind};
+use domain::utils::hex_encode;
+
+use super::errors::map_list_error;
+use crate::global::output::{self, json_string};
+use crate::global::states::{Key, ListFormat, PasswordState};
+use crate::warn;
+
+// Listing decrypts the manifest and stops, so the archive's final block is never
+// authenticated. The warning goes to stderr so `--json` output stays parseable.
+pub(crate) fn list(input: &str, header: Option<&str>, key: &Key, format: ListFormat) -> Result<()> {
+    let intent = domain::unpack::list::ListIntent::new(
+        input,
+        header.map(Path::new),
+        key.credential(&PasswordState::Direct)?,
+    )
+    .map_err(map_list_error)?;
+    let listing = domain::unpack::list::execute_uncommitted(intent).map_err(map_list_error)?;
+
+    warn!(
+        "This listing is read from the archive manifest only and is not final-authenticated; run `dexios verify` to authenticate the whole archive"
+    );
+
+    if output::is_json() {
+        output::record("entries", entries_json(listing.entries()));
+        return Ok(());
+    }
+
+    match format {
+        ListFormat::Names => {
+            for entry in listing.entries() {
+                println!("{}", display_path(entry));
+            }
+        }
+        ListFormat::Long => {
+            for entry in listing.entries() {
+                let (kind, body_len) = match (entry.kind(), entry.body_len()) {
+                    (ManifestEntryKind::File, Some(body_len)) => ("file", body_len.to_string()),
+                    (ManifestEntryKind::Symlink, _) => ("link", String::from("-")),
+                    (ManifestEntryKind::HardLink, _) => ("hard", String::from("-")),
+                    _ => ("dir", String::from("-")),
+                };
+                println!("{kind:<4} {body_len:>12} {}", display_path(entry));
+            }
+        }
+        ListFormat::Json => println!("{}", entries_json(listing.entries())),
+    }
+
+    Ok(())
+}
+
+fn display_path(entry: &ManifestEntry) -> String {
+    let path = String::from_utf8_lossy(entry.normalized_path());
+    match entry.kind() {
+        ManifestEntryKind::File => path.into_owned(),
+        ManifestEntryKind::Directory => format!("{path}/"),
+        ManifestEntryKind::Symlink | ManifestEntryKind::HardLink => format!(
+            "{path} -> {}",
+            String::from_utf8_lossy(entry.link_target().unwrap_or_default())
+        ),
+    }
+}
+
+fn entries_json(entries: &[ManifestEntry]) -> String {
+    let mut json = String::from("[");
+    for (index, entry) in entries.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        let kind = match entry.kind() {
+            ManifestEntryKind::File => "file",
+            ManifestEntryKind::Directory => "directory",
+            ManifestEntryKind::Symlink => "symlink",
+            ManifestEntryKind::HardLink => "hardlink",
+        };
+        let body_len = entry
+            .body_len()
+            .map_or_else(|| String::from("null"), |len| len.to_string());
+        let _ = write!(
+            json,
+            r#"{{"kind":"{kind}","path":{},"body_len":{body_len}"#,
+            json_string(&String::from_utf8_lossy(entry.normalized_path()))
+        );
+        // Only link entries carry a target, so older consumers see no new field
+        // on files and directories.
+        if let Some(target) = entry.link_target() {
+            let _ = write!(
+                json,
+                r#","link_target":{}"#,
+                json_string(&String::from_utf8_lossy(target))
+            );
+        }
+        if let Some(digest) = entry.blake3_digest() {
+            let _ = write!(json, r#","blake3":"{}""#, hex_encode(digest));
+        }
+        json.push('}');
+    }
+    json.push(']');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_output_escapes_paths_and_marks_directories() {
+        let entries = [
+            ManifestEntry::directory(b"dir".to_vec()).unwrap(),
+            ManifestEntry::file(b"dir/a \"quoted\"\\name\n".to_vec(), 12).unwrap(),
+        ];
+
+        assert_eq!(
+            entries_json(&entries),
+            r#"[{"kind":"directory","path":"dir","body_len":null},{"kind":"file","path":"dir/a \"quoted\"\\name\n","body_len":12}]"#
+        );
+        assert_eq!(json_string("\u{1}"), r#""\u0001""#);
+    }
+
+    #[test]
+    fn json_output_carries_link_targets() {
+        let entries = [
+            ManifestEntry::file(b"a".to_vec(), 1).unwrap(),
+            ManifestEntry::symlink(b"s".to_vec(), b"a".to_vec()).unwrap(),
+            ManifestEntry::hard_link(b"h".to_vec(), b"a".to_vec()).unwrap(),
+        ];
+
+        assert_eq!(
+            entries_json(&entries),
+            r#"[{"kind":"file","path":"a","body_len":1},{"kind":"symlink","path":"s","body_len":null,"link_target":"a"},{"kind":"hardlink","path":"h","body_len":null,"link_target":"a"}]"#
+        );
+        assert_eq!(display_path(&entries[1]), "s -> a");
+    }
+
+    #[test]
+    fn json_output_carries_recorded_digests() {
+        let entries = [
+            ManifestEntry::file(b"a".to_vec(), 1)
+                .unwrap()
+                .with_blake3_digest([0xab; 32])
+                .unwrap(),
+            ManifestEntry::file(b"b".to_vec(), 1).unwrap(),
+        ];
+
+        assert_eq!(
+            entries_json(&entries),
+            format!(
+                r#"[{{"kind":"file","path":"a","body_len":1,"blake3":"{}"}},{{"kind":"file","path":"b","body_len":1}}]"#,
+                "ab".repeat(32)
+            )
+        );
+    }
+}
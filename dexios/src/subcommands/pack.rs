@@ -5,14 +5,17 @@ use anyhow::Result;
 use crate::cli::overwrite::{
     ExistingPathProbe, PlannedOverwrite, confirm_overwrites, reject_stdin_keyfile_prompt_conflict,
 };
+use crate::global::output;
 use crate::global::states::{
-    DeleteSource, DirectoryMode, HeaderLocation, PasswordState, PrintMode,
+    DeleteSource, DirectoryMode, EntryDigests, HeaderLocation, HeaderTrailer, PasswordState,
+    PrintMode,
 };
 use crate::global::structs::{CryptoParams, PackParams};
 use crate::info;
-use crate::subcommands::errors::map_pack_error;
+use crate::subcommands::errors::{map_pack_error, map_parity_error};
 use domain::archive::ArchivePolicy;
 use domain::pack::{DetachedHeaderTarget, PackIntent};
+use domain::parity::{ParitySidecarTarget, sidecar_path_for};
 
 pub(crate) struct Request<'a> {
     pub input_file: &'a Vec<String>,
@@ -44,23 +47,28 @@ pub(crate) fn execute(req: &Request<'_>) -> Result<()> {
     let detached_header_plan = detached_header_path
         .as_ref()
         .map(|path| PlannedOverwrite::new(path, ExistingPathProbe::SymlinkMetadata));
+    let parity_plan = req.crypto_params.parity.map(|_| {
+        PlannedOverwrite::new(
+            sidecar_path_for(&output_path),
+            ExistingPathProbe::SymlinkMetadata,
+        )
+    });
     reject_stdin_keyfile_prompt_conflict(
         &req.crypto_params,
         output_plan.exists()
             || detached_header_plan
                 .as_ref()
-                .is_some_and(PlannedOverwrite::exists),
+                .is_some_and(PlannedOverwrite::exists)
+            || parity_plan.as_ref().is_some_and(PlannedOverwrite::exists),
     )?;
     let mut prompt_targets = vec![&output_plan];
-    if let Some(detached_header_plan) = &detached_header_plan {
-        prompt_targets.push(detached_header_plan);
-    }
+    prompt_targets.extend(&detached_header_plan);
+    prompt_targets.extend(&parity_plan);
     if !confirm_overwrites(prompt_targets, req.crypto_params.force)? {
         return Ok(());
     }
 
     let input_files = req.input_file.iter().map(PathBuf::from).collect::<Vec<_>>();
-    let raw_key = req.crypto_params.key.get_secret(&PasswordState::Validate)?;
 
     let on_archive_entry = (req.pack_params.print_mode == PrintMode::Verbose).then(|| {
         Box::new(|archive_path: &Path| {
@@ -73,20 +81,46 @@ pub(crate) fn execute(req: &Request<'_>) -> Result<()> {
         .map(|plan| DetachedHeaderTarget::new(plan.path(), plan.policy()));
 
     // 2. compress and encrypt files
-    let intent = PackIntent::new(
-        input_files,
-        output_path,
-        output_plan.policy(),
-        detached_header_target,
-        raw_key,
-        req.crypto_params.kdf,
-        ArchivePolicy::default(),
-        req.pack_params.dir_mode == DirectoryMode::Recursive,
-        on_archive_entry,
-    )
-    .map_err(map_pack_error)?;
+    let recursive = req.pack_params.dir_mode == DirectoryMode::Recursive;
+    let intent = if req.crypto_params.recipients.is_empty() {
+        let raw_key = req.crypto_params.key.get_secret(&PasswordState::Validate)?;
+        PackIntent::new(
+            input_files,
+            output_path,
+            output_plan.policy(),
+            detached_header_target,
+            raw_key,
+            req.crypto_params.kdf,
+            ArchivePolicy::default(),
+            recursive,
+            on_archive_entry,
+        )
+    } else {
+        PackIntent::new_for_recipients(
+            input_files,
+            output_path,
+            output_plan.policy(),
+            detached_header_target,
+            req.crypto_params.recipients.clone(),
+            ArchivePolicy::default(),
+            recursive,
+            on_archive_entry,
+        )
+    }
+    .map_err(map_pack_error)?
+    .with_metadata(req.pack_params.metadata);
+    let intent = match req.pack_params.compression {
+        Some(compression) => intent.with_compression(compression),
+        None => intent,
+    };
+    let intent = match req.pack_params.entry_digests {
+        EntryDigests::Record => intent.with_entry_digests(),
+        EntryDigests::Omit => intent,
+    };
+    let intent = with_output_options(intent, &req.crypto_params, parity_plan.as_ref())?;
     let result =
         domain::pack::execute_transactional_with_cleanup(intent).map_err(map_pack_error)?;
+    output::record_commit(result.commit_receipt());
 
     let hash_verification = super::hash_after_commit(
         &[String::from(req.output_file)],
@@ -103,3 +137,33 @@ pub(crate) fn execute(req: &Request<'_>) -> Result<()> {
 
     Ok(())
 }
+
+// Keyslot capacity, header trailer and parity sidecar shape what is written
+// around the archive body rather than the body itself.
+fn with_output_options(
+    intent: PackIntent,
+    crypto_params: &CryptoParams,
+    parity_plan: Option<&PlannedOverwrite>,
+) -> Result<PackIntent> {
+    let intent = match crypto_params.keyslots {
+        Some(capacity) => intent
+            .with_keyslot_capacity(capacity)
+            .map_err(map_pack_error)?,
+        None => intent,
+    };
+    let intent = match crypto_params.header_trailer {
+        HeaderTrailer::Append => intent.with_header_trailer().map_err(map_pack_error)?,
+        HeaderTrailer::Omit => intent,
+    };
+    let intent = match (parity_plan, crypto_params.parity) {
+        (Some(plan), Some(parity_blocks)) => intent
+            .with_parity_sidecar(
+                ParitySidecarTarget::new(plan.path(), plan.policy(), parity_blocks)
+                    .map_err(map_parity_error)?,
+            )
+            .map_err(map_pack_error)?,
+        _ => intent,
+    };
+
+    Ok(intent)
+}
@@ -0,0 +1,32 @@
+use std::path::Path;
+
+use crate::cli::prompt::overwrite_check;
+use crate::global::output;
+use crate::global::states::ForceMode;
+use crate::success;
+use anyhow::Result;
+use domain::parity::sidecar_path_for;
+
+use super::errors::map_parity_error;
+
+// Blocks are matched against the digests in the sidecar, so only the damaged ones
+// are rewritten and no key is needed.
+pub(crate) fn repair(input: &str, sidecar: Option<&str>, force: ForceMode) -> Result<()> {
+    if !overwrite_check(input, force)? {
+        return Ok(());
+    }
+
+    let sidecar = sidecar.map_or_else(|| sidecar_path_for(Path::new(input)), Into::into);
+    let intent =
+        domain::parity::repair::RepairIntent::new(input, sidecar).map_err(map_parity_error)?;
+    let receipt = domain::parity::repair::execute(intent).map_err(map_parity_error)?;
+
+    output::record_commit(receipt.commit_receipt());
+    output::record("repaired_blocks", receipt.repaired_blocks().to_string());
+    success!(
+        "Repaired {} damaged block(s) in {input}",
+        receipt.repaired_blocks()
+    );
+
+    Ok(())
+}
@@ -4,9 +4,11 @@ use crate::{
 };
 
 use anyhow::Result;
+use domain::archive::{ArchiveSelection, MetadataOptions};
 
 use super::errors::map_unpack_error;
 use crate::global::{
+    output,
     states::{ForceMode, HeaderLocation, PasswordState, PrintMode},
     structs::CryptoParams,
 };
@@ -53,6 +55,8 @@ pub(crate) fn unpack(
     input: &str,  // encrypted archive file
     output: &str, // directory
     print_mode: PrintMode,
+    selection: ArchiveSelection,
+    metadata: MetadataOptions,
     params: CryptoParams, // params for decrypt function
 ) -> Result<()> {
     let header_path = match &params.header_location {
@@ -61,14 +65,14 @@ pub(crate) fn unpack(
     };
 
     reject_stdin_keyfile_dynamic_prompt_conflict(&params)?;
-    let raw_key = params.key.get_secret(&PasswordState::Direct)?;
+    let credential = params.key.credential(&PasswordState::Direct)?;
     let verbose = print_mode == PrintMode::Verbose;
 
     let intent = domain::unpack::UnpackIntent::new(
         input,
         header_path,
         output,
-        raw_key,
+        credential,
         None,
         None,
         Some(Box::new(move |file_path| {
@@ -82,9 +86,12 @@ pub(crate) fn unpack(
             )
         })),
     )
-    .map_err(map_unpack_error)?;
+    .map_err(map_unpack_error)?
+    .with_selection(selection)
+    .with_metadata(metadata);
     let extraction_result =
         domain::unpack::execute_with_cleanup(intent).map_err(map_unpack_error)?;
+    output::record_commit(extraction_result.commit_receipt());
 
     let hash_verification = super::hash_after_commit(&[String::from(input)], params.hash_mode)?;
 
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use anyhow::Result;
+use core::recipient::RecipientPublicKey;
+use domain::archive::MetadataOptions;
+use domain::update::UpdateIntent;
+
+use crate::cli::overwrite::{
+    ExistingPathProbe, PlannedOverwrite, confirm_overwrites, reject_stdin_key_prompt_conflict,
+};
+use crate::global::output;
+use crate::global::states::{ForceMode, Key, PasswordState, PrintMode};
+use crate::info;
+use crate::subcommands::errors::map_update_error;
+
+pub(crate) struct Request<'a> {
+    pub input_file: &'a str,
+    pub output_file: &'a str,
+    pub additions: Vec<String>,
+    pub removals: Vec<String>,
+    pub keys: Vec<Key>,
+    pub recipients: Vec<RecipientPublicKey>,
+    pub metadata: MetadataOptions,
+    pub drop_locked_keys: bool,
+    pub print_mode: PrintMode,
+    pub force: ForceMode,
+}
+
+// Updating is delegated to the domain layer, which streams the input's entries
+// into a new archive under a fresh master key, carrying over every keyslot
+// the given keys or recipient public keys rebuild; the input is never modified.
+pub(crate) fn execute(req: Request<'_>) -> Result<()> {
+    if req.input_file == req.output_file {
+        return Err(anyhow::anyhow!(
+            "Input and output files cannot have the same name."
+        ));
+    }
+
+    let output_plan = PlannedOverwrite::new(req.output_file, ExistingPathProbe::SymlinkMetadata);
+    for key in &req.keys {
+        reject_stdin_key_prompt_conflict(key, req.force, output_plan.exists())?;
+    }
+    if !confirm_overwrites([&output_plan], req.force)? {
+        return Ok(());
+    }
+
+    let mut credentials = Vec::with_capacity(req.keys.len());
+    for key in &req.keys {
+        if key == &Key::User && req.keys.len() > 1 {
+            info!("Please enter the key of another keyslot below");
+        }
+        credentials.push(key.credential(&PasswordState::Direct)?);
+    }
+    let mut credentials = credentials.into_iter();
+    let credential = credentials
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No key was given to open the input archive"))?;
+    let mut intent = UpdateIntent::new(
+        req.input_file,
+        output_plan.path(),
+        output_plan.policy(),
+        credential,
+        req.additions,
+        req.removals,
+    )
+    .map_err(map_update_error)?
+    .with_credentials(credentials)
+    .with_recipients(req.recipients)
+    .with_metadata(req.metadata);
+    if req.drop_locked_keys {
+        intent = intent.with_locked_keyslots_dropped();
+    }
+    if req.print_mode == PrintMode::Verbose {
+        intent = intent.with_archive_entry_observer(Box::new(|archive_path: &Path| {
+            info!("Packing {}", archive_path.display());
+        }));
+    }
+
+    let receipt = domain::update::execute(intent).map_err(map_update_error)?;
+    output::record_commit(&receipt);
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use anyhow::Result;
+use domain::verify::{VerifiedPayload, VerifyIntent};
+
+use super::errors::map_verify_error;
+use crate::global::output;
+use crate::global::states::{Key, PasswordState};
+use crate::success;
+
+// Verifying decrypts the whole payload into a sink and only reports once the
+// final block has authenticated, so nothing is ever written to disk.
+pub(crate) fn verify(input: &str, header: Option<&str>, key: &Key) -> Result<()> {
+    let intent = VerifyIntent::new(
+        input,
+        header.map(Path::new),
+        key.credential(&PasswordState::Direct)?,
+    )
+    .map_err(map_verify_error)?;
+    let report = domain::verify::execute(intent).map_err(map_verify_error)?;
+    output::record("verified", verified_json(report.payload()));
+
+    match report.payload() {
+        VerifiedPayload::RawFile { plaintext_len } => {
+            success!("{input}: verified file ({plaintext_len} bytes)");
+        }
+        VerifiedPayload::Archive {
+            entries,
+            body_bytes,
+            digests,
+        } => {
+            success!(
+                "{input}: verified archive with {} entries (files: {}, directories: {}, symlinks: {}, hard links: {}; {body_bytes} body bytes)",
+                entries.total(),
+                entries.files,
+                entries.directories,
+                entries.symlinks,
+                entries.hard_links,
+            );
+            if digests > 0 {
+                success!("{input}: {digests} file bodies matched their recorded BLAKE3 digests");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn verified_json(payload: VerifiedPayload) -> String {
+    match payload {
+        VerifiedPayload::RawFile { plaintext_len } => {
+            format!(r#"{{"kind":"file","plaintext_len":{plaintext_len}}}"#)
+        }
+        VerifiedPayload::Archive {
+            entries,
+            body_bytes,
+            digests,
+        } => format!(
+            r#"{{"kind":"archive","entries":{{"files":{},"directories":{},"symlinks":{},"hard_links":{}}},"body_bytes":{body_bytes},"digests":{digests}}}"#,
+            entries.files, entries.directories, entries.symlinks, entries.hard_links,
+        ),
+    }
+}
@@ -73,6 +73,32 @@ fn run_cli_with_stdin(current_dir: &Path, args: &[&str], stdin: &[u8]) -> std::p
     child.wait_with_output().unwrap()
 }
 
+// Feeds stdin from a separate thread so large pipe-mode payloads cannot deadlock
+// against the child filling its stdout pipe.
+fn run_cli_piped(current_dir: &Path, args: &[&str], stdin: Vec<u8>) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dexios"))
+        .current_dir(current_dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut child_stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn(move || {
+        if let Err(error) = child_stdin.write_all(&stdin) {
+            assert_eq!(
+                error.kind(),
+                ErrorKind::BrokenPipe,
+                "unexpected stdin write error: {error}"
+            );
+        }
+    });
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    output
+}
+
 fn stderr(output: &std::process::Output) -> String {
     String::from_utf8_lossy(&output.stderr).into_owned()
 }
@@ -441,3 +467,213 @@ fn decrypt_malformed_and_legacy_formats_use_typed_mapping() {
         stderr(&retired_output)
     );
 }
+
+#[test]
+fn decrypt_pipe_to_stdout_exits_nonzero_when_final_chunk_fails() {
+    let test_dir = TestDir::new("decrypt-pipe-final-auth");
+    let plaintext = multichunk_plaintext();
+    fs::write(test_dir.path().join("plain.txt"), &plaintext).unwrap();
+    let enc = run_cli(
+        test_dir.path(),
+        CORRECT_PASSWORD,
+        &["encrypt", "--force", "plain.txt", "plain.enc"],
+    );
+    assert!(enc.status.success(), "encrypt failed: {}", stderr(&enc));
+    let mut ciphertext = fs::read(test_dir.path().join("plain.enc")).unwrap();
+    corrupt_final_chunk(&mut ciphertext);
+    fs::write(test_dir.path().join("pipe.key"), CORRECT_PASSWORD).unwrap();
+
+    let dec = run_cli_piped(
+        test_dir.path(),
+        &["decrypt", "--keyfile", "pipe.key", "-", "-"],
+        ciphertext,
+    );
+
+    assert!(!dec.status.success(), "tampered pipe decrypt must fail");
+    assert!(dec.stdout.len() < plaintext.len());
+    assert_eq!(dec.stdout, plaintext[..dec.stdout.len()]);
+    let stderr = stderr(&dec);
+    assert!(
+        stderr.contains("plaintext already written to stdout is unauthenticated"),
+        "stderr did not warn about streamed plaintext: {stderr}"
+    );
+    assert!(stderr.contains("Authentication failed"), "{stderr}");
+}
+
+#[test]
+fn decrypt_pipe_rejects_detached_header_and_delete_input() {
+    let test_dir = TestDir::new("decrypt-pipe-option-conflicts");
+    fs::write(test_dir.path().join("pipe.key"), CORRECT_PASSWORD).unwrap();
+
+    for (flags, message) in [
+        (
+            &["--header", "plain.hdr"][..],
+            "--header cannot be combined",
+        ),
+        (&["--delete-input"][..], "--delete-input cannot be combined"),
+    ] {
+        let mut args = vec!["decrypt", "--keyfile", "pipe.key"];
+        args.extend_from_slice(flags);
+        args.extend_from_slice(&["-", "plain.out"]);
+        let dec = run_cli_with_stdin(test_dir.path(), &args, b"");
+
+        assert!(!dec.status.success());
+        assert!(stderr(&dec).contains(message), "{}", stderr(&dec));
+        assert!(!test_dir.path().join("plain.out").exists());
+    }
+}
+
+#[test]
+fn decrypt_range_writes_only_the_requested_plaintext_bytes() {
+    let test_dir = TestDir::new("decrypt-range");
+    let plaintext = multichunk_plaintext();
+    fs::write(test_dir.path().join("plain.txt"), &plaintext).unwrap();
+    let enc = run_cli(
+        test_dir.path(),
+        CORRECT_PASSWORD,
+        &["encrypt", "--force", "plain.txt", "plain.enc"],
+    );
+    assert!(enc.status.success(), "encrypt failed: {}", stderr(&enc));
+    fs::write(test_dir.path().join("range.key"), CORRECT_PASSWORD).unwrap();
+
+    let start = BLOCK_SIZE - 10;
+    let range = format!("{start}:{}", BLOCK_SIZE + 20);
+    let to_file = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--keyfile",
+            "range.key",
+            "--range",
+            &range,
+            "plain.enc",
+            "range.out",
+        ],
+        b"",
+    );
+    assert!(to_file.status.success(), "{}", stderr(&to_file));
+    assert!(
+        fs::read(test_dir.path().join("range.out")).unwrap()
+            == plaintext[start..start + BLOCK_SIZE + 20]
+    );
+
+    let to_stdout = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--keyfile",
+            "range.key",
+            "--range",
+            "5:11",
+            "plain.enc",
+            "-",
+        ],
+        b"",
+    );
+    assert!(to_stdout.status.success(), "{}", stderr(&to_stdout));
+    assert_eq!(to_stdout.stdout, plaintext[5..16]);
+
+    let past_end = format!("{}:2", plaintext.len() - 1);
+    let out_of_bounds = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--keyfile",
+            "range.key",
+            "--range",
+            &past_end,
+            "plain.enc",
+            "-",
+        ],
+        b"",
+    );
+    assert!(!out_of_bounds.status.success());
+    assert!(
+        stderr(&out_of_bounds).contains("is outside the"),
+        "{}",
+        stderr(&out_of_bounds)
+    );
+    assert!(out_of_bounds.stdout.is_empty());
+}
+
+#[test]
+fn decrypt_range_checks_the_final_block_and_rejects_archives() {
+    let test_dir = TestDir::new("decrypt-range-final-auth");
+    let plaintext = multichunk_plaintext();
+    fs::write(test_dir.path().join("plain.txt"), &plaintext).unwrap();
+    fs::create_dir(test_dir.path().join("dir")).unwrap();
+    fs::write(test_dir.path().join("dir/file.txt"), b"archived").unwrap();
+    let enc = run_cli(
+        test_dir.path(),
+        CORRECT_PASSWORD,
+        &["encrypt", "--force", "plain.txt", "plain.enc"],
+    );
+    assert!(enc.status.success(), "encrypt failed: {}", stderr(&enc));
+    let pack = run_cli(
+        test_dir.path(),
+        CORRECT_PASSWORD,
+        &["pack", "--force", "dir", "dir.enc"],
+    );
+    assert!(pack.status.success(), "pack failed: {}", stderr(&pack));
+    let mut ciphertext = fs::read(test_dir.path().join("plain.enc")).unwrap();
+    corrupt_final_chunk(&mut ciphertext);
+    fs::write(test_dir.path().join("tampered.enc"), ciphertext).unwrap();
+    fs::write(test_dir.path().join("range.key"), CORRECT_PASSWORD).unwrap();
+
+    let tampered = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--keyfile",
+            "range.key",
+            "--range",
+            "0:16",
+            "tampered.enc",
+            "-",
+        ],
+        b"",
+    );
+    assert!(!tampered.status.success());
+    assert!(
+        stderr(&tampered).contains("Authentication failed"),
+        "{}",
+        stderr(&tampered)
+    );
+    assert!(tampered.stdout.is_empty());
+
+    let archive = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--keyfile",
+            "range.key",
+            "--range",
+            "0:4",
+            "dir.enc",
+            "-",
+        ],
+        b"",
+    );
+    assert!(!archive.status.success());
+    assert!(
+        stderr(&archive).contains("single-file payloads"),
+        "{}",
+        stderr(&archive)
+    );
+
+    let stdin_input = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--keyfile",
+            "range.key",
+            "--range",
+            "0:4",
+            "-",
+            "-",
+        ],
+        b"",
+    );
+    assert!(!stdin_input.status.success());
+    assert!(stderr(&stdin_input).contains("--range requires a file input"));
+}
@@ -66,6 +66,32 @@ fn run_cli_with_stdin(current_dir: &Path, args: &[&str], stdin: &[u8]) -> std::p
     child.wait_with_output().unwrap()
 }
 
+// Feeds stdin from a separate thread so large pipe-mode payloads cannot deadlock
+// against the child filling its stdout pipe.
+fn run_cli_piped(current_dir: &Path, args: &[&str], stdin: Vec<u8>) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dexios"))
+        .current_dir(current_dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut child_stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn(move || {
+        if let Err(error) = child_stdin.write_all(&stdin) {
+            assert_eq!(
+                error.kind(),
+                ErrorKind::BrokenPipe,
+                "unexpected stdin write error: {error}"
+            );
+        }
+    });
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    output
+}
+
 #[test]
 fn encrypt_auto_generated_passphrase_disclosure_uses_stderr_not_stdout() {
     let test_dir = TestDir::new("encrypt-auto-stderr");
@@ -272,3 +298,686 @@ fn encrypt_directory_target_fails_during_staging_preflight() {
     );
     assert!(output_dir.is_dir());
 }
+
+#[test]
+fn encrypt_to_recipient_round_trips_with_generated_identity() {
+    let test_dir = TestDir::new("encrypt-recipient-round-trip");
+    fs::write(test_dir.path().join("plain.txt"), b"for the recipient").unwrap();
+
+    let keygen = run_cli_with_stdin(test_dir.path(), &["keygen", "alice.key"], b"");
+    assert!(
+        keygen.status.success(),
+        "keygen failed: stderr={}",
+        String::from_utf8_lossy(&keygen.stderr)
+    );
+    let stdout = String::from_utf8(keygen.stdout).unwrap();
+    let public_key = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("[i] Public key: "))
+        .expect("keygen should print the public key")
+        .to_owned();
+    assert!(public_key.starts_with("dexios-pk-"));
+
+    let encrypt = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--recipient",
+            &public_key,
+            "plain.txt",
+            "plain.enc",
+        ],
+        b"",
+    );
+    assert!(
+        encrypt.status.success(),
+        "encrypt failed: stderr={}",
+        String::from_utf8_lossy(&encrypt.stderr)
+    );
+
+    let decrypt = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--identity",
+            "alice.key",
+            "plain.enc",
+            "roundtrip.txt",
+        ],
+        b"",
+    );
+    assert!(
+        decrypt.status.success(),
+        "decrypt failed: stderr={}",
+        String::from_utf8_lossy(&decrypt.stderr)
+    );
+    assert_eq!(
+        fs::read(test_dir.path().join("roundtrip.txt")).unwrap(),
+        b"for the recipient"
+    );
+}
+
+#[test]
+fn decrypt_rejects_identity_file_that_is_not_an_identity() {
+    let test_dir = TestDir::new("decrypt-identity-invalid");
+    fs::write(test_dir.path().join("plain.txt"), b"plaintext").unwrap();
+    fs::write(test_dir.path().join("key.bin"), b"an ordinary keyfile").unwrap();
+
+    let encrypt = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--keyfile",
+            "key.bin",
+            "--kdf-profile",
+            "interactive",
+            "plain.txt",
+            "plain.enc",
+        ],
+        b"",
+    );
+    assert!(
+        encrypt.status.success(),
+        "encrypt failed: stderr={}",
+        String::from_utf8_lossy(&encrypt.stderr)
+    );
+
+    let decrypt = run_cli_with_stdin(
+        test_dir.path(),
+        &["decrypt", "--identity", "key.bin", "plain.enc", "out.txt"],
+        b"",
+    );
+    assert!(!decrypt.status.success());
+    assert!(
+        String::from_utf8_lossy(&decrypt.stderr).contains("Invalid identity file: key.bin"),
+        "stderr={}",
+        String::from_utf8_lossy(&decrypt.stderr)
+    );
+    assert!(!test_dir.path().join("out.txt").exists());
+}
+
+#[test]
+fn encrypt_rejects_malformed_recipient_before_writing_output() {
+    let test_dir = TestDir::new("encrypt-recipient-malformed");
+    fs::write(test_dir.path().join("plain.txt"), b"plaintext").unwrap();
+
+    let output = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--recipient",
+            "dexios-pk-not-hex",
+            "plain.txt",
+            "plain.enc",
+        ],
+        b"",
+    );
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Invalid recipient public key"),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!test_dir.path().join("plain.enc").exists());
+}
+
+#[test]
+fn encrypt_and_decrypt_pipe_through_stdin_and_stdout() {
+    let test_dir = TestDir::new("encrypt-pipe-round-trip");
+    fs::write(test_dir.path().join("pipe.key"), PASSWORD).unwrap();
+    let plaintext: Vec<u8> = (0..200_000).map(|index| (index % 251) as u8).collect();
+
+    let encrypt = run_cli_piped(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--keyfile",
+            "pipe.key",
+            "--kdf-profile",
+            "interactive",
+            "-",
+            "-",
+        ],
+        plaintext.clone(),
+    );
+    assert!(
+        encrypt.status.success(),
+        "pipe encrypt failed: stderr={}",
+        String::from_utf8_lossy(&encrypt.stderr)
+    );
+    assert!(encrypt.stdout.starts_with(b"DXIO"));
+
+    let decrypt = run_cli_piped(
+        test_dir.path(),
+        &["decrypt", "--keyfile", "pipe.key", "-", "-"],
+        encrypt.stdout,
+    );
+    assert!(
+        decrypt.status.success(),
+        "pipe decrypt failed: stderr={}",
+        String::from_utf8_lossy(&decrypt.stderr)
+    );
+    assert_eq!(decrypt.stdout, plaintext);
+}
+
+#[test]
+fn encrypt_pipe_rejects_stdin_keyfile_with_stdin_input() {
+    let test_dir = TestDir::new("encrypt-pipe-stdin-keyfile");
+
+    let output = run_cli_with_stdin(
+        test_dir.path(),
+        &["encrypt", "--keyfile", "-", "-", "plain.enc"],
+        b"secret-from-stdin\n",
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--keyfile - cannot be combined with reading input from stdin"),
+        "stderr did not explain stdin conflict: {stderr}"
+    );
+    assert!(!test_dir.path().join("plain.enc").exists());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn encrypt_and_decrypt_with_threads_round_trip_multi_block_files() {
+    let test_dir = TestDir::new("encrypt-threads-round-trip");
+    fs::write(test_dir.path().join("threads.key"), PASSWORD).unwrap();
+    let plaintext: Vec<u8> = (0..(5 * 1024 * 1024 + 123))
+        .map(|index| (index % 251) as u8)
+        .collect();
+    fs::write(test_dir.path().join("plain.bin"), &plaintext).unwrap();
+
+    let encrypt = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--keyfile",
+            "threads.key",
+            "--kdf-profile",
+            "interactive",
+            "--threads",
+            "4",
+            "plain.bin",
+            "plain.enc",
+        ],
+        b"",
+    );
+    assert!(
+        encrypt.status.success(),
+        "threaded encrypt failed: stderr={}",
+        String::from_utf8_lossy(&encrypt.stderr)
+    );
+
+    let decrypt = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--keyfile",
+            "threads.key",
+            "--threads",
+            "3",
+            "plain.enc",
+            "plain.out",
+        ],
+        b"",
+    );
+    assert!(
+        decrypt.status.success(),
+        "threaded decrypt failed: stderr={}",
+        String::from_utf8_lossy(&decrypt.stderr)
+    );
+    assert!(fs::read(test_dir.path().join("plain.out")).unwrap() == plaintext);
+
+    let sequential = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--keyfile",
+            "threads.key",
+            "plain.enc",
+            "sequential.out",
+        ],
+        b"",
+    );
+    assert!(sequential.status.success());
+    assert!(fs::read(test_dir.path().join("sequential.out")).unwrap() == plaintext);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn encrypt_rejects_threads_in_pipe_mode_and_zero_threads() {
+    let test_dir = TestDir::new("encrypt-threads-rejected");
+    fs::write(test_dir.path().join("plain.txt"), b"plaintext").unwrap();
+    fs::write(test_dir.path().join("threads.key"), PASSWORD).unwrap();
+
+    let pipe = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--keyfile",
+            "threads.key",
+            "--threads",
+            "2",
+            "plain.txt",
+            "-",
+        ],
+        b"",
+    );
+    assert!(!pipe.status.success());
+    assert!(
+        String::from_utf8_lossy(&pipe.stderr)
+            .contains("--threads cannot be combined with stdin/stdout pipe mode"),
+        "stderr={}",
+        String::from_utf8_lossy(&pipe.stderr)
+    );
+
+    let zero = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--keyfile",
+            "threads.key",
+            "--threads",
+            "0",
+            "plain.txt",
+            "plain.enc",
+        ],
+        b"",
+    );
+    assert!(!zero.status.success());
+    assert!(!test_dir.path().join("plain.enc").exists());
+}
+
+#[test]
+fn encrypt_parity_sidecar_repairs_damaged_blocks() {
+    let test_dir = TestDir::new("encrypt-parity-repair");
+    let plaintext: Vec<u8> = (0..(2 * 1024 * 1024 + 77))
+        .map(|index| (index % 251) as u8)
+        .collect();
+    fs::write(test_dir.path().join("plain.bin"), &plaintext).unwrap();
+
+    let encrypt = run_cli(
+        test_dir.path(),
+        &["encrypt", "--parity", "2", "plain.bin", "plain.enc"],
+    );
+    assert!(
+        encrypt.status.success(),
+        "encrypt with parity failed: stderr={}",
+        String::from_utf8_lossy(&encrypt.stderr)
+    );
+    let encrypted = test_dir.path().join("plain.enc");
+    assert!(test_dir.path().join("plain.enc.dxpar").exists());
+    let original = fs::read(&encrypted).unwrap();
+
+    let intact = run_cli(test_dir.path(), &["repair", "--force", "plain.enc"]);
+    assert!(!intact.status.success());
+    assert!(
+        String::from_utf8_lossy(&intact.stderr).contains("No damaged blocks to repair"),
+        "stderr={}",
+        String::from_utf8_lossy(&intact.stderr)
+    );
+
+    let mut damaged = original.clone();
+    damaged[..64].fill(0xEE);
+    damaged[1024 * 1024 + 10] ^= 0xFF;
+    fs::write(&encrypted, &damaged).unwrap();
+    let repair = run_cli(test_dir.path(), &["repair", "--force", "plain.enc"]);
+    assert!(
+        repair.status.success(),
+        "repair failed: stderr={}",
+        String::from_utf8_lossy(&repair.stderr)
+    );
+    assert!(String::from_utf8_lossy(&repair.stdout).contains("Repaired 2 damaged block(s)"));
+    assert!(fs::read(&encrypted).unwrap() == original);
+
+    let decrypt = run_cli(test_dir.path(), &["decrypt", "plain.enc", "plain.out"]);
+    assert!(decrypt.status.success());
+    assert!(fs::read(test_dir.path().join("plain.out")).unwrap() == plaintext);
+
+    let pipe = run_cli(
+        test_dir.path(),
+        &["encrypt", "--parity", "1", "plain.bin", "-"],
+    );
+    assert!(!pipe.status.success());
+    assert!(
+        String::from_utf8_lossy(&pipe.stderr)
+            .contains("--parity cannot be combined with stdin/stdout pipe mode"),
+        "stderr={}",
+        String::from_utf8_lossy(&pipe.stderr)
+    );
+}
+
+#[test]
+fn json_output_reports_artifacts_hashes_and_error_classes() {
+    let test_dir = TestDir::new("json-output");
+    fs::write(test_dir.path().join("plain.txt"), b"json output").unwrap();
+
+    let encrypt = run_cli(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--output",
+            "json",
+            "--hash",
+            "plain.txt",
+            "plain.enc",
+        ],
+    );
+    assert!(
+        encrypt.status.success(),
+        "encrypt failed: stderr={}",
+        String::from_utf8_lossy(&encrypt.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&encrypt.stdout);
+    assert_eq!(stdout.lines().count(), 1, "stdout={stdout}");
+    assert!(
+        stdout.starts_with(r#"{"schema_version":1,"command":"encrypt","status":"ok","artifacts":[{"role":"payload","path":"#),
+        "stdout={stdout}"
+    );
+    assert!(stdout.contains(r#"plain.enc"}]"#), "stdout={stdout}");
+    assert!(
+        stdout.contains(r#""hashes":[{"path":"plain.enc","algorithm":"blake3","digest":""#),
+        "stdout={stdout}"
+    );
+
+    let details = run_cli(
+        test_dir.path(),
+        &["header", "details", "--output", "json", "plain.enc"],
+    );
+    assert!(details.status.success());
+    let stdout = String::from_utf8_lossy(&details.stdout);
+    assert!(
+        stdout.contains(r#""keyslots":[{"slot":0,"kdf":"argon2id","salt":""#),
+        "stdout={stdout}"
+    );
+    assert!(!stdout.contains("encrypted_master_key"), "stdout={stdout}");
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_dexios"));
+    command.current_dir(test_dir.path());
+    keyfile_cli::append_keyed_args(
+        &mut command,
+        test_dir.path(),
+        "wrong-password",
+        &["decrypt", "--output", "json", "plain.enc", "plain.out"],
+    );
+    let wrong_key = command.output().unwrap();
+    assert!(!wrong_key.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&wrong_key.stdout).trim_end(),
+        r#"{"schema_version":1,"command":"decrypt","status":"error","artifacts":[],"hashes":[],"error":{"class":"incorrect-key","message":"Authentication failed"}}"#
+    );
+    assert!(String::from_utf8_lossy(&wrong_key.stderr).contains("Authentication failed"));
+
+    let pipe = run_cli(
+        test_dir.path(),
+        &["encrypt", "--output", "json", "plain.txt", "-"],
+    );
+    assert!(!pipe.status.success());
+    assert!(
+        String::from_utf8_lossy(&pipe.stdout)
+            .contains("--output json cannot be combined with writing to stdout"),
+        "stdout={}",
+        String::from_utf8_lossy(&pipe.stdout)
+    );
+}
+
+#[test]
+fn batch_mode_reports_each_failure_without_aborting_the_rest() {
+    let test_dir = TestDir::new("batch-mode");
+    for name in ["a.txt", "b.txt"] {
+        fs::write(test_dir.path().join(name), name).unwrap();
+    }
+    fs::create_dir(test_dir.path().join("sub")).unwrap();
+    fs::write(test_dir.path().join("sub/a.txt"), b"sub").unwrap();
+    for dir in ["enc", "plain"] {
+        fs::create_dir(test_dir.path().join(dir)).unwrap();
+    }
+
+    let clash = run_cli(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--batch",
+            "a.txt",
+            "sub/a.txt",
+            "--out-dir",
+            "enc",
+        ],
+    );
+    assert!(!clash.status.success());
+    assert!(
+        String::from_utf8_lossy(&clash.stderr).contains("would both be written to enc/a.txt.dx"),
+        "stderr={}",
+        String::from_utf8_lossy(&clash.stderr)
+    );
+    assert!(!test_dir.path().join("enc/a.txt.dx").exists());
+
+    let encrypt = run_cli(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--kdf-profile",
+            "interactive",
+            "--batch",
+            "a.txt",
+            "b.txt",
+            "--out-dir",
+            "enc",
+        ],
+    );
+    assert!(
+        encrypt.status.success(),
+        "encrypt failed: stderr={}",
+        String::from_utf8_lossy(&encrypt.stderr)
+    );
+    fs::write(test_dir.path().join("enc/junk.dx"), b"not encrypted").unwrap();
+
+    let decrypt = run_cli(
+        test_dir.path(),
+        &[
+            "decrypt",
+            "--output",
+            "json",
+            "--batch",
+            "enc/a.txt.dx",
+            "enc/junk.dx",
+            "enc/b.txt.dx",
+            "--out-dir",
+            "plain",
+        ],
+    );
+    assert_eq!(decrypt.status.code(), Some(1));
+    assert_eq!(
+        fs::read(test_dir.path().join("plain/a.txt")).unwrap(),
+        b"a.txt"
+    );
+    assert_eq!(
+        fs::read(test_dir.path().join("plain/b.txt")).unwrap(),
+        b"b.txt"
+    );
+    assert!(!test_dir.path().join("plain/junk").exists());
+
+    let stdout = String::from_utf8_lossy(&decrypt.stdout);
+    assert!(
+        stdout.contains(
+            r#"{"input":"enc/junk.dx","output":"plain/junk","status":"error","error":{"class":"unsupported-format","#
+        ),
+        "stdout={stdout}"
+    );
+    assert!(
+        stdout.contains(r#"{"input":"enc/b.txt.dx","output":"plain/b.txt","status":"ok"}"#),
+        "stdout={stdout}"
+    );
+    assert!(
+        stdout.contains(r#""message":"1 of 3 file(s) could not be decrypted""#),
+        "stdout={stdout}"
+    );
+}
+
+#[test]
+fn hash_coreutils_output_round_trips_through_check() {
+    let test_dir = TestDir::new("hash-coreutils-check");
+    fs::write(test_dir.path().join("abc.txt"), b"abc").unwrap();
+    fs::write(test_dir.path().join("other.txt"), b"other").unwrap();
+
+    let hash = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "hash",
+            "--algorithm",
+            "sha256",
+            "--coreutils",
+            "abc.txt",
+            "other.txt",
+        ],
+        b"",
+    );
+    assert!(
+        hash.status.success(),
+        "hash failed: stderr={}",
+        String::from_utf8_lossy(&hash.stderr)
+    );
+    let sums = String::from_utf8(hash.stdout).unwrap();
+    assert!(
+        sums.starts_with(
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  abc.txt\n"
+        ),
+        "{sums}"
+    );
+    fs::write(test_dir.path().join("SHA256SUMS"), &sums).unwrap();
+
+    let check = run_cli_with_stdin(
+        test_dir.path(),
+        &["hash", "--algorithm", "sha256", "--check", "SHA256SUMS"],
+        b"",
+    );
+    assert!(
+        check.status.success(),
+        "check failed: stderr={}",
+        String::from_utf8_lossy(&check.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&check.stdout),
+        "abc.txt: OK\nother.txt: OK\n"
+    );
+
+    fs::write(test_dir.path().join("other.txt"), b"changed").unwrap();
+    let check = run_cli_with_stdin(
+        test_dir.path(),
+        &["hash", "--algorithm", "sha256", "-c", "SHA256SUMS"],
+        b"",
+    );
+    assert!(!check.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&check.stdout),
+        "abc.txt: OK\nother.txt: FAILED\n"
+    );
+
+    let wrong_algorithm = run_cli_with_stdin(
+        test_dir.path(),
+        &["hash", "--algorithm", "sha512", "--check", "SHA256SUMS"],
+        b"",
+    );
+    assert!(!wrong_algorithm.status.success());
+    assert!(
+        String::from_utf8_lossy(&wrong_algorithm.stderr)
+            .contains("no properly formatted sha512 checksum lines"),
+        "stderr={}",
+        String::from_utf8_lossy(&wrong_algorithm.stderr)
+    );
+}
+
+#[test]
+fn encrypt_hash_uses_the_requested_algorithm() {
+    let test_dir = TestDir::new("encrypt-hash-algorithm");
+    fs::write(test_dir.path().join("plain.txt"), b"abc").unwrap();
+
+    let encrypt = run_cli(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--hash",
+            "--algorithm",
+            "sha512",
+            "--kdf-profile",
+            "interactive",
+            "plain.txt",
+            "plain.enc",
+        ],
+    );
+    assert!(
+        encrypt.status.success(),
+        "encrypt failed: stderr={}",
+        String::from_utf8_lossy(&encrypt.stderr)
+    );
+
+    let hash = run_cli_with_stdin(
+        test_dir.path(),
+        &["hash", "--algorithm", "sha512", "plain.enc"],
+        b"",
+    );
+    let stdout = String::from_utf8_lossy(&hash.stdout);
+    let expected = stdout.trim_end().rsplit(' ').next().unwrap();
+    assert_eq!(expected.len(), 128, "{stdout}");
+    assert!(
+        String::from_utf8_lossy(&encrypt.stdout).contains(expected),
+        "stdout={}",
+        String::from_utf8_lossy(&encrypt.stdout)
+    );
+}
+
+#[test]
+fn hash_walks_directories_in_order_and_reports_unreadable_files() {
+    let test_dir = TestDir::new("hash-directories");
+    let tree = test_dir.path().join("tree");
+    fs::create_dir_all(tree.join("nested")).unwrap();
+    fs::write(tree.join("b.txt"), b"abc").unwrap();
+    fs::write(tree.join("a.txt"), b"abc").unwrap();
+    fs::write(tree.join("nested/c.txt"), vec![7u8; 3 * 1024 * 1024]).unwrap();
+    fs::write(test_dir.path().join("top.txt"), b"abc").unwrap();
+    let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    let hash = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "hash",
+            "--algorithm",
+            "sha256",
+            "--coreutils",
+            "--threads",
+            "3",
+            "tree",
+            "top.txt",
+        ],
+        b"",
+    );
+    assert!(
+        hash.status.success(),
+        "hash failed: stderr={}",
+        String::from_utf8_lossy(&hash.stderr)
+    );
+    let stdout = String::from_utf8(hash.stdout).unwrap();
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4, "{stdout}");
+    assert_eq!(lines[0], format!("{abc}  tree/a.txt"));
+    assert_eq!(lines[1], format!("{abc}  tree/b.txt"));
+    assert!(lines[2].ends_with("  tree/nested/c.txt"), "{stdout}");
+    assert_eq!(lines[3], format!("{abc}  top.txt"));
+
+    let hash = run_cli_with_stdin(test_dir.path(), &["hash", "missing.txt", "top.txt"], b"");
+    assert!(!hash.status.success());
+    assert!(
+        String::from_utf8_lossy(&hash.stdout).contains("top.txt: "),
+        "stdout={}",
+        String::from_utf8_lossy(&hash.stdout)
+    );
+    let stderr = String::from_utf8_lossy(&hash.stderr);
+    assert!(
+        stderr.contains("missing.txt: Unable to open file"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("1 file(s) could not be hashed"), "{stderr}");
+}
@@ -383,3 +383,92 @@ fn header_restore_requires_confirmation_without_force() {
     assert_success(&output, "forced header restore");
     assert_eq!(fs::read(&stripped).unwrap(), original);
 }
+
+#[test]
+fn header_repair_rebuilds_a_damaged_header_from_its_trailer() {
+    let test_dir = TestDir::new("header-repair");
+    fs::write(test_dir.path().join("plain.txt"), b"payload bytes").unwrap();
+    let output = run_cli(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--force",
+            "--header-trailer",
+            "plain.txt",
+            "plain.enc",
+        ],
+    );
+    assert_success(&output, "encrypt with header trailer");
+    let encrypted = test_dir.path().join("plain.enc");
+    let original = fs::read(&encrypted).unwrap();
+
+    let output = run_cli(
+        test_dir.path(),
+        &["header", "repair", "--force", "plain.enc"],
+    );
+    assert_failure(&output, "repair of an intact header");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("nothing to repair"));
+
+    let mut damaged = original.clone();
+    damaged[..HEADER_LEN].fill(0xEE);
+    fs::write(&encrypted, &damaged).unwrap();
+    let output = run_cli(
+        test_dir.path(),
+        &["header", "repair", "--force", "plain.enc"],
+    );
+    assert_success(&output, "header repair");
+    assert_eq!(fs::read(&encrypted).unwrap(), original);
+
+    let output = run_cli(
+        test_dir.path(),
+        &["decrypt", "--force", "plain.enc", "plain.out"],
+    );
+    assert_success(&output, "decrypt repaired file");
+    assert_eq!(
+        fs::read(test_dir.path().join("plain.out")).unwrap(),
+        b"payload bytes"
+    );
+
+    encrypt_fixture(&test_dir, "bare", b"payload bytes");
+    let output = run_cli(
+        test_dir.path(),
+        &["header", "repair", "--force", "bare.enc"],
+    );
+    assert_failure(&output, "repair without a trailer");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no header trailer"));
+}
+
+#[test]
+fn header_strip_refreshes_the_parity_sidecar_so_repair_keeps_it_stripped() {
+    let test_dir = TestDir::new("header-strip-parity");
+    fs::write(test_dir.path().join("plain.txt"), b"payload bytes").unwrap();
+    let output = run_cli(
+        test_dir.path(),
+        &["encrypt", "--parity", "1", "plain.txt", "plain.enc"],
+    );
+    assert_success(&output, "encrypt with parity");
+    dump_header(&test_dir, "plain.enc", "plain.hdr");
+
+    let output = run_cli(
+        test_dir.path(),
+        &[
+            "header",
+            "strip",
+            "--force",
+            "--header",
+            "plain.hdr",
+            "plain.enc",
+        ],
+    );
+    assert_success(&output, "header strip");
+    let stripped = fs::read(test_dir.path().join("plain.enc")).unwrap();
+
+    let output = run_cli(test_dir.path(), &["repair", "--force", "plain.enc"]);
+    assert_failure(&output, "repair after header strip");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("No damaged blocks to repair"),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(fs::read(test_dir.path().join("plain.enc")).unwrap(), stripped);
+}
@@ -81,7 +81,7 @@ fn write_malformed_v1_header_fixture(output_path: &Path) {
     bytes[12] = 0x01;
     bytes[13] = 0x01;
     bytes[14] = 0x04;
-    bytes[15] = 1;
+    bytes[15] = 0x80;
 
     let mut file = File::create(output_path).unwrap();
     file.write_all(&bytes).unwrap();
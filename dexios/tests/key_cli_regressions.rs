@@ -215,7 +215,7 @@ fn write_malformed_v1_fixture(path: &Path) {
     bytes[12] = 0x01;
     bytes[13] = 0x01;
     bytes[14] = 0x04;
-    bytes[15] = 1;
+    bytes[15] = 0x80;
     fs::write(path, bytes).unwrap();
 }
 
@@ -502,6 +502,88 @@ fn key_verify_maps_success_and_incorrect_key_after_read_only_preparation() {
     assert_sanitized_key_stderr(&stderr(&wrong));
 }
 
+#[test]
+fn key_commands_prove_recipient_slots_with_identity_files() {
+    let test_dir = TestDir::new("key-identity");
+    fs::write(test_dir.path().join("plain.txt"), b"for the recipient").unwrap();
+
+    let keygen = run_cli_with_stdin(test_dir.path(), &["keygen", "alice.key"], b"");
+    assert!(
+        keygen.status.success(),
+        "keygen failed: {}",
+        stderr(&keygen)
+    );
+    let public_key = stdout(&keygen)
+        .lines()
+        .find_map(|line| line.strip_prefix("[i] Public key: "))
+        .expect("keygen should print the public key")
+        .to_owned();
+    let encrypt = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "--recipient",
+            &public_key,
+            "plain.txt",
+            "plain.enc",
+        ],
+        b"",
+    );
+    assert!(
+        encrypt.status.success(),
+        "encrypt failed: {}",
+        stderr(&encrypt)
+    );
+
+    let verify = run_cli_with_stdin(
+        test_dir.path(),
+        &["key", "verify", "--identity", "alice.key", "plain.enc"],
+        b"",
+    );
+    assert!(
+        verify.status.success(),
+        "verify failed: {}",
+        stderr(&verify)
+    );
+
+    // An identity handed over as a keyfile is just bytes and opens nothing.
+    let as_keyfile = run_cli_with_stdin(
+        test_dir.path(),
+        &["key", "verify", "--keyfile", "alice.key", "plain.enc"],
+        b"",
+    );
+    assert!(!as_keyfile.status.success());
+
+    write_keyfile(test_dir.path(), "new.key", "new-pass");
+    let add = run_cli_with_stdin(
+        test_dir.path(),
+        &[
+            "key",
+            "add",
+            "--identity",
+            "alice.key",
+            "--keyfile-new",
+            "new.key",
+            "--kdf-profile",
+            "interactive",
+            "plain.enc",
+        ],
+        b"",
+    );
+    assert!(add.status.success(), "key add failed: {}", stderr(&add));
+
+    let verify_new = run_cli_with_stdin(
+        test_dir.path(),
+        &["key", "verify", "--keyfile", "new.key", "plain.enc"],
+        b"",
+    );
+    assert!(
+        verify_new.status.success(),
+        "new password slot did not verify: {}",
+        stderr(&verify_new)
+    );
+}
+
 #[test]
 fn key_change_rejects_preflight_errors_before_prompting_for_secrets() {
     let test_dir = TestDir::new("change-preflight-errors");
@@ -750,7 +832,7 @@ fn key_delete_maps_failures_without_remaining_key_collection() {
 #[test]
 fn key_mutation_cli_source_orders_secrets_through_domain_intents() {
     let add_old_key_secret = KEY_SUBCOMMAND_SOURCE
-        .find("params.key_old.get_secret")
+        .find("params.key_old.credential")
         .expect("add/change should read the old key");
     let add_old_key_proof = KEY_SUBCOMMAND_SOURCE
         .find("verify_old_key")
@@ -759,7 +841,7 @@ fn key_mutation_cli_source_orders_secrets_through_domain_intents() {
         .find("params.key_new.get_secret")
         .expect("add/change should read the new key after old proof");
     let old_key_secret = KEY_SUBCOMMAND_SOURCE
-        .rfind("params.key_old.get_secret")
+        .rfind("params.key_old.credential")
         .expect("change should still read the old key");
     let old_key_proof = KEY_SUBCOMMAND_SOURCE
         .rfind("verify_old_key")
@@ -786,3 +868,407 @@ fn key_mutation_cli_source_orders_secrets_through_domain_intents() {
         "CLI key delete must not collect a remaining verification key"
     );
 }
+
+#[test]
+fn key_rotate_keeps_only_the_keyslots_it_is_given() {
+    let test_dir = TestDir::new("key-rotate");
+    let encrypted = encrypt_fixture(test_dir.path(), "rotate");
+    let encrypted_arg = encrypted.to_str().unwrap();
+    write_keyfile(test_dir.path(), "old.key", PASSWORD);
+    write_keyfile(test_dir.path(), "new.key", "new-pass");
+    let add = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "add",
+            "-k",
+            "old.key",
+            "-n",
+            "new.key",
+            encrypted_arg,
+        ],
+        None,
+    );
+    assert!(add.status.success(), "key add failed: {}", stderr(&add));
+    let original = fs::read(&encrypted).unwrap();
+
+    let rotate = run_cli(
+        test_dir.path(),
+        &["key", "rotate", "-k", "new.key", "--force", encrypted_arg],
+        None,
+    );
+
+    assert!(
+        rotate.status.success(),
+        "rotate failed: {}",
+        stderr(&rotate)
+    );
+    assert!(
+        stdout(&rotate).contains("keeping keyslot(s) 1"),
+        "{}",
+        stdout(&rotate)
+    );
+    let rotated = fs::read(&encrypted).unwrap();
+    assert_ne!(rotated[HEADER_LEN..], original[HEADER_LEN..]);
+    let verify_new = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "new.key", encrypted_arg],
+        None,
+    );
+    assert!(verify_new.status.success(), "{}", stderr(&verify_new));
+    let verify_old = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "old.key", encrypted_arg],
+        None,
+    );
+    assert!(!verify_old.status.success());
+
+    let wrong = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "rotate",
+            "-k",
+            "new.key",
+            "-k",
+            "old.key",
+            "--force",
+            encrypted_arg,
+        ],
+        None,
+    );
+    assert!(!wrong.status.success());
+    assert!(
+        stderr(&wrong).contains("Incorrect key"),
+        "{}",
+        stderr(&wrong)
+    );
+    assert_sanitized_key_stderr(&stderr(&wrong));
+    assert_eq!(fs::read(&encrypted).unwrap(), rotated);
+}
+
+#[test]
+fn key_rotate_rejects_two_credentials_reading_stdin() {
+    let test_dir = TestDir::new("key-rotate-stdin");
+    let encrypted = encrypt_fixture(test_dir.path(), "rotate-stdin");
+
+    let output = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "rotate",
+            "-k",
+            "-",
+            "--identity",
+            "-",
+            encrypted.to_str().unwrap(),
+        ],
+        None,
+    );
+
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("only one --keyfile or --identity can read from stdin"),
+        "{}",
+        stderr(&output)
+    );
+    assert_no_prompt(&output);
+}
+
+#[test]
+fn key_labels_show_in_header_details_and_target_key_del() {
+    let test_dir = TestDir::new("key-label");
+    let encrypted = encrypt_fixture(test_dir.path(), "label");
+    let encrypted_arg = encrypted.to_str().unwrap();
+    write_keyfile(test_dir.path(), "old.key", PASSWORD);
+    write_keyfile(test_dir.path(), "ops.key", "ops-pass");
+    let add = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "add",
+            "-k",
+            "old.key",
+            "-n",
+            "ops.key",
+            "--label",
+            "ops-recovery",
+            encrypted_arg,
+        ],
+        None,
+    );
+    assert!(add.status.success(), "key add failed: {}", stderr(&add));
+
+    let details = run_cli(test_dir.path(), &["header", "details", encrypted_arg], None);
+    assert!(details.status.success(), "{}", stderr(&details));
+    assert!(
+        stdout(&details).contains("Keyslot 1:\n  Label: ops-recovery\n  Created: "),
+        "{}",
+        stdout(&details)
+    );
+
+    let missing = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "del",
+            "-k",
+            "old.key",
+            "--label",
+            "alice",
+            "--force",
+            encrypted_arg,
+        ],
+        None,
+    );
+    assert!(!missing.status.success());
+    assert!(
+        stderr(&missing).contains("No keyslot is labeled \"alice\""),
+        "{}",
+        stderr(&missing)
+    );
+
+    // the key for keyslot 0 authorizes deleting the labeled keyslot 1
+    let delete = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "del",
+            "-k",
+            "old.key",
+            "--label",
+            "ops-recovery",
+            "--force",
+            encrypted_arg,
+        ],
+        None,
+    );
+    assert!(delete.status.success(), "{}", stderr(&delete));
+    let verify_old = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "old.key", encrypted_arg],
+        None,
+    );
+    assert!(verify_old.status.success(), "{}", stderr(&verify_old));
+    let verify_ops = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "ops.key", encrypted_arg],
+        None,
+    );
+    assert!(!verify_ops.status.success());
+}
+
+#[test]
+fn key_del_slot_is_authorized_with_another_keyfile() {
+    let test_dir = TestDir::new("key-del-slot");
+    let encrypted = encrypt_fixture(test_dir.path(), "del-slot");
+    let encrypted_arg = encrypted.to_str().unwrap();
+    write_keyfile(test_dir.path(), "admin.key", PASSWORD);
+    write_keyfile(test_dir.path(), "leaver.key", "leaver-pass");
+    let add = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "add",
+            "-k",
+            "admin.key",
+            "-n",
+            "leaver.key",
+            encrypted_arg,
+        ],
+        None,
+    );
+    assert!(add.status.success(), "key add failed: {}", stderr(&add));
+    let original = fs::read(&encrypted).unwrap();
+
+    let empty = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "del",
+            "--slot",
+            "3",
+            "--authorize-with",
+            "admin.key",
+            "--force",
+            encrypted_arg,
+        ],
+        None,
+    );
+    assert!(!empty.status.success());
+    assert!(
+        stderr(&empty).contains("Keyslot 3 is not in use"),
+        "{}",
+        stderr(&empty)
+    );
+    assert_eq!(fs::read(&encrypted).unwrap(), original);
+
+    let delete = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "del",
+            "--slot",
+            "1",
+            "--authorize-with",
+            "admin.key",
+            "--force",
+            encrypted_arg,
+        ],
+        None,
+    );
+    assert!(delete.status.success(), "{}", stderr(&delete));
+    let verify_admin = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "admin.key", encrypted_arg],
+        None,
+    );
+    assert!(verify_admin.status.success(), "{}", stderr(&verify_admin));
+    let verify_leaver = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "leaver.key", encrypted_arg],
+        None,
+    );
+    assert!(!verify_leaver.status.success());
+}
+
+#[test]
+fn encrypt_keyslots_reserves_room_for_more_than_four_keys() {
+    let test_dir = TestDir::new("key-capacity");
+    fs::write(test_dir.path().join("plain.txt"), b"Hello world").unwrap();
+    write_keyfile(test_dir.path(), "old.key", PASSWORD);
+    let encrypt = run_cli(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "-k",
+            "old.key",
+            "--kdf-profile",
+            "interactive",
+            "--keyslots",
+            "6",
+            "plain.txt",
+            "plain.enc",
+        ],
+        None,
+    );
+    assert!(encrypt.status.success(), "{}", stderr(&encrypt));
+
+    for name in ["one", "two", "three", "four"] {
+        write_keyfile(test_dir.path(), &format!("{name}.key"), name);
+        let add = run_cli(
+            test_dir.path(),
+            &[
+                "key",
+                "add",
+                "-k",
+                "old.key",
+                "-n",
+                &format!("{name}.key"),
+                "--kdf-profile",
+                "interactive",
+                "plain.enc",
+            ],
+            None,
+        );
+        assert!(add.status.success(), "key add failed: {}", stderr(&add));
+    }
+
+    let details = run_cli(test_dir.path(), &["header", "details", "plain.enc"], None);
+    assert!(details.status.success(), "{}", stderr(&details));
+    assert!(
+        stdout(&details).contains("Keyslots: 5 of 6 in use"),
+        "{}",
+        stdout(&details)
+    );
+    assert!(
+        stdout(&details).contains("Keyslot 4:"),
+        "{}",
+        stdout(&details)
+    );
+
+    let verify = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "four.key", "plain.enc"],
+        None,
+    );
+    assert!(verify.status.success(), "{}", stderr(&verify));
+}
+
+#[test]
+fn key_del_refreshes_the_parity_sidecar_so_repair_cannot_restore_the_keyslot() {
+    let test_dir = TestDir::new("key-del-parity");
+    fs::write(test_dir.path().join("plain.txt"), b"Hello world").unwrap();
+    write_keyfile(test_dir.path(), "old.key", PASSWORD);
+    write_keyfile(test_dir.path(), "ops.key", "ops-pass");
+    let encrypt = run_cli(
+        test_dir.path(),
+        &[
+            "encrypt",
+            "-k",
+            "old.key",
+            "--parity",
+            "2",
+            "plain.txt",
+            "plain.enc",
+        ],
+        None,
+    );
+    assert!(encrypt.status.success(), "{}", stderr(&encrypt));
+    let add = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "add",
+            "-k",
+            "old.key",
+            "-n",
+            "ops.key",
+            "--label",
+            "ops",
+            "plain.enc",
+        ],
+        None,
+    );
+    assert!(add.status.success(), "key add failed: {}", stderr(&add));
+    let sidecar = test_dir.path().join("plain.enc.dxpar");
+    let sidecar_with_ops = fs::read(&sidecar).unwrap();
+
+    let delete = run_cli(
+        test_dir.path(),
+        &[
+            "key",
+            "del",
+            "-k",
+            "old.key",
+            "--label",
+            "ops",
+            "--force",
+            "plain.enc",
+        ],
+        None,
+    );
+    assert!(delete.status.success(), "{}", stderr(&delete));
+    assert!(fs::read(&sidecar).unwrap() != sidecar_with_ops);
+
+    // the sidecar now describes the file without the deleted keyslot
+    let repair = run_cli(test_dir.path(), &["repair", "--force", "plain.enc"], None);
+    assert!(!repair.status.success());
+    assert!(
+        stderr(&repair).contains("No damaged blocks to repair"),
+        "{}",
+        stderr(&repair)
+    );
+    let verify_ops = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "ops.key", "plain.enc"],
+        None,
+    );
+    assert!(!verify_ops.status.success());
+    let verify_old = run_cli(
+        test_dir.path(),
+        &["key", "verify", "-k", "old.key", "plain.enc"],
+        None,
+    );
+    assert!(verify_old.status.success(), "{}", stderr(&verify_old));
+}
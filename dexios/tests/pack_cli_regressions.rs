@@ -35,9 +35,12 @@ use std::io::{ErrorKind, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
-use core::payload::{ManifestEntryKind, ManifestFirstPayload};
+use core::payload::{
+    MANIFEST_VERSION, MANIFEST_VERSION_DIGEST, ManifestEntryKind, ManifestFirstPayload,
+};
 use core::protected::Protected;
 use domain::decrypt;
+use domain::hasher::{Blake3Hasher, Hasher};
 use domain::storage::identity::OverwritePolicy;
 use tempdir::{TestDir, unique_file_name};
 
@@ -87,7 +90,7 @@ fn run_cli_with_stdin(current_dir: &Path, args: &[&str], stdin: &[u8]) -> std::p
     child.wait_with_output().unwrap()
 }
 
-fn decrypt_manifest_entry_names(archive_path: &Path, header_path: Option<&Path>) -> Vec<String> {
+fn decrypt_archive_payload(archive_path: &Path, header_path: Option<&Path>) -> Vec<u8> {
     let decrypted_path = archive_path
         .parent()
         .unwrap()
@@ -103,7 +106,11 @@ fn decrypt_manifest_entry_names(archive_path: &Path, header_path: Option<&Path>)
     .unwrap();
     decrypt::execute(intent).unwrap();
 
-    let bytes = fs::read(decrypted_path).unwrap();
+    fs::read(decrypted_path).unwrap()
+}
+
+fn decrypt_manifest_entry_names(archive_path: &Path, header_path: Option<&Path>) -> Vec<String> {
+    let bytes = decrypt_archive_payload(archive_path, header_path);
     let payload = ManifestFirstPayload::parse(&bytes).unwrap();
     let mut names = payload
         .manifest()
@@ -304,6 +311,171 @@ fn pack_dot_input_uses_current_directory_name() {
     );
 }
 
+#[test]
+fn pack_to_recipient_unpacks_with_generated_identity() {
+    let test_dir = TestDir::new("pack-recipient-round-trip");
+    let source_dir = test_dir.path().join("source");
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::write(source_dir.join("hello.txt"), b"for the recipient").unwrap();
+
+    let keygen = run_cli_with_stdin(test_dir.path(), &["keygen", "alice.key"], b"");
+    assert!(
+        keygen.status.success(),
+        "keygen failed: stderr={}",
+        String::from_utf8_lossy(&keygen.stderr)
+    );
+    let stdout = String::from_utf8(keygen.stdout).unwrap();
+    let public_key = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("[i] Public key: "))
+        .expect("keygen should print the public key")
+        .to_owned();
+
+    let pack = run_cli_with_stdin(
+        test_dir.path(),
+        &["pack", "--recipient", &public_key, "source", "archive.enc"],
+        b"",
+    );
+    assert!(
+        pack.status.success(),
+        "pack failed: stderr={}",
+        String::from_utf8_lossy(&pack.stderr)
+    );
+
+    let unpack = run_cli_with_stdin(
+        test_dir.path(),
+        &["unpack", "--identity", "alice.key", "archive.enc", "out"],
+        b"",
+    );
+    assert!(
+        unpack.status.success(),
+        "unpack failed: stderr={}",
+        String::from_utf8_lossy(&unpack.stderr)
+    );
+    assert_eq!(
+        fs::read(test_dir.path().join("out/source/hello.txt")).unwrap(),
+        b"for the recipient"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn pack_unpack_restores_modes_and_times_unless_opted_out() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, SystemTime};
+
+    let test_dir = TestDir::new("pack-metadata");
+    let source_dir = test_dir.path().join("source");
+    fs::create_dir_all(&source_dir).unwrap();
+    let script = source_dir.join("deploy.sh");
+    fs::write(&script, b"#!/bin/sh\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o750)).unwrap();
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+    fs::File::options()
+        .write(true)
+        .open(&script)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+
+    let pack = run_pack(test_dir.path(), &[], &["source"], "archive.enc");
+    assert!(
+        pack.status.success(),
+        "pack failed: stderr={}",
+        String::from_utf8_lossy(&pack.stderr)
+    );
+    let run_unpack = |output: &str, extra_args: &[&str]| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_dexios"));
+        command.current_dir(test_dir.path());
+        let mut args = vec!["unpack", "-f"];
+        args.extend_from_slice(extra_args);
+        args.extend(["archive.enc", output]);
+        keyfile_cli::append_keyed_args(&mut command, test_dir.path(), PASSWORD, &args);
+        let result = command.output().unwrap();
+        assert!(
+            result.status.success(),
+            "unpack failed: stderr={}",
+            String::from_utf8_lossy(&result.stderr)
+        );
+        fs::metadata(test_dir.path().join(output).join("source/deploy.sh")).unwrap()
+    };
+
+    let restored = run_unpack("out", &[]);
+    assert_eq!(restored.permissions().mode() & 0o7777, 0o750);
+    assert_eq!(restored.modified().unwrap(), mtime);
+
+    let plain = run_unpack("plain", &["--no-permissions", "--no-times"]);
+    assert_ne!(plain.permissions().mode() & 0o7777, 0o750);
+    assert_ne!(plain.modified().unwrap(), mtime);
+
+    let pack = run_pack(test_dir.path(), &["--no-metadata"], &["source"], "bare.enc");
+    assert!(pack.status.success());
+    let payload = decrypt_archive_payload(&test_dir.path().join("bare.enc"), None);
+    assert_eq!(
+        u16::from_le_bytes([payload[4], payload[5]]),
+        MANIFEST_VERSION,
+        "--no-metadata keeps the manifest readable by version 1 readers"
+    );
+}
+
+#[test]
+fn pack_entry_digests_are_recorded_listed_and_verified() {
+    let test_dir = TestDir::new("pack-entry-digests");
+    let source_dir = test_dir.path().join("source");
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::write(source_dir.join("a.txt"), b"alpha").unwrap();
+
+    let pack = run_pack(
+        test_dir.path(),
+        &["--entry-digests"],
+        &["source"],
+        "archive.enc",
+    );
+    assert!(
+        pack.status.success(),
+        "pack failed: stderr={}",
+        String::from_utf8_lossy(&pack.stderr)
+    );
+    let bytes = decrypt_archive_payload(&test_dir.path().join("archive.enc"), None);
+    assert_eq!(
+        u16::from_le_bytes([bytes[4], bytes[5]]),
+        MANIFEST_VERSION_DIGEST
+    );
+    let payload = ManifestFirstPayload::parse(&bytes).unwrap();
+    let file = payload
+        .manifest()
+        .entries()
+        .iter()
+        .find(|entry| entry.kind() == ManifestEntryKind::File)
+        .unwrap();
+    let mut hasher = Blake3Hasher::default();
+    hasher.write(b"alpha");
+    assert_eq!(file.blake3_digest(), Some(&hasher.digest()));
+
+    let run = |args: &[&str]| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_dexios"));
+        command.current_dir(test_dir.path());
+        keyfile_cli::append_keyed_args(&mut command, test_dir.path(), PASSWORD, args);
+        let output = command.output().unwrap();
+        assert!(
+            output.status.success(),
+            "{args:?} failed: stderr={}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let listing = run(&["list", "--json", "archive.enc"]);
+    assert!(
+        listing.contains(&format!(r#""blake3":"{}""#, hasher.finish())),
+        "{listing}"
+    );
+    let verified = run(&["verify", "archive.enc"]);
+    assert!(
+        verified.contains("1 file bodies matched their recorded BLAKE3 digests"),
+        "{verified}"
+    );
+}
+
 #[test]
 fn pack_rejects_generated_output_inside_source_and_keeps_source() {
     let test_dir = TestDir::new("pack-generated-output-inside-source");
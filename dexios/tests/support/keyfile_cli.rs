@@ -53,7 +53,10 @@ pub(crate) fn append_keyed_args(
         ["encrypt" | "pack", rest @ ..] if has_auto_arg(rest) => {
             command.args(args);
         }
-        ["encrypt" | "decrypt" | "pack" | "unpack", rest @ ..] => {
+        [
+            "encrypt" | "decrypt" | "pack" | "unpack" | "list" | "diff" | "update" | "verify",
+            rest @ ..,
+        ] => {
             command
                 .arg(args[0])
                 .arg("--keyfile")
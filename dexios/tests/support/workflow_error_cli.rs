@@ -232,7 +232,7 @@ pub(crate) fn write_malformed_v1_header(path: &Path) {
     bytes[12] = 0x01;
     bytes[13] = 0x01;
     bytes[14] = 0x04;
-    bytes[15] = 1;
+    bytes[15] = 0x80;
     bytes.extend_from_slice(b"payload");
     fs::write(path, bytes).unwrap();
 }
@@ -683,3 +683,335 @@ fn unpack_help_no_longer_mentions_secure_erase() {
     assert!(!stdout.contains("--erase"));
     assert!(stdout.contains("--delete-input"));
 }
+
+fn run_list(archive: &Path, extra_args: &[&str]) -> std::process::Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_dexios"));
+    let current_dir = archive
+        .parent()
+        .expect("test archive should have a parent directory");
+    command.current_dir(current_dir);
+
+    let mut args = vec!["list"];
+    args.extend_from_slice(extra_args);
+    args.push(archive.to_str().expect("test input path should be UTF-8"));
+    keyfile_cli::append_keyed_args(&mut command, current_dir, PASSWORD, &args);
+    command.output().unwrap()
+}
+
+#[test]
+fn unpack_include_exclude_extracts_only_selected_entries() {
+    let test_dir = TestDir::new("unpack-selective");
+    let archive = test_dir.path().join("archive.enc");
+    let output_dir = test_dir.path().join("out");
+    // The skipped body spans several blocks and sits between selected entries,
+    // so it must be decrypted and discarded for the next frame to line up.
+    let skipped_body = vec![3u8; BLOCK_SIZE * 2 + 11];
+    write_manifest_archive_with_entries(
+        &archive,
+        &[
+            ("db/", b""),
+            ("db/big.bin", &skipped_body),
+            ("db/dump.sql", b"select 1;"),
+            ("db/notes.txt", b"notes"),
+            ("tmp/", b""),
+            ("tmp/scratch.sql", b"scratch"),
+            ("top.sql", b"select 2;"),
+        ],
+    );
+
+    let output = run_unpack_with_args(
+        &archive,
+        &output_dir,
+        &["--include", "*.sql", "--exclude", "tmp/**"],
+    );
+
+    assert!(
+        output.status.success(),
+        "selective unpack failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read(output_dir.join("db/dump.sql")).unwrap(),
+        b"select 1;"
+    );
+    assert_eq!(fs::read(output_dir.join("top.sql")).unwrap(), b"select 2;");
+    assert!(!output_dir.join("db/big.bin").exists());
+    assert!(!output_dir.join("db/notes.txt").exists());
+    assert!(!output_dir.join("tmp").exists());
+}
+
+#[test]
+fn unpack_selection_still_validates_and_authenticates_the_whole_archive() {
+    let test_dir = TestDir::new("unpack-selective-validation");
+
+    let collision = test_dir.path().join("collision.enc");
+    write_manifest_archive_with_entries(
+        &collision,
+        &[
+            ("keep.txt", b"keep"),
+            ("skip.txt", b"first"),
+            ("skip.txt", b"second"),
+        ],
+    );
+    let collision_out = test_dir.path().join("collision-out");
+    let output = run_unpack_with_args(&collision, &collision_out, &["--include", "keep.txt"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Duplicate output path"),
+        "skipped entries must still be collision-checked: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!collision_out.join("keep.txt").exists());
+
+    let tampered = test_dir.path().join("tampered.enc");
+    write_manifest_archive_with_entries(
+        &tampered,
+        &[
+            ("keep.txt", b"keep"),
+            ("skip.bin", &vec![9u8; BLOCK_SIZE + 3]),
+        ],
+    );
+    tamper_final_stream_chunk(&tampered);
+    let tampered_out = test_dir.path().join("tampered-out");
+    let output = run_unpack_with_args(&tampered, &tampered_out, &["--exclude", "skip.bin"]);
+    assert!(
+        !output.status.success(),
+        "a damaged skipped body must still fail final authentication"
+    );
+    assert!(!tampered_out.join("keep.txt").exists());
+
+    let invalid = run_unpack_with_args(&tampered, &tampered_out, &["--include", "../escape"]);
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("archive path pattern"));
+}
+
+#[test]
+fn list_prints_manifest_entries_without_final_authentication() {
+    let test_dir = TestDir::new("list-manifest");
+    let archive = test_dir.path().join("archive.enc");
+    let large_body = vec![7u8; BLOCK_SIZE * 2 + 5];
+    write_manifest_archive_with_entries(
+        &archive,
+        &[
+            ("docs/", b""),
+            ("docs/a \"b\".txt", b"alpha"),
+            ("large.bin", &large_body),
+        ],
+    );
+    // The manifest sits in the first block; a damaged final block is only
+    // caught by a full unpack.
+    tamper_final_stream_chunk(&archive);
+
+    let names = run_list(&archive, &[]);
+    assert!(
+        names.status.success(),
+        "list failed: {}",
+        String::from_utf8_lossy(&names.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&names.stdout),
+        "docs/\ndocs/a \"b\".txt\nlarge.bin\n"
+    );
+    assert!(
+        String::from_utf8_lossy(&names.stderr).contains("not final-authenticated"),
+        "list must warn that the listing is unverified: {}",
+        String::from_utf8_lossy(&names.stderr)
+    );
+
+    let long = run_list(&archive, &["--long"]);
+    assert!(long.status.success());
+    let long_stdout = String::from_utf8_lossy(&long.stdout);
+    let lines = long_stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("dir ") && lines[0].ends_with(" - docs/"));
+    assert!(lines[1].starts_with("file") && lines[1].ends_with(" 5 docs/a \"b\".txt"));
+    assert!(lines[2].ends_with(&format!(" {} large.bin", large_body.len())));
+
+    let json = run_list(&archive, &["--json"]);
+    assert!(json.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&json.stdout).trim_end(),
+        format!(
+            r#"[{{"kind":"directory","path":"docs","body_len":null}},{{"kind":"file","path":"docs/a \"b\".txt","body_len":5}},{{"kind":"file","path":"large.bin","body_len":{}}}]"#,
+            large_body.len()
+        )
+    );
+
+    let unpack = run_unpack(&archive, &test_dir.path().join("out"));
+    assert!(
+        !unpack.status.success(),
+        "unpack must reject the tampered archive"
+    );
+}
+
+#[test]
+fn list_rejects_single_file_payloads_and_wrong_keys() {
+    let test_dir = TestDir::new("list-rejects");
+    let archive = test_dir.path().join("archive.enc");
+    write_manifest_archive_with_entries(&archive, &[("a.txt", b"alpha")]);
+    fs::write(test_dir.path().join("wrong.key"), "wrong-password").unwrap();
+
+    let wrong_key = run_list(&archive, &["--keyfile", "wrong.key"]);
+    assert!(!wrong_key.status.success());
+    assert!(String::from_utf8_lossy(&wrong_key.stderr).contains("Authentication failed"));
+    assert!(wrong_key.stdout.is_empty());
+
+    fs::write(test_dir.path().join("plain.txt"), b"not an archive").unwrap();
+    let mut encrypt = Command::new(env!("CARGO_BIN_EXE_dexios"));
+    encrypt.current_dir(test_dir.path());
+    keyfile_cli::append_keyed_args(
+        &mut encrypt,
+        test_dir.path(),
+        PASSWORD,
+        &["encrypt", "plain.txt", "plain.enc"],
+    );
+    assert!(encrypt.output().unwrap().status.success());
+
+    let raw_file = run_list(&test_dir.path().join("plain.enc"), &[]);
+    assert!(!raw_file.status.success());
+    assert!(String::from_utf8_lossy(&raw_file.stderr).contains("Malformed archive data"));
+}
+
+#[test]
+fn update_writes_a_new_archive_with_entries_added_and_removed() {
+    let test_dir = TestDir::new("update-entries");
+    let archive = test_dir.path().join("archive.enc");
+    write_manifest_archive_with_entries(
+        &archive,
+        &[
+            ("docs/", b""),
+            ("docs/a.txt", b"alpha"),
+            ("old.txt", b"old"),
+        ],
+    );
+    let original = fs::read(&archive).unwrap();
+    fs::write(test_dir.path().join("new.txt"), b"new").unwrap();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_dexios"));
+    command.current_dir(test_dir.path());
+    keyfile_cli::append_keyed_args(
+        &mut command,
+        test_dir.path(),
+        PASSWORD,
+        &[
+            "update",
+            "--add",
+            "new.txt",
+            "--remove",
+            "old.txt",
+            "archive.enc",
+            "updated.enc",
+        ],
+    );
+    let update = command.output().unwrap();
+    assert!(
+        update.status.success(),
+        "update failed: {}",
+        String::from_utf8_lossy(&update.stderr)
+    );
+    assert_eq!(fs::read(&archive).unwrap(), original);
+
+    let names = run_list(&test_dir.path().join("updated.enc"), &[]);
+    assert!(names.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&names.stdout),
+        "docs/\ndocs/a.txt\nnew.txt\n"
+    );
+}
+
+#[test]
+fn verify_reports_entry_counts_and_the_failure_class() {
+    let test_dir = TestDir::new("verify-archive");
+    let archive = test_dir.path().join("archive.enc");
+    write_manifest_archive_with_entries(
+        &archive,
+        &[("docs/", b""), ("docs/a.txt", b"alpha"), ("b.txt", b"beta")],
+    );
+
+    let run_verify = || {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_dexios"));
+        command.current_dir(test_dir.path());
+        keyfile_cli::append_keyed_args(
+            &mut command,
+            test_dir.path(),
+            PASSWORD,
+            &["verify", "archive.enc"],
+        );
+        command.output().unwrap()
+    };
+
+    let verified = run_verify();
+    assert!(
+        verified.status.success(),
+        "verify failed: {}",
+        String::from_utf8_lossy(&verified.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&verified.stdout);
+    assert!(
+        stdout.contains(
+            "3 entries (files: 2, directories: 1, symlinks: 0, hard links: 0; 9 body bytes)"
+        ),
+        "{stdout}"
+    );
+
+    tamper_final_stream_chunk(&archive);
+    let tampered = run_verify();
+    assert!(!tampered.status.success());
+    let stderr = String::from_utf8_lossy(&tampered.stderr);
+    assert!(
+        stderr.contains("Verification failed (authentication-failure)"),
+        "{stderr}"
+    );
+    let leftovers = fs::read_dir(test_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name != "archive.enc" && name != ".dexios-test-key")
+        .collect::<Vec<_>>();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+}
+
+#[test]
+fn diff_prints_changed_paths_as_text_and_json() {
+    let test_dir = TestDir::new("diff-archive");
+    let archive = test_dir.path().join("archive.enc");
+    write_manifest_archive_with_entries(
+        &archive,
+        &[
+            ("docs/", b""),
+            ("docs/a.txt", b"alpha"),
+            ("old.txt", b"old"),
+        ],
+    );
+    let restore = test_dir.path().join("restore");
+    fs::create_dir_all(restore.join("docs")).unwrap();
+    fs::write(restore.join("docs/a.txt"), b"ALPHA").unwrap();
+    fs::write(restore.join("new.txt"), b"new").unwrap();
+
+    let run_diff = |extra_args: &[&str]| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_dexios"));
+        command.current_dir(test_dir.path());
+        let mut args = vec!["diff"];
+        args.extend_from_slice(extra_args);
+        args.extend(["archive.enc", "restore"]);
+        keyfile_cli::append_keyed_args(&mut command, test_dir.path(), PASSWORD, &args);
+        command.output().unwrap()
+    };
+
+    let text = run_diff(&[]);
+    assert!(
+        text.status.success(),
+        "diff failed: {}",
+        String::from_utf8_lossy(&text.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&text.stdout),
+        "added    new.txt\nremoved  old.txt\nmodified docs/a.txt\n"
+    );
+
+    let json = run_diff(&["--json"]);
+    assert!(json.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&json.stdout),
+        "{\"added\":[\"new.txt\"],\"removed\":[\"old.txt\"],\"modified\":[\"docs/a.txt\"]}\n"
+    );
+}
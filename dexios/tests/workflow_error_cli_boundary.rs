@@ -136,3 +136,47 @@ fn wrong_key_decrypt_default_stderr_is_display_only() {
     assert!(!wrong_key_stderr.contains("keyslot"));
     assert!(!wrong_key_stderr.contains("master key"));
 }
+
+#[test]
+fn exit_codes_follow_the_workflow_class() {
+    let test_dir = TestDir::new("workflow-error-exit-codes");
+    encrypt_fixture(&test_dir);
+
+    let wrong_key = run_cli(
+        test_dir.path(),
+        WRONG_PASSWORD,
+        &["decrypt", "--force", "plain.enc", "plain.out"],
+    );
+    assert_eq!(wrong_key.status.code(), Some(20), "{}", stderr(&wrong_key));
+
+    let not_dexios = run_cli(
+        test_dir.path(),
+        CORRECT_PASSWORD,
+        &["decrypt", "--force", "plain.txt", "plain.out"],
+    );
+    assert_eq!(
+        not_dexios.status.code(),
+        Some(11),
+        "{}",
+        stderr(&not_dexios)
+    );
+
+    let cli_conflict = run_cli(
+        test_dir.path(),
+        CORRECT_PASSWORD,
+        &["encrypt", "--parity", "1", "plain.txt", "-"],
+    );
+    assert_eq!(
+        cli_conflict.status.code(),
+        Some(1),
+        "{}",
+        stderr(&cli_conflict)
+    );
+
+    let usage = run_cli(
+        test_dir.path(),
+        CORRECT_PASSWORD,
+        &["decrypt", "--no-such-flag", "plain.enc", "plain.out"],
+    );
+    assert_eq!(usage.status.code(), Some(2), "{}", stderr(&usage));
+}
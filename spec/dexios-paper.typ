@@ -187,7 +187,9 @@ version `0x13`.
 === Hashing Algorithms
 
 The normal write policy is Argon2id only. New canonical V1 files do not
-expose alternate KDF selection or user-configurable KDF parameters.
+expose raw KDF parameters; writers choose one of three frozen Argon2id cost
+profiles (`interactive`, `default`, `paranoid`), and each keyslot records its
+profile in the KDF parameter profile id byte.
 
 The historical Argon2id tag `[0xDF, 0x02]` may be recognized as unsupported
 metadata for explicit diagnostics, but it is not used for current derivation